@@ -0,0 +1,156 @@
+//! Criterion benchmarks of the hot paths of the removal algorithms: the common neighbours of an
+//! edge, the check of strong filtration-domination, the query of the stripes of the
+//! filtration-domination check, and the removal algorithms end to end. Run it with
+//! `cargo bench --features bench-internals --bench hot_paths`.
+//!
+//! The synthetic datasets are sampled with a fixed seed, which can be changed with the
+//! `FILTRATION_DOMINATION_BENCH_SEED` environment variable.
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use filtration_domination::datasets::{get_dataset_density_edge_list_with_rng, Dataset, Threshold};
+use filtration_domination::edges::{EdgeList, FilteredEdge, GradedEdgeList};
+use filtration_domination::removal::internals::{seeded_rng, Stripes};
+use filtration_domination::removal::{
+    is_strongly_filtration_dominated, remove_filtration_dominated,
+    remove_strongly_filtration_dominated, BifilteredGraph, EdgeOrder,
+};
+use filtration_domination::OneCriticalGrade;
+use ordered_float::OrderedFloat;
+
+type BifilteredEdges = EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>;
+
+const SYNTHETIC_DATASETS: [(&str, Dataset); 5] = [
+    ("uniform", Dataset::Uniform { n_points: 200 }),
+    ("circle", Dataset::Circle { n_points: 200 }),
+    ("sphere", Dataset::Sphere { n_points: 200 }),
+    ("torus", Dataset::Torus { n_points: 200 }),
+    ("swiss_roll", Dataset::SwissRoll { n_points: 200 }),
+];
+
+fn sample(dataset: Dataset) -> BifilteredEdges {
+    get_dataset_density_edge_list_with_rng(
+        dataset,
+        Threshold::KeepAll,
+        None,
+        false,
+        &mut seeded_rng(),
+    )
+    .expect("Sampling the dataset")
+}
+
+fn adjacency_queries(c: &mut Criterion) {
+    let edges = sample(Dataset::Torus { n_points: 200 });
+    let graph = BifilteredGraph::new(&edges);
+
+    c.bench_function("common_neighbours", |b| {
+        b.iter(|| {
+            edges
+                .edge_iter()
+                .map(|edge| {
+                    let (u, v) = (edge.edge.0, edge.edge.1);
+                    graph.common_neighbours(black_box(u), black_box(v)).count()
+                })
+                .sum::<usize>()
+        })
+    });
+    c.bench_function("is_strongly_filtration_dominated", |b| {
+        b.iter(|| {
+            edges
+                .edge_iter()
+                .filter(|edge| {
+                    is_strongly_filtration_dominated(graph.adjacency_matrix(), black_box(edge))
+                })
+                .count()
+        })
+    });
+}
+
+fn stripes_queries(c: &mut Criterion) {
+    // Stripes and points taken from the grades of the edges, as in the filtration-domination
+    // check.
+    let edges = sample(Dataset::Torus { n_points: 200 });
+    let stripes = Stripes::new(
+        edges
+            .edge_iter()
+            .map(|edge| {
+                let [x, y] = edge.grade.0;
+                ((x, x + OrderedFloat(0.1)), y)
+            })
+            .collect(),
+    );
+    let points: Vec<_> = edges
+        .edge_iter()
+        .map(|edge| (edge.grade.0[0], edge.grade.0[1]))
+        .collect();
+
+    c.bench_function("stripes_contains_point", |b| {
+        b.iter(|| {
+            points
+                .iter()
+                .filter(|&&p| stripes.contains_point(black_box(p)))
+                .count()
+        })
+    });
+}
+
+fn removal(c: &mut Criterion) {
+    let mut group = c.benchmark_group("removal");
+    group.sample_size(10);
+    for (name, dataset) in SYNTHETIC_DATASETS {
+        let edges = sample(dataset);
+        group.bench_function(format!("strong/{}", name), |b| {
+            b.iter_batched_ref(
+                || edges.clone(),
+                |edges| remove_strongly_filtration_dominated(edges, EdgeOrder::default()),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(format!("full/{}", name), |b| {
+            b.iter_batched_ref(
+                || edges.clone(),
+                |edges| remove_filtration_dominated(edges, EdgeOrder::default()),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Compares an [EdgeList] with a [GradedEdgeList] of the same edges, when sorting the edges and
+/// when scanning the values of one parameter.
+fn graded_edge_list(c: &mut Criterion) {
+    let edges = sample(Dataset::Uniform { n_points: 1500 });
+    let graded = GradedEdgeList::from(edges.clone());
+    let mut group = c.benchmark_group("graded_edge_list");
+    group.sample_size(10);
+    group.bench_function("sort/edge_list", |b| {
+        b.iter_batched_ref(
+            || edges.clone(),
+            |edges| edges.sort_reverse_lexicographically(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("sort/graded", |b| {
+        b.iter_batched_ref(
+            || graded.clone(),
+            |graded| graded.sort_reverse_lexicographically(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("max_parameter/edge_list", |b| {
+        b.iter(|| black_box(&edges).edge_iter().map(|e| e.grade.0[1]).max())
+    });
+    group.bench_function("max_parameter/graded", |b| {
+        b.iter(|| black_box(&graded).parameter(1).iter().max().copied())
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    adjacency_queries,
+    stripes_queries,
+    removal,
+    graded_edge_list
+);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Criterion benchmarks of the removal algorithms on the dragon dataset. Run it with
+//! `cargo bench --bench removal`.
+//!
+//! The dataset must have been downloaded with the `download_datasets.sh` script; otherwise, a
+//! sampled torus is used instead.
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use filtration_domination::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+use filtration_domination::edges::{EdgeList, FilteredEdge};
+use filtration_domination::removal::{
+    remove_filtration_dominated, remove_strongly_filtration_dominated, EdgeOrder,
+};
+use filtration_domination::OneCriticalGrade;
+use ordered_float::OrderedFloat;
+
+type BifilteredEdges = EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>;
+
+/// The edges of the dragon dataset, or of a sampled torus if it was not downloaded, and the name
+/// of the dataset.
+fn dataset_edges() -> (&'static str, BifilteredEdges) {
+    match get_dataset_density_edge_list(Dataset::Dragon, Threshold::KeepAll, None, false) {
+        Ok(edges) => ("dragon", edges),
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("Using a sampled torus with 800 points instead.");
+            let edges = get_dataset_density_edge_list(
+                Dataset::Torus { n_points: 800 },
+                Threshold::KeepAll,
+                None,
+                false,
+            )
+            .expect("Sampling the torus");
+            ("torus", edges)
+        }
+    }
+}
+
+fn removal(c: &mut Criterion) {
+    let (name, edges) = dataset_edges();
+    let mut group = c.benchmark_group("removal");
+    group.sample_size(10);
+    group.bench_function(format!("strong/{name}"), |b| {
+        b.iter_batched_ref(
+            || edges.clone(),
+            |edges| remove_strongly_filtration_dominated(edges, EdgeOrder::ReverseLexicographic),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function(format!("full/{name}"), |b| {
+        b.iter_batched_ref(
+            || edges.clone(),
+            |edges| remove_filtration_dominated(edges, EdgeOrder::ReverseLexicographic),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, removal);
+criterion_main!(benches);
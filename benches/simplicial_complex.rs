@@ -0,0 +1,47 @@
+//! Criterion benchmarks comparing the simplicial complex implementations when building the chain
+//! complex of the flag bifiltration of a sampled torus. Run it with
+//! `cargo bench --bench simplicial_complex`.
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use filtration_domination::chain_complex::ChainComplex;
+use filtration_domination::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+use filtration_domination::edges::{EdgeList, FilteredEdge};
+use filtration_domination::simplicial_complex::{
+    MapSimplicialComplex, SimplicialComplex, SortedSimplicialComplex,
+};
+use filtration_domination::OneCriticalGrade;
+use ordered_float::OrderedFloat;
+
+const HOMOLOGY: usize = 1;
+
+type BifilteredEdges = EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>;
+
+fn chain_complex<S>(edges: &BifilteredEdges) -> ChainComplex<OrderedFloat<f64>, 2>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    ChainComplex::from_flag_filtration_with_complex::<S>(edges, HOMOLOGY)
+        .expect("The keys fit the simplices")
+}
+
+fn simplicial_complex(c: &mut Criterion) {
+    let edges = get_dataset_density_edge_list(
+        Dataset::Torus { n_points: 400 },
+        Threshold::Percentile(0.2),
+        None,
+        false,
+    )
+    .expect("Sampling the torus");
+    let mut group = c.benchmark_group("simplicial_complex");
+    group.sample_size(10);
+    group.bench_function("map", |b| {
+        b.iter(|| chain_complex::<MapSimplicialComplex>(&edges))
+    });
+    group.bench_function("sorted", |b| {
+        b.iter(|| chain_complex::<SortedSimplicialComplex>(&edges))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, simplicial_complex);
+criterion_main!(benches);
@@ -1,8 +1,7 @@
 use clap::Parser;
 use filtration_domination::edges::{read_edge_list, write_edge_list, EdgeList, FilteredEdge};
-use filtration_domination::removal::{
-    remove_filtration_dominated, remove_strongly_filtration_dominated, EdgeOrder,
-};
+use filtration_domination::pipeline::Pipeline;
+use filtration_domination::removal::EdgeOrder;
 use filtration_domination::OneCriticalGrade;
 use ordered_float::OrderedFloat;
 use std::fs::File;
@@ -22,14 +21,15 @@ fn main() -> anyhow::Result<()> {
     let opts: Cli = Cli::parse();
     let edge_list_file = File::open(&opts.input)?;
     let reader = BufReader::new(edge_list_file);
-    let mut edge_list: EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>> =
+    let edge_list: EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>> =
         read_edge_list(reader)?;
 
-    let remaining_edges = if opts.full {
-        remove_filtration_dominated(&mut edge_list, EdgeOrder::ReverseLexicographic)
+    let pipeline = if opts.full {
+        Pipeline::new().full_removal(EdgeOrder::ReverseLexicographic)
     } else {
-        remove_strongly_filtration_dominated(&mut edge_list, EdgeOrder::ReverseLexicographic)
+        Pipeline::new().strong_removal(EdgeOrder::ReverseLexicographic)
     };
+    let remaining_edges = pipeline.run(edge_list)?.edges;
 
     let out_file = File::create(&opts.output)?;
     let mut writer = BufWriter::new(out_file);
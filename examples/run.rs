@@ -3,10 +3,13 @@ use filtration_domination::datasets;
 use filtration_domination::datasets::Threshold;
 use filtration_domination::distance_matrix::density_estimation::DensityEstimator;
 use filtration_domination::edges::write_edge_list;
-use filtration_domination::mpfree::compute_minimal_presentation;
-use filtration_domination::removal::{
-    remove_filtration_dominated, remove_strongly_filtration_dominated, EdgeOrder,
+use filtration_domination::filtration::{build_flag_filtration, Filtration};
+use filtration_domination::mpfree::{
+    compute_minimal_presentation, compute_minimal_presentation_of_filtration, MpfreeOptions,
 };
+use filtration_domination::pipeline::Pipeline;
+use filtration_domination::removal::EdgeOrder;
+use filtration_domination::simplicial_complex::MapSimplicialComplex;
 use ordered_float::OrderedFloat;
 use std::fmt::Formatter;
 use std::fs::File;
@@ -43,6 +46,11 @@ struct RunCli {
     #[clap(short, long)]
     threshold: Option<f64>,
 
+    /// Maximum value on the codensities. If given, the distances are bounded by --threshold,
+    /// inclusive, or not at all.
+    #[clap(long)]
+    max_codensity: Option<f64>,
+
     /// Save the input edges (not reduced) to a file.
     #[clap(long)]
     export_edges: Option<String>,
@@ -61,6 +69,8 @@ enum Dataset {
     Torus,
     SwissRoll,
     NoisyTorus,
+    #[cfg(feature = "bundled-examples")]
+    BundledCircle50,
 }
 
 impl Dataset {
@@ -87,6 +97,8 @@ impl Dataset {
                 n_points: n_points.unwrap_or(200),
             },
             Dataset::NoisyTorus => datasets::Dataset::NoisyTorus,
+            #[cfg(feature = "bundled-examples")]
+            Dataset::BundledCircle50 => datasets::Dataset::BundledCircle50,
         }
     }
 }
@@ -108,6 +120,8 @@ impl std::fmt::Display for Dataset {
                 Dataset::Torus => "torus",
                 Dataset::SwissRoll => "swiss roll",
                 Dataset::NoisyTorus => "noisy torus",
+                #[cfg(feature = "bundled-examples")]
+                Dataset::BundledCircle50 => "bundled circle",
             }
         )
     }
@@ -118,9 +132,18 @@ fn main() -> anyhow::Result<()> {
 
     let dataset = opts.dataset.to_internal_dataset(None);
 
-    let mut edges = datasets::get_dataset_density_edge_list(
-        dataset,
-        opts.threshold.map_or(Threshold::KeepAll, Threshold::Fixed),
+    let threshold = match (opts.max_codensity, opts.threshold) {
+        (Some(max_codensity), max_distance) => Threshold::Rectangle {
+            max_codensity,
+            max_distance: max_distance.unwrap_or(f64::INFINITY),
+        },
+        (None, Some(t)) => Threshold::Fixed(t),
+        (None, None) => Threshold::KeepAll,
+    };
+
+    let edges = datasets::get_dataset_density_edge_list(
+        dataset.clone(),
+        threshold,
         opts.bandwidth
             .map(|b| DensityEstimator::Gaussian(OrderedFloat(b))),
         true,
@@ -132,37 +155,58 @@ fn main() -> anyhow::Result<()> {
         write_edge_list(&edges, &mut writer, false)?;
     }
 
-    let start = std::time::Instant::now();
-    let remaining_edges = if opts.strong {
-        remove_strongly_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic)
+    let removal = if opts.strong {
+        Pipeline::new().strong_removal(EdgeOrder::ReverseLexicographic)
     } else {
         println!("Removing filtration-dominated edges...");
         println!("Run with --strong to remove strongly filtration-dominated edges.");
-        remove_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic)
+        Pipeline::new().full_removal(EdgeOrder::ReverseLexicographic)
     };
-    let duration = start.elapsed();
+    let removal_output = removal.run(edges.clone())?;
+    let duration = removal_output.total_duration();
+    let remaining_edges = removal_output.edges;
 
     println!("Original edges: {}", edges.len());
     println!("Remaining edges: {}", remaining_edges.len());
     println!("Removal took {duration:?}");
 
     if opts.mpfree {
-        println!("Running mpfree on remaining edges...");
-        let mpfree_remaining = compute_minimal_presentation(
-            &format!("test_mpfree_{}_strong_collapse", dataset),
-            HOMOLOGY,
-            &remaining_edges,
-        )?;
-
-        if opts.full_mpfree {
+        let mpfree_remaining = if opts.full_mpfree {
+            // Build the filtration of the full edges once, and restrict it to the remaining edges.
+            let full: Filtration<_, MapSimplicialComplex> =
+                build_flag_filtration(edges.n_vertices, HOMOLOGY + 1, edges.edge_iter().copied());
+            let remaining = full.restrict_to_edges(remaining_edges.edge_iter().map(|e| e.edge));
+            let options = MpfreeOptions::default();
+
+            println!("Running mpfree on remaining edges...");
+            let mpfree_remaining = compute_minimal_presentation_of_filtration(
+                &format!("test_mpfree_{}_strong_collapse", dataset),
+                HOMOLOGY,
+                &remaining,
+                &options,
+            )?;
             println!("Running mpfree on full edges...");
-            let mpfree_no_collapse = compute_minimal_presentation(
+            let mpfree_no_collapse = compute_minimal_presentation_of_filtration(
                 &format!("test_mpfree_{}", dataset),
                 HOMOLOGY,
-                &edges,
+                &full,
+                &options,
             )?;
-            assert_eq!(mpfree_remaining.output, mpfree_no_collapse.output);
-        }
+            let diff = mpfree_no_collapse.output.diff(&mpfree_remaining.output);
+            anyhow::ensure!(
+                diff.is_empty(),
+                "The minimal presentations differ after the removal:\n{}",
+                diff
+            );
+            mpfree_remaining
+        } else {
+            println!("Running mpfree on remaining edges...");
+            compute_minimal_presentation(
+                &format!("test_mpfree_{}_strong_collapse", dataset),
+                HOMOLOGY,
+                &remaining_edges,
+            )?
+        };
 
         println!(
             "Minimal presentation sizes: {:?}",
@@ -0,0 +1,139 @@
+use clap::Args;
+use std::fmt::Formatter;
+use std::time::Duration;
+
+use crate::CliDataset;
+use crate::{display, display_duration, save_table, Algorithm, Row, Table, ALL_DATASETS};
+
+use filtration_domination::datasets;
+use filtration_domination::datasets::Threshold;
+use filtration_domination::removal::{
+    remove_filtration_dominated_with_deletion, remove_strongly_filtration_dominated_with_deletion,
+    DeletionMode, EdgeOrder,
+};
+
+#[derive(Debug, Args)]
+pub struct DeletionCli {
+    #[clap(arg_enum)]
+    datasets: Vec<CliDataset>,
+
+    /// Number of points to sample, if the dataset is sampled.
+    #[clap(short)]
+    n_points: Option<usize>,
+
+    /// Use the strong filtration-domination removal algorithm.
+    #[clap(short, long)]
+    strong: bool,
+
+    /// Timeout, in seconds, when removing edges.
+    #[clap(short, default_value_t = 60 * 60 * 2)]
+    timeout: u64,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Mode(DeletionMode);
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            DeletionMode::Immediate => write!(f, "Immediate"),
+            DeletionMode::Deferred => write!(f, "Deferred"),
+        }
+    }
+}
+
+const ALL_MODES: [DeletionMode; 2] = [DeletionMode::Immediate, DeletionMode::Deferred];
+
+#[derive(Debug)]
+struct DeletionRow {
+    dataset: CliDataset,
+    n_points: usize,
+    modality: Algorithm,
+    mode: Mode,
+    edges_before_collapse: usize,
+    edges_after_collapse: usize,
+    collapse_duration: Duration,
+}
+
+impl Row for DeletionRow {
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "Dataset", "Points", "Modality", "Deletion", "Before", "After", "Time",
+        ]
+    }
+
+    fn fields(&self) -> Vec<Option<String>> {
+        vec![
+            Some(display(self.dataset)),
+            Some(display(self.n_points)),
+            Some(display(self.modality)),
+            Some(display(self.mode)),
+            Some(display(self.edges_before_collapse)),
+            Some(display(self.edges_after_collapse)),
+            Some(display_duration(&self.collapse_duration)),
+        ]
+    }
+}
+
+pub fn compare_deletion_modes(opts: DeletionCli) -> anyhow::Result<()> {
+    let datasets = if opts.datasets.is_empty() {
+        Vec::from(ALL_DATASETS)
+    } else {
+        opts.datasets
+    };
+
+    let modality = if opts.strong {
+        Algorithm::StrongFiltrationDomination
+    } else {
+        Algorithm::FiltrationDomination
+    };
+
+    let timeout = Duration::from_secs(opts.timeout);
+    println!("Using {timeout:?} as timeout.");
+
+    let mut rows: Vec<DeletionRow> = Vec::new();
+    for dataset in datasets {
+        println!("Processing dataset {}", dataset);
+        let mut edges = datasets::get_dataset_density_edge_list(
+            dataset.to_internal_dataset(opts.n_points),
+            Threshold::KeepAll,
+            None,
+            true,
+        )?;
+        let edges_before_collapse = edges.len();
+        for mode in ALL_MODES {
+            let start = std::time::Instant::now();
+            let collapsed_edges = match modality {
+                Algorithm::FiltrationDomination => remove_filtration_dominated_with_deletion(
+                    &mut edges,
+                    EdgeOrder::ReverseLexicographic,
+                    mode,
+                    Some(timeout),
+                ),
+                Algorithm::StrongFiltrationDomination => {
+                    remove_strongly_filtration_dominated_with_deletion(
+                        &mut edges,
+                        EdgeOrder::ReverseLexicographic,
+                        mode,
+                        Some(timeout),
+                    )
+                }
+            };
+            let duration = start.elapsed();
+
+            rows.push(DeletionRow {
+                dataset,
+                n_points: edges.n_vertices,
+                modality,
+                mode: Mode(mode),
+                edges_before_collapse,
+                edges_after_collapse: collapsed_edges.len(),
+                collapse_duration: duration,
+            });
+        }
+    }
+
+    save_table(Table::new(rows), "compare_deletion_modes")?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 pub mod asymptotics;
+pub mod deletion;
 pub mod mpfree;
 pub mod multiple_iterations;
 pub mod orders;
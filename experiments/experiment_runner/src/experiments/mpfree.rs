@@ -2,16 +2,16 @@ use clap::ArgEnum;
 use clap::Args;
 use std::error::Error as StdError;
 use std::time::Duration;
-use thiserror::Error;
 
 use filtration_domination::datasets;
 use filtration_domination::datasets::Threshold;
 use filtration_domination::mpfree::{
-    compute_minimal_presentation_with_check, MinimalPresentationComputationTime,
-};
-use filtration_domination::removal::{
-    remove_filtration_dominated, remove_strongly_filtration_dominated, EdgeOrder,
+    compute_minimal_presentation_with_check_and_options, FiltrationStatistics,
+    MinimalPresentationComputationTime, MpfreeOptions,
 };
+use filtration_domination::pipeline::Pipeline;
+use filtration_domination::removal::EdgeOrder;
+use filtration_domination::resource::{MemoryGuard, MemoryGuardError, MemoryMeasure};
 
 use crate::memory_usage::{get_maximum_memory_usage, Kilobytes, Resource};
 use crate::table::{display_option, display_option_as};
@@ -21,8 +21,6 @@ use filtration_domination::mpfree::CheckedMpfreeError;
 // Degree of homology to do minimal presentations with.
 const HOMOLOGY: usize = 1;
 
-const BYTES_IN_GIGABYTE: u64 = 1024 * 1024 * 1024;
-
 #[derive(Debug, Args)]
 pub struct MpfreeCli {
     #[clap(arg_enum)]
@@ -33,7 +31,11 @@ pub struct MpfreeCli {
 
     /// The maximum memory, in gigabytes, to allow when building the filtration.
     #[clap(short, long)]
-    maximum_memory_gigabytes: Option<u64>,
+    maximum_memory_gigabytes: Option<usize>,
+
+    /// Stream the bifiltration to the stdin of mpfree, instead of writing it to a file.
+    #[clap(long)]
+    pipe: bool,
 }
 
 #[derive(Debug, Copy, Clone, ArgEnum)]
@@ -63,6 +65,7 @@ struct MpfreeRow<E> {
     after: usize,
     modality: MpfreeComputationModality,
     mpfree_timers: Result<MinimalPresentationComputationTime, E>,
+    filtration: Option<FiltrationStatistics>,
     removal_time: Duration,
     maximum_memory_kb: Option<Kilobytes>,
 }
@@ -70,8 +73,19 @@ struct MpfreeRow<E> {
 impl<E: StdError> Row for MpfreeRow<E> {
     fn headers() -> Vec<&'static str> {
         vec![
-            "Dataset", "Points", "Before", "After", "Modality", "Collapse", "Build", "Write",
-            "Mpfree", "Error", "Memory",
+            "Dataset",
+            "Points",
+            "Before",
+            "After",
+            "Modality",
+            "Collapse",
+            "Build",
+            "Write",
+            "Mpfree",
+            "Error",
+            "Memory",
+            "Simplices",
+            "Memory estimate",
         ]
     }
 
@@ -107,33 +121,24 @@ impl<E: StdError> Row for MpfreeRow<E> {
                 self.mpfree_timers.as_ref().err().map(|e| e.to_string()),
             )),
             Some(display_option(self.maximum_memory_kb.as_ref())),
+            Some(display_option(
+                self.filtration
+                    .as_ref()
+                    .map(|f| f.sizes.iter().sum::<usize>()),
+            )),
+            Some(display_option(
+                self.filtration
+                    .as_ref()
+                    .map(|f| f.memory_bytes_estimate / 1024),
+            )),
         ]
     }
 }
 
-#[derive(Error, Debug)]
-pub enum MemoryError {
-    #[error("Procfs error: {0}")]
-    Procfs(#[from] procfs::ProcError),
-
-    #[error("Memory limit exceeded")]
-    MemoryLimit,
-}
-
-fn memory_consumption_check(iteration: usize, max_memory_bytes: u64) -> Result<(), MemoryError> {
-    if iteration % 1000 == 0 {
-        let proc_info = procfs::process::Process::myself()?;
-        if proc_info.stat()?.vsize > max_memory_bytes {
-            return Err(MemoryError::MemoryLimit);
-        }
-    }
-    Ok(())
-}
-
 pub fn compare_mpfree(opts: MpfreeCli) -> anyhow::Result<()> {
-    let mut rows: Vec<MpfreeRow<CheckedMpfreeError<MemoryError>>> = Vec::new();
+    let mut rows: Vec<MpfreeRow<CheckedMpfreeError<MemoryGuardError>>> = Vec::new();
 
-    let mut edges = datasets::get_dataset_density_edge_list(
+    let edges = datasets::get_dataset_density_edge_list(
         opts.dataset.to_internal_dataset(None),
         Threshold::KeepAll,
         None,
@@ -141,27 +146,36 @@ pub fn compare_mpfree(opts: MpfreeCli) -> anyhow::Result<()> {
     )?;
     let n_initial_edges = edges.len();
 
-    let start = std::time::Instant::now();
-    let edges = match opts.modality {
-        MpfreeComputationModality::OnlyMpfree => edges,
+    let removal = match opts.modality {
+        MpfreeComputationModality::OnlyMpfree => Pipeline::new(),
         MpfreeComputationModality::FiltrationDomination => {
-            remove_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic)
+            Pipeline::new().full_removal(EdgeOrder::ReverseLexicographic)
         }
         MpfreeComputationModality::StrongFiltrationDomination => {
-            remove_strongly_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic)
+            Pipeline::new().strong_removal(EdgeOrder::ReverseLexicographic)
         }
     };
-    let duration_edge_removal = start.elapsed();
+    let removal_output = removal.run(edges)?;
+    let duration_edge_removal = removal_output.total_duration();
+    let edges = removal_output.edges;
 
     eprintln!("Computing the minimal presentation...");
     let maximum_memory_check = opts.maximum_memory_gigabytes.map(|gigabytes| {
-        move |iteration| memory_consumption_check(iteration, gigabytes * BYTES_IN_GIGABYTE)
+        MemoryGuard {
+            measure: MemoryMeasure::Virtual,
+            ..MemoryGuard::with_gigabytes(gigabytes)
+        }
+        .check_fn()
     });
-    let mpfree = compute_minimal_presentation_with_check(
+    let mpfree = compute_minimal_presentation_with_check_and_options(
         &format!("comp_mpfree_{}_{}", opts.dataset, opts.modality),
         HOMOLOGY,
         &edges,
         maximum_memory_check,
+        &MpfreeOptions {
+            pipe: opts.pipe,
+            ..MpfreeOptions::default()
+        },
     );
 
     // Get the memory consumed by this process: this includes both the run of the filtration-domination
@@ -181,6 +195,7 @@ pub fn compare_mpfree(opts: MpfreeCli) -> anyhow::Result<()> {
         before: n_initial_edges,
         after: edges.len(),
         removal_time: duration_edge_removal,
+        filtration: mpfree.as_ref().ok().map(|info| info.filtration.clone()),
         mpfree_timers: mpfree.map(|info| info.timers),
         modality: opts.modality,
         maximum_memory_kb: memory,
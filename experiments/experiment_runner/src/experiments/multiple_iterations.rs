@@ -4,14 +4,15 @@ use std::time::Duration;
 use crate::{display, display_duration, save_table, CliDataset, Row, Table, ALL_DATASETS};
 use filtration_domination::datasets;
 use filtration_domination::datasets::Threshold;
-use filtration_domination::removal::{remove_strongly_filtration_dominated, EdgeOrder};
+use filtration_domination::removal::{iterate_strongly_filtration_dominated, EdgeOrder};
 
 #[derive(Debug, Args)]
 pub struct MultipleIterationsCli {
     #[clap(arg_enum)]
     datasets: Vec<CliDataset>,
 
-    /// Number of iterations to run per dataset.
+    /// Maximum number of iterations to run per dataset. Stops earlier if an iteration removes no
+    /// edges.
     #[clap(short, default_value_t = 5)]
     iterations: usize,
 }
@@ -49,7 +50,7 @@ pub fn compare_multiple_iterations(opts: MultipleIterationsCli) -> anyhow::Resul
     let mut rows: Vec<MultipleIterationsRow> = Vec::new();
     for dataset in datasets {
         println!("Processing dataset {}", dataset);
-        let mut edges = datasets::get_dataset_density_edge_list(
+        let edges = datasets::get_dataset_density_edge_list(
             dataset.to_internal_dataset(None),
             Threshold::KeepAll,
             None,
@@ -61,22 +62,20 @@ pub fn compare_multiple_iterations(opts: MultipleIterationsCli) -> anyhow::Resul
             edges: edges.len(),
             collapse_duration: Default::default(),
         });
-        let start = std::time::Instant::now();
-        for i in 1..=opts.iterations {
-            let collapsed_edges =
-                remove_strongly_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
-
-            let duration = start.elapsed();
-            let row = MultipleIterationsRow {
+        let iterations = iterate_strongly_filtration_dominated(
+            &edges,
+            EdgeOrder::ReverseLexicographic.into(),
+            None,
+        );
+        let mut duration = Duration::ZERO;
+        for (i, iteration) in iterations.take(opts.iterations).enumerate() {
+            duration += iteration.duration;
+            rows.push(MultipleIterationsRow {
                 dataset,
-                iteration: i,
-                edges: collapsed_edges.len(),
+                iteration: i + 1,
+                edges: iteration.edges_after(),
                 collapse_duration: duration,
-            };
-
-            rows.push(row);
-
-            edges = collapsed_edges;
+            });
         }
     }
 
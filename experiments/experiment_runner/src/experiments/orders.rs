@@ -5,10 +5,9 @@ use std::time::Duration;
 use crate::CliDataset;
 use crate::{display, display_duration, save_table, Algorithm, Row, Table, ALL_DATASETS};
 
+use filtration_domination::datasets;
 use filtration_domination::datasets::Threshold;
-use filtration_domination::edges::{EdgeList, FilteredEdge};
 use filtration_domination::removal::{remove_filtration_dominated_timed, EdgeOrder};
-use filtration_domination::{datasets, OneCriticalGrade, Value};
 
 #[derive(Debug, Args)]
 pub struct OrderCli {
@@ -30,19 +29,18 @@ pub enum Order {
     ForwardLexicographic,
     ForwardColexicographic,
     Random,
+    Heuristic,
 }
 
 impl Order {
-    fn apply<VF: Value, const N: usize>(
-        &self,
-        edges: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
-    ) {
+    fn to_edge_order(self) -> EdgeOrder {
         match self {
-            Order::ReverseLexicographic => edges.sort_reverse_lexicographically(),
-            Order::ReverseColexicographic => edges.sort_reverse_colexicographically(),
-            Order::ForwardLexicographic => edges.sort_lexicographically(),
-            Order::ForwardColexicographic => edges.sort_colexicographically(),
-            Order::Random => edges.shuffle(),
+            Order::ReverseLexicographic => EdgeOrder::ReverseLexicographic,
+            Order::ReverseColexicographic => EdgeOrder::ReverseColexicographic,
+            Order::ForwardLexicographic => EdgeOrder::Lexicographic,
+            Order::ForwardColexicographic => EdgeOrder::Colexicographic,
+            Order::Random => EdgeOrder::Random(rand::random()),
+            Order::Heuristic => EdgeOrder::Heuristic,
         }
     }
 
@@ -53,6 +51,7 @@ impl Order {
             Order::ForwardLexicographic => "Lex",
             Order::ForwardColexicographic => "Colex",
             Order::Random => "Rand",
+            Order::Heuristic => "Heur",
         }
     }
 }
@@ -63,12 +62,13 @@ impl std::fmt::Display for Order {
     }
 }
 
-const ALL_ORDERS: [Order; 5] = [
+const ALL_ORDERS: [Order; 6] = [
     Order::ReverseLexicographic,
     Order::ReverseColexicographic,
     Order::ForwardLexicographic,
     Order::ForwardColexicographic,
     Order::Random,
+    Order::Heuristic,
 ];
 
 #[derive(Debug)]
@@ -129,7 +129,7 @@ pub fn compare_orders(opts: OrderCli) -> anyhow::Result<()> {
         )?;
         let edges_before_collapse = edges.len();
         for &order in &orders {
-            order.apply(&mut edges);
+            order.to_edge_order().apply(&mut edges);
 
             let start = std::time::Instant::now();
             let collapsed_edges =
@@ -1,12 +1,12 @@
 use clap::Args;
 
 use crate::experiments::orders::Order;
-use crate::utils::{forget_densities, random_densities, zero_grades};
 use crate::{display, display_duration, save_table, CliDataset, Row, Table, ALL_DATASETS};
 use filtration_domination::datasets;
 use filtration_domination::datasets::Threshold;
-use filtration_domination::removal::utils::count_isolated_edges;
-use filtration_domination::removal::{remove_strongly_filtration_dominated_timed, EdgeOrder};
+use filtration_domination::edges::transform::{forget_parameter, randomize_parameter};
+use filtration_domination::removal::utils::{isolated_edge_counts, IsolatedEdgeCounts};
+use filtration_domination::removal::{remove_strongly_filtration_dominated_with_report, EdgeOrder};
 use std::fmt::Formatter;
 use std::time::Duration;
 
@@ -70,6 +70,7 @@ struct RandomDensitiesRow {
     edges_before_collapse: usize,
     edges_after_collapse: usize,
     collapse_duration: Duration,
+    timed_out: bool,
     isolated: usize,
     dominated: usize,
 }
@@ -84,6 +85,7 @@ impl Row for RandomDensitiesRow {
             "Before",
             "After",
             "Time",
+            "Timeout",
             "Isolated",
             "Dominated",
         ]
@@ -98,6 +100,7 @@ impl Row for RandomDensitiesRow {
             Some(display(self.edges_before_collapse)),
             Some(display(self.edges_after_collapse)),
             Some(display_duration(&self.collapse_duration)),
+            Some(display(self.timed_out)),
             Some(display(self.isolated)),
             Some(display(self.dominated)),
         ]
@@ -122,28 +125,33 @@ pub fn compare_random_densities(opts: RandomDensitiesCli) -> anyhow::Result<()>
         )?;
 
         let mut zero_density_edges = edges.clone();
-        forget_densities(&mut zero_density_edges);
+        forget_parameter(&mut zero_density_edges, 0);
 
         let mut zero_grades_edges = edges.clone();
-        zero_grades(&mut zero_grades_edges);
+        forget_parameter(&mut zero_grades_edges, 0);
+        forget_parameter(&mut zero_grades_edges, 1);
 
         let edges_before_collapse = edges.len();
         let n_points = edges.n_vertices;
 
         let mut edges_random_densities = edges.clone();
-        random_densities(&mut edges_random_densities);
+        randomize_parameter(&mut edges_random_densities, 0);
 
         if opts.colexicograhic {
-            let (isolated, dominated) = count_isolated_edges(&edges_random_densities);
+            let IsolatedEdgeCounts {
+                isolated,
+                dominated_at_entry: dominated,
+                ..
+            } = isolated_edge_counts(&edges_random_densities);
             edges_random_densities.sort_reverse_colexicographically();
 
             let start = std::time::Instant::now();
-            let resulting_edges = remove_strongly_filtration_dominated_timed(
+            let report = remove_strongly_filtration_dominated_with_report(
                 &mut edges_random_densities,
                 EdgeOrder::Maintain,
                 Some(TIMEOUT_DURATION_RANDOM_DENSITIES),
             );
-            let edges_after_collapse = resulting_edges.len();
+            let edges_after_collapse = report.remaining.len();
 
             rows.push(RandomDensitiesRow {
                 dataset,
@@ -153,20 +161,25 @@ pub fn compare_random_densities(opts: RandomDensitiesCli) -> anyhow::Result<()>
                 edges_before_collapse,
                 edges_after_collapse,
                 collapse_duration: start.elapsed(),
+                timed_out: report.timed_out(),
                 isolated,
                 dominated,
             });
         }
 
         {
-            let (isolated, dominated) = count_isolated_edges(&edges_random_densities);
+            let IsolatedEdgeCounts {
+                isolated,
+                dominated_at_entry: dominated,
+                ..
+            } = isolated_edge_counts(&edges_random_densities);
             let start = std::time::Instant::now();
-            let resulting_edges = remove_strongly_filtration_dominated_timed(
+            let report = remove_strongly_filtration_dominated_with_report(
                 &mut edges_random_densities,
                 EdgeOrder::ReverseLexicographic,
                 Some(TIMEOUT_DURATION_RANDOM_DENSITIES),
             );
-            let edges_after_collapse = resulting_edges.len();
+            let edges_after_collapse = report.remaining.len();
 
             rows.push(RandomDensitiesRow {
                 dataset,
@@ -176,20 +189,25 @@ pub fn compare_random_densities(opts: RandomDensitiesCli) -> anyhow::Result<()>
                 edges_before_collapse,
                 edges_after_collapse,
                 collapse_duration: start.elapsed(),
+                timed_out: report.timed_out(),
                 isolated,
                 dominated,
             });
         }
 
         if !opts.only_random {
-            let (isolated, dominated) = count_isolated_edges(&zero_density_edges);
+            let IsolatedEdgeCounts {
+                isolated,
+                dominated_at_entry: dominated,
+                ..
+            } = isolated_edge_counts(&zero_density_edges);
             let start = std::time::Instant::now();
-            let resulting_edges = remove_strongly_filtration_dominated_timed(
+            let report = remove_strongly_filtration_dominated_with_report(
                 &mut zero_density_edges,
                 EdgeOrder::ReverseLexicographic,
                 Some(TIMEOUT_DURATION_RANDOM_DENSITIES),
             );
-            let edges_after_collapse = resulting_edges.len();
+            let edges_after_collapse = report.remaining.len();
 
             rows.push(RandomDensitiesRow {
                 dataset,
@@ -199,20 +217,25 @@ pub fn compare_random_densities(opts: RandomDensitiesCli) -> anyhow::Result<()>
                 edges_before_collapse,
                 edges_after_collapse,
                 collapse_duration: start.elapsed(),
+                timed_out: report.timed_out(),
                 isolated,
                 dominated,
             });
         }
 
         if !opts.only_random {
-            let (isolated, dominated) = count_isolated_edges(&zero_grades_edges);
+            let IsolatedEdgeCounts {
+                isolated,
+                dominated_at_entry: dominated,
+                ..
+            } = isolated_edge_counts(&zero_grades_edges);
             let start = std::time::Instant::now();
-            let resulting_edges = remove_strongly_filtration_dominated_timed(
+            let report = remove_strongly_filtration_dominated_with_report(
                 &mut zero_grades_edges,
                 EdgeOrder::ReverseLexicographic,
                 Some(TIMEOUT_DURATION_RANDOM_DENSITIES),
             );
-            let edges_after_collapse = resulting_edges.len();
+            let edges_after_collapse = report.remaining.len();
 
             rows.push(RandomDensitiesRow {
                 dataset,
@@ -222,20 +245,25 @@ pub fn compare_random_densities(opts: RandomDensitiesCli) -> anyhow::Result<()>
                 edges_before_collapse,
                 edges_after_collapse,
                 collapse_duration: start.elapsed(),
+                timed_out: report.timed_out(),
                 isolated,
                 dominated,
             });
         }
 
         if !opts.only_random {
-            let (isolated, dominated) = count_isolated_edges(&edges);
+            let IsolatedEdgeCounts {
+                isolated,
+                dominated_at_entry: dominated,
+                ..
+            } = isolated_edge_counts(&edges);
             let start = std::time::Instant::now();
-            let resulting_edges = remove_strongly_filtration_dominated_timed(
+            let report = remove_strongly_filtration_dominated_with_report(
                 &mut edges,
                 EdgeOrder::ReverseLexicographic,
                 Some(TIMEOUT_DURATION_RANDOM_DENSITIES),
             );
-            let edges_after_collapse = resulting_edges.len();
+            let edges_after_collapse = report.remaining.len();
 
             rows.push(RandomDensitiesRow {
                 dataset,
@@ -245,6 +273,7 @@ pub fn compare_random_densities(opts: RandomDensitiesCli) -> anyhow::Result<()>
                 edges_before_collapse,
                 edges_after_collapse,
                 collapse_duration: start.elapsed(),
+                timed_out: report.timed_out(),
                 isolated,
                 dominated,
             });
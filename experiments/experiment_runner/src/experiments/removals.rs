@@ -1,11 +1,12 @@
 use crate::single_collapse::run_single_parameter_edge_collapse;
-use crate::utils::{delete_densities, forget_densities};
 use crate::{display, display_duration, save_table, CliDataset, Row, Table, ALL_DATASETS};
 use clap::Args;
 use filtration_domination::datasets::Threshold;
+use filtration_domination::edges::transform::{forget_parameter, project};
 use filtration_domination::edges::{write_edge_list, EdgeList, FilteredEdge};
 use filtration_domination::removal::{
-    remove_filtration_dominated, remove_strongly_filtration_dominated, EdgeOrder,
+    remove_filtration_dominated, remove_filtration_dominated_single_parameter,
+    remove_strongly_filtration_dominated, EdgeOrder,
 };
 use filtration_domination::{datasets, OneCriticalGrade, Value};
 use std::fmt::{Display, Formatter};
@@ -31,6 +32,7 @@ enum RemovalPolicy {
     StrongFiltrationDomination,
     FiltrationDomination,
     SingleParameter,
+    SingleParameterNative,
 
     StrongFiltrationDominationSingle,
     FiltrationDominationSingle,
@@ -42,6 +44,7 @@ impl Display for RemovalPolicy {
             RemovalPolicy::StrongFiltrationDomination => write!(f, "strong-filtration-domination"),
             RemovalPolicy::FiltrationDomination => write!(f, "filtration-domination"),
             RemovalPolicy::SingleParameter => write!(f, "single-parameter"),
+            RemovalPolicy::SingleParameterNative => write!(f, "single-parameter-native"),
             RemovalPolicy::StrongFiltrationDominationSingle => {
                 write!(f, "strong-filtration-domination-single")
             }
@@ -54,7 +57,8 @@ const ALL_REMOVAL_POLICIES: [RemovalPolicy; 3] = [
     RemovalPolicy::StrongFiltrationDomination,
     RemovalPolicy::FiltrationDomination,
     RemovalPolicy::SingleParameter,
-    // By default we do not do the single parameter variants of (strong) filtration domination.
+    // By default we do not do the single parameter variants of (strong) filtration domination,
+    // nor the edge collapse of the library, which does not shift the grades of the edges.
 ];
 
 #[derive(Debug)]
@@ -89,15 +93,21 @@ fn save_single_parameter_edges<VF: Value>(
     dataset: CliDataset,
     policy: RemovalPolicy,
 ) -> anyhow::Result<()> {
-    let edges_no_density = delete_densities(edges);
+    save_edges(&project(edges, [1]), dataset, policy)
+}
 
+fn save_edges<VF: Value>(
+    edges: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>>,
+    dataset: CliDataset,
+    policy: RemovalPolicy,
+) -> anyhow::Result<()> {
     let directory = std::path::Path::new(TMP_DIRECTORY);
     std::fs::create_dir_all(&directory)?;
     let out_edges_path =
         directory.join(format!("single_parameter_edges_{}_{}.txt", dataset, policy));
 
     let mut out_edges_file = std::fs::File::create(out_edges_path)?;
-    write_edge_list(&edges_no_density, &mut out_edges_file, true)?;
+    write_edge_list(edges, &mut out_edges_file, true)?;
 
     Ok(())
 }
@@ -124,10 +134,10 @@ pub fn compare_removals(opts: RemovalCli) -> anyhow::Result<()> {
             None,
             true,
         )?;
-        let single_parameter_edges = delete_densities(&edges);
+        let single_parameter_edges = project(&edges, [1]);
 
         let mut zero_density_edges = edges.clone();
-        forget_densities(&mut zero_density_edges);
+        forget_parameter(&mut zero_density_edges, 0);
 
         let edges_before_collapse = edges.len();
         let n_points = edges.n_vertices;
@@ -162,6 +172,20 @@ pub fn compare_removals(opts: RemovalCli) -> anyhow::Result<()> {
 
                     result
                 }
+                RemovalPolicy::SingleParameterNative => {
+                    let start = std::time::Instant::now();
+                    let resulting_edges = remove_filtration_dominated_single_parameter(
+                        &mut single_parameter_edges.clone(),
+                        EdgeOrder::ReverseLexicographic,
+                    );
+                    let duration = start.elapsed();
+
+                    if opts.save_single_parameter_edges {
+                        save_edges(&resulting_edges, dataset, policy)?;
+                    }
+
+                    (resulting_edges.len(), duration)
+                }
                 RemovalPolicy::StrongFiltrationDominationSingle => {
                     let start = std::time::Instant::now();
                     let resulting_edges = remove_strongly_filtration_dominated(
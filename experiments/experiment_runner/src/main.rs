@@ -2,7 +2,6 @@ mod experiments;
 mod memory_usage;
 mod single_collapse;
 mod table;
-mod utils;
 
 use crate::experiments::orders::{compare_orders, OrderCli};
 use crate::table::{display, display_duration, Row, Table};
@@ -12,6 +11,7 @@ use filtration_domination::datasets;
 use clap::Parser;
 
 use crate::experiments::asymptotics::{compare_asymptotics, AsymptoticCli};
+use crate::experiments::deletion::{compare_deletion_modes, DeletionCli};
 use crate::experiments::mpfree::{compare_mpfree, MpfreeCli};
 use crate::experiments::multiple_iterations::{compare_multiple_iterations, MultipleIterationsCli};
 use crate::experiments::random_densities::{compare_random_densities, RandomDensitiesCli};
@@ -34,6 +34,7 @@ enum ExperimentCli {
     Asymptotics(AsymptoticCli),
     MultipleIterations(MultipleIterationsCli),
     RandomDensities(RandomDensitiesCli),
+    Deletion(DeletionCli),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, clap::ArgEnum)]
@@ -48,9 +49,14 @@ enum CliDataset {
     Circle,
     Torus,
     SwissRoll,
+    Rgg,
+    Girg,
+    GaussianMixture,
+    Annulus,
+    FigureEight,
 }
 
-const ALL_DATASETS: [CliDataset; 10] = [
+const ALL_DATASETS: [CliDataset; 15] = [
     CliDataset::Senate,
     CliDataset::Eleg,
     CliDataset::Netwsc,
@@ -61,6 +67,11 @@ const ALL_DATASETS: [CliDataset; 10] = [
     CliDataset::Circle,
     CliDataset::Torus,
     CliDataset::SwissRoll,
+    CliDataset::Rgg,
+    CliDataset::Girg,
+    CliDataset::GaussianMixture,
+    CliDataset::Annulus,
+    CliDataset::FigureEight,
 ];
 
 impl CliDataset {
@@ -76,6 +87,11 @@ impl CliDataset {
             CliDataset::Circle => Some(100),
             CliDataset::Torus => Some(200),
             CliDataset::SwissRoll => Some(200),
+            CliDataset::Rgg => Some(400),
+            CliDataset::Girg => Some(400),
+            CliDataset::GaussianMixture => Some(400),
+            CliDataset::Annulus => Some(200),
+            CliDataset::FigureEight => Some(200),
         }
     }
 
@@ -101,6 +117,29 @@ impl CliDataset {
             CliDataset::SwissRoll => datasets::Dataset::SwissRoll {
                 n_points: n_points.unwrap_or(200),
             },
+            CliDataset::Rgg => datasets::Dataset::RandomGeometricGraph {
+                n_points: n_points.unwrap_or(400),
+                radius: 0.1,
+                weight_exponent: None,
+            },
+            CliDataset::Girg => datasets::Dataset::RandomGeometricGraph {
+                n_points: n_points.unwrap_or(400),
+                radius: 0.05,
+                weight_exponent: Some(2.5),
+            },
+            CliDataset::GaussianMixture => datasets::Dataset::GaussianMixture {
+                n_points: n_points.unwrap_or(400),
+                n_clusters: 4,
+                noise: 0.1,
+            },
+            CliDataset::Annulus => datasets::Dataset::Annulus {
+                n_points: n_points.unwrap_or(200),
+                noise: 0.05,
+            },
+            CliDataset::FigureEight => datasets::Dataset::FigureEight {
+                n_points: n_points.unwrap_or(200),
+                noise: 0.05,
+            },
         }
     }
 }
@@ -121,6 +160,11 @@ impl std::fmt::Display for CliDataset {
                 CliDataset::Circle => "circle",
                 CliDataset::Torus => "torus",
                 CliDataset::SwissRoll => "swiss-roll",
+                CliDataset::Rgg => "rgg",
+                CliDataset::Girg => "girg",
+                CliDataset::GaussianMixture => "gaussian-mixture",
+                CliDataset::Annulus => "annulus",
+                CliDataset::FigureEight => "figure-eight",
             }
         )
     }
@@ -179,6 +223,9 @@ fn main() -> anyhow::Result<()> {
         ExperimentCli::RandomDensities(opts) => {
             compare_random_densities(opts)?;
         }
+        ExperimentCli::Deletion(opts) => {
+            compare_deletion_modes(opts)?;
+        }
     }
 
     Ok(())
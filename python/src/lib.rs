@@ -1,10 +1,16 @@
 use ::filtration_domination::edges::{BareEdge, EdgeList, FilteredEdge};
 use ::filtration_domination::OneCriticalGrade;
-use ::filtration_domination::removal::EdgeOrder;
+use ::filtration_domination::removal::{EdgeOrder, Line};
 use ::filtration_domination::points::{Point, PointCloud};
+use ::filtration_domination::datasets::{self, Dataset, Threshold};
 use ::filtration_domination::distance_matrix::density_estimation::DensityEstimator;
+use ::filtration_domination::distance_matrix::{DistanceMatrix, Symmetrization};
 use ordered_float::OrderedFloat;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::PyList;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 type Edge = (usize, usize);
 type BifilteredEdge = (Edge, (f64, f64));
@@ -51,6 +57,63 @@ fn remove_filtration_dominated(py: Python<'_>, edges: Vec<BifilteredEdge>) -> Py
     py.allow_threads(|| remove_filtration_dominated_original(edges))
 }
 
+/// Removes strongly filtration-dominated edges in repeated passes, until a pass removes no edges
+/// or `max_iterations` passes have been done. Returns, for each pass, the edges that it removed
+/// and the time it took, in seconds.
+#[pyfunction]
+#[pyo3(text_signature = "(edges, max_iterations)")]
+fn remove_strongly_filtration_dominated_iterations(
+    py: Python<'_>,
+    edges: Vec<BifilteredEdge>,
+    max_iterations: usize,
+) -> PyResult<Vec<(Vec<BifilteredEdge>, f64)>> {
+    let edge_list = vector_to_edge_list(edges);
+    let iterations: Vec<_> = py.allow_threads(|| {
+        ::filtration_domination::removal::iterate_strongly_filtration_dominated(
+            &edge_list,
+            EdgeOrder::ReverseLexicographic.into(),
+            None,
+        )
+        .take(max_iterations)
+        .collect()
+    });
+    Ok(iterations
+        .into_iter()
+        .map(|iteration| {
+            let removed = EdgeList::from_iterator(iteration.removed.into_iter().map(|r| r.edge));
+            (edge_list_to_vector(&removed), iteration.duration.as_secs_f64())
+        })
+        .collect())
+}
+
+/// Slices the bifiltered edges along each of the given lines, each given by a base point and a
+/// direction with non-negative coordinates, and collapses each slice with the single-parameter
+/// edge collapse. Returns, for each line, the number of edges of the slice before and after the
+/// collapse.
+#[pyfunction]
+#[pyo3(text_signature = "(edges, lines)")]
+fn collapse_along_lines(
+    py: Python<'_>,
+    edges: Vec<BifilteredEdge>,
+    lines: Vec<((f64, f64), (f64, f64))>,
+) -> PyResult<Vec<(usize, usize)>> {
+    let lines: Vec<Line<OrderedFloat<f64>>> = lines
+        .into_iter()
+        .map(|((b1, b2), (d1, d2))| Line {
+            base: [OrderedFloat(b1), OrderedFloat(b2)],
+            direction: [OrderedFloat(d1), OrderedFloat(d2)],
+        })
+        .collect();
+    let edge_list = vector_to_edge_list(edges);
+    let collapses = py
+        .allow_threads(|| ::filtration_domination::removal::collapse_along_lines(&edge_list, &lines))
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(collapses
+        .into_iter()
+        .map(|collapse| (collapse.sliced_edges, collapse.remaining_edges))
+        .collect())
+}
+
 #[pyfunction]
 fn gaussian_density_estimation(points: Vec<(f64, f64)>, bandwidth: f64) -> PyResult<Vec<f64>> {
     let points = points.into_iter().map(|(x, y)| Point([x, y])).collect();
@@ -60,13 +123,198 @@ fn gaussian_density_estimation(points: Vec<(f64, f64)>, bandwidth: f64) -> PyRes
     Ok(estimator.estimate(&dist_matrix))
 }
 
+fn points_distance_matrix(points: &[Vec<f64>]) -> PyResult<DistanceMatrix<f64>> {
+    let dimension = points.first().map_or(0, |p| p.len());
+    if let Some(i) = points.iter().position(|p| p.len() != dimension) {
+        return Err(PyValueError::new_err(format!(
+            "Point {} has {} coordinates, but point 0 has {}",
+            i,
+            points[i].len(),
+            dimension
+        )));
+    }
+    let mut matrix = DistanceMatrix::new(points.len());
+    for u in 0..points.len() {
+        for v in 0..u {
+            let squared: f64 = points[u].iter().zip(&points[v]).map(|(a, b)| (a - b) * (a - b)).sum();
+            matrix.set(u, v, squared.sqrt());
+        }
+    }
+    Ok(matrix)
+}
+
+/// Reads a precomputed distance matrix, either in condensed form, as returned by
+/// `scipy.spatial.distance.pdist`, or as a square matrix, whose asymmetries are averaged.
+fn precomputed_distance_matrix(data: &PyAny) -> PyResult<DistanceMatrix<f64>> {
+    let matrix = match data.extract::<Vec<f64>>() {
+        Ok(condensed) => DistanceMatrix::from_condensed(&condensed),
+        Err(_) => DistanceMatrix::symmetrize(&data.extract::<Vec<Vec<f64>>>()?, Symmetrization::Average),
+    };
+    matrix.map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+fn density_estimator(kernel: &str, bandwidth: Option<f64>, k: Option<usize>) -> PyResult<DensityEstimator<f64>> {
+    let missing = |argument: &str| PyValueError::new_err(format!("The {} kernel needs the {} argument", kernel, argument));
+    match kernel {
+        "ball" => Ok(DensityEstimator::Ball(bandwidth.ok_or_else(|| missing("bandwidth"))?)),
+        "gaussian" => Ok(DensityEstimator::Gaussian(bandwidth.ok_or_else(|| missing("bandwidth"))?)),
+        "knn" => Ok(DensityEstimator::KNearest(k.ok_or_else(|| missing("k"))?)),
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown kernel {}: it must be one of ball, gaussian or knn",
+            kernel
+        ))),
+    }
+}
+
+/// Estimates the density of each point, given either as the rows of an n x d array of points or,
+/// if `precomputed` is true, by a distance matrix, square or condensed. The kernel is one of
+/// "ball" and "gaussian", which need a `bandwidth`, or "knn", which needs `k`. If `weights` are
+/// given, each point counts as many times as its weight.
+#[pyfunction(bandwidth = "None", k = "None", precomputed = "false", weights = "None")]
+#[pyo3(text_signature = "(data, kernel, bandwidth=None, k=None, precomputed=False, weights=None)")]
+fn density_estimation(
+    py: Python<'_>,
+    data: &PyAny,
+    kernel: &str,
+    bandwidth: Option<f64>,
+    k: Option<usize>,
+    precomputed: bool,
+    weights: Option<Vec<f64>>,
+) -> PyResult<Vec<f64>> {
+    let estimator = density_estimator(kernel, bandwidth, k)?;
+    let dist_matrix = if precomputed {
+        precomputed_distance_matrix(data)?
+    } else {
+        points_distance_matrix(&data.extract::<Vec<Vec<f64>>>()?)?
+    };
+    match weights {
+        Some(weights) if weights.len() != dist_matrix.len() => Err(PyValueError::new_err(format!(
+            "There are {} weights for {} points",
+            weights.len(),
+            dist_matrix.len()
+        ))),
+        Some(weights) => Ok(py.allow_threads(|| estimator.estimate_weighted(&dist_matrix, &weights))),
+        None => Ok(py.allow_threads(|| estimator.estimate(&dist_matrix))),
+    }
+}
+
+/// The dataset of the given name, with the same names and default parameters as the experiment
+/// runner.
+fn named_dataset(name: &str, n_points: Option<usize>) -> PyResult<Dataset> {
+    let dataset = match name {
+        "senate" => Dataset::Senate,
+        "eleg" => Dataset::Eleg,
+        "netwsc" => Dataset::Netwsc,
+        "hiv" => Dataset::Hiv,
+        "dragon" => Dataset::Dragon,
+        "uniform" => Dataset::Uniform { n_points: n_points.unwrap_or(400) },
+        "sphere" => Dataset::Sphere { n_points: n_points.unwrap_or(100) },
+        "circle" => Dataset::Circle { n_points: n_points.unwrap_or(100) },
+        "torus" => Dataset::Torus { n_points: n_points.unwrap_or(200) },
+        "swiss-roll" => Dataset::SwissRoll { n_points: n_points.unwrap_or(200) },
+        "rgg" => Dataset::RandomGeometricGraph {
+            n_points: n_points.unwrap_or(400),
+            radius: 0.1,
+            weight_exponent: None,
+        },
+        "girg" => Dataset::RandomGeometricGraph {
+            n_points: n_points.unwrap_or(400),
+            radius: 0.05,
+            weight_exponent: Some(2.5),
+        },
+        "gaussian-mixture" => Dataset::GaussianMixture {
+            n_points: n_points.unwrap_or(400),
+            n_clusters: 4,
+            noise: 0.1,
+        },
+        "annulus" => Dataset::Annulus { n_points: n_points.unwrap_or(200), noise: 0.05 },
+        "figure-eight" => Dataset::FigureEight { n_points: n_points.unwrap_or(200), noise: 0.05 },
+        _ => return Err(PyValueError::new_err(format!("Unknown dataset {}", name))),
+    };
+    if n_points.is_some() && !name_is_sampled(name) {
+        return Err(PyValueError::new_err(format!("The {} dataset is not sampled, and takes no n_points", name)));
+    }
+    Ok(dataset)
+}
+
+fn name_is_sampled(name: &str) -> bool {
+    !matches!(name, "senate" | "eleg" | "netwsc" | "hiv" | "dragon")
+}
+
+/// Returns the edge list of a dataset of the paper, or of one of the samplers, bifiltered by
+/// codensity and length, as a NumPy structured array with fields `u`, `v`, `codensity` and
+/// `distance`.
+///
+/// Edges longer than `threshold` are removed, or, if `percentile` is true, the edges longer than
+/// the `threshold` percentile, from 0 to 1, of the distances. The density is estimated with the
+/// Gaussian kernel with the given `bandwidth`, which by default is the 20th percentile of the
+/// distances. Sampled datasets have `n_points` points, sampled reproducibly if a `seed` is given.
+/// The built-in datasets are read from, and the sampled ones cached in, `directory`.
+#[pyfunction(
+    n_points = "None",
+    threshold = "None",
+    percentile = "false",
+    bandwidth = "None",
+    seed = "None",
+    use_cache = "false",
+    directory = "\"datasets\".to_string()",
+)]
+#[pyo3(
+    text_signature = "(name, n_points=None, threshold=None, percentile=False, bandwidth=None, seed=None, use_cache=False, directory=\"datasets\")"
+)]
+#[allow(clippy::too_many_arguments)]
+fn dataset_density_edge_list<'py>(
+    py: Python<'py>,
+    name: &str,
+    n_points: Option<usize>,
+    threshold: Option<f64>,
+    percentile: bool,
+    bandwidth: Option<f64>,
+    seed: Option<u64>,
+    use_cache: bool,
+    directory: String,
+) -> PyResult<&'py PyAny> {
+    let dataset = named_dataset(name, n_points)?;
+    let threshold = match threshold {
+        None => Threshold::KeepAll,
+        Some(t) if percentile => Threshold::Percentile(t),
+        Some(t) => Threshold::Fixed(t),
+    };
+    let estimator = bandwidth.map(|b| DensityEstimator::Gaussian(b.into()));
+    let edge_list = py
+        .allow_threads(|| {
+            let mut rng = match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            datasets::get_dataset_density_edge_list_in_directory(
+                directory, dataset, threshold, estimator, use_cache, &mut rng,
+            )
+        })
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+    let rows = PyList::empty(py);
+    for e in edge_list.edge_iter() {
+        rows.append((e.edge.0, e.edge.1, e.grade.0[0].0, e.grade.0[1].0))?;
+    }
+    let dtype = vec![("u", "u8"), ("v", "u8"), ("codensity", "f8"), ("distance", "f8")];
+    py.import("numpy")?.call_method1("array", (rows, dtype))
+}
+
 #[pymodule]
 fn filtration_domination(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     let utils = PyModule::new(_py, "utils")?;
     utils.add_function(wrap_pyfunction!(gaussian_density_estimation, m)?)?;
+    utils.add_function(wrap_pyfunction!(density_estimation, m)?)?;
     m.add_submodule(utils)?;
 
+    let datasets = PyModule::new(_py, "datasets")?;
+    datasets.add_function(wrap_pyfunction!(dataset_density_edge_list, m)?)?;
+    m.add_submodule(datasets)?;
+
     m.add_function(wrap_pyfunction!(remove_strongly_filtration_dominated, m)?)?;
     m.add_function(wrap_pyfunction!(remove_filtration_dominated, m)?)?;
+    m.add_function(wrap_pyfunction!(remove_strongly_filtration_dominated_iterations, m)?)?;
+    m.add_function(wrap_pyfunction!(collapse_along_lines, m)?)?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,346 @@
+//! A long-running service that exposes the algorithms of the crate through JSON-RPC 2.0 over
+//! the standard input and output, so that they can be used from any language that can spawn a
+//! process and exchange JSON messages with it.
+//!
+//! Each line of the standard input must contain a single request, and each response is written in
+//! a single line of the standard output. Requests without an `id` are notifications, and are not
+//! answered. The supported methods are:
+//! - `remove_strongly_filtration_dominated` and `remove_filtration_dominated`, with parameters
+//!   `{"edge_list": ..., "order": "ReverseLexicographic"}`, return the reduced edge list.
+//!   The `order` is optional, and defaults to `ReverseLexicographic`.
+//! - `estimate_density`, with parameters `{"distance_matrix": [[], [1.0], ...], "estimator": {"Gaussian": 0.5}}`,
+//!   returns the estimated density at each point. The `i`-th row of the distance matrix must
+//!   contain, at least, the distances from the `i`-th point to the points `0..i`.
+//! - `write_scc2020`, with parameters `{"edge_list": ..., "homology": 1}`, returns the scc2020
+//!   representation of the clique bifiltration of the edge list, as a string.
+//! - `minimal_presentation`, with parameters `{"edge_list": ..., "homology": 1, "name": "..."}`,
+//!   calls mpfree and returns the summary of the minimal presentation. The `name` is optional,
+//!   and is used to name the temporary files.
+//!
+//! Edge lists are objects of the form
+//! `{"n_vertices": 3, "edges": [{"grade": [0.0, 1.5], "edge": [0, 1]}, ...]}`.
+use std::io;
+use std::io::{BufRead, Write};
+use std::panic::AssertUnwindSafe;
+
+use ordered_float::OrderedFloat;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use filtration_domination::distance_matrix::density_estimation::DensityEstimator;
+use filtration_domination::distance_matrix::DistanceMatrix;
+use filtration_domination::edges::{EdgeList, FilteredEdge};
+use filtration_domination::filtration::validate_flag_edges;
+use filtration_domination::mpfree::{compute_minimal_presentation, write_flag_scc2020};
+use filtration_domination::removal::{
+    remove_filtration_dominated, remove_strongly_filtration_dominated, EdgeOrder,
+};
+use filtration_domination::OneCriticalGrade;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+type BifilteredEdgeList = EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>;
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(&line) {
+            writeln!(out, "{}", response)?;
+            out.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// A JSON-RPC error object.
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl std::fmt::Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct RemovalParams {
+    edge_list: BifilteredEdgeList,
+    #[serde(default = "default_order")]
+    order: EdgeOrder,
+}
+
+fn default_order() -> EdgeOrder {
+    EdgeOrder::ReverseLexicographic
+}
+
+#[derive(Deserialize)]
+struct DensityParams {
+    distance_matrix: Vec<Vec<f64>>,
+    estimator: DensityEstimator<f64>,
+}
+
+#[derive(Deserialize)]
+struct FiltrationParams {
+    edge_list: BifilteredEdgeList,
+    homology: usize,
+}
+
+#[derive(Deserialize)]
+struct MinimalPresentationParams {
+    edge_list: BifilteredEdgeList,
+    homology: usize,
+    #[serde(default = "default_name")]
+    name: String,
+}
+
+fn default_name() -> String {
+    String::from("service")
+}
+
+/// Process a line of input, and return the response, if the request is not a notification.
+fn handle_line(line: &str) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, err))),
+    };
+    let request: Request = match serde_json::from_value(request) {
+        Ok(request) => request,
+        Err(err) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(INVALID_REQUEST, err),
+            ))
+        }
+    };
+    if request.jsonrpc != "2.0" {
+        let err = RpcError::new(INVALID_REQUEST, "Only JSON-RPC 2.0 is supported");
+        return Some(error_response(request.id.unwrap_or(Value::Null), err));
+    }
+
+    // A panic in an algorithm should not bring down the whole service.
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        dispatch(&request.method, request.params)
+    }))
+    .unwrap_or_else(|_| Err(RpcError::new(INTERNAL_ERROR, "The computation panicked")));
+
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(err) => error_response(id, err),
+    })
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": err.code, "message": err.message},
+    })
+}
+
+fn dispatch(method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "remove_strongly_filtration_dominated" => {
+            let mut params: RemovalParams = parse_params(params)?;
+            check_edge_list(&params.edge_list)?;
+            let reduced = remove_strongly_filtration_dominated(&mut params.edge_list, params.order);
+            to_result(&reduced)
+        }
+        "remove_filtration_dominated" => {
+            let mut params: RemovalParams = parse_params(params)?;
+            check_edge_list(&params.edge_list)?;
+            let reduced = remove_filtration_dominated(&mut params.edge_list, params.order);
+            to_result(&reduced)
+        }
+        "estimate_density" => {
+            let params: DensityParams = parse_params(params)?;
+            let distance_matrix = distance_matrix_from_rows(&params.distance_matrix)?;
+            to_result(&params.estimator.estimate(&distance_matrix))
+        }
+        "write_scc2020" => {
+            let params: FiltrationParams = parse_params(params)?;
+            check_flag_edge_list(&params.edge_list)?;
+            let mut buffer: Vec<u8> = Vec::new();
+            write_flag_scc2020(&params.edge_list, params.homology, &mut buffer)
+                .map_err(|err| RpcError::new(INTERNAL_ERROR, err))?;
+            let scc2020 =
+                String::from_utf8(buffer).map_err(|err| RpcError::new(INTERNAL_ERROR, err))?;
+            Ok(Value::String(scc2020))
+        }
+        "minimal_presentation" => {
+            let params: MinimalPresentationParams = parse_params(params)?;
+            check_flag_edge_list(&params.edge_list)?;
+            let valid_name = !params.name.is_empty()
+                && params
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid_name {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    "The name must only contain ASCII alphanumeric characters, '_' or '-'",
+                ));
+            }
+            let summary = compute_minimal_presentation::<OrderedFloat<f64>, _>(
+                &params.name,
+                params.homology,
+                &params.edge_list,
+            )
+            .map_err(|err| RpcError::new(INTERNAL_ERROR, err))?;
+            to_result(&summary)
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method: {}", method),
+        )),
+    }
+}
+
+fn parse_params<P: DeserializeOwned>(params: Value) -> Result<P, RpcError> {
+    serde_json::from_value(params).map_err(|err| RpcError::new(INVALID_PARAMS, err))
+}
+
+fn to_result<T: serde::Serialize>(result: &T) -> Result<Value, RpcError> {
+    serde_json::to_value(result).map_err(|err| RpcError::new(INTERNAL_ERROR, err))
+}
+
+/// Deserialized edge lists skip the checks of [EdgeList::add_edge], so we do them here.
+fn check_edge_list(edge_list: &BifilteredEdgeList) -> Result<(), RpcError> {
+    edge_list
+        .validate()
+        .map_err(|err| RpcError::new(INVALID_PARAMS, err))
+}
+
+/// As [check_edge_list], but also checks that the grades of the edges are nonnegative, so that
+/// the flag filtration of the edges can be built.
+fn check_flag_edge_list(edge_list: &BifilteredEdgeList) -> Result<(), RpcError> {
+    validate_flag_edges(edge_list).map_err(|err| RpcError::new(INVALID_PARAMS, err))
+}
+
+fn distance_matrix_from_rows(rows: &[Vec<f64>]) -> Result<DistanceMatrix<f64>, RpcError> {
+    let mut matrix = DistanceMatrix::new(rows.len());
+    for (u, row) in rows.iter().enumerate() {
+        if row.len() < u {
+            return Err(RpcError::new(
+                INVALID_PARAMS,
+                format!(
+                    "Row {} of the distance matrix has less than {} entries",
+                    u, u
+                ),
+            ));
+        }
+        for (v, &d) in row.iter().take(u).enumerate() {
+            matrix.set(u, v, d);
+        }
+    }
+    Ok(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{handle_line, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR};
+    use serde_json::{json, Value};
+
+    fn request(method: &str, params: Value) -> String {
+        json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}).to_string()
+    }
+
+    #[test]
+    fn service_removes_dominated_edges() {
+        // A triangle: the last edge is dominated.
+        let edge_list = json!({
+            "n_vertices": 3,
+            "edges": [
+                {"grade": [0.0, 0.0], "edge": [0, 1]},
+                {"grade": [0.0, 0.0], "edge": [1, 2]},
+                {"grade": [1.0, 1.0], "edge": [0, 2]},
+            ]
+        });
+        for method in [
+            "remove_strongly_filtration_dominated",
+            "remove_filtration_dominated",
+        ] {
+            let response =
+                handle_line(&request(method, json!({ "edge_list": edge_list }))).unwrap();
+            assert_eq!(response["id"], json!(1));
+            let edges = response["result"]["edges"].as_array().unwrap();
+            assert_eq!(edges.len(), 2);
+        }
+    }
+
+    #[test]
+    fn service_writes_scc2020() {
+        let edge_list = json!({
+            "n_vertices": 2,
+            "edges": [{"grade": [1.0, 2.0], "edge": [0, 1]}]
+        });
+        let response = handle_line(&request(
+            "write_scc2020",
+            json!({"edge_list": edge_list, "homology": 0}),
+        ))
+        .unwrap();
+        let scc2020 = response["result"].as_str().unwrap();
+        assert!(scc2020.starts_with("scc2020\n"));
+    }
+
+    #[test]
+    fn service_estimates_density() {
+        let params = json!({
+            "distance_matrix": [[], [1.0], [1.0, 1.0]],
+            "estimator": {"Ball": 2.0}
+        });
+        let response = handle_line(&request("estimate_density", params)).unwrap();
+        let densities = response["result"].as_array().unwrap();
+        assert_eq!(densities.len(), 3);
+    }
+
+    #[test]
+    fn service_reports_errors() {
+        let response = handle_line("{").unwrap();
+        assert_eq!(response["error"]["code"], json!(PARSE_ERROR));
+
+        let response = handle_line(&request("unknown", json!({}))).unwrap();
+        assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND));
+
+        let self_loop = json!({
+            "n_vertices": 2,
+            "edges": [{"grade": [0.0, 0.0], "edge": [1, 1]}]
+        });
+        let response = handle_line(&request(
+            "remove_filtration_dominated",
+            json!({ "edge_list": self_loop }),
+        ))
+        .unwrap();
+        assert_eq!(response["error"]["code"], json!(INVALID_PARAMS));
+
+        let notification = json!({"jsonrpc": "2.0", "method": "unknown"}).to_string();
+        assert!(handle_line(&notification).is_none());
+    }
+}
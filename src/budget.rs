@@ -0,0 +1,178 @@
+//! Wall-clock limits shared by the stages of a computation.
+//!
+//! A [TimeBudget] starts counting when it is created, and can be split into budgets for the
+//! stages of a computation, each capped on its own but never ending after the budget it was split
+//! from. The remaining time of a budget can be given as the `max_time` of the removal algorithms,
+//! like [remove_strongly_filtration_dominated_timed](crate::removal::remove_strongly_filtration_dominated_timed),
+//! and as the timeout of an external tool, see [ToolOptions::timeout](crate::external::ToolOptions::timeout),
+//! and a budget can be passed as the check of
+//! [build_flag_filtration_with_check](crate::filtration::build_flag_filtration_with_check)
+//! through [TimeBudget::check_fn].
+//!
+//! ```
+//! use std::time::Duration;
+//! use filtration_domination::budget::TimeBudget;
+//!
+//! let total = TimeBudget::new(Duration::from_secs(60));
+//! // The removal gets at most half of the total, and the rest of the stages what is left.
+//! let removal = total.fraction(0.5);
+//! assert!(removal.remaining() <= total.remaining());
+//! assert!(!total.is_exhausted());
+//! ```
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+/// A wall-clock time limit that started when it was created. See the
+/// [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeBudget {
+    start: Instant,
+    /// When the budget runs out, or None if it does not.
+    deadline: Option<Instant>,
+}
+
+impl TimeBudget {
+    /// A budget of the given time, starting now.
+    pub fn new(limit: Duration) -> Self {
+        let start = Instant::now();
+        Self {
+            start,
+            deadline: start.checked_add(limit),
+        }
+    }
+
+    /// A budget that never runs out.
+    pub fn unlimited() -> Self {
+        Self {
+            start: Instant::now(),
+            deadline: None,
+        }
+    }
+
+    /// The time since the budget was created.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// The time left before the budget runs out, which is zero once it has run out, or None if
+    /// the budget is unlimited.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Whether the budget has run out.
+    pub fn is_exhausted(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => Instant::now() >= deadline,
+            None => false,
+        }
+    }
+
+    /// A budget for a stage, starting now, that runs out after the given time, or when this
+    /// budget runs out, whatever happens first.
+    pub fn stage(&self, limit: Duration) -> Self {
+        let stage = Self::new(limit);
+        Self {
+            start: stage.start,
+            deadline: earliest(stage.deadline, self.deadline),
+        }
+    }
+
+    /// A budget for a stage, starting now, that gets the given fraction, between 0 and 1, of the
+    /// time left in this budget. If this budget is unlimited, so is the stage.
+    ///
+    /// Since the fraction is of the time left when the stage starts, the time that a stage does
+    /// not use is left for the next ones.
+    pub fn fraction(&self, fraction: f64) -> Self {
+        match self.remaining() {
+            Some(remaining) => self.stage(remaining.mul_f64(fraction.clamp(0., 1.))),
+            None => Self::unlimited(),
+        }
+    }
+
+    /// The smaller of the given maximum time of a computation and the time left in this budget,
+    /// or None if both are None. Use it as the `max_time` of the removal algorithms, so that they
+    /// respect both their own limit and this budget.
+    pub fn cap(&self, max_time: Option<Duration>) -> Option<Duration> {
+        match (max_time, self.remaining()) {
+            (Some(max_time), Some(remaining)) => Some(max_time.min(remaining)),
+            (max_time, remaining) => max_time.or(remaining),
+        }
+    }
+
+    /// Returns an error if the budget has run out.
+    pub fn check(&self) -> Result<(), TimeBudgetExceeded> {
+        if self.is_exhausted() {
+            Err(TimeBudgetExceeded {
+                elapsed: self.elapsed(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// A check, for [build_flag_filtration_with_check](crate::filtration::build_flag_filtration_with_check)
+    /// and the like, that fails once the budget has run out.
+    pub fn check_fn(self) -> impl Fn(usize) -> Result<(), TimeBudgetExceeded> {
+        move |_iteration| self.check()
+    }
+}
+
+impl Default for TimeBudget {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+fn earliest(a: Option<Instant>, b: Option<Instant>) -> Option<Instant> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// The error of [TimeBudget::check] once the budget has run out.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("The time budget ran out after {elapsed:?}")]
+pub struct TimeBudgetExceeded {
+    /// The time since the budget was created.
+    pub elapsed: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::budget::TimeBudget;
+
+    #[test]
+    fn stages_end_with_their_budget() {
+        let total = TimeBudget::new(Duration::from_secs(3600));
+        let short = total.stage(Duration::from_secs(60));
+        assert!(short.remaining().unwrap() <= Duration::from_secs(60));
+        let long = total.stage(Duration::from_secs(7200));
+        assert!(long.remaining().unwrap() <= Duration::from_secs(3600));
+        let half = total.fraction(0.5);
+        assert!(half.remaining().unwrap() <= Duration::from_secs(1800));
+        assert_eq!(total.check(), Ok(()));
+        assert_eq!(
+            total.cap(Some(Duration::from_secs(1))),
+            Some(Duration::from_secs(1))
+        );
+        assert!(total.cap(None).unwrap() <= Duration::from_secs(3600));
+
+        let exhausted = total.stage(Duration::ZERO);
+        assert!(exhausted.is_exhausted());
+        assert_eq!(exhausted.remaining(), Some(Duration::ZERO));
+        assert!(exhausted.check_fn()(0).is_err());
+        assert!(!total.is_exhausted());
+
+        let unlimited = TimeBudget::unlimited();
+        assert_eq!(unlimited.remaining(), None);
+        assert_eq!(unlimited.fraction(0.1).remaining(), None);
+        assert_eq!(unlimited.cap(None), None);
+        assert!(unlimited.stage(Duration::ZERO).is_exhausted());
+    }
+}
@@ -1,9 +1,21 @@
+//! Chain complexes of free persistence modules, represented as graded matrices with Z2
+//! coefficients. See [ChainComplex].
+use rustc_hash::FxHashSet;
 use std::io;
+use std::io::BufRead;
+use std::str::FromStr;
+use thiserror::Error;
 
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::filtration::{build_flag_filtration, Filtration};
+use crate::io_utils::{invalid_data, parse, parse_next};
+use crate::simplicial_complex::{
+    MapSimplicialComplex, SimplexKey, SimplicialComplex, WideMapSimplicialComplex,
+};
 use crate::{CriticalGrade, OneCriticalGrade, Value};
 
 /// A column with coefficients in Z2.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Column {
     /// Position of the non-zero entries of the column.
     non_zeros: Vec<usize>,
@@ -17,6 +29,21 @@ impl Column {
     pub fn new(non_zeros: Vec<usize>) -> Self {
         Self { non_zeros }
     }
+
+    /// Sorts the entries of the column and removes the repeated ones in pairs, since they cancel
+    /// out with Z2 coefficients.
+    fn normalize(&mut self) {
+        self.non_zeros.sort_unstable();
+        let mut normalized: Vec<usize> = Vec::with_capacity(self.non_zeros.len());
+        for &row in self.non_zeros.iter() {
+            if normalized.last() == Some(&row) {
+                normalized.pop();
+            } else {
+                normalized.push(row);
+            }
+        }
+        self.non_zeros = normalized;
+    }
 }
 
 impl<const N: usize> From<[usize; N]> for Column {
@@ -25,8 +52,58 @@ impl<const N: usize> From<[usize; N]> for Column {
     }
 }
 
+/// Rank, over Z2, of the matrix with the given columns, computed by column reduction.
+fn rank<'a, I: Iterator<Item = &'a Column>>(columns: I) -> usize {
+    // For each row, the reduced column whose lowest non-zero entry is in that row.
+    let mut pivots: Vec<Option<Vec<usize>>> = Vec::new();
+    let mut rank = 0;
+    for column in columns {
+        let mut reduced = column.non_zeros.clone();
+        reduced.sort_unstable();
+        while let Some(&low) = reduced.last() {
+            if low >= pivots.len() {
+                pivots.resize(low + 1, None);
+            }
+            match &pivots[low] {
+                Some(pivot) => reduced = symmetric_difference(&reduced, pivot),
+                None => break,
+            }
+        }
+        if let Some(&low) = reduced.last() {
+            pivots[low] = Some(reduced);
+            rank += 1;
+        }
+    }
+    rank
+}
+
+/// Sum of two sorted columns with Z2 coefficients.
+fn symmetric_difference(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut result = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => {
+                result.push(a[i]);
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                result.push(b[j]);
+                j += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    result.extend_from_slice(&a[i..]);
+    result.extend_from_slice(&b[j..]);
+    result
+}
+
 /// A column matrix with coefficients in Z2.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct ColumnMatrix {
     columns: Vec<Column>,
 }
@@ -59,7 +136,7 @@ impl<const N: usize, const M: usize> From<[[usize; N]; M]> for ColumnMatrix {
 
 /// A column matrix with a graded associated to each column.
 /// The matrix has Z2 coefficients.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct GradedMatrix<VF: Value, const N: usize> {
     grades: Vec<OneCriticalGrade<VF, N>>,
     matrix: ColumnMatrix,
@@ -73,13 +150,30 @@ impl<VF: Value, const N: usize> GradedMatrix<VF, N> {
         )
     }
 
+    /// A matrix whose columns have the given grades.
+    ///
+    /// Panics: if there is not one grade per column. See [GradedMatrix::try_new] for a
+    /// non-panicking version.
     pub fn new(matrix: ColumnMatrix, grades: Vec<OneCriticalGrade<VF, N>>) -> Self {
-        assert_eq!(
-            matrix.n_cols(),
-            grades.len(),
-            "The number of grades must be equal to the number of columns."
-        );
-        Self { grades, matrix }
+        match Self::try_new(matrix, grades) {
+            Ok(graded_matrix) => graded_matrix,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// A matrix whose columns have the given grades, or an error if there is not one grade per
+    /// column.
+    pub fn try_new(
+        matrix: ColumnMatrix,
+        grades: Vec<OneCriticalGrade<VF, N>>,
+    ) -> Result<Self, ChainComplexError> {
+        if matrix.n_cols() != grades.len() {
+            return Err(ChainComplexError::GradeCountMismatch {
+                columns: matrix.n_cols(),
+                grades: grades.len(),
+            });
+        }
+        Ok(Self { grades, matrix })
     }
 
     pub fn add_column(&mut self, grade: OneCriticalGrade<VF, N>, column: Column) {
@@ -91,15 +185,40 @@ impl<VF: Value, const N: usize> GradedMatrix<VF, N> {
         self.matrix.n_cols()
     }
 
+    /// Rank, over Z2, of the submatrix formed by the columns whose grade is less than or equal
+    /// to the given one.
+    fn rank_at(&self, grade: &OneCriticalGrade<VF, N>) -> usize {
+        let columns = self
+            .iter()
+            .filter(|(g, _)| g.lte(grade))
+            .map(|(_, column)| column);
+        rank(columns)
+    }
+
     fn iter(&self) -> impl Iterator<Item = (&OneCriticalGrade<VF, N>, &Column)> {
         let column_iter = self.matrix.columns.iter();
         let grades_iter = self.grades.iter();
         Iterator::zip(grades_iter, column_iter)
     }
+
+    /// Keeps only the columns for which `keep` returns true, given their index, grade and column.
+    fn retain_columns<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(usize, &OneCriticalGrade<VF, N>, &Column) -> bool,
+    {
+        let columns = std::mem::take(&mut self.matrix.columns);
+        let grades = std::mem::take(&mut self.grades);
+        for (idx, (grade, column)) in grades.into_iter().zip(columns).enumerate() {
+            if keep(idx, &grade, &column) {
+                self.add_column(grade, column);
+            }
+        }
+    }
 }
 
 /// A chain complex, a sequence of graded matrices representing free persistence modules.
-#[derive(Debug)]
+/// The rows of each matrix are the columns of the next one.
+#[derive(Debug, PartialEq, Eq)]
 pub struct ChainComplex<VF: Value, const N: usize> {
     matrices: Vec<GradedMatrix<VF, N>>,
 }
@@ -108,9 +227,271 @@ impl<VF: Value, const N: usize> ChainComplex<VF, N> {
     pub fn new(matrices: Vec<GradedMatrix<VF, N>>) -> Self {
         Self { matrices }
     }
+
+    /// Returns the chain complex, of the flag bifiltration of the given edges, that computes
+    /// homology of the given dimension. It has three matrices: the boundary matrices of the
+    /// simplices of dimension `homology + 1`, `homology`, and `homology - 1`.
+    pub fn from_flag_filtration(
+        edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+        homology: usize,
+    ) -> Self {
+        if usize::fits(edge_list.n_vertices, homology + 1) {
+            Self::flag_filtration_with_complex::<MapSimplicialComplex>(edge_list, homology)
+        } else {
+            Self::flag_filtration_with_complex::<WideMapSimplicialComplex>(edge_list, homology)
+        }
+    }
+
+    /// As [ChainComplex::from_flag_filtration], but the flag filtration is built on the
+    /// simplicial complex implementation `S`. Returns [ChainComplexError::TooManyVertices] if the
+    /// keys of `S` do not fit the simplices of dimension `homology + 1`, see [SimplexKey::fits].
+    pub fn from_flag_filtration_with_complex<S>(
+        edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+        homology: usize,
+    ) -> Result<Self, ChainComplexError>
+    where
+        S: for<'a> SimplicialComplex<'a>,
+    {
+        let max_dim = homology + 1;
+        if !S::fits(edge_list.n_vertices, max_dim) {
+            return Err(ChainComplexError::TooManyVertices {
+                n_vertices: edge_list.n_vertices,
+                max_dim,
+            });
+        }
+        Ok(Self::flag_filtration_with_complex::<S>(edge_list, homology))
+    }
+
+    fn flag_filtration_with_complex<S>(
+        edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+        homology: usize,
+    ) -> Self
+    where
+        S: for<'a> SimplicialComplex<'a>,
+    {
+        let filtration: Filtration<_, S> = build_flag_filtration(
+            edge_list.n_vertices,
+            homology + 1,
+            edge_list.edge_iter().cloned(),
+        );
+        filtration.to_free_implicit_representation(homology)
+    }
+
+    /// Removes redundant columns, without changing the homology at the second matrix, which is
+    /// the one computed by mpfree and by [ChainComplex::hilbert_function]:
+    /// - entries repeated in a column cancel out, since the coefficients are in Z2,
+    /// - empty columns of the first matrix are dropped, since they bound nothing,
+    /// - columns of the first matrix equal to a previous one with the same grade are dropped,
+    /// - columns of the third matrix that are not an entry of any column of the second one are
+    ///   dropped, and the entries of the second matrix are reindexed accordingly.
+    ///
+    /// Chain complexes built from flag filtrations only have such columns if the edge list has
+    /// repeated edges or isolated vertices, but complexes read from other sources might.
+    pub fn compress(&mut self) {
+        for graded_matrix in self.matrices.iter_mut() {
+            for column in graded_matrix.matrix.columns.iter_mut() {
+                column.normalize();
+            }
+        }
+
+        if let Some(first) = self.matrices.first_mut() {
+            let mut seen: FxHashSet<(OneCriticalGrade<VF, N>, Vec<usize>)> = FxHashSet::default();
+            first.retain_columns(|_idx, grade, column| {
+                !column.non_zeros.is_empty() && seen.insert((*grade, column.non_zeros.clone()))
+            });
+        }
+
+        if self.matrices.len() >= 3 {
+            let n_rows = self.matrices[2].n_cols();
+            let mut new_index = vec![None; n_rows];
+            for column in self.matrices[1].matrix.columns.iter() {
+                for &row in column.non_zeros.iter() {
+                    if let Some(index) = new_index.get_mut(row) {
+                        *index = Some(0);
+                    }
+                }
+            }
+            for (n_kept, index) in new_index.iter_mut().flatten().enumerate() {
+                *index = n_kept;
+            }
+            self.matrices[2].retain_columns(|idx, _grade, _column| new_index[idx].is_some());
+            for column in self.matrices[1].matrix.columns.iter_mut() {
+                for row in column.non_zeros.iter_mut() {
+                    if let Some(Some(index)) = new_index.get(*row) {
+                        *row = *index;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the Hilbert function of the homology of the chain complex at each of the given
+    /// grades. The homology is taken at the columns of the second matrix: it is the kernel of the
+    /// second matrix modulo the image of the first one.
+    ///
+    /// Returns an error if, at some grade, the ranks of the two matrices add up to more than the
+    /// number of generators, which cannot happen if the chain complex is well-formed and the
+    /// product of consecutive matrices is zero.
+    ///
+    /// Panics: if the chain complex has less than two matrices.
+    pub fn hilbert_function(
+        &self,
+        grades: &[OneCriticalGrade<VF, N>],
+    ) -> Result<Vec<usize>, ChainComplexError> {
+        assert!(
+            self.matrices.len() >= 2,
+            "Computing homology requires at least two matrices."
+        );
+        let high = &self.matrices[0];
+        let mid = &self.matrices[1];
+        grades
+            .iter()
+            .enumerate()
+            .map(|(idx, grade)| {
+                let generators = mid.grades.iter().filter(|g| g.lte(grade)).count();
+                let ranks = mid.rank_at(grade) + high.rank_at(grade);
+                generators
+                    .checked_sub(ranks)
+                    .ok_or(ChainComplexError::InconsistentRanks {
+                        grade: idx,
+                        generators,
+                        ranks,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// A chain complex that is not well-formed, see [ChainComplex::validate], a graded matrix
+/// without one grade per column, see [GradedMatrix::try_new], a chain complex whose homology
+/// is not defined, see [ChainComplex::hilbert_function], or a flag filtration too large for the
+/// chosen simplicial complex, see [ChainComplex::from_flag_filtration_with_complex].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainComplexError {
+    #[error("Column {column} of matrix {matrix} has an entry in row {row}, but the next matrix has {n_rows} columns")]
+    RowOutOfRange {
+        matrix: usize,
+        column: usize,
+        row: usize,
+        n_rows: usize,
+    },
+
+    #[error("Column {column} of matrix {matrix} has an entry in row {row}, whose grade is not less than or equal to the grade of the column")]
+    GradeNotMonotone {
+        matrix: usize,
+        column: usize,
+        row: usize,
+    },
+
+    #[error("A matrix with {columns} columns cannot have {grades} grades")]
+    GradeCountMismatch { columns: usize, grades: usize },
+
+    #[error("At grade {grade}, the ranks of the matrices add up to {ranks}, more than the {generators} generators")]
+    InconsistentRanks {
+        grade: usize,
+        generators: usize,
+        ranks: usize,
+    },
+
+    #[error(
+        "The simplices of dimension up to {max_dim} on {n_vertices} vertices do not fit in the keys of the simplicial complex"
+    )]
+    TooManyVertices { n_vertices: usize, max_dim: usize },
 }
 
 impl<VF: Value, const N: usize> ChainComplex<VF, N> {
+    /// Checks that the chain complex is well-formed: the entries of the columns of each matrix
+    /// must be rows of the matrix, that is, columns of the next matrix, and the grade of each
+    /// column must be greater than or equal to the grades of its entries.
+    ///
+    /// The columns of the last matrix are not checked, since the scc2020 format does not store
+    /// them.
+    pub fn validate(&self) -> Result<(), ChainComplexError> {
+        for (matrix, pair) in self.matrices.windows(2).enumerate() {
+            let (graded_matrix, next) = (&pair[0], &pair[1]);
+            for (column, (grade, col)) in graded_matrix.iter().enumerate() {
+                for &row in col.non_zeros.iter() {
+                    if row >= next.n_cols() {
+                        return Err(ChainComplexError::RowOutOfRange {
+                            matrix,
+                            column,
+                            row,
+                            n_rows: next.n_cols(),
+                        });
+                    }
+                    if !next.grades[row].lte(grade) {
+                        return Err(ChainComplexError::GradeNotMonotone {
+                            matrix,
+                            column,
+                            row,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a chain complex in the scc2020 format, like those written by
+    /// [ChainComplex::write_scc2020] or by mpfree. Lines starting with `#` and empty lines are
+    /// ignored.
+    ///
+    /// As the format does not describe the generators of the last matrix, its columns are empty
+    /// and have the minimum grade. Returns an error of kind [io::ErrorKind::InvalidData] if the
+    /// file does not follow the format, has a number of parameters different from `N`, or
+    /// describes a chain complex that does not pass [ChainComplex::validate].
+    pub fn read_scc2020<R: BufRead>(r: R) -> io::Result<Self>
+    where
+        VF: FromStr,
+        <VF as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let mut lines = r.lines().filter(|line| match line {
+            Ok(line) => {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#')
+            }
+            Err(_) => true,
+        });
+        let mut next_line = || -> io::Result<String> {
+            lines
+                .next()
+                .unwrap_or_else(|| Err(invalid_data("The scc2020 file ended unexpectedly")))
+        };
+
+        if next_line()?.trim() != "scc2020" {
+            return Err(invalid_data("The scc2020 header is missing"));
+        }
+        let parameters: usize = parse(next_line()?.trim())?;
+        if parameters != N {
+            return Err(invalid_data(format!(
+                "Expected {} parameters, but the scc2020 file has {}",
+                N, parameters
+            )));
+        }
+        let sizes: Vec<usize> = next_line()?
+            .split_whitespace()
+            .map(parse)
+            .collect::<io::Result<_>>()?;
+        let (&last_size, described_sizes) = sizes
+            .split_last()
+            .ok_or_else(|| invalid_data("The scc2020 file has no matrices"))?;
+
+        let mut matrices = Vec::with_capacity(sizes.len());
+        for &size in described_sizes {
+            let mut matrix = GradedMatrix::new_empty(0);
+            for _ in 0..size {
+                let (grade, column) = parse_scc2020_column(&next_line()?)?;
+                matrix.add_column(grade, column);
+            }
+            matrices.push(matrix);
+        }
+        matrices.push(GradedMatrix::new_empty(last_size));
+
+        let chain_complex = Self::new(matrices);
+        chain_complex.validate().map_err(invalid_data)?;
+        Ok(chain_complex)
+    }
+
     pub fn write_scc2020<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
         writeln!(w, "scc2020")?;
         writeln!(w, "{}", N)?;
@@ -129,16 +510,7 @@ impl<VF: Value, const N: usize> ChainComplex<VF, N> {
                 continue;
             }
             for (grade, column) in graded_matrix.iter() {
-                for v in grade.iter() {
-                    write!(w, "{} ", v)?;
-                }
-
-                write!(w, ";")?;
-
-                for c in column.non_zeros.iter() {
-                    write!(w, " {}", c)?;
-                }
-                writeln!(w)?;
+                write_scc2020_column(w, grade, column.non_zeros.iter().copied())?;
             }
             if idx_matrix != self.matrices.len() - 2 {
                 writeln!(w)?;
@@ -149,6 +521,110 @@ impl<VF: Value, const N: usize> ChainComplex<VF, N> {
     }
 }
 
+impl<VF: Value> ChainComplex<VF, 2> {
+    /// Writes the chain complex in the firep format of RIVET. The chain complex must have three
+    /// matrices, like those returned by [ChainComplex::from_flag_filtration]. Otherwise, an error
+    /// of kind [io::ErrorKind::InvalidInput] is returned.
+    pub fn write_firep<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        if self.matrices.len() != 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The firep format requires a chain complex with three matrices",
+            ));
+        }
+        writeln!(w, "--datatype firep")?;
+        writeln!(
+            w,
+            "{} {} {}",
+            self.matrices[0].n_cols(),
+            self.matrices[1].n_cols(),
+            self.matrices[2].n_cols()
+        )?;
+        for graded_matrix in self.matrices[..2].iter() {
+            for (grade, column) in graded_matrix.iter() {
+                write_scc2020_column(w, grade, column.non_zeros.iter().copied())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the chain complex in the given format.
+    pub fn write_format<W: io::Write>(
+        &self,
+        format: PresentationFormat,
+        w: &mut W,
+    ) -> io::Result<()> {
+        match format {
+            PresentationFormat::Scc2020 => self.write_scc2020(w),
+            PresentationFormat::Firep => self.write_firep(w),
+        }
+    }
+}
+
+/// Text formats of free implicit representations, that is, of chain complexes of free modules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PresentationFormat {
+    /// The scc2020 format, read by mpfree and other tools.
+    #[default]
+    Scc2020,
+    /// The firep format of RIVET, only for two parameters.
+    Firep,
+}
+
+/// Writes a line of a scc2020 file, that is, a column and its grade.
+/// The firep format uses the same lines.
+pub(crate) fn write_scc2020_column<
+    VF: Value,
+    W: io::Write,
+    I: Iterator<Item = usize>,
+    const N: usize,
+>(
+    w: &mut W,
+    grade: &OneCriticalGrade<VF, N>,
+    non_zeros: I,
+) -> io::Result<()> {
+    for v in grade.iter() {
+        write!(w, "{} ", v)?;
+    }
+
+    write!(w, ";")?;
+
+    for c in non_zeros {
+        write!(w, " {}", c)?;
+    }
+    writeln!(w)
+}
+
+/// Parses a line of a scc2020 file, that is, a column and its grade.
+fn parse_scc2020_column<VF: Value + FromStr, const N: usize>(
+    line: &str,
+) -> io::Result<(OneCriticalGrade<VF, N>, Column)>
+where
+    <VF as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    let (grade_part, column_part) = line
+        .split_once(';')
+        .ok_or_else(|| invalid_data("A column of the scc2020 file has no ';'"))?;
+
+    let mut grade_values = grade_part.split_whitespace();
+    let mut grade = OneCriticalGrade::min_value();
+    for value in grade.0.iter_mut() {
+        *value = parse_next(&mut grade_values)?;
+    }
+    if grade_values.next().is_some() {
+        return Err(invalid_data(
+            "A grade of the scc2020 file has more values than parameters",
+        ));
+    }
+
+    let non_zeros = column_part
+        .split_whitespace()
+        .map(parse)
+        .collect::<io::Result<_>>()?;
+    Ok((grade, Column::new(non_zeros)))
+}
+
 pub trait ToFreeImplicitRepresentation<VF: Value, const N: usize> {
     fn to_free_implicit_representation(&self, homology: usize) -> ChainComplex<VF, N>;
 
@@ -157,3 +633,268 @@ pub trait ToFreeImplicitRepresentation<VF: Value, const N: usize> {
         chain_complex.write_scc2020(w)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::chain_complex::{ChainComplex, ChainComplexError, Column};
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::removal::{remove_strongly_filtration_dominated, EdgeOrder};
+    use crate::simplicial_complex::{SortedSimplicialComplex, WideSortedSimplicialComplex};
+    use crate::OneCriticalGrade;
+
+    fn square_with_diagonal() -> EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> {
+        let mut edges: Vec<FilteredEdge<OneCriticalGrade<usize, 2>>> =
+            [(0, 1), (1, 2), (2, 3), (0, 3)]
+                .into_iter()
+                .map(|(u, v)| FilteredEdge {
+                    edge: BareEdge(u, v),
+                    grade: OneCriticalGrade([0, 0]),
+                })
+                .collect();
+        edges.push(FilteredEdge {
+            edge: BareEdge(0, 2),
+            grade: OneCriticalGrade([1, 1]),
+        });
+        edges.into()
+    }
+
+    #[test]
+    fn flag_filtration_with_sorted_complex() {
+        let mut edge_list = EdgeList::new(7);
+        for u in 0..7 {
+            for v in (u + 1)..7 {
+                if (u * v) % 5 != 3 {
+                    edge_list.add_edge(FilteredEdge {
+                        edge: BareEdge(u, v),
+                        grade: OneCriticalGrade([(u + 3 * v) % 4, (u * v) % 3]),
+                    });
+                }
+            }
+        }
+        for homology in 0..3 {
+            let expected = ChainComplex::from_flag_filtration(&edge_list, homology);
+            let sorted = ChainComplex::from_flag_filtration_with_complex::<SortedSimplicialComplex>(
+                &edge_list, homology,
+            );
+            let wide_sorted = ChainComplex::from_flag_filtration_with_complex::<
+                WideSortedSimplicialComplex,
+            >(&edge_list, homology);
+            assert_eq!(sorted.unwrap(), expected);
+            assert_eq!(wide_sorted.unwrap(), expected);
+        }
+
+        // The keys of 2^22 vertices overflow usize at dimension 2, but not u128.
+        let large: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = EdgeList::new(1 << 22);
+        assert_eq!(
+            ChainComplex::from_flag_filtration_with_complex::<SortedSimplicialComplex>(&large, 1),
+            Err(ChainComplexError::TooManyVertices {
+                n_vertices: 1 << 22,
+                max_dim: 2
+            })
+        );
+    }
+
+    #[test]
+    fn compress_removes_redundant_columns() {
+        let edges = square_with_diagonal();
+        let original = ChainComplex::from_flag_filtration(&edges, 1);
+        // Compressing a flag complex only sorts the entries of the columns.
+        let mut expected = ChainComplex::from_flag_filtration(&edges, 1);
+        expected.compress();
+        let grid = [OneCriticalGrade([0, 0]), OneCriticalGrade([1, 1])];
+        assert_eq!(
+            expected.hilbert_function(&grid).unwrap(),
+            original.hilbert_function(&grid).unwrap()
+        );
+        let mut compressed = ChainComplex::from_flag_filtration(&edges, 1);
+        compressed.compress();
+        assert_eq!(compressed, expected);
+
+        // A repeated triangle, an empty column, a column whose entries cancel, and a vertex
+        // that is not in any edge.
+        let first_triangle = compressed.matrices[0].matrix.columns[0].clone();
+        let first_grade = compressed.matrices[0].grades[0];
+        compressed.matrices[0].add_column(first_grade, first_triangle);
+        compressed.matrices[0].add_column(OneCriticalGrade([0, 0]), Column::new_empty());
+        compressed.matrices[0].add_column(OneCriticalGrade([2, 2]), Column::from([1, 1]));
+        compressed.matrices[2].add_column(OneCriticalGrade([0, 0]), Column::new_empty());
+        assert_ne!(compressed, expected);
+
+        compressed.compress();
+        assert_eq!(compressed, expected);
+    }
+
+    #[test]
+    fn hilbert_function_square() {
+        let edges = square_with_diagonal();
+        let h1 = ChainComplex::from_flag_filtration(&edges, 1);
+        assert_eq!(
+            h1.hilbert_function(&[
+                OneCriticalGrade([0, 0]),
+                OneCriticalGrade([1, 0]),
+                OneCriticalGrade([0, 1]),
+                OneCriticalGrade([1, 1]),
+            ]),
+            Ok(vec![1, 1, 1, 0])
+        );
+
+        let h0 = ChainComplex::from_flag_filtration(&edges, 0);
+        assert_eq!(
+            h0.hilbert_function(&[OneCriticalGrade([0, 0]), OneCriticalGrade([1, 1])]),
+            Ok(vec![1, 1])
+        );
+    }
+
+    #[test]
+    fn hilbert_function_of_inconsistent_complex() {
+        let edges = square_with_diagonal();
+        let mut h1 = ChainComplex::from_flag_filtration(&edges, 1);
+        // At grade (0, 0) the four edges of the square have a boundary of rank three, so two
+        // independent columns in the first matrix are one too many.
+        h1.matrices[0].add_column(OneCriticalGrade([0, 0]), Column::from([0]));
+        h1.matrices[0].add_column(OneCriticalGrade([0, 0]), Column::from([1]));
+        assert_eq!(
+            h1.hilbert_function(&[OneCriticalGrade([0, 0])]),
+            Err(ChainComplexError::InconsistentRanks {
+                grade: 0,
+                generators: 4,
+                ranks: 5
+            })
+        );
+    }
+
+    #[test]
+    fn hilbert_function_preserved_by_removal() {
+        let mut edges: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = EdgeList::new(8);
+        for u in 0..8 {
+            for v in (u + 1)..8 {
+                edges.add_edge(FilteredEdge {
+                    edge: BareEdge(u, v),
+                    grade: OneCriticalGrade([(u * 3 + v) % 4, (u + v * 5) % 7]),
+                });
+            }
+        }
+        let remaining =
+            remove_strongly_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+        assert!(remaining.len() < edges.len());
+
+        let mut grid = Vec::new();
+        for x in 0..4 {
+            for y in 0..7 {
+                grid.push(OneCriticalGrade([x, y]));
+            }
+        }
+        for homology in 0..2 {
+            let before = ChainComplex::from_flag_filtration(&edges, homology);
+            let after = ChainComplex::from_flag_filtration(&remaining, homology);
+            assert_eq!(
+                before.hilbert_function(&grid).unwrap(),
+                after.hilbert_function(&grid).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn scc2020_round_trip() {
+        let edges = square_with_diagonal();
+        let grades = [
+            OneCriticalGrade([0, 0]),
+            OneCriticalGrade([1, 0]),
+            OneCriticalGrade([1, 1]),
+        ];
+        for homology in 0..2 {
+            let chain_complex = ChainComplex::from_flag_filtration(&edges, homology);
+            let mut written = Vec::new();
+            chain_complex.write_scc2020(&mut written).unwrap();
+
+            let read: ChainComplex<usize, 2> =
+                ChainComplex::read_scc2020(BufReader::new(written.as_slice())).unwrap();
+            assert_eq!(read.matrices[..2], chain_complex.matrices[..2]);
+            assert_eq!(
+                read.matrices[2].n_cols(),
+                chain_complex.matrices[2].n_cols()
+            );
+            assert_eq!(
+                read.hilbert_function(&grades).unwrap(),
+                chain_complex.hilbert_function(&grades).unwrap()
+            );
+
+            let mut rewritten = Vec::new();
+            read.write_scc2020(&mut rewritten).unwrap();
+            assert_eq!(written, rewritten);
+        }
+    }
+
+    #[test]
+    fn read_scc2020_with_comments() {
+        let file =
+            "# A comment.\nscc2020\n2\n1 2 1\n\n1 1 ; 0 1\n\n# Another one.\n0 0 ; 0\n0 1 ;\n";
+        let read: ChainComplex<usize, 2> =
+            ChainComplex::read_scc2020(BufReader::new(file.as_bytes())).unwrap();
+        assert_eq!(read.matrices.len(), 3);
+        assert_eq!(read.matrices[0].grades, vec![OneCriticalGrade([1, 1])]);
+        assert_eq!(read.matrices[1].n_cols(), 2);
+    }
+
+    #[test]
+    fn read_invalid_scc2020() {
+        let read = |file: &str| {
+            ChainComplex::<usize, 2>::read_scc2020(BufReader::new(file.as_bytes()))
+                .unwrap_err()
+                .kind()
+        };
+        let invalid_data = std::io::ErrorKind::InvalidData;
+        // Missing header.
+        assert_eq!(read("2\n1 1\n0 0 ; 0\n"), invalid_data);
+        // Wrong number of parameters.
+        assert_eq!(read("scc2020\n3\n1 1\n0 0 0 ; 0\n"), invalid_data);
+        // Missing columns.
+        assert_eq!(read("scc2020\n2\n2 1\n0 0 ; 0\n"), invalid_data);
+        // Row out of range.
+        assert_eq!(read("scc2020\n2\n1 1\n0 0 ; 1\n"), invalid_data);
+        // Column with a smaller grade than its entries.
+        assert_eq!(read("scc2020\n2\n1 1 0\n0 0 ; 0\n1 1 ;\n"), invalid_data);
+    }
+
+    #[test]
+    fn write_firep_square() {
+        let edges = square_with_diagonal();
+        let mut written = Vec::new();
+        ChainComplex::from_flag_filtration(&edges, 1)
+            .write_firep(&mut written)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "--datatype firep\n2 5 4\n\
+             1 1 ; 1 4 0\n1 1 ; 2 3 4\n\
+             0 0 ; 1 0\n0 0 ; 2 1\n0 0 ; 3 2\n0 0 ; 3 0\n1 1 ; 2 0\n"
+        );
+    }
+
+    #[test]
+    fn validate_chain_complex() {
+        let edges = square_with_diagonal();
+        assert_eq!(
+            ChainComplex::from_flag_filtration(&edges, 1).validate(),
+            Ok(())
+        );
+
+        let file = "scc2020\n2\n1 1 0\n0 0 ; 0\n1 1 ;\n";
+        let error = ChainComplex::<usize, 2>::read_scc2020(BufReader::new(file.as_bytes()))
+            .unwrap_err()
+            .into_inner()
+            .unwrap()
+            .downcast::<ChainComplexError>()
+            .unwrap();
+        assert_eq!(
+            *error,
+            ChainComplexError::GradeNotMonotone {
+                matrix: 0,
+                column: 0,
+                row: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Enumeration of the small cliques of a graph, using a degeneracy ordering of its vertices.
+//!
+//! Each clique is found from its vertex of lowest rank in the ordering, among the neighbours of
+//! higher rank of that vertex. There are at most as many of them as the degeneracy of the graph,
+//! usually small, so the adjacency between them is stored as bitsets, and the candidates to
+//! extend a clique are found by intersecting bitsets.
+use crate::edges::BareEdge;
+
+/// Returns the rank of each vertex in a degeneracy ordering of the graph, obtained by repeatedly
+/// removing a vertex of minimum degree. Every vertex has at most `d` neighbours of higher rank,
+/// where `d` is the degeneracy of the graph.
+///
+/// This is the algorithm of Batagelj and Zaversnik, that runs in linear time.
+pub(crate) fn degeneracy_ranks(neighbours: &[Vec<usize>]) -> Vec<usize> {
+    let (order, _) = degeneracy_order(neighbours);
+    let mut rank = vec![0; neighbours.len()];
+    for (i, v) in order.into_iter().enumerate() {
+        rank[v] = i;
+    }
+    rank
+}
+
+/// Returns the core number of each vertex: the largest `k` such that the vertex is in the
+/// `k`-core of the graph, the largest subgraph whose vertices all have degree at least `k`.
+pub(crate) fn core_numbers(neighbours: &[Vec<usize>]) -> Vec<usize> {
+    let (_, core) = degeneracy_order(neighbours);
+    core
+}
+
+/// Returns the vertices in a degeneracy ordering, and the core number of each vertex, which is
+/// its degree when it is removed.
+fn degeneracy_order(neighbours: &[Vec<usize>]) -> (Vec<usize>, Vec<usize>) {
+    let n = neighbours.len();
+    let mut degree: Vec<usize> = neighbours.iter().map(Vec::len).collect();
+    let max_degree = degree.iter().copied().max().unwrap_or(0);
+
+    // Vertices sorted by degree, and the start of each degree in that order.
+    let mut bin_start = vec![0; max_degree + 1];
+    for d in degree.iter() {
+        bin_start[*d] += 1;
+    }
+    let mut start = 0;
+    for bin in bin_start.iter_mut() {
+        let size = *bin;
+        *bin = start;
+        start += size;
+    }
+    let mut order = vec![0; n];
+    let mut position = vec![0; n];
+    for v in 0..n {
+        position[v] = bin_start[degree[v]];
+        order[position[v]] = v;
+        bin_start[degree[v]] += 1;
+    }
+    for d in (1..=max_degree).rev() {
+        bin_start[d] = bin_start[d - 1];
+    }
+    bin_start[0] = 0;
+
+    for i in 0..n {
+        let v = order[i];
+        for &u in neighbours[v].iter() {
+            // Vertices already processed have a degree not greater than the one of v.
+            if degree[u] <= degree[v] {
+                continue;
+            }
+            // Move u to the start of its bin, and decrease its degree.
+            let du = degree[u];
+            let first_position = bin_start[du];
+            let first = order[first_position];
+            if first != u {
+                order.swap(first_position, position[u]);
+                position[first] = position[u];
+                position[u] = first_position;
+            }
+            bin_start[du] += 1;
+            degree[u] -= 1;
+        }
+    }
+    (order, degree)
+}
+
+/// Calls `f` on each clique of the graph with at least 3 and at most `max_size` vertices. The
+/// graph is given by its edges, that must not be repeated.
+///
+/// The function `f` receives the vertices of the clique, in no particular order, and the indices
+/// of its edges in the given slice.
+pub(crate) fn for_each_clique<E, F>(
+    n_vertices: usize,
+    edges: &[BareEdge],
+    max_size: usize,
+    mut f: F,
+) -> Result<(), E>
+where
+    F: FnMut(&[usize], &[usize]) -> Result<(), E>,
+{
+    if max_size < 3 {
+        return Ok(());
+    }
+
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); n_vertices];
+    for &BareEdge(u, v) in edges.iter() {
+        neighbours[u].push(v);
+        neighbours[v].push(u);
+    }
+    let rank = degeneracy_ranks(&neighbours);
+    drop(neighbours);
+
+    // The neighbours of higher rank of each vertex, and the index of the edge to them.
+    let mut higher_neighbours: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n_vertices];
+    for (idx, &BareEdge(u, v)) in edges.iter().enumerate() {
+        if rank[u] < rank[v] {
+            higher_neighbours[u].push((v, idx));
+        } else {
+            higher_neighbours[v].push((u, idx));
+        }
+    }
+
+    let mut local_index = vec![usize::MAX; n_vertices];
+    let mut neighbourhood = LocalNeighbourhood::default();
+    let mut clique = CliqueBuffer::default();
+    for x in 0..n_vertices {
+        let higher = &higher_neighbours[x];
+        if higher.len() < 2 {
+            continue;
+        }
+        for (i, &(w, _)) in higher.iter().enumerate() {
+            local_index[w] = i;
+        }
+        neighbourhood.reset(higher.len());
+        for (i, &(w, _)) in higher.iter().enumerate() {
+            for &(y, edge_idx) in higher_neighbours[w].iter() {
+                let j = local_index[y];
+                if j != usize::MAX {
+                    neighbourhood.add_edge(i, j, edge_idx);
+                }
+            }
+        }
+        for &(w, _) in higher.iter() {
+            local_index[w] = usize::MAX;
+        }
+
+        clique.reset(x);
+        for (i, &(w, edge_idx)) in higher.iter().enumerate() {
+            clique.push(w, i, edge_idx, &neighbourhood);
+            let candidates = neighbourhood.row(i).to_vec();
+            extend_clique(
+                higher,
+                &neighbourhood,
+                &mut clique,
+                &candidates,
+                i,
+                max_size,
+                &mut f,
+            )?;
+            clique.pop();
+        }
+    }
+    Ok(())
+}
+
+/// Adds to the clique, in every possible way, a vertex of the candidates with local index greater
+/// than `last`, calls `f` on the resulting clique, and recurses.
+fn extend_clique<E, F>(
+    higher: &[(usize, usize)],
+    neighbourhood: &LocalNeighbourhood,
+    clique: &mut CliqueBuffer,
+    candidates: &[u64],
+    last: usize,
+    max_size: usize,
+    f: &mut F,
+) -> Result<(), E>
+where
+    F: FnMut(&[usize], &[usize]) -> Result<(), E>,
+{
+    for j in iter_bits_after(candidates, last) {
+        let (w, edge_idx) = higher[j];
+        clique.push(w, j, edge_idx, neighbourhood);
+        f(&clique.vertices, &clique.edges)?;
+        if clique.vertices.len() < max_size {
+            let next_candidates: Vec<u64> = candidates
+                .iter()
+                .zip(neighbourhood.row(j))
+                .map(|(a, b)| a & b)
+                .collect();
+            extend_clique(
+                higher,
+                neighbourhood,
+                clique,
+                &next_candidates,
+                j,
+                max_size,
+                f,
+            )?;
+        }
+        clique.pop();
+    }
+    Ok(())
+}
+
+/// Iterates over the set bits, greater than `last`, of a bitset.
+fn iter_bits_after(bitset: &[u64], last: usize) -> impl Iterator<Item = usize> + '_ {
+    let first = last + 1;
+    bitset
+        .iter()
+        .enumerate()
+        .skip(first / 64)
+        .flat_map(move |(word_idx, &word)| {
+            let mut word = if word_idx == first / 64 {
+                word & (u64::MAX << (first % 64))
+            } else {
+                word
+            };
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(word_idx * 64 + bit)
+            })
+        })
+}
+
+/// The subgraph induced by the neighbours of higher rank of a vertex, indexed locally.
+#[derive(Default)]
+struct LocalNeighbourhood {
+    size: usize,
+    words: usize,
+    /// The adjacency bitset of each local vertex.
+    adjacency: Vec<u64>,
+    /// The index of the edge between each pair of adjacent local vertices.
+    edge_indices: Vec<usize>,
+}
+
+impl LocalNeighbourhood {
+    fn reset(&mut self, size: usize) {
+        self.size = size;
+        // The number of 64-bit words needed for `size` bits.
+        self.words = (size + 63) >> 6;
+        self.adjacency.clear();
+        self.adjacency.resize(size * self.words, 0);
+        self.edge_indices.clear();
+        self.edge_indices.resize(size * size, usize::MAX);
+    }
+
+    fn add_edge(&mut self, i: usize, j: usize, edge_idx: usize) {
+        self.adjacency[i * self.words + j / 64] |= 1 << (j % 64);
+        self.adjacency[j * self.words + i / 64] |= 1 << (i % 64);
+        self.edge_indices[i * self.size + j] = edge_idx;
+        self.edge_indices[j * self.size + i] = edge_idx;
+    }
+
+    fn row(&self, i: usize) -> &[u64] {
+        &self.adjacency[i * self.words..(i + 1) * self.words]
+    }
+
+    fn edge_index(&self, i: usize, j: usize) -> usize {
+        self.edge_indices[i * self.size + j]
+    }
+}
+
+/// The clique being built: its vertices, their local indices, and its edges.
+#[derive(Default)]
+struct CliqueBuffer {
+    vertices: Vec<usize>,
+    local: Vec<usize>,
+    edges: Vec<usize>,
+}
+
+impl CliqueBuffer {
+    fn reset(&mut self, x: usize) {
+        self.vertices.clear();
+        self.local.clear();
+        self.edges.clear();
+        self.vertices.push(x);
+    }
+
+    /// Adds a vertex, given with its local index and the index of its edge to the lowest vertex.
+    fn push(&mut self, w: usize, j: usize, edge_idx: usize, neighbourhood: &LocalNeighbourhood) {
+        self.edges.push(edge_idx);
+        for &i in self.local.iter() {
+            self.edges.push(neighbourhood.edge_index(i, j));
+        }
+        self.vertices.push(w);
+        self.local.push(j);
+    }
+
+    fn pop(&mut self) {
+        let n_edges = self.local.len();
+        self.edges.truncate(self.edges.len() - n_edges);
+        self.vertices.pop();
+        self.local.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rustc_hash::FxHashSet;
+
+    use crate::cliques::{degeneracy_ranks, for_each_clique};
+    use crate::edges::BareEdge;
+
+    #[test]
+    fn degeneracy_of_a_tree_is_one() {
+        // A star with a path attached.
+        let edges = [(0, 1), (0, 2), (0, 3), (3, 4), (4, 5)];
+        let mut neighbours = vec![Vec::new(); 6];
+        for (u, v) in edges {
+            neighbours[u].push(v);
+            neighbours[v].push(u);
+        }
+        let rank = degeneracy_ranks(&neighbours);
+        for (v, vertex_neighbours) in neighbours.iter().enumerate() {
+            let n_higher = vertex_neighbours
+                .iter()
+                .filter(|&&u| rank[u] > rank[v])
+                .count();
+            assert!(n_higher <= 1);
+        }
+    }
+
+    #[test]
+    fn cliques_of_random_graphs() {
+        let mut rng = StdRng::seed_from_u64(1300);
+        let n = 70;
+        let mut edges = Vec::new();
+        let mut edge_set = FxHashSet::default();
+        for u in 0..n {
+            for v in (u + 1)..n {
+                if rng.gen_bool(0.4) {
+                    edges.push(BareEdge(u, v));
+                    edge_set.insert(BareEdge(u, v));
+                }
+            }
+        }
+        let adjacent = |u: usize, v: usize| edge_set.contains(&BareEdge(u.min(v), u.max(v)));
+
+        let mut found: FxHashSet<Vec<usize>> = FxHashSet::default();
+        for_each_clique(n, &edges, 4, |vertices, edge_indices| {
+            assert_eq!(
+                edge_indices.len(),
+                vertices.len() * (vertices.len() - 1) / 2
+            );
+            for &idx in edge_indices {
+                let BareEdge(u, v) = edges[idx];
+                assert!(vertices.contains(&u) && vertices.contains(&v));
+            }
+            let mut sorted = vertices.to_vec();
+            sorted.sort_unstable();
+            assert!(found.insert(sorted), "Cliques are found once");
+            Ok::<(), ()>(())
+        })
+        .unwrap();
+
+        let mut expected = 0;
+        for a in 0..n {
+            for b in (a + 1)..n {
+                for c in (b + 1)..n {
+                    if adjacent(a, b) && adjacent(a, c) && adjacent(b, c) {
+                        expected += 1;
+                        expected += ((c + 1)..n)
+                            .filter(|&d| adjacent(a, d) && adjacent(b, d) && adjacent(c, d))
+                            .count();
+                    }
+                }
+            }
+        }
+        assert_eq!(found.len(), expected);
+    }
+}
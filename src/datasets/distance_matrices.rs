@@ -1,21 +1,33 @@
 use num::Float;
 use ordered_float::OrderedFloat;
+use rand::Rng;
 use std::fs;
+use std::io;
 use std::io::{BufReader, BufWriter};
 use std::path::Path;
 
 use crate::datasets::sampling::{
-    sample_noisy_sphere, sample_random_points, sample_swiss_roll, sample_torus,
+    sample_annulus_with_rng, sample_figure_eight_with_rng, sample_gaussian_mixture_with_rng,
+    sample_noisy_sphere_with_rng, sample_random_geometric_graph_with_rng,
+    sample_random_points_with_rng, sample_swiss_roll_with_rng, sample_torus_with_rng,
+};
+use crate::datasets::{Dataset, DatasetError, Threshold};
+use crate::distance_matrix::input::{
+    read_distance_matrix_flexible, read_lower_triangular_distance_matrix,
 };
-use crate::datasets::{Dataset, DatasetError, Threshold, DATASET_DIRECTORY};
-use crate::distance_matrix::input::read_lower_triangular_distance_matrix;
 use crate::distance_matrix::output::write_lower_triangular_distance_matrix;
 use crate::distance_matrix::DistanceMatrix;
 use crate::edges::{EdgeList, FilteredEdge};
-use crate::points::input::read_point_cloud;
+use crate::points::input::{
+    read_point_cloud, read_point_cloud_dimension, read_point_cloud_in_format, PointCloudFormat,
+};
 use crate::points::PointCloud;
 use crate::{OneCriticalGrade, Value};
 
+/// The lower triangular distance matrix of [Dataset::BundledCircle50].
+#[cfg(feature = "bundled-examples")]
+const BUNDLED_CIRCLE_50: &[u8] = include_bytes!("bundled/circle_50_distmat.txt");
+
 /// Build an edge list out of a distance matrix. Each edge is graded by the distance between its
 /// vertices.
 /// If `threshold` is given, edges of grade less than `threshold` are not included.
@@ -30,21 +42,28 @@ pub fn get_distance_matrix_edge_list(
         Threshold::KeepAll => None,
         Threshold::Percentile(p) => Some(*distance_matrix.percentile(p)),
         Threshold::Fixed(t) => Some(OrderedFloat::from(t)),
+        Threshold::Rectangle { .. } => None,
     };
 
-    if let Some(threshold_value) = actual_threshold {
+    let mut edge_list = if let Some(threshold_value) = actual_threshold {
         EdgeList::from_iterator(filter_by_threshold(edges, threshold_value))
     } else {
         EdgeList::from_iterator(edges)
+    };
+    if let Threshold::Rectangle { max_distance, .. } = threshold {
+        edge_list.truncate(OneCriticalGrade([OrderedFloat::from(max_distance)]));
     }
+    edge_list
 }
 
-/// Returns the distance matrix of the given dataset.
-pub fn get_dataset_distance_matrix(
+/// Returns the distance matrix of the given dataset. The built-in datasets are read from, and
+/// cached in, the given directory. The sampled datasets use the given random number generator.
+pub fn get_dataset_distance_matrix<R: Rng>(
+    dataset_directory: &Path,
     dataset: Dataset,
     use_cache: bool,
+    rng: &mut R,
 ) -> Result<DistanceMatrix<OrderedFloat<f64>>, DatasetError> {
-    let dataset_directory: &Path = Path::new(DATASET_DIRECTORY);
     match dataset {
         Dataset::Senate => read_distance_matrix_from_file(
             dataset_directory.join("senate104_edge_list.txt_0.68902_distmat.txt"),
@@ -67,7 +86,7 @@ pub fn get_dataset_distance_matrix(
                 dst_filename,
                 || {
                     sample_distance_matrix(n_points, |n| {
-                        sample_noisy_sphere::<f64, 3>(n, 0.9, 0.75, 0.3)
+                        sample_noisy_sphere_with_rng::<f64, _, 3>(n, 0.9, 0.75, 0.3, rng)
                     })
                 },
                 use_cache,
@@ -77,7 +96,7 @@ pub fn get_dataset_distance_matrix(
             let dst_filename = dataset_directory.join(format!("torus_{n_points}_distmat.txt"));
             read_or_save_distance_matrix(
                 dst_filename,
-                || sample_distance_matrix(n_points, sample_torus),
+                || sample_distance_matrix(n_points, |n| sample_torus_with_rng(n, rng)),
                 use_cache,
             )
         }
@@ -85,7 +104,7 @@ pub fn get_dataset_distance_matrix(
             let dst_filename = dataset_directory.join(format!("swiss_roll_{n_points}_distmat.txt"));
             read_or_save_distance_matrix(
                 dst_filename,
-                || sample_distance_matrix(n_points, sample_swiss_roll),
+                || sample_distance_matrix(n_points, |n| sample_swiss_roll_with_rng(n, rng)),
                 use_cache,
             )
         }
@@ -95,7 +114,7 @@ pub fn get_dataset_distance_matrix(
                 dst_filename,
                 || {
                     sample_distance_matrix(n_points, |n| {
-                        sample_noisy_sphere::<f64, 2>(n, 1., 0., 0.)
+                        sample_noisy_sphere_with_rng::<f64, _, 2>(n, 1., 0., 0., rng)
                     })
                 },
                 use_cache,
@@ -105,7 +124,72 @@ pub fn get_dataset_distance_matrix(
             let dst_filename = dataset_directory.join(format!("uniform_{n_points}_distmat.txt"));
             read_or_save_distance_matrix(
                 dst_filename,
-                || sample_distance_matrix(n_points, sample_random_points::<f64, 2>),
+                || {
+                    sample_distance_matrix(n_points, |n| {
+                        sample_random_points_with_rng::<f64, _, 2>(n, rng)
+                    })
+                },
+                use_cache,
+            )
+        }
+        Dataset::RandomGeometricGraph {
+            n_points,
+            radius,
+            weight_exponent,
+        } => {
+            if let Some(exponent) = weight_exponent {
+                if exponent <= 1. || exponent.is_nan() {
+                    return Err(DatasetError::InvalidWeightExponent(exponent));
+                }
+            }
+            let name = match weight_exponent {
+                None => format!("rgg_{n_points}_{radius}"),
+                Some(exponent) => format!("girg_{n_points}_{radius}_{exponent}"),
+            };
+            let dst_filename = dataset_directory.join(format!("{name}_distmat.txt"));
+            read_or_save_distance_matrix(
+                dst_filename,
+                || sample_random_geometric_graph_with_rng(n_points, radius, weight_exponent, rng),
+                use_cache,
+            )
+        }
+        Dataset::GaussianMixture {
+            n_points,
+            n_clusters,
+            noise,
+        } => {
+            let dst_filename = dataset_directory.join(format!(
+                "gaussian_mixture_{n_points}_{n_clusters}_{noise}_distmat.txt"
+            ));
+            read_or_save_distance_matrix(
+                dst_filename,
+                || {
+                    sample_distance_matrix(n_points, |n| {
+                        sample_gaussian_mixture_with_rng(n, n_clusters, noise, rng)
+                    })
+                },
+                use_cache,
+            )
+        }
+        Dataset::Annulus { n_points, noise } => {
+            let dst_filename =
+                dataset_directory.join(format!("annulus_{n_points}_{noise}_distmat.txt"));
+            read_or_save_distance_matrix(
+                dst_filename,
+                || sample_distance_matrix(n_points, |n| sample_annulus_with_rng(n, noise, rng)),
+                use_cache,
+            )
+        }
+        Dataset::FigureEight { n_points, noise } => {
+            let dst_filename =
+                dataset_directory.join(format!("figure_eight_{n_points}_{noise}_distmat.txt"));
+            read_or_save_distance_matrix(
+                dst_filename,
+                || {
+                    sample_distance_matrix(n_points, |n| {
+                        sample_figure_eight_with_rng(n, noise, rng)
+                    })
+                },
                 use_cache,
             )
         }
@@ -122,11 +206,59 @@ pub fn get_dataset_distance_matrix(
             let point_cloud: PointCloud<OrderedFloat<f64>, 2> = read_point_cloud(reader)?;
             Ok(point_cloud.distance_matrix())
         }
+        #[cfg(feature = "bundled-examples")]
+        Dataset::BundledCircle50 => Ok(read_lower_triangular_distance_matrix(BUNDLED_CIRCLE_50)?),
+        #[cfg(not(feature = "bundled-examples"))]
+        Dataset::BundledCircle50 => Err(DatasetError::NotBundled(dataset.to_string())),
+        Dataset::DistanceMatrixFile(filepath) => {
+            read_distance_matrix_from_file_with(filepath, read_distance_matrix_flexible)
+        }
+        Dataset::PointCloudFile(filepath) => read_point_cloud_distance_matrix(filepath),
+    }
+}
+
+/// Reads a point cloud file, in the format given by its extension, and returns its distance
+/// matrix.
+fn read_point_cloud_distance_matrix<P: AsRef<Path>>(
+    filepath: P,
+) -> Result<DistanceMatrix<OrderedFloat<f64>>, DatasetError> {
+    let filepath = filepath.as_ref();
+    if !filepath.is_file() {
+        return Err(DatasetError::FileNotFound(format!(
+            "{}",
+            filepath.display()
+        )));
     }
+    let format = PointCloudFormat::from_extension(filepath).unwrap_or(PointCloudFormat::Xyz);
+    let content = fs::read(filepath)?;
+    let dimension = read_point_cloud_dimension(content.as_slice(), format)?;
+
+    macro_rules! distance_matrix_in_dimension {
+        ($($n:literal),*) => {
+            match dimension {
+                $(
+                    $n => {
+                        let point_cloud: PointCloud<OrderedFloat<f64>, $n> =
+                            read_point_cloud_in_format(content.as_slice(), format)?;
+                        Ok(point_cloud.distance_matrix())
+                    }
+                )*
+                _ => Err(DatasetError::UnsupportedDimension(dimension)),
+            }
+        };
+    }
+    distance_matrix_in_dimension!(1, 2, 3, 4, 5, 6, 7, 8)
 }
 
 fn read_distance_matrix_from_file<P: AsRef<Path>>(
     filepath: P,
+) -> Result<DistanceMatrix<OrderedFloat<f64>>, DatasetError> {
+    read_distance_matrix_from_file_with(filepath, read_lower_triangular_distance_matrix)
+}
+
+fn read_distance_matrix_from_file_with<P: AsRef<Path>>(
+    filepath: P,
+    read: impl FnOnce(BufReader<fs::File>) -> io::Result<DistanceMatrix<OrderedFloat<f64>>>,
 ) -> Result<DistanceMatrix<OrderedFloat<f64>>, DatasetError> {
     if !filepath.as_ref().is_file() {
         return Err(DatasetError::FileNotFound(format!(
@@ -135,13 +267,13 @@ fn read_distance_matrix_from_file<P: AsRef<Path>>(
         )));
     }
     let file = fs::File::open(filepath)?;
-    let reader = BufReader::new(&file);
-    let distance_matrix = read_lower_triangular_distance_matrix(reader)?;
+    let reader = BufReader::new(file);
+    let distance_matrix = read(reader)?;
 
     Ok(distance_matrix)
 }
 
-fn sample_distance_matrix<F: Fn(usize) -> PointCloud<f64, N>, const N: usize>(
+fn sample_distance_matrix<F: FnOnce(usize) -> PointCloud<f64, N>, const N: usize>(
     n_points: usize,
     f: F,
 ) -> DistanceMatrix<OrderedFloat<f64>> {
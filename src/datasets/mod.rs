@@ -1,25 +1,37 @@
 //! Dataset reading and sampling.
 //!
 //! The main entry point is [get_dataset_density_edge_list], which returns a bifiltered edge list.
+//! Besides the built-in datasets, it works on user data given by a [Dataset::DistanceMatrixFile]
+//! or a [Dataset::PointCloudFile]. To filter the vertices by other functions than codensity, see
+//! [get_dataset_bifiltered_edge_list]. With the `bundled-examples` feature, small datasets like
+//! [Dataset::BundledCircle50] are embedded in the crate, and are available without downloading
+//! any files.
 use ordered_float::OrderedFloat;
+use rand::Rng;
 use std::cmp::max;
 use std::fmt::Formatter;
 use std::io;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 use crate::datasets::distance_matrices::get_dataset_distance_matrix;
 use crate::distance_matrix::density_estimation::DensityEstimator;
 use crate::distance_matrix::DistanceMatrix;
 use crate::edges::{BareEdge, EdgeList, FilteredEdge};
-use crate::{OneCriticalGrade, Value};
+use crate::{CriticalGrade, OneCriticalGrade, Value};
+use num::Float;
 
 mod distance_matrices;
-mod sampling;
+pub mod sampling;
 
-const DATASET_DIRECTORY: &str = "datasets";
+/// The directory of the built-in datasets, relative to the working directory, used by
+/// [get_dataset_density_edge_list]. See [get_dataset_density_edge_list_in_directory] to use
+/// another directory.
+pub const DATASET_DIRECTORY: &str = "datasets";
 
 /// All datasets that we support.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dataset {
     /// The senate dataset from <https://github.com/n-otter/PH-roadmap>.
     Senate,
@@ -52,6 +64,46 @@ pub enum Dataset {
         n_points: usize,
     },
     NoisyTorus,
+    /// A geometric inhomogeneous random graph on points of `\[0,1\]^2`, where each point gets a
+    /// weight from a Pareto distribution with the given exponent, and two points are adjacent if
+    /// their distance is at most `radius` times the geometric mean of their weights. If
+    /// `weight_exponent` is not given, it is a random geometric graph. The distances are the
+    /// shortest path distances, as in the graph datasets above, and only the largest connected
+    /// component is kept.
+    RandomGeometricGraph {
+        n_points: usize,
+        radius: f64,
+        weight_exponent: Option<f64>,
+    },
+    /// A mixture of Gaussians in R^2, with clusters of different sizes and spreads. The
+    /// standard deviation of the widest cluster is `noise`.
+    GaussianMixture {
+        n_points: usize,
+        n_clusters: usize,
+        noise: f64,
+    },
+    /// An annulus in R^2 with Gaussian noise.
+    Annulus {
+        n_points: usize,
+        noise: f64,
+    },
+    /// A figure eight in R^2 with Gaussian noise.
+    FigureEight {
+        n_points: usize,
+        noise: f64,
+    },
+    /// 50 points on the unit circle, sampled once and embedded in the crate. Unlike the other
+    /// datasets, it needs no files, so it works offline. Requires the `bundled-examples` feature:
+    /// without it, reading the dataset returns [DatasetError::NotBundled].
+    BundledCircle50,
+    /// A distance matrix read from the given file, lower triangular or square, with comments and
+    /// any of the separators of
+    /// [read_distance_matrix_flexible](crate::distance_matrix::input::read_distance_matrix_flexible).
+    DistanceMatrixFile(PathBuf),
+    /// A point cloud read from the given file, with Euclidean distances. The format is guessed
+    /// from the extension with [PointCloudFormat::from_extension](crate::points::input::PointCloudFormat::from_extension),
+    /// and defaults to whitespace-separated coordinates. The points can have up to 8 coordinates.
+    PointCloudFile(PathBuf),
 }
 
 impl std::fmt::Display for Dataset {
@@ -90,12 +142,46 @@ impl std::fmt::Display for Dataset {
             Dataset::NoisyTorus => {
                 write!(f, "noisy_torus")
             }
+            Dataset::RandomGeometricGraph {
+                n_points,
+                radius,
+                weight_exponent: None,
+            } => {
+                write!(f, "rgg({n_points}, {radius})")
+            }
+            Dataset::RandomGeometricGraph {
+                n_points,
+                radius,
+                weight_exponent: Some(exponent),
+            } => {
+                write!(f, "girg({n_points}, {radius}, {exponent})")
+            }
+            Dataset::GaussianMixture {
+                n_points,
+                n_clusters,
+                noise,
+            } => {
+                write!(f, "gaussian-mixture({n_points}, {n_clusters}, {noise})")
+            }
+            Dataset::Annulus { n_points, noise } => {
+                write!(f, "annulus({n_points}, {noise})")
+            }
+            Dataset::FigureEight { n_points, noise } => {
+                write!(f, "figure-eight({n_points}, {noise})")
+            }
+            Dataset::BundledCircle50 => {
+                write!(f, "bundled-circle(50)")
+            }
+            Dataset::DistanceMatrixFile(path) | Dataset::PointCloudFile(path) => {
+                write!(f, "{}", path.display())
+            }
         }
     }
 }
 
 /// Possible thresholding settings.
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Threshold {
     /// Keep all edges.
     KeepAll,
@@ -103,6 +189,54 @@ pub enum Threshold {
     Percentile(f64),
     /// Restrict to the edges of length less that the given value.
     Fixed(f64),
+    /// Restrict to the edges of codensity at most `max_codensity` and length at most
+    /// `max_distance`, that is, to the grades in a rectangle. See [EdgeList::truncate].
+    /// Edge lists graded only by length are restricted by `max_distance` alone.
+    Rectangle {
+        max_codensity: f64,
+        max_distance: f64,
+    },
+}
+
+/// The function on the vertices that grades the first parameter of the bifiltration. An edge
+/// appears at the maximum of the values of its vertices. Smaller values appear first.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VertexFiltration<T: Copy> {
+    /// The codensity, that is, one minus the density, estimated with the given estimator.
+    Density(DensityEstimator<T>),
+    /// The eccentricity, that is, the maximum distance to any other vertex. See
+    /// [DistanceMatrix::eccentricity_vector].
+    Eccentricity,
+    /// The given values, one per vertex.
+    Custom(Vec<T>),
+}
+
+impl<T: Float + Ord> VertexFiltration<T> {
+    /// The value of each vertex of the given distance matrix.
+    pub fn values(&self, distance_matrix: &DistanceMatrix<T>) -> Result<Vec<T>, DatasetError> {
+        match self {
+            VertexFiltration::Density(estimator) => {
+                let mut estimations = estimator.estimate(distance_matrix);
+                // Instead of working with densities, we work with codensities. That is, smaller
+                // values correspond to higher density estimations.
+                for e in estimations.iter_mut() {
+                    *e = T::one() - *e;
+                }
+                Ok(estimations)
+            }
+            VertexFiltration::Eccentricity => Ok(distance_matrix.eccentricity_vector()),
+            VertexFiltration::Custom(values) => {
+                if values.len() != distance_matrix.len() {
+                    return Err(DatasetError::VertexValuesMismatch {
+                        expected: distance_matrix.len(),
+                        found: values.len(),
+                    });
+                }
+                Ok(values.clone())
+            }
+        }
+    }
 }
 
 /// Error when reading or creating a dataset.
@@ -111,6 +245,18 @@ pub enum DatasetError {
     #[error("Couldn't find file \"{0}\". Did you download the datasets?")]
     FileNotFound(String),
 
+    #[error("Points of dimension {0} are not supported, the maximum is 8")]
+    UnsupportedDimension(usize),
+
+    #[error("The dataset has {expected} vertices, but {found} vertex values were given")]
+    VertexValuesMismatch { expected: usize, found: usize },
+
+    #[error("The weight exponent must be greater than 1, but it is {0}")]
+    InvalidWeightExponent(f64),
+
+    #[error("The dataset {0} is only available with the bundled-examples feature")]
+    NotBundled(String),
+
     #[error(transparent)]
     Io(#[from] io::Error),
 }
@@ -122,40 +268,177 @@ pub enum DatasetError {
 /// If a `estimator` is not provided, the function uses the Gaussian kernel estimator with
 /// bandwidth parameter set to the 20th percentile of the distances.
 /// If `use_cache` is set, the function caches the distance matrices of the sampled datasets.
+///
+/// The sampled datasets are sampled with [rand::thread_rng]. To make them reproducible, use
+/// [get_dataset_density_edge_list_with_rng] instead.
 pub fn get_dataset_density_edge_list(
     dataset: Dataset,
     threshold: Threshold,
     estimator: Option<DensityEstimator<OrderedFloat<f64>>>,
     use_cache: bool,
 ) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>, DatasetError> {
-    let distance_matrix = get_dataset_distance_matrix(dataset, use_cache)?;
+    get_dataset_density_edge_list_with_rng(
+        dataset,
+        threshold,
+        estimator,
+        use_cache,
+        &mut rand::thread_rng(),
+    )
+}
+
+/// Like [get_dataset_density_edge_list], but the sampled datasets are sampled with the given
+/// random number generator. For example, seeding a [rand::rngs::StdRng] gives reproducible
+/// datasets. Note that, if `use_cache` is set and the dataset has already been cached, the cached
+/// dataset is returned instead.
+pub fn get_dataset_density_edge_list_with_rng<R: Rng>(
+    dataset: Dataset,
+    threshold: Threshold,
+    estimator: Option<DensityEstimator<OrderedFloat<f64>>>,
+    use_cache: bool,
+    rng: &mut R,
+) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>, DatasetError> {
+    get_dataset_density_edge_list_in_directory(
+        DATASET_DIRECTORY,
+        dataset,
+        threshold,
+        estimator,
+        use_cache,
+        rng,
+    )
+}
 
+/// Like [get_dataset_density_edge_list_with_rng], but the built-in datasets are read from, and the
+/// sampled datasets are cached in, the given directory instead of [DATASET_DIRECTORY]. The paths
+/// of [Dataset::DistanceMatrixFile] and [Dataset::PointCloudFile] are used as given.
+pub fn get_dataset_density_edge_list_in_directory<P: AsRef<Path>, R: Rng>(
+    directory: P,
+    dataset: Dataset,
+    threshold: Threshold,
+    estimator: Option<DensityEstimator<OrderedFloat<f64>>>,
+    use_cache: bool,
+    rng: &mut R,
+) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>, DatasetError> {
+    let distance_matrix = get_dataset_distance_matrix(directory.as_ref(), dataset, use_cache, rng)?;
     let estimator = estimator.unwrap_or_else(|| default_estimator(&distance_matrix));
-    let mut estimations = estimator.estimate(&distance_matrix);
-    // Instead of working with densities, we work with codensities. That is, smaller values correspond
-    // to higher density estimations.
-    for e in estimations.iter_mut() {
-        *e = OrderedFloat::from(1.0) - *e;
-    }
+    bifiltered_edge_list(
+        &distance_matrix,
+        threshold,
+        &VertexFiltration::Density(estimator),
+    )
+}
+
+/// Return the edge list of the associated dataset, where each edge is bifiltered by the given
+/// function on its vertices and its length. The edge appears at the maximum of the values of its
+/// vertices. With [VertexFiltration::Density], this is [get_dataset_density_edge_list].
+///
+/// The first parameter is bounded by the `max_codensity` of a [Threshold::Rectangle], whatever the
+/// vertex function is.
+pub fn get_dataset_bifiltered_edge_list(
+    dataset: Dataset,
+    threshold: Threshold,
+    filtration: &VertexFiltration<OrderedFloat<f64>>,
+    use_cache: bool,
+) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>, DatasetError> {
+    get_dataset_bifiltered_edge_list_in_directory(
+        DATASET_DIRECTORY,
+        dataset,
+        threshold,
+        filtration,
+        use_cache,
+        &mut rand::thread_rng(),
+    )
+}
+
+/// Like [get_dataset_bifiltered_edge_list], but with the directory and the random number
+/// generator of [get_dataset_density_edge_list_in_directory].
+pub fn get_dataset_bifiltered_edge_list_in_directory<P: AsRef<Path>, R: Rng>(
+    directory: P,
+    dataset: Dataset,
+    threshold: Threshold,
+    filtration: &VertexFiltration<OrderedFloat<f64>>,
+    use_cache: bool,
+    rng: &mut R,
+) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>, DatasetError> {
+    let distance_matrix = get_dataset_distance_matrix(directory.as_ref(), dataset, use_cache, rng)?;
+    bifiltered_edge_list(&distance_matrix, threshold, filtration)
+}
+
+fn bifiltered_edge_list(
+    distance_matrix: &DistanceMatrix<OrderedFloat<f64>>,
+    threshold: Threshold,
+    filtration: &VertexFiltration<OrderedFloat<f64>>,
+) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>, DatasetError> {
+    bifiltered_edge_list_with(distance_matrix, threshold, filtration, |value| value)
+}
+
+/// As [bifiltered_edge_list], but the values of the grades are converted with `convert` as the
+/// edges are built, after truncating them, so that no edge list with the original values is built.
+fn bifiltered_edge_list_with<VO: Value>(
+    distance_matrix: &DistanceMatrix<OrderedFloat<f64>>,
+    threshold: Threshold,
+    filtration: &VertexFiltration<OrderedFloat<f64>>,
+    convert: impl Fn(OrderedFloat<f64>) -> VO,
+) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<VO, 2>>>, DatasetError> {
+    let vertex_values = filtration.values(distance_matrix)?;
 
-    let edges = distance_matrices::get_distance_matrix_edge_list(&distance_matrix, threshold);
+    let edges = distance_matrices::get_distance_matrix_edge_list(distance_matrix, threshold);
 
-    let density_edges_it = edges.edges().iter().map(|edge| {
+    let rectangle = match threshold {
+        Threshold::Rectangle {
+            max_codensity,
+            max_distance,
+        } => Some(OneCriticalGrade([
+            max_codensity.into(),
+            max_distance.into(),
+        ])),
+        _ => None,
+    };
+
+    let bifiltered_edges_it = edges.edges().iter().filter_map(|edge| {
         let FilteredEdge {
             grade: OneCriticalGrade([dist]),
             edge: BareEdge(u, v),
         } = edge;
 
-        // The edge density is the max of the codensity of its vertices.
-        let edge_density = max(estimations[*u], estimations[*v]);
+        // The edge appears once both of its vertices have.
+        let edge_value = max(vertex_values[*u], vertex_values[*v]);
 
-        FilteredEdge {
-            grade: OneCriticalGrade([edge_density, *dist]),
-            edge: BareEdge(*u, *v),
+        let grade = OneCriticalGrade([edge_value, *dist]);
+        match rectangle {
+            Some(rectangle) if !grade.lte(&rectangle) => None,
+            _ => Some(FilteredEdge {
+                grade: OneCriticalGrade(grade.0.map(&convert)),
+                edge: BareEdge(*u, *v),
+            }),
         }
     });
 
-    Ok(EdgeList::from_iterator(density_edges_it))
+    Ok(EdgeList::from_iterator(bifiltered_edges_it))
+}
+
+/// Like [get_dataset_density_edge_list], but the grades are single-precision floats, which take
+/// 24 bytes per edge instead of 32. The densities and distances are computed in double precision
+/// and rounded as each edge is built, as in [EdgeList::to_single_precision], so the edge list with
+/// double-precision grades is never built.
+pub fn get_dataset_density_edge_list_f32(
+    dataset: Dataset,
+    threshold: Threshold,
+    estimator: Option<DensityEstimator<OrderedFloat<f64>>>,
+    use_cache: bool,
+) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f32>, 2>>>, DatasetError> {
+    let distance_matrix = get_dataset_distance_matrix(
+        Path::new(DATASET_DIRECTORY),
+        dataset,
+        use_cache,
+        &mut rand::thread_rng(),
+    )?;
+    let estimator = estimator.unwrap_or_else(|| default_estimator(&distance_matrix));
+    bifiltered_edge_list_with(
+        &distance_matrix,
+        threshold,
+        &VertexFiltration::Density(estimator),
+        |value| OrderedFloat(value.0 as f32),
+    )
 }
 
 fn default_estimator<F: Value + std::fmt::Display>(
@@ -164,3 +447,259 @@ fn default_estimator<F: Value + std::fmt::Display>(
     let bandwidth = matrix.percentile(0.2);
     DensityEstimator::Gaussian(*bandwidth)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::datasets::{
+        get_dataset_bifiltered_edge_list_in_directory, get_dataset_density_edge_list,
+        get_dataset_density_edge_list_f32, get_dataset_density_edge_list_in_directory, Dataset,
+        DatasetError, Threshold, VertexFiltration,
+    };
+    use crate::distance_matrix::density_estimation::DensityEstimator;
+    use crate::io_utils::unique_temp_path;
+
+    #[test]
+    fn single_precision_datasets() {
+        let directory = unique_temp_path("single_precision");
+        fs::create_dir_all(&directory).unwrap();
+        let points_path = directory.join("square.csv");
+        fs::write(&points_path, "0,0\n1,0\n1,1\n0,1\n0.5,0.5\n").unwrap();
+
+        for threshold in [
+            Threshold::KeepAll,
+            Threshold::Rectangle {
+                max_codensity: 0.9,
+                max_distance: 1.,
+            },
+        ] {
+            let double = get_dataset_density_edge_list(
+                Dataset::PointCloudFile(points_path.clone()),
+                threshold,
+                None,
+                false,
+            )
+            .unwrap();
+            let single = get_dataset_density_edge_list_f32(
+                Dataset::PointCloudFile(points_path.clone()),
+                threshold,
+                None,
+                false,
+            )
+            .unwrap();
+            assert_eq!(single.edges(), double.to_single_precision().edges());
+        }
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn datasets_from_user_files() {
+        let directory = unique_temp_path("user_datasets");
+        fs::create_dir_all(&directory).unwrap();
+        let matrix_path = directory.join("square_distmat.txt");
+        fs::write(
+            &matrix_path,
+            "# A unit square.\n0\n1, 0\n1.5, 1, 0\n1, 1.5, 1, 0\n",
+        )
+        .unwrap();
+        let points_path = directory.join("square.csv");
+        fs::write(&points_path, "0,0\n1,0\n1,1\n0,1\n").unwrap();
+
+        let from_matrix = get_dataset_density_edge_list(
+            Dataset::DistanceMatrixFile(matrix_path),
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(from_matrix.n_vertices, 4);
+        assert_eq!(from_matrix.len(), 6);
+
+        let from_points = get_dataset_density_edge_list(
+            Dataset::PointCloudFile(points_path),
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(from_points.len(), 6);
+        let max_length = from_points.edge_iter().map(|e| e.grade.0[1]).max().unwrap();
+        assert_eq!(max_length.0, 2f64.sqrt());
+
+        // The built-in datasets are looked up in the given directory.
+        let missing = get_dataset_density_edge_list_in_directory(
+            &directory,
+            Dataset::Senate,
+            Threshold::KeepAll,
+            None,
+            false,
+            &mut StdRng::seed_from_u64(1315),
+        );
+        match missing {
+            Err(DatasetError::FileNotFound(path)) => {
+                assert!(path.starts_with(directory.to_str().unwrap()))
+            }
+            _ => panic!("The senate dataset should not be found"),
+        }
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[cfg(feature = "bundled-examples")]
+    #[test]
+    fn bundled_datasets_need_no_files() {
+        let directory = unique_temp_path("no_datasets");
+        let edges = get_dataset_density_edge_list_in_directory(
+            &directory,
+            Dataset::BundledCircle50,
+            Threshold::KeepAll,
+            None,
+            true,
+            &mut StdRng::seed_from_u64(1362),
+        )
+        .unwrap();
+        assert_eq!(edges.n_vertices, 50);
+        assert_eq!(edges.len(), 50 * 49 / 2);
+        let max_length = edges.edge_iter().map(|e| e.grade.0[1]).max().unwrap();
+        assert!(max_length.0 <= 2.);
+        assert!(!directory.exists());
+    }
+
+    #[cfg(not(feature = "bundled-examples"))]
+    #[test]
+    fn bundled_datasets_need_the_feature() {
+        let directory = unique_temp_path("no_datasets");
+        let result = get_dataset_density_edge_list_in_directory(
+            &directory,
+            Dataset::BundledCircle50,
+            Threshold::KeepAll,
+            None,
+            true,
+            &mut StdRng::seed_from_u64(1362),
+        );
+        assert!(matches!(result, Err(DatasetError::NotBundled(_))));
+        assert!(!directory.exists());
+    }
+
+    #[test]
+    fn rectangle_threshold_bounds_both_parameters() {
+        let dataset = Dataset::Sphere { n_points: 60 };
+        let mut rng = StdRng::seed_from_u64(1339);
+        let all = get_dataset_density_edge_list_in_directory(
+            "",
+            dataset.clone(),
+            Threshold::KeepAll,
+            None,
+            false,
+            &mut rng,
+        )
+        .unwrap();
+        // The medians of each parameter.
+        let median = |parameter: usize| {
+            let mut values: Vec<f64> = all.edge_iter().map(|e| e.grade.0[parameter].0).collect();
+            values.sort_by(f64::total_cmp);
+            values[values.len() / 2]
+        };
+        let (max_codensity, max_distance) = (median(0), median(1));
+        let truncated = get_dataset_density_edge_list_in_directory(
+            "",
+            dataset,
+            Threshold::Rectangle {
+                max_codensity,
+                max_distance,
+            },
+            None,
+            false,
+            &mut StdRng::seed_from_u64(1339),
+        )
+        .unwrap();
+
+        let expected: Vec<_> = all
+            .edge_iter()
+            .filter(|e| e.grade.0[0].0 <= max_codensity && e.grade.0[1].0 <= max_distance)
+            .copied()
+            .collect();
+        assert!(!expected.is_empty());
+        assert!(expected.len() < all.len());
+        assert_eq!(truncated.edges(), &expected[..]);
+    }
+
+    #[test]
+    fn vertex_filtrations() {
+        let directory = unique_temp_path("vertex_filtrations");
+        fs::create_dir_all(&directory).unwrap();
+        let matrix_path = directory.join("path_distmat.txt");
+        // Four points on a line, at 0, 1, 2 and 4.
+        fs::write(&matrix_path, "0\n1 0\n2 1 0\n4 3 2 0\n").unwrap();
+        let dataset = Dataset::DistanceMatrixFile(matrix_path);
+        let edge_list = |filtration: &VertexFiltration<_>| {
+            get_dataset_bifiltered_edge_list_in_directory(
+                &directory,
+                dataset.clone(),
+                Threshold::KeepAll,
+                filtration,
+                false,
+                &mut StdRng::seed_from_u64(1342),
+            )
+        };
+
+        let eccentricity = edge_list(&VertexFiltration::Eccentricity).unwrap();
+        let eccentricity_grades: Vec<f64> =
+            eccentricity.edge_iter().map(|e| e.grade.0[0].0).collect();
+        // The eccentricities are 4, 3, 2 and 4, and the edges are in lower triangular order.
+        assert_eq!(eccentricity_grades, [4., 4., 3., 4., 4., 4.]);
+
+        let values = [5., 1., 2., 3.].map(Into::into).to_vec();
+        let custom = edge_list(&VertexFiltration::Custom(values)).unwrap();
+        let custom_grades: Vec<f64> = custom.edge_iter().map(|e| e.grade.0[0].0).collect();
+        assert_eq!(custom_grades, [5., 5., 2., 5., 3., 3.]);
+
+        let estimator = DensityEstimator::Gaussian(1.0.into());
+        let density = edge_list(&VertexFiltration::Density(estimator)).unwrap();
+        let expected = get_dataset_density_edge_list_in_directory(
+            &directory,
+            dataset.clone(),
+            Threshold::KeepAll,
+            Some(estimator),
+            false,
+            &mut StdRng::seed_from_u64(1342),
+        )
+        .unwrap();
+        assert_eq!(density.edges(), expected.edges());
+
+        let too_few = edge_list(&VertexFiltration::Custom(vec![1.0.into()]));
+        assert!(matches!(
+            too_few,
+            Err(DatasetError::VertexValuesMismatch {
+                expected: 4,
+                found: 1
+            })
+        ));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn girg_with_invalid_weight_exponent() {
+        let directory = unique_temp_path("invalid_girg");
+        let result = get_dataset_density_edge_list_in_directory(
+            &directory,
+            Dataset::RandomGeometricGraph {
+                n_points: 10,
+                radius: 0.15,
+                weight_exponent: Some(1.),
+            },
+            Threshold::KeepAll,
+            None,
+            false,
+            &mut StdRng::seed_from_u64(1278),
+        );
+        assert!(matches!(
+            result,
+            Err(DatasetError::InvalidWeightExponent(exponent)) if exponent == 1.
+        ));
+    }
+}
@@ -1,17 +1,31 @@
+//! Samplers of point clouds and random graphs. Each sampler has a `_with_rng` variant that takes
+//! the random number generator to use, so that the samples can be reproduced from a seed.
 use num::Float;
+use ordered_float::OrderedFloat;
 use rand::distributions::uniform::SampleUniform;
 use rand::distributions::Uniform;
 use rand::Rng;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::f64::consts::PI;
 
+use crate::distance_matrix::DistanceMatrix;
+use crate::points::transform::standard_normal;
 use crate::points::{Point, PointCloud};
 
 /// Sample n points from `\[0,1\]^DIM` uniformly.
 pub fn sample_random_points<T: Float + SampleUniform, const DIM: usize>(
     n: usize,
+) -> PointCloud<T, DIM> {
+    sample_random_points_with_rng(n, &mut rand::thread_rng())
+}
+
+/// Sample n points from `\[0,1\]^DIM` uniformly, using the given random number generator.
+pub fn sample_random_points_with_rng<T: Float + SampleUniform, R: Rng, const DIM: usize>(
+    n: usize,
+    rng: &mut R,
 ) -> PointCloud<T, DIM> {
     let point_distribution = Uniform::new(T::zero(), T::one());
-    let mut rng = rand::thread_rng();
     let mut point_cloud: PointCloud<T, DIM> = PointCloud::new();
     for _i in 0..n {
         let mut point_coordinates = [T::zero(); DIM];
@@ -26,10 +40,18 @@ pub fn sample_random_points<T: Float + SampleUniform, const DIM: usize>(
 }
 
 /// Sample points from a torus in `R^3`.
+///
+/// The two angles are drawn uniformly, so the points are denser on the inner side of the torus
+/// than on the outer side. This sampler is kept to reproduce the datasets of the paper, see
+/// [sample_uniform_torus] for points that are uniform with respect to the surface area.
 pub fn sample_torus(n: usize) -> PointCloud<f64, 3> {
+    sample_torus_with_rng(n, &mut rand::thread_rng())
+}
+
+/// Sample points from a torus in `R^3`, using the given random number generator.
+pub fn sample_torus_with_rng<R: Rng>(n: usize, rng: &mut R) -> PointCloud<f64, 3> {
     let radius = 0.5;
     let center_distance = 2.;
-    let mut rng = rand::thread_rng();
     let mut point_cloud = PointCloud::new();
     for _i in 0..n {
         let theta = rng.gen_range(0.0..1.0) * 2. * PI;
@@ -43,10 +65,115 @@ pub fn sample_torus(n: usize) -> PointCloud<f64, 3> {
     point_cloud
 }
 
+/// Samples n points uniformly, with respect to the surface area, from the torus in `R^3` around
+/// the `z` axis, whose tube has radius `minor_radius` and is centered at distance `major_radius`
+/// from the origin, and perturbs them with Gaussian noise of standard deviation `noise`.
+///
+/// The angle around the `z` axis is drawn uniformly, and the angle around the tube by rejection:
+/// the area element at angle `theta` is proportional to `major_radius + minor_radius * cos(theta)`,
+/// so an angle is kept with probability `|major_radius + minor_radius * cos(theta)|` divided by
+/// `major_radius + minor_radius`.
+pub fn sample_uniform_torus(
+    n: usize,
+    major_radius: f64,
+    minor_radius: f64,
+    noise: f64,
+) -> PointCloud<f64, 3> {
+    sample_uniform_torus_with_rng(
+        n,
+        major_radius,
+        minor_radius,
+        noise,
+        &mut rand::thread_rng(),
+    )
+}
+
+/// See [sample_uniform_torus]. Uses the given random number generator.
+pub fn sample_uniform_torus_with_rng<R: Rng>(
+    n: usize,
+    major_radius: f64,
+    minor_radius: f64,
+    noise: f64,
+    rng: &mut R,
+) -> PointCloud<f64, 3> {
+    let max_density = major_radius.abs() + minor_radius.abs();
+    let mut point_cloud = PointCloud::new();
+    while point_cloud.len() < n {
+        let theta = rng.gen_range(0.0..1.0) * 2. * PI;
+        let density = (major_radius + minor_radius * theta.cos()).abs();
+        if max_density > 0. && rng.gen_range(0.0..max_density) >= density {
+            continue;
+        }
+        let phi = rng.gen_range(0.0..1.0) * 2. * PI;
+        let x = (major_radius + minor_radius * theta.cos()) * phi.cos();
+        let y = (major_radius + minor_radius * theta.cos()) * phi.sin();
+        let z = minor_radius * theta.sin();
+        let mut point = Point([x, y, z]);
+        add_gaussian_noise(&mut point, noise, rng);
+        point_cloud.push_point(point);
+    }
+
+    point_cloud
+}
+
+/// Samples n points uniformly from the sphere in `R^DIM` centered at the origin of the given
+/// radius, and perturbs them with Gaussian noise of standard deviation `noise`. A proportion
+/// `outliers`, between 0 and 1, of the points are instead drawn uniformly from the cube
+/// `[-2 * radius, 2 * radius]^DIM`, and come after the points of the sphere.
+///
+/// Unlike [sample_noisy_sphere], which is kept to reproduce the datasets of the paper, the
+/// points of the sphere are normalized standard Gaussian vectors, which are exactly uniform, and
+/// the number of outliers is fixed instead of random.
+///
+/// Panics: if there are outliers and the radius is zero.
+pub fn sample_uniform_sphere<const DIM: usize>(
+    n: usize,
+    radius: f64,
+    noise: f64,
+    outliers: f64,
+) -> PointCloud<f64, DIM> {
+    sample_uniform_sphere_with_rng(n, radius, noise, outliers, &mut rand::thread_rng())
+}
+
+/// See [sample_uniform_sphere]. Uses the given random number generator.
+pub fn sample_uniform_sphere_with_rng<R: Rng, const DIM: usize>(
+    n: usize,
+    radius: f64,
+    noise: f64,
+    outliers: f64,
+    rng: &mut R,
+) -> PointCloud<f64, DIM> {
+    let n_outliers = ((n as f64) * outliers.clamp(0., 1.)).round() as usize;
+    let mut point_cloud = PointCloud::new();
+    while point_cloud.len() < n - n_outliers {
+        let mut point = Point([0.; DIM]);
+        for x in point.0.iter_mut() {
+            *x = standard_normal(rng);
+        }
+        let norm = point.norm();
+        // Almost never happens, but the direction of the origin is not defined.
+        if norm == 0. {
+            continue;
+        }
+        for x in point.0.iter_mut() {
+            *x *= radius / norm;
+        }
+        add_gaussian_noise(&mut point, noise, rng);
+        point_cloud.push_point(point);
+    }
+    add_outliers(n_outliers, 2. * radius.abs(), &mut point_cloud, rng);
+
+    point_cloud
+}
+
 /// A plane rolled up into a spiral in R^3.
 /// Equations are the same as in <https://jlmelville.github.io/smallvis/swisssne.html>.
 pub fn sample_swiss_roll(n: usize) -> PointCloud<f64, 3> {
-    let mut rng = rand::thread_rng();
+    sample_swiss_roll_with_rng(n, &mut rand::thread_rng())
+}
+
+/// See [sample_swiss_roll]. Uses the given random number generator.
+pub fn sample_swiss_roll_with_rng<R: Rng>(n: usize, rng: &mut R) -> PointCloud<f64, 3> {
     let mut point_cloud = PointCloud::new();
     for _i in 0..n {
         let phi = rng.gen_range(1.5..4.5) * PI;
@@ -62,7 +189,8 @@ pub fn sample_swiss_roll(n: usize) -> PointCloud<f64, 3> {
 }
 
 /// Draws n points from the unit sphere in R^DIM, and adds outliers from [-2, 2]^DIM.
-/// It can sample less points from a disc around the north pole.
+/// It can sample less points from a disc around the north pole. See [sample_uniform_sphere] for
+/// a sampler with a given radius, noise level, and number of outliers.
 ///
 /// The proportion of sampled points from the sphere is given in sample_weight.
 /// Also, the proportion of sampled points from the disc of radius north_pole_radius is given in north_pole_weight.
@@ -71,12 +199,28 @@ pub fn sample_noisy_sphere<T: Float + SampleUniform, const DIM: usize>(
     sample_weight: f32,
     north_pole_radius: T,
     north_pole_weight: f32,
+) -> PointCloud<T, DIM> {
+    sample_noisy_sphere_with_rng(
+        n,
+        sample_weight,
+        north_pole_radius,
+        north_pole_weight,
+        &mut rand::thread_rng(),
+    )
+}
+
+/// See [sample_noisy_sphere]. Uses the given random number generator.
+pub fn sample_noisy_sphere_with_rng<T: Float + SampleUniform, R: Rng, const DIM: usize>(
+    n: usize,
+    sample_weight: f32,
+    north_pole_radius: T,
+    north_pole_weight: f32,
+    rng: &mut R,
 ) -> PointCloud<T, DIM> {
     let mut north_pole = Point([T::zero(); DIM]);
     north_pole.0[DIM - 1] = T::one();
 
-    let mut rng = rand::thread_rng();
-    let mut cloud = PointCloud(Vec::new());
+    let mut cloud = PointCloud::new();
 
     let mut samples: usize = 0;
     for _i in 0..n {
@@ -85,11 +229,11 @@ pub fn sample_noisy_sphere<T: Float + SampleUniform, const DIM: usize>(
             samples += 1;
         }
     }
-    add_outliers(n - samples, T::from(2).unwrap(), &mut cloud, &mut rng);
+    add_outliers(n - samples, T::from(2).unwrap(), &mut cloud, rng);
 
     let uni_dist = Uniform::new(-T::one(), T::one());
     while cloud.len() < n {
-        let mut point = Point::random(&uni_dist, &mut rng);
+        let mut point = Point::random(&uni_dist, rng);
         let norm = point.norm();
 
         if norm < T::one() && norm != T::zero() {
@@ -98,10 +242,10 @@ pub fn sample_noisy_sphere<T: Float + SampleUniform, const DIM: usize>(
             if (point - north_pole).norm() < north_pole_radius {
                 let coin: f32 = rng.gen_range(0.0..1.0);
                 if coin < north_pole_weight {
-                    cloud.0.push(point);
+                    cloud.push_point(point);
                 }
             } else {
-                cloud.0.push(point);
+                cloud.push_point(point);
             }
         }
     }
@@ -118,6 +262,295 @@ fn add_outliers<T: Float + SampleUniform, R: Rng, const DIM: usize>(
     let uni_dist = Uniform::new(-limit * T::one(), limit * T::one());
     for _i in 0..n {
         let point = Point::random(&uni_dist, rng);
-        cloud.0.push(point);
+        cloud.push_point(point);
+    }
+}
+
+/// Samples n points from a mixture of `n_clusters` Gaussians in the plane, whose centers are drawn
+/// uniformly from `\[0,1\]^2`. To get clusters of different densities, the `i`-th cluster
+/// (counting from 0) is chosen with probability proportional to `i + 1`, and has standard
+/// deviation `noise * (i + 1) / n_clusters`.
+pub fn sample_gaussian_mixture(n: usize, n_clusters: usize, noise: f64) -> PointCloud<f64, 2> {
+    sample_gaussian_mixture_with_rng(n, n_clusters, noise, &mut rand::thread_rng())
+}
+
+/// See [sample_gaussian_mixture]. Uses the given random number generator.
+pub fn sample_gaussian_mixture_with_rng<R: Rng>(
+    n: usize,
+    n_clusters: usize,
+    noise: f64,
+    rng: &mut R,
+) -> PointCloud<f64, 2> {
+    let n_clusters = n_clusters.max(1);
+    let unit_square = Uniform::new(0., 1.);
+    let centers: Vec<Point<f64, 2>> = (0..n_clusters)
+        .map(|_| Point::random(&unit_square, rng))
+        .collect();
+
+    let total_weight = n_clusters * (n_clusters + 1) / 2;
+    let mut point_cloud = PointCloud::new();
+    for _i in 0..n {
+        // Pick the cluster i with probability (i + 1) / total_weight.
+        let mut ticket = rng.gen_range(0..total_weight);
+        let mut cluster = 0;
+        while ticket > cluster {
+            ticket -= cluster + 1;
+            cluster += 1;
+        }
+        let std_dev = noise * (cluster + 1) as f64 / n_clusters as f64;
+        let mut point = centers[cluster];
+        add_gaussian_noise(&mut point, std_dev, rng);
+        point_cloud.push_point(point);
+    }
+
+    point_cloud
+}
+
+/// Samples n points uniformly from the annulus of the plane centered at the origin, between the
+/// circles of radius 0.5 and 1, and perturbs them with Gaussian noise of standard deviation `noise`.
+pub fn sample_annulus(n: usize, noise: f64) -> PointCloud<f64, 2> {
+    sample_annulus_with_rng(n, noise, &mut rand::thread_rng())
+}
+
+/// See [sample_annulus]. Uses the given random number generator.
+pub fn sample_annulus_with_rng<R: Rng>(n: usize, noise: f64, rng: &mut R) -> PointCloud<f64, 2> {
+    let inner_radius: f64 = 0.5;
+    let outer_radius: f64 = 1.;
+    let mut point_cloud = PointCloud::new();
+    for _i in 0..n {
+        let theta = rng.gen_range(0.0..1.0) * 2. * PI;
+        // Sample the radius so that the points are uniform with respect to the area.
+        let r = rng
+            .gen_range(inner_radius.powi(2)..outer_radius.powi(2))
+            .sqrt();
+        let mut point = Point([r * theta.cos(), r * theta.sin()]);
+        add_gaussian_noise(&mut point, noise, rng);
+        point_cloud.push_point(point);
+    }
+
+    point_cloud
+}
+
+/// Samples n points from a figure eight, the lemniscate of Gerono `(sin t, sin t cos t)`, and
+/// perturbs them with Gaussian noise of standard deviation `noise`.
+pub fn sample_figure_eight(n: usize, noise: f64) -> PointCloud<f64, 2> {
+    sample_figure_eight_with_rng(n, noise, &mut rand::thread_rng())
+}
+
+/// See [sample_figure_eight]. Uses the given random number generator.
+pub fn sample_figure_eight_with_rng<R: Rng>(
+    n: usize,
+    noise: f64,
+    rng: &mut R,
+) -> PointCloud<f64, 2> {
+    let mut point_cloud = PointCloud::new();
+    for _i in 0..n {
+        let t = rng.gen_range(0.0..1.0) * 2. * PI;
+        let mut point = Point([t.sin(), t.sin() * t.cos()]);
+        add_gaussian_noise(&mut point, noise, rng);
+        point_cloud.push_point(point);
+    }
+
+    point_cloud
+}
+
+/// Samples a geometric inhomogeneous random graph on n vertices, placed uniformly in `\[0,1\]^2`,
+/// and returns the shortest path distances on it, where each edge is weighted by the Euclidean
+/// distance between its endpoints.
+///
+/// If `weight_exponent` is `None`, two vertices are adjacent if their distance is at most `radius`,
+/// giving a random geometric graph. Otherwise, each vertex `u` gets a weight `w_u` drawn from a
+/// Pareto distribution of the given exponent, which must be greater than 1, and two vertices are
+/// adjacent if their distance is at most `radius * sqrt(w_u * w_v)`: a few vertices of large weight
+/// act as hubs, making the density of the graph heterogeneous.
+///
+/// Only the vertices in the largest connected component are kept, so that all distances are
+/// finite. Hence, the resulting distance matrix can have less than n points.
+///
+/// Panics: if the weight exponent is not greater than 1.
+pub fn sample_random_geometric_graph(
+    n: usize,
+    radius: f64,
+    weight_exponent: Option<f64>,
+) -> DistanceMatrix<OrderedFloat<f64>> {
+    sample_random_geometric_graph_with_rng(n, radius, weight_exponent, &mut rand::thread_rng())
+}
+
+/// See [sample_random_geometric_graph]. Uses the given random number generator.
+pub fn sample_random_geometric_graph_with_rng<R: Rng>(
+    n: usize,
+    radius: f64,
+    weight_exponent: Option<f64>,
+    rng: &mut R,
+) -> DistanceMatrix<OrderedFloat<f64>> {
+    if let Some(exponent) = weight_exponent {
+        // The Pareto distribution has no finite mean otherwise, and the sampling below divides by
+        // `exponent - 1`.
+        assert!(
+            exponent > 1.,
+            "The weight exponent must be greater than 1, but it is {exponent}"
+        );
+    }
+    let points: PointCloud<f64, 2> = sample_random_points_with_rng(n, rng);
+    let weights: Vec<f64> = (0..n)
+        .map(|_| match weight_exponent {
+            // Inverse transform sampling of a Pareto distribution with minimum value 1.
+            Some(exponent) => (1. - rng.gen_range(0.0..1.0)).powf(-1. / (exponent - 1.)),
+            None => 1.,
+        })
+        .collect();
+
+    let mut adjacency: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+    for u in 0..n {
+        for v in (u + 1)..n {
+            let dist = points.0[u].euclidean_distance(&points.0[v]);
+            if dist <= radius * (weights[u] * weights[v]).sqrt() {
+                adjacency[u].push((v, dist));
+                adjacency[v].push((u, dist));
+            }
+        }
+    }
+
+    let all_distances: Vec<Vec<f64>> = (0..n).map(|u| shortest_paths(&adjacency, u)).collect();
+
+    // Find the largest connected component, as the vertices at finite distance of some vertex.
+    let mut component: Vec<usize> = Vec::new();
+    for distances in all_distances.iter() {
+        let reachable: Vec<usize> = (0..n).filter(|&v| distances[v].is_finite()).collect();
+        if reachable.len() > component.len() {
+            component = reachable;
+        }
+    }
+
+    let mut matrix = DistanceMatrix::new(component.len());
+    for (i, &u) in component.iter().enumerate() {
+        for (j, &v) in component.iter().enumerate().take(i) {
+            matrix.set(i, j, OrderedFloat(all_distances[u][v]));
+        }
+    }
+    matrix
+}
+
+/// Dijkstra's algorithm. Unreachable vertices are at infinite distance.
+fn shortest_paths(adjacency: &[Vec<(usize, f64)>], source: usize) -> Vec<f64> {
+    let mut distances = vec![f64::INFINITY; adjacency.len()];
+    let mut queue = BinaryHeap::new();
+    distances[source] = 0.;
+    queue.push(Reverse((OrderedFloat(0.), source)));
+    while let Some(Reverse((OrderedFloat(d), u))) = queue.pop() {
+        if d > distances[u] {
+            continue;
+        }
+        for &(v, length) in adjacency[u].iter() {
+            let new_distance = d + length;
+            if new_distance < distances[v] {
+                distances[v] = new_distance;
+                queue.push(Reverse((OrderedFloat(new_distance), v)));
+            }
+        }
+    }
+    distances
+}
+
+/// Adds Gaussian noise of the given standard deviation to each coordinate of the point.
+fn add_gaussian_noise<R: Rng, const DIM: usize>(
+    point: &mut Point<f64, DIM>,
+    std_dev: f64,
+    rng: &mut R,
+) {
+    if std_dev == 0. {
+        return;
+    }
+    for x in point.0.iter_mut() {
+        *x += std_dev * standard_normal::<f64, _>(rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::datasets::sampling::{
+        sample_gaussian_mixture, sample_random_geometric_graph, sample_torus_with_rng,
+        sample_uniform_sphere_with_rng, sample_uniform_torus_with_rng,
+    };
+    use crate::points::PointCloud;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn random_geometric_graph_is_connected() {
+        let matrix = sample_random_geometric_graph(100, 0.15, Some(2.5));
+        assert!(!matrix.is_empty());
+        for u in 0..matrix.len() {
+            for v in 0..u {
+                assert!(matrix.get(u, v).is_finite());
+                assert!(matrix.get(u, v).0 > 0.);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The weight exponent must be greater than 1")]
+    fn girg_with_weight_exponent_one() {
+        sample_random_geometric_graph(10, 0.15, Some(1.));
+    }
+
+    #[test]
+    fn gaussian_mixture_without_noise() {
+        let cloud = sample_gaussian_mixture(50, 1, 0.);
+        assert_eq!(cloud.len(), 50);
+        assert!(cloud.0.iter().all(|p| *p == cloud.0[0]));
+    }
+
+    #[test]
+    fn seeded_samples_are_reproducible() {
+        let first = sample_torus_with_rng(20, &mut StdRng::seed_from_u64(42));
+        let second = sample_torus_with_rng(20, &mut StdRng::seed_from_u64(42));
+        assert_eq!(first.0, second.0);
+    }
+
+    #[test]
+    fn uniform_torus_weights_the_outer_side() {
+        let (major_radius, minor_radius) = (2., 1.);
+        let n = 4000;
+        let torus = sample_uniform_torus_with_rng(
+            n,
+            major_radius,
+            minor_radius,
+            0.,
+            &mut StdRng::seed_from_u64(1375),
+        );
+        assert_eq!(torus.len(), n);
+        let mut inner = 0;
+        for p in torus.0.iter() {
+            let [x, y, z] = p.0;
+            let distance_to_axis = (x * x + y * y).sqrt();
+            let distance_to_tube = ((distance_to_axis - major_radius).powi(2) + z * z).sqrt();
+            assert!((distance_to_tube - minor_radius).abs() < 1e-9);
+            if distance_to_axis < major_radius {
+                inner += 1;
+            }
+        }
+        // The inner half of the tube has (pi * R - 2 * r) / (2 * pi * R) of the area, while
+        // uniform angles would put half of the points there.
+        let expected = (PI * major_radius - 2. * minor_radius) / (2. * PI * major_radius);
+        assert!((inner as f64 / n as f64 - expected).abs() < 0.03);
+    }
+
+    #[test]
+    fn uniform_sphere_with_outliers() {
+        let mut rng = StdRng::seed_from_u64(1375);
+        let sphere: PointCloud<f64, 3> =
+            sample_uniform_sphere_with_rng(1000, 3., 0., 0.1, &mut rng);
+        assert_eq!(sphere.len(), 1000);
+        for p in sphere.0[..900].iter() {
+            assert!((p.norm() - 3.).abs() < 1e-9);
+        }
+        for p in sphere.0[900..].iter() {
+            assert!(p.0.iter().all(|x| x.abs() <= 6.));
+        }
+        let mean_z = sphere.0[..900].iter().map(|p| p.0[2]).sum::<f64>() / 900.;
+        assert!(mean_z.abs() < 0.3);
     }
 }
@@ -3,48 +3,245 @@
 use num::Float;
 
 use crate::distance_matrix::DistanceMatrix;
+use crate::points::{Point, PointCloud, WeightedPointCloud};
 
 /// Density estimators. See [DensityEstimator::estimate].
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DensityEstimator<T: Copy> {
     /// Ball kernel density estimator with the given bandwidth.
     Ball(T),
     /// Gaussian kernel density estimator with the given bandwidth.
     Gaussian(T),
+    /// k-nearest neighbours density estimator with the given k: the density of a point is
+    /// inversely proportional to the distance to its k-th nearest neighbour.
+    KNearest(usize),
+}
+
+/// Data-driven choices of the bandwidth of a kernel density estimator. See
+/// [DensityEstimator::auto].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BandwidthSelection {
+    /// Silverman's rule of thumb for points of the given dimension. It is optimal for normally
+    /// distributed points, and tends to oversmooth otherwise. The standard deviation of the
+    /// points is recovered from the distances, which must be Euclidean.
+    Silverman { dimension: usize },
+    /// The median of the distances between the points, which is robust to outliers.
+    Median,
+    /// The mean distance of the points to their k-th nearest neighbour, which adapts to the local
+    /// scale of the points.
+    KNearest(usize),
 }
 
 impl<T: Float> DensityEstimator<T> {
     /// Returns a vector of the estimated densities of the points in the given distance matrix.
     pub fn estimate(&self, dists: &DistanceMatrix<T>) -> Vec<T> {
+        self.estimate_with(dists, None)
+    }
+
+    /// As [DensityEstimator::estimate], but each point counts as many times as its weight, which
+    /// must be non-negative. With weights one, the densities are the same as those of
+    /// [DensityEstimator::estimate]. For the k-nearest neighbours estimator, the k-th nearest
+    /// neighbour of a point is the closest point at which the weights of the other points add up to
+    /// k.
+    ///
+    /// Panics: if the number of weights is not the number of points.
+    pub fn estimate_weighted(&self, dists: &DistanceMatrix<T>, weights: &[T]) -> Vec<T> {
+        assert_eq!(
+            weights.len(),
+            dists.len(),
+            "There must be one weight per point"
+        );
+        self.estimate_with(dists, Some(weights))
+    }
+
+    /// Returns a vector of the estimated densities of the given points, with Euclidean distances.
+    pub fn estimate_points<const N: usize>(&self, points: &PointCloud<T, N>) -> Vec<T> {
+        self.estimate(&points.distance_matrix())
+    }
+
+    /// As [DensityEstimator::estimate_points], but the weights of the points are used as in
+    /// [DensityEstimator::estimate_weighted].
+    pub fn estimate_weighted_points<const N: usize>(
+        &self,
+        points: &WeightedPointCloud<T, N>,
+    ) -> Vec<T> {
+        self.estimate_with(&points.points().distance_matrix(), Some(points.weights()))
+    }
+
+    fn estimate_with(&self, dists: &DistanceMatrix<T>, weights: Option<&[T]>) -> Vec<T> {
         match self {
-            Self::Ball(radius) => ball_density(dists, *radius),
-            Self::Gaussian(radius) => gaussian_density(dists, *radius),
+            Self::Ball(radius) => ball_density(dists, *radius, weights),
+            Self::Gaussian(radius) => gaussian_density(dists, *radius, weights),
+            Self::KNearest(k) => k_nearest_density(dists, *k, weights),
+        }
+    }
+
+    /// The Gaussian kernel density estimator with a bandwidth chosen from the given distances.
+    /// If the chosen bandwidth is zero, for example because most points are repeated, it is
+    /// replaced by the smallest positive distance between the points. Returns None if there are
+    /// less than two points, or if they are all at distance zero, since then no bandwidth gives
+    /// well-defined densities.
+    pub fn auto(dists: &DistanceMatrix<T>, selection: BandwidthSelection) -> Option<Self> {
+        let smallest = smallest_positive_distance(dists)?;
+        let bandwidth = match selection {
+            BandwidthSelection::Silverman { dimension } => {
+                silverman_bandwidth(dists.len(), dimension, euclidean_total_variance(dists))
+            }
+            BandwidthSelection::Median => median_distance(dists),
+            BandwidthSelection::KNearest(k) => {
+                let distances = kth_neighbour_distances(dists, k, None);
+                if distances.is_empty() {
+                    T::zero()
+                } else {
+                    let total = distances.iter().fold(T::zero(), |acc, &d| acc + d);
+                    total / T::from(distances.len()).unwrap()
+                }
+            }
+        };
+        if bandwidth > T::zero() {
+            Some(Self::Gaussian(bandwidth))
+        } else {
+            Some(Self::Gaussian(smallest))
+        }
+    }
+
+    /// The Gaussian kernel density estimator with the bandwidth of Silverman's rule of thumb,
+    /// computed from the coordinates of the points. It agrees with [DensityEstimator::auto] with
+    /// [BandwidthSelection::Silverman] on the Euclidean distance matrix of the points, but takes
+    /// linear time. Returns None if there are less than two points, or if they are all equal.
+    pub fn silverman<const N: usize>(points: &PointCloud<T, N>) -> Option<Self> {
+        Self::silverman_with(&points.0, |_| T::one())
+    }
+
+    /// As [DensityEstimator::silverman], but the variance is weighted by the weights of the
+    /// points, and the number of points is replaced by the effective sample size
+    /// `(sum w)^2 / sum w^2`, rounded down. Returns None if the effective sample size is less than
+    /// two, or if the points of positive weight are all equal.
+    pub fn silverman_weighted<const N: usize>(points: &WeightedPointCloud<T, N>) -> Option<Self> {
+        Self::silverman_with(&points.points().0, |u| points.weight(u))
+    }
+
+    fn silverman_with<const N: usize>(
+        points: &[Point<T, N>],
+        weight: impl Fn(usize) -> T,
+    ) -> Option<Self> {
+        let (total_weight, total_squared_weight) = (0..points.len())
+            .map(&weight)
+            .fold((T::zero(), T::zero()), |(sum, squares), w| {
+                (sum + w, squares + w * w)
+            });
+        if total_weight <= T::zero() {
+            return None;
+        }
+        let mut mean = [T::zero(); N];
+        for (u, p) in points.iter().enumerate() {
+            let w = weight(u) / total_weight;
+            for (m, &x) in mean.iter_mut().zip(p.0.iter()) {
+                *m = *m + w * x;
+            }
+        }
+        let total_variance = points.iter().enumerate().fold(T::zero(), |acc, (u, p)| {
+            let w = weight(u) / total_weight;
+            p.0.iter()
+                .zip(mean.iter())
+                .fold(acc, |acc, (&x, &m)| acc + w * (x - m) * (x - m))
+        });
+        let n_effective = (total_weight * total_weight / total_squared_weight)
+            .to_usize()
+            .unwrap_or(0);
+        let bandwidth = silverman_bandwidth(n_effective, N, total_variance);
+        if bandwidth > T::zero() {
+            Some(Self::Gaussian(bandwidth))
+        } else {
+            None
+        }
+    }
+}
+
+/// The smallest positive distance between the points, or None if there is none.
+fn smallest_positive_distance<T: Float>(dists: &DistanceMatrix<T>) -> Option<T> {
+    let n = dists.len();
+    (0..n)
+        .flat_map(|u| ((u + 1)..n).map(move |v| *dists.get(u, v)))
+        .filter(|&dist| dist > T::zero())
+        .fold(None, |smallest, dist| match smallest {
+            Some(smallest) if smallest <= dist => Some(smallest),
+            _ => Some(dist),
+        })
+}
+
+/// Silverman's rule of thumb for n points of the given dimension, whose coordinates have the given
+/// sum of variances.
+fn silverman_bandwidth<T: Float>(n: usize, dimension: usize, total_variance: T) -> T {
+    if n <= 1 || dimension == 0 {
+        return T::zero();
+    }
+    let d = T::from(dimension).unwrap();
+    let two = T::from(2.).unwrap();
+    let four = T::from(4.).unwrap();
+    let sigma = (total_variance / d).sqrt();
+    let factor = (four / ((d + two) * T::from(n).unwrap())).powf(T::one() / (d + four));
+    factor * sigma
+}
+
+/// The sum of the variances of the coordinates of points with the given Euclidean distances.
+/// The sum of the squared distances between all pairs of n points is n^2 times the total variance.
+fn euclidean_total_variance<T: Float>(dists: &DistanceMatrix<T>) -> T {
+    let n = dists.len();
+    if n == 0 {
+        return T::zero();
+    }
+    let mut total = T::zero();
+    for u in 0..n {
+        for v in (u + 1)..n {
+            let dist = *dists.get(u, v);
+            total = total + dist * dist;
         }
     }
+    let n_f = T::from(n).unwrap();
+    total / (n_f * n_f)
 }
 
-fn ball_density<T: Float>(dists: &DistanceMatrix<T>, radius: T) -> Vec<T> {
+fn median_distance<T: Float>(dists: &DistanceMatrix<T>) -> T {
     let n = dists.len();
-    let mut densities: Vec<usize> = vec![0; n];
-    let mut total: usize = 0;
+    let mut distances: Vec<T> = (0..n)
+        .flat_map(|u| ((u + 1)..n).map(move |v| *dists.get(u, v)))
+        .collect();
+    if distances.is_empty() {
+        return T::zero();
+    }
+    let middle = distances.len() / 2;
+    let (_, median, _) = distances.select_nth_unstable_by(middle, |a, b| {
+        a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    *median
+}
+
+fn ball_density<T: Float>(dists: &DistanceMatrix<T>, radius: T, weights: Option<&[T]>) -> Vec<T> {
+    let n = dists.len();
+    let mut densities: Vec<T> = vec![T::zero(); n];
+    let mut total = T::zero();
     for u in 0..n {
         for v in (u + 1)..n {
             if *dists.get(u, v) <= radius {
-                densities[u] += 1;
-                densities[v] += 1;
-                total += 2;
+                let (w_u, w_v) = (weight(weights, u), weight(weights, v));
+                densities[u] = densities[u] + w_v;
+                densities[v] = densities[v] + w_u;
+                total = total + w_u + w_v;
             }
         }
     }
-    let total_f: T = T::from(total).unwrap();
-    densities
-        .into_iter()
-        .map(|x| T::from(x).unwrap() / total_f)
-        .collect()
+    densities.into_iter().map(|x| x / total).collect()
 }
 
 /// Simple (slow) algorithm to estimate the density via the Gaussian kernel.
-fn gaussian_density<T: Float>(dists: &DistanceMatrix<T>, radius: T) -> Vec<T> {
+fn gaussian_density<T: Float>(
+    dists: &DistanceMatrix<T>,
+    radius: T,
+    weights: Option<&[T]>,
+) -> Vec<T> {
     if dists.is_empty() {
         return vec![];
     }
@@ -56,18 +253,100 @@ fn gaussian_density<T: Float>(dists: &DistanceMatrix<T>, radius: T) -> Vec<T> {
         for v in (u + 1)..n {
             let dist = *dists.get(u, v);
             let incr = (-dist * dist / h).exp();
-            densities[u] = densities[u] + incr;
-            densities[v] = densities[v] + incr;
-            total = total + incr * T::from(2.).unwrap();
+            let (w_u, w_v) = (weight(weights, u), weight(weights, v));
+            densities[u] = densities[u] + incr * w_v;
+            densities[v] = densities[v] + incr * w_u;
+            total = total + incr * (w_u + w_v);
         }
     }
     densities.into_iter().map(|x| x / total).collect()
 }
 
+/// The inverse of the distance of each point to its k-th nearest neighbour, normalized to sum
+/// one. If k is at least the number of points, the farthest point is taken. A point with k other
+/// points at distance zero has infinite density, and the normalization only counts the finite
+/// densities.
+fn k_nearest_density<T: Float>(
+    dists: &DistanceMatrix<T>,
+    k: usize,
+    weights: Option<&[T]>,
+) -> Vec<T> {
+    let n = dists.len();
+    if n <= 1 {
+        return vec![T::one(); n];
+    }
+    let densities: Vec<T> = kth_neighbour_distances(dists, k, weights)
+        .into_iter()
+        .map(|kth| T::one() / kth)
+        .collect();
+    let total = densities
+        .iter()
+        .filter(|d| d.is_finite())
+        .fold(T::zero(), |acc, &d| acc + d);
+    densities.into_iter().map(|x| x / total).collect()
+}
+
+/// The distance of each point to its k-th nearest neighbour. If k is at least the number of
+/// points, the farthest point is taken. Empty if there are less than two points.
+///
+/// With weights, the k-th nearest neighbour is the closest point at which the weights of the other
+/// points add up to k, and the farthest point if they never do.
+fn kth_neighbour_distances<T: Float>(
+    dists: &DistanceMatrix<T>,
+    k: usize,
+    weights: Option<&[T]>,
+) -> Vec<T> {
+    let n = dists.len();
+    if n <= 1 {
+        return vec![];
+    }
+    let mut kth_distances = Vec::with_capacity(n);
+    let mut neighbour_distances = Vec::with_capacity(n - 1);
+    for u in 0..n {
+        neighbour_distances.clear();
+        neighbour_distances.extend((0..n).filter(|&v| v != u).map(|v| (*dists.get(u, v), v)));
+        let kth = match weights {
+            None => {
+                let (_, &mut (kth, _), _) = neighbour_distances
+                    .select_nth_unstable_by(k.clamp(1, n - 1) - 1, |a, b| {
+                        a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                kth
+            }
+            Some(weights) => {
+                neighbour_distances.sort_unstable_by(|a, b| {
+                    a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal)
+                });
+                let k_weight = T::from(k.max(1)).unwrap();
+                let mut accumulated = T::zero();
+                let mut kth = T::zero();
+                for &(dist, v) in neighbour_distances.iter() {
+                    accumulated = accumulated + weights[v];
+                    kth = dist;
+                    if accumulated >= k_weight {
+                        break;
+                    }
+                }
+                kth
+            }
+        };
+        kth_distances.push(kth);
+    }
+    kth_distances
+}
+
+/// The weight of the `u`-th point, which is one if there are no weights.
+fn weight<T: Float>(weights: Option<&[T]>, u: usize) -> T {
+    weights.map_or(T::one(), |weights| weights[u])
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::distance_matrix::density_estimation::{ball_density, gaussian_density};
+    use crate::distance_matrix::density_estimation::{
+        ball_density, gaussian_density, k_nearest_density, BandwidthSelection, DensityEstimator,
+    };
     use crate::distance_matrix::DistanceMatrix;
+    use crate::points::{PointCloud, WeightedPointCloud};
 
     #[test]
     fn ball_density_happy_case() {
@@ -75,7 +354,7 @@ mod tests {
         dists.set(0, 1, 0.4);
         dists.set(0, 2, 0.2);
         dists.set(1, 2, 0.2);
-        assert_eq!(ball_density(&dists, 0.2), [0.25, 0.25, 0.5]);
+        assert_eq!(ball_density(&dists, 0.2, None), [0.25, 0.25, 0.5]);
     }
 
     #[test]
@@ -85,8 +364,148 @@ mod tests {
         dists.set(0, 2, 0.2);
         dists.set(1, 2, 0.2);
         assert_eq!(
-            gaussian_density(&dists, 0.2),
+            gaussian_density(&dists, 0.2, None),
             [0.2750918911708629, 0.2750918911708629, 0.4498162176582741]
         );
     }
+
+    #[test]
+    fn k_nearest_density_happy_case() {
+        let mut dists = DistanceMatrix::new(3);
+        dists.set(0, 1, 0.4);
+        dists.set(0, 2, 0.2);
+        dists.set(1, 2, 0.2);
+        assert_eq!(
+            k_nearest_density(&dists, 1, None),
+            [1. / 3., 1. / 3., 1. / 3.]
+        );
+        assert_eq!(k_nearest_density(&dists, 2, None), [0.25, 0.25, 0.5]);
+        assert_eq!(k_nearest_density(&dists, 5, None), [0.25, 0.25, 0.5]);
+    }
+
+    #[test]
+    fn weighted_densities() {
+        let mut dists = DistanceMatrix::new(3);
+        dists.set(0, 1, 0.4);
+        dists.set(0, 2, 0.2);
+        dists.set(1, 2, 0.2);
+        let ones = [1., 1., 1.];
+        for estimator in [
+            DensityEstimator::Ball(0.2),
+            DensityEstimator::Gaussian(0.2),
+            DensityEstimator::KNearest(2),
+        ] {
+            assert_eq!(
+                estimator.estimate_weighted(&dists, &ones),
+                estimator.estimate(&dists)
+            );
+        }
+
+        // The third point counts twice, which evens out the densities.
+        let weights = [1., 1., 2.];
+        let third = 1. / 3.;
+        assert_eq!(
+            DensityEstimator::Ball(0.2).estimate_weighted(&dists, &weights),
+            [third, third, third]
+        );
+        assert_eq!(
+            DensityEstimator::KNearest(2).estimate_weighted(&dists, &weights),
+            [third, third, third]
+        );
+        // Unweighted, k = 5 is clamped to the farthest point. With weight 10, the first two points
+        // reach k = 5 at their closest neighbour.
+        assert_eq!(
+            DensityEstimator::KNearest(5).estimate_weighted(&dists, &[1., 1., 10.]),
+            [third, third, third]
+        );
+
+        let mut points: WeightedPointCloud<f64, 1> =
+            PointCloud::from(vec![[0.].into(), [0.2].into()]).into();
+        points.push_point([0.4].into(), 2.);
+        assert_eq!(points.weights(), &[1., 1., 2.][..]);
+        let by_points = DensityEstimator::Ball(0.2).estimate_weighted_points(&points);
+        let by_matrix = DensityEstimator::Ball(0.2)
+            .estimate_weighted(&points.points().distance_matrix(), &[1., 1., 2.]);
+        assert_eq!(by_points, by_matrix);
+        let (unweighted, _) = points.into_parts();
+        assert_eq!(
+            DensityEstimator::Ball(0.2).estimate_points(&unweighted),
+            DensityEstimator::Ball(0.2).estimate(&unweighted.distance_matrix())
+        );
+    }
+
+    fn gaussian_bandwidth(estimator: Option<DensityEstimator<f64>>) -> f64 {
+        match estimator {
+            Some(DensityEstimator::Gaussian(bandwidth)) => bandwidth,
+            _ => panic!("Expected a Gaussian estimator"),
+        }
+    }
+
+    #[test]
+    fn automatic_bandwidths() {
+        let mut dists = DistanceMatrix::new(3);
+        dists.set(0, 1, 0.4);
+        dists.set(0, 2, 0.2);
+        dists.set(1, 2, 0.2);
+        let median = DensityEstimator::auto(&dists, BandwidthSelection::Median);
+        assert_eq!(gaussian_bandwidth(median), 0.2);
+        let k_nearest = DensityEstimator::auto(&dists, BandwidthSelection::KNearest(2));
+        assert!((gaussian_bandwidth(k_nearest) - 1. / 3.).abs() < 1e-12);
+
+        // A square of side 2, whose coordinates have variance 1.
+        let mut points: PointCloud<f64, 2> = PointCloud::new();
+        for p in [[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]] {
+            points.push_point(p.into());
+        }
+        let expected = (4f64 / (4. * 4.)).powf(1. / 6.);
+        let from_points = gaussian_bandwidth(DensityEstimator::silverman(&points));
+        assert!((from_points - expected).abs() < 1e-12);
+        let weighted = WeightedPointCloud::with_weights(PointCloud(points.0.clone()), vec![2.; 4]);
+        let from_weighted = gaussian_bandwidth(DensityEstimator::silverman_weighted(&weighted));
+        assert!((from_weighted - expected).abs() < 1e-12);
+        let from_distances = gaussian_bandwidth(DensityEstimator::auto(
+            &points.distance_matrix(),
+            BandwidthSelection::Silverman { dimension: 2 },
+        ));
+        assert!((from_distances - expected).abs() < 1e-12);
+
+        let single = DistanceMatrix::<f64>::new(1);
+        for selection in [
+            BandwidthSelection::Median,
+            BandwidthSelection::KNearest(1),
+            BandwidthSelection::Silverman { dimension: 2 },
+        ] {
+            assert!(DensityEstimator::auto(&single, selection).is_none());
+        }
+    }
+
+    #[test]
+    fn automatic_bandwidths_of_duplicate_points() {
+        // Five copies of a point and two of another at distance 0.5, so that most distances, and
+        // the distances to the nearest neighbours, are zero.
+        let mut points: PointCloud<f64, 2> = PointCloud::new();
+        for p in [[0., 0.]; 5].into_iter().chain([[0.5, 0.]; 2]) {
+            points.push_point(p.into());
+        }
+        let dists = points.distance_matrix();
+        for selection in [
+            BandwidthSelection::Median,
+            BandwidthSelection::KNearest(1),
+            BandwidthSelection::Silverman { dimension: 0 },
+        ] {
+            let estimator = DensityEstimator::auto(&dists, selection);
+            assert_eq!(gaussian_bandwidth(estimator), 0.5);
+            let densities = estimator.unwrap().estimate(&dists);
+            assert!(densities.iter().all(|density| density.is_finite()));
+        }
+
+        let mut equal: PointCloud<f64, 2> = PointCloud::new();
+        for _ in 0..3 {
+            equal.push_point([1., 1.].into());
+        }
+        assert!(
+            DensityEstimator::auto(&equal.distance_matrix(), BandwidthSelection::Median).is_none()
+        );
+        assert!(DensityEstimator::silverman(&equal).is_none());
+    }
 }
@@ -1,4 +1,8 @@
 //! Utilities to read graphs and distance matrices from files.
+//!
+//! [read_lower_triangular_distance_matrix] reads the whitespace-separated files of the built-in
+//! datasets, while [read_distance_matrix_flexible] also skips comments and blank lines, accepts
+//! commas and semicolons as separators, and reports the line of the first malformed row.
 use num::Zero;
 use std::fmt::Display;
 use std::io;
@@ -6,7 +10,7 @@ use std::io::BufRead;
 use std::str::FromStr;
 
 use crate::distance_matrix::DistanceMatrix;
-use crate::io_utils::parse;
+use crate::io_utils::{invalid_data, parse};
 
 /// Read a space separated lower triangular distance matrix.
 /// It can also be used to read a full distance matrix.
@@ -24,7 +28,87 @@ where
             if v > u {
                 break;
             }
-            matrix.set(u, v, parse(d)?);
+            let d: T = parse(d)?;
+            if u == v && !d.is_zero() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("The distance of point {} to itself is not zero: {}", u, d),
+                ));
+            }
+            matrix.set(u, v, d);
+        }
+    }
+
+    Ok(matrix)
+}
+
+/// Read a lower triangular, or full, distance matrix, one row per line, more leniently than
+/// [read_lower_triangular_distance_matrix]:
+/// - everything after a `#` is a comment, and lines that are blank after removing comments are
+///   skipped,
+/// - the values may be separated by whitespace, commas or semicolons, and consecutive separators
+///   count as one.
+///
+/// The format is given by the first row: if it has a single value the matrix is lower
+/// triangular, with the diagonal, so that the `i`-th row has `i + 1` values, and otherwise it
+/// must be square, with as many values in each row as there are rows, and symmetric. The
+/// diagonal must be zero. The errors have kind [io::ErrorKind::InvalidData] and give the line
+/// numbers, counted from 1, of the offending values.
+pub fn read_distance_matrix_flexible<T: Zero + Clone + PartialEq + FromStr + Display, R: BufRead>(
+    r: R,
+) -> io::Result<DistanceMatrix<T>>
+where
+    <T as FromStr>::Err: std::error::Error + 'static + Send + Sync,
+{
+    let mut rows: Vec<(usize, Vec<T>)> = Vec::new();
+    for (idx, line) in r.lines().enumerate() {
+        let line = line?;
+        let line_number = idx + 1;
+        let content = line.split('#').next().unwrap_or_default();
+        let values = content
+            .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                parse(value).map_err(|err| {
+                    invalid_data(format!("Invalid distance at line {}: {}", line_number, err))
+                })
+            })
+            .collect::<io::Result<Vec<T>>>()?;
+        if !values.is_empty() {
+            rows.push((line_number, values));
+        }
+    }
+
+    let n = rows.len();
+    let square = matches!(rows.first(), Some((_, values)) if values.len() > 1);
+    let mut matrix = DistanceMatrix::new(n);
+    for (u, (line_number, values)) in rows.iter().enumerate() {
+        let expected = if square { n } else { u + 1 };
+        if values.len() != expected {
+            return Err(invalid_data(format!(
+                "Expected {} distances at line {}, as row {} of a {} matrix with {} rows, found {}",
+                expected,
+                line_number,
+                u,
+                if square { "square" } else { "lower triangular" },
+                n,
+                values.len()
+            )));
+        }
+        for (v, d) in values.iter().enumerate().take(u + 1) {
+            if u == v && !d.is_zero() {
+                return Err(invalid_data(format!(
+                    "The distance of point {} to itself is not zero at line {}: {}",
+                    u, line_number, d
+                )));
+            }
+            if square && rows[v].1[u] != *d {
+                return Err(invalid_data(format!(
+                    "The matrix is not symmetric: the distance between points {} and {} is {} at line {}, but {} at line {}",
+                    u, v, d, line_number, rows[v].1[u], rows[v].0
+                )));
+            }
+            matrix.set(u, v, d.clone());
         }
     }
 
@@ -35,7 +119,9 @@ where
 mod tests {
     use std::io::BufReader;
 
-    use crate::distance_matrix::input::read_lower_triangular_distance_matrix;
+    use crate::distance_matrix::input::{
+        read_distance_matrix_flexible, read_lower_triangular_distance_matrix,
+    };
     use crate::distance_matrix::DistanceMatrix;
 
     #[test]
@@ -63,4 +149,55 @@ mod tests {
         assert_eq!(*matrix.get(2, 0), 123.);
         assert_eq!(*matrix.get(2, 1), 456.2112);
     }
+
+    #[test]
+    fn read_distance_matrix_nonzero_diagonal() {
+        let s = "0
+                      0.1 2";
+        let result: std::io::Result<DistanceMatrix<f64>> =
+            read_lower_triangular_distance_matrix(BufReader::new(s.as_bytes()));
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(std::io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    fn read_distance_matrix_flexible_formats() {
+        let lower = "# Three points.\n\
+                     0\n\
+                     \n\
+                     0.1, 0 # The second point.\n\
+                     123.;456.2112;0\n";
+        let full = "0 0.1 123.\n\
+                    0.1,\t0, 456.2112\n\
+                    # The last point.\n\
+                    123., 456.2112, 0";
+        for s in [lower, full] {
+            let matrix: DistanceMatrix<f64> =
+                read_distance_matrix_flexible(BufReader::new(s.as_bytes())).unwrap();
+            assert_eq!(matrix.len(), 3);
+            assert_eq!(*matrix.get(1, 0), 0.1);
+            assert_eq!(*matrix.get(2, 0), 123.);
+            assert_eq!(*matrix.get(2, 1), 456.2112);
+        }
+    }
+
+    #[test]
+    fn read_distance_matrix_flexible_errors() {
+        let cases = [
+            ("0\n# Comment.\n0.1 0 3", "at line 3"),
+            ("0 1 2\n1 0\n2 3 0", "at line 2"),
+            ("0 1\n2 0", "at line 2, but 1 at line 1"),
+            ("0\n1 2", "to itself is not zero at line 2"),
+            ("0\n\n1 x", "Invalid distance at line 3"),
+        ];
+        for (s, message) in cases {
+            let err = read_distance_matrix_flexible::<f64, _>(BufReader::new(s.as_bytes()))
+                .err()
+                .unwrap();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            assert!(err.to_string().contains(message), "{}", err);
+        }
+    }
 }
@@ -1,7 +1,8 @@
 //! Distance matrices: reading them, outputting them, and handling them,
 //! including density estimation.
-use num::Zero;
+use num::{Float, Zero};
 use std::cmp::max;
+use thiserror::Error;
 
 use crate::edges::{BareEdge, FilteredEdge};
 use crate::OneCriticalGrade;
@@ -27,20 +28,207 @@ impl<T: Zero + Clone> DistanceMatrix<T> {
     }
 
     /// Set the distance between two points.
-    /// Panics when u == v.
+    /// Panics: when u == v and the distance is not zero, or when a point is out of range. See
+    /// [DistanceMatrix::try_set] for a non-panicking version.
     pub fn set(&mut self, u: usize, v: usize, d: T) {
+        if let Err(err) = self.try_set(u, v, d) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Set the distance between two points, or return an error if u == v and the distance is not
+    /// zero, or a point is out of range.
+    pub fn try_set(&mut self, u: usize, v: usize, d: T) -> Result<(), DistanceMatrixError> {
+        let n = self.distances.len();
+        if u >= n || v >= n {
+            return Err(DistanceMatrixError::PointOutOfRange { u, v, n });
+        }
         if u == v {
             if !d.is_zero() {
-                panic!("The distance between the same vertex cannot be different from zero.");
+                return Err(DistanceMatrixError::NonZeroDiagonal(u));
             }
         } else {
             let (new_u, new_v) = max_min(u, v);
             self.distances[new_u][new_v] = d;
         }
+        Ok(())
+    }
+
+    /// Adds a point, given its distances to the existing points, in order, and returns its index,
+    /// which is the previous number of points. Returns an error if there is not one distance per
+    /// existing point.
+    pub fn push_point(
+        &mut self,
+        distances_to_existing: &[T],
+    ) -> Result<usize, DistanceMatrixError> {
+        let n = self.len();
+        if distances_to_existing.len() != n {
+            return Err(DistanceMatrixError::PointDistancesMismatch {
+                expected: n,
+                found: distances_to_existing.len(),
+            });
+        }
+        let mut row = Vec::with_capacity(n + 1);
+        row.extend_from_slice(distances_to_existing);
+        row.push(T::zero());
+        self.distances.push(row);
+        Ok(n)
+    }
+}
+
+impl<T> DistanceMatrix<T> {
+    /// Removes the point of the given index, and returns its distances to the other points, in
+    /// order. The points after it are shifted down by one index. Returns an error if there is no
+    /// such point.
+    pub fn remove_point(&mut self, idx: usize) -> Result<Vec<T>, DistanceMatrixError> {
+        let n = self.len();
+        if idx >= n {
+            return Err(DistanceMatrixError::PointIndexOutOfRange { idx, n });
+        }
+        let mut removed = self.distances.remove(idx);
+        // Drop the diagonal entry.
+        removed.pop();
+        for row in self.distances[idx..].iter_mut() {
+            removed.push(row.remove(idx));
+        }
+        Ok(removed)
+    }
+}
+
+impl<T: Zero + Clone> DistanceMatrix<T> {
+    /// Creates a distance matrix from its condensed form: the entries above the diagonal, row by
+    /// row, as returned by `scipy.spatial.distance.pdist`. That is, the distances between points
+    /// `(0, 1)`, `(0, 2)`, ..., `(0, n - 1)`, `(1, 2)`, and so on.
+    ///
+    /// Returns an error if the number of entries is not `n * (n - 1) / 2` for some `n`. As in
+    /// scipy, no entries give a distance matrix of a single point.
+    pub fn from_condensed(condensed: &[T]) -> Result<DistanceMatrix<T>, DistanceMatrixError> {
+        let n = condensed_size(condensed.len())
+            .ok_or(DistanceMatrixError::InvalidCondensedLength(condensed.len()))?;
+        let mut matrix = DistanceMatrix::new(n);
+        let mut entries = condensed.iter();
+        for u in 0..n {
+            for (v, d) in ((u + 1)..n).zip(&mut entries) {
+                matrix.distances[v][u] = d.clone();
+            }
+        }
+        Ok(matrix)
     }
 }
 
+impl<T: Float> DistanceMatrix<T> {
+    /// Creates a distance matrix from a square matrix, given by its rows, that may not be
+    /// symmetric, like the ones of some dissimilarities. The distance between points `u` and `v`
+    /// combines the entries `(u, v)` and `(v, u)` as given by `symmetrization`. The diagonal is
+    /// ignored.
+    ///
+    /// Returns an error if some row does not have as many entries as there are rows.
+    pub fn symmetrize(
+        rows: &[Vec<T>],
+        symmetrization: Symmetrization,
+    ) -> Result<DistanceMatrix<T>, DistanceMatrixError> {
+        let n = rows.len();
+        if let Some((row, entries)) = rows.iter().enumerate().find(|(_, r)| r.len() != n) {
+            return Err(DistanceMatrixError::NotSquare {
+                row,
+                len: entries.len(),
+                n,
+            });
+        }
+        let mut matrix = DistanceMatrix::new(n);
+        for u in 0..n {
+            for v in 0..u {
+                matrix.distances[u][v] = symmetrization.combine(rows[u][v], rows[v][u]);
+            }
+        }
+        Ok(matrix)
+    }
+}
+
+impl<T: Clone> DistanceMatrix<T> {
+    /// Returns the condensed form of the distance matrix, as taken by
+    /// [DistanceMatrix::from_condensed].
+    pub fn to_condensed(&self) -> Vec<T> {
+        let n = self.len();
+        let mut condensed = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+        for u in 0..n {
+            for v in (u + 1)..n {
+                condensed.push(self.get(u, v).clone());
+            }
+        }
+        condensed
+    }
+}
+
+/// How [DistanceMatrix::symmetrize] combines the two entries of a pair of points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Symmetrization {
+    /// The mean of both entries.
+    #[default]
+    Average,
+    /// The maximum of both entries.
+    Max,
+    /// The minimum of both entries.
+    Min,
+}
+
+impl Symmetrization {
+    fn combine<T: Float>(self, a: T, b: T) -> T {
+        match self {
+            Symmetrization::Average => (a + b) / (T::one() + T::one()),
+            Symmetrization::Max => a.max(b),
+            Symmetrization::Min => a.min(b),
+        }
+    }
+}
+
+/// The error of building a distance matrix from a condensed or asymmetric matrix, or of setting,
+/// adding, or removing some of its entries.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMatrixError {
+    #[error(
+        "A condensed distance matrix cannot have {0} entries: it is not n * (n - 1) / 2 for any n"
+    )]
+    InvalidCondensedLength(usize),
+
+    #[error("Row {row} of the matrix has {len} entries, but the matrix has {n} rows")]
+    NotSquare { row: usize, len: usize, n: usize },
+
+    #[error("The distance between point {0} and itself cannot be different from zero")]
+    NonZeroDiagonal(usize),
+
+    #[error("The pair of points ({u}, {v}) is out of the range 0..{n}")]
+    PointOutOfRange { u: usize, v: usize, n: usize },
+
+    #[error("The point {idx} is out of the range 0..{n}")]
+    PointIndexOutOfRange { idx: usize, n: usize },
+
+    #[error(
+        "A new point needs {expected} distances to the existing points, but {found} were given"
+    )]
+    PointDistancesMismatch { expected: usize, found: usize },
+}
+
+/// The number of points of a condensed distance matrix with the given number of entries, if any.
+fn condensed_size(entries: usize) -> Option<usize> {
+    // Solve n * (n - 1) / 2 = entries, and check the rounded solution.
+    let estimate = ((1. + (1. + 8. * entries as f64).sqrt()) / 2.).round() as usize;
+    (estimate.saturating_sub(1)..=estimate + 1)
+        .find(|&n| n >= 1 && n.checked_mul(n - 1).map(|m| m / 2) == Some(entries))
+}
+
 impl<T> DistanceMatrix<T> {
+    /// Creates a distance matrix from the rows of its lower triangular part, including the
+    /// diagonal, so the row of index `u` has length `u + 1`.
+    pub(crate) fn from_lower_triangular_rows(distances: Vec<Vec<T>>) -> DistanceMatrix<T> {
+        debug_assert!(distances
+            .iter()
+            .enumerate()
+            .all(|(u, row)| row.len() == u + 1));
+        DistanceMatrix { distances }
+    }
+
     /// Returns the number of points.
     pub fn len(&self) -> usize {
         self.distances.len()
@@ -101,7 +289,7 @@ pub struct EdgeIterator<'a, T> {
 }
 
 impl<'a, T> EdgeIterator<'a, T> {
-    fn new(matrix: &DistanceMatrix<T>) -> EdgeIterator<T> {
+    fn new(matrix: &DistanceMatrix<T>) -> EdgeIterator<'_, T> {
         EdgeIterator {
             matrix,
             current_edge: BareEdge(0, 0),
@@ -144,7 +332,7 @@ fn max_min(u: usize, v: usize) -> (usize, usize) {
 
 #[cfg(test)]
 mod tests {
-    use crate::distance_matrix::DistanceMatrix;
+    use crate::distance_matrix::{DistanceMatrix, DistanceMatrixError, Symmetrization};
     use crate::edges::BareEdge;
     use crate::edges::FilteredEdge;
     use crate::OneCriticalGrade;
@@ -205,4 +393,84 @@ mod tests {
         assert_eq!(*m.percentile(0.50), OrderedFloat(0.5));
         assert_eq!(*m.percentile(0.55), OrderedFloat(0.5));
     }
+
+    #[test]
+    fn condensed_round_trip() {
+        let condensed = [1, 2, 3, 4, 5, 6];
+        let m = DistanceMatrix::from_condensed(&condensed).unwrap();
+        assert_eq!(m.len(), 4);
+        assert_eq!(*m.get(0, 3), 3);
+        assert_eq!(*m.get(2, 1), 4);
+        assert_eq!(*m.get(2, 3), 6);
+        assert_eq!(m.to_condensed(), condensed);
+
+        assert_eq!(DistanceMatrix::<i32>::from_condensed(&[]).unwrap().len(), 1);
+        assert_eq!(
+            DistanceMatrix::from_condensed(&[1, 2]).err(),
+            Some(DistanceMatrixError::InvalidCondensedLength(2))
+        );
+    }
+
+    #[test]
+    fn symmetrize_asymmetric_matrix() {
+        let rows = vec![vec![0., 1., 4.], vec![3., 0., 2.], vec![2., 2., 0.]];
+        let average = DistanceMatrix::symmetrize(&rows, Symmetrization::Average).unwrap();
+        assert_eq!(average.to_condensed(), vec![2., 3., 2.]);
+        let maximum = DistanceMatrix::symmetrize(&rows, Symmetrization::Max).unwrap();
+        assert_eq!(maximum.to_condensed(), vec![3., 4., 2.]);
+        let minimum = DistanceMatrix::symmetrize(&rows, Symmetrization::Min).unwrap();
+        assert_eq!(minimum.to_condensed(), vec![1., 2., 2.]);
+
+        assert_eq!(
+            DistanceMatrix::symmetrize(&rows[..2], Symmetrization::Max).err(),
+            Some(DistanceMatrixError::NotSquare {
+                row: 0,
+                len: 3,
+                n: 2
+            })
+        );
+    }
+
+    #[test]
+    fn try_set_rejects_invalid_entries() {
+        let mut m: DistanceMatrix<i32> = DistanceMatrix::new(3);
+        assert_eq!(m.try_set(0, 2, 5), Ok(()));
+        assert_eq!(*m.get(2, 0), 5);
+        assert_eq!(m.try_set(1, 1, 0), Ok(()));
+        assert_eq!(
+            m.try_set(1, 1, 2),
+            Err(DistanceMatrixError::NonZeroDiagonal(1))
+        );
+        assert_eq!(
+            m.try_set(0, 3, 1),
+            Err(DistanceMatrixError::PointOutOfRange { u: 0, v: 3, n: 3 })
+        );
+    }
+
+    #[test]
+    fn push_and_remove_points() {
+        let mut m: DistanceMatrix<i32> = DistanceMatrix::new(0);
+        assert_eq!(m.push_point(&[]), Ok(0));
+        assert_eq!(m.push_point(&[1]), Ok(1));
+        assert_eq!(m.push_point(&[2, 3]), Ok(2));
+        assert_eq!(m.push_point(&[4, 5, 6]), Ok(3));
+        assert_eq!(m.to_condensed(), vec![1, 2, 4, 3, 5, 6]);
+        assert_eq!(
+            m.push_point(&[1, 2]),
+            Err(DistanceMatrixError::PointDistancesMismatch {
+                expected: 4,
+                found: 2
+            })
+        );
+
+        assert_eq!(m.remove_point(1), Ok(vec![1, 3, 5]));
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.to_condensed(), vec![2, 4, 6]);
+        assert_eq!(m.remove_point(2), Ok(vec![4, 6]));
+        assert_eq!(m.to_condensed(), vec![2]);
+        assert_eq!(
+            m.remove_point(2),
+            Err(DistanceMatrixError::PointIndexOutOfRange { idx: 2, n: 2 })
+        );
+    }
 }
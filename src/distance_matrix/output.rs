@@ -1,9 +1,17 @@
 //! Utilities to save distance matrices to disk.
+//!
+//! Besides the whitespace-separated lower triangular and square formats, matrices can be written
+//! as CSV, with [write_csv_distance_matrix], and, with the `npy` feature, as NumPy arrays, with
+//! [write_npy_distance_matrix], to load them from Python without conversion scripts.
 use std::fmt::Display;
 use std::io;
 use std::io::Write;
 
+#[cfg(feature = "npy")]
+use num::ToPrimitive;
+
 use crate::distance_matrix::DistanceMatrix;
+use crate::io_utils::invalid_input;
 
 /// Write a lower triangular distance matrix.
 ///
@@ -34,13 +42,103 @@ pub fn write_lower_triangular_distance_matrix<T: Display, W: Write>(
 pub fn write_distance_matrix<T: Display, W: Write>(
     distance_matrix: &DistanceMatrix<T>,
     writer: &mut W,
+) -> io::Result<()> {
+    write_square_distance_matrix(distance_matrix, writer, " ")
+}
+
+/// Write a full distance matrix as CSV, one row per line with comma-separated values. If `labels`
+/// is given, the first line is a header with a label for each point, quoted if needed. Without a
+/// header, the output can be read back with
+/// [read_distance_matrix_flexible](crate::distance_matrix::input::read_distance_matrix_flexible).
+///
+/// Returns an error of kind [io::ErrorKind::InvalidInput] if the number of labels is not the
+/// number of points.
+///
+/// Example output, with the labels `a`, `b` and `c`:
+/// ```
+/// "a,b,c\n0,0.1,0.2\n0.1,0,0.3\n0.2,0.3,0\n";
+/// ```
+pub fn write_csv_distance_matrix<T: Display, W: Write>(
+    distance_matrix: &DistanceMatrix<T>,
+    writer: &mut W,
+    labels: Option<&[&str]>,
+) -> io::Result<()> {
+    if let Some(labels) = labels {
+        if labels.len() != distance_matrix.len() {
+            return Err(invalid_input(format!(
+                "Expected a label for each of the {} points, found {}",
+                distance_matrix.len(),
+                labels.len()
+            )));
+        }
+        for (i, label) in labels.iter().enumerate() {
+            if i != 0 {
+                write!(writer, ",")?;
+            }
+            if label.contains(&[',', '"', '\n', '\r'][..]) {
+                write!(writer, "\"{}\"", label.replace('"', "\"\""))?;
+            } else {
+                write!(writer, "{}", label)?;
+            }
+        }
+        writeln!(writer)?;
+    }
+    write_square_distance_matrix(distance_matrix, writer, ",")
+}
+
+/// Write a full distance matrix in the NumPy `.npy` format, as a square array of little-endian
+/// 64-bit floats in row-major order, to load it with `numpy.load`.
+///
+/// Returns an error of kind [io::ErrorKind::InvalidInput] if a distance cannot be converted to a
+/// 64-bit float.
+#[cfg(feature = "npy")]
+pub fn write_npy_distance_matrix<T: ToPrimitive + Display, W: Write>(
+    distance_matrix: &DistanceMatrix<T>,
+    writer: &mut W,
+) -> io::Result<()> {
+    let n_vertices = distance_matrix.len();
+    let mut header = format!(
+        "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}",
+        n_vertices, n_vertices
+    );
+    // The magic string, the version and the length of the header take 10 bytes, and the data
+    // must start at a multiple of 64 bytes, after a newline.
+    let padding = (64 - (10 + header.len() + 1) % 64) % 64;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+    let header_len = u16::try_from(header.len())
+        .map_err(|_| invalid_input("The header of the .npy file is too long"))?;
+
+    writer.write_all(b"\x93NUMPY\x01\x00")?;
+    writer.write_all(&header_len.to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    for u in 0..n_vertices {
+        for v in 0..n_vertices {
+            let d = distance_matrix.get(u, v);
+            let d = d.to_f64().ok_or_else(|| {
+                invalid_input(format!(
+                    "The distance {} between points {} and {} is not a 64-bit float",
+                    d, u, v
+                ))
+            })?;
+            writer.write_all(&d.to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_square_distance_matrix<T: Display, W: Write>(
+    distance_matrix: &DistanceMatrix<T>,
+    writer: &mut W,
+    separator: &str,
 ) -> io::Result<()> {
     let n_vertices = distance_matrix.len();
 
     for u in 0..n_vertices {
         for v in 0..n_vertices {
             if v != 0 {
-                write!(writer, " ")?;
+                write!(writer, "{}", separator)?;
             }
             write!(writer, "{}", distance_matrix.get(u, v))?;
         }
@@ -49,3 +147,66 @@ pub fn write_distance_matrix<T: Display, W: Write>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::distance_matrix::input::read_distance_matrix_flexible;
+    use crate::distance_matrix::output::{write_csv_distance_matrix, write_distance_matrix};
+    use crate::distance_matrix::DistanceMatrix;
+
+    fn triangle() -> DistanceMatrix<f64> {
+        let mut matrix = DistanceMatrix::new(3);
+        matrix.set(1, 0, 0.1);
+        matrix.set(2, 0, 0.2);
+        matrix.set(2, 1, 0.3);
+        matrix
+    }
+
+    #[test]
+    fn write_square_and_csv_distance_matrices() {
+        let matrix = triangle();
+        let mut square = Vec::new();
+        write_distance_matrix(&matrix, &mut square).unwrap();
+        assert_eq!(square, b"0 0.1 0.2\n0.1 0 0.3\n0.2 0.3 0\n");
+
+        let mut csv = Vec::new();
+        write_csv_distance_matrix(&matrix, &mut csv, None).unwrap();
+        assert_eq!(csv, b"0,0.1,0.2\n0.1,0,0.3\n0.2,0.3,0\n");
+        let read: DistanceMatrix<f64> =
+            read_distance_matrix_flexible(BufReader::new(&csv[..])).unwrap();
+        assert_eq!(read.to_condensed(), matrix.to_condensed());
+
+        let mut with_header = Vec::new();
+        write_csv_distance_matrix(&matrix, &mut with_header, Some(&["a", "b,c", "d\"e"])).unwrap();
+        assert!(with_header.starts_with(b"a,\"b,c\",\"d\"\"e\"\n0,0.1,0.2\n"));
+
+        let err = write_csv_distance_matrix(&matrix, &mut Vec::new(), Some(&["a"])).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "npy")]
+    #[test]
+    fn write_npy_distance_matrix_header_and_data() {
+        let matrix = triangle();
+        let mut npy = Vec::new();
+        crate::distance_matrix::output::write_npy_distance_matrix(&matrix, &mut npy).unwrap();
+        assert!(npy.starts_with(b"\x93NUMPY\x01\x00"));
+        let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+        assert!(header.contains("'shape': (3, 3)"));
+        assert!(header.ends_with('\n'));
+
+        let data: Vec<f64> = npy[10 + header_len..]
+            .chunks(8)
+            .map(|bytes| {
+                let mut le_bytes = [0; 8];
+                le_bytes.copy_from_slice(bytes);
+                f64::from_le_bytes(le_bytes)
+            })
+            .collect();
+        assert_eq!(data, [0., 0.1, 0.2, 0.1, 0., 0.3, 0.2, 0.3, 0.]);
+    }
+}
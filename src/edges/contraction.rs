@@ -0,0 +1,204 @@
+//! Contraction of edges, which merges their endpoints into a single vertex. Unlike the removal
+//! algorithms, which only remove edges that keep the homology of the clique complex at every
+//! grade, contractions are an approximation in general, meant for aggressive reductions where
+//! some error is acceptable.
+use rustc_hash::FxHashMap;
+
+use crate::edges::{BareEdge, EdgeList, EdgeListError, FilteredEdge};
+use crate::{CriticalGrade, OneCriticalGrade, Value};
+
+/// The result of contracting an edge, see [EdgeList::contract_edge].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeContraction {
+    /// The least endpoint of the contracted edge, into which both endpoints were merged.
+    pub kept_vertex: usize,
+    /// The greatest endpoint of the contracted edge, which is left without edges.
+    pub removed_vertex: usize,
+    /// The number of edges that were merged into another one, because they had the same
+    /// endpoints after relabeling the removed vertex.
+    pub merged_edges: usize,
+    /// Whether the clique bifiltration of the contracted edge list is known to have the same
+    /// homology as the original one at every grade. This is the case when the contracted edge
+    /// enters with the vertices, at grade zero, and one endpoint strongly dominates the other one
+    /// at every grade. Otherwise, the contraction is an approximation.
+    pub preserves_homology: bool,
+}
+
+impl<VF: Value, const N: usize> EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>> {
+    /// Contracts the given edge: its greatest endpoint is relabeled to its least endpoint, and the
+    /// edge itself is removed. If an edge has the same endpoints as a previous one after
+    /// relabeling, they are merged into the first one, whose grade becomes the minimum of both
+    /// grades, see [OneCriticalGrade::meet]. The order of the remaining edges is kept, and the
+    /// number of vertices does not change, see [EdgeList::compact_vertices] to drop the removed
+    /// vertex.
+    ///
+    /// The merged grades make the edges enter earlier, so the homology of the clique complex is
+    /// not preserved in general, see [EdgeContraction::preserves_homology].
+    ///
+    /// Returns an error if the edge is a self-loop or not in the edge list, in which case the
+    /// edge list is not modified.
+    pub fn contract_edge(&mut self, edge: BareEdge) -> Result<EdgeContraction, EdgeListError> {
+        let BareEdge(u, v) = edge;
+        if u == v {
+            return Err(EdgeListError::SelfLoop(edge));
+        }
+        let (kept, removed) = (u.min(v), u.max(v));
+        let is_contracted = |e: &FilteredEdge<OneCriticalGrade<VF, N>>| {
+            e.edge.0.min(e.edge.1) == kept && e.edge.0.max(e.edge.1) == removed
+        };
+        let contracted_grade = self
+            .edge_iter()
+            .filter(|e| is_contracted(e))
+            .map(|e| e.grade)
+            .reduce(|a, b| a.meet(&b))
+            .ok_or(EdgeListError::EdgeNotFound(edge))?;
+        let preserves_homology = contracted_grade.lte(&OneCriticalGrade::zero())
+            && (strongly_dominates(self, kept, removed) || strongly_dominates(self, removed, kept));
+
+        let mut merged_edges = 0;
+        // The position in the contracted edges of the edge between the kept vertex and each of
+        // its neighbours.
+        let mut kept_edges: FxHashMap<usize, usize> = FxHashMap::default();
+        let mut contracted: Vec<FilteredEdge<OneCriticalGrade<VF, N>>> =
+            Vec::with_capacity(self.len());
+        for mut e in self.edges.drain(..) {
+            if is_contracted(&e) {
+                continue;
+            }
+            if e.edge.0 == removed {
+                e.edge.0 = kept;
+            }
+            if e.edge.1 == removed {
+                e.edge.1 = kept;
+            }
+            let neighbour = match e.edge {
+                BareEdge(a, b) if a == kept => b,
+                BareEdge(a, b) if b == kept => a,
+                _ => {
+                    contracted.push(e);
+                    continue;
+                }
+            };
+            match kept_edges.get(&neighbour) {
+                Some(&idx) => {
+                    contracted[idx].grade = contracted[idx].grade.meet(&e.grade);
+                    merged_edges += 1;
+                }
+                None => {
+                    kept_edges.insert(neighbour, contracted.len());
+                    contracted.push(e);
+                }
+            }
+        }
+        self.edges = contracted;
+
+        Ok(EdgeContraction {
+            kept_vertex: kept,
+            removed_vertex: removed,
+            merged_edges,
+            preserves_homology,
+        })
+    }
+}
+
+/// Whether, for every edge between `dominated` and a vertex w other than `dominating`, there is an
+/// edge between `dominating` and w with a lesser or equal grade. Together with an edge between both
+/// vertices at grade zero, the closed neighbourhood of `dominating` contains the one of
+/// `dominated` at every grade, and removing `dominated` is a strong collapse.
+fn strongly_dominates<VF: Value, const N: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    dominating: usize,
+    dominated: usize,
+) -> bool {
+    let mut dominating_grades: FxHashMap<usize, Vec<OneCriticalGrade<VF, N>>> =
+        FxHashMap::default();
+    for e in edge_list.edge_iter() {
+        let BareEdge(a, b) = e.edge;
+        if a == dominating || b == dominating {
+            let neighbour = if a == dominating { b } else { a };
+            dominating_grades
+                .entry(neighbour)
+                .or_default()
+                .push(e.grade);
+        }
+    }
+    edge_list.edge_iter().all(|e| {
+        let BareEdge(a, b) = e.edge;
+        if a != dominated && b != dominated {
+            return true;
+        }
+        let neighbour = if a == dominated { b } else { a };
+        neighbour == dominating
+            || dominating_grades
+                .get(&neighbour)
+                .into_iter()
+                .flatten()
+                .any(|g| g.lte(&e.grade))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::edges::{BareEdge, EdgeContraction, EdgeList, EdgeListError, FilteredEdge};
+    use crate::OneCriticalGrade;
+
+    fn edge(u: usize, v: usize, grade: [usize; 2]) -> FilteredEdge<OneCriticalGrade<usize, 2>> {
+        FilteredEdge {
+            edge: BareEdge(u, v),
+            grade: OneCriticalGrade(grade),
+        }
+    }
+
+    #[test]
+    fn contract_edge_merges_duplicate_edges() {
+        // A square 0-1-2-3 with the diagonal 1-3. Contracting 1-3 merges 0-3 into 0-1, and 2-3
+        // into 1-2.
+        let mut edge_list: EdgeList<_> = vec![
+            edge(0, 1, [1, 2]),
+            edge(1, 2, [1, 1]),
+            edge(2, 3, [0, 3]),
+            edge(0, 3, [2, 1]),
+            edge(1, 3, [2, 2]),
+        ]
+        .into();
+        let contraction = edge_list.contract_edge(BareEdge(3, 1)).unwrap();
+        assert_eq!(
+            contraction,
+            EdgeContraction {
+                kept_vertex: 1,
+                removed_vertex: 3,
+                merged_edges: 2,
+                preserves_homology: false,
+            }
+        );
+        assert_eq!(edge_list.edges(), &[edge(0, 1, [1, 1]), edge(1, 2, [0, 1])]);
+        assert_eq!(edge_list.n_vertices, 4);
+
+        assert_eq!(
+            edge_list.contract_edge(BareEdge(0, 2)),
+            Err(EdgeListError::EdgeNotFound(BareEdge(0, 2)))
+        );
+        assert_eq!(
+            edge_list.contract_edge(BareEdge(1, 1)),
+            Err(EdgeListError::SelfLoop(BareEdge(1, 1)))
+        );
+        assert_eq!(edge_list.len(), 2);
+    }
+
+    #[test]
+    fn contracting_a_dominated_vertex_preserves_homology() {
+        // Vertex 2 is dominated by vertex 0, and the edge between them enters at grade zero.
+        let mut edge_list: EdgeList<_> = vec![
+            edge(0, 1, [1, 0]),
+            edge(0, 2, [0, 0]),
+            edge(1, 2, [1, 1]),
+            edge(0, 3, [0, 1]),
+            edge(2, 3, [2, 1]),
+        ]
+        .into();
+        let contraction = edge_list.contract_edge(BareEdge(0, 2)).unwrap();
+        assert!(contraction.preserves_homology);
+        assert_eq!(edge_list.edges(), &[edge(0, 1, [1, 0]), edge(0, 3, [0, 1])]);
+    }
+}
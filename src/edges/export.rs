@@ -0,0 +1,151 @@
+//! Export of filtered graphs to the DOT and GraphML formats, to visualize them with tools like
+//! Graphviz, Gephi, or yEd.
+//!
+//! Each edge carries its grade, as one attribute per parameter, `grade_0`, `grade_1`, and so on,
+//! and a boolean attribute `removed`. Both writers optionally take a second list of edges, for
+//! example the edges removed by one of the algorithms of [crate::removal], which are written with
+//! `removed` set to true. In DOT, removed edges are also drawn dashed and in red.
+use std::io;
+
+use crate::edges::{Edge, EdgeList, FilteredEdge};
+use crate::{OneCriticalGrade, Value};
+
+/// Writes the graph in the DOT format of Graphviz. Edges are labelled by their grade.
+/// The edges of `removed`, if given, are marked as removed. See the
+/// [module documentation](self).
+pub fn write_dot<VF: Value, W: io::Write, const N: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    removed: Option<&EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>>,
+    w: &mut W,
+) -> io::Result<()> {
+    writeln!(w, "graph {{")?;
+    for v in 0..n_vertices(edge_list, removed) {
+        writeln!(w, "  {};", v)?;
+    }
+    for (edge, is_removed) in tagged_edges(edge_list, removed) {
+        write!(
+            w,
+            "  {} -- {} [label=\"{}\"",
+            edge.edge.0, edge.edge.1, edge.grade
+        )?;
+        for (parameter, value) in edge.grade.0.iter().enumerate() {
+            write!(w, ", grade_{}=\"{}\"", parameter, value)?;
+        }
+        write!(w, ", removed={}", is_removed)?;
+        if is_removed {
+            write!(w, ", color=red, style=dashed")?;
+        }
+        writeln!(w, "];")?;
+    }
+    writeln!(w, "}}")
+}
+
+/// Writes the graph in the GraphML format. The edges of `removed`, if given, are marked as
+/// removed. See the [module documentation](self).
+pub fn write_graphml<VF: Value, W: io::Write, const N: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    removed: Option<&EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>>,
+    w: &mut W,
+) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    for parameter in 0..N {
+        writeln!(
+            w,
+            r#"  <key id="grade_{0}" for="edge" attr.name="grade_{0}" attr.type="double"/>"#,
+            parameter
+        )?;
+    }
+    writeln!(
+        w,
+        r#"  <key id="removed" for="edge" attr.name="removed" attr.type="boolean"><default>false</default></key>"#
+    )?;
+    writeln!(w, r#"  <graph id="G" edgedefault="undirected">"#)?;
+    for v in 0..n_vertices(edge_list, removed) {
+        writeln!(w, r#"    <node id="n{}"/>"#, v)?;
+    }
+    for (edge, is_removed) in tagged_edges(edge_list, removed) {
+        writeln!(
+            w,
+            r#"    <edge source="n{}" target="n{}">"#,
+            edge.edge.0, edge.edge.1
+        )?;
+        for (parameter, value) in edge.grade.0.iter().enumerate() {
+            writeln!(
+                w,
+                r#"      <data key="grade_{}">{}</data>"#,
+                parameter, value
+            )?;
+        }
+        writeln!(w, r#"      <data key="removed">{}</data>"#, is_removed)?;
+        writeln!(w, "    </edge>")?;
+    }
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</graphml>")
+}
+
+fn n_vertices<E>(edge_list: &EdgeList<E>, removed: Option<&EdgeList<E>>) -> usize {
+    removed.map_or(edge_list.n_vertices, |removed| {
+        edge_list.n_vertices.max(removed.n_vertices)
+    })
+}
+
+/// The edges of the list, followed by the removed edges, together with whether they are removed.
+fn tagged_edges<'a, E: Edge>(
+    edge_list: &'a EdgeList<E>,
+    removed: Option<&'a EdgeList<E>>,
+) -> impl Iterator<Item = (&'a E, bool)> {
+    let kept = edge_list.edge_iter().map(|e| (e, false));
+    let removed_edges = removed
+        .into_iter()
+        .flat_map(|removed| removed.edge_iter().map(|e| (e, true)));
+    kept.chain(removed_edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::edges::export::{write_dot, write_graphml};
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::OneCriticalGrade;
+
+    fn edge(u: usize, v: usize, grade: [usize; 2]) -> FilteredEdge<OneCriticalGrade<usize, 2>> {
+        FilteredEdge {
+            grade: OneCriticalGrade(grade),
+            edge: BareEdge(u, v),
+        }
+    }
+
+    #[test]
+    fn write_dot_with_removed_edges() {
+        let kept: EdgeList<_> = vec![edge(0, 1, [1, 2])].into();
+        let removed: EdgeList<_> = vec![edge(1, 2, [3, 4])].into();
+        let mut out = Vec::new();
+        write_dot(&kept, Some(&removed), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "graph {\n  0;\n  1;\n  2;\n  \
+            0 -- 1 [label=\"1 2\", grade_0=\"1\", grade_1=\"2\", removed=false];\n  \
+            1 -- 2 [label=\"3 4\", grade_0=\"3\", grade_1=\"4\", removed=true, color=red, style=dashed];\n}\n"
+        );
+    }
+
+    #[test]
+    fn write_graphml_edges() {
+        let kept: EdgeList<_> = vec![edge(0, 1, [1, 2]), edge(0, 2, [0, 5])].into();
+        let mut out = Vec::new();
+        write_graphml(&kept, None, &mut out).unwrap();
+        let graphml = String::from_utf8(out).unwrap();
+        assert_eq!(graphml.matches("<node ").count(), 3);
+        assert_eq!(graphml.matches("<edge ").count(), 2);
+        assert!(graphml.contains(
+            "    <edge source=\"n0\" target=\"n2\">\n      \
+            <data key=\"grade_0\">0</data>\n      \
+            <data key=\"grade_1\">5</data>\n      \
+            <data key=\"removed\">false</data>\n    </edge>\n"
+        ));
+        assert!(graphml.ends_with("  </graph>\n</graphml>\n"));
+    }
+}
@@ -0,0 +1,289 @@
+//! Edge lists whose grades are stored as one column per parameter, see [GradedEdgeList].
+use std::cmp::Ordering;
+
+use crate::edges::{BareEdge, EdgeList, EdgeListError, FilteredEdge};
+use crate::{OneCriticalGrade, Value};
+
+/// A list of 1-critical filtered edges stored as a struct of arrays: the endpoints of the edges
+/// in one vector, and the values of each parameter of their grades in another one.
+///
+/// An [EdgeList] of [FilteredEdge]s stores each grade next to its edge, so sorting it moves whole
+/// records, and scanning the values of one parameter reads the rest of the record too. This list
+/// sorts by computing a permutation of the indices of the edges and then moving each column once,
+/// and gives access to each parameter as a contiguous slice, see [GradedEdgeList::parameter].
+/// Convert from and to an [EdgeList] with [From], or with [GradedEdgeList::to_edge_list].
+///
+/// On the uniform sample of 1500 points, with about 1.1 million edges, the maximum of one
+/// parameter is about 7 times faster to compute on this list, while sorting it is about 15%
+/// slower, see the `graded_edge_list` benchmark in `benches/hot_paths.rs`. The removal
+/// algorithms keep using an [EdgeList]: they read each edge together with its grade, so they do
+/// not gain from the columns, and converting the list would only add a copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GradedEdgeList<VF, const N: usize> {
+    /// Total number of vertices.
+    pub n_vertices: usize,
+    edges: Vec<BareEdge>,
+    parameters: [Vec<VF>; N],
+}
+
+impl<VF: Value, const N: usize> GradedEdgeList<VF, N> {
+    /// New empty edge list.
+    pub fn new(n_vertices: usize) -> Self {
+        Self {
+            n_vertices,
+            edges: Vec::new(),
+            parameters: [(); N].map(|_| Vec::new()),
+        }
+    }
+
+    /// Returns the number of edges.
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns whether there are edges.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the endpoints of the edges.
+    pub fn edges(&self) -> &[BareEdge] {
+        &self.edges
+    }
+
+    /// Returns the values of the given parameter of the grades of the edges, in the order of the
+    /// edges.
+    ///
+    /// Panics: if `parameter` is not less than `N`.
+    pub fn parameter(&self, parameter: usize) -> &[VF] {
+        &self.parameters[parameter]
+    }
+
+    /// Returns the grade of the `i`-th edge, or None if there are not that many edges.
+    pub fn grade(&self, i: usize) -> Option<OneCriticalGrade<VF, N>> {
+        (i < self.len()).then(|| self.grade_unchecked(i))
+    }
+
+    /// Returns the `i`-th edge with its grade, or None if there are not that many edges.
+    pub fn edge(&self, i: usize) -> Option<FilteredEdge<OneCriticalGrade<VF, N>>> {
+        (i < self.len()).then(|| self.edge_unchecked(i))
+    }
+
+    /// Returns an iterator over the edges with their grades.
+    pub fn edge_iter(&self) -> impl Iterator<Item = FilteredEdge<OneCriticalGrade<VF, N>>> + '_ {
+        (0..self.len()).map(|i| self.edge_unchecked(i))
+    }
+
+    /// Adds an edge to the graph.
+    /// Panics: if the edge to add is a self-loop. See [GradedEdgeList::try_add_edge] for a
+    /// non-panicking version.
+    pub fn add_edge(&mut self, e: FilteredEdge<OneCriticalGrade<VF, N>>) {
+        let BareEdge(u, v) = e.edge;
+        assert_ne!(u, v, "Trying to add a self loop to a graph");
+
+        self.n_vertices = self.n_vertices.max(u.max(v) + 1);
+        self.edges.push(e.edge);
+        for (column, value) in self.parameters.iter_mut().zip(e.grade.0) {
+            column.push(value);
+        }
+    }
+
+    /// Adds an edge to the graph, or returns an error if the edge is a self-loop.
+    pub fn try_add_edge(
+        &mut self,
+        e: FilteredEdge<OneCriticalGrade<VF, N>>,
+    ) -> Result<(), EdgeListError> {
+        if e.edge.0 == e.edge.1 {
+            return Err(EdgeListError::SelfLoop(e.edge));
+        }
+        self.add_edge(e);
+        Ok(())
+    }
+
+    /// Returns a copy of the edges as an [EdgeList], in the same order.
+    pub fn to_edge_list(&self) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>> {
+        EdgeList {
+            n_vertices: self.n_vertices,
+            edges: self.edge_iter().collect(),
+        }
+    }
+
+    /// Sort the filtered edges lexicographically in increasing order, as
+    /// [EdgeList::sort_lexicographically].
+    pub fn sort_lexicographically(&mut self) {
+        self.sort_by(|list, a, b| list.cmp_lexicographically(a, b))
+    }
+
+    /// Reverse sort the filtered edges lexicographically, as
+    /// [EdgeList::sort_reverse_lexicographically].
+    pub fn sort_reverse_lexicographically(&mut self) {
+        self.sort_by(|list, a, b| list.cmp_lexicographically(b, a))
+    }
+
+    /// Sort the filtered edges colexicographically in increasing order, as
+    /// [EdgeList::sort_colexicographically].
+    pub fn sort_colexicographically(&mut self) {
+        self.sort_by(|list, a, b| list.cmp_colexicographically(a, b))
+    }
+
+    /// Reverse sort the filtered edges colexicographically, as
+    /// [EdgeList::sort_reverse_colexicographically].
+    pub fn sort_reverse_colexicographically(&mut self) {
+        self.sort_by(|list, a, b| list.cmp_colexicographically(b, a))
+    }
+
+    fn grade_unchecked(&self, i: usize) -> OneCriticalGrade<VF, N> {
+        let mut grade = [VF::zero(); N];
+        for (value, column) in grade.iter_mut().zip(self.parameters.iter()) {
+            *value = column[i];
+        }
+        OneCriticalGrade(grade)
+    }
+
+    fn edge_unchecked(&self, i: usize) -> FilteredEdge<OneCriticalGrade<VF, N>> {
+        FilteredEdge {
+            grade: self.grade_unchecked(i),
+            edge: self.edges[i],
+        }
+    }
+
+    /// Compares the grades of the `a`-th and `b`-th edges lexicographically, and resolves ties by
+    /// comparing the edges, as the order of [FilteredEdge].
+    fn cmp_lexicographically(&self, a: usize, b: usize) -> Ordering {
+        self.parameters
+            .iter()
+            .map(|column| column[a].cmp(&column[b]))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| self.edges[a].cmp(&self.edges[b]))
+    }
+
+    /// As [GradedEdgeList::cmp_lexicographically], but compares the grades colexicographically.
+    fn cmp_colexicographically(&self, a: usize, b: usize) -> Ordering {
+        self.parameters
+            .iter()
+            .rev()
+            .map(|column| column[a].cmp(&column[b]))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| self.edges[a].cmp(&self.edges[b]))
+    }
+
+    /// Sorts the indices of the edges by the given comparison, and then moves the edges and each
+    /// column of values to their sorted positions. The sort is stable.
+    fn sort_by(&mut self, cmp: impl Fn(&Self, usize, usize) -> Ordering) {
+        let mut permutation: Vec<usize> = (0..self.len()).collect();
+        permutation.sort_by(|&a, &b| cmp(self, a, b));
+        self.edges = permutation.iter().map(|&i| self.edges[i]).collect();
+        for column in self.parameters.iter_mut() {
+            *column = permutation.iter().map(|&i| column[i]).collect();
+        }
+    }
+}
+
+impl<VF: Value, const N: usize> From<EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>>
+    for GradedEdgeList<VF, N>
+{
+    fn from(edge_list: EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>) -> Self {
+        let mut graded = Self::new(edge_list.n_vertices);
+        graded.edges = edge_list.edges.iter().map(|e| e.edge).collect();
+        for (p, column) in graded.parameters.iter_mut().enumerate() {
+            *column = edge_list.edges.iter().map(|e| e.grade.0[p]).collect();
+        }
+        graded
+    }
+}
+
+impl<VF: Value, const N: usize> From<GradedEdgeList<VF, N>>
+    for EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>
+{
+    fn from(graded: GradedEdgeList<VF, N>) -> Self {
+        graded.to_edge_list()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::{BareEdge, EdgeList, EdgeListError, FilteredEdge, GradedEdgeList};
+    use crate::{OneCriticalGrade, Value};
+
+    #[test]
+    fn graded_edge_list_sorts_as_edge_list() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Uniform { n_points: 40 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let graded = GradedEdgeList::from(edges.clone());
+        assert_eq!(graded.len(), edges.len());
+        assert_eq!(graded.to_edge_list().edges(), edges.edges());
+        assert_eq!(graded.edge(3), Some(edges.edges()[3]));
+        assert_eq!(graded.grade(edges.len()), None);
+
+        assert_sorts_agree(
+            &graded,
+            &edges,
+            GradedEdgeList::sort_lexicographically,
+            EdgeList::sort_lexicographically,
+        );
+        assert_sorts_agree(
+            &graded,
+            &edges,
+            GradedEdgeList::sort_reverse_lexicographically,
+            EdgeList::sort_reverse_lexicographically,
+        );
+        assert_sorts_agree(
+            &graded,
+            &edges,
+            GradedEdgeList::sort_colexicographically,
+            EdgeList::sort_colexicographically,
+        );
+        assert_sorts_agree(
+            &graded,
+            &edges,
+            GradedEdgeList::sort_reverse_colexicographically,
+            EdgeList::sort_reverse_colexicographically,
+        );
+    }
+
+    fn assert_sorts_agree<VF: Value, const N: usize>(
+        graded: &GradedEdgeList<VF, N>,
+        edges: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+        sort_graded: impl Fn(&mut GradedEdgeList<VF, N>),
+        sort_edges: impl Fn(&mut EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>),
+    ) {
+        let mut sorted_graded = graded.clone();
+        sort_graded(&mut sorted_graded);
+        let mut sorted_edges = edges.clone();
+        sort_edges(&mut sorted_edges);
+        assert_eq!(EdgeList::from(sorted_graded).edges(), sorted_edges.edges());
+    }
+
+    #[test]
+    fn graded_edge_list_columns() {
+        let mut graded: GradedEdgeList<usize, 2> = GradedEdgeList::new(0);
+        graded.add_edge(FilteredEdge {
+            grade: OneCriticalGrade([2, 1]),
+            edge: BareEdge(0, 1),
+        });
+        graded.add_edge(FilteredEdge {
+            grade: OneCriticalGrade([1, 3]),
+            edge: BareEdge(1, 4),
+        });
+        assert_eq!(
+            graded.try_add_edge(FilteredEdge {
+                grade: OneCriticalGrade([0, 0]),
+                edge: BareEdge(2, 2),
+            }),
+            Err(EdgeListError::SelfLoop(BareEdge(2, 2)))
+        );
+        assert_eq!(graded.n_vertices, 5);
+        assert_eq!(graded.parameter(0), &[2, 1]);
+        assert_eq!(graded.parameter(1), &[1, 3]);
+
+        graded.sort_lexicographically();
+        assert_eq!(graded.edges(), &[BareEdge(1, 4), BareEdge(0, 1)]);
+        assert_eq!(graded.parameter(1), &[3, 1]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Lower-star multifiltrations of graphs, from functions on their vertices.
+//!
+//! Given a graph and, for each vertex, a value of each of `N` functions, the lower-star
+//! multifiltration adds each edge at the grade whose coordinates are the maxima of the values of
+//! its endpoints. This builds bifiltrations of meshes or images, where the functions are, for
+//! example, a height and an intensity, instead of a distance and a density.
+//!
+//! Edge lists do not store the grades of vertices: like in the rest of this crate, all vertices
+//! are present from the minimum grade. In the lower-star multifiltration a vertex enters at its
+//! own values, so the two differ in homology 0 below the grades of the vertices, but their flag
+//! complexes agree on every simplex of dimension at least 1.
+use crate::edges::{BareEdge, Edge, EdgeList, EdgeListError, FilteredEdge};
+use crate::{OneCriticalGrade, Value};
+
+/// Returns the lower-star multifiltration of the graph, in which each edge has, as grade, the
+/// componentwise maximum of the values of its endpoints, given by `vertex_values`. The edges keep
+/// their order, and the number of vertices is the one of the graph.
+///
+/// Returns an error if an endpoint of an edge has no values, or if an edge is a self-loop.
+pub fn lower_star<VF: Value, const N: usize>(
+    graph: &EdgeList<BareEdge>,
+    vertex_values: &[[VF; N]],
+) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>, EdgeListError> {
+    let mut filtered = EdgeList::new(graph.n_vertices);
+    for &edge in graph.edge_iter() {
+        let values = |vertex: usize| {
+            vertex_values
+                .get(vertex)
+                .map(|values| OneCriticalGrade(*values))
+                .ok_or(EdgeListError::VertexOutOfRange(edge, vertex_values.len()))
+        };
+        let (grade_u, grade_v) = (values(edge.u())?, values(edge.v())?);
+        let mut grade = grade_u;
+        for (value, other) in grade.0.iter_mut().zip(grade_v.0) {
+            *value = (*value).max(other);
+        }
+        filtered.try_add_edge(FilteredEdge { grade, edge })?;
+    }
+    Ok(filtered)
+}
+
+/// As [lower_star], with two functions on the vertices, given as the values `f` and `g` of each
+/// vertex. The lower-star bifiltration has the values of `f` as its first parameter and the ones
+/// of `g` as its second.
+///
+/// Returns an error if an endpoint of an edge has no value in `f` or `g`, or if an edge is a
+/// self-loop.
+pub fn lower_star_bifiltration<VF: Value>(
+    graph: &EdgeList<BareEdge>,
+    f: &[VF],
+    g: &[VF],
+) -> Result<EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>, EdgeListError> {
+    let vertex_values: Vec<[VF; 2]> = f.iter().zip(g).map(|(&f, &g)| [f, g]).collect();
+    lower_star(graph, &vertex_values)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::edges::lower_star::{lower_star, lower_star_bifiltration};
+    use crate::edges::{BareEdge, EdgeList, EdgeListError};
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn lower_star_of_a_path() {
+        let mut path: EdgeList<BareEdge> = EdgeList::new(4);
+        path.add_edge(BareEdge(0, 1));
+        path.add_edge(BareEdge(1, 2));
+        path.add_edge(BareEdge(2, 3));
+
+        let bifiltered = lower_star_bifiltration(&path, &[0, 3, 1, 2], &[5, 1, 4, 0]).unwrap();
+        assert_eq!(bifiltered.n_vertices, 4);
+        let grades: Vec<_> = bifiltered.edge_iter().map(|e| e.grade).collect();
+        assert_eq!(
+            grades,
+            vec![
+                OneCriticalGrade([3, 5]),
+                OneCriticalGrade([3, 4]),
+                OneCriticalGrade([2, 4])
+            ]
+        );
+
+        let trifiltered = lower_star(&path, &[[0, 0, 1], [1, 0, 0], [0, 1, 0], [0, 0, 0]]).unwrap();
+        assert_eq!(trifiltered.edges()[0].grade, OneCriticalGrade([1, 0, 1]));
+
+        assert_eq!(
+            lower_star_bifiltration(&path, &[0, 3, 1], &[5, 1, 4]).unwrap_err(),
+            EdgeListError::VertexOutOfRange(BareEdge(2, 3), 3)
+        );
+    }
+}
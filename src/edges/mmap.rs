@@ -0,0 +1,532 @@
+//! Edge lists stored in memory-mapped files, for graphs that do not fit in memory. Requires the
+//! `mmap` feature.
+//!
+//! A [MmapEdgeList] keeps its edges as fixed-size records in a file, and reads and writes them
+//! through a memory map, so the operating system pages them in and out as needed. The edges can
+//! be sorted in place, see [MmapEdgeList::sort], and then streamed through the strong removal
+//! with
+//! [remove_strongly_filtration_dominated_mmap](crate::removal::remove_strongly_filtration_dominated_mmap),
+//! which writes the remaining edges to another file. The removal still keeps the adjacency
+//! structure of the graph in memory, but not the edge list nor the list of remaining edges.
+//!
+//! The file starts with a header of four little-endian 64-bit words: the magic bytes
+//! `FDEDGES1`, the number of vertices, the number of parameters of the grades, and the number of
+//! edges. Each edge follows as a record of little-endian 64-bit words: its two endpoints, and
+//! then the value of each parameter of its grade, see [FixedSizeValue].
+//!
+//! As with any memory map, the file must not be modified or truncated, by this or another
+//! process, while it is mapped: the edges are read straight from the pages of the file, so such
+//! changes are undefined behaviour, or crash the process. [MmapEdgeList::open] and
+//! [MmapEdgeList::open_read_only] are unsafe for this reason. The files made by
+//! [MmapEdgeList::create] are new, and the same applies to them, but only code that knows their
+//! path can break it.
+use std::cmp::Ordering;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use memmap2::{Mmap, MmapMut};
+use ordered_float::OrderedFloat;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use thiserror::Error;
+
+use crate::edges::{BareEdge, EdgeList, EdgeListError, FilteredEdge};
+use crate::removal::EdgeOrder;
+use crate::{OneCriticalGrade, Value};
+
+const MAGIC: &[u8; 8] = b"FDEDGES1";
+const WORD_BYTES: usize = 8;
+const HEADER_BYTES: usize = 4 * WORD_BYTES;
+
+/// A value of a grade that is stored as a 64-bit word in a [MmapEdgeList].
+pub trait FixedSizeValue: Value {
+    /// The word that stores the value.
+    fn to_word(self) -> u64;
+
+    /// The value stored in the word.
+    fn from_word(word: u64) -> Self;
+}
+
+impl FixedSizeValue for usize {
+    fn to_word(self) -> u64 {
+        self as u64
+    }
+
+    fn from_word(word: u64) -> Self {
+        word as usize
+    }
+}
+
+impl FixedSizeValue for OrderedFloat<f64> {
+    fn to_word(self) -> u64 {
+        self.0.to_bits()
+    }
+
+    fn from_word(word: u64) -> Self {
+        OrderedFloat(f64::from_bits(word))
+    }
+}
+
+/// The errors of creating or opening a [MmapEdgeList].
+#[derive(Error, Debug)]
+pub enum MmapEdgeListError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    InvalidEdge(#[from] EdgeListError),
+
+    #[error("The file is not a memory-mapped edge list")]
+    InvalidHeader,
+
+    #[error("The file has grades with {found} parameters, but {expected} were expected")]
+    ParameterMismatch { expected: usize, found: usize },
+
+    #[error("The file has {found} bytes, but its header requires {expected}")]
+    InvalidLength { expected: usize, found: usize },
+
+    #[error("The edges of a memory-mapped edge list cannot be sorted in the order {0:?}")]
+    UnsupportedOrder(EdgeOrder),
+
+    #[error("The memory-mapped edge list was opened read-only")]
+    ReadOnly,
+
+    #[error("The output file {0:?} is the input file")]
+    SameFile(PathBuf),
+}
+
+/// The memory map of a [MmapEdgeList], which is writable unless the list was opened with
+/// [MmapEdgeList::open_read_only].
+enum Map {
+    ReadOnly(Mmap),
+    Writable(MmapMut),
+}
+
+impl Deref for Map {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Map::ReadOnly(map) => map,
+            Map::Writable(map) => map,
+        }
+    }
+}
+
+/// An edge list whose edges are stored in a memory-mapped file. See the
+/// [module documentation](self).
+///
+/// The changes made by [MmapEdgeList::sort] are written to the file by the operating system at
+/// some point after they are made; call [MmapEdgeList::flush] to write them right away.
+pub struct MmapEdgeList<VF, const N: usize> {
+    map: Map,
+    path: PathBuf,
+    n_vertices: usize,
+    len: usize,
+    _value: PhantomData<VF>,
+}
+
+impl<VF: FixedSizeValue, const N: usize> MmapEdgeList<VF, N> {
+    const RECORD_BYTES: usize = (2 + N) * WORD_BYTES;
+
+    /// Writes the edges to a new file at the given path, replacing it if it exists, and maps it.
+    /// Returns an error if an edge is a self-loop or has a vertex out of the range
+    /// 0..`n_vertices`, in which case the file is left incomplete.
+    ///
+    /// The file must not be modified while it is mapped, see the [module documentation](self).
+    /// In particular, the path must not be that of another mapped edge list.
+    pub fn create<P, I>(path: P, n_vertices: usize, edges: I) -> Result<Self, MmapEdgeListError>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = FilteredEdge<OneCriticalGrade<VF, N>>>,
+    {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path.as_ref())?;
+        let mut writer = BufWriter::new(file);
+        for word in [u64::from_le_bytes(*MAGIC), n_vertices as u64, N as u64, 0] {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+        let mut len: u64 = 0;
+        let mut record = vec![0; Self::RECORD_BYTES];
+        for edge in edges {
+            let BareEdge(u, v) = edge.edge;
+            if u == v {
+                return Err(EdgeListError::SelfLoop(edge.edge).into());
+            }
+            if u >= n_vertices || v >= n_vertices {
+                return Err(EdgeListError::VertexOutOfRange(edge.edge, n_vertices).into());
+            }
+            write_record(&mut record, &edge);
+            writer.write_all(&record)?;
+            len += 1;
+        }
+        let mut written = writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        written.seek(SeekFrom::Start(3 * WORD_BYTES as u64))?;
+        written.write_all(&len.to_le_bytes())?;
+        // Safety: the file was just created, see the module documentation.
+        let map = unsafe { MmapMut::map_mut(&written)? };
+        Self::from_map(Map::Writable(map), path.as_ref())
+    }
+
+    /// Writes the edges of the list to a new file at the given path, as [MmapEdgeList::create].
+    pub fn from_edge_list<P: AsRef<Path>>(
+        path: P,
+        edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    ) -> Result<Self, MmapEdgeListError> {
+        Self::create(path, edge_list.n_vertices, edge_list.edge_iter().copied())
+    }
+
+    /// Maps the edge list stored in the file at the given path, to read and sort its edges.
+    /// Returns an error if the file is not a valid edge list: if its header or length are wrong,
+    /// or if an edge is a self-loop or has a vertex out of range.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the list is alive, by this or another
+    /// process, see the [module documentation](self).
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self, MmapEdgeListError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path.as_ref())?;
+        let map = MmapMut::map_mut(&file)?;
+        Self::from_map(Map::Writable(map), path.as_ref())
+    }
+
+    /// As [MmapEdgeList::open], but maps the file read-only, so it can be an input that is not
+    /// writable. Sorting the edges returns [MmapEdgeListError::ReadOnly].
+    ///
+    /// # Safety
+    ///
+    /// As [MmapEdgeList::open].
+    pub unsafe fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self, MmapEdgeListError> {
+        let file = File::open(path.as_ref())?;
+        let map = Mmap::map(&file)?;
+        Self::from_map(Map::ReadOnly(map), path.as_ref())
+    }
+
+    fn from_map(map: Map, path: &Path) -> Result<Self, MmapEdgeListError> {
+        if map.len() < HEADER_BYTES || &map[..WORD_BYTES] != MAGIC {
+            return Err(MmapEdgeListError::InvalidHeader);
+        }
+        let [n_vertices, n_parameters, len] = [1, 2, 3].map(|i| read_word(&map, i) as usize);
+        if n_parameters != N {
+            return Err(MmapEdgeListError::ParameterMismatch {
+                expected: N,
+                found: n_parameters,
+            });
+        }
+        let expected = len
+            .checked_mul(Self::RECORD_BYTES)
+            .and_then(|bytes| bytes.checked_add(HEADER_BYTES));
+        if expected != Some(map.len()) {
+            return Err(MmapEdgeListError::InvalidLength {
+                expected: expected.unwrap_or(usize::MAX),
+                found: map.len(),
+            });
+        }
+        let list = Self {
+            map,
+            path: fs::canonicalize(path)?,
+            n_vertices,
+            len,
+            _value: PhantomData,
+        };
+        for edge in list.edge_iter() {
+            let BareEdge(u, v) = edge.edge;
+            if u == v {
+                return Err(EdgeListError::SelfLoop(edge.edge).into());
+            }
+            if u >= n_vertices || v >= n_vertices {
+                return Err(EdgeListError::VertexOutOfRange(edge.edge, n_vertices).into());
+            }
+        }
+        Ok(list)
+    }
+
+    /// The canonical path of the file of the edge list.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Total number of vertices.
+    pub fn n_vertices(&self) -> usize {
+        self.n_vertices
+    }
+
+    /// The number of edges.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the list has no edges.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The edge at the given position, if the list has that many edges.
+    pub fn get(&self, index: usize) -> Option<FilteredEdge<OneCriticalGrade<VF, N>>> {
+        (index < self.len).then(|| read_record(self.record(index)))
+    }
+
+    /// Returns an iterator over the edges, in the order of the file.
+    pub fn edge_iter(&self) -> impl Iterator<Item = FilteredEdge<OneCriticalGrade<VF, N>>> + '_ {
+        self.map[HEADER_BYTES..]
+            .chunks_exact(Self::RECORD_BYTES)
+            .map(read_record)
+    }
+
+    /// Reads all the edges into memory.
+    pub fn to_edge_list(&self) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>> {
+        let mut edge_list = EdgeList::new(self.n_vertices);
+        for edge in self.edge_iter() {
+            edge_list.add_edge(edge);
+        }
+        edge_list
+    }
+
+    /// Sorts the edges in place, in the file, in the given order. Returns an error for
+    /// [EdgeOrder::Heuristic], which needs the whole graph to score the edges, and if the list was
+    /// opened read-only.
+    ///
+    /// The edges are sorted with heapsort, which takes no memory besides the map, but is not
+    /// stable. [EdgeOrder::Random] shuffles the edges, but in a different order than
+    /// [EdgeOrder::apply] with the same seed.
+    pub fn sort(&mut self, order: EdgeOrder) -> Result<(), MmapEdgeListError> {
+        if let Map::ReadOnly(_) = self.map {
+            return Err(MmapEdgeListError::ReadOnly);
+        }
+        match order {
+            EdgeOrder::Maintain => {}
+            EdgeOrder::Random(seed) => {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                for i in (1..self.len).rev() {
+                    self.swap(i, rng.gen_range(0..=i));
+                }
+            }
+            EdgeOrder::Heuristic => return Err(MmapEdgeListError::UnsupportedOrder(order)),
+            _ => {
+                if let Some(cmp) = order.comparator() {
+                    self.sort_by(cmp)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sorts the edges in place, in the file, with the given comparison function. See
+    /// [MmapEdgeList::sort].
+    pub fn sort_by<F>(&mut self, mut compare: F) -> Result<(), MmapEdgeListError>
+    where
+        F: FnMut(
+            &FilteredEdge<OneCriticalGrade<VF, N>>,
+            &FilteredEdge<OneCriticalGrade<VF, N>>,
+        ) -> Ordering,
+    {
+        if let Map::ReadOnly(_) = self.map {
+            return Err(MmapEdgeListError::ReadOnly);
+        }
+        for start in (0..self.len / 2).rev() {
+            self.sift_down(start, self.len, &mut compare);
+        }
+        for end in (1..self.len).rev() {
+            self.swap(0, end);
+            self.sift_down(0, end, &mut compare);
+        }
+        Ok(())
+    }
+
+    /// Moves the edge at `root` down the max-heap made of the first `end` edges.
+    fn sift_down<F>(&mut self, mut root: usize, end: usize, compare: &mut F)
+    where
+        F: FnMut(
+            &FilteredEdge<OneCriticalGrade<VF, N>>,
+            &FilteredEdge<OneCriticalGrade<VF, N>>,
+        ) -> Ordering,
+    {
+        let mut root_edge = read_record(self.record(root));
+        loop {
+            let mut child = 2 * root + 1;
+            if child >= end {
+                return;
+            }
+            let mut child_edge = read_record(self.record(child));
+            if child + 1 < end {
+                let right_edge = read_record(self.record(child + 1));
+                if compare(&child_edge, &right_edge) == Ordering::Less {
+                    child += 1;
+                    child_edge = right_edge;
+                }
+            }
+            if compare(&root_edge, &child_edge) != Ordering::Less {
+                return;
+            }
+            self.swap(root, child);
+            root = child;
+            root_edge = read_record(self.record(root));
+        }
+    }
+
+    fn record(&self, index: usize) -> &[u8] {
+        let start = HEADER_BYTES + index * Self::RECORD_BYTES;
+        &self.map[start..start + Self::RECORD_BYTES]
+    }
+
+    /// Swaps two edges. Does nothing if the list is read-only, which the callers check first.
+    fn swap(&mut self, i: usize, j: usize) {
+        let map = match &mut self.map {
+            Map::Writable(map) => map,
+            Map::ReadOnly(_) => return,
+        };
+        if i == j {
+            return;
+        }
+        let (first, second) = (i.min(j), i.max(j));
+        let (low, high) = map[HEADER_BYTES..].split_at_mut(second * Self::RECORD_BYTES);
+        low[first * Self::RECORD_BYTES..(first + 1) * Self::RECORD_BYTES]
+            .swap_with_slice(&mut high[..Self::RECORD_BYTES]);
+    }
+
+    /// Writes the changes to the file. Does nothing if the list is read-only.
+    pub fn flush(&self) -> io::Result<()> {
+        match &self.map {
+            Map::Writable(map) => map.flush(),
+            Map::ReadOnly(_) => Ok(()),
+        }
+    }
+}
+
+fn read_word(bytes: &[u8], index: usize) -> u64 {
+    let mut word = [0; WORD_BYTES];
+    word.copy_from_slice(&bytes[index * WORD_BYTES..(index + 1) * WORD_BYTES]);
+    u64::from_le_bytes(word)
+}
+
+fn write_word(bytes: &mut [u8], index: usize, word: u64) {
+    bytes[index * WORD_BYTES..(index + 1) * WORD_BYTES].copy_from_slice(&word.to_le_bytes());
+}
+
+fn read_record<VF: FixedSizeValue, const N: usize>(
+    record: &[u8],
+) -> FilteredEdge<OneCriticalGrade<VF, N>> {
+    let mut values = [VF::zero(); N];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = VF::from_word(read_word(record, 2 + i));
+    }
+    FilteredEdge {
+        edge: BareEdge(read_word(record, 0) as usize, read_word(record, 1) as usize),
+        grade: OneCriticalGrade(values),
+    }
+}
+
+fn write_record<VF: FixedSizeValue, const N: usize>(
+    record: &mut [u8],
+    edge: &FilteredEdge<OneCriticalGrade<VF, N>>,
+) {
+    write_word(record, 0, edge.edge.0 as u64);
+    write_word(record, 1, edge.edge.1 as u64);
+    for (i, value) in edge.grade.0.iter().enumerate() {
+        write_word(record, 2 + i, value.to_word());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::mmap::{MmapEdgeList, MmapEdgeListError, HEADER_BYTES, WORD_BYTES};
+    use crate::edges::{BareEdge, FilteredEdge};
+    use crate::io_utils::unique_temp_path;
+    use crate::removal::EdgeOrder;
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn mmap_edge_list_sorts_like_edge_list() {
+        let directory = unique_temp_path("mmap_tests");
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("sort.edges");
+
+        let mut edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 40 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let mut mapped: MmapEdgeList<OrderedFloat<f64>, 2> =
+            MmapEdgeList::from_edge_list(&path, &edges).unwrap();
+        assert_eq!(mapped.len(), edges.len());
+        assert_eq!(mapped.get(3), Some(edges.edges()[3]));
+        assert_eq!(mapped.get(edges.len()), None);
+
+        for order in [
+            EdgeOrder::ReverseLexicographic,
+            EdgeOrder::Colexicographic,
+            EdgeOrder::Lexicographic,
+        ] {
+            mapped.sort(order).unwrap();
+            order.apply(&mut edges);
+            assert_eq!(mapped.to_edge_list().edges(), edges.edges());
+        }
+        assert!(matches!(
+            mapped.sort(EdgeOrder::Heuristic),
+            Err(MmapEdgeListError::UnsupportedOrder(_))
+        ));
+
+        // The sorted edges are in the file.
+        mapped.flush().unwrap();
+        drop(mapped);
+        // Safety: the file is only used by this test.
+        let mut reopened: MmapEdgeList<OrderedFloat<f64>, 2> =
+            unsafe { MmapEdgeList::open_read_only(&path) }.unwrap();
+        assert_eq!(reopened.n_vertices(), edges.n_vertices);
+        assert_eq!(reopened.to_edge_list().edges(), edges.edges());
+        assert!(matches!(
+            reopened.sort(EdgeOrder::Lexicographic),
+            Err(MmapEdgeListError::ReadOnly)
+        ));
+        drop(reopened);
+        assert!(matches!(
+            unsafe { MmapEdgeList::<usize, 3>::open(&path) },
+            Err(MmapEdgeListError::ParameterMismatch {
+                expected: 3,
+                found: 2
+            })
+        ));
+
+        let self_loop = FilteredEdge {
+            edge: BareEdge(1, 1),
+            grade: OneCriticalGrade([0, 0]),
+        };
+        assert!(matches!(
+            MmapEdgeList::<usize, 2>::create(&path, 2, [self_loop]),
+            Err(MmapEdgeListError::InvalidEdge(_))
+        ));
+
+        // An edge with a vertex out of range, written over a valid file.
+        let edge = FilteredEdge {
+            edge: BareEdge(0, 1),
+            grade: OneCriticalGrade([0, 0]),
+        };
+        drop(MmapEdgeList::<usize, 2>::create(&path, 2, [edge]).unwrap());
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[HEADER_BYTES + WORD_BYTES..HEADER_BYTES + 2 * WORD_BYTES]
+            .copy_from_slice(&5u64.to_le_bytes());
+        std::fs::write(&path, bytes).unwrap();
+        assert!(matches!(
+            unsafe { MmapEdgeList::<usize, 2>::open(&path) },
+            Err(MmapEdgeListError::InvalidEdge(_))
+        ));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
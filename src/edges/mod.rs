@@ -0,0 +1,873 @@
+//! Edges, edge lists, and associated functions.
+//!
+//! Apart from the documented panics, like the one of [EdgeList::add_edge], the functions of this
+//! module do not panic: malformed input is reported through errors instead.
+#![cfg_attr(
+    not(test),
+    deny(clippy::panic, clippy::unwrap_used, clippy::expect_used)
+)]
+use crate::io_utils::parse_next;
+use crate::{CriticalGrade, OneCriticalGrade, Value};
+use ordered_float::OrderedFloat;
+use rand::prelude::SliceRandom;
+use rand::{thread_rng, Rng};
+use rustc_hash::FxHashSet;
+use std::cmp::{max, Ordering};
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::io::BufRead;
+use std::ops::{Add, Mul};
+use thiserror::Error;
+
+pub mod contraction;
+pub mod export;
+pub mod graded;
+pub mod lower_star;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod transform;
+
+#[cfg(feature = "petgraph")]
+mod petgraph_interop;
+
+pub use contraction::EdgeContraction;
+pub use graded::GradedEdgeList;
+pub use lower_star::{lower_star, lower_star_bifiltration};
+pub use transform::{quantize, Quantization, QuantizationMap, Rounding};
+
+/// Common functionality of an undirected edge. See [BareEdge] and [FilteredEdge].
+pub trait Edge {
+    /// First endpoint. This is an undirected edge, but the first endpoint must be consistent
+    /// for a fixed instance.
+    fn u(&self) -> usize;
+
+    /// Returns a mutable reference to the first endpoint.
+    fn u_mut(&mut self) -> &mut usize;
+
+    /// Second endpoint. This is an undirected edge, but the second endpoint must be consistent
+    /// for a fixed instance.
+    fn v(&self) -> usize;
+
+    /// Returns a mutable reference to the second endpoint.
+    fn v_mut(&mut self) -> &mut usize;
+
+    /// The greatest endpoint.
+    fn max(&self) -> usize {
+        std::cmp::max(self.u(), self.v())
+    }
+
+    /// The least endpoint.
+    fn min(&self) -> usize {
+        std::cmp::min(self.u(), self.v())
+    }
+
+    /// Return a pair whose first element is the greatest endpoint,
+    /// and the second is the least endpoint.
+    fn minmax(&self) -> (usize, usize) {
+        (self.min(), self.max())
+    }
+}
+
+/// Edge that is not filtered.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BareEdge(pub usize, pub usize);
+
+impl Edge for BareEdge {
+    fn u(&self) -> usize {
+        self.0
+    }
+
+    fn u_mut(&mut self) -> &mut usize {
+        &mut self.0
+    }
+
+    fn v(&self) -> usize {
+        self.1
+    }
+
+    fn v_mut(&mut self) -> &mut usize {
+        &mut self.1
+    }
+}
+
+impl PartialEq for BareEdge {
+    fn eq(&self, other: &Self) -> bool {
+        self.minmax() == other.minmax()
+    }
+}
+
+impl Eq for BareEdge {}
+
+/// Lexicographic order on the minimum and maximum vertex.
+impl PartialOrd for BareEdge {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Lexicographic order on the minimum and maximum vertex.
+impl Ord for BareEdge {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.minmax().cmp(&other.minmax())
+    }
+}
+
+impl Hash for BareEdge {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.minmax().hash(state);
+    }
+}
+
+impl std::fmt::Display for BareEdge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}]", self.0, self.1)
+    }
+}
+
+/// An edge with its associated critical grade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilteredEdge<G> {
+    /// The critical grade of this edge.
+    pub grade: G,
+    /// The endpoints of this edge.
+    pub edge: BareEdge,
+}
+
+impl<G> Edge for FilteredEdge<G> {
+    fn u(&self) -> usize {
+        self.edge.u()
+    }
+
+    fn u_mut(&mut self) -> &mut usize {
+        self.edge.u_mut()
+    }
+
+    fn v(&self) -> usize {
+        self.edge.v()
+    }
+
+    fn v_mut(&mut self) -> &mut usize {
+        self.edge.v_mut()
+    }
+}
+
+/// Implements a total ordering, same as .cmp().
+impl<G: Ord> PartialOrd<Self> for FilteredEdge<G> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Implements a lexicographic ordering.
+/// First lexicographically compare the grades, and resolve ties by comparing edges.
+impl<G: Ord> Ord for FilteredEdge<G> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.grade.cmp(&other.grade) {
+            Ordering::Equal => self.edge.cmp(&other.edge),
+            not_eq => not_eq,
+        }
+    }
+}
+
+impl<G: Ord> FilteredEdge<G> {
+    /// First compare grades, by the given function `grade_cmp`,
+    /// and, if they are equal, compare edge values.
+    fn cmp_by(&self, other: &Self, grade_cmp: impl Fn(&G, &G) -> Ordering) -> Ordering {
+        match grade_cmp(&self.grade, &other.grade) {
+            Ordering::Equal => self.edge.cmp(&other.edge),
+            not_eq => not_eq,
+        }
+    }
+}
+
+impl<G: std::fmt::Display> std::fmt::Display for FilteredEdge<G> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.edge, self.grade)?;
+        Ok(())
+    }
+}
+
+impl<G> From<FilteredEdge<G>> for BareEdge {
+    fn from(e: FilteredEdge<G>) -> Self {
+        e.edge
+    }
+}
+
+/// The reasons why an edge list can be invalid, see [EdgeList::validate], or an operation on an
+/// edge list can fail.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeListError {
+    #[error("The edge list contains the self-loop {0}")]
+    SelfLoop(BareEdge),
+
+    #[error("The edge {0} has a vertex out of the range 0..{1}")]
+    VertexOutOfRange(BareEdge, usize),
+
+    #[error("The edge {0} is not in the edge list")]
+    EdgeNotFound(BareEdge),
+}
+
+/// A graph represented as a list of edges, whose vertices are in the range 0..`n_vertices`.
+/// No self-loops are allowed.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeList<E> {
+    /// Total number of vertices.
+    pub n_vertices: usize,
+    edges: Vec<E>,
+}
+
+impl<E: Edge> EdgeList<E> {
+    /// New empty edge list.
+    pub fn new(n_vertices: usize) -> Self {
+        Self {
+            n_vertices,
+            edges: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying slice of edges.
+    pub fn edges(&self) -> &[E] {
+        &self.edges
+    }
+
+    /// Returns a mutable slice of the underlying slice of edges.
+    pub fn edges_mut(&mut self) -> &mut [E] {
+        &mut self.edges
+    }
+
+    /// Returns the number of edges.
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns whether there are edges.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Collects all the edges from the given iterator.
+    pub fn from_iterator<I: Iterator<Item = E>>(it: I) -> Self {
+        let edges: Vec<E> = it.collect();
+        edges.into()
+    }
+
+    /// Returns the number of vertices.
+    pub fn number_of_vertices(&self) -> usize {
+        self.n_vertices
+    }
+
+    /// Adds an edge to the graph.
+    /// Panics: if the edge to add is a self-loop. See [EdgeList::try_add_edge] for a
+    /// non-panicking version.
+    pub fn add_edge(&mut self, e: E) {
+        let u = e.u();
+        let v = e.v();
+        assert_ne!(u, v, "Trying to add a self loop to a graph");
+
+        let greatest_vertex = max(u, v);
+        self.n_vertices = max(self.n_vertices, greatest_vertex + 1);
+        self.edges.push(e);
+    }
+
+    /// Adds an edge to the graph, or returns an error if the edge is a self-loop.
+    pub fn try_add_edge(&mut self, e: E) -> Result<(), EdgeListError> {
+        if e.u() == e.v() {
+            return Err(EdgeListError::SelfLoop(BareEdge(e.u(), e.v())));
+        }
+        self.add_edge(e);
+        Ok(())
+    }
+
+    /// Checks that the edge list has no self-loops and that all the vertices are in the range
+    /// 0..`n_vertices`. Edge lists built with [EdgeList::add_edge] always pass this check, but
+    /// ones that have been deserialized or modified through [EdgeList::edges_mut] might not.
+    pub fn validate(&self) -> Result<(), EdgeListError> {
+        for e in self.edge_iter() {
+            let bare_edge = BareEdge(e.u(), e.v());
+            if e.u() == e.v() {
+                return Err(EdgeListError::SelfLoop(bare_edge));
+            }
+            if e.max() >= self.n_vertices {
+                return Err(EdgeListError::VertexOutOfRange(bare_edge, self.n_vertices));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the edges with the given endpoints, in either direction, keeping the order of the
+    /// remaining ones. Returns the removed edges, in their order in the list, which includes every
+    /// copy of a repeated edge. The number of vertices does not change.
+    ///
+    /// Returns an error if some given edge is not in the list, in which case the list is not
+    /// modified. See [validate_removal](crate::removal::validate_removal) to check that the
+    /// removed edges were dominated.
+    pub fn remove_edges(&mut self, edges: &[BareEdge]) -> Result<Vec<E>, EdgeListError> {
+        let to_remove: FxHashSet<BareEdge> = edges.iter().copied().collect();
+        let present: FxHashSet<BareEdge> = self
+            .edge_iter()
+            .map(|e| BareEdge(e.u(), e.v()))
+            .filter(|e| to_remove.contains(e))
+            .collect();
+        if let Some(missing) = edges.iter().find(|e| !present.contains(e)) {
+            return Err(EdgeListError::EdgeNotFound(*missing));
+        }
+        let (removed, kept) = self
+            .edges
+            .drain(..)
+            .partition(|e| to_remove.contains(&BareEdge(e.u(), e.v())));
+        self.edges = kept;
+        Ok(removed)
+    }
+
+    /// Returns an iterator over the edges.
+    pub fn edge_iter(&self) -> impl Iterator<Item = &E> + '_ {
+        self.edges.iter()
+    }
+
+    /// Returns a count of the degree of each vertex.
+    pub fn degrees(&self) -> Vec<usize> {
+        let mut degree_count = vec![0; self.n_vertices];
+        for e in self.edge_iter() {
+            degree_count[e.u()] += 1;
+            degree_count[e.v()] += 1;
+        }
+        degree_count
+    }
+
+    /// Returns the maximum degree of a vertex in the edge list.
+    pub fn maximum_degree(&self) -> usize {
+        // Return 0 as maximum degree if there are no vertices.
+        self.degrees().into_iter().max().unwrap_or(0usize)
+    }
+
+    /// Relabels the vertices that are endpoints of some edge to the range 0..k, where k is their
+    /// number, keeping their relative order, and sets `n_vertices` to k. Vertices without edges
+    /// are dropped. The grades and the order of the edges do not change.
+    ///
+    /// Returns the relabeling, which maps the results computed on the compacted list back to the
+    /// original labels, see [VertexRelabeling::restore].
+    pub fn compact_vertices(&mut self) -> VertexRelabeling {
+        // The ids can be much larger than the number of edges, so the new labels are found by
+        // binary search instead of a table indexed by the ids.
+        let mut original_vertices: Vec<usize> =
+            self.edges.iter().flat_map(|e| [e.u(), e.v()]).collect();
+        original_vertices.sort_unstable();
+        original_vertices.dedup();
+        for e in self.edges.iter_mut() {
+            let (u, v) = (e.u(), e.v());
+            *e.u_mut() = compacted_vertex(&original_vertices, u);
+            *e.v_mut() = compacted_vertex(&original_vertices, v);
+        }
+        let n_original_vertices = self.n_vertices;
+        self.n_vertices = original_vertices.len();
+
+        VertexRelabeling {
+            n_original_vertices,
+            original_vertices,
+        }
+    }
+
+    fn count_vertices(edges: &[E]) -> usize {
+        let mut n_vertices = 0;
+
+        for e in edges.iter() {
+            n_vertices = max(n_vertices, e.max() + 1);
+        }
+
+        n_vertices
+    }
+}
+
+/// The new label of a vertex used by some edge, which is always in the sorted
+/// `original_vertices` computed by [EdgeList::compact_vertices].
+fn compacted_vertex(original_vertices: &[usize], v: usize) -> usize {
+    original_vertices.binary_search(&v).unwrap_or(v)
+}
+
+/// A relabeling of the vertices of an edge list, returned by [EdgeList::compact_vertices].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VertexRelabeling {
+    /// The number of vertices of the edge list before it was compacted.
+    pub n_original_vertices: usize,
+    /// The original label of each new vertex, in increasing order.
+    pub original_vertices: Vec<usize>,
+}
+
+impl VertexRelabeling {
+    /// The new label of the given original vertex, if it was not dropped.
+    pub fn new_vertex(&self, original: usize) -> Option<usize> {
+        self.original_vertices.binary_search(&original).ok()
+    }
+
+    /// The original label of the given new vertex.
+    ///
+    /// Panics: if the vertex is not less than the number of new vertices.
+    pub fn original_vertex(&self, new: usize) -> usize {
+        self.original_vertices[new]
+    }
+
+    /// Relabels the vertices of an edge list with new labels, like the output of a removal
+    /// algorithm on the compacted list, back to their original labels, and sets `n_vertices` to
+    /// the original number of vertices.
+    ///
+    /// Panics: if a vertex is not less than the number of new vertices.
+    pub fn restore<E: Edge>(&self, edge_list: &mut EdgeList<E>) {
+        for e in edge_list.edges.iter_mut() {
+            let (u, v) = (e.u(), e.v());
+            *e.u_mut() = self.original_vertex(u);
+            *e.v_mut() = self.original_vertex(v);
+        }
+        edge_list.n_vertices = self.n_original_vertices;
+    }
+}
+
+impl<G> EdgeList<FilteredEdge<G>> {
+    /// Returns the edge list with the same edges, in the same order, whose grades are the result
+    /// of applying the given function to the grades of this one. For example, to convert the
+    /// grades to another number of parameters, see [OneCriticalGrade::pad] and
+    /// [OneCriticalGrade::project].
+    pub fn map_grades<H>(&self, mut f: impl FnMut(&G) -> H) -> EdgeList<FilteredEdge<H>> {
+        EdgeList {
+            n_vertices: self.n_vertices,
+            edges: self
+                .edges
+                .iter()
+                .map(|e| FilteredEdge {
+                    grade: f(&e.grade),
+                    edge: e.edge,
+                })
+                .collect(),
+        }
+    }
+
+    /// As [EdgeList::map_grades], for conversions that can fail: returns None if the function
+    /// returns None for some grade.
+    pub fn try_map_grades<H>(
+        &self,
+        mut f: impl FnMut(&G) -> Option<H>,
+    ) -> Option<EdgeList<FilteredEdge<H>>> {
+        let edges = self
+            .edges
+            .iter()
+            .map(|e| {
+                Some(FilteredEdge {
+                    grade: f(&e.grade)?,
+                    edge: e.edge,
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(EdgeList {
+            n_vertices: self.n_vertices,
+            edges,
+        })
+    }
+}
+
+impl<VF: Value, const N: usize> EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>> {
+    /// Sort the filtered edges lexicographically in increasing order.
+    pub fn sort_lexicographically(&mut self) {
+        self.edges.sort()
+    }
+
+    /// Reverse sort the filtered edges lexicographically.
+    pub fn sort_reverse_lexicographically(&mut self) {
+        self.edges.sort_by(|a, b| b.cmp(a))
+    }
+
+    /// Sort the filtered edges colexicographically in increasing order.
+    pub fn sort_colexicographically(&mut self) {
+        self.edges
+            .sort_by(|a, b| a.cmp_by(b, OneCriticalGrade::cmp_colexicographically))
+    }
+
+    /// Reverse sort the filtered edges colexicographically.
+    pub fn sort_reverse_colexicographically(&mut self) {
+        self.edges
+            .sort_by(|a, b| b.cmp_by(a, OneCriticalGrade::cmp_colexicographically))
+    }
+
+    /// Put a random order on the edges..
+    pub fn shuffle(&mut self) {
+        self.shuffle_with_rng(&mut thread_rng())
+    }
+
+    /// Put a random order on the edges, using the given random number generator.
+    pub fn shuffle_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.edges.shuffle(rng)
+    }
+
+    /// Swap the `i`-th and `j`-th parameters of the grades of all edges.
+    /// The removal algorithms process the edges in reverse lexicographic order, in which the
+    /// first parameter takes precedence, so swapping the parameters can change which edges are
+    /// removed.
+    ///
+    /// Panics: if `i` or `j` are not less than `N`.
+    pub fn swap_parameters(&mut self, i: usize, j: usize) {
+        for e in self.edges.iter_mut() {
+            e.grade.0.swap(i, j);
+        }
+    }
+
+    /// Keep only the edges whose grade is less than or equal to the given one in every parameter,
+    /// that is, truncate the filtration to the rectangle below `grade`. The number of vertices is
+    /// not changed, see [EdgeList::compact_vertices] to remove the isolated ones.
+    pub fn truncate(&mut self, grade: OneCriticalGrade<VF, N>) {
+        self.edges.retain(|e| e.grade.lte(&grade));
+    }
+
+    /// Clamp the grades of all edges to the given ranges. See [OneCriticalGrade::clamp_parameters].
+    pub fn clamp_grades(&mut self, min: [VF; N], max: [VF; N]) {
+        for e in self.edges.iter_mut() {
+            e.grade = e.grade.clamp_parameters(min, max);
+        }
+    }
+}
+
+impl<VF: Value + Add<Output = VF> + Mul<Output = VF>, const N: usize>
+    EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>
+{
+    /// Apply an affine transformation to each parameter of the grades of all edges, for example to
+    /// bring the parameters to comparable ranges. See [OneCriticalGrade::affine_transform].
+    ///
+    /// Panics: in debug builds, if the transformed value of an integer grade overflows. In release
+    /// builds, it wraps around instead.
+    pub fn affine_transform_grades(&mut self, scale: [VF; N], shift: [VF; N]) {
+        for e in self.edges.iter_mut() {
+            e.grade = e.grade.affine_transform(scale, shift);
+        }
+    }
+}
+
+impl<const N: usize> EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, N>>> {
+    /// Returns a copy of the edge list with single-precision grades, which take half the memory of
+    /// the grades, so that a bifiltered edge takes 24 bytes instead of 32. Each value is rounded to
+    /// the nearest `f32`. Rounding keeps the order between grades, but close values may become
+    /// equal, so the result is a coarser bifiltration.
+    ///
+    /// All the algorithms of this crate work with single-precision grades, so this is useful for
+    /// large edge lists.
+    pub fn to_single_precision(
+        &self,
+    ) -> EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f32>, N>>> {
+        let mut single = EdgeList::new(self.n_vertices);
+        for e in self.edges.iter() {
+            single.add_edge(FilteredEdge {
+                grade: OneCriticalGrade(e.grade.0.map(|value| OrderedFloat(value.0 as f32))),
+                edge: e.edge,
+            });
+        }
+        single
+    }
+}
+
+impl<E: Edge> From<Vec<E>> for EdgeList<E> {
+    fn from(edges: Vec<E>) -> Self {
+        let n_vertices = Self::count_vertices(&edges);
+        Self { n_vertices, edges }
+    }
+}
+
+pub fn write_edge_list<T: Value + Display, W: std::io::Write, const N: usize>(
+    edges: &EdgeList<FilteredEdge<OneCriticalGrade<T, N>>>,
+    writer: &mut W,
+    write_number: bool,
+) -> std::io::Result<()> {
+    if write_number {
+        writeln!(writer, "{}", edges.len())?;
+    }
+
+    for e in edges.edge_iter() {
+        write!(writer, "{} {}", e.edge.0, e.edge.1)?;
+        for i in 0..N {
+            write!(writer, " {}", e.grade.0[i])?;
+        }
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+pub fn read_edge_list<T: Value + std::str::FromStr, R: std::io::Read, const N: usize>(
+    reader: std::io::BufReader<R>,
+) -> std::io::Result<EdgeList<FilteredEdge<OneCriticalGrade<T, N>>>>
+where
+    <T as std::str::FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    let mut edge_list = EdgeList::new(0);
+    for l in reader.lines() {
+        let l = l?;
+        let mut line_parts = l.split_whitespace();
+        let u: usize = parse_next(&mut line_parts)?;
+        let v: usize = parse_next(&mut line_parts)?;
+
+        let mut grade = OneCriticalGrade::zero();
+        for grade_coord in grade.0.iter_mut() {
+            *grade_coord = parse_next(&mut line_parts)?;
+        }
+
+        edge_list
+            .try_add_edge(FilteredEdge {
+                grade,
+                edge: BareEdge(u, v),
+            })
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    }
+    Ok(edge_list)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chain_complex::PresentationFormat;
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::{read_edge_list, BareEdge, EdgeList, EdgeListError, FilteredEdge};
+    use crate::mpfree::write_flag_free_implicit_representation;
+    use crate::removal::{remove_strongly_filtration_dominated, EdgeOrder};
+    use crate::OneCriticalGrade;
+    use ordered_float::OrderedFloat;
+    use std::io::BufReader;
+
+    #[test]
+    fn edge_list_lexicographic_order() {
+        let mut edges: EdgeList<_> = sorting_test_dataset();
+        edges.sort_lexicographically();
+        let grades: Vec<OneCriticalGrade<usize, 2>> = edges.edge_iter().map(|e| e.grade).collect();
+        let expected_grades: Vec<OneCriticalGrade<usize, 2>> =
+            vec![[1, 1].into(), [1, 2].into(), [2, 1].into(), [2, 2].into()];
+        assert_eq!(grades, expected_grades);
+    }
+
+    #[test]
+    fn edge_list_reverse_lexicographic_order() {
+        let mut edges: EdgeList<_> = sorting_test_dataset();
+        edges.sort_reverse_lexicographically();
+        let grades: Vec<OneCriticalGrade<usize, 2>> = edges.edge_iter().map(|e| e.grade).collect();
+        let expected_grades: Vec<OneCriticalGrade<usize, 2>> =
+            vec![[2, 2].into(), [2, 1].into(), [1, 2].into(), [1, 1].into()];
+        assert_eq!(grades, expected_grades);
+    }
+
+    #[test]
+    fn edge_list_colexicographic_order() {
+        let mut edges: EdgeList<_> = sorting_test_dataset();
+        edges.sort_colexicographically();
+        let grades: Vec<OneCriticalGrade<usize, 2>> = edges.edge_iter().map(|e| e.grade).collect();
+        let expected_grades: Vec<OneCriticalGrade<usize, 2>> =
+            vec![[1, 1].into(), [2, 1].into(), [1, 2].into(), [2, 2].into()];
+        assert_eq!(grades, expected_grades);
+    }
+
+    #[test]
+    fn edge_list_reverse_colexicographic_order() {
+        let mut edges: EdgeList<_> = sorting_test_dataset();
+        edges.sort_reverse_colexicographically();
+        let grades: Vec<OneCriticalGrade<usize, 2>> = edges.edge_iter().map(|e| e.grade).collect();
+        let expected_grades: Vec<OneCriticalGrade<usize, 2>> =
+            vec![[2, 2].into(), [1, 2].into(), [2, 1].into(), [1, 1].into()];
+        assert_eq!(grades, expected_grades);
+    }
+
+    #[test]
+    fn edge_list_validation() {
+        let mut edges = sorting_test_dataset();
+        assert_eq!(edges.validate(), Ok(()));
+
+        edges.n_vertices = 5;
+        assert_eq!(
+            edges.validate(),
+            Err(EdgeListError::VertexOutOfRange(BareEdge(5, 3), 5))
+        );
+
+        let self_loop = FilteredEdge {
+            grade: OneCriticalGrade([0, 0]),
+            edge: BareEdge(2, 2),
+        };
+        assert_eq!(
+            edges.try_add_edge(self_loop),
+            Err(EdgeListError::SelfLoop(BareEdge(2, 2)))
+        );
+        assert_eq!(edges.len(), 4);
+
+        let input = "0 1 0.5 1.0\n2 2 1.0 1.0\n";
+        let read: std::io::Result<EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>> =
+            read_edge_list(BufReader::new(input.as_bytes()));
+        assert_eq!(read.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn edge_list_grade_transformations() {
+        let mut edges: EdgeList<_> = sorting_test_dataset();
+        edges.affine_transform_grades([10, 1], [0, 5]);
+        let transformed: Vec<OneCriticalGrade<usize, 2>> =
+            edges.edge_iter().map(|e| e.grade).collect();
+        let expected_transformed: Vec<OneCriticalGrade<usize, 2>> = vec![
+            [10, 6].into(),
+            [20, 7].into(),
+            [20, 6].into(),
+            [10, 7].into(),
+        ];
+        assert_eq!(transformed, expected_transformed);
+
+        edges.clamp_grades([15, 0], [100, 6]);
+        let clamped: Vec<OneCriticalGrade<usize, 2>> = edges.edge_iter().map(|e| e.grade).collect();
+        let expected_clamped: Vec<OneCriticalGrade<usize, 2>> = vec![
+            [15, 6].into(),
+            [20, 6].into(),
+            [20, 6].into(),
+            [15, 6].into(),
+        ];
+        assert_eq!(clamped, expected_clamped);
+    }
+
+    #[test]
+    fn edge_list_truncate() {
+        let mut edges: EdgeList<_> = sorting_test_dataset();
+        let n_vertices = edges.n_vertices;
+        edges.truncate([1, 2].into());
+        let grades: Vec<OneCriticalGrade<usize, 2>> = edges.edge_iter().map(|e| e.grade).collect();
+        let expected_grades: Vec<OneCriticalGrade<usize, 2>> = vec![[1, 1].into(), [1, 2].into()];
+        assert_eq!(grades, expected_grades);
+        assert_eq!(edges.n_vertices, n_vertices);
+
+        edges.truncate([0, 2].into());
+        assert!(edges.is_empty());
+    }
+
+    #[test]
+    fn edge_list_swap_parameters() {
+        let mut edges: EdgeList<_> = sorting_test_dataset();
+        edges.swap_parameters(0, 1);
+        let grades: Vec<OneCriticalGrade<usize, 2>> = edges.edge_iter().map(|e| e.grade).collect();
+        let expected_grades: Vec<OneCriticalGrade<usize, 2>> =
+            vec![[1, 1].into(), [2, 2].into(), [1, 2].into(), [2, 1].into()];
+        assert_eq!(grades, expected_grades);
+    }
+
+    #[test]
+    fn single_precision_pipeline() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 50 },
+            Threshold::Percentile(0.5),
+            None,
+            false,
+        )
+        .unwrap();
+        let mut single = edges.to_single_precision();
+        assert_eq!(single.len(), edges.len());
+        assert_eq!(
+            std::mem::size_of::<OneCriticalGrade<OrderedFloat<f32>, 2>>(),
+            std::mem::size_of::<OneCriticalGrade<OrderedFloat<f64>, 2>>() / 2
+        );
+        assert_eq!(
+            std::mem::size_of::<FilteredEdge<OneCriticalGrade<OrderedFloat<f32>, 2>>>(),
+            24
+        );
+        assert_eq!(
+            std::mem::size_of::<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>(),
+            32
+        );
+        for (e, e_single) in edges.edge_iter().zip(single.edge_iter()) {
+            assert_eq!(e.edge, e_single.edge);
+            assert_eq!(e.grade.0[1].0 as f32, e_single.grade.0[1].0);
+        }
+
+        let remaining =
+            remove_strongly_filtration_dominated(&mut single, EdgeOrder::ReverseLexicographic);
+        assert!(remaining.len() < single.len());
+        let mut scc2020 = Vec::new();
+        write_flag_free_implicit_representation(
+            &remaining,
+            1,
+            PresentationFormat::Scc2020,
+            &mut scc2020,
+        )
+        .unwrap();
+        assert!(scc2020.starts_with(b"scc2020"));
+    }
+
+    #[test]
+    fn edge_list_compact_vertices() {
+        let original: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = vec![
+            FilteredEdge {
+                grade: [1, 1].into(),
+                edge: BareEdge(10, 3),
+            },
+            FilteredEdge {
+                grade: [2, 0].into(),
+                edge: BareEdge(3, 7),
+            },
+        ]
+        .into();
+        let mut edges = original.clone();
+        let relabeling = edges.compact_vertices();
+        assert_eq!(edges.n_vertices, 3);
+        assert_eq!(relabeling.original_vertices, vec![3, 7, 10]);
+        assert_eq!(edges.edges()[0].edge, BareEdge(2, 0));
+        assert_eq!(edges.edges()[1].grade, [2, 0].into());
+        assert_eq!(relabeling.new_vertex(7), Some(1));
+        assert_eq!(relabeling.new_vertex(4), None);
+        assert_eq!(relabeling.new_vertex(11), None);
+
+        relabeling.restore(&mut edges);
+        assert_eq!(edges.n_vertices, original.n_vertices);
+        assert_eq!(edges.edges(), original.edges());
+        assert_eq!(edges.edges()[0].edge.0, 10);
+
+        // Large ids do not allocate anything proportional to them.
+        let large_id = 1 << 40;
+        let mut sparse: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = vec![FilteredEdge {
+            grade: [1, 1].into(),
+            edge: BareEdge(large_id, 5),
+        }]
+        .into();
+        let sparse_relabeling = sparse.compact_vertices();
+        assert_eq!(sparse.n_vertices, 2);
+        assert_eq!(sparse.edges()[0].edge, BareEdge(1, 0));
+        assert_eq!(sparse_relabeling.new_vertex(large_id), Some(1));
+        sparse_relabeling.restore(&mut sparse);
+        assert_eq!(sparse.n_vertices, large_id + 1);
+        assert_eq!(sparse.edges()[0].edge, BareEdge(large_id, 5));
+    }
+
+    #[test]
+    fn map_grades_between_parameter_counts() {
+        let edges = sorting_test_dataset();
+        let projected = edges.try_map_grades(|grade| grade.project(1)).unwrap();
+        assert_eq!(projected.n_vertices, edges.n_vertices);
+        assert_eq!(projected.edges()[2].grade, OneCriticalGrade([1]));
+        assert!(edges.try_map_grades(|grade| grade.project(2)).is_none());
+
+        let padded = projected.map_grades(|&grade| OneCriticalGrade::<_, 2>::from(grade));
+        assert_eq!(padded.edges()[1].grade, OneCriticalGrade([0, 2]));
+        assert_eq!(
+            OneCriticalGrade([3]).pad(1, usize::MIN),
+            Some(OneCriticalGrade([3, 0]))
+        );
+        assert_eq!(OneCriticalGrade([3]).pad(2, 0), None);
+    }
+
+    fn sorting_test_dataset() -> EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> {
+        vec![
+            FilteredEdge {
+                grade: [1, 1].into(),
+                edge: BareEdge(0, 1),
+            },
+            FilteredEdge {
+                grade: [2, 2].into(),
+                edge: BareEdge(5, 3),
+            },
+            FilteredEdge {
+                grade: [2, 1].into(),
+                edge: BareEdge(0, 3),
+            },
+            FilteredEdge {
+                grade: [1, 2].into(),
+                edge: BareEdge(2, 1),
+            },
+        ]
+        .into()
+    }
+}
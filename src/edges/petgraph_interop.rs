@@ -0,0 +1,84 @@
+//! Conversions between edge lists and petgraph graphs whose edge weights are grades. Requires the
+//! `petgraph` feature.
+//!
+//! The vertices of the edge list are the indices of the nodes of the graph. Node weights are
+//! ignored when converting from a graph, and are `()` when converting to one.
+use petgraph::graph::{Graph, IndexType};
+use petgraph::{EdgeType, Undirected};
+
+use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+
+/// The edges of the graph, with their weights as grades. Directed edges are taken as undirected,
+/// and self-loops are skipped, since they do not change the clique complex. Parallel edges are
+/// kept, so the graph must not have them to be given to the removal algorithms.
+impl<N, G: Clone, Ty: EdgeType, Ix: IndexType> From<&Graph<N, G, Ty, Ix>>
+    for EdgeList<FilteredEdge<G>>
+{
+    fn from(graph: &Graph<N, G, Ty, Ix>) -> Self {
+        let mut edge_list = EdgeList::new(graph.node_count());
+        for edge in graph.raw_edges() {
+            let u = edge.source().index();
+            let v = edge.target().index();
+            if u != v {
+                edge_list.add_edge(FilteredEdge {
+                    grade: edge.weight.clone(),
+                    edge: BareEdge(u.min(v), u.max(v)),
+                });
+            }
+        }
+        edge_list
+    }
+}
+
+/// An undirected graph with a node for each vertex, and the edges of the list, in the same
+/// order, weighted by their grades.
+impl<G: Clone, Ix: IndexType> From<&EdgeList<FilteredEdge<G>>> for Graph<(), G, Undirected, Ix> {
+    fn from(edge_list: &EdgeList<FilteredEdge<G>>) -> Self {
+        let mut graph = Graph::with_capacity(edge_list.n_vertices, edge_list.len());
+        for _ in 0..edge_list.n_vertices {
+            graph.add_node(());
+        }
+        for edge in edge_list.edges() {
+            let BareEdge(u, v) = edge.edge;
+            graph.add_edge(Ix::new(u).into(), Ix::new(v).into(), edge.grade.clone());
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::{DiGraph, UnGraph};
+
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::removal::{remove_strongly_filtration_dominated, EdgeOrder};
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn petgraph_round_trip() {
+        let mut graph: UnGraph<&str, OneCriticalGrade<usize, 2>> = UnGraph::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_edge(b, a, OneCriticalGrade([0, 1]));
+        graph.add_edge(a, c, OneCriticalGrade([1, 0]));
+        graph.add_edge(b, c, OneCriticalGrade([1, 1]));
+        graph.add_edge(c, c, OneCriticalGrade([2, 2]));
+
+        let mut edges = EdgeList::from(&graph);
+        assert_eq!(edges.n_vertices, 3);
+        assert_eq!(edges.len(), 3);
+        assert_eq!(edges.edges()[0].edge, BareEdge(0, 1));
+
+        let remaining = remove_strongly_filtration_dominated(&mut edges, EdgeOrder::default());
+        let reduced: UnGraph<(), OneCriticalGrade<usize, 2>> = (&remaining).into();
+        assert_eq!(reduced.node_count(), 3);
+        assert_eq!(reduced.edge_count(), remaining.len());
+        assert_eq!(EdgeList::from(&reduced).edges(), remaining.edges());
+
+        let directed: DiGraph<(), OneCriticalGrade<usize, 2>> =
+            DiGraph::from_edges([(2, 0, OneCriticalGrade([0, 0]))]);
+        let from_directed: EdgeList<FilteredEdge<_>> = (&directed).into();
+        assert_eq!(from_directed.edges()[0].edge, BareEdge(0, 2));
+    }
+}
@@ -0,0 +1,662 @@
+//! Transformations of the grades of bifiltered edge lists, to prepare them as inputs for the
+//! removal algorithms or mpfree: projecting onto some parameters, forgetting or randomizing a
+//! parameter, normalizing the parameters, replacing the values by their ranks, quantizing
+//! them, restricting them to a rectangle, and slicing them along a line. Also, checking whether
+//! a parameter of a bifiltration is redundant, see [redundant_parameter].
+use num::{Float, NumCast};
+use rand::distributions::uniform::SampleUniform;
+use rand::distributions::Uniform;
+use rand::Rng;
+
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::{CriticalGrade, OneCriticalGrade, Value};
+
+/// Returns the edge list whose grades are the projection of the given ones onto the given
+/// parameters, in the given order. For example, `project(edge_list, [1])` drops the first parameter
+/// of a bifiltered edge list.
+///
+/// Panics: if a parameter is not less than `N`.
+pub fn project<VF: Value, const N: usize, const M: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    parameters: [usize; M],
+) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, M>>> {
+    edge_list.map_grades(|grade| OneCriticalGrade(parameters.map(|p| grade.0[p])))
+}
+
+/// Sets the given parameter of all grades to zero, so that it does not play a role anymore,
+/// while keeping the number of parameters.
+///
+/// Panics: if the list has edges and `parameter` is not less than `N`.
+pub fn forget_parameter<VF: Value, const N: usize>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    parameter: usize,
+) {
+    for edge in edge_list.edges_mut() {
+        edge.grade.0[parameter] = VF::zero();
+    }
+}
+
+/// Replaces the given parameter of all grades by a random value, drawn uniformly between zero
+/// and the maximum value.
+///
+/// Panics: if the maximum value of `VF` is not greater than zero, or if the list has edges and
+/// `parameter` is not less than `N`.
+pub fn randomize_parameter<VF: Value + SampleUniform, const N: usize>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    parameter: usize,
+) {
+    randomize_parameter_with_rng(edge_list, parameter, &mut rand::thread_rng())
+}
+
+/// See [randomize_parameter]. Uses the given random number generator.
+///
+/// Panics: as [randomize_parameter].
+pub fn randomize_parameter_with_rng<VF: Value + SampleUniform, R: Rng, const N: usize>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    parameter: usize,
+    rng: &mut R,
+) {
+    let distribution = Uniform::new(VF::zero(), VF::max_value());
+    for edge in edge_list.edges_mut() {
+        edge.grade.0[parameter] = rng.sample(&distribution);
+    }
+}
+
+/// Returns the values that the given parameter takes in the grades of the edge list, sorted
+/// increasingly and without repetitions.
+///
+/// Panics: if the list has edges and `parameter` is not less than `N`.
+pub fn critical_values<VF: Value, const N: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    parameter: usize,
+) -> Vec<VF> {
+    let mut values: Vec<VF> = edge_list
+        .edge_iter()
+        .map(|e| e.grade.0[parameter])
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+/// Rescales each parameter linearly so that its values span the range from 0 to 1. Parameters
+/// that take a single value are set to zero.
+pub fn normalize<VF: Value + Float, const N: usize>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+) {
+    for parameter in 0..N {
+        let values = critical_values(edge_list, parameter);
+        let (min, max) = match (values.first(), values.last()) {
+            (Some(&min), Some(&max)) => (min, max),
+            _ => return,
+        };
+        let range = max - min;
+        for edge in edge_list.edges_mut() {
+            let value = &mut edge.grade.0[parameter];
+            *value = if range.is_zero() {
+                VF::zero()
+            } else {
+                (*value - min) / range
+            };
+        }
+    }
+}
+
+/// Replaces each value of each parameter by its rank among the critical values of the parameter,
+/// see [critical_values]. This keeps the relative order of the grades, so it does not change the
+/// removed edges nor the minimal presentations, but the resulting grades are small integers.
+pub fn to_ranks<VF: Value, const N: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+) -> EdgeList<FilteredEdge<OneCriticalGrade<usize, N>>> {
+    let all_critical_values: Vec<Vec<VF>> = (0..N)
+        .map(|parameter| critical_values(edge_list, parameter))
+        .collect();
+    let mut ranked = EdgeList::new(edge_list.n_vertices);
+    for edge in edge_list.edge_iter() {
+        let mut grade = OneCriticalGrade([0; N]);
+        for (parameter, rank) in grade.0.iter_mut().enumerate() {
+            let values = &all_critical_values[parameter];
+            // The value is always found, since the critical values come from the edge list.
+            *rank = values
+                .binary_search(&edge.grade.0[parameter])
+                .unwrap_or_else(|insertion| insertion);
+        }
+        ranked.add_edge(FilteredEdge {
+            grade,
+            edge: edge.edge,
+        });
+    }
+    ranked
+}
+
+/// Restricts the multifiltration to the rectangle of grades between `min` and `max`, both
+/// included. Edges whose grade is not less than or equal to `max` are dropped, and the grades of
+/// the remaining edges are raised to be at least `min`, so that at each grade of the rectangle the
+/// clique complex of the restriction is the one of the original edge list.
+///
+/// The edges keep their order, and the number of vertices is kept.
+pub fn restrict_to_rectangle<VF: Value, const N: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    min: [VF; N],
+    max: [VF; N],
+) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>> {
+    let min_grade = OneCriticalGrade(min);
+    let max_grade = OneCriticalGrade(max);
+    let mut restricted = EdgeList::new(edge_list.n_vertices);
+    for edge in edge_list.edge_iter() {
+        if !edge.grade.lte(&max_grade) {
+            continue;
+        }
+        restricted.add_edge(FilteredEdge {
+            grade: edge.grade.join(&min_grade),
+            edge: edge.edge,
+        });
+    }
+    restricted
+}
+
+/// Slices the multifiltration along the line of grades `base + t * direction`, for real `t`.
+/// Returns the 1-parameter edge list in which each edge has, as grade, the least `t` at which the
+/// line enters the upset of its original grade. Along the line, the clique complexes of the slice
+/// are those of the original edge list.
+///
+/// The line must be monotone, that is, all coordinates of the direction must be non-negative. The
+/// edges that the line never reaches, because their grade is greater than `base` in a parameter
+/// in which the direction is zero, are dropped. The other edges keep their order, and the number
+/// of vertices is kept.
+///
+/// Panics: if a coordinate of the direction is negative, or all of them are zero.
+pub fn slice_along_line<VF: Value + Float, const N: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    base: [VF; N],
+    direction: [VF; N],
+) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>> {
+    assert!(
+        direction.iter().all(|d| *d >= VF::zero()) && direction.iter().any(|d| *d > VF::zero()),
+        "The direction of a slice must be non-negative and non-zero"
+    );
+    let mut slice = EdgeList::new(edge_list.n_vertices);
+    'edges: for edge in edge_list.edge_iter() {
+        let mut entry = VF::neg_infinity();
+        for parameter in 0..N {
+            let offset = edge.grade.0[parameter] - base[parameter];
+            if direction[parameter].is_zero() {
+                if offset > VF::zero() {
+                    continue 'edges;
+                }
+            } else {
+                entry = Ord::max(entry, offset / direction[parameter]);
+            }
+        }
+        slice.add_edge(FilteredEdge {
+            grade: OneCriticalGrade([entry]),
+            edge: edge.edge,
+        });
+    }
+    slice
+}
+
+/// A non-decreasing map from the values of one parameter of a bifiltered edge list, the
+/// determining one, to the values of the other one, the redundant one. Returned by
+/// [redundant_parameter].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MonotoneReparameterization<VF> {
+    /// The parameter whose values are determined by the other one.
+    pub redundant: usize,
+    /// The parameter that determines the values of the other one.
+    pub determining: usize,
+    /// The critical values of the determining parameter, in increasing order, and the value of
+    /// the redundant parameter of the edges with each of them, which is non-decreasing.
+    pub values: Vec<(VF, VF)>,
+}
+
+impl<VF: Value> MonotoneReparameterization<VF> {
+    /// The value of the redundant parameter for the given value of the determining one, if it is
+    /// one of its critical values.
+    pub fn redundant_value(&self, determining_value: VF) -> Option<VF> {
+        self.values
+            .binary_search_by(|(value, _)| value.cmp(&determining_value))
+            .ok()
+            .map(|idx| self.values[idx].1)
+    }
+
+    /// Returns the single-parameter edge list whose grades are the values of the determining
+    /// parameter, see [project]. Its clique filtration has the same complexes as the bifiltration,
+    /// so it can be reduced with the single-parameter algorithms, like
+    /// [remove_filtration_dominated_single_parameter], and turned back into a bifiltered edge list
+    /// with [MonotoneReparameterization::lift].
+    ///
+    /// [remove_filtration_dominated_single_parameter]: crate::removal::remove_filtration_dominated_single_parameter
+    pub fn project(
+        &self,
+        edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    ) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>> {
+        project(edge_list, [self.determining])
+    }
+
+    /// Returns the bifiltered edge list whose grades have the values of the given single-parameter
+    /// edge list as the determining parameter, and the corresponding values of the redundant one.
+    /// Returns None if a value is not a critical value of the determining parameter.
+    pub fn lift(
+        &self,
+        edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>>,
+    ) -> Option<EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>> {
+        let mut lifted = EdgeList::new(edge_list.n_vertices);
+        for edge in edge_list.edge_iter() {
+            let [determining_value] = edge.grade.0;
+            let mut grade = OneCriticalGrade([determining_value; 2]);
+            grade.0[self.redundant] = self.redundant_value(determining_value)?;
+            lifted.add_edge(FilteredEdge {
+                grade,
+                edge: edge.edge,
+            });
+        }
+        Some(lifted)
+    }
+}
+
+/// Checks whether the given parameter of the bifiltered edge list is redundant, that is, whether
+/// the order of the edges along it is determined by the other parameter: edges with the same value
+/// of the other parameter have the same value of the given one, and edges with a greater value of
+/// the other parameter have a greater or equal value of the given one. Returns the map from the
+/// values of the other parameter to the values of the given one if so.
+///
+/// If a parameter is redundant, the clique complex at each grade of the bifiltration is one of the
+/// complexes of the single-parameter filtration by the other parameter, so the single-parameter
+/// algorithms, which are cheaper, preserve its homology. See [MonotoneReparameterization::project].
+///
+/// Panics: if the parameter is not 0 or 1.
+pub fn redundant_parameter<VF: Value>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    parameter: usize,
+) -> Option<MonotoneReparameterization<VF>> {
+    let determining = if parameter == 0 { 1 } else { 0 };
+    let mut values: Vec<(VF, VF)> = edge_list
+        .edge_iter()
+        .map(|e| (e.grade.0[determining], e.grade.0[parameter]))
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    let is_monotone = values
+        .windows(2)
+        .all(|pair| pair[0].0 < pair[1].0 && pair[0].1 <= pair[1].1);
+    is_monotone.then_some(MonotoneReparameterization {
+        redundant: parameter,
+        determining,
+        values,
+    })
+}
+
+/// How [quantize] coarsens the values of a parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Quantization<VF> {
+    /// Keep the values as they are.
+    Keep,
+    /// Snap each value to a multiple of the given resolution. A resolution that is not positive
+    /// keeps the values as they are.
+    Resolution(VF),
+    /// Keep at most the given number of distinct values, at evenly spaced ranks among the critical
+    /// values of the parameter, see [critical_values]. Zero is treated as one.
+    MaxValues(usize),
+}
+
+/// In which direction [quantize] moves each value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rounding {
+    /// Round each value up, to the smallest snapped value greater than or equal to it. Every edge
+    /// appears later, so at each grade the quantized clique complex is a subcomplex of the
+    /// original one, and both are equal at the grades whose coordinates are snapped values.
+    #[default]
+    Up,
+    /// Round each value down, to the largest snapped value less than or equal to it. Every edge
+    /// appears earlier, so at each grade the quantized clique complex is a supercomplex of the
+    /// original one.
+    Down,
+}
+
+/// The values replaced by [quantize]: for each parameter, the pairs of an original value and the
+/// value that replaced it, sorted by original value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuantizationMap<VF, const N: usize> {
+    pub values: [Vec<(VF, VF)>; N],
+}
+
+impl<VF: Value, const N: usize> QuantizationMap<VF, N> {
+    /// The value that replaced the given value of the given parameter, if it was a value of the
+    /// parameter.
+    pub fn get(&self, parameter: usize, value: VF) -> Option<VF> {
+        let values = self.values.get(parameter)?;
+        values
+            .binary_search_by(|(original, _)| original.cmp(&value))
+            .ok()
+            .map(|idx| values[idx].1)
+    }
+}
+
+/// Coarsens the grades of the edge list, by snapping each parameter as given in `quantization`
+/// and in the direction given by `rounding`. Returns the values that were replaced.
+///
+/// Coarser grades give fewer distinct grades, which speeds up the removal algorithms and mpfree,
+/// at the cost of approximating the bifiltration. See [Rounding] for the relation between the
+/// clique complexes before and after.
+pub fn quantize<VF: Value + NumCast, const N: usize>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    quantization: [Quantization<VF>; N],
+    rounding: Rounding,
+) -> QuantizationMap<VF, N> {
+    let mut current_parameter = 0;
+    let values = quantization.map(|parameter_quantization| {
+        let critical = critical_values(edge_list, current_parameter);
+        current_parameter += 1;
+        let snapped: Vec<VF> = match parameter_quantization {
+            Quantization::Keep => critical.clone(),
+            Quantization::Resolution(resolution) => critical
+                .iter()
+                .map(|&value| snap_to_resolution(value, resolution, rounding))
+                .collect(),
+            Quantization::MaxValues(max_values) => {
+                snap_to_max_values(&critical, max_values, rounding)
+            }
+        };
+        critical.into_iter().zip(snapped).collect::<Vec<_>>()
+    });
+    let map = QuantizationMap { values };
+    for edge in edge_list.edges_mut() {
+        for (parameter, value) in edge.grade.0.iter_mut().enumerate() {
+            // The value is always found, since the critical values come from the edge list.
+            if let Some(snapped) = map.get(parameter, *value) {
+                *value = snapped;
+            }
+        }
+    }
+    map
+}
+
+/// Snaps the value to a multiple of the resolution, in the given direction. The multiple is
+/// computed in double precision; if it cannot be represented by the value type, or if the value is
+/// so large that the nearby multiples are not exact in double precision, the value is kept.
+fn snap_to_resolution<VF: Value + NumCast>(value: VF, resolution: VF, rounding: Rounding) -> VF {
+    let (v, r) = match (value.to_f64(), resolution.to_f64()) {
+        (Some(v), Some(r)) if r > 0.0 && v.is_finite() => (v, r),
+        _ => return value,
+    };
+    let mut multiple = match rounding {
+        Rounding::Up => (v / r).ceil(),
+        Rounding::Down => (v / r).floor(),
+    };
+    // The division may be off by one ulp, so fix the direction of the rounding if needed. This
+    // takes at most one step, unless the multiples are not exact, and then the value is kept.
+    for _ in 0..2 {
+        let snapped = match VF::from(multiple * r) {
+            Some(snapped) => snapped,
+            None => return value,
+        };
+        match rounding {
+            Rounding::Up if snapped < value => multiple += 1.0,
+            Rounding::Down if snapped > value => multiple -= 1.0,
+            _ => return snapped,
+        }
+    }
+    value
+}
+
+/// Snaps each of the given sorted values to one of at most `max_values` of them, at evenly spaced
+/// ranks, in the given direction.
+fn snap_to_max_values<VF: Value>(
+    critical: &[VF],
+    max_values: usize,
+    rounding: Rounding,
+) -> Vec<VF> {
+    let n = critical.len();
+    let k = max_values.max(1);
+    if n <= k {
+        return critical.to_vec();
+    }
+    // The ranks of the kept values. Rounding up needs the maximum to be kept, and rounding down
+    // the minimum.
+    let kept: Vec<usize> = match rounding {
+        Rounding::Up => (1..=k).map(|i| (i * n - 1) / k).collect(),
+        Rounding::Down => (0..k).map(|i| i * n / k).collect(),
+    };
+    let mut snapped = Vec::with_capacity(n);
+    let mut next = 0;
+    for rank in 0..n {
+        let target = match rounding {
+            Rounding::Up => {
+                while kept[next] < rank {
+                    next += 1;
+                }
+                kept[next]
+            }
+            Rounding::Down => {
+                while next + 1 < k && kept[next + 1] <= rank {
+                    next += 1;
+                }
+                kept[next]
+            }
+        };
+        snapped.push(critical[target]);
+    }
+    snapped
+}
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use crate::edges::transform::{
+        critical_values, forget_parameter, normalize, project, quantize, redundant_parameter,
+        restrict_to_rectangle, slice_along_line, snap_to_resolution, to_ranks,
+        MonotoneReparameterization, Quantization, Rounding,
+    };
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::OneCriticalGrade;
+
+    fn test_edge_list() -> EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>> {
+        vec![
+            FilteredEdge {
+                grade: OneCriticalGrade([OrderedFloat(0.5), OrderedFloat(10.)]),
+                edge: BareEdge(0, 1),
+            },
+            FilteredEdge {
+                grade: OneCriticalGrade([OrderedFloat(0.25), OrderedFloat(30.)]),
+                edge: BareEdge(1, 2),
+            },
+            FilteredEdge {
+                grade: OneCriticalGrade([OrderedFloat(0.5), OrderedFloat(20.)]),
+                edge: BareEdge(0, 2),
+            },
+        ]
+        .into()
+    }
+
+    fn grades<VF: Copy, const N: usize>(
+        edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    ) -> Vec<[VF; N]> {
+        edge_list.edge_iter().map(|e| e.grade.0).collect()
+    }
+
+    #[test]
+    fn project_and_forget_parameters() {
+        let mut edge_list = test_edge_list();
+        let projected = project(&edge_list, [1]);
+        assert_eq!(projected.n_vertices, 3);
+        assert_eq!(
+            grades(&projected),
+            vec![
+                [OrderedFloat(10.)],
+                [OrderedFloat(30.)],
+                [OrderedFloat(20.)]
+            ]
+        );
+        let swapped = project(&edge_list, [1, 0]);
+        assert_eq!(grades(&swapped)[1], [OrderedFloat(30.), OrderedFloat(0.25)]);
+
+        forget_parameter(&mut edge_list, 0);
+        assert!(edge_list
+            .edge_iter()
+            .all(|e| e.grade.0[0] == OrderedFloat(0.)));
+    }
+
+    #[test]
+    fn normalize_and_rank_parameters() {
+        let mut edge_list = test_edge_list();
+        assert_eq!(
+            critical_values(&edge_list, 0),
+            vec![OrderedFloat(0.25), OrderedFloat(0.5)]
+        );
+
+        assert_eq!(grades(&to_ranks(&edge_list)), vec![[1, 0], [0, 2], [1, 1]]);
+
+        normalize(&mut edge_list);
+        assert_eq!(
+            grades(&edge_list),
+            vec![
+                [OrderedFloat(1.), OrderedFloat(0.)],
+                [OrderedFloat(0.), OrderedFloat(1.)],
+                [OrderedFloat(1.), OrderedFloat(0.5)]
+            ]
+        );
+    }
+
+    #[test]
+    fn quantize_parameters() {
+        let mut edge_list = test_edge_list();
+        let map = quantize(
+            &mut edge_list,
+            [
+                Quantization::Resolution(OrderedFloat(0.3)),
+                Quantization::MaxValues(2),
+            ],
+            Rounding::Up,
+        );
+        let expected = [
+            [OrderedFloat(0.6), OrderedFloat(20.)],
+            [OrderedFloat(0.3), OrderedFloat(30.)],
+            [OrderedFloat(0.6), OrderedFloat(20.)],
+        ];
+        for (grade, expected_grade) in grades(&edge_list).into_iter().zip(expected) {
+            assert!((grade[0].0 - expected_grade[0].0).abs() < 1e-12);
+            assert!(grade[0] >= expected_grade[0]);
+            assert_eq!(grade[1], expected_grade[1]);
+        }
+        assert_eq!(map.get(1, OrderedFloat(10.)), Some(OrderedFloat(20.)));
+        assert_eq!(map.get(1, OrderedFloat(15.)), None);
+
+        let mut ranks = to_ranks(&test_edge_list());
+        let rank_map = quantize(
+            &mut ranks,
+            [Quantization::Keep, Quantization::MaxValues(2)],
+            Rounding::Down,
+        );
+        assert_eq!(grades(&ranks), vec![[1, 0], [0, 1], [1, 1]]);
+        assert_eq!(rank_map.values[0], vec![(0, 0), (1, 1)]);
+
+        let mut coarse_ranks = to_ranks(&test_edge_list());
+        quantize(
+            &mut coarse_ranks,
+            [Quantization::Resolution(2), Quantization::Resolution(2)],
+            Rounding::Down,
+        );
+        assert_eq!(grades(&coarse_ranks), vec![[0, 0], [0, 2], [0, 0]]);
+    }
+
+    #[test]
+    fn snap_huge_values_to_resolution() {
+        // Beyond 2^53 the multiples of 3 are not all exact in double precision.
+        let value = OrderedFloat(4.162383209004567e18);
+        let resolution = OrderedFloat(3.);
+        assert!(snap_to_resolution(value, resolution, Rounding::Up) >= value);
+        assert!(snap_to_resolution(value, resolution, Rounding::Down) <= value);
+        assert_eq!(
+            snap_to_resolution(u64::MAX - 1, 3, Rounding::Up),
+            u64::MAX - 1
+        );
+    }
+
+    #[test]
+    fn restrict_and_slice_parameters() {
+        let edge_list = test_edge_list();
+        let restricted = restrict_to_rectangle(
+            &edge_list,
+            [OrderedFloat(0.3), OrderedFloat(0.)],
+            [OrderedFloat(1.), OrderedFloat(20.)],
+        );
+        assert_eq!(restricted.n_vertices, 3);
+        assert_eq!(
+            grades(&restricted),
+            vec![
+                [OrderedFloat(0.5), OrderedFloat(10.)],
+                [OrderedFloat(0.5), OrderedFloat(20.)]
+            ]
+        );
+        assert_eq!(restricted.edges()[1].edge, BareEdge(0, 2));
+
+        let slice = slice_along_line(
+            &edge_list,
+            [OrderedFloat(0.), OrderedFloat(0.)],
+            [OrderedFloat(0.0625), OrderedFloat(1.)],
+        );
+        assert_eq!(
+            grades(&slice),
+            vec![
+                [OrderedFloat(10.)],
+                [OrderedFloat(30.)],
+                [OrderedFloat(20.)]
+            ]
+        );
+
+        let vertical = slice_along_line(
+            &edge_list,
+            [OrderedFloat(0.25), OrderedFloat(0.)],
+            [OrderedFloat(0.), OrderedFloat(1.)],
+        );
+        assert_eq!(grades(&vertical), vec![[OrderedFloat(30.)]]);
+        assert_eq!(vertical.edges()[0].edge, BareEdge(1, 2));
+    }
+
+    #[test]
+    fn redundant_parameters() {
+        let edge = |u, v, grade| FilteredEdge {
+            grade: OneCriticalGrade::<usize, 2>(grade),
+            edge: BareEdge(u, v),
+        };
+        // The first parameter is twice the second one, plus one.
+        let edge_list: EdgeList<_> = vec![
+            edge(0, 1, [5, 2]),
+            edge(1, 2, [1, 0]),
+            edge(0, 2, [5, 2]),
+            edge(2, 3, [3, 1]),
+        ]
+        .into();
+        let reparameterization = redundant_parameter(&edge_list, 0).unwrap();
+        assert_eq!(
+            reparameterization,
+            MonotoneReparameterization {
+                redundant: 0,
+                determining: 1,
+                values: vec![(0, 1), (1, 3), (2, 5)],
+            }
+        );
+        assert_eq!(reparameterization.redundant_value(1), Some(3));
+        assert_eq!(reparameterization.redundant_value(4), None);
+        let single = reparameterization.project(&edge_list);
+        assert_eq!(grades(&single), vec![[2], [0], [2], [1]]);
+        let lifted = reparameterization.lift(&single).unwrap();
+        assert_eq!(grades(&lifted), grades(&edge_list));
+        assert!(redundant_parameter(&edge_list, 1).is_some());
+
+        // Two edges with the same second value and different first values.
+        let mut not_determined = edge_list.clone();
+        not_determined.add_edge(edge(1, 3, [4, 2]));
+        assert_eq!(redundant_parameter(&not_determined, 0), None);
+        assert!(redundant_parameter(&not_determined, 1).is_some());
+
+        // The usual density and distance parameters are not monotone.
+        assert_eq!(redundant_parameter(&test_edge_list(), 0), None);
+        assert_eq!(redundant_parameter(&test_edge_list(), 1), None);
+    }
+}
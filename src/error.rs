@@ -0,0 +1,61 @@
+//! The errors of the crate. Each module returns its own error type, and [enum@Error] gathers all of
+//! them, so that applications can propagate any of them with the `?` operator.
+use std::io;
+
+use thiserror::Error;
+
+use crate::budget::TimeBudgetExceeded;
+use crate::chain_complex::ChainComplexError;
+use crate::datasets::DatasetError;
+use crate::distance_matrix::DistanceMatrixError;
+use crate::edges::EdgeListError;
+use crate::external::ExternalError;
+use crate::filtration::FiltrationError;
+use crate::mpfree::MpfreeError;
+use crate::pipeline::PipelineError;
+use crate::rivet::RivetError;
+
+/// Any error of the crate.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    EdgeList(#[from] EdgeListError),
+
+    #[error(transparent)]
+    Filtration(#[from] FiltrationError),
+
+    #[error(transparent)]
+    DistanceMatrix(#[from] DistanceMatrixError),
+
+    #[error(transparent)]
+    ChainComplex(#[from] ChainComplexError),
+
+    #[error(transparent)]
+    Dataset(#[from] DatasetError),
+
+    #[error(transparent)]
+    Mpfree(#[from] MpfreeError),
+
+    #[error(transparent)]
+    Pipeline(#[from] PipelineError),
+
+    #[error(transparent)]
+    Rivet(#[from] RivetError),
+
+    #[error(transparent)]
+    External(#[from] ExternalError),
+
+    #[error(transparent)]
+    TimeBudget(#[from] TimeBudgetExceeded),
+
+    #[cfg(feature = "mmap")]
+    #[error(transparent)]
+    Mmap(#[from] crate::edges::mmap::MmapEdgeListError),
+
+    #[cfg(feature = "memory-guard")]
+    #[error(transparent)]
+    MemoryGuard(#[from] crate::resource::MemoryGuardError),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
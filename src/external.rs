@@ -0,0 +1,480 @@
+//! Utilities to run external tools, like mpfree and RIVET, on input files or streamed input.
+//!
+//! Each tool is described by a type implementing [Tool], which gives the default name of its
+//! binary and an environment variable that overrides it. How a tool is run is configured with
+//! [ToolOptions]: where the binary is, in which directory the input and output files are written,
+//! whether to keep them afterwards, extra arguments for the tool, and how long it may run.
+//!
+//! A running tool is killed if the computation that started it does not wait for it, because it
+//! returns early with an error or panics. By default, the files in the working directory are kept
+//! afterwards, to inspect them; if they should not be kept, they are deleted when they are
+//! dropped, so that also failed runs leave nothing behind. The tools write their diagnostics to
+//! the stderr of this process.
+use std::ffi::{OsStr, OsString};
+use std::io::{BufWriter, Read, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{fs, io};
+use thiserror::Error;
+
+/// Environment variable that, if set, overrides the default working directory of every tool.
+pub const WORKING_DIR_ENV_VAR: &str = "FILTRATION_DOMINATION_TMP";
+
+const DEFAULT_WORKING_DIR: &str = "tmp";
+
+/// How often a tool with a timeout, or that can be cancelled, is polled to check whether it has
+/// exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// An external tool.
+pub trait Tool {
+    /// The default binary, looked up in `PATH`.
+    const BINARY: &'static str;
+    /// Environment variable that, if set, overrides the default binary path.
+    const BINARY_ENV_VAR: &'static str;
+}
+
+/// How to run an external tool.
+///
+/// The default options read the binary path from the environment variable given by
+/// [Tool::BINARY_ENV_VAR], and the working directory from [WORKING_DIR_ENV_VAR]. If they are not
+/// set, the binary [Tool::BINARY] is looked up in `PATH`, and files are written to `tmp`, where
+/// they are kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolOptions<T> {
+    /// Path to the binary. If it is a bare file name, it is looked up in `PATH`.
+    pub binary_path: PathBuf,
+    /// Directory where input and output files are written. It is created if it does not exist.
+    pub working_dir: PathBuf,
+    /// Whether to keep the input and output files after running the tool. If false, they are
+    /// deleted also when the tool fails, is killed, or the computation panics.
+    pub keep_files: bool,
+    /// Extra arguments given to the tool, before the arguments of the computation.
+    pub extra_args: Vec<OsString>,
+    /// Whether to stream the input to the stdin of the tool, and read its output from stdout,
+    /// instead of going through files in the working directory. The tool is given the paths
+    /// `/dev/stdin` and `/dev/stdout`, so the tool and the platform must support them.
+    /// Currently only used by mpfree.
+    pub pipe: bool,
+    /// If the tool runs for longer than this, it is killed and [ExternalError::Timeout] is
+    /// returned. To share a time limit with other computations, set it to the remaining time of
+    /// a [TimeBudget](crate::budget::TimeBudget). If None then the tool may run for any time.
+    pub timeout: Option<Duration>,
+    pub tool: PhantomData<T>,
+}
+
+impl<T: Tool> Default for ToolOptions<T> {
+    fn default() -> Self {
+        let binary_path = std::env::var_os(T::BINARY_ENV_VAR).unwrap_or_else(|| T::BINARY.into());
+        let working_dir =
+            std::env::var_os(WORKING_DIR_ENV_VAR).unwrap_or_else(|| DEFAULT_WORKING_DIR.into());
+        Self {
+            binary_path: binary_path.into(),
+            working_dir: working_dir.into(),
+            keep_files: true,
+            extra_args: Vec::new(),
+            pipe: false,
+            timeout: None,
+            tool: PhantomData,
+        }
+    }
+}
+
+impl<T> ToolOptions<T> {
+    /// Creates the working directory, and returns a set of files in it that are deleted when
+    /// dropped, unless `keep_files` is true.
+    pub fn working_files(&self) -> Result<WorkingFiles, ExternalError> {
+        fs::create_dir_all(&self.working_dir)
+            .map_err(|err| ExternalError::CreateWorkingDir(self.working_dir.clone(), err))?;
+        Ok(WorkingFiles {
+            directory: self.working_dir.clone(),
+            prefix: format!("{}-{:08x}", std::process::id(), rand::random::<u32>()),
+            paths: Vec::new(),
+            keep: self.keep_files,
+        })
+    }
+
+    /// Runs the tool with the extra arguments followed by the given ones, and returns what it
+    /// wrote to stdout. Its stderr goes to the stderr of this process.
+    pub fn run<I, S>(&self, args: I) -> Result<Vec<u8>, ExternalError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut child = ChildGuard(
+            Command::new(&self.binary_path)
+                .args(&self.extra_args)
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::inherit())
+                .spawn()
+                .map_err(|err| ExternalError::Spawn(self.binary_path.clone(), err))?,
+        );
+
+        let mut stdout = child.0.stdout.take().ok_or_else(missing_pipe)?;
+        let reader = std::thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+
+        let status = child.wait(self.timeout, None)?;
+        let output = reader
+            .join()
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Reading stdout panicked"))??;
+        self.check_status(status)?;
+        Ok(output)
+    }
+
+    /// Runs the tool with the extra arguments followed by the given ones, streams the input
+    /// written by `write_input` to its stdin, and returns what it wrote to stdout. Its stderr goes
+    /// to the stderr of this process.
+    pub fn run_with_input<I, S, F>(&self, args: I, write_input: F) -> Result<Vec<u8>, ExternalError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+        F: FnOnce(&mut BufWriter<ChildStdin>) -> io::Result<()>,
+    {
+        let mut child = ChildGuard(
+            Command::new(&self.binary_path)
+                .args(&self.extra_args)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::inherit())
+                .spawn()
+                .map_err(|err| ExternalError::Spawn(self.binary_path.clone(), err))?,
+        );
+
+        // Read stdout in another thread, so that the tool does not block on a full stdout pipe
+        // while we are blocked on a full stdin pipe.
+        let mut stdout = child.0.stdout.take().ok_or_else(missing_pipe)?;
+        let reader = std::thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+
+        let stdin = child.0.stdin.take().ok_or_else(missing_pipe)?;
+        // Wait in another thread too, so that the tool is killed on timeout even if it stops
+        // reading its stdin while we are blocked writing to it. If `write_input` panics, the
+        // cancellation is dropped and the waiting thread kills the tool.
+        let timeout = self.timeout;
+        let cancellation = Cancellation::default();
+        let cancelled = cancellation.0.clone();
+        let waiter = std::thread::spawn(move || child.wait(timeout, Some(&cancelled)));
+
+        let mut writer = BufWriter::new(stdin);
+        // Dropping the writer closes stdin, so that the tool sees the end of the input.
+        let written = write_input(&mut writer).and_then(|_| writer.flush());
+        drop(writer);
+
+        let status = waiter.join().map_err(|_| {
+            io::Error::new(io::ErrorKind::BrokenPipe, "Waiting for tool panicked")
+        })??;
+        let output = reader
+            .join()
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Reading stdout panicked"))??;
+        // If the tool failed, writing to its stdin may have failed too: report the tool failure.
+        self.check_status(status)?;
+        written?;
+        Ok(output)
+    }
+
+    /// Turns the exit status of the tool, or None if it was killed on timeout, into an error if
+    /// the tool did not succeed.
+    fn check_status(&self, status: Option<ExitStatus>) -> Result<(), ExternalError> {
+        match status {
+            Some(status) if status.success() => Ok(()),
+            Some(status) => Err(ExternalError::ExitStatus(self.binary_path.clone(), status)),
+            None => Err(ExternalError::Timeout(
+                self.binary_path.clone(),
+                self.timeout.unwrap_or_default(),
+            )),
+        }
+    }
+}
+
+/// A child process that is killed when dropped, unless it has already exited, so that a tool
+/// does not keep running when the computation that started it returns early or panics.
+struct ChildGuard(Child);
+
+impl ChildGuard {
+    /// Waits for the child process to exit, and returns its exit status. If it runs for longer
+    /// than the timeout, or the cancellation flag is set, it is killed and None is returned.
+    fn wait(
+        &mut self,
+        timeout: Option<Duration>,
+        cancelled: Option<&AtomicBool>,
+    ) -> io::Result<Option<ExitStatus>> {
+        if timeout.is_none() && cancelled.is_none() {
+            return self.0.wait().map(Some);
+        }
+        let start = Instant::now();
+        loop {
+            if let Some(status) = self.0.try_wait()? {
+                return Ok(Some(status));
+            }
+            let timed_out = match timeout {
+                Some(timeout) => start.elapsed() >= timeout,
+                None => false,
+            };
+            let is_cancelled = match cancelled {
+                Some(cancelled) => cancelled.load(Ordering::Relaxed),
+                None => false,
+            };
+            if timed_out || is_cancelled {
+                self.0.kill()?;
+                self.0.wait()?;
+                return Ok(None);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        if let Ok(None) = self.0.try_wait() {
+            // The child may exit before it is killed, and then there is nothing to do.
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+}
+
+/// A flag that is set when dropped, to kill a tool that is waited for in another thread if the
+/// thread that started it returns early or panics. See [ChildGuard::wait].
+#[derive(Default)]
+struct Cancellation(Arc<AtomicBool>);
+
+impl Drop for Cancellation {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+fn missing_pipe() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::BrokenPipe,
+        "Missing pipe to the child process",
+    )
+}
+
+/// Files in the working directory of a tool, deleted when dropped unless they should be kept.
+///
+/// The names of the files start with the id of the process and a random number, which are the
+/// same for all the files of a set, so that runs with the same file names, in parallel or in
+/// different processes, do not overwrite each other's files.
+#[derive(Debug)]
+pub struct WorkingFiles {
+    directory: PathBuf,
+    prefix: String,
+    paths: Vec<PathBuf>,
+    keep: bool,
+}
+
+impl WorkingFiles {
+    /// The path of a file with the given name, after the prefix of this set of files, in the
+    /// working directory. The file does not need to exist, and it is deleted, if it exists, when
+    /// this is dropped.
+    pub fn file(&mut self, file_name: &str) -> PathBuf {
+        let path = self
+            .directory
+            .join(format!("{}_{}", self.prefix, file_name));
+        self.paths.push(path.clone());
+        path
+    }
+
+    /// The paths of the files of this set, in the order in which they were added.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// The working directory.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+}
+
+impl Drop for WorkingFiles {
+    fn drop(&mut self) {
+        if !self.keep {
+            for path in self.paths.iter() {
+                // The file may have not been created, for example if the tool failed.
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// A error that happened when running an external tool.
+#[derive(Error, Debug)]
+pub enum ExternalError {
+    #[error("Spawning {0}. Is it installed?")]
+    Spawn(PathBuf, #[source] io::Error),
+
+    #[error("{0} ended with a non-okay exit code: {1}")]
+    ExitStatus(PathBuf, ExitStatus),
+
+    #[error("{0} was killed after running for longer than {1:?}")]
+    Timeout(PathBuf, Duration),
+
+    #[error("Creating working directory {0}")]
+    CreateWorkingDir(PathBuf, #[source] io::Error),
+
+    #[error("Communicating with the tool")]
+    Io(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::marker::PhantomData;
+    use std::panic::AssertUnwindSafe;
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    use crate::external::{ChildGuard, ExternalError, Tool, ToolOptions};
+    use crate::io_utils::unique_temp_path;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Missing;
+
+    impl Tool for Missing {
+        const BINARY: &'static str = "filtration_domination_missing_tool";
+        const BINARY_ENV_VAR: &'static str = "FILTRATION_DOMINATION_MISSING_TOOL";
+    }
+
+    fn options(working_dir: PathBuf, keep_files: bool) -> ToolOptions<Missing> {
+        ToolOptions {
+            binary_path: PathBuf::from(Missing::BINARY),
+            working_dir,
+            keep_files,
+            extra_args: Vec::new(),
+            pipe: false,
+            timeout: None,
+            tool: PhantomData,
+        }
+    }
+
+    #[test]
+    fn default_options_without_env_vars() {
+        let default_options = ToolOptions::<Missing>::default();
+        assert_eq!(default_options.binary_path, PathBuf::from(Missing::BINARY));
+        assert!(default_options.keep_files);
+    }
+
+    #[test]
+    fn missing_binary_fails_to_spawn() {
+        let result = options(PathBuf::from("tmp"), false).run(["--help"]);
+        assert!(matches!(result, Err(ExternalError::Spawn(_, _))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_with_input_streams_stdin_to_stdout() {
+        let cat = ToolOptions::<Missing> {
+            binary_path: PathBuf::from("cat"),
+            ..options(PathBuf::from("tmp"), false)
+        };
+        // Large enough to fill the pipes in both directions.
+        let input: Vec<u8> = (0..1_000_000).map(|i| (i % 251) as u8).collect();
+        let output = cat.run_with_input(["-"], |w| w.write_all(&input)).unwrap();
+        assert_eq!(output, input);
+
+        let result = options(PathBuf::from("tmp"), false).run_with_input(["-"], |_| Ok(()));
+        assert!(matches!(result, Err(ExternalError::Spawn(_, _))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tools_are_killed_on_timeout() {
+        let sleep = ToolOptions::<Missing> {
+            binary_path: PathBuf::from("sleep"),
+            timeout: Some(Duration::from_millis(50)),
+            ..options(PathBuf::from("tmp"), false)
+        };
+        let start = Instant::now();
+        let result = sleep.run(["10"]);
+        assert!(matches!(result, Err(ExternalError::Timeout(_, _))));
+        // The tool does not read its stdin, so writing a lot to it blocks until it is killed.
+        let input = vec![0u8; 1_000_000];
+        let piped = sleep.run_with_input(["10"], |w| w.write_all(&input));
+        assert!(matches!(piped, Err(ExternalError::Timeout(_, _))));
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        let quick = ToolOptions {
+            timeout: Some(Duration::from_secs(10)),
+            ..sleep
+        };
+        assert_eq!(quick.run(["0"]).unwrap(), Vec::<u8>::new());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn tools_are_killed_when_not_waited_for() {
+        let is_running = |pid: u32| std::path::Path::new(&format!("/proc/{pid}")).exists();
+
+        let child = std::process::Command::new("sleep")
+            .arg("10")
+            .spawn()
+            .unwrap();
+        let sleep_pid = child.id();
+        drop(ChildGuard(child));
+        assert!(!is_running(sleep_pid));
+
+        // A tool that writes its process id, and then waits without reading its stdin.
+        let pid_path = unique_temp_path("killed_tool.pid");
+        let sh = ToolOptions::<Missing> {
+            binary_path: PathBuf::from("sh"),
+            extra_args: vec!["-c".into(), "echo $$ > $0; exec sleep 10".into()],
+            ..options(PathBuf::from("tmp"), false)
+        };
+        let panicked = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            sh.run_with_input([&pid_path], |_| {
+                while !pid_path.exists() {
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                panic!("Cancelling the computation");
+            })
+        }));
+        assert!(panicked.is_err());
+        let tool_pid: u32 = std::fs::read_to_string(&pid_path)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        std::fs::remove_file(&pid_path).unwrap();
+        let start = Instant::now();
+        while is_running(tool_pid) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn working_files_are_deleted_unless_kept() {
+        let working_dir = unique_temp_path("working_files");
+        let mut deleted = options(working_dir.clone(), false).working_files().unwrap();
+        let deleted_path = deleted.file("deleted");
+        std::fs::write(&deleted_path, "").unwrap();
+        drop(deleted);
+        assert!(!deleted_path.exists());
+
+        let mut kept = options(working_dir.clone(), true).working_files().unwrap();
+        let kept_path = kept.file("kept");
+        // Another set of files with the same names gets other paths.
+        let mut other = options(working_dir.clone(), true).working_files().unwrap();
+        assert_ne!(other.file("kept"), kept_path);
+        assert!(kept_path.to_str().unwrap().ends_with("_kept"));
+        std::fs::write(&kept_path, "").unwrap();
+        drop(kept);
+        assert!(kept_path.exists());
+
+        std::fs::remove_dir_all(working_dir).unwrap();
+    }
+}
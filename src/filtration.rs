@@ -1,22 +1,258 @@
+//! Filtrations of simplicial complexes, where each simplex has a critical grade, and the
+//! construction of flag filtrations from filtered edges, see [build_flag_filtration].
+use num::ToPrimitive;
 use sorted_iter::assume::AssumeSortedByItemExt;
 use sorted_iter::SortedIterator;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error as StdError;
+use std::io;
 use thiserror::Error;
 
-use crate::chain_complex::{ChainComplex, Column, GradedMatrix, ToFreeImplicitRepresentation};
-use crate::edges::{BareEdge, FilteredEdge};
-use crate::simplicial_complex::{is_sorted, Dimension, SimplicialComplex, Vertex};
+use crate::chain_complex::{
+    write_scc2020_column, ChainComplex, Column, GradedMatrix, ToFreeImplicitRepresentation,
+};
+use crate::cliques::{degeneracy_ranks, for_each_clique};
+use crate::edges::{BareEdge, Edge, EdgeList, EdgeListError, FilteredEdge};
+use crate::simplicial_complex::{
+    is_sorted, vec_bytes, Dimension, MapSimplicialComplex, SimplexKey, SimplicialComplex, Vertex,
+    WideMapSimplicialComplex,
+};
+use crate::trace::pass;
 use crate::{CriticalGrade, OneCriticalGrade, Value};
 
 #[derive(Error, Debug)]
 enum EmptyError {}
 
+/// The reasons why a simplex cannot be added to a filtration, or a flag filtration cannot be built
+/// from some edges. See [Filtration::try_add] and [try_build_flag_filtration].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FiltrationError {
+    #[error("A simplex must have at least one vertex")]
+    EmptySimplex,
+
+    #[error("The vertices of the simplex {0:?} are not strictly increasing")]
+    UnsortedSimplex(Vec<Vertex>),
+
+    #[error("The simplex {0:?} has a vertex out of the range 0..{1}")]
+    VertexOutOfRange(Vec<Vertex>, usize),
+
+    #[error("The simplex {0:?} has dimension greater than the maximum dimension {1}")]
+    DimensionOutOfRange(Vec<Vertex>, Dimension),
+
+    #[error("A facet of the simplex {0:?} is not in the filtration")]
+    MissingFacet(Vec<Vertex>),
+
+    #[error(
+        "The grade of the simplex {0:?} is not greater than or equal to the grades of its facets"
+    )]
+    GradeLowerThanFacet(Vec<Vertex>),
+
+    #[error(transparent)]
+    InvalidEdge(#[from] EdgeListError),
+}
+
 /// Build a flag multi-filtration from an iterator of multi-filtered edges.
 /// The iterator does not need to be sorted.
 /// The resulting multi-filtration is 1-critical.
-#[allow(dead_code)]
-fn build_flag_filtration<G: CriticalGrade, S, I: Iterator<Item = FilteredEdge<G>>>(
+pub fn build_flag_filtration<G: CriticalGrade, S, I: Iterator<Item = FilteredEdge<G>>>(
+    vertices: usize,
+    max_dim: usize,
+    edges: I,
+) -> Filtration<G, S>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    let result = build_flag_filtration_with_check::<
+        _,
+        _,
+        _,
+        EmptyError,
+        fn(usize) -> Result<(), EmptyError>,
+    >(vertices, max_dim, edges, None);
+    match result {
+        Ok(filtration) => filtration,
+        Err(never) => match never {},
+    }
+}
+
+/// As [build_flag_filtration], but returns an error instead of panicking if an edge is a self-loop,
+/// has a vertex out of the range 0..`vertices`, or has a grade that is not greater than or equal to
+/// the grade of the vertices, [CriticalGrade::zero]. The edges are checked as they are added, so
+/// they are not collected.
+pub fn try_build_flag_filtration<G: CriticalGrade, S, I: Iterator<Item = FilteredEdge<G>>>(
+    vertices: usize,
+    max_dim: usize,
+    edges: I,
+) -> Result<Filtration<G, S>, FiltrationError>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    let vertex_grade = G::zero();
+    let mut invalid_edge = None;
+    let checked_edges =
+        edges.map_while(
+            |edge| match check_flag_edge(vertices, &vertex_grade, &edge) {
+                Ok(()) => Some(edge),
+                Err(err) => {
+                    invalid_edge = Some(err);
+                    None
+                }
+            },
+        );
+    let filtration = build_flag_filtration(vertices, max_dim, checked_edges);
+    match invalid_edge {
+        Some(err) => Err(err),
+        None => Ok(filtration),
+    }
+}
+
+/// Checks that the edges of the edge list can be given to [build_flag_filtration] without
+/// panicking, as [try_build_flag_filtration] does, but without building the filtration.
+pub fn validate_flag_edges<G: CriticalGrade>(
+    edge_list: &EdgeList<FilteredEdge<G>>,
+) -> Result<(), FiltrationError> {
+    let vertex_grade = G::zero();
+    edge_list
+        .edge_iter()
+        .try_for_each(|edge| check_flag_edge(edge_list.n_vertices, &vertex_grade, edge))
+}
+
+/// Upper bounds on the number of simplices of each dimension of a flag filtration, see
+/// [estimate_flag_size].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlagSizeEstimate {
+    /// An upper bound on the number of simplices of each dimension, from 0 to the maximum
+    /// dimension. The numbers of vertices and of edges are exact.
+    pub sizes: Vec<usize>,
+    /// The degeneracy of the graph: every vertex has at most this many neighbours after it in a
+    /// degeneracy ordering.
+    pub degeneracy: usize,
+}
+
+impl FlagSizeEstimate {
+    /// An upper bound on the total number of simplices.
+    pub fn total(&self) -> usize {
+        self.sizes
+            .iter()
+            .fold(0, |total, &size| total.saturating_add(size))
+    }
+}
+
+/// Bounds the number of simplices of each dimension, up to `max_dim`, of the flag filtration of
+/// the edges, without building it, so that a computation can give up before building a filtration
+/// that does not fit in memory.
+///
+/// Each clique is counted from its first vertex in a degeneracy ordering of the graph, among the
+/// neighbours after that vertex, so a vertex with `k` such neighbours starts at most `k choose d`
+/// simplices of dimension `d`. This runs in time linear in the size of the graph. The bounds
+/// saturate at [usize::MAX]. Repeated edges and self-loops are ignored.
+pub fn estimate_flag_size<E: Edge>(edge_list: &EdgeList<E>, max_dim: usize) -> FlagSizeEstimate {
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); edge_list.n_vertices];
+    for edge in edge_list.edge_iter() {
+        let (u, v) = (edge.u(), edge.v());
+        if u != v {
+            neighbours[u].push(v);
+            neighbours[v].push(u);
+        }
+    }
+    let mut n_edges = 0;
+    for vertex_neighbours in neighbours.iter_mut() {
+        vertex_neighbours.sort_unstable();
+        vertex_neighbours.dedup();
+        n_edges += vertex_neighbours.len();
+    }
+    let rank = degeneracy_ranks(&neighbours);
+    let later_neighbours: Vec<usize> = neighbours
+        .iter()
+        .enumerate()
+        .map(|(v, vertex_neighbours)| {
+            vertex_neighbours
+                .iter()
+                .filter(|&&u| rank[u] > rank[v])
+                .count()
+        })
+        .collect();
+
+    let mut sizes = Vec::with_capacity(max_dim + 1);
+    for dim in 0..=max_dim {
+        let size = match dim {
+            0 => edge_list.n_vertices,
+            1 => n_edges / 2,
+            _ => later_neighbours
+                .iter()
+                .fold(0, |total: usize, &k| total.saturating_add(binomial(k, dim))),
+        };
+        sizes.push(size);
+    }
+    FlagSizeEstimate {
+        sizes,
+        degeneracy: later_neighbours.iter().copied().max().unwrap_or(0),
+    }
+}
+
+/// The binomial coefficient `n choose k`, saturating at [usize::MAX].
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    let mut result: u128 = 1;
+    for i in 0..k {
+        // The product of i + 1 consecutive numbers is divisible by (i + 1)!.
+        result = match result.checked_mul((n - i) as u128) {
+            Some(product) => product / (i + 1) as u128,
+            None => return usize::MAX,
+        };
+        if result > usize::MAX as u128 {
+            return usize::MAX;
+        }
+    }
+    result as usize
+}
+
+fn check_flag_edge<G: CriticalGrade>(
+    vertices: usize,
+    vertex_grade: &G,
+    edge: &FilteredEdge<G>,
+) -> Result<(), FiltrationError> {
+    let BareEdge(u, v) = edge.edge;
+    if u == v {
+        return Err(EdgeListError::SelfLoop(edge.edge).into());
+    }
+    if u.max(v) >= vertices {
+        return Err(EdgeListError::VertexOutOfRange(edge.edge, vertices).into());
+    }
+    if !vertex_grade.lte(&edge.grade) {
+        return Err(FiltrationError::GradeLowerThanFacet(vec![
+            u.min(v),
+            u.max(v),
+        ]));
+    }
+    Ok(())
+}
+
+/// The maximum dimension up to which [build_flag_filtration_by_degeneracy] enumerates cliques with
+/// a degeneracy ordering, instead of expanding each edge as it is added.
+const MAX_DEGENERACY_DIMENSION: usize = 3;
+
+/// Build a flag multi-filtration from an iterator of multi-filtered edges, as
+/// [build_flag_filtration], but, up to dimension 3, the cliques are enumerated with a degeneracy
+/// ordering of the whole graph, and then added in the same order in which expanding each edge, as
+/// it is added, would add them. Above dimension 3, it is the same as [build_flag_filtration].
+///
+/// The resulting filtration is the same, but it is built faster on dense graphs, at the cost of
+/// memory: the cliques are collected and sorted before they are added, which raises the peak
+/// memory usage to 2 to 2.5 times the size of the filtration. On a sampled torus of 400 points,
+/// with the edges below the 30th percentile of the distances, building up to dimension 3 is 1.7
+/// times faster but peaks at 1.65 GB instead of 0.67 GB, and building up to dimension 2 takes the
+/// same time but peaks at 93 MB instead of 45 MB. Hence, it is only worth it when time, and not
+/// memory, is the bottleneck.
+pub fn build_flag_filtration_by_degeneracy<
+    G: CriticalGrade,
+    S,
+    I: Iterator<Item = FilteredEdge<G>>,
+>(
     vertices: usize,
     max_dim: usize,
     edges: I,
@@ -24,12 +260,31 @@ fn build_flag_filtration<G: CriticalGrade, S, I: Iterator<Item = FilteredEdge<G>
 where
     S: for<'a> SimplicialComplex<'a>,
 {
-    build_flag_filtration_with_check::<_, _, _, EmptyError, fn(usize) -> Result<(), EmptyError>>(
-        vertices, max_dim, edges, None,
-    )
-    .unwrap()
+    if max_dim > MAX_DEGENERACY_DIMENSION {
+        return build_flag_filtration(vertices, max_dim, edges);
+    }
+    let pass =
+        pass!("build_flag_filtration", vertices = vertices, max_dim = max_dim; edges, simplices);
+    let result = build_flag_filtration_by_degeneracy_with_check::<
+        _,
+        _,
+        _,
+        EmptyError,
+        fn(usize) -> Result<(), EmptyError>,
+    >(vertices, max_dim, edges, None);
+    let filtration = match result {
+        Ok(filtration) => filtration,
+        Err(never) => match never {},
+    };
+    let sizes = filtration.sizes();
+    pass.record("edges", sizes.get(1).copied().unwrap_or(0));
+    pass.record("simplices", sizes.iter().sum());
+    filtration
 }
 
+/// Build a flag multi-filtration from an iterator of multi-filtered edges, as
+/// [build_flag_filtration], calling `check` periodically with the index of the edge that is being
+/// added, and stopping if it fails.
 pub fn build_flag_filtration_with_check<
     G: CriticalGrade,
     S,
@@ -42,6 +297,36 @@ pub fn build_flag_filtration_with_check<
     edges: I,
     check: Option<F>,
 ) -> Result<Filtration<G, S>, E>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    let pass =
+        pass!("build_flag_filtration", vertices = vertices, max_dim = max_dim; edges, simplices);
+    let filtration = build_flag_filtration_by_expansion(vertices, max_dim, edges, check)?;
+    let sizes = filtration.sizes();
+    pass.record("edges", sizes.get(1).copied().unwrap_or(0));
+    pass.record("simplices", sizes.iter().sum());
+    Ok(filtration)
+}
+
+/// Build a flag multi-filtration by adding each edge, followed by the cliques that it creates.
+///
+/// The grade of each clique is the join of the grades of its edges, which is computed
+/// incrementally while the clique grows, see [add_flag_simplex], instead of looking up the grades
+/// of its facets in the filtration.
+#[allow(clippy::needless_pass_by_value)]
+fn build_flag_filtration_by_expansion<
+    G: CriticalGrade,
+    S,
+    I: Iterator<Item = FilteredEdge<G>>,
+    E: StdError,
+    F: Fn(usize) -> Result<(), E>,
+>(
+    vertices: usize,
+    max_dim: usize,
+    edges: I,
+    check: Option<F>,
+) -> Result<Filtration<G, S>, E>
 where
     S: for<'a> SimplicialComplex<'a>,
 {
@@ -54,43 +339,198 @@ where
         f.add(G::zero(), &vertex_simplex);
     }
 
-    let mut neighbours: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); vertices];
+    // The neighbours of each vertex, with the grade of the edge to them.
+    let mut neighbours: Vec<BTreeMap<usize, G>> = vec![BTreeMap::new(); vertices];
 
     let mut simplex_buffer = BTreeSet::new();
     for (iteration, filtered_edge) in edges.enumerate() {
         if let Some(ref check_fn) = check {
             check_fn(iteration)?;
         }
+        if max_dim == 0 {
+            continue;
+        }
         let BareEdge(u, v) = filtered_edge.edge;
         simplex_buffer.insert(u);
         simplex_buffer.insert(v);
-        f.add_iter(filtered_edge.grade, 1, simplex_buffer.iter().copied());
+        // A repeated edge keeps the grade it was first added with.
+        let edge_grade = neighbours[u]
+            .get(&v)
+            .cloned()
+            .unwrap_or(filtered_edge.grade);
+        f.add_iter(edge_grade.clone(), 1, simplex_buffer.iter().copied());
 
-        let common_neighbours: BTreeSet<usize> = neighbours[u]
-            .intersection(&neighbours[v])
-            .copied()
+        let common_neighbours: Vec<(usize, G)> = neighbours[u]
+            .iter()
+            .filter_map(|(w, grade_u)| {
+                neighbours[v]
+                    .get(w)
+                    .map(|grade_v| (*w, grade_u.join(grade_v)))
+            })
             .collect();
         add_flag_simplex(
             &mut f,
             &neighbours,
             max_dim,
+            &edge_grade,
             &common_neighbours,
             &mut simplex_buffer,
         );
 
-        neighbours[u].insert(v);
-        neighbours[v].insert(u);
+        neighbours[u].entry(v).or_insert_with(|| edge_grade.clone());
+        neighbours[v].entry(u).or_insert(edge_grade);
         simplex_buffer.clear();
     }
 
     Ok(f)
 }
 
+/// A clique of dimension 2 or 3, to be added to a filtration.
+struct Clique<G> {
+    /// The index of the last of its edges, the one that creates the clique.
+    creating_edge: usize,
+    /// The vertices that are not endpoints of the creating edge, in decreasing order, padded with
+    /// zeros.
+    others: [usize; MAX_DEGENERACY_DIMENSION - 1],
+    /// Its vertices, in increasing order, padded with zeros.
+    vertices: [usize; MAX_DEGENERACY_DIMENSION + 1],
+    grade: G,
+}
+
+/// Build a flag multi-filtration, of dimension at most 3, by adding all the edges, and then all
+/// the cliques of each dimension, found with [for_each_clique]. The check is called with the
+/// number of edges and cliques visited so far, so that it also runs while the cliques are
+/// enumerated.
+///
+/// The cliques created by an edge are ordered as [add_flag_simplex] adds them: the vertices that
+/// are added to the edge are decreasing, and the cliques are visited in depth-first order, so it
+/// is enough to sort them by the creating edge and then lexicographically by the added vertices.
+#[allow(clippy::needless_pass_by_value)]
+fn build_flag_filtration_by_degeneracy_with_check<
+    G: CriticalGrade,
+    S,
+    I: Iterator<Item = FilteredEdge<G>>,
+    E: StdError,
+    F: Fn(usize) -> Result<(), E>,
+>(
+    vertices: usize,
+    max_dim: usize,
+    edges: I,
+    check: Option<F>,
+) -> Result<Filtration<G, S>, E>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    debug_assert!(max_dim <= MAX_DEGENERACY_DIMENSION);
+    let mut f: Filtration<G, S> = Filtration::new_empty(vertices, max_dim);
+    let mut vertex_simplex = [0];
+
+    // Add vertices.
+    for v in 0..vertices {
+        vertex_simplex[0] = v;
+        f.add(G::zero(), &vertex_simplex);
+    }
+
+    // Add edges. Repeated edges are skipped, so the index of an edge is its index in the filtration.
+    let mut iteration = 0;
+    let mut bare_edges: Vec<BareEdge> = Vec::new();
+    let mut grades: Vec<G> = Vec::new();
+    for filtered_edge in edges {
+        if let Some(ref check_fn) = check {
+            check_fn(iteration)?;
+        }
+        iteration += 1;
+        if max_dim == 0 {
+            continue;
+        }
+        let BareEdge(u, v) = filtered_edge.edge;
+        let edge = BareEdge(u.min(v), u.max(v));
+        let added = f.add_iter(
+            filtered_edge.grade.clone(),
+            1,
+            [edge.0, edge.1].into_iter().assume_sorted_by_item(),
+        );
+        if added.is_some() {
+            bare_edges.push(edge);
+            grades.push(filtered_edge.grade);
+        }
+    }
+
+    // Find all cliques, and add them in order, by increasing dimension.
+    let mut cliques: Vec<Vec<Clique<G>>> = (0..=max_dim).map(|_| Vec::new()).collect();
+    for_each_clique(
+        vertices,
+        &bare_edges,
+        max_dim + 1,
+        |clique_vertices, edge_indices| {
+            if let Some(ref check_fn) = check {
+                check_fn(iteration)?;
+            }
+            iteration += 1;
+
+            let mut creating_edge = 0;
+            let mut grade = G::min_value();
+            for &idx in edge_indices {
+                creating_edge = creating_edge.max(idx);
+                grade = grade.join(&grades[idx]);
+            }
+            let BareEdge(u, v) = bare_edges[creating_edge];
+            let mut clique = Clique {
+                creating_edge,
+                others: [0; MAX_DEGENERACY_DIMENSION - 1],
+                vertices: [0; MAX_DEGENERACY_DIMENSION + 1],
+                grade,
+            };
+            let mut n_others = 0;
+            for (i, &w) in clique_vertices.iter().enumerate() {
+                clique.vertices[i] = w;
+                if w != u && w != v {
+                    clique.others[n_others] = w;
+                    n_others += 1;
+                }
+            }
+            let dim = clique_vertices.len() - 1;
+            clique.vertices[..=dim].sort_unstable();
+            clique.others[..n_others].sort_unstable_by(|a, b| b.cmp(a));
+            cliques[dim].push(clique);
+            Ok(())
+        },
+    )?;
+
+    for (dim, dim_cliques) in cliques.iter_mut().enumerate().skip(2) {
+        dim_cliques.sort_unstable_by_key(|clique| (clique.creating_edge, clique.others));
+        for clique in dim_cliques.drain(..) {
+            // The facets of the clique, of lower dimension, have all been added before, and its
+            // grade is the join of their grades.
+            f.add_iter_unchecked(
+                clique.grade,
+                dim,
+                clique.vertices[..=dim]
+                    .iter()
+                    .copied()
+                    .assume_sorted_by_item(),
+            );
+        }
+    }
+
+    Ok(f)
+}
+
+/// Adds the cofaces of `simplex` that are cliques of the graph, up to dimension `max_dim`.
+///
+/// The common neighbours of the vertices of the simplex come with the join of the grades of their
+/// edges to the simplex, so that the grade of each coface, the join of the grades of its edges, is
+/// computed from the grade of the simplex without looking up the grades of its facets. Building
+/// the flag filtration up to dimension 5 of the sampled torus of 200 points, with the edges below
+/// the 15th percentile of the distances, this is about 1.6x faster, and on the uniform sample of
+/// 400 points, with the edges below the 8th percentile, about 1.25x faster. The rest of the time
+/// is mostly spent adding the simplices to the simplicial complex.
 fn add_flag_simplex<G: CriticalGrade, S>(
     f: &mut Filtration<G, S>,
-    neighbours: &[BTreeSet<usize>],
+    neighbours: &[BTreeMap<usize, G>],
     max_dim: usize,
-    common_neighbours: &BTreeSet<usize>,
+    simplex_grade: &G,
+    common_neighbours: &[(usize, G)],
     simplex: &mut BTreeSet<usize>,
 ) where
     S: for<'a> SimplicialComplex<'a>,
@@ -101,32 +541,199 @@ fn add_flag_simplex<G: CriticalGrade, S>(
         return;
     }
 
-    for v in common_neighbours.iter() {
+    for (v, v_grade) in common_neighbours.iter() {
         simplex.insert(*v);
 
-        let mut vf = G::min_value();
-        for boundary_idx in f
-            .simplicial_complex()
-            .simplex_boundary(dim, simplex.iter().copied())
-        {
-            let boundary_vf = f.value_of(dim - 1, boundary_idx);
-            vf = vf.join(boundary_vf);
-        }
-
-        f.add_iter(vf, dim, simplex.iter().copied());
+        let grade = simplex_grade.join(v_grade);
+        f.add_iter(grade.clone(), dim, simplex.iter().copied());
 
         if dim < max_dim {
             // Recurse.
+            let new_common_neighbours: Vec<(usize, G)> = common_neighbours
+                .iter()
+                .take_while(|(x, _)| x < v)
+                .filter_map(|(x, x_grade)| {
+                    neighbours[*v]
+                        .get(x)
+                        .map(|edge_grade| (*x, x_grade.join(edge_grade)))
+                })
+                .collect();
+            add_flag_simplex(
+                f,
+                neighbours,
+                max_dim,
+                &grade,
+                &new_common_neighbours,
+                simplex,
+            );
+        }
+
+        simplex.remove(v);
+    }
+}
+
+/// Writes, in the scc2020 format, the chain complex that computes the homology of the given
+/// dimension of the flag bifiltration of the given edges.
+///
+/// The output is the same as the one of building the flag filtration up to dimension
+/// `homology + 1` and writing it, but the simplices of dimension `homology + 1` (usually the
+/// majority) are never stored: they are enumerated on the fly, once to count them and once more
+/// to write them. This lowers the peak memory usage on dense graphs.
+///
+/// Returns an error of kind [io::ErrorKind::InvalidInput], before writing anything, if the edges
+/// cannot be given to [build_flag_filtration], see [validate_flag_edges]. In particular, the
+/// vertices have the same grade as in [build_flag_filtration], [CriticalGrade::zero], so edges
+/// with a negative grade are rejected, instead of being written at the grade of the vertices.
+pub fn write_flag_scc2020<VF: Value, W: io::Write, const N: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    homology: usize,
+    w: &mut W,
+) -> io::Result<()> {
+    validate_flag_edges(edge_list)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    if usize::fits(edge_list.n_vertices, homology + 1) {
+        write_flag_scc2020_with::<_, _, MapSimplicialComplex, N>(edge_list, homology, w)
+    } else {
+        write_flag_scc2020_with::<_, _, WideMapSimplicialComplex, N>(edge_list, homology, w)
+    }
+}
+
+fn write_flag_scc2020_with<VF: Value, W: io::Write, S, const N: usize>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    homology: usize,
+    w: &mut W,
+) -> io::Result<()>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    let filtration: Filtration<_, S> = build_flag_filtration(
+        edge_list.n_vertices,
+        homology,
+        edge_list.edge_iter().cloned(),
+    );
+    let complex = filtration.simplicial_complex();
+    let top_dimension = homology + 1;
+
+    let mut n_top_simplices: usize = 0;
+    for_each_flag_simplex(edge_list, top_dimension, |_edge, _simplex| {
+        n_top_simplices += 1;
+        Ok::<(), io::Error>(())
+    })?;
+    let n_low_simplices = if homology > 0 {
+        complex.n_cells(homology - 1)
+    } else {
+        0
+    };
+
+    writeln!(w, "scc2020")?;
+    writeln!(w, "{}", N)?;
+    writeln!(
+        w,
+        "{} {} {}",
+        n_top_simplices,
+        complex.n_cells(homology),
+        n_low_simplices
+    )?;
+
+    let mut boundary = Vec::with_capacity(top_dimension + 1);
+    for_each_flag_simplex(edge_list, top_dimension, |edge, simplex| {
+        boundary.clear();
+        boundary.extend(complex.simplex_boundary(
+            top_dimension,
+            simplex.iter().copied().assume_sorted_by_item(),
+        ));
+        let mut grade = edge.grade;
+        for facet_idx in boundary.iter() {
+            grade = grade.join(filtration.value_of(homology, *facet_idx));
+        }
+        write_scc2020_column(w, &grade, boundary.iter().copied())
+    })?;
+    writeln!(w)?;
+
+    for idx in 0..complex.n_cells(homology) {
+        write_scc2020_column(
+            w,
+            filtration.value_of(homology, idx),
+            complex.boundary_iterator(homology, idx),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Calls `f` on each simplex of the given dimension, which must be at least 1, of the flag
+/// complex of the given edges, together with the edge whose insertion created the simplex.
+/// The simplices are visited in the same order in which [build_flag_filtration_with_check]
+/// adds them.
+fn for_each_flag_simplex<G: CriticalGrade, E, F>(
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    dim: usize,
+    mut f: F,
+) -> Result<(), E>
+where
+    F: FnMut(&FilteredEdge<G>, &BTreeSet<usize>) -> Result<(), E>,
+{
+    let mut neighbours: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); edge_list.n_vertices];
+
+    let mut simplex_buffer = BTreeSet::new();
+    for filtered_edge in edge_list.edge_iter() {
+        let BareEdge(u, v) = filtered_edge.edge;
+        if neighbours[u].contains(&v) {
+            // Repeated edges do not add new simplices.
+            continue;
+        }
+        simplex_buffer.insert(u);
+        simplex_buffer.insert(v);
+
+        if dim == 1 {
+            f(filtered_edge, &simplex_buffer)?;
+        } else {
+            let common_neighbours: BTreeSet<usize> = neighbours[u]
+                .intersection(&neighbours[v])
+                .copied()
+                .collect();
+            visit_flag_simplices(
+                &neighbours,
+                dim,
+                &common_neighbours,
+                &mut simplex_buffer,
+                &mut |simplex| f(filtered_edge, simplex),
+            )?;
+        }
+
+        neighbours[u].insert(v);
+        neighbours[v].insert(u);
+        simplex_buffer.clear();
+    }
+    Ok(())
+}
+
+fn visit_flag_simplices<E, F: FnMut(&BTreeSet<usize>) -> Result<(), E>>(
+    neighbours: &[BTreeSet<usize>],
+    dim: usize,
+    common_neighbours: &BTreeSet<usize>,
+    simplex: &mut BTreeSet<usize>,
+    f: &mut F,
+) -> Result<(), E> {
+    // Adding a vertex to the simplex results in a simplex of dimension simplex.len().
+    let current_dim = simplex.len();
+    for v in common_neighbours.iter() {
+        simplex.insert(*v);
+
+        if current_dim == dim {
+            f(simplex)?;
+        } else {
             let new_common_neighbours: BTreeSet<usize> = common_neighbours
                 .intersection(&neighbours[*v])
                 .copied()
                 .filter(|x| x < v)
                 .collect();
-            add_flag_simplex(f, neighbours, max_dim, &new_common_neighbours, simplex);
+            visit_flag_simplices(neighbours, dim, &new_common_neighbours, simplex, f)?;
         }
 
         simplex.remove(v);
     }
+    Ok(())
 }
 
 #[derive(Debug)]
@@ -156,6 +763,11 @@ where
         Self::new(s)
     }
 
+    /// Adds the simplex with the given vertices, in increasing order, at the given grade, and
+    /// returns its dimension and index, or None if it was already in the filtration.
+    ///
+    /// Panics: if the simplex cannot be added. See [Filtration::try_add] for a non-panicking
+    /// version.
     pub fn add(&mut self, g: G, s: &[Vertex]) -> Option<(Dimension, usize)> {
         assert!(is_sorted(s), "To add a simplex it must be sorted first.");
 
@@ -185,13 +797,437 @@ where
         added_simplex
     }
 
+    /// As [Filtration::add], but returns an error instead of panicking if the vertices are not
+    /// strictly increasing or out of range, the dimension is greater than the maximum dimension,
+    /// or a facet of the simplex is missing or has a grade that is not less than or equal to the
+    /// given one.
+    pub fn try_add(
+        &mut self,
+        g: G,
+        s: &[Vertex],
+    ) -> Result<Option<(Dimension, usize)>, FiltrationError> {
+        if s.is_empty() {
+            return Err(FiltrationError::EmptySimplex);
+        }
+        if !s.windows(2).all(|w| w[0] < w[1]) {
+            return Err(FiltrationError::UnsortedSimplex(s.to_vec()));
+        }
+        let max_vertices = self.complex.max_vertices();
+        if s[s.len() - 1] >= max_vertices {
+            return Err(FiltrationError::VertexOutOfRange(s.to_vec(), max_vertices));
+        }
+        let dim = s.len() - 1;
+        if dim > self.complex.max_dimension() {
+            return Err(FiltrationError::DimensionOutOfRange(
+                s.to_vec(),
+                self.complex.max_dimension(),
+            ));
+        }
+        if dim > 0 {
+            let mut facet = Vec::with_capacity(dim);
+            for removed in 0..s.len() {
+                facet.clear();
+                facet.extend(s[..removed].iter().chain(&s[removed + 1..]));
+                let (_, facet_idx) = self
+                    .complex
+                    .find(&facet)
+                    .ok_or_else(|| FiltrationError::MissingFacet(s.to_vec()))?;
+                if !self.grades[dim - 1][facet_idx].lte(&g) {
+                    return Err(FiltrationError::GradeLowerThanFacet(s.to_vec()));
+                }
+            }
+        }
+        Ok(self.add_iter_unchecked(g, dim, s.iter().copied().assume_sorted_by_item()))
+    }
+
+    /// As [Filtration::add_iter], but without checking that the facets of the simplex have been
+    /// added before, with grades less than or equal to the given one. The checks still run in
+    /// debug builds.
+    pub(crate) fn add_iter_unchecked<I: SortedIterator<Item = usize>>(
+        &mut self,
+        g: G,
+        dim: Dimension,
+        iter: I,
+    ) -> Option<(Dimension, usize)> {
+        if cfg!(debug_assertions) {
+            return self.add_iter(g, dim, iter);
+        }
+        let added_simplex = self.complex.add_iter_unchecked(dim, iter);
+        if let Some((dimension, _)) = added_simplex {
+            self.grades[dimension].push(g);
+        }
+        added_simplex
+    }
+
+    /// The grade of the simplex of the given dimension and index.
+    ///
+    /// Panics: if there is no such simplex. See [Filtration::try_value_of].
     pub fn value_of(&self, dim: Dimension, idx: usize) -> &G {
         &self.grades[dim][idx]
     }
 
+    /// The grade of the simplex of the given dimension and index, or None if there is no such
+    /// simplex.
+    pub fn try_value_of(&self, dim: Dimension, idx: usize) -> Option<&G> {
+        self.grades.get(dim)?.get(idx)
+    }
+
+    /// The grade of the simplex with the given vertices, in any order, or None if the simplex is
+    /// not in the filtration.
+    pub fn grade_of_simplex(&self, s: &[Vertex]) -> Option<&G> {
+        let mut vertices = s.to_vec();
+        vertices.sort_unstable();
+        let (dim, idx) = self.complex.find(&vertices)?;
+        self.try_value_of(dim, idx)
+    }
+
+    /// Iterates over the simplices of the given dimension, in the order in which they were added,
+    /// as pairs of their vertices, in increasing order, and their grade. It is empty if the
+    /// dimension is greater than the maximum dimension of the filtration.
+    pub fn simplices(&self, dim: Dimension) -> impl Iterator<Item = (Vec<Vertex>, &G)> + '_ {
+        let grades = self.grades.get(dim).map(Vec::as_slice).unwrap_or_default();
+        grades
+            .iter()
+            .enumerate()
+            .map(move |(idx, grade)| (self.complex.simplex_vertices(dim, idx).collect(), grade))
+    }
+
     pub fn simplicial_complex(&self) -> &S {
         &self.complex
     }
+
+    /// The subfiltration that keeps all the vertices, the given edges, and the simplices of higher
+    /// dimension whose facets are all kept. The simplices keep their grades and relative order.
+    /// Edges that are not in the filtration are ignored.
+    ///
+    /// Restricting the flag filtration of an edge list to a subset of its edges, like the edges
+    /// that remain after [crate::removal], gives the flag filtration of the subset, so both can be
+    /// compared after building the larger one only once.
+    pub fn restrict_to_edges<I: IntoIterator<Item = BareEdge>>(&self, edges: I) -> Self {
+        let max_dim = self.complex.max_dimension();
+        let mut restricted: Filtration<G, S> =
+            Filtration::new_empty(self.complex.n_cells(0), max_dim);
+
+        // Whether each simplex of the previous dimension is kept.
+        let mut kept_facets: Vec<bool> = vec![true; self.complex.n_cells(0)];
+        for (idx, grade) in self.grades[0].iter().enumerate() {
+            restricted.add_iter_unchecked(
+                grade.clone(),
+                0,
+                self.complex
+                    .simplex_vertices(0, idx)
+                    .assume_sorted_by_item(),
+            );
+        }
+        if max_dim == 0 {
+            return restricted;
+        }
+
+        let mut kept_edges = vec![false; self.complex.n_cells(1)];
+        for BareEdge(u, v) in edges {
+            if let Some((1, idx)) = self.complex.find(&[u.min(v), u.max(v)]) {
+                kept_edges[idx] = true;
+            }
+        }
+        for dim in 1..=max_dim {
+            let kept: Vec<bool> = if dim == 1 {
+                std::mem::take(&mut kept_edges)
+            } else {
+                (0..self.complex.n_cells(dim))
+                    .map(|idx| {
+                        self.complex
+                            .boundary_iterator(dim, idx)
+                            .all(|facet_idx| kept_facets[facet_idx])
+                    })
+                    .collect()
+            };
+            for (idx, grade) in self.grades[dim].iter().enumerate() {
+                if kept[idx] {
+                    restricted.add_iter_unchecked(
+                        grade.clone(),
+                        dim,
+                        self.complex
+                            .simplex_vertices(dim, idx)
+                            .assume_sorted_by_item(),
+                    );
+                }
+            }
+            kept_facets = kept;
+        }
+        restricted
+    }
+
+    /// The number of simplices of each dimension, from dimension 0 to the maximum dimension.
+    pub fn sizes(&self) -> Vec<usize> {
+        self.grades.iter().map(Vec::len).collect()
+    }
+
+    /// An estimate of the heap memory used by the filtration, in bytes: the grades and the
+    /// simplicial complex, see [SimplicialComplex::memory_bytes_estimate]. It does not include
+    /// memory owned by the grades themselves, like that of k-critical grades.
+    pub fn memory_bytes_estimate(&self) -> usize {
+        vec_bytes(&self.grades)
+            + self.grades.iter().map(vec_bytes).sum::<usize>()
+            + self.complex.memory_bytes_estimate()
+    }
+}
+
+/// Text formats of the graded Hasse diagram of a filtration, see
+/// [Filtration::write_hasse_diagram].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HasseDiagramFormat {
+    /// Tab-separated values, with a header line and one line per simplex. Lists of values, like
+    /// the vertices, are separated by commas.
+    #[default]
+    Tsv,
+    /// A JSON array with one object per simplex, one per line.
+    Json,
+}
+
+impl<VF: Value, S, const N: usize> Filtration<OneCriticalGrade<VF, N>, S>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    /// Writes the graded Hasse diagram of the filtration: for each simplex, its id, its
+    /// dimension, its vertices, its grade, and the ids of its facets, in increasing order. The
+    /// simplices are numbered consecutively from 0, by dimension and then in the order in which
+    /// they were added, which is the order in which they are written.
+    ///
+    /// Unlike [ToFreeImplicitRepresentation::write_scc2020], which only writes the dimensions
+    /// needed to compute the homology of one dimension, this writes all the simplices, for
+    /// scripts that process the filtration on their own.
+    ///
+    /// In TSV, the columns are `id`, `dimension`, `vertices`, `grade` and `facets`. In JSON, each
+    /// simplex is an object with the same keys. JSON numbers cannot be infinite or NaN, so such
+    /// values of the grades are written as the strings `"inf"`, `"-inf"` and `"NaN"`.
+    pub fn write_hasse_diagram<W: io::Write>(
+        &self,
+        format: HasseDiagramFormat,
+        w: &mut W,
+    ) -> io::Result<()> {
+        match format {
+            HasseDiagramFormat::Tsv => writeln!(w, "id\tdimension\tvertices\tgrade\tfacets")?,
+            HasseDiagramFormat::Json => writeln!(w, "[")?,
+        }
+        let n_simplices: usize = self.sizes().iter().sum();
+        // The id of the first simplex of the previous and of the current dimension.
+        let mut facets_offset = 0;
+        let mut offset = 0;
+        let mut facets: Vec<usize> = Vec::new();
+        for (dim, grades) in self.grades.iter().enumerate() {
+            for (idx, grade) in grades.iter().enumerate() {
+                let id = offset + idx;
+                let vertices = self.complex.simplex_vertices(dim, idx);
+                facets.clear();
+                if dim > 0 {
+                    facets.extend(
+                        self.complex
+                            .boundary_iterator(dim, idx)
+                            .map(|facet_idx| facets_offset + facet_idx),
+                    );
+                    facets.sort_unstable();
+                }
+                match format {
+                    HasseDiagramFormat::Tsv => {
+                        write!(w, "{}\t{}\t", id, dim)?;
+                        write_separated(w, vertices)?;
+                        write!(w, "\t")?;
+                        write_separated(w, grade.0.iter())?;
+                        write!(w, "\t")?;
+                        write_separated(w, facets.iter())?;
+                        writeln!(w)?;
+                    }
+                    HasseDiagramFormat::Json => {
+                        write!(
+                            w,
+                            "{{\"id\": {}, \"dimension\": {}, \"vertices\": [",
+                            id, dim
+                        )?;
+                        write_separated(w, vertices)?;
+                        write!(w, "], \"grade\": [")?;
+                        write_json_values(w, grade.0.iter())?;
+                        write!(w, "], \"facets\": [")?;
+                        write_separated(w, facets.iter())?;
+                        let separator = if id + 1 < n_simplices { "," } else { "" };
+                        writeln!(w, "]}}{}", separator)?;
+                    }
+                }
+            }
+            facets_offset = offset;
+            offset += grades.len();
+        }
+        if format == HasseDiagramFormat::Json {
+            writeln!(w, "]")?;
+        }
+        Ok(())
+    }
+}
+
+/// The number of simplices of each dimension of a filtration whose grade falls in each cell of a
+/// grid, see [Filtration::grade_histogram].
+///
+/// The range of the values of each parameter, from the smallest to the largest value of that
+/// parameter among the grades of all the simplices, is split into the given number of cells of
+/// the same width. The last cell of each parameter includes the largest value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradeHistogram<VF, const N: usize> {
+    /// The smallest and largest value of each parameter, or None if the filtration is empty.
+    pub bounds: Option<[(VF, VF); N]>,
+    /// The number of cells of each parameter.
+    pub resolution: [usize; N],
+    /// For each dimension, the number of simplices in each cell of the grid, with the cells
+    /// ordered lexicographically by their coordinates, so that the last parameter varies fastest.
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl<VF: Value + ToPrimitive, const N: usize> GradeHistogram<VF, N> {
+    /// The number of simplices of the given dimension in the cell with the given coordinates, or
+    /// zero if there is no such dimension or cell.
+    pub fn count(&self, dim: Dimension, cell: [usize; N]) -> usize {
+        match (self.counts.get(dim), self.cell_index(cell)) {
+            (Some(counts), Some(index)) => counts[index],
+            _ => 0,
+        }
+    }
+
+    /// The cell with the most simplices of the given dimension, and their number, or None if
+    /// there are no simplices of that dimension. Ties go to the first cell.
+    pub fn densest_cell(&self, dim: Dimension) -> Option<([usize; N], usize)> {
+        let counts = self.counts.get(dim)?;
+        let (index, &count) = counts
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, &count)| count)?;
+        (count > 0).then_some((self.cell_coordinates(index), count))
+    }
+
+    /// The values of the given parameter, as floats, at which the given cell of that parameter
+    /// starts and ends, or None if the filtration is empty.
+    pub fn cell_range(&self, parameter: usize, cell: usize) -> Option<(f64, f64)> {
+        let (min, max) = self.float_bounds(parameter)?;
+        let width = (max - min) / self.resolution[parameter] as f64;
+        Some((min + width * cell as f64, min + width * (cell + 1) as f64))
+    }
+
+    fn float_bounds(&self, parameter: usize) -> Option<(f64, f64)> {
+        let (min, max) = self.bounds?[parameter];
+        Some((min.to_f64()?, max.to_f64()?))
+    }
+
+    /// The cell of each parameter of the grade.
+    fn cell_of(&self, grade: &OneCriticalGrade<VF, N>) -> [usize; N] {
+        let mut cell = [0; N];
+        for (parameter, c) in cell.iter_mut().enumerate() {
+            let (min, max) = match self.float_bounds(parameter) {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+            let value = grade.0[parameter].to_f64().unwrap_or(min);
+            let resolution = self.resolution[parameter];
+            if max > min {
+                let position = (value - min) / (max - min) * resolution as f64;
+                *c = (position as usize).min(resolution - 1);
+            }
+        }
+        cell
+    }
+
+    fn cell_index(&self, cell: [usize; N]) -> Option<usize> {
+        let mut index = 0;
+        for (&c, &resolution) in cell.iter().zip(self.resolution.iter()) {
+            if c >= resolution {
+                return None;
+            }
+            index = index * resolution + c;
+        }
+        Some(index)
+    }
+
+    fn cell_coordinates(&self, mut index: usize) -> [usize; N] {
+        let mut cell = [0; N];
+        for (c, &resolution) in cell.iter_mut().zip(self.resolution.iter()).rev() {
+            *c = index % resolution;
+            index /= resolution;
+        }
+        cell
+    }
+}
+
+impl<VF: Value + ToPrimitive, S, const N: usize> Filtration<OneCriticalGrade<VF, N>, S>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    /// Counts the simplices of each dimension whose grade falls in each cell of a grid with the
+    /// given number of cells per parameter, see [GradeHistogram]. A resolution of zero is
+    /// treated as one.
+    ///
+    /// This shows where in the grades the simplices are, which helps to choose the resolution of
+    /// [quantize](crate::edges::transform::quantize), and to see why the input of mpfree is large.
+    pub fn grade_histogram(&self, resolution: [usize; N]) -> GradeHistogram<VF, N> {
+        let resolution = resolution.map(|r| r.max(1));
+        let n_cells: usize = resolution.iter().product();
+        let mut bounds: Option<[(VF, VF); N]> = None;
+        for grade in self.grades.iter().flatten() {
+            let b = bounds.get_or_insert_with(|| grade.0.map(|value| (value, value)));
+            for ((min, max), &value) in b.iter_mut().zip(grade.0.iter()) {
+                *min = (*min).min(value);
+                *max = (*max).max(value);
+            }
+        }
+        let mut histogram = GradeHistogram {
+            bounds,
+            resolution,
+            counts: vec![vec![0; n_cells]; self.grades.len()],
+        };
+        for (dim, grades) in self.grades.iter().enumerate() {
+            for grade in grades {
+                let cell = histogram.cell_of(grade);
+                if let Some(index) = histogram.cell_index(cell) {
+                    histogram.counts[dim][index] += 1;
+                }
+            }
+        }
+        histogram
+    }
+}
+
+/// Writes the values separated by commas.
+fn write_separated<W: io::Write, T: std::fmt::Display, I: Iterator<Item = T>>(
+    w: &mut W,
+    values: I,
+) -> io::Result<()> {
+    for (i, value) in values.enumerate() {
+        if i > 0 {
+            write!(w, ",")?;
+        }
+        write!(w, "{}", value)?;
+    }
+    Ok(())
+}
+
+/// Writes the values separated by commas, as JSON numbers if they are finite numbers, and as JSON
+/// strings otherwise, like the infinite and NaN floats, which are not valid JSON numbers.
+fn write_json_values<W: io::Write, T: std::fmt::Display, I: Iterator<Item = T>>(
+    w: &mut W,
+    values: I,
+) -> io::Result<()> {
+    for (i, value) in values.enumerate() {
+        if i > 0 {
+            write!(w, ",")?;
+        }
+        let value = value.to_string();
+        match value.parse::<f64>() {
+            Ok(number) if number.is_finite() => write!(w, "{}", value)?,
+            _ => write!(
+                w,
+                "\"{}\"",
+                value.replace('\\', "\\\\").replace('"', "\\\"")
+            )?,
+        }
+    }
+    Ok(())
 }
 
 impl<VF: Value, S, const N: usize> ToFreeImplicitRepresentation<VF, N>
@@ -233,11 +1269,59 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::edges::{BareEdge, FilteredEdge};
-    use crate::filtration::{build_flag_filtration, Filtration};
+    use crate::chain_complex::ToFreeImplicitRepresentation;
+    use crate::edges::{BareEdge, EdgeList, EdgeListError, FilteredEdge};
+    use ordered_float::OrderedFloat;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::filtration::{
+        build_flag_filtration, build_flag_filtration_by_degeneracy,
+        build_flag_filtration_by_expansion, estimate_flag_size, try_build_flag_filtration,
+        write_flag_scc2020, write_json_values, EmptyError, Filtration, FiltrationError,
+        HasseDiagramFormat,
+    };
     use crate::simplicial_complex::{MapSimplicialComplex, SimplicialComplex};
     use crate::OneCriticalGrade;
 
+    #[test]
+    fn grade_histogram_of_triangle() {
+        let edges = vec![
+            FilteredEdge {
+                edge: BareEdge(0, 1),
+                grade: OneCriticalGrade([0, 1]),
+            },
+            FilteredEdge {
+                edge: BareEdge(0, 2),
+                grade: OneCriticalGrade([1, 4]),
+            },
+            FilteredEdge {
+                edge: BareEdge(1, 2),
+                grade: OneCriticalGrade([4, 2]),
+            },
+        ];
+        let f: Filtration<_, MapSimplicialComplex> = build_flag_filtration(3, 2, edges.into_iter());
+        let histogram = f.grade_histogram([2, 0]);
+        assert_eq!(histogram.bounds, Some([(0, 4), (0, 4)]));
+        assert_eq!(histogram.resolution, [2, 1]);
+        // The vertices are at the zero grade.
+        assert_eq!(histogram.count(0, [0, 0]), 3);
+        assert_eq!(histogram.count(1, [0, 0]), 2);
+        assert_eq!(histogram.count(1, [1, 0]), 1);
+        assert_eq!(histogram.count(2, [1, 0]), 1);
+        assert_eq!(histogram.count(1, [2, 0]), 0);
+        assert_eq!(histogram.densest_cell(1), Some(([0, 0], 2)));
+        assert_eq!(histogram.densest_cell(3), None);
+        assert_eq!(histogram.cell_range(0, 1), Some((2., 4.)));
+        for (dim, size) in f.sizes().into_iter().enumerate() {
+            assert_eq!(histogram.counts[dim].iter().sum::<usize>(), size);
+        }
+
+        let empty: Filtration<OneCriticalGrade<usize, 2>, MapSimplicialComplex> =
+            Filtration::new_empty(0, 1);
+        assert_eq!(empty.grade_histogram([4, 4]).bounds, None);
+    }
+
     #[test]
     fn flag_filtration_triangle() {
         let edges = vec![
@@ -303,4 +1387,390 @@ mod tests {
         let vertices2: Vec<_> = f.simplicial_complex().simplex_vertices(3, 1).collect();
         assert_eq!(vec![3, 4, 5, 6], vertices2);
     }
+
+    #[test]
+    fn write_flag_scc2020_same_as_filtration() {
+        let mut edges = Vec::new();
+        for u in 0..6 {
+            for v in (u + 1)..6 {
+                if (u + v) % 4 == 1 {
+                    continue;
+                }
+                edges.push(FilteredEdge {
+                    edge: BareEdge(u, v),
+                    grade: OneCriticalGrade([(u * v) % 5, u + v]),
+                });
+            }
+        }
+        let edge_list: EdgeList<_> = edges.into();
+
+        for homology in 0..3 {
+            let f: Filtration<_, MapSimplicialComplex> = build_flag_filtration(
+                edge_list.n_vertices,
+                homology + 1,
+                edge_list.edge_iter().cloned(),
+            );
+            let mut expected = Vec::new();
+            f.write_scc2020(homology, &mut expected).unwrap();
+
+            let mut streamed = Vec::new();
+            write_flag_scc2020(&edge_list, homology, &mut streamed).unwrap();
+
+            assert_eq!(
+                String::from_utf8(streamed).unwrap(),
+                String::from_utf8(expected).unwrap()
+            );
+        }
+
+        // Negative grades are below the grade of the vertices, so neither the filtration nor
+        // its streamed chain complex can be built.
+        let negative: EdgeList<_> = vec![
+            FilteredEdge {
+                edge: BareEdge(0, 1),
+                grade: OneCriticalGrade([1, 2]),
+            },
+            FilteredEdge {
+                edge: BareEdge(1, 2),
+                grade: OneCriticalGrade([-1_i64, 2]),
+            },
+        ]
+        .into();
+        assert!(matches!(
+            try_build_flag_filtration::<_, MapSimplicialComplex, _>(
+                negative.n_vertices,
+                1,
+                negative.edge_iter().cloned()
+            ),
+            Err(FiltrationError::GradeLowerThanFacet(_))
+        ));
+        for homology in 0..2 {
+            let mut streamed = Vec::new();
+            let err = write_flag_scc2020(&negative, homology, &mut streamed).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+            assert!(streamed.is_empty());
+        }
+    }
+
+    #[test]
+    fn degeneracy_and_expansion_build_the_same_filtration() {
+        type Check = fn(usize) -> Result<(), EmptyError>;
+        let mut rng = StdRng::seed_from_u64(1300);
+        for _ in 0..20 {
+            let n_vertices = 15;
+            let mut edges = Vec::new();
+            for u in 0..n_vertices {
+                for v in (u + 1)..n_vertices {
+                    if rng.gen_bool(0.6) {
+                        // Some edges are given with their endpoints swapped.
+                        let edge = if rng.gen_bool(0.5) {
+                            BareEdge(u, v)
+                        } else {
+                            BareEdge(v, u)
+                        };
+                        edges.push(FilteredEdge {
+                            edge,
+                            grade: OneCriticalGrade([rng.gen_range(0..5), rng.gen_range(0..5)]),
+                        });
+                    }
+                }
+            }
+            // Repeated edges are ignored.
+            let repeated = edges[0];
+            edges.push(repeated);
+            let swapped = rng.gen_range(0..edges.len());
+            edges.swap(0, swapped);
+
+            // Above dimension 3, the degeneracy build falls back to the expansion.
+            for max_dim in 0..=4 {
+                let expansion: Filtration<_, MapSimplicialComplex> =
+                    build_flag_filtration_by_expansion::<_, _, _, _, Check>(
+                        n_vertices,
+                        max_dim,
+                        edges.iter().cloned(),
+                        None,
+                    )
+                    .unwrap();
+                let degeneracy: Filtration<_, MapSimplicialComplex> =
+                    build_flag_filtration_by_degeneracy(n_vertices, max_dim, edges.iter().cloned());
+                let complex = expansion.simplicial_complex();
+                for dim in 0..=max_dim {
+                    assert_eq!(
+                        complex.n_cells(dim),
+                        degeneracy.simplicial_complex().n_cells(dim)
+                    );
+                    for idx in 0..complex.n_cells(dim) {
+                        let vertices: Vec<_> = complex.simplex_vertices(dim, idx).collect();
+                        let degeneracy_vertices: Vec<_> = degeneracy
+                            .simplicial_complex()
+                            .simplex_vertices(dim, idx)
+                            .collect();
+                        assert_eq!(vertices, degeneracy_vertices);
+                        assert_eq!(expansion.value_of(dim, idx), degeneracy.value_of(dim, idx));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn restricted_filtration_is_the_flag_filtration_of_the_subset() {
+        let mut rng = StdRng::seed_from_u64(1346);
+        let n_vertices = 12;
+        let mut edges = Vec::new();
+        for u in 0..n_vertices {
+            for v in (u + 1)..n_vertices {
+                if rng.gen_bool(0.7) {
+                    edges.push(FilteredEdge {
+                        edge: BareEdge(u, v),
+                        grade: OneCriticalGrade([rng.gen_range(0..5), rng.gen_range(0..5)]),
+                    });
+                }
+            }
+        }
+        let subset: Vec<_> = edges
+            .iter()
+            .copied()
+            .filter(|_| rng.gen_bool(0.6))
+            .collect();
+
+        for max_dim in [1, 3, 4] {
+            let full: Filtration<_, MapSimplicialComplex> =
+                build_flag_filtration(n_vertices, max_dim, edges.iter().copied());
+            let restricted = full.restrict_to_edges(subset.iter().map(|e| e.edge));
+            let expected: Filtration<_, MapSimplicialComplex> =
+                build_flag_filtration(n_vertices, max_dim, subset.iter().copied());
+            assert_eq!(restricted.sizes(), expected.sizes());
+            for dim in 0..=max_dim {
+                let restricted_simplices: Vec<_> = restricted.simplices(dim).collect();
+                let expected_simplices: Vec<_> = expected.simplices(dim).collect();
+                assert_eq!(restricted_simplices, expected_simplices);
+            }
+        }
+    }
+
+    #[test]
+    fn flag_size_estimate_bounds_the_sizes() {
+        let mut rng = StdRng::seed_from_u64(1369);
+        let n_vertices = 20;
+        let mut edges = Vec::new();
+        for u in 0..n_vertices {
+            for v in (u + 1)..n_vertices {
+                if rng.gen_bool(0.4) {
+                    edges.push(FilteredEdge {
+                        edge: BareEdge(u, v),
+                        grade: OneCriticalGrade([rng.gen_range(0..5), rng.gen_range(0..5)]),
+                    });
+                }
+            }
+        }
+        let max_dim = 4;
+        let filtration: Filtration<_, MapSimplicialComplex> =
+            build_flag_filtration(n_vertices, max_dim, edges.iter().copied());
+        let sizes = filtration.sizes();
+
+        // Repeated edges are counted once.
+        let mut edge_list = EdgeList::from_iterator(edges.iter().copied());
+        edge_list.n_vertices = n_vertices;
+        edge_list.add_edge(edges[0]);
+        let estimate = estimate_flag_size(&edge_list, max_dim);
+        assert_eq!(estimate.sizes.len(), max_dim + 1);
+        assert_eq!(estimate.sizes[..2], sizes[..2]);
+        for (bound, size) in estimate.sizes.iter().zip(sizes.iter()) {
+            assert!(bound >= size);
+        }
+        assert!(estimate.total() >= sizes.iter().sum());
+
+        let empty: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = EdgeList::new(3);
+        assert_eq!(estimate_flag_size(&empty, 2).sizes, vec![3, 0, 0]);
+        assert_eq!(estimate_flag_size(&empty, 2).degeneracy, 0);
+    }
+
+    #[test]
+    fn filtration_accessors() {
+        let edges = vec![
+            FilteredEdge {
+                edge: BareEdge(0, 1),
+                grade: OneCriticalGrade([0, 1]),
+            },
+            FilteredEdge {
+                edge: BareEdge(0, 2),
+                grade: OneCriticalGrade([1, 2]),
+            },
+            FilteredEdge {
+                edge: BareEdge(1, 2),
+                grade: OneCriticalGrade([2, 0]),
+            },
+        ];
+        let f: Filtration<_, MapSimplicialComplex> = build_flag_filtration(4, 2, edges.into_iter());
+
+        assert_eq!(
+            f.grade_of_simplex(&[2, 1, 0]),
+            Some(&OneCriticalGrade([2, 2]))
+        );
+        assert_eq!(f.grade_of_simplex(&[2, 0]), Some(&OneCriticalGrade([1, 2])));
+        assert_eq!(f.grade_of_simplex(&[3]), Some(&OneCriticalGrade([0, 0])));
+        assert_eq!(f.grade_of_simplex(&[0, 3]), None);
+        assert_eq!(f.grade_of_simplex(&[0, 0]), None);
+        assert_eq!(f.grade_of_simplex(&[0, 4]), None);
+        assert_eq!(f.grade_of_simplex(&[0, 1, 2, 3]), None);
+        assert_eq!(f.grade_of_simplex(&[]), None);
+
+        assert_eq!(f.try_value_of(1, 2), Some(&OneCriticalGrade([2, 0])));
+        assert_eq!(f.try_value_of(1, 3), None);
+        assert_eq!(f.try_value_of(3, 0), None);
+
+        let edge_simplices: Vec<_> = f.simplices(1).collect();
+        assert_eq!(
+            edge_simplices,
+            vec![
+                (vec![0, 1], &OneCriticalGrade([0, 1])),
+                (vec![0, 2], &OneCriticalGrade([1, 2])),
+                (vec![1, 2], &OneCriticalGrade([2, 0])),
+            ]
+        );
+        assert_eq!(f.simplices(0).count(), 4);
+        assert_eq!(f.simplices(3).count(), 0);
+
+        assert_eq!(f.sizes(), vec![4, 3, 1]);
+        let grades_bytes = 8 * std::mem::size_of::<OneCriticalGrade<i32, 2>>();
+        assert!(
+            f.memory_bytes_estimate()
+                >= grades_bytes + f.simplicial_complex().memory_bytes_estimate()
+        );
+    }
+
+    #[test]
+    fn try_add_rejects_invalid_simplices() {
+        let mut f: Filtration<OneCriticalGrade<usize, 2>, MapSimplicialComplex> =
+            Filtration::new_empty(3, 2);
+        for v in 0..3 {
+            assert!(f.try_add(OneCriticalGrade([0, 0]), &[v]).unwrap().is_some());
+        }
+        assert_eq!(
+            f.try_add(OneCriticalGrade([1, 1]), &[1, 0]),
+            Err(FiltrationError::UnsortedSimplex(vec![1, 0]))
+        );
+        assert_eq!(
+            f.try_add(OneCriticalGrade([1, 1]), &[0, 3]),
+            Err(FiltrationError::VertexOutOfRange(vec![0, 3], 3))
+        );
+        assert_eq!(
+            f.try_add(OneCriticalGrade([1, 1]), &[0, 1, 2]),
+            Err(FiltrationError::MissingFacet(vec![0, 1, 2]))
+        );
+
+        for edge in [[0, 1], [0, 2], [1, 2]] {
+            assert!(f
+                .try_add(OneCriticalGrade([1, 1]), &edge)
+                .unwrap()
+                .is_some());
+        }
+        assert_eq!(
+            f.try_add(OneCriticalGrade([2, 0]), &[0, 1, 2]),
+            Err(FiltrationError::GradeLowerThanFacet(vec![0, 1, 2]))
+        );
+        assert_eq!(f.try_add(OneCriticalGrade([1, 1]), &[0, 1]), Ok(None));
+        assert!(f
+            .try_add(OneCriticalGrade([2, 1]), &[0, 1, 2])
+            .unwrap()
+            .is_some());
+        assert_eq!(f.value_of(2, 0), &OneCriticalGrade([2, 1]));
+    }
+
+    #[test]
+    fn try_build_flag_filtration_rejects_invalid_edges() {
+        let edge = |u, v, grade| FilteredEdge {
+            edge: BareEdge(u, v),
+            grade: OneCriticalGrade::<i32, 2>(grade),
+        };
+        let valid = vec![edge(0, 1, [0, 1]), edge(1, 2, [1, 0]), edge(0, 2, [1, 1])];
+        let f: Filtration<_, MapSimplicialComplex> =
+            try_build_flag_filtration(3, 2, valid.into_iter()).unwrap();
+        assert_eq!(f.value_of(2, 0), &OneCriticalGrade([1, 1]));
+
+        let negative = vec![edge(0, 1, [0, 1]), edge(1, 2, [-1, 0])];
+        let negative_result: Result<Filtration<_, MapSimplicialComplex>, _> =
+            try_build_flag_filtration(3, 2, negative.into_iter());
+        assert_eq!(
+            negative_result.err(),
+            Some(FiltrationError::GradeLowerThanFacet(vec![1, 2]))
+        );
+
+        let out_of_range = vec![edge(0, 3, [0, 0])];
+        let out_of_range_result: Result<Filtration<_, MapSimplicialComplex>, _> =
+            try_build_flag_filtration(3, 2, out_of_range.into_iter());
+        assert_eq!(
+            out_of_range_result.err(),
+            Some(FiltrationError::InvalidEdge(
+                EdgeListError::VertexOutOfRange(BareEdge(0, 3), 3)
+            ))
+        );
+    }
+
+    #[test]
+    fn write_hasse_diagram_of_triangle() {
+        let edges = vec![
+            FilteredEdge {
+                edge: BareEdge(0, 1),
+                grade: OneCriticalGrade([0, 1]),
+            },
+            FilteredEdge {
+                edge: BareEdge(0, 2),
+                grade: OneCriticalGrade([1, 2]),
+            },
+            FilteredEdge {
+                edge: BareEdge(1, 2),
+                grade: OneCriticalGrade([2, 0]),
+            },
+        ];
+        let f: Filtration<_, MapSimplicialComplex> = build_flag_filtration(3, 2, edges.into_iter());
+
+        let mut tsv: Vec<u8> = Vec::new();
+        f.write_hasse_diagram(HasseDiagramFormat::Tsv, &mut tsv)
+            .unwrap();
+        let expected_tsv = "id\tdimension\tvertices\tgrade\tfacets\n\
+            0\t0\t0\t0,0\t\n\
+            1\t0\t1\t0,0\t\n\
+            2\t0\t2\t0,0\t\n\
+            3\t1\t0,1\t0,1\t0,1\n\
+            4\t1\t0,2\t1,2\t0,2\n\
+            5\t1\t1,2\t2,0\t1,2\n\
+            6\t2\t0,1,2\t2,2\t3,4,5\n";
+        assert_eq!(String::from_utf8(tsv).unwrap(), expected_tsv);
+
+        let mut json: Vec<u8> = Vec::new();
+        f.write_hasse_diagram(HasseDiagramFormat::Json, &mut json)
+            .unwrap();
+        let json = String::from_utf8(json).unwrap();
+        let lines: Vec<&str> = json.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(
+            lines[4],
+            r#"{"id": 3, "dimension": 1, "vertices": [0,1], "grade": [0,1], "facets": [0,1]},"#
+        );
+        assert_eq!(
+            lines[7],
+            r#"{"id": 6, "dimension": 2, "vertices": [0,1,2], "grade": [2,2], "facets": [3,4,5]}"#
+        );
+        assert_eq!(lines[8], "]");
+
+        let infinite_edges = vec![FilteredEdge {
+            edge: BareEdge(0, 1),
+            grade: OneCriticalGrade([OrderedFloat(f64::NAN), OrderedFloat(f64::INFINITY)]),
+        }];
+        let infinite: Filtration<_, MapSimplicialComplex> =
+            build_flag_filtration(2, 1, infinite_edges.into_iter());
+        let mut infinite_json: Vec<u8> = Vec::new();
+        infinite
+            .write_hasse_diagram(HasseDiagramFormat::Json, &mut infinite_json)
+            .unwrap();
+        let infinite_json = String::from_utf8(infinite_json).unwrap();
+        assert_eq!(
+            infinite_json.lines().nth(3).unwrap(),
+            r#"{"id": 2, "dimension": 1, "vertices": [0,1], "grade": ["NaN","inf"], "facets": [0,1]}"#
+        );
+        let mut values: Vec<u8> = Vec::new();
+        write_json_values(&mut values, [f64::NEG_INFINITY, -1.5, 0.].iter()).unwrap();
+        assert_eq!(String::from_utf8(values).unwrap(), r#""-inf",-1.5,0"#);
+    }
 }
@@ -0,0 +1,181 @@
+//! Plain floats as grades.
+//!
+//! The algorithms of this crate need a total order on the values of the grades, see [Value], so
+//! they do not work with `f64` or `f32` directly. The [GradeValue] trait maps such types to a
+//! [Value], like [OrderedFloat], so that edge lists with plain float grades can be built and read
+//! back without converting every value by hand:
+//!
+//! ```
+//! use filtration_domination::edges::{BareEdge, EdgeList, FilteredEdge};
+//! use filtration_domination::removal::{remove_strongly_filtration_dominated, EdgeOrder};
+//! use filtration_domination::OneCriticalGrade;
+//!
+//! let edges: EdgeList<FilteredEdge<OneCriticalGrade<f64, 2>>> = vec![FilteredEdge {
+//!     grade: OneCriticalGrade([0.5, 1.0]),
+//!     edge: BareEdge(0, 1),
+//! }]
+//! .into();
+//! let mut ordered = edges.to_ordered();
+//! let remaining = remove_strongly_filtration_dominated(&mut ordered, EdgeOrder::default());
+//! let remaining: EdgeList<FilteredEdge<OneCriticalGrade<f64, 2>>> = remaining.from_ordered();
+//! assert_eq!(remaining.edges(), edges.edges());
+//! ```
+//!
+//! With floats, NaN is greater than every other value, and all NaNs are equal, as in
+//! [OrderedFloat].
+use ordered_float::OrderedFloat;
+
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::{OneCriticalGrade, Value};
+
+/// A type that can be used as the value of a grade, through a [Value] with the same values.
+///
+/// This trait is sealed: it is implemented for `f32` and `f64`, which are mapped to
+/// [OrderedFloat], and for the primitive integers and ordered floats, which are mapped to
+/// themselves.
+pub trait GradeValue: Copy + private::Sealed {
+    /// The totally ordered type used by the algorithms.
+    type Ordered: Value;
+
+    fn to_ordered(self) -> Self::Ordered;
+
+    fn from_ordered(value: Self::Ordered) -> Self;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_grade_value_for_floats {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl GradeValue for $t {
+                type Ordered = OrderedFloat<$t>;
+
+                fn to_ordered(self) -> Self::Ordered {
+                    OrderedFloat(self)
+                }
+
+                fn from_ordered(value: Self::Ordered) -> Self {
+                    value.0
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_grade_value_for_values {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl GradeValue for $t {
+                type Ordered = $t;
+
+                fn to_ordered(self) -> Self::Ordered {
+                    self
+                }
+
+                fn from_ordered(value: Self::Ordered) -> Self {
+                    value
+                }
+            }
+        )*
+    };
+}
+
+impl_grade_value_for_floats!(f32, f64);
+impl_grade_value_for_values!(
+    OrderedFloat<f32>,
+    OrderedFloat<f64>,
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    isize
+);
+
+impl<T: GradeValue, const N: usize> OneCriticalGrade<T, N> {
+    /// Returns the grade with totally ordered values.
+    pub fn to_ordered(&self) -> OneCriticalGrade<T::Ordered, N> {
+        OneCriticalGrade(self.0.map(T::to_ordered))
+    }
+
+    /// Returns the grade with the given totally ordered values.
+    pub fn from_ordered(grade: &OneCriticalGrade<T::Ordered, N>) -> Self {
+        OneCriticalGrade(grade.0.map(T::from_ordered))
+    }
+}
+
+impl<T: GradeValue, const N: usize> EdgeList<FilteredEdge<OneCriticalGrade<T, N>>> {
+    /// Returns a copy of the edge list whose grades have totally ordered values, so that it can
+    /// be given to the algorithms of this crate.
+    pub fn to_ordered(&self) -> EdgeList<FilteredEdge<OneCriticalGrade<T::Ordered, N>>> {
+        let mut ordered = EdgeList::new(self.n_vertices);
+        for e in self.edges() {
+            ordered.add_edge(FilteredEdge {
+                grade: e.grade.to_ordered(),
+                edge: e.edge,
+            });
+        }
+        ordered
+    }
+}
+
+impl<VF: Value, const N: usize> EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>> {
+    /// The inverse of [EdgeList::to_ordered]: returns a copy of the edge list whose grades have
+    /// values of type `T`.
+    pub fn from_ordered<T: GradeValue<Ordered = VF>>(
+        &self,
+    ) -> EdgeList<FilteredEdge<OneCriticalGrade<T, N>>> {
+        let mut converted = EdgeList::new(self.n_vertices);
+        for e in self.edge_iter() {
+            converted.add_edge(FilteredEdge {
+                grade: OneCriticalGrade::from_ordered(&e.grade),
+                edge: e.edge,
+            });
+        }
+        converted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn float_edge_list_round_trip() {
+        let edges: EdgeList<FilteredEdge<OneCriticalGrade<f32, 2>>> = vec![
+            FilteredEdge {
+                grade: OneCriticalGrade([0.5, f32::NAN]),
+                edge: BareEdge(0, 1),
+            },
+            FilteredEdge {
+                grade: OneCriticalGrade([-1.0, 2.0]),
+                edge: BareEdge(1, 3),
+            },
+        ]
+        .into();
+        let mut ordered = edges.to_ordered();
+        assert_eq!(ordered.n_vertices, 4);
+        ordered.sort_lexicographically();
+        assert_eq!(
+            ordered.edges()[0].grade,
+            OneCriticalGrade([OrderedFloat(-1.0), OrderedFloat(2.0)])
+        );
+
+        let back: EdgeList<FilteredEdge<OneCriticalGrade<f32, 2>>> = ordered.from_ordered();
+        assert_eq!(back.edges()[0].grade.0, [-1.0, 2.0]);
+        assert!(back.edges()[1].grade.0[1].is_nan());
+    }
+}
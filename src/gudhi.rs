@@ -0,0 +1,184 @@
+//! Export of single-parameter flag filtrations in a format that is easy to load into GUDHI.
+//!
+//! The format is a list of filtered simplices, one per line: the vertices of the simplex,
+//! separated by spaces, followed by its filtration value. Simplices are written by increasing
+//! dimension, so every simplex comes after its faces. For example, the filled triangle whose edges
+//! appear at 1, 2, and 3 is
+//! ```text
+//! 0 1
+//! 1 1
+//! 2 2
+//! 0 1 1
+//! 0 2 2
+//! 1 2 3
+//! 0 1 2 3
+//! ```
+//! In Python, the file can be loaded into a `gudhi.SimplexTree` with
+//! ```python
+//! simplex_tree = gudhi.SimplexTree()
+//! for line in open(path):
+//!     *vertices, value = line.split()
+//!     simplex_tree.insert([int(v) for v in vertices], filtration=float(value))
+//! ```
+//! Since flag complexes are determined by their edges, it is usually enough to write the graph,
+//! with a maximum dimension of 1, and call `simplex_tree.expansion(max_dimension)` after loading.
+use std::io;
+
+use crate::edges::{Edge, EdgeList, FilteredEdge};
+use crate::filtration::{build_flag_filtration, validate_flag_edges, Filtration};
+use crate::simplicial_complex::{
+    MapSimplicialComplex, SimplexKey, SimplicialComplex, WideMapSimplicialComplex,
+};
+use crate::{OneCriticalGrade, Value};
+
+impl<VF: Value, S> Filtration<OneCriticalGrade<VF, 1>, S>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    /// Writes all the simplices of the single-parameter filtration, each at its grade, as a list
+    /// of filtered simplices. See the [module documentation](crate::gudhi) for the format.
+    pub fn write_simplex_list<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        write_simplices(self, 0, w)
+    }
+}
+
+/// Writes the flag filtration, up to the given dimension, of a single-parameter filtered edge
+/// list, like those output by
+/// [remove_filtration_dominated_single_parameter](crate::removal::remove_filtration_dominated_single_parameter),
+/// as a list of filtered simplices. See the [module documentation](self) for the format.
+///
+/// Each vertex is written at the grade at which it enters the graph, the smallest grade of its
+/// edges, as GUDHI does when inserting an edge whose vertices are missing. Isolated vertices are
+/// written at zero.
+///
+/// Returns an error of kind [io::ErrorKind::InvalidInput], before writing anything, if the edges
+/// cannot be given to [build_flag_filtration], see [validate_flag_edges].
+pub fn write_flag_simplex_list<VF: Value, W: io::Write>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>>,
+    max_dimension: usize,
+    w: &mut W,
+) -> io::Result<()> {
+    validate_flag_edges(edge_list)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    if usize::fits(edge_list.n_vertices, max_dimension) {
+        write_flag_simplex_list_with::<_, _, MapSimplicialComplex>(edge_list, max_dimension, w)
+    } else {
+        write_flag_simplex_list_with::<_, _, WideMapSimplicialComplex>(edge_list, max_dimension, w)
+    }
+}
+
+fn write_flag_simplex_list_with<VF: Value, W: io::Write, S>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>>,
+    max_dimension: usize,
+    w: &mut W,
+) -> io::Result<()>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    let mut vertex_grades: Vec<Option<VF>> = vec![None; edge_list.n_vertices];
+    for edge in edge_list.edge_iter() {
+        let [value] = edge.grade.0;
+        for v in [edge.u(), edge.v()] {
+            let grade = vertex_grades[v].get_or_insert(value);
+            *grade = std::cmp::min(*grade, value);
+        }
+    }
+    for (v, grade) in vertex_grades.into_iter().enumerate() {
+        writeln!(w, "{} {}", v, grade.unwrap_or_else(VF::zero))?;
+    }
+
+    let filtration: Filtration<_, S> = build_flag_filtration(
+        edge_list.n_vertices,
+        max_dimension,
+        edge_list.edge_iter().cloned(),
+    );
+    write_simplices(&filtration, 1, w)
+}
+
+/// Writes the simplices of the filtration of dimension at least `min_dim`, by increasing
+/// dimension.
+fn write_simplices<VF: Value, W: io::Write, S>(
+    filtration: &Filtration<OneCriticalGrade<VF, 1>, S>,
+    min_dim: usize,
+    w: &mut W,
+) -> io::Result<()>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    let complex = filtration.simplicial_complex();
+    for dim in min_dim..=complex.max_dimension() {
+        for idx in 0..complex.n_cells(dim) {
+            for v in complex.simplex_vertices(dim, idx) {
+                write!(w, "{} ", v)?;
+            }
+            writeln!(w, "{}", filtration.value_of(dim, idx).0[0])?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::filtration::Filtration;
+    use crate::gudhi::write_flag_simplex_list;
+    use crate::simplicial_complex::MapSimplicialComplex;
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn write_triangle_simplex_list() {
+        let edge = |u, v, t| FilteredEdge {
+            grade: OneCriticalGrade([t]),
+            edge: BareEdge(u, v),
+        };
+        let edge_list: EdgeList<_> = vec![edge(0, 1, 1), edge(0, 2, 2), edge(1, 2, 3)].into();
+
+        let mut out = Vec::new();
+        write_flag_simplex_list(&edge_list, 2, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0 1\n1 1\n2 2\n0 1 1\n0 2 2\n1 2 3\n0 1 2 3\n"
+        );
+
+        let mut graph_out = Vec::new();
+        write_flag_simplex_list(&edge_list, 1, &mut graph_out).unwrap();
+        assert_eq!(
+            String::from_utf8(graph_out).unwrap(),
+            "0 1\n1 1\n2 2\n0 1 1\n0 2 2\n1 2 3\n"
+        );
+    }
+
+    #[test]
+    fn write_isolated_vertex_and_negative_grades() {
+        let edge = |u, v, t| FilteredEdge {
+            grade: OneCriticalGrade([t]),
+            edge: BareEdge(u, v),
+        };
+        let mut edge_list: EdgeList<_> = vec![edge(1, 2, 4)].into();
+        edge_list.n_vertices = 3;
+        let mut out = Vec::new();
+        write_flag_simplex_list(&edge_list, 2, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0 0\n1 4\n2 4\n1 2 4\n");
+
+        let negative: EdgeList<_> = vec![edge(0, 1, -1)].into();
+        let mut negative_out = Vec::new();
+        let err = write_flag_simplex_list(&negative, 1, &mut negative_out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(negative_out.is_empty());
+    }
+
+    #[test]
+    fn write_filtration_simplex_list() {
+        let mut filtration: Filtration<OneCriticalGrade<i32, 1>, MapSimplicialComplex> =
+            Filtration::new_empty(2, 1);
+        filtration.add(OneCriticalGrade([-2]), &[0]);
+        filtration.add(OneCriticalGrade([3]), &[1]);
+        filtration.add(OneCriticalGrade([5]), &[0, 1]);
+
+        let mut out = Vec::new();
+        filtration.write_simplex_list(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0 -2\n1 3\n0 1 5\n");
+    }
+}
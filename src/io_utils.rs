@@ -14,11 +14,43 @@ where
     })?)
 }
 
+/// Wraps the error in a [io::Error] of kind [io::ErrorKind::InvalidData].
+pub(crate) fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(
+    error: E,
+) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Wraps the error in a [io::Error] of kind [io::ErrorKind::InvalidInput].
+pub(crate) fn invalid_input<E: Into<Box<dyn std::error::Error + Send + Sync>>>(
+    error: E,
+) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
 /// Tries to parse, and if that fails wraps the error in a [io::Error].
 pub(crate) fn parse<F: FromStr>(x: &str) -> Result<F, io::Error>
 where
     <F as FromStr>::Err: std::error::Error + Send + Sync + 'static,
 {
-    x.parse()
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    x.parse().map_err(io::Error::other)
+}
+
+/// A path in the temporary directory, named after `name`, that no other test, or other run of the
+/// tests, uses.
+#[cfg(test)]
+pub(crate) fn unique_temp_path(name: &str) -> std::path::PathBuf {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Tells apart the paths of this process, which may be asked for in the same nanosecond.
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    std::env::temp_dir().join(format!(
+        "filtration_domination_{name}_{}_{nanos}_{count}",
+        std::process::id()
+    ))
 }
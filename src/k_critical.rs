@@ -0,0 +1,198 @@
+//! Grades with several minimal entrance grades.
+//!
+//! In a k-critical multifiltration a simplex can enter at several incomparable grades, and it is
+//! present at every grade greater than or equal to one of them. Degree-Rips bifiltrations and
+//! multicover bifiltrations are naturally k-critical. A [KCriticalGrade] stores the minimal
+//! entrance grades, and implements [CriticalGrade], so that the generic algorithms of this crate,
+//! like [remove_strongly_filtration_dominated](crate::removal::remove_strongly_filtration_dominated),
+//! accept edges with these grades.
+use std::cmp::Ordering;
+
+use crate::{CriticalGrade, OneCriticalGrade, Value};
+
+/// A k-critical grade: an antichain of 1-critical grades, the minimal grades at which a simplex
+/// enters the multifiltration. The grades are kept sorted lexicographically, and none of them is
+/// less than or equal to another.
+///
+/// A grade is less than or equal to another if the simplices with the second grade are present
+/// only where the simplices with the first grade are present, that is, if every grade of the second
+/// is greater than or equal to some grade of the first. The grade with no entrance grades is
+/// greater than every other: a simplex with it is never present.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KCriticalGrade<VF, const N: usize>(Vec<OneCriticalGrade<VF, N>>);
+
+impl<VF: Value, const N: usize> KCriticalGrade<VF, N> {
+    /// The k-critical grade with the minimal grades among the given ones.
+    pub fn new<I: IntoIterator<Item = OneCriticalGrade<VF, N>>>(grades: I) -> Self {
+        let mut grades: Vec<OneCriticalGrade<VF, N>> = grades.into_iter().collect();
+        grades.sort_unstable();
+        grades.dedup();
+        // In lexicographic order, a grade can only be greater than or equal to previous grades.
+        let mut minimal: Vec<OneCriticalGrade<VF, N>> = Vec::with_capacity(grades.len());
+        for grade in grades {
+            if !minimal.iter().any(|kept| kept.lte(&grade)) {
+                minimal.push(grade);
+            }
+        }
+        Self(minimal)
+    }
+
+    /// The minimal entrance grades, sorted lexicographically.
+    pub fn grades(&self) -> &[OneCriticalGrade<VF, N>] {
+        &self.0
+    }
+
+    /// Returns the minimal entrance grades, sorted lexicographically.
+    pub fn into_grades(self) -> Vec<OneCriticalGrade<VF, N>> {
+        self.0
+    }
+
+    /// Whether a simplex with this grade is present at the given 1-critical grade.
+    pub fn contains(&self, grade: &OneCriticalGrade<VF, N>) -> bool {
+        self.0.iter().any(|entrance| entrance.lte(grade))
+    }
+}
+
+impl<VF: Value, const N: usize> CriticalGrade for KCriticalGrade<VF, N> {
+    fn min_value() -> Self {
+        Self(vec![OneCriticalGrade::min_value()])
+    }
+
+    fn max_value() -> Self {
+        Self(vec![OneCriticalGrade::max_value()])
+    }
+
+    fn zero() -> Self {
+        Self(vec![OneCriticalGrade::zero()])
+    }
+
+    /// The grade at which simplices with both grades are present: the minimal joins of a grade
+    /// of each.
+    fn join(&self, other: &Self) -> Self {
+        Self::new(
+            self.0
+                .iter()
+                .flat_map(|a| other.0.iter().map(move |b| a.join(b))),
+        )
+    }
+
+    fn lte(&self, other: &Self) -> bool {
+        other.0.iter().all(|grade| self.contains(grade))
+    }
+
+    fn gte(&self, other: &Self) -> bool {
+        other.lte(self)
+    }
+
+    /// Compares the entrance grades colexicographically, one by one.
+    fn cmp_colexicographically(&self, other: &Self) -> Ordering {
+        for (a, b) in self.0.iter().zip(other.0.iter()) {
+            match a.cmp_colexicographically(b) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            }
+        }
+        self.0.len().cmp(&other.0.len())
+    }
+
+    fn parameters() -> usize {
+        N
+    }
+}
+
+impl<VF: Value, const N: usize> From<OneCriticalGrade<VF, N>> for KCriticalGrade<VF, N> {
+    fn from(grade: OneCriticalGrade<VF, N>) -> Self {
+        Self(vec![grade])
+    }
+}
+
+impl<VF: Value, const N: usize> FromIterator<OneCriticalGrade<VF, N>> for KCriticalGrade<VF, N> {
+    fn from_iter<I: IntoIterator<Item = OneCriticalGrade<VF, N>>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+/// Grades are serialized as a sequence of 1-critical grades.
+#[cfg(feature = "serde")]
+impl<VF: serde::Serialize, const N: usize> serde::Serialize for KCriticalGrade<VF, N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// The deserialized grades are reduced to the minimal ones, as in [KCriticalGrade::new].
+#[cfg(feature = "serde")]
+impl<'de, VF: Value + serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de>
+    for KCriticalGrade<VF, N>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let grades: Vec<OneCriticalGrade<VF, N>> = Vec::deserialize(deserializer)?;
+        Ok(Self::new(grades))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::removal::{remove_strongly_filtration_dominated, EdgeOrder};
+    use crate::{CriticalGrade, KCriticalGrade, OneCriticalGrade};
+
+    fn grade(grades: &[[usize; 2]]) -> KCriticalGrade<usize, 2> {
+        grades.iter().map(|&g| OneCriticalGrade(g)).collect()
+    }
+
+    #[test]
+    fn k_critical_grade_order() {
+        let a = grade(&[[2, 0], [0, 2], [1, 1], [2, 2]]);
+        assert_eq!(
+            a.grades(),
+            &[
+                OneCriticalGrade([0, 2]),
+                OneCriticalGrade([1, 1]),
+                OneCriticalGrade([2, 0])
+            ]
+        );
+        assert!(a.contains(&OneCriticalGrade([1, 3])));
+        assert!(!a.contains(&OneCriticalGrade([0, 1])));
+
+        let b = grade(&[[3, 0], [0, 3]]);
+        assert!(a.lte(&b));
+        assert!(!b.lte(&a));
+        assert!(a.lte(&a.join(&b)) && b.lte(&a.join(&b)));
+        assert_eq!(
+            grade(&[[1, 0]]).join(&grade(&[[0, 1], [2, 0]])),
+            grade(&[[1, 1], [2, 0]])
+        );
+        let one_critical = KCriticalGrade::from(OneCriticalGrade([1, 1]));
+        assert!(a.lte(&one_critical));
+        assert!(one_critical.lte(&KCriticalGrade::new([])));
+    }
+
+    #[test]
+    fn k_critical_strong_removal() {
+        // A triangle with a pendant edge, where the edge 0-1 is strongly dominated by 2.
+        let mut edges: EdgeList<FilteredEdge<KCriticalGrade<usize, 2>>> = vec![
+            FilteredEdge {
+                grade: grade(&[[0, 0]]),
+                edge: BareEdge(0, 2),
+            },
+            FilteredEdge {
+                grade: grade(&[[0, 1], [1, 0]]),
+                edge: BareEdge(1, 2),
+            },
+            FilteredEdge {
+                grade: grade(&[[1, 1]]),
+                edge: BareEdge(0, 1),
+            },
+            FilteredEdge {
+                grade: grade(&[[0, 3], [3, 0]]),
+                edge: BareEdge(1, 3),
+            },
+        ]
+        .into();
+        let remaining = remove_strongly_filtration_dominated(&mut edges, EdgeOrder::Maintain);
+        let remaining_edges: Vec<BareEdge> = remaining.edge_iter().map(|e| e.edge).collect();
+        assert!(!remaining_edges.contains(&BareEdge(0, 1)));
+        assert!(remaining_edges.contains(&BareEdge(1, 3)));
+    }
+}
@@ -11,21 +11,39 @@ use num::{Bounded, Zero};
 use std::cmp::Ordering;
 use std::fmt::Formatter;
 use std::hash::Hash;
-use std::ops::{Index, IndexMut};
+use std::ops::{Add, Index, IndexMut, Mul};
 use std::slice::Iter;
 
 pub mod edges;
 
+pub mod budget;
+pub mod chain_complex;
 pub mod datasets;
 pub mod distance_matrix;
+pub mod error;
+pub mod external;
+pub mod filtration;
+pub mod grade_value;
+pub mod gudhi;
+pub mod k_critical;
 pub mod mpfree;
+pub mod pipeline;
 pub mod points;
 pub mod removal;
+#[cfg(feature = "memory-guard")]
+pub mod resource;
+pub mod rivet;
+pub mod simplicial_complex;
+#[cfg(feature = "mpfree-test-utils")]
+pub mod test_utils;
 
-mod chain_complex;
-mod filtration;
+mod cliques;
 mod io_utils;
-mod simplicial_complex;
+mod trace;
+
+pub use error::Error;
+pub use grade_value::GradeValue;
+pub use k_critical::KCriticalGrade;
 
 /// A generic value, like usize or i32, that we can use as grades in a bifiltered graph.
 pub trait Value:
@@ -82,6 +100,14 @@ pub trait CriticalGrade:
         !self.lte(other) && !self.gte(other)
     }
 
+    /// Compares the grades colexicographically: the last parameter takes precedence.
+    ///
+    /// By default, the grades are compared with [Ord], which is only colexicographic if the grades
+    /// have one parameter, so grades of several parameters should override it.
+    fn cmp_colexicographically(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
     /// Number of parameters.
     fn parameters() -> usize;
 }
@@ -115,6 +141,87 @@ impl<VF: Value, const N: usize> OneCriticalGrade<VF, N> {
     }
 }
 
+impl<VF: Value, const N: usize> OneCriticalGrade<VF, N> {
+    /// Returns the grade whose values are clamped to the given ranges: the `i`-th value is
+    /// replaced by `min[i]` if it is smaller, and by `max[i]` if it is greater.
+    /// If `min[i]` is greater than `max[i]`, the `i`-th value is set to `min[i]`.
+    #[must_use]
+    pub fn clamp_parameters(&self, min: [VF; N], max: [VF; N]) -> Self {
+        let mut clamped = *self;
+        for n in 0..N {
+            clamped[n] = std::cmp::max(std::cmp::min(clamped[n], max[n]), min[n]);
+        }
+        clamped
+    }
+
+    /// Returns the greatest grade that is less than or equal to both grades, whose `i`-th value
+    /// is the minimum of their `i`-th values. See [CriticalGrade::join] for the least grade that
+    /// is greater than or equal to both.
+    #[must_use]
+    pub fn meet(&self, other: &Self) -> Self {
+        let mut meet = *self;
+        for n in 0..N {
+            meet[n] = std::cmp::min(meet[n], other[n]);
+        }
+        meet
+    }
+}
+
+impl<VF: Value> OneCriticalGrade<VF, 1> {
+    /// Returns the bigrade whose given parameter is `value`, and whose other parameter is the
+    /// value of this grade. Pad with zero to add a parameter at which everything enters at the
+    /// start, as `OneCriticalGrade::from` does, or with the minimum value of `VF` to make it
+    /// enter before every other grade.
+    ///
+    /// Returns None if `parameter` is not 0 or 1.
+    pub fn pad(self, parameter: usize, value: VF) -> Option<OneCriticalGrade<VF, 2>> {
+        let [x] = self.0;
+        match parameter {
+            0 => Some(OneCriticalGrade([value, x])),
+            1 => Some(OneCriticalGrade([x, value])),
+            _ => None,
+        }
+    }
+}
+
+impl<VF: Value> OneCriticalGrade<VF, 2> {
+    /// Returns the single-parameter grade with the value of the given parameter of this bigrade,
+    /// forgetting the other one. See [edges::transform::project] to project whole edge lists.
+    ///
+    /// Returns None if `parameter` is not 0 or 1.
+    pub fn project(self, parameter: usize) -> Option<OneCriticalGrade<VF, 1>> {
+        self.0.get(parameter).map(|&x| OneCriticalGrade([x]))
+    }
+}
+
+/// Pads the grade with a first parameter of value zero, which is the grade of the edge in a
+/// bifiltration whose first parameter plays no role, see [OneCriticalGrade::pad].
+impl<VF: Value> From<OneCriticalGrade<VF, 1>> for OneCriticalGrade<VF, 2> {
+    fn from(grade: OneCriticalGrade<VF, 1>) -> Self {
+        let [x] = grade.0;
+        OneCriticalGrade([VF::zero(), x])
+    }
+}
+
+impl<VF: Value + Add<Output = VF> + Mul<Output = VF>, const N: usize> OneCriticalGrade<VF, N> {
+    /// Returns the grade whose `i`-th value is `scale[i] * x + shift[i]`, where `x` is the `i`-th
+    /// value of this grade.
+    ///
+    /// With positive scales, the transformation keeps the order between grades, so it does not
+    /// change which edges are dominated. A negative scale reverses the order of a parameter.
+    ///
+    /// Panics: in debug builds, if the transformed value of an integer grade overflows. In release
+    /// builds, it wraps around instead.
+    #[must_use]
+    pub fn affine_transform(&self, scale: [VF; N], shift: [VF; N]) -> Self {
+        let mut transformed = *self;
+        for n in 0..N {
+            transformed[n] = scale[n] * transformed[n] + shift[n];
+        }
+        transformed
+    }
+}
+
 impl<VF: Value, const N: usize> CriticalGrade for OneCriticalGrade<VF, N> {
     fn min_value() -> OneCriticalGrade<VF, N> {
         OneCriticalGrade([VF::min_value(); N])
@@ -156,6 +263,10 @@ impl<VF: Value, const N: usize> CriticalGrade for OneCriticalGrade<VF, N> {
         true
     }
 
+    fn cmp_colexicographically(&self, other: &Self) -> Ordering {
+        OneCriticalGrade::cmp_colexicographically(self, other)
+    }
+
     fn parameters() -> usize {
         N
     }
@@ -187,6 +298,65 @@ impl<VF: Value> From<VF> for OneCriticalGrade<VF, 1> {
     }
 }
 
+/// Grades are serialized as a sequence of `N` values.
+#[cfg(feature = "serde")]
+impl<VF: serde::Serialize, const N: usize> serde::Serialize for OneCriticalGrade<VF, N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for value in self.0.iter() {
+            tuple.serialize_element(value)?;
+        }
+        tuple.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, VF: serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de>
+    for OneCriticalGrade<VF, N>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_tuple(N, GradeVisitor(std::marker::PhantomData))
+    }
+}
+
+/// Reads the tuple of `N` values written by the serialization of [OneCriticalGrade], and
+/// rejects tuples of another length, which self-describing formats like JSON can contain.
+#[cfg(feature = "serde")]
+struct GradeVisitor<VF, const N: usize>(std::marker::PhantomData<VF>);
+
+#[cfg(feature = "serde")]
+impl<'de, VF: serde::Deserialize<'de>, const N: usize> serde::de::Visitor<'de>
+    for GradeVisitor<VF, N>
+{
+    type Value = OneCriticalGrade<VF, N>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a grade of {N} parameters")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        use serde::de::{Error, IgnoredAny};
+
+        let mut values: Vec<VF> = Vec::with_capacity(N);
+        while values.len() < N {
+            match seq.next_element()? {
+                Some(value) => values.push(value),
+                None => return Err(A::Error::invalid_length(values.len(), &self)),
+            }
+        }
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            return Err(A::Error::invalid_length(N + 1, &self));
+        }
+        let grade: [VF; N] = match values.try_into() {
+            Ok(grade) => grade,
+            Err(_) => unreachable!("Exactly N values were read"),
+        };
+        Ok(OneCriticalGrade(grade))
+    }
+}
+
 impl<VF: Value, const N: usize> std::fmt::Display for OneCriticalGrade<VF, N> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for i in 0..N {
@@ -198,3 +368,35 @@ impl<VF: Value, const N: usize> std::fmt::Display for OneCriticalGrade<VF, N> {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn grades_round_trip_through_bincode() {
+        let float_grade = OneCriticalGrade([OrderedFloat(0.5), OrderedFloat(2.)]);
+        let float_bytes = bincode::serialize(&float_grade).unwrap();
+        let float_read: OneCriticalGrade<OrderedFloat<f64>, 2> =
+            bincode::deserialize(&float_bytes).unwrap();
+        assert_eq!(float_read, float_grade);
+
+        let usize_grade = OneCriticalGrade([3usize, 1, 4]);
+        let usize_bytes = bincode::serialize(&usize_grade).unwrap();
+        let usize_read: OneCriticalGrade<usize, 3> = bincode::deserialize(&usize_bytes).unwrap();
+        assert_eq!(usize_read, usize_grade);
+    }
+
+    #[cfg(feature = "service")]
+    #[test]
+    fn grades_of_another_length_are_rejected() {
+        let longer: Result<OneCriticalGrade<usize, 2>, _> = serde_json::from_str("[1, 2, 3]");
+        assert!(longer.is_err());
+        let shorter: Result<OneCriticalGrade<usize, 2>, _> = serde_json::from_str("[1]");
+        assert!(shorter.is_err());
+        let exact: OneCriticalGrade<usize, 2> = serde_json::from_str("[1, 2]").unwrap();
+        assert_eq!(exact, OneCriticalGrade([1, 2]));
+    }
+}
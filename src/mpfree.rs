@@ -1,29 +1,84 @@
 //! Interface with mpfree that allows to compute minimal presentations.
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::{Display, Formatter};
 use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter};
-use std::path::Path;
-use std::process::{Command, ExitStatus, Stdio};
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::{ChildStdin, ExitStatus};
 use std::time::Duration;
-use std::{fs, io};
 use thiserror::Error;
 
-use crate::chain_complex::ToFreeImplicitRepresentation;
+use crate::chain_complex::{ChainComplex, PresentationFormat, ToFreeImplicitRepresentation};
 use crate::edges::{EdgeList, FilteredEdge};
+use crate::external::{ExternalError, Tool, ToolOptions};
 use crate::filtration::{build_flag_filtration_with_check, Filtration};
-use crate::simplicial_complex::MapSimplicialComplex;
-use crate::{CriticalGrade, Value};
+use crate::simplicial_complex::{
+    MapSimplicialComplex, SimplexKey, SimplicialComplex, WideMapSimplicialComplex,
+};
+use crate::trace::{pass, Pass};
+use crate::{CriticalGrade, OneCriticalGrade, Value};
 
-const TMP_DIRECTORY: &str = "tmp";
+pub use crate::filtration::write_flag_scc2020;
 
-/// The time taken to run mpfree, and the parsed output.
+/// The mpfree binary. Its path can be set with the `MPFREE_PATH` environment variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mpfree;
+
+impl Tool for Mpfree {
+    const BINARY: &'static str = "mpfree";
+    const BINARY_ENV_VAR: &'static str = "MPFREE_PATH";
+}
+
+/// How to run mpfree. See [ToolOptions].
+pub type MpfreeOptions = ToolOptions<Mpfree>;
+
+/// Writes, in the given format, the chain complex that computes the homology at the given
+/// dimension of the clique bifiltration of the given bifiltered edge list.
+///
+/// The scc2020 output can be given to mpfree, as done by [compute_minimal_presentation], and
+/// the firep output to RIVET, for example with `rivet_console input output.rivet`.
+pub fn write_flag_free_implicit_representation<VF: Value, W: io::Write>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    homology: usize,
+    format: PresentationFormat,
+    w: &mut W,
+) -> io::Result<()> {
+    match format {
+        PresentationFormat::Scc2020 => write_flag_scc2020(edge_list, homology, w),
+        PresentationFormat::Firep => {
+            ChainComplex::from_flag_filtration(edge_list, homology).write_firep(w)
+        }
+    }
+}
+
+/// The time taken to run mpfree, the size of the filtration given to it, and the parsed output.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MinimalPresentationComputationSummary {
     pub timers: MinimalPresentationComputationTime,
+    pub filtration: FiltrationStatistics,
     pub output: ParsedMpfreeOutput,
+    /// The input and output files of mpfree, see [WorkingFiles](crate::external::WorkingFiles).
+    /// They still exist only if the options keep them, and there are none if the input is piped.
+    pub files: Vec<PathBuf>,
+}
+
+/// Size of the flag filtration built to compute a minimal presentation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FiltrationStatistics {
+    /// The number of simplices of each dimension, see [Filtration::sizes].
+    pub sizes: Vec<usize>,
+    /// See [Filtration::memory_bytes_estimate].
+    pub memory_bytes_estimate: usize,
 }
 
 /// Timers related to minimal presentation computation.
 #[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MinimalPresentationComputationTime {
     pub build_filtration: Duration,
     pub write_bifiltration: Duration,
@@ -32,15 +87,101 @@ pub struct MinimalPresentationComputationTime {
 
 /// Summaries of the minimal presentation computed by mpfree.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParsedMpfreeOutput {
     pub parameters: usize,
+    /// The sizes in the header of the scc2020 output: the number of relations, of generators,
+    /// and a last one that is zero for a presentation.
     pub sizes: [usize; 3],
 }
 
+/// The names of the sizes of a [ParsedMpfreeOutput], as printed by [PresentationDiff].
+const SIZE_NAMES: [&str; 3] = ["relations", "generators", "third size"];
+
+impl ParsedMpfreeOutput {
+    /// Compares this output, the expected one, with the one found, for example before and after
+    /// removing edges. Unlike comparing them with `==`, the [PresentationDiff] tells which sizes
+    /// differ, and by how much, and can be logged with [Display].
+    pub fn diff(&self, found: &ParsedMpfreeOutput) -> PresentationDiff {
+        PresentationDiff {
+            expected: *self,
+            found: *found,
+        }
+    }
+}
+
+/// The differences between two minimal presentations, see [ParsedMpfreeOutput::diff].
+///
+/// It is displayed as one line for each of the number of parameters and the sizes that differ,
+/// like `relations: expected 12, found 10 (-2)`, or as `no differences`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PresentationDiff {
+    pub expected: ParsedMpfreeOutput,
+    pub found: ParsedMpfreeOutput,
+}
+
+impl PresentationDiff {
+    /// Whether the presentations have the same number of parameters and sizes.
+    pub fn is_empty(&self) -> bool {
+        self.expected == self.found
+    }
+
+    /// The found minus the expected sizes, in the order of [ParsedMpfreeOutput::sizes].
+    pub fn size_differences(&self) -> [i64; 3] {
+        let mut differences = [0; 3];
+        for (difference, (&expected, &found)) in differences
+            .iter_mut()
+            .zip(self.expected.sizes.iter().zip(self.found.sizes.iter()))
+        {
+            *difference = found as i64 - expected as i64;
+        }
+        differences
+    }
+}
+
+impl Display for PresentationDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no differences");
+        }
+        let mut lines = Vec::new();
+        if self.expected.parameters != self.found.parameters {
+            lines.push(format!(
+                "parameters: expected {}, found {}",
+                self.expected.parameters, self.found.parameters
+            ));
+        }
+        for (i, difference) in self.size_differences().into_iter().enumerate() {
+            if difference != 0 {
+                lines.push(format!(
+                    "{}: expected {}, found {} ({:+})",
+                    SIZE_NAMES[i], self.expected.sizes[i], self.found.sizes[i], difference
+                ));
+            }
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Compares the headers of two minimal presentations in scc2020 files, like the outputs of
+/// mpfree kept with [ToolOptions::keep_files], as [ParsedMpfreeOutput::diff].
+pub fn diff_scc2020_files<P: AsRef<Path>, Q: AsRef<Path>>(
+    expected: P,
+    found: Q,
+) -> Result<PresentationDiff, MpfreeError> {
+    let read_header = |path: &Path| {
+        let file = File::open(path).map_err(MpfreeError::OutputFile)?;
+        parse_mpfree_output(BufReader::new(file))
+    };
+    Ok(read_header(expected.as_ref())?.diff(&read_header(found.as_ref())?))
+}
+
 /// Compute a minimal presentation of the homology at the given dimension of the clique bifiltration
 /// of the given bifiltered edge list.
 ///
 /// The `name` parameter is used to name and identify temporary files.
+/// Mpfree is run with the default [MpfreeOptions].
 pub fn compute_minimal_presentation<VF: Value, G: CriticalGrade>(
     name: &str,
     homology: usize,
@@ -48,22 +189,204 @@ pub fn compute_minimal_presentation<VF: Value, G: CriticalGrade>(
 ) -> Result<MinimalPresentationComputationSummary, MpfreeError>
 where
     Filtration<G, MapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+    Filtration<G, WideMapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    compute_minimal_presentation_with_options(name, homology, edge_list, &MpfreeOptions::default())
+}
+
+/// As [compute_minimal_presentation], but mpfree is run with the given options.
+pub fn compute_minimal_presentation_with_options<VF: Value, G: CriticalGrade>(
+    name: &str,
+    homology: usize,
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    options: &MpfreeOptions,
+) -> Result<MinimalPresentationComputationSummary, MpfreeError>
+where
+    Filtration<G, MapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+    Filtration<G, WideMapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
 {
-    let result = compute_minimal_presentation_with_check::<
+    let result = compute_minimal_presentation_with_check_and_options::<
         _,
         _,
-        std::io::Error,
-        fn(usize) -> Result<(), io::Error>,
-    >(name, homology, edge_list, None);
-    match result {
-        Ok(summary) => Ok(summary),
-        Err(err) => match err {
-            CheckedMpfreeError::CheckFailed(_) => {
-                panic!("Programming error: we didn't specify a check.")
-            }
-            CheckedMpfreeError::Mpfree(err) => Err(err),
-        },
+        Infallible,
+        fn(usize) -> Result<(), Infallible>,
+    >(name, homology, edge_list, None, options);
+    result.map_err(|err| match err {
+        CheckedMpfreeError::CheckFailed(never) => match never {},
+        CheckedMpfreeError::Mpfree(err) => err,
+    })
+}
+
+/// As [compute_minimal_presentation_with_options], but the flag filtration is built on the
+/// simplicial complex implementation `S`, like
+/// [SortedSimplicialComplex](crate::simplicial_complex::SortedSimplicialComplex), instead of the
+/// default [MapSimplicialComplex]. Returns [MpfreeError::TooManyVertices] if the keys of `S` do
+/// not fit the simplices of dimension `homology + 1`, see [SimplexKey::fits].
+pub fn compute_minimal_presentation_with_complex<VF: Value, G: CriticalGrade, S>(
+    name: &str,
+    homology: usize,
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    options: &MpfreeOptions,
+) -> Result<MinimalPresentationComputationSummary, MpfreeError>
+where
+    S: for<'a> SimplicialComplex<'a>,
+    Filtration<G, S>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    let max_dim = homology + 1;
+    if !S::fits(edge_list.n_vertices, max_dim) {
+        return Err(MpfreeError::TooManyVertices {
+            n_vertices: edge_list.n_vertices,
+            max_dim,
+        });
     }
+    let mut timers = MinimalPresentationComputationTime::default();
+    let mut filtration = FiltrationStatistics::default();
+    let result = build_bifiltration_and_run_mpfree::<
+        VF,
+        _,
+        S,
+        Infallible,
+        fn(usize) -> Result<(), Infallible>,
+    >(
+        name,
+        homology,
+        edge_list,
+        None,
+        options,
+        &mut timers,
+        &mut filtration,
+    );
+    let (output, files) = result.map_err(|err| match err {
+        CheckedMpfreeError::CheckFailed(never) => match never {},
+        CheckedMpfreeError::Mpfree(err) => err,
+    })?;
+    Ok(MinimalPresentationComputationSummary {
+        timers,
+        filtration,
+        output,
+        files,
+    })
+}
+
+/// Compute minimal presentations of the homology at each of the given dimensions of the clique
+/// bifiltration of the given bifiltered edge list, returned by dimension.
+///
+/// The flag filtration is built once, up to the maximum dimension plus one, and mpfree is run once
+/// per dimension, with the default [MpfreeOptions]. All the summaries have the same statistics of
+/// the filtration and time to build it.
+pub fn compute_minimal_presentations<VF: Value, G: CriticalGrade>(
+    name: &str,
+    homology: RangeInclusive<usize>,
+    edge_list: &EdgeList<FilteredEdge<G>>,
+) -> Result<BTreeMap<usize, MinimalPresentationComputationSummary>, MpfreeError>
+where
+    Filtration<G, MapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+    Filtration<G, WideMapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    compute_minimal_presentations_with_options(name, homology, edge_list, &MpfreeOptions::default())
+}
+
+/// As [compute_minimal_presentations], but mpfree is run with the given options. The temporary
+/// files of each dimension are named after `name` and the dimension.
+pub fn compute_minimal_presentations_with_options<VF: Value, G: CriticalGrade>(
+    name: &str,
+    homology: RangeInclusive<usize>,
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    options: &MpfreeOptions,
+) -> Result<BTreeMap<usize, MinimalPresentationComputationSummary>, MpfreeError>
+where
+    Filtration<G, MapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+    Filtration<G, WideMapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    if homology.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    if usize::fits(edge_list.n_vertices, homology.end() + 1) {
+        minimal_presentations_with_complex::<VF, _, MapSimplicialComplex>(
+            name, homology, edge_list, options,
+        )
+    } else {
+        minimal_presentations_with_complex::<VF, _, WideMapSimplicialComplex>(
+            name, homology, edge_list, options,
+        )
+    }
+}
+
+fn minimal_presentations_with_complex<VF: Value, G: CriticalGrade, S>(
+    name: &str,
+    homology: RangeInclusive<usize>,
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    options: &MpfreeOptions,
+) -> Result<BTreeMap<usize, MinimalPresentationComputationSummary>, MpfreeError>
+where
+    S: for<'a> SimplicialComplex<'a>,
+    Filtration<G, S>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    let mut timers = MinimalPresentationComputationTime::default();
+    let mut filtration_statistics = FiltrationStatistics::default();
+    let filtration = build_filtration::<_, S, Infallible, fn(usize) -> Result<(), Infallible>>(
+        edge_list,
+        homology.end() + 1,
+        None,
+        &mut timers,
+        &mut filtration_statistics,
+    )
+    .map_err(|err| match err {
+        CheckedMpfreeError::CheckFailed(never) => match never {},
+        CheckedMpfreeError::Mpfree(err) => err,
+    })?;
+
+    let mut summaries = BTreeMap::new();
+    for dimension in homology {
+        let mut dimension_timers = timers;
+        let (output, files) = run_mpfree_on_filtration(
+            &format!("{}_h{}", name, dimension),
+            dimension,
+            &filtration,
+            options,
+            &mut dimension_timers,
+        )?;
+        summaries.insert(
+            dimension,
+            MinimalPresentationComputationSummary {
+                timers: dimension_timers,
+                filtration: filtration_statistics.clone(),
+                output,
+                files,
+            },
+        );
+    }
+    Ok(summaries)
+}
+
+/// Compute a minimal presentation of the homology at the given dimension of an already built
+/// filtration, which must contain the simplices up to dimension `homology + 1`. This allows to
+/// build a filtration once and compute minimal presentations of it and of its subfiltrations, see
+/// [Filtration::restrict_to_edges].
+///
+/// The time to build the filtration is reported as zero.
+pub fn compute_minimal_presentation_of_filtration<VF: Value, G: CriticalGrade, S>(
+    name: &str,
+    homology: usize,
+    filtration: &Filtration<G, S>,
+    options: &MpfreeOptions,
+) -> Result<MinimalPresentationComputationSummary, MpfreeError>
+where
+    S: for<'a> SimplicialComplex<'a>,
+    Filtration<G, S>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    let mut timers = MinimalPresentationComputationTime::default();
+    let (output, files) =
+        run_mpfree_on_filtration(name, homology, filtration, options, &mut timers)?;
+    Ok(MinimalPresentationComputationSummary {
+        timers,
+        filtration: FiltrationStatistics {
+            sizes: filtration.sizes(),
+            memory_bytes_estimate: filtration.memory_bytes_estimate(),
+        },
+        output,
+        files,
+    })
 }
 
 #[derive(Error, Debug)]
@@ -79,6 +402,10 @@ pub enum CheckedMpfreeError<E> {
 /// of the given bifiltered edge list.
 ///
 /// The `name` parameter is used to name and identify temporary files.
+/// Mpfree is run with the default [MpfreeOptions]. While the filtration is built, it calls
+/// `memory_check_fn` periodically with an increasing counter, see
+/// [build_flag_filtration_with_check], so that the computation can be stopped if it becomes too
+/// large.
 pub fn compute_minimal_presentation_with_check<
     VF: Value,
     G: CriticalGrade,
@@ -92,35 +419,168 @@ pub fn compute_minimal_presentation_with_check<
 ) -> Result<MinimalPresentationComputationSummary, CheckedMpfreeError<E>>
 where
     Filtration<G, MapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+    Filtration<G, WideMapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    compute_minimal_presentation_with_check_and_options(
+        name,
+        homology,
+        edge_list,
+        memory_check_fn,
+        &MpfreeOptions::default(),
+    )
+}
+
+/// As [compute_minimal_presentation_with_check], but mpfree is run with the given options.
+pub fn compute_minimal_presentation_with_check_and_options<
+    VF: Value,
+    G: CriticalGrade,
+    E: std::error::Error,
+    F: Fn(usize) -> Result<(), E>,
+>(
+    name: &str,
+    homology: usize,
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    memory_check_fn: Option<F>,
+    options: &MpfreeOptions,
+) -> Result<MinimalPresentationComputationSummary, CheckedMpfreeError<E>>
+where
+    Filtration<G, MapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+    Filtration<G, WideMapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
 {
     let mut timers = MinimalPresentationComputationTime::default();
+    let mut filtration = FiltrationStatistics::default();
+    let (output, files) = if usize::fits(edge_list.n_vertices, homology + 1) {
+        build_bifiltration_and_run_mpfree::<VF, _, MapSimplicialComplex, _, _>(
+            name,
+            homology,
+            edge_list,
+            memory_check_fn,
+            options,
+            &mut timers,
+            &mut filtration,
+        )?
+    } else {
+        build_bifiltration_and_run_mpfree::<VF, _, WideMapSimplicialComplex, _, _>(
+            name,
+            homology,
+            edge_list,
+            memory_check_fn,
+            options,
+            &mut timers,
+            &mut filtration,
+        )?
+    };
+
+    Ok(MinimalPresentationComputationSummary {
+        timers,
+        filtration,
+        output,
+        files,
+    })
+}
 
-    // Build filtration.
+/// Builds the flag filtration and runs mpfree on it, see [run_mpfree_on_filtration].
+fn build_bifiltration_and_run_mpfree<
+    VF: Value,
+    G: CriticalGrade,
+    S,
+    E: std::error::Error,
+    F: Fn(usize) -> Result<(), E>,
+>(
+    name: &str,
+    homology: usize,
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    memory_check_fn: Option<F>,
+    options: &MpfreeOptions,
+    timers: &mut MinimalPresentationComputationTime,
+    statistics: &mut FiltrationStatistics,
+) -> Result<(ParsedMpfreeOutput, Vec<PathBuf>), CheckedMpfreeError<E>>
+where
+    S: for<'a> SimplicialComplex<'a>,
+    Filtration<G, S>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    let filtration: Filtration<_, S> =
+        build_filtration(edge_list, homology + 1, memory_check_fn, timers, statistics)?;
+    Ok(run_mpfree_on_filtration(
+        name,
+        homology,
+        &filtration,
+        options,
+        timers,
+    )?)
+}
+
+/// Builds the flag filtration of the edges up to the given dimension, and records its size and
+/// the time taken.
+fn build_filtration<G: CriticalGrade, S, E: std::error::Error, F: Fn(usize) -> Result<(), E>>(
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    max_dim: usize,
+    memory_check_fn: Option<F>,
+    timers: &mut MinimalPresentationComputationTime,
+    statistics: &mut FiltrationStatistics,
+) -> Result<Filtration<G, S>, CheckedMpfreeError<E>>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
     let start_filtration = std::time::Instant::now();
-    let filtration: Filtration<_, MapSimplicialComplex> = build_flag_filtration_with_check(
+    let filtration: Filtration<_, S> = build_flag_filtration_with_check(
         edge_list.n_vertices,
-        homology + 1,
+        max_dim,
         edge_list.edge_iter().cloned(),
         memory_check_fn,
     )
     .map_err(CheckedMpfreeError::CheckFailed)?;
     timers.build_filtration = start_filtration.elapsed();
+    *statistics = FiltrationStatistics {
+        sizes: filtration.sizes(),
+        memory_bytes_estimate: filtration.memory_bytes_estimate(),
+    };
+    Ok(filtration)
+}
+
+/// Writes the chain complex of the given homology dimension of the filtration, and runs mpfree on
+/// it. Returns the parsed output of mpfree and its input and output files, if it is not piped.
+fn run_mpfree_on_filtration<VF: Value, G: CriticalGrade, S>(
+    name: &str,
+    homology: usize,
+    filtration: &Filtration<G, S>,
+    options: &MpfreeOptions,
+    timers: &mut MinimalPresentationComputationTime,
+) -> Result<(ParsedMpfreeOutput, Vec<PathBuf>), MpfreeError>
+where
+    S: for<'a> SimplicialComplex<'a>,
+    Filtration<G, S>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    if options.pipe {
+        // Writing the bifiltration and running mpfree overlap, so they are timed together.
+        let pass = pass!("run_mpfree_piped", homology = homology; generators, relations);
+        let start_mpfree = std::time::Instant::now();
+        let output = run_mpfree_piped(|w| filtration.write_scc2020(homology, w), options)?;
+        timers.mpfree = start_mpfree.elapsed();
+        record_presentation_sizes(&pass, &output);
+        return Ok((output, Vec::new()));
+    }
+
+    let mut files = options.working_files()?;
+    let filepath_mpfree_input = files.file(&format!("{}_scc2020", name));
+    let filepath_out = files.file(&format!("{}_scc2020.out", name));
 
-    // Save filtration to disk.
     let start_io = std::time::Instant::now();
-    let directory = Path::new(TMP_DIRECTORY);
-    fs::create_dir_all(&directory).map_err(MpfreeError::CreateTmpDirectory)?;
-    let filepath_mpfree_input = directory.join(format!("{}_scc2020", name));
-    let filepath_out = filepath_mpfree_input.with_extension("out");
-    write_bifiltration(&filepath_mpfree_input, homology, &filtration).map_err(MpfreeError::Io)?;
+    write_bifiltration(&filepath_mpfree_input, homology, filtration).map_err(MpfreeError::Io)?;
     timers.write_bifiltration = start_io.elapsed();
 
-    // Compute minimal presentation.
+    let pass = pass!("run_mpfree", homology = homology; generators, relations);
     let start_mpfree = std::time::Instant::now();
-    let output = run_mpfree(filepath_mpfree_input, filepath_out)?;
+    let output = run_mpfree_with_options(&filepath_mpfree_input, &filepath_out, options)?;
     timers.mpfree = start_mpfree.elapsed();
+    record_presentation_sizes(&pass, &output);
+    Ok((output, files.paths().to_vec()))
+}
 
-    Ok(MinimalPresentationComputationSummary { timers, output })
+/// Records the number of generators and relations of the minimal presentation in the pass.
+fn record_presentation_sizes(pass: &Pass, output: &ParsedMpfreeOutput) {
+    pass.record("generators", output.sizes[1]);
+    pass.record("relations", output.sizes[0]);
 }
 
 fn write_bifiltration<
@@ -133,15 +593,25 @@ fn write_bifiltration<
     homology: usize,
     f: &F,
 ) -> io::Result<()> {
+    let pass = pass!("write_bifiltration", homology = homology; bytes);
     let file = std::fs::File::create(&filepath)?;
     let mut writer = BufWriter::new(&file);
     f.write_scc2020(homology, &mut writer)?;
+    writer.flush()?;
+    if let Ok(metadata) = file.metadata() {
+        pass.record("bytes", metadata.len() as usize);
+    }
     Ok(())
 }
 
 /// A error that happened when executing mpfree.
 #[derive(Error, Debug)]
 pub enum MpfreeError {
+    #[error(
+        "The simplices of dimension up to {max_dim} on {n_vertices} vertices do not fit in the keys of the simplicial complex"
+    )]
+    TooManyVertices { n_vertices: usize, max_dim: usize },
+
     #[error("Mpfree ended with a non-okay exit code: {0}")]
     ExitStatus(ExitStatus),
 
@@ -157,6 +627,10 @@ pub enum MpfreeError {
     #[error("Creating tmp directory")]
     CreateTmpDirectory(#[source] io::Error),
 
+    /// Any other error of running mpfree, like [ExternalError::Timeout].
+    #[error(transparent)]
+    External(ExternalError),
+
     #[error("Processing mpfree output file")]
     OutputFile(#[source] io::Error),
 
@@ -167,35 +641,77 @@ pub enum MpfreeError {
     WrongNumberFormat(#[from] std::num::ParseIntError),
 }
 
+impl From<ExternalError> for MpfreeError {
+    fn from(err: ExternalError) -> Self {
+        match err {
+            ExternalError::Spawn(_, err) => MpfreeError::SpawnMpfree(err),
+            ExternalError::ExitStatus(_, status) => MpfreeError::ExitStatus(status),
+            ExternalError::CreateWorkingDir(_, err) => MpfreeError::CreateTmpDirectory(err),
+            err => MpfreeError::External(err),
+        }
+    }
+}
+
+/// Runs mpfree, with the default [MpfreeOptions], on the given scc2020 input file, and parses
+/// the sizes of the minimal presentation it writes to the given output file.
 pub fn run_mpfree<P: AsRef<Path>>(
     filepath_in: P,
     filepath_out: P,
 ) -> Result<ParsedMpfreeOutput, MpfreeError> {
-    let mut child = Command::new("mpfree")
-        .args([
-            filepath_in.as_ref().as_os_str(),
-            filepath_out.as_ref().as_os_str(),
-        ])
-        .stdout(Stdio::null())
-        .spawn()
-        .map_err(MpfreeError::SpawnMpfree)?;
-    let exit_code = child.wait()?;
-    if !exit_code.success() {
-        return Err(MpfreeError::ExitStatus(exit_code));
-    }
+    run_mpfree_with_options(filepath_in, filepath_out, &MpfreeOptions::default())
+}
+
+/// As [run_mpfree], but mpfree is run with the given options. In particular, it is killed if it
+/// runs for longer than [ToolOptions::timeout].
+pub fn run_mpfree_with_options<P: AsRef<Path>>(
+    filepath_in: P,
+    filepath_out: P,
+    options: &MpfreeOptions,
+) -> Result<ParsedMpfreeOutput, MpfreeError> {
+    options.run([
+        filepath_in.as_ref().as_os_str(),
+        filepath_out.as_ref().as_os_str(),
+    ])?;
 
     let output_file = File::open(filepath_out.as_ref()).map_err(MpfreeError::OutputFile)?;
-    let mut child_stdout = BufReader::new(output_file);
+    parse_mpfree_output(BufReader::new(output_file))
+}
+
+/// Runs mpfree, streaming the scc2020 input written by `write_input` to its stdin, and parses the
+/// minimal presentation it writes to stdout. See [ToolOptions::pipe].
+fn run_mpfree_piped<F>(
+    write_input: F,
+    options: &MpfreeOptions,
+) -> Result<ParsedMpfreeOutput, MpfreeError>
+where
+    F: FnOnce(&mut BufWriter<ChildStdin>) -> io::Result<()>,
+{
+    let stdout = options.run_with_input(["/dev/stdin", "/dev/stdout"], write_input)?;
+    // Mpfree logs its progress to stdout, so the presentation starts after the logs.
+    let header_start = if stdout.starts_with(b"scc2020\n") {
+        0
+    } else {
+        stdout
+            .windows(b"\nscc2020\n".len())
+            .position(|window| window == b"\nscc2020\n")
+            .ok_or(MpfreeError::BadOutputHeader)?
+            + 1
+    };
+    parse_mpfree_output(&stdout[header_start..])
+}
+
+/// Parses the header of a minimal presentation in scc2020 format.
+fn parse_mpfree_output<R: BufRead>(mut r: R) -> Result<ParsedMpfreeOutput, MpfreeError> {
     let mut buffer = String::new();
-    child_stdout.read_line(&mut buffer)?;
+    r.read_line(&mut buffer)?;
     if buffer != "scc2020\n" {
         return Err(MpfreeError::BadOutputHeader);
     }
     buffer.clear();
-    child_stdout.read_line(&mut buffer)?;
+    r.read_line(&mut buffer)?;
     let parameters: usize = buffer.trim().parse()?;
     buffer.clear();
-    child_stdout.read_line(&mut buffer)?;
+    r.read_line(&mut buffer)?;
     let mut sizes_raw = buffer.split_whitespace();
     let mut sizes: [usize; 3] = [0, 0, 0];
     for s in sizes.iter_mut() {
@@ -207,3 +723,228 @@ pub fn run_mpfree<P: AsRef<Path>>(
 
     Ok(ParsedMpfreeOutput { parameters, sizes })
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::chain_complex::{ChainComplex, PresentationFormat};
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::io_utils::unique_temp_path;
+    use crate::mpfree::{
+        compute_minimal_presentation_with_complex, compute_minimal_presentation_with_options,
+        compute_minimal_presentations_with_options, diff_scc2020_files, parse_mpfree_output,
+        write_flag_free_implicit_representation, write_flag_scc2020, MpfreeError, MpfreeOptions,
+        ParsedMpfreeOutput,
+    };
+    use crate::simplicial_complex::SortedSimplicialComplex;
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn write_flag_free_implicit_representation_formats() {
+        let mut edge_list = EdgeList::new(5);
+        for u in 0..5 {
+            for v in (u + 1)..5 {
+                edge_list.add_edge(FilteredEdge {
+                    edge: BareEdge(u, v),
+                    grade: OneCriticalGrade([(u + 2 * v) % 3, (u * v) % 4]),
+                });
+            }
+        }
+
+        let mut scc2020 = Vec::new();
+        write_flag_free_implicit_representation(
+            &edge_list,
+            1,
+            PresentationFormat::Scc2020,
+            &mut scc2020,
+        )
+        .unwrap();
+        let mut expected_scc2020 = Vec::new();
+        write_flag_scc2020(&edge_list, 1, &mut expected_scc2020).unwrap();
+        assert_eq!(scc2020, expected_scc2020);
+
+        let mut firep = Vec::new();
+        write_flag_free_implicit_representation(
+            &edge_list,
+            1,
+            PresentationFormat::Firep,
+            &mut firep,
+        )
+        .unwrap();
+        let mut expected_firep = Vec::new();
+        ChainComplex::from_flag_filtration(&edge_list, 1)
+            .write_firep(&mut expected_firep)
+            .unwrap();
+        assert_eq!(firep, expected_firep);
+    }
+
+    #[test]
+    fn parse_mpfree_output_header() {
+        let output = "scc2020\n2\n3 4 1\n0 0 ;\n";
+        assert_eq!(
+            parse_mpfree_output(output.as_bytes()).unwrap(),
+            ParsedMpfreeOutput {
+                parameters: 2,
+                sizes: [3, 4, 1]
+            }
+        );
+        assert!(matches!(
+            parse_mpfree_output("firep\n2\n3 4 1\n".as_bytes()),
+            Err(MpfreeError::BadOutputHeader)
+        ));
+    }
+
+    #[test]
+    fn presentation_diffs() {
+        let expected = ParsedMpfreeOutput {
+            parameters: 2,
+            sizes: [12, 7, 0],
+        };
+        assert!(expected.diff(&expected).is_empty());
+        assert_eq!(expected.diff(&expected).to_string(), "no differences");
+
+        let found = ParsedMpfreeOutput {
+            parameters: 2,
+            sizes: [10, 7, 1],
+        };
+        let diff = expected.diff(&found);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.size_differences(), [-2, 0, 1]);
+        assert_eq!(
+            diff.to_string(),
+            "relations: expected 12, found 10 (-2)\nthird size: expected 0, found 1 (+1)"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_mpfree_piped_skips_logs() {
+        // A fake mpfree that echoes its input, preceded by some logs.
+        let options = MpfreeOptions {
+            binary_path: "sh".into(),
+            extra_args: vec!["-c".into(), "echo Reading input; cat $0".into()],
+            pipe: true,
+            ..MpfreeOptions::default()
+        };
+        let output = crate::mpfree::run_mpfree_piped(
+            |w| std::io::Write::write_all(w, b"scc2020\n2\n5 6 0\n"),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(output.sizes, [5, 6, 0]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_runs_delete_their_files() {
+        let working_dir = unique_temp_path("failed_mpfree");
+        // A fake mpfree that reads its input and fails.
+        let options = MpfreeOptions {
+            binary_path: "sh".into(),
+            extra_args: vec!["-c".into(), "cat $0 > /dev/null; exit 1".into()],
+            working_dir: working_dir.clone(),
+            keep_files: false,
+            ..MpfreeOptions::default()
+        };
+        let mut edge_list: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = EdgeList::new(3);
+        edge_list.add_edge(FilteredEdge {
+            edge: BareEdge(0, 1),
+            grade: OneCriticalGrade([1, 2]),
+        });
+        let result = compute_minimal_presentation_with_options("failed", 0, &edge_list, &options);
+        assert!(matches!(result, Err(MpfreeError::ExitStatus(_))));
+        assert_eq!(std::fs::read_dir(&working_dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(working_dir).unwrap();
+    }
+
+    #[test]
+    fn complex_too_small_for_the_keys() {
+        // The keys of 2^22 vertices overflow usize at dimension 2, so mpfree is never run.
+        let edge_list: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = EdgeList::new(1 << 22);
+        let result = compute_minimal_presentation_with_complex::<usize, _, SortedSimplicialComplex>(
+            "too_many_vertices",
+            1,
+            &edge_list,
+            &MpfreeOptions::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(MpfreeError::TooManyVertices {
+                n_vertices: 4194304,
+                max_dim: 2
+            })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_with_the_same_name_use_other_files() {
+        let working_dir = unique_temp_path("kept_mpfree");
+        // A fake mpfree that copies its input to its output.
+        let options = MpfreeOptions {
+            binary_path: "sh".into(),
+            extra_args: vec!["-c".into(), "cp $0 $1".into()],
+            working_dir: working_dir.clone(),
+            keep_files: true,
+            ..MpfreeOptions::default()
+        };
+        let mut edge_list: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = EdgeList::new(3);
+        edge_list.add_edge(FilteredEdge {
+            edge: BareEdge(0, 1),
+            grade: OneCriticalGrade([1, 2]),
+        });
+        let first =
+            compute_minimal_presentation_with_options("same", 0, &edge_list, &options).unwrap();
+        let second =
+            compute_minimal_presentation_with_options("same", 0, &edge_list, &options).unwrap();
+        assert_eq!(first.files.len(), 2);
+        assert_ne!(first.files, second.files);
+        for path in first.files.iter().chain(second.files.iter()) {
+            assert!(path.is_file());
+        }
+        assert_eq!(
+            std::fs::read(&first.files[0]).unwrap(),
+            std::fs::read(&first.files[1]).unwrap()
+        );
+        assert!(diff_scc2020_files(&first.files[1], &second.files[1])
+            .unwrap()
+            .is_empty());
+        std::fs::remove_dir_all(working_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn minimal_presentations_of_homology_range() {
+        // A fake mpfree that echoes its input, so that the sizes are those of the chain complexes.
+        let options = MpfreeOptions {
+            binary_path: "sh".into(),
+            extra_args: vec!["-c".into(), "cat $0".into()],
+            pipe: true,
+            ..MpfreeOptions::default()
+        };
+        let mut edge_list: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = EdgeList::new(5);
+        for u in 0..5 {
+            for v in (u + 1)..5 {
+                edge_list.add_edge(FilteredEdge {
+                    edge: BareEdge(u, v),
+                    grade: OneCriticalGrade([u, v]),
+                });
+            }
+        }
+        let summaries =
+            compute_minimal_presentations_with_options("range", 0..=2, &edge_list, &options)
+                .unwrap();
+
+        assert_eq!(summaries.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(summaries[&0].output.sizes, [10, 5, 0]);
+        assert_eq!(summaries[&1].output.sizes, [10, 10, 5]);
+        assert_eq!(summaries[&2].output.sizes, [5, 10, 10]);
+        for summary in summaries.values() {
+            assert_eq!(summary.filtration.sizes, vec![5, 10, 10, 5]);
+        }
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty =
+            compute_minimal_presentations_with_options("range", 1..=0, &edge_list, &options);
+        assert!(empty.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,757 @@
+//! Chains of the usual preprocessing steps of a bifiltered edge list, from thresholding and
+//! quantizing the grades to removing edges, building the flag filtration and computing a minimal
+//! presentation with mpfree, configured once as a [Pipeline] and run with per-step timings.
+//! With [Pipeline::time_budget], all the stages share one wall-clock limit, and with
+//! [Pipeline::max_simplices] the pipeline gives up before building a filtration that is too large.
+//!
+//! ```
+//! use filtration_domination::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+//! use filtration_domination::pipeline::Pipeline;
+//! use filtration_domination::removal::EdgeOrder;
+//!
+//! let edges = get_dataset_density_edge_list(
+//!     Dataset::Sphere { n_points: 50 },
+//!     Threshold::KeepAll,
+//!     None,
+//!     false,
+//! )
+//! .unwrap();
+//! let output = Pipeline::new()
+//!     .sort(EdgeOrder::ReverseLexicographic)
+//!     .strong_removal(EdgeOrder::Maintain)
+//!     .build_filtration(2)
+//!     .run(edges.clone())
+//!     .unwrap();
+//! assert!(output.edges.len() < edges.len());
+//! assert_eq!(output.timings.len(), 3);
+//! ```
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+use num::NumCast;
+use thiserror::Error;
+
+use crate::budget::{TimeBudget, TimeBudgetExceeded};
+use crate::edges::transform::{quantize, Quantization, Rounding};
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::filtration::{
+    build_flag_filtration_with_check, estimate_flag_size, validate_flag_edges, Filtration,
+    FiltrationError,
+};
+use crate::mpfree::{
+    compute_minimal_presentation_of_filtration, MinimalPresentationComputationSummary, MpfreeError,
+    MpfreeOptions,
+};
+use crate::removal::{
+    remove_filtration_dominated_with_options, remove_low_core_edges,
+    remove_strongly_filtration_dominated_until_stable,
+    remove_strongly_filtration_dominated_with_options, EdgeOrder, PassOrder, RemovalOptions,
+};
+use crate::simplicial_complex::{
+    MapSimplicialComplex, SimplexKey, SimplicialComplex, WideMapSimplicialComplex,
+};
+use crate::{OneCriticalGrade, Value};
+
+type BifilteredEdgeList<VF> = EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>;
+
+/// A step of a [Pipeline] that transforms the edge list.
+#[derive(Debug, Clone)]
+pub enum Step<VF> {
+    /// Keep only the edges whose grade is less than or equal to the given one, see
+    /// [EdgeList::truncate].
+    Truncate(OneCriticalGrade<VF, 2>),
+    /// Coarsen the grades, see [quantize].
+    Quantize([Quantization<VF>; 2], Rounding),
+    /// Sort or shuffle the edges, see [EdgeOrder::apply].
+    Sort(EdgeOrder),
+    /// Remove the edges that are in no simplex of the given dimension, found by their core
+    /// numbers, see [remove_low_core_edges]. Only the homology of that dimension and above is
+    /// preserved.
+    CorePruning(usize),
+    /// Remove strongly filtration-dominated edges, see
+    /// [remove_strongly_filtration_dominated_with_options].
+    StrongRemoval(RemovalOptions),
+    /// Remove filtration-dominated edges, see [remove_filtration_dominated_with_options].
+    FullRemoval(RemovalOptions),
+    /// Remove strongly filtration-dominated edges in passes until no pass removes an edge, see
+    /// [remove_strongly_filtration_dominated_until_stable].
+    UntilStable {
+        order: PassOrder,
+        max_iterations: usize,
+        max_time: Option<Duration>,
+    },
+}
+
+impl<VF> Step<VF> {
+    /// The stage of the pipeline that runs this step.
+    pub fn stage(&self) -> Stage {
+        match self {
+            Step::Truncate(_) => Stage::Truncate,
+            Step::Quantize(_, _) => Stage::Quantize,
+            Step::Sort(_) => Stage::Sort,
+            Step::CorePruning(_) => Stage::CorePruning,
+            Step::StrongRemoval(_) => Stage::StrongRemoval,
+            Step::FullRemoval(_) => Stage::FullRemoval,
+            Step::UntilStable { .. } => Stage::UntilStable,
+        }
+    }
+}
+
+/// The kinds of stages of a [Pipeline], which label its [StepTiming]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stage {
+    Truncate,
+    Quantize,
+    Sort,
+    CorePruning,
+    StrongRemoval,
+    FullRemoval,
+    UntilStable,
+    BuildFiltration,
+    Mpfree,
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Stage::Truncate => "truncate",
+            Stage::Quantize => "quantize",
+            Stage::Sort => "sort",
+            Stage::CorePruning => "core-pruning",
+            Stage::StrongRemoval => "strong-removal",
+            Stage::FullRemoval => "full-removal",
+            Stage::UntilStable => "until-stable",
+            Stage::BuildFiltration => "build-filtration",
+            Stage::Mpfree => "mpfree",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The wall-clock time taken by a stage of a [Pipeline], and the number of edges before and after
+/// it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StepTiming {
+    pub stage: Stage,
+    pub edges_before: usize,
+    pub edges_after: usize,
+    pub duration: Duration,
+}
+
+/// The computation of a minimal presentation that ends a [Pipeline], see
+/// [compute_minimal_presentation_of_filtration].
+#[derive(Debug, Clone)]
+struct MpfreeStep {
+    name: String,
+    homology: usize,
+    options: MpfreeOptions,
+}
+
+/// The reasons why a [Pipeline] can fail.
+#[derive(Error, Debug)]
+pub enum PipelineError {
+    #[error(
+        "The simplices of dimension up to {max_dim} on {n_vertices} vertices do not fit in the keys of the simplicial complex"
+    )]
+    TooManyVertices { n_vertices: usize, max_dim: usize },
+
+    #[error(transparent)]
+    Filtration(#[from] FiltrationError),
+
+    #[error(transparent)]
+    Mpfree(#[from] MpfreeError),
+
+    #[error("Building the flag filtration")]
+    TimeBudget(#[source] TimeBudgetExceeded),
+
+    #[error(
+        "The flag filtration may have up to {estimate} simplices, more than the maximum of {cap}"
+    )]
+    TooManySimplices { estimate: usize, cap: usize },
+
+    #[error(
+        "Core pruning for the homology of dimension {pruning} does not preserve the homology of dimension {homology} computed by the pipeline"
+    )]
+    CorePruningAboveHomology { pruning: usize, homology: usize },
+}
+
+/// A sequence of steps on a bifiltered edge list, optionally followed by building its flag
+/// filtration and computing a minimal presentation of its homology with mpfree.
+///
+/// The steps are added with the builder methods, and run in the order in which they were added by
+/// [Pipeline::run], which takes the input edge list by value and returns the resulting edges.
+/// Building the filtration and running mpfree always come after all the steps.
+#[derive(Debug, Clone)]
+pub struct Pipeline<VF> {
+    steps: Vec<Step<VF>>,
+    filtration_dimension: Option<usize>,
+    mpfree: Option<MpfreeStep>,
+    time_budget: Option<Duration>,
+    max_simplices: Option<usize>,
+}
+
+/// The result of running a [Pipeline].
+#[derive(Debug)]
+pub struct PipelineOutput<VF> {
+    /// The edges after all the steps.
+    pub edges: BifilteredEdgeList<VF>,
+    /// The flag filtration of the edges, if the pipeline builds it.
+    pub filtration: Option<PipelineFiltration<VF>>,
+    /// The minimal presentation computed by mpfree, if the pipeline runs it.
+    pub minimal_presentation: Option<MinimalPresentationComputationSummary>,
+    /// The time taken by each stage, in the order in which they were run.
+    pub timings: Vec<StepTiming>,
+}
+
+/// The flag filtration built by a [Pipeline], on the simplicial complex with the smallest keys
+/// that fit its simplices, see [SimplexKey::fits].
+#[derive(Debug)]
+pub enum PipelineFiltration<VF> {
+    /// A filtration on a [MapSimplicialComplex], with 64-bit keys.
+    Narrow(Filtration<OneCriticalGrade<VF, 2>, MapSimplicialComplex>),
+    /// A filtration on a [WideMapSimplicialComplex], with 128-bit keys, for the simplices that do
+    /// not fit in 64 bits.
+    Wide(Filtration<OneCriticalGrade<VF, 2>, WideMapSimplicialComplex>),
+}
+
+impl<VF: Value> PipelineFiltration<VF> {
+    /// The number of simplices of each dimension, see [Filtration::sizes].
+    pub fn sizes(&self) -> Vec<usize> {
+        match self {
+            PipelineFiltration::Narrow(filtration) => filtration.sizes(),
+            PipelineFiltration::Wide(filtration) => filtration.sizes(),
+        }
+    }
+}
+
+impl<VF> PipelineOutput<VF> {
+    /// The total time taken by the stages of the pipeline.
+    pub fn total_duration(&self) -> Duration {
+        self.timings.iter().map(|timing| timing.duration).sum()
+    }
+}
+
+impl<VF> Default for Pipeline<VF> {
+    fn default() -> Self {
+        Self {
+            steps: Vec::new(),
+            filtration_dimension: None,
+            mpfree: None,
+            time_budget: None,
+            max_simplices: None,
+        }
+    }
+}
+
+impl<VF: Value + NumCast> Pipeline<VF> {
+    /// A pipeline without steps, which returns its input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The steps of the pipeline that transform the edge list, in order.
+    pub fn steps(&self) -> &[Step<VF>] {
+        &self.steps
+    }
+
+    /// Adds the given step.
+    #[must_use]
+    pub fn step(mut self, step: Step<VF>) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Adds a [Step::Truncate] step.
+    #[must_use]
+    pub fn truncate(self, grade: OneCriticalGrade<VF, 2>) -> Self {
+        self.step(Step::Truncate(grade))
+    }
+
+    /// Adds a [Step::Quantize] step.
+    #[must_use]
+    pub fn quantize(self, quantization: [Quantization<VF>; 2], rounding: Rounding) -> Self {
+        self.step(Step::Quantize(quantization, rounding))
+    }
+
+    /// Adds a [Step::Sort] step.
+    #[must_use]
+    pub fn sort(self, order: EdgeOrder) -> Self {
+        self.step(Step::Sort(order))
+    }
+
+    /// Adds a [Step::CorePruning] step for the homology of the given dimension. Running the
+    /// pipeline fails with [PipelineError::CorePruningAboveHomology] if it computes the homology
+    /// of a lower dimension: that of mpfree, or, without mpfree, the highest one determined by the
+    /// filtration, one less than its dimension.
+    #[must_use]
+    pub fn core_pruning(self, homology: usize) -> Self {
+        self.step(Step::CorePruning(homology))
+    }
+
+    /// Adds a [Step::StrongRemoval] step that processes the edges in the given order, with the
+    /// default options otherwise.
+    #[must_use]
+    pub fn strong_removal(self, order: EdgeOrder) -> Self {
+        self.step(Step::StrongRemoval(RemovalOptions {
+            order,
+            ..RemovalOptions::default()
+        }))
+    }
+
+    /// Adds a [Step::FullRemoval] step that processes the edges in the given order, with the
+    /// default options otherwise.
+    #[must_use]
+    pub fn full_removal(self, order: EdgeOrder) -> Self {
+        self.step(Step::FullRemoval(RemovalOptions {
+            order,
+            ..RemovalOptions::default()
+        }))
+    }
+
+    /// Adds a [Step::UntilStable] step without a maximum time.
+    #[must_use]
+    pub fn until_stable(self, order: impl Into<PassOrder>, max_iterations: usize) -> Self {
+        self.step(Step::UntilStable {
+            order: order.into(),
+            max_iterations,
+            max_time: None,
+        })
+    }
+
+    /// Builds the flag filtration of the resulting edges, up to the given dimension.
+    #[must_use]
+    pub fn build_filtration(mut self, max_dim: usize) -> Self {
+        self.filtration_dimension = Some(self.filtration_dimension.unwrap_or(0).max(max_dim));
+        self
+    }
+
+    /// Computes a minimal presentation of the homology at the given dimension of the flag
+    /// filtration of the resulting edges, with mpfree, which is built up to dimension
+    /// `homology + 1` if needed. The `name` is used to name the temporary files.
+    #[must_use]
+    pub fn mpfree(self, name: &str, homology: usize, options: MpfreeOptions) -> Self {
+        let mut pipeline = self.build_filtration(homology + 1);
+        pipeline.mpfree = Some(MpfreeStep {
+            name: name.to_string(),
+            homology,
+            options,
+        });
+        pipeline
+    }
+
+    /// Limits the wall-clock time of each run of the pipeline, counted from the start of
+    /// [Pipeline::run], see [TimeBudget]. The steps that remove edges stop once the time runs out,
+    /// as when their own maximum time is exceeded, and return the edges they were given. Building
+    /// the filtration fails with [PipelineError::TimeBudget], and mpfree is killed, failing with
+    /// [ExternalError::Timeout](crate::external::ExternalError::Timeout). The maximum times of the
+    /// steps, and the timeout of the mpfree options, still apply if they are shorter.
+    #[must_use]
+    pub fn time_budget(mut self, limit: Duration) -> Self {
+        self.time_budget = Some(limit);
+        self
+    }
+
+    /// Fails with [PipelineError::TooManySimplices], before building the filtration, if the
+    /// [estimate_flag_size] of the resulting edges is more than the given number of simplices.
+    /// Since the estimate is an upper bound, the pipeline may give up on filtrations that would
+    /// have fit.
+    #[must_use]
+    pub fn max_simplices(mut self, cap: usize) -> Self {
+        self.max_simplices = Some(cap);
+        self
+    }
+
+    /// Runs the steps of the pipeline on the edge list, and then builds the filtration and runs
+    /// mpfree, if the pipeline does so. The filtration is built on a [MapSimplicialComplex] if its
+    /// simplices fit in its keys, and otherwise on a [WideMapSimplicialComplex], as
+    /// [compute_minimal_presentation](crate::mpfree::compute_minimal_presentation) does.
+    pub fn run(
+        &self,
+        edge_list: BifilteredEdgeList<VF>,
+    ) -> Result<PipelineOutput<VF>, PipelineError> {
+        self.check_core_pruning()?;
+        let budget = match self.time_budget {
+            Some(limit) => TimeBudget::new(limit),
+            None => TimeBudget::unlimited(),
+        };
+        let mut edges = edge_list;
+        let mut timings = Vec::with_capacity(self.steps.len() + 2);
+        for step in self.steps.iter() {
+            let edges_before = edges.len();
+            let start = Instant::now();
+            edges = run_step(step, edges, &budget);
+            timings.push(StepTiming {
+                stage: step.stage(),
+                edges_before,
+                edges_after: edges.len(),
+                duration: start.elapsed(),
+            });
+        }
+
+        let filtration = match self.filtration_dimension {
+            Some(max_dim) => {
+                if !u128::fits(edges.n_vertices, max_dim) {
+                    return Err(PipelineError::TooManyVertices {
+                        n_vertices: edges.n_vertices,
+                        max_dim,
+                    });
+                }
+                validate_flag_edges(&edges)?;
+                if let Some(cap) = self.max_simplices {
+                    let estimate = estimate_flag_size(&edges, max_dim).total();
+                    if estimate > cap {
+                        return Err(PipelineError::TooManySimplices { estimate, cap });
+                    }
+                }
+                let start = Instant::now();
+                let filtration = if usize::fits(edges.n_vertices, max_dim) {
+                    PipelineFiltration::Narrow(build_filtration(&edges, max_dim, &budget)?)
+                } else {
+                    PipelineFiltration::Wide(build_filtration(&edges, max_dim, &budget)?)
+                };
+                timings.push(StepTiming {
+                    stage: Stage::BuildFiltration,
+                    edges_before: edges.len(),
+                    edges_after: edges.len(),
+                    duration: start.elapsed(),
+                });
+                Some(filtration)
+            }
+            None => None,
+        };
+
+        let minimal_presentation = match (&self.mpfree, &filtration) {
+            (Some(mpfree), Some(filtration)) => {
+                let options = MpfreeOptions {
+                    timeout: budget.cap(mpfree.options.timeout),
+                    ..mpfree.options.clone()
+                };
+                let start = Instant::now();
+                let summary = match filtration {
+                    PipelineFiltration::Narrow(filtration) => {
+                        compute_minimal_presentation_of_filtration(
+                            &mpfree.name,
+                            mpfree.homology,
+                            filtration,
+                            &options,
+                        )
+                    }
+                    PipelineFiltration::Wide(filtration) => {
+                        compute_minimal_presentation_of_filtration(
+                            &mpfree.name,
+                            mpfree.homology,
+                            filtration,
+                            &options,
+                        )
+                    }
+                }?;
+                timings.push(StepTiming {
+                    stage: Stage::Mpfree,
+                    edges_before: edges.len(),
+                    edges_after: edges.len(),
+                    duration: start.elapsed(),
+                });
+                Some(summary)
+            }
+            _ => None,
+        };
+
+        Ok(PipelineOutput {
+            edges,
+            filtration,
+            minimal_presentation,
+            timings,
+        })
+    }
+}
+
+impl<VF> Pipeline<VF> {
+    /// Checks that the core pruning steps preserve the homology computed by the pipeline.
+    fn check_core_pruning(&self) -> Result<(), PipelineError> {
+        let homology = match (&self.mpfree, self.filtration_dimension) {
+            (Some(mpfree), _) => mpfree.homology,
+            (None, Some(max_dim)) => max_dim.saturating_sub(1),
+            (None, None) => return Ok(()),
+        };
+        for step in self.steps.iter() {
+            if let Step::CorePruning(pruning) = *step {
+                if pruning > homology {
+                    return Err(PipelineError::CorePruningAboveHomology { pruning, homology });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds the flag filtration of the edges on the given simplicial complex, which must fit their
+/// simplices, and fails once the budget runs out.
+fn build_filtration<VF: Value, S>(
+    edges: &BifilteredEdgeList<VF>,
+    max_dim: usize,
+    budget: &TimeBudget,
+) -> Result<Filtration<OneCriticalGrade<VF, 2>, S>, PipelineError>
+where
+    S: for<'a> SimplicialComplex<'a>,
+{
+    build_flag_filtration_with_check(
+        edges.n_vertices,
+        max_dim,
+        edges.edge_iter().copied(),
+        Some(budget.check_fn()),
+    )
+    .map_err(PipelineError::TimeBudget)
+}
+
+/// Runs the step on the edges. The steps that remove edges stop once the budget runs out.
+fn run_step<VF: Value + NumCast>(
+    step: &Step<VF>,
+    mut edges: BifilteredEdgeList<VF>,
+    budget: &TimeBudget,
+) -> BifilteredEdgeList<VF> {
+    match step {
+        Step::Truncate(grade) => {
+            edges.truncate(*grade);
+            edges
+        }
+        Step::Quantize(quantization, rounding) => {
+            quantize(&mut edges, *quantization, *rounding);
+            edges
+        }
+        Step::Sort(order) => {
+            order.apply(&mut edges);
+            edges
+        }
+        Step::CorePruning(homology) => remove_low_core_edges(&edges, *homology),
+        Step::StrongRemoval(options) => {
+            let options = RemovalOptions {
+                max_time: budget.cap(options.max_time),
+                ..*options
+            };
+            remove_strongly_filtration_dominated_with_options(&mut edges, &options).remaining
+        }
+        Step::FullRemoval(options) => {
+            let options = RemovalOptions {
+                max_time: budget.cap(options.max_time),
+                ..*options
+            };
+            remove_filtration_dominated_with_options(&mut edges, &options).remaining
+        }
+        Step::UntilStable {
+            order,
+            max_iterations,
+            max_time,
+        } => {
+            remove_strongly_filtration_dominated_until_stable(
+                &edges,
+                *order,
+                *max_iterations,
+                budget.cap(*max_time),
+            )
+            .remaining
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::transform::{quantize, Quantization, Rounding};
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::mpfree::MpfreeOptions;
+    use crate::pipeline::{Pipeline, PipelineError, PipelineFiltration, Stage};
+    use crate::removal::{
+        remove_low_core_edges, remove_strongly_filtration_dominated,
+        remove_strongly_filtration_dominated_until_stable, EdgeOrder,
+    };
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn pipeline_runs_steps_in_order() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 80 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let quantization = [Quantization::MaxValues(16), Quantization::MaxValues(16)];
+        let output = Pipeline::new()
+            .truncate(OneCriticalGrade([OrderedFloat(1.), OrderedFloat(1.)]))
+            .quantize(quantization, Rounding::Up)
+            .strong_removal(EdgeOrder::ReverseLexicographic)
+            .until_stable(EdgeOrder::ReverseLexicographic, 5)
+            .build_filtration(2)
+            .run(edges.clone())
+            .unwrap();
+
+        let mut preprocessed = edges.clone();
+        preprocessed.truncate(OneCriticalGrade([OrderedFloat(1.), OrderedFloat(1.)]));
+        quantize(&mut preprocessed, quantization, Rounding::Up);
+        let once = remove_strongly_filtration_dominated(
+            &mut preprocessed,
+            EdgeOrder::ReverseLexicographic,
+        );
+        let expected = remove_strongly_filtration_dominated_until_stable(
+            &once,
+            EdgeOrder::ReverseLexicographic.into(),
+            5,
+            None,
+        )
+        .remaining;
+        assert_eq!(output.edges.edges(), expected.edges());
+
+        let stages: Vec<Stage> = output.timings.iter().map(|t| t.stage).collect();
+        assert_eq!(
+            stages,
+            vec![
+                Stage::Truncate,
+                Stage::Quantize,
+                Stage::StrongRemoval,
+                Stage::UntilStable,
+                Stage::BuildFiltration
+            ]
+        );
+        assert_eq!(output.timings[0].edges_before, edges.len());
+        assert_eq!(output.timings[3].edges_after, expected.len());
+        assert!(output.filtration.is_some());
+        assert!(output.minimal_presentation.is_none());
+    }
+
+    #[test]
+    fn pipeline_prunes_low_core_edges() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 60 },
+            Threshold::Fixed(0.6),
+            None,
+            false,
+        )
+        .unwrap();
+        let output = Pipeline::new().core_pruning(3).run(edges.clone()).unwrap();
+        let expected = remove_low_core_edges(&edges, 3);
+        assert!(expected.len() < edges.len());
+        assert_eq!(output.edges.edges(), expected.edges());
+        assert_eq!(output.timings[0].stage, Stage::CorePruning);
+
+        let filtration = Pipeline::new()
+            .core_pruning(3)
+            .build_filtration(4)
+            .run(edges.clone())
+            .unwrap();
+        assert_eq!(filtration.edges.edges(), expected.edges());
+
+        // Pruning for the homology of dimension 2 may change the homology of dimension 1.
+        let mismatched = Pipeline::new()
+            .core_pruning(2)
+            .mpfree("pipeline_core_pruning", 1, MpfreeOptions::default())
+            .run(edges.clone());
+        assert!(matches!(
+            mismatched,
+            Err(PipelineError::CorePruningAboveHomology {
+                pruning: 2,
+                homology: 1
+            })
+        ));
+        let too_low = Pipeline::new()
+            .core_pruning(3)
+            .build_filtration(3)
+            .run(edges);
+        assert!(matches!(
+            too_low,
+            Err(PipelineError::CorePruningAboveHomology {
+                pruning: 3,
+                homology: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn pipeline_respects_time_budget() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Sphere { n_points: 50 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let pipeline = Pipeline::new()
+            .strong_removal(EdgeOrder::ReverseLexicographic)
+            .build_filtration(2);
+
+        let output = pipeline
+            .clone()
+            .time_budget(Duration::from_secs(3600))
+            .run(edges.clone())
+            .unwrap();
+        assert!(output.edges.len() < edges.len());
+
+        // The removal stops right away and returns its input, and then building the filtration
+        // fails.
+        let exhausted = pipeline.time_budget(Duration::ZERO).run(edges.clone());
+        assert!(matches!(exhausted, Err(PipelineError::TimeBudget(_))));
+    }
+
+    #[test]
+    fn pipeline_respects_max_simplices() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Sphere { n_points: 50 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let pipeline = Pipeline::new().build_filtration(2);
+        let n_simplices: usize = pipeline
+            .run(edges.clone())
+            .unwrap()
+            .filtration
+            .unwrap()
+            .sizes()
+            .iter()
+            .sum();
+
+        // The estimate of the complete graph is exact.
+        let output = pipeline
+            .clone()
+            .max_simplices(n_simplices)
+            .run(edges.clone());
+        assert!(output.is_ok());
+        let too_many = pipeline.max_simplices(n_simplices - 1).run(edges.clone());
+        assert!(matches!(
+            too_many,
+            Err(PipelineError::TooManySimplices { estimate, cap })
+                if estimate == n_simplices && cap == n_simplices - 1
+        ));
+    }
+    #[test]
+    fn pipeline_builds_wide_filtrations() {
+        // The tetrahedra on 100000 vertices do not fit in 64-bit keys.
+        let mut edges: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = EdgeList::new(100_000);
+        for (u, v) in [
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (0, 99_999),
+            (1, 99_999),
+            (2, 99_999),
+        ] {
+            edges.add_edge(FilteredEdge {
+                grade: OneCriticalGrade([u, v]),
+                edge: BareEdge(u, v),
+            });
+        }
+        let output = Pipeline::new().build_filtration(3).run(edges).unwrap();
+        match output.filtration {
+            Some(filtration @ PipelineFiltration::Wide(_)) => {
+                assert_eq!(filtration.sizes(), vec![100_000, 6, 4, 1]);
+            }
+            _ => panic!("Expected a filtration with 128-bit keys"),
+        }
+    }
+}
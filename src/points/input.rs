@@ -1,11 +1,18 @@
 //! Utilities to read point clouds from disk.
+//!
+//! Besides [read_point_cloud], which reads the comma-separated files of the built-in datasets,
+//! there are readers for CSV files, with or without a header, whitespace-separated XYZ files, and
+//! the vertices of OFF and ASCII PLY files. They check that every point has as many coordinates
+//! as the dimension `N` of the point cloud, and report the line of the first malformed point.
+//! [read_point_cloud_in_format] picks the reader from a [PointCloudFormat].
 use num::Float;
 use std::fmt::Display;
 use std::io;
 use std::io::BufRead;
+use std::path::Path;
 use std::str::FromStr;
 
-use crate::io_utils::parse_next;
+use crate::io_utils::{invalid_data, parse, parse_next};
 use crate::points::{Point, PointCloud};
 
 /// Read a point cloud from the given reader.
@@ -31,6 +38,368 @@ where
     Ok(PointCloud(points))
 }
 
+/// The file formats of point clouds that can be read by [read_point_cloud_in_format] and written
+/// by [write_point_cloud_in_format](crate::points::output::write_point_cloud_in_format).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointCloudFormat {
+    /// Comma-separated coordinates, one point per line, optionally after a header line.
+    Csv { header: bool },
+    /// Whitespace-separated coordinates, one point per line. Lines starting with `#` are
+    /// comments.
+    Xyz,
+    /// The vertices of an OFF file. The faces are ignored.
+    Off,
+    /// The `x`, `y` and `z` properties of the vertices of an ASCII PLY file.
+    Ply,
+}
+
+impl PointCloudFormat {
+    /// Guesses the format from the extension of the path: `csv`, `xyz` or `txt`, `off`, or `ply`.
+    /// CSV files are assumed to have no header.
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(PointCloudFormat::Csv { header: false }),
+            "xyz" | "txt" => Some(PointCloudFormat::Xyz),
+            "off" => Some(PointCloudFormat::Off),
+            "ply" => Some(PointCloudFormat::Ply),
+            _ => None,
+        }
+    }
+}
+
+/// Read a point cloud in the given format.
+pub fn read_point_cloud_in_format<T: Float + FromStr + Display, R: BufRead, const N: usize>(
+    r: R,
+    format: PointCloudFormat,
+) -> io::Result<PointCloud<T, N>>
+where
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    match format {
+        PointCloudFormat::Csv { header } => read_csv_point_cloud(r, header),
+        PointCloudFormat::Xyz => read_xyz_point_cloud(r),
+        PointCloudFormat::Off => read_off_point_cloud(r),
+        PointCloudFormat::Ply => read_ply_point_cloud(r),
+    }
+}
+
+/// Read a point cloud with comma-separated coordinates, one point per line. If `header` is true,
+/// the first line is skipped. Empty lines are ignored.
+pub fn read_csv_point_cloud<T: Float + FromStr + Display, R: BufRead, const N: usize>(
+    r: R,
+    header: bool,
+) -> io::Result<PointCloud<T, N>>
+where
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    let mut points = Vec::new();
+    for (idx, line) in r.lines().enumerate().skip(usize::from(header)) {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        points.push(parse_point(line.split(',').map(str::trim), idx + 1, true)?);
+    }
+    Ok(PointCloud(points))
+}
+
+/// Read a point cloud with whitespace-separated coordinates, one point per line. Empty lines, and
+/// lines starting with `#`, are ignored.
+pub fn read_xyz_point_cloud<T: Float + FromStr + Display, R: BufRead, const N: usize>(
+    r: R,
+) -> io::Result<PointCloud<T, N>>
+where
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    let mut points = Vec::new();
+    for (idx, line) in r.lines().enumerate() {
+        let line = line?;
+        if is_blank_or_comment(&line) {
+            continue;
+        }
+        points.push(parse_point(line.split_whitespace(), idx + 1, true)?);
+    }
+    Ok(PointCloud(points))
+}
+
+/// Read the vertices of an OFF file. The dimension of the file, 3 for `OFF`, 4 for `4OFF`, or the
+/// one given after the header for `nOFF`, must be `N`. Other values of the vertices, like the
+/// colors of `COFF` files, are ignored.
+pub fn read_off_point_cloud<T: Float + FromStr + Display, R: BufRead, const N: usize>(
+    r: R,
+) -> io::Result<PointCloud<T, N>>
+where
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    let mut next_line = off_lines(r);
+    let (dimension, n_vertices) = read_off_header(&mut next_line)?;
+    if dimension != N {
+        return Err(invalid_data(format!(
+            "The OFF file has dimension {}, but points of dimension {} were expected",
+            dimension, N
+        )));
+    }
+
+    // The number of vertices comes from the file, so the vector grows as the vertices are read.
+    let mut points = Vec::new();
+    for _ in 0..n_vertices {
+        let (line_number, line) = next_line()?;
+        points.push(parse_point(line.split_whitespace(), line_number, false)?);
+    }
+    Ok(PointCloud(points))
+}
+
+/// Read the vertices of an ASCII PLY file. The coordinates are the properties `x`, `y`, and `z`
+/// of the `vertex` element, in that order, and there must be exactly `N` of them. Other
+/// properties are ignored.
+pub fn read_ply_point_cloud<T: Float + FromStr + Display, R: BufRead, const N: usize>(
+    r: R,
+) -> io::Result<PointCloud<T, N>>
+where
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    let mut next_line = ply_lines(r);
+    let header = read_ply_header(&mut next_line)?;
+    if header.coordinates.len() != N {
+        return Err(invalid_data(format!(
+            "The PLY vertices have {} coordinates, but points of dimension {} were expected",
+            header.coordinates.len(),
+            N
+        )));
+    }
+
+    let mut points = Vec::new();
+    for (name, count) in header.elements.iter() {
+        for _ in 0..*count {
+            let (line_number, line) = next_line()?;
+            if name != "vertex" {
+                continue;
+            }
+            let values: Vec<&str> = line.split_whitespace().collect();
+            if values.len() != header.n_vertex_properties {
+                return Err(wrong_number_of_values(
+                    line_number,
+                    header.n_vertex_properties,
+                    values.len(),
+                ));
+            }
+            let coords = header.coordinates.iter().map(|&position| values[position]);
+            points.push(parse_point(coords, line_number, true)?);
+        }
+    }
+    Ok(PointCloud(points))
+}
+
+/// Returns the dimension of the points of a point cloud in the given format: the number of
+/// values of the first point of CSV and XYZ files, and the dimension given by the header of OFF
+/// and PLY files. Useful to pick the dimension `N` of the point cloud to read.
+pub fn read_point_cloud_dimension<R: BufRead>(r: R, format: PointCloudFormat) -> io::Result<usize> {
+    match format {
+        PointCloudFormat::Csv { header } => {
+            for line in r.lines().skip(usize::from(header)) {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    return Ok(line.split(',').count());
+                }
+            }
+            Err(invalid_data("The CSV file has no points"))
+        }
+        PointCloudFormat::Xyz => {
+            for line in r.lines() {
+                let line = line?;
+                if !is_blank_or_comment(&line) {
+                    return Ok(line.split_whitespace().count());
+                }
+            }
+            Err(invalid_data("The XYZ file has no points"))
+        }
+        PointCloudFormat::Off => Ok(read_off_header(&mut off_lines(r))?.0),
+        PointCloudFormat::Ply => Ok(read_ply_header(&mut ply_lines(r))?.coordinates.len()),
+    }
+}
+
+/// The numbered lines of an OFF file, skipping blank lines and comments.
+fn off_lines<R: BufRead>(r: R) -> impl FnMut() -> io::Result<(usize, String)> {
+    let mut lines = r
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| line.map(|l| (idx + 1, l)))
+        .filter(|line| !matches!(line, Ok((_, l)) if is_blank_or_comment(l)));
+    move || {
+        lines
+            .next()
+            .unwrap_or_else(|| Err(invalid_data("Unexpected end of the OFF file")))
+    }
+}
+
+/// Reads the header of an OFF file, and returns the dimension of its vertices and their number.
+fn read_off_header<F: FnMut() -> io::Result<(usize, String)>>(
+    next_line: &mut F,
+) -> io::Result<(usize, usize)> {
+    let (_, header_line) = next_line()?;
+    let mut tokens: Vec<String> = header_line.split_whitespace().map(String::from).collect();
+    let header = if tokens.is_empty() {
+        String::new()
+    } else {
+        tokens.remove(0)
+    };
+    let prefix = header
+        .strip_suffix("OFF")
+        .ok_or_else(|| invalid_data(format!("Invalid OFF header: {}", header)))?;
+    let dimension = if prefix.contains('n') {
+        if tokens.is_empty() {
+            let (_, line) = next_line()?;
+            tokens = line.split_whitespace().map(String::from).collect();
+        }
+        let n: usize = parse(&tokens.remove(0))?;
+        n + usize::from(prefix.contains('4'))
+    } else if prefix.contains('4') {
+        4
+    } else {
+        3
+    };
+    if tokens.is_empty() {
+        let (_, line) = next_line()?;
+        tokens = line.split_whitespace().map(String::from).collect();
+    }
+    let n_vertices: usize = parse_next(&mut tokens.iter().map(String::as_str))?;
+    Ok((dimension, n_vertices))
+}
+
+/// The numbered lines of a PLY file.
+fn ply_lines<R: BufRead>(r: R) -> impl FnMut() -> io::Result<(usize, String)> {
+    let mut lines = r.lines().enumerate();
+    move || match lines.next() {
+        Some((idx, line)) => line.map(|l| (idx + 1, l)),
+        None => Err(invalid_data("Unexpected end of the PLY file")),
+    }
+}
+
+/// The header of a PLY file.
+struct PlyHeader {
+    /// The elements of the file, with their number of lines.
+    elements: Vec<(String, usize)>,
+    /// The position of the `x`, `y` and `z` coordinates among the properties of the vertices.
+    coordinates: Vec<usize>,
+    /// The number of properties of the vertices.
+    n_vertex_properties: usize,
+}
+
+fn read_ply_header<F: FnMut() -> io::Result<(usize, String)>>(
+    next_line: &mut F,
+) -> io::Result<PlyHeader> {
+    if next_line()?.1.trim() != "ply" {
+        return Err(invalid_data("Invalid PLY header"));
+    }
+    let mut elements: Vec<(String, usize)> = Vec::new();
+    let mut coordinates: Vec<(usize, &str)> = Vec::new();
+    let mut n_vertex_properties = 0;
+    loop {
+        let (line_number, line) = next_line()?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["end_header"] => break,
+            ["format", "ascii", _] => {}
+            ["format", format, _] => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("Only ASCII PLY files are supported, found {}", format),
+                ))
+            }
+            ["comment", ..] | ["obj_info", ..] => {}
+            ["element", name, count] => elements.push((name.to_string(), parse(count)?)),
+            ["property", "list", ..] if is_last_element(&elements, "vertex") => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "List properties of vertices are not supported",
+                ))
+            }
+            ["property", _, name] => {
+                if is_last_element(&elements, "vertex") {
+                    if let Some(&coordinate) = ["x", "y", "z"].iter().find(|&c| c == name) {
+                        coordinates.push((n_vertex_properties, coordinate));
+                    }
+                    n_vertex_properties += 1;
+                }
+            }
+            ["property", "list", _, _, _] => {}
+            _ => {
+                return Err(invalid_data(format!(
+                    "Invalid line {} in the PLY header: {}",
+                    line_number, line
+                )))
+            }
+        }
+    }
+    let expected = ["x", "y", "z"];
+    if coordinates
+        .iter()
+        .zip(expected.iter())
+        .any(|((_, c), e)| c != e)
+    {
+        return Err(invalid_data(format!(
+            "The PLY vertices have coordinates {:?}, instead of x, y and z in order",
+            coordinates.iter().map(|(_, c)| c).collect::<Vec<_>>(),
+        )));
+    }
+    Ok(PlyHeader {
+        elements,
+        coordinates: coordinates
+            .into_iter()
+            .map(|(position, _)| position)
+            .collect(),
+        n_vertex_properties,
+    })
+}
+
+/// Parses a point from the given values. If `exact` is true, there must be exactly `N` of them,
+/// otherwise at least `N`, and the rest are ignored.
+fn parse_point<'a, T: Float + FromStr, I: Iterator<Item = &'a str>, const N: usize>(
+    mut values: I,
+    line_number: usize,
+    exact: bool,
+) -> io::Result<Point<T, N>>
+where
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+{
+    let mut coords = [T::zero(); N];
+    for i in 0..N {
+        let value = values
+            .next()
+            .ok_or_else(|| wrong_number_of_values(line_number, N, i))?;
+        coords[i] = parse(value).map_err(|err| {
+            invalid_data(format!(
+                "Invalid coordinate at line {}: {}",
+                line_number, err
+            ))
+        })?;
+    }
+    if exact {
+        let extra = values.count();
+        if extra > 0 {
+            return Err(wrong_number_of_values(line_number, N, N + extra));
+        }
+    }
+    Ok(Point(coords))
+}
+
+fn is_last_element(elements: &[(String, usize)], name: &str) -> bool {
+    matches!(elements.last(), Some((last, _)) if last == name)
+}
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+fn wrong_number_of_values(line_number: usize, expected: usize, found: usize) -> io::Error {
+    invalid_data(format!(
+        "Expected {} values at line {}, found {}",
+        expected, line_number, found
+    ))
+}
+
 fn remove_whitespace(s: &mut String) {
     s.retain(|c| !c.is_whitespace());
 }
@@ -39,7 +408,10 @@ fn remove_whitespace(s: &mut String) {
 mod tests {
     use std::io::BufReader;
 
-    use crate::points::input::read_point_cloud;
+    use crate::points::input::{
+        read_csv_point_cloud, read_off_point_cloud, read_ply_point_cloud, read_point_cloud,
+        read_point_cloud_dimension, read_xyz_point_cloud, PointCloudFormat,
+    };
     use crate::points::{Point, PointCloud};
 
     #[test]
@@ -49,4 +421,66 @@ mod tests {
         let points: PointCloud<f64, 2> = read_point_cloud(BufReader::new(s.as_bytes())).unwrap();
         assert_eq!(points.0, [Point([1.57, 2.40]), Point([1.21, -2.7])]);
     }
+
+    #[test]
+    fn read_point_cloud_formats() {
+        let expected = [Point([1.5, 2.0, -1.0]), Point([0.0, 0.25, 3.0])];
+
+        let csv = "x,y,z\n1.5, 2, -1\n\n0,0.25,3\n";
+        let csv_points: PointCloud<f64, 3> = read_csv_point_cloud(csv.as_bytes(), true).unwrap();
+        assert_eq!(csv_points.0, expected);
+
+        let xyz = "# A comment\n1.5 2 -1\n0\t0.25 3\n";
+        let xyz_points: PointCloud<f64, 3> = read_xyz_point_cloud(xyz.as_bytes()).unwrap();
+        assert_eq!(xyz_points.0, expected);
+
+        let off = "OFF\n# vertices faces edges\n2 1 0\n1.5 2 -1\n0 0.25 3\n3 0 1 1\n";
+        let off_points: PointCloud<f64, 3> = read_off_point_cloud(off.as_bytes()).unwrap();
+        assert_eq!(off_points.0, expected);
+
+        let ply = "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\n\
+                   property float y\nproperty float z\nproperty uchar red\n\
+                   element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                   1.5 2 -1 255\n0 0.25 3 0\n3 0 1 1\n";
+        let ply_points: PointCloud<f64, 3> = read_ply_point_cloud(ply.as_bytes()).unwrap();
+        assert_eq!(ply_points.0, expected);
+
+        for (content, format) in [
+            (csv, PointCloudFormat::Csv { header: true }),
+            (xyz, PointCloudFormat::Xyz),
+            (off, PointCloudFormat::Off),
+            (ply, PointCloudFormat::Ply),
+        ] {
+            assert_eq!(
+                read_point_cloud_dimension(content.as_bytes(), format).unwrap(),
+                3
+            );
+        }
+    }
+
+    #[test]
+    fn read_point_cloud_wrong_dimension() {
+        let xyz_result: std::io::Result<PointCloud<f64, 2>> =
+            read_xyz_point_cloud("1 2\n3 4 5\n".as_bytes());
+        assert_eq!(
+            xyz_result.err().unwrap().to_string(),
+            "Expected 2 values at line 2, found 3"
+        );
+
+        let csv_result: std::io::Result<PointCloud<f64, 2>> =
+            read_csv_point_cloud("1,a\n".as_bytes(), false);
+        assert!(csv_result.is_err());
+
+        let off_result: std::io::Result<PointCloud<f64, 2>> =
+            read_off_point_cloud("OFF\n1 0 0\n1 2 3\n".as_bytes());
+        assert!(off_result.is_err());
+
+        let huge_off_result: std::io::Result<PointCloud<f64, 3>> =
+            read_off_point_cloud("OFF\n18446744073709551615 0 0\n1 2 3\n".as_bytes());
+        assert!(huge_off_result.is_err());
+
+        let nd_off = "nOFF\n2\n1 0 0\n1 2\n";
+        let nd_points: PointCloud<f64, 2> = read_off_point_cloud(nd_off.as_bytes()).unwrap();
+        assert_eq!(nd_points.0, [Point([1.0, 2.0])]);
+    }
 }
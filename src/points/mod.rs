@@ -8,7 +8,9 @@ use std::fmt::Formatter;
 use crate::distance_matrix::DistanceMatrix;
 
 pub mod input;
+pub mod neighbours;
 pub mod output;
+pub mod transform;
 
 /// A point in `R^N`.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -24,6 +26,39 @@ impl<T: Float, const N: usize> Point<T, N> {
         d.sqrt()
     }
 
+    /// Computes the Manhattan, or L1, distance between the given points.
+    pub fn manhattan_distance(&self, other: &Point<T, N>) -> T {
+        let mut d = T::zero();
+        for i in 0..N {
+            d = d + (self.0[i] - other.0[i]).abs();
+        }
+        d
+    }
+
+    /// Computes the Chebyshev, or L-infinity, distance between the given points.
+    pub fn chebyshev_distance(&self, other: &Point<T, N>) -> T {
+        let mut d = T::zero();
+        for i in 0..N {
+            d = d.max((self.0[i] - other.0[i]).abs());
+        }
+        d
+    }
+
+    /// Computes the cosine distance between the given points, that is, one minus the cosine of
+    /// the angle between them. If one of the points is the origin, the distance is one.
+    pub fn cosine_distance(&self, other: &Point<T, N>) -> T {
+        let norms = self.norm() * other.norm();
+        if norms.is_zero() {
+            return T::one();
+        }
+        let mut dot = T::zero();
+        for i in 0..N {
+            dot = dot + self.0[i] * other.0[i];
+        }
+        // Rounding errors can make the cosine slightly greater than one.
+        (T::one() - dot / norms).max(T::zero())
+    }
+
     /// Computes the norm of the point.
     pub fn norm(&self) -> T {
         let mut d = T::zero();
@@ -89,7 +124,33 @@ where
     }
 }
 
-/// A collection of points.
+/// A distance function between points, used to build distance matrices from point clouds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Metric {
+    /// See [Point::euclidean_distance].
+    #[default]
+    Euclidean,
+    /// See [Point::manhattan_distance].
+    Manhattan,
+    /// See [Point::chebyshev_distance].
+    Chebyshev,
+    /// See [Point::cosine_distance].
+    Cosine,
+}
+
+impl Metric {
+    /// Computes the distance between the given points.
+    pub fn distance<T: Float, const N: usize>(&self, p: &Point<T, N>, q: &Point<T, N>) -> T {
+        match self {
+            Metric::Euclidean => p.euclidean_distance(q),
+            Metric::Manhattan => p.manhattan_distance(q),
+            Metric::Chebyshev => p.chebyshev_distance(q),
+            Metric::Cosine => p.cosine_distance(q),
+        }
+    }
+}
+
+/// A collection of points. See [WeightedPointCloud] for points with weights.
 pub struct PointCloud<T: Float, const N: usize>(pub Vec<Point<T, N>>);
 
 impl<T: Float, const N: usize> Default for PointCloud<T, N> {
@@ -112,14 +173,44 @@ impl<T: Float, const N: usize> PointCloud<T, N> {
     /// Return the distance matrix of the point cloud, where the order is the order in which the
     /// points where added.
     pub fn distance_matrix(&self) -> DistanceMatrix<T> {
-        let n = self.len();
-        let mut matrix = DistanceMatrix::new(n);
-        for u in 0..n {
-            for v in (u + 1)..n {
-                matrix.set(u, v, self.0[u].euclidean_distance(&self.0[v]))
-            }
-        }
-        matrix
+        self.distance_matrix_with_metric(Metric::Euclidean)
+    }
+
+    /// As [PointCloud::distance_matrix], but the distances are computed with the given metric.
+    pub fn distance_matrix_with_metric(&self, metric: Metric) -> DistanceMatrix<T> {
+        let rows = self
+            .0
+            .iter()
+            .enumerate()
+            .map(|(u, p)| self.distance_row(metric, u, p))
+            .collect();
+        DistanceMatrix::from_lower_triangular_rows(rows)
+    }
+
+    /// As [PointCloud::distance_matrix_with_metric], but the rows of the distance matrix are
+    /// computed in parallel.
+    #[cfg(feature = "parallel")]
+    pub fn par_distance_matrix_with_metric(&self, metric: Metric) -> DistanceMatrix<T>
+    where
+        T: Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        let rows = self
+            .0
+            .par_iter()
+            .enumerate()
+            .map(|(u, p)| self.distance_row(metric, u, p))
+            .collect();
+        DistanceMatrix::from_lower_triangular_rows(rows)
+    }
+
+    /// The distances from the point `p`, at index `u`, to the points before it, followed by the
+    /// distance from `p` to itself.
+    fn distance_row(&self, metric: Metric, u: usize, p: &Point<T, N>) -> Vec<T> {
+        let mut row: Vec<T> = self.0[..u].iter().map(|q| metric.distance(p, q)).collect();
+        row.push(T::zero());
+        row
     }
 
     /// Returns the number of points in the point cloud.
@@ -133,12 +224,151 @@ impl<T: Float, const N: usize> PointCloud<T, N> {
     }
 }
 
+impl<T: Float, const N: usize> From<Vec<Point<T, N>>> for PointCloud<T, N> {
+    fn from(points: Vec<Point<T, N>>) -> Self {
+        Self(points)
+    }
+}
+
 impl<const N: usize> From<PointCloud<f64, N>> for PointCloud<OrderedFloat<f64>, N> {
     fn from(points: PointCloud<f64, N>) -> Self {
         let mut result: PointCloud<OrderedFloat<f64>, N> = PointCloud::new();
         for p in points.0.into_iter() {
-            result.push_point(p.0.try_into().unwrap());
+            result.push_point(p.0.into());
         }
         result
     }
 }
+
+/// A collection of points with a weight for each of them. Weights count points as many times, for
+/// example to undo a subsampling or to weight the points by importance, see
+/// [DensityEstimator::estimate_weighted_points](crate::distance_matrix::density_estimation::DensityEstimator::estimate_weighted_points).
+///
+/// The points and the weights are only modified together, so that there is always one weight per
+/// point.
+pub struct WeightedPointCloud<T: Float, const N: usize> {
+    points: PointCloud<T, N>,
+    weights: Vec<T>,
+}
+
+impl<T: Float, const N: usize> Default for WeightedPointCloud<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float, const N: usize> WeightedPointCloud<T, N> {
+    /// Create a new empty weighted point cloud.
+    pub fn new() -> Self {
+        Self {
+            points: PointCloud::new(),
+            weights: Vec::new(),
+        }
+    }
+
+    /// The given points with the given weights, in the order of the points.
+    ///
+    /// Panics: if the number of weights is not the number of points.
+    pub fn with_weights(points: PointCloud<T, N>, weights: Vec<T>) -> Self {
+        assert_eq!(
+            weights.len(),
+            points.len(),
+            "There must be one weight per point"
+        );
+        Self { points, weights }
+    }
+
+    /// Add a new point with the given weight.
+    pub fn push_point(&mut self, p: Point<T, N>, weight: T) {
+        self.points.push_point(p);
+        self.weights.push(weight);
+    }
+
+    /// The points, without their weights.
+    pub fn points(&self) -> &PointCloud<T, N> {
+        &self.points
+    }
+
+    /// The weights of the points, in the order of the points.
+    pub fn weights(&self) -> &[T] {
+        &self.weights
+    }
+
+    /// The weight of the `u`-th point.
+    pub fn weight(&self, u: usize) -> T {
+        self.weights[u]
+    }
+
+    /// Returns the points and their weights.
+    pub fn into_parts(self) -> (PointCloud<T, N>, Vec<T>) {
+        (self.points, self.weights)
+    }
+
+    /// Returns the number of points in the point cloud.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns whether the point cloud has no points.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+impl<T: Float, const N: usize> From<PointCloud<T, N>> for WeightedPointCloud<T, N> {
+    /// The points, each with weight one.
+    fn from(points: PointCloud<T, N>) -> Self {
+        let weights = vec![T::one(); points.len()];
+        Self { points, weights }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::points::{Metric, Point, PointCloud};
+
+    #[test]
+    fn metrics_happy_case() {
+        let p: Point<f64, 2> = Point([0., 0.]);
+        let q: Point<f64, 2> = Point([3., -4.]);
+        assert_eq!(Metric::Euclidean.distance(&p, &q), 5.);
+        assert_eq!(Metric::Manhattan.distance(&p, &q), 7.);
+        assert_eq!(Metric::Chebyshev.distance(&p, &q), 4.);
+        assert_eq!(Metric::Cosine.distance(&p, &q), 1.);
+
+        let r: Point<f64, 2> = Point([-6., 8.]);
+        assert_eq!(Metric::Cosine.distance(&q, &r), 2.);
+        assert_eq!(Metric::Cosine.distance(&q, &q), 0.);
+    }
+
+    #[test]
+    fn distance_matrix_with_metric() {
+        let points = PointCloud::from(vec![Point([0., 0.]), Point([1., 1.]), Point([3., 0.])]);
+        let matrix = points.distance_matrix_with_metric(Metric::Manhattan);
+        assert_eq!(matrix.len(), 3);
+        assert_eq!(*matrix.get(0, 1), 2.);
+        assert_eq!(*matrix.get(2, 1), 3.);
+        assert_eq!(*matrix.get(0, 2), 3.);
+        assert_eq!(*matrix.get(1, 1), 0.);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_distance_matrix_agrees() {
+        let points: PointCloud<f64, 3> = crate::datasets::sampling::sample_torus(100);
+        for metric in [
+            Metric::Euclidean,
+            Metric::Manhattan,
+            Metric::Chebyshev,
+            Metric::Cosine,
+        ] {
+            let serial = points.distance_matrix_with_metric(metric);
+            let parallel = points.par_distance_matrix_with_metric(metric);
+            for u in 0..points.len() {
+                for v in 0..u {
+                    assert_eq!(serial.get(u, v), parallel.get(u, v));
+                }
+            }
+        }
+    }
+}
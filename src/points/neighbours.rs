@@ -0,0 +1,463 @@
+//! Sparse neighbourhood graphs of point clouds.
+//!
+//! Building a filtered graph from the distance matrix of a point cloud takes quadratic time and
+//! memory, which is infeasible for large point clouds. The functions in this module only look at
+//! pairs of points that are close to each other: the points are bucketed in a grid, and only
+//! nearby cells of the grid are searched. No edge of the resulting graphs is longer than the given
+//! threshold.
+//!
+//! The grid search relies on the distance being at least the Chebyshev distance, which is the
+//! case for the Euclidean, Manhattan, and Chebyshev metrics. For the cosine metric all pairs of
+//! points are checked, as they are if a coordinate of a point is not finite or too large for the
+//! index of its cell to fit in an `i64`.
+use num::Float;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+use crate::points::{Metric, Point, PointCloud};
+use crate::OneCriticalGrade;
+
+/// The neighbourhood graph to build. See [neighbourhood_graph].
+#[derive(Debug, Clone, Copy)]
+pub enum NeighbourhoodGraph<T> {
+    /// Connect all pairs of points at distance at most the given threshold.
+    Threshold(T),
+    /// Connect each point to its `k` nearest neighbours, as long as they are at distance at most
+    /// `threshold`. An edge is added if either of its endpoints is one of the `k` nearest
+    /// neighbours of the other.
+    KNearest { k: usize, threshold: T },
+}
+
+/// Builds the given neighbourhood graph of the points, where each edge is graded by the distance
+/// between its endpoints. The edges `BareEdge(u, v)` are sorted, and satisfy `u < v`.
+pub fn neighbourhood_graph<T: Float, const N: usize>(
+    points: &PointCloud<T, N>,
+    graph: NeighbourhoodGraph<T>,
+    metric: Metric,
+) -> EdgeList<FilteredEdge<OneCriticalGrade<T, 1>>> {
+    let mut edges: Vec<FilteredEdge<OneCriticalGrade<T, 1>>> = match graph {
+        NeighbourhoodGraph::Threshold(threshold) => threshold_edges(points, threshold, metric),
+        NeighbourhoodGraph::KNearest { k, threshold } => {
+            k_nearest_edges(points, k, threshold, metric)
+        }
+    };
+    edges.sort_by_key(|e| e.edge);
+    let mut edge_list = EdgeList::new(points.len());
+    for edge in edges {
+        edge_list.add_edge(edge);
+    }
+    edge_list
+}
+
+/// Builds a bifiltered graph from a neighbourhood graph of the points, as done for the datasets
+/// in [crate::datasets]: the first parameter of an edge is the maximum codensity of its
+/// endpoints, and the second one is its length.
+///
+/// The density is estimated with a ball kernel of the given bandwidth, which only needs the
+/// pairs of points at distance at most the bandwidth, so it is also computed with a sparse
+/// neighbourhood graph.
+pub fn density_neighbourhood_graph<T: Float, const N: usize>(
+    points: &PointCloud<T, N>,
+    graph: NeighbourhoodGraph<T>,
+    metric: Metric,
+    bandwidth: T,
+) -> EdgeList<FilteredEdge<OneCriticalGrade<T, 2>>> {
+    let mut neighbour_counts: Vec<usize> = vec![0; points.len()];
+    let mut total: usize = 0;
+    for edge in threshold_edges(points, bandwidth, metric) {
+        let BareEdge(u, v) = edge.edge;
+        neighbour_counts[u] += 1;
+        neighbour_counts[v] += 1;
+        total += 2;
+    }
+    let total: T = to_float(total.max(1));
+    let codensities: Vec<T> = neighbour_counts
+        .into_iter()
+        .map(|count| T::one() - to_float::<T>(count) / total)
+        .collect();
+
+    let edges = neighbourhood_graph(points, graph, metric);
+    let mut density_edges = EdgeList::new(points.len());
+    for edge in edges.edge_iter() {
+        let BareEdge(u, v) = edge.edge;
+        density_edges.add_edge(FilteredEdge {
+            grade: OneCriticalGrade([codensities[u].max(codensities[v]), edge.grade.0[0]]),
+            edge: edge.edge,
+        });
+    }
+    density_edges
+}
+
+fn threshold_edges<T: Float, const N: usize>(
+    points: &PointCloud<T, N>,
+    threshold: T,
+    metric: Metric,
+) -> Vec<FilteredEdge<OneCriticalGrade<T, 1>>> {
+    let cell_size = if threshold > T::zero() {
+        threshold
+    } else {
+        T::one()
+    };
+    let grid = Grid::new(points, cell_size, metric);
+    let max_ring = grid.rings_within(threshold);
+
+    let mut edges = Vec::new();
+    for (u, p) in points.0.iter().enumerate() {
+        for ring in 0..=max_ring {
+            for v in grid.ring(u, ring) {
+                if u < v {
+                    let distance = metric.distance(p, &points.0[v]);
+                    if distance <= threshold {
+                        edges.push(graded_edge(u, v, distance));
+                    }
+                }
+            }
+        }
+    }
+    edges
+}
+
+fn k_nearest_edges<T: Float, const N: usize>(
+    points: &PointCloud<T, N>,
+    k: usize,
+    threshold: T,
+    metric: Metric,
+) -> Vec<FilteredEdge<OneCriticalGrade<T, 1>>> {
+    if k == 0 || points.is_empty() {
+        return Vec::new();
+    }
+    let grid = Grid::new(points, grid_cell_size(points, k), metric);
+    let max_ring = grid.rings_within(threshold);
+
+    let mut seen: FxHashSet<BareEdge> = FxHashSet::default();
+    let mut edges = Vec::new();
+    for (u, p) in points.0.iter().enumerate() {
+        // The k nearest neighbours found so far, sorted by distance.
+        let mut nearest: Vec<(T, usize)> = Vec::with_capacity(k + 1);
+        for ring in 0..=max_ring {
+            for v in grid.ring(u, ring) {
+                if u == v {
+                    continue;
+                }
+                let distance = metric.distance(p, &points.0[v]);
+                if distance > threshold {
+                    continue;
+                }
+                if nearest.len() == k && nearest[k - 1].0 <= distance {
+                    continue;
+                }
+                let position = nearest.partition_point(|(d, _)| *d <= distance);
+                nearest.insert(position, (distance, v));
+                nearest.truncate(k);
+            }
+            // Points in the following rings are further than those in the current ring.
+            if nearest.len() == k && nearest[k - 1].0 <= grid.distance_beyond_ring(ring) {
+                break;
+            }
+        }
+
+        for (distance, v) in nearest {
+            let edge = BareEdge(u.min(v), u.max(v));
+            if seen.insert(edge) {
+                edges.push(graded_edge(edge.0, edge.1, distance));
+            }
+        }
+    }
+    edges
+}
+
+fn graded_edge<T>(u: usize, v: usize, distance: T) -> FilteredEdge<OneCriticalGrade<T, 1>> {
+    FilteredEdge {
+        grade: OneCriticalGrade([distance]),
+        edge: BareEdge(u, v),
+    }
+}
+
+/// A cell size such that the cells have around `k` points each, if the points were uniformly
+/// distributed in their bounding box.
+fn grid_cell_size<T: Float, const N: usize>(points: &PointCloud<T, N>, k: usize) -> T {
+    let mut volume = T::one();
+    for i in 0..N {
+        let (min, max) = points
+            .0
+            .iter()
+            .fold((T::infinity(), T::neg_infinity()), |(min, max), p| {
+                (min.min(p.0[i]), max.max(p.0[i]))
+            });
+        if max > min {
+            volume = volume * (max - min);
+        }
+    }
+    let points_per_cell: T = to_float::<T>(k) / to_float(points.len());
+    let cell_size = (volume * points_per_cell).powf(T::one() / to_float(N));
+    if cell_size > T::zero() && cell_size.is_finite() {
+        cell_size
+    } else {
+        T::one()
+    }
+}
+
+fn to_float<T: Float>(x: usize) -> T {
+    T::from(x).expect("a count is representable as a float")
+}
+
+/// The points of a point cloud bucketed in a grid of cubes of the same size.
+struct Grid<'a, T: Float, const N: usize> {
+    points: &'a PointCloud<T, N>,
+    cell_size: T,
+    // The cell of each point.
+    point_cells: Vec<[i64; N]>,
+    cells: FxHashMap<[i64; N], Vec<usize>>,
+    min_cell: [i64; N],
+    max_cell: [i64; N],
+    // Whether the distance is at least the Chebyshev distance, and the cells of the points are
+    // representable, so that distant cells can be skipped. If not, all points are in the ring 0.
+    bounded_by_cells: bool,
+}
+
+impl<'a, T: Float, const N: usize> Grid<'a, T, N> {
+    fn new(points: &'a PointCloud<T, N>, cell_size: T, metric: Metric) -> Self {
+        let point_cells: Option<Vec<[i64; N]>> = if metric == Metric::Cosine {
+            None
+        } else {
+            points.0.iter().map(|p| cell_of(p, cell_size)).collect()
+        };
+        let bounded_by_cells = point_cells.is_some();
+        let point_cells = point_cells.unwrap_or_else(|| vec![[0; N]; points.len()]);
+        let mut cells: FxHashMap<[i64; N], Vec<usize>> = FxHashMap::default();
+        let mut min_cell = [i64::MAX; N];
+        let mut max_cell = [i64::MIN; N];
+        for (u, cell) in point_cells.iter().enumerate() {
+            for i in 0..N {
+                min_cell[i] = min_cell[i].min(cell[i]);
+                max_cell[i] = max_cell[i].max(cell[i]);
+            }
+            cells.entry(*cell).or_default().push(u);
+        }
+        Self {
+            points,
+            cell_size,
+            point_cells,
+            cells,
+            min_cell,
+            max_cell,
+            bounded_by_cells,
+        }
+    }
+
+    /// The number of rings around a cell that contain all points at distance at most `distance`
+    /// of a point of the cell, capped at the number of rings needed to cover the whole grid.
+    fn rings_within(&self, distance: T) -> i64 {
+        if !self.bounded_by_cells || self.points.is_empty() {
+            return 0;
+        }
+        let grid_extent = (0..N)
+            .map(|i| self.max_cell[i].saturating_sub(self.min_cell[i]))
+            .max()
+            .unwrap_or(0);
+        let rings = (distance / self.cell_size).ceil();
+        match rings.to_i64() {
+            Some(rings) => rings.min(grid_extent),
+            None => grid_extent,
+        }
+    }
+
+    /// A lower bound for the distance between a point and the points outside of the given ring
+    /// around its cell.
+    fn distance_beyond_ring(&self, ring: i64) -> T {
+        if self.bounded_by_cells {
+            T::from(ring).expect("a count is representable as a float") * self.cell_size
+        } else {
+            T::infinity()
+        }
+    }
+
+    /// The indices of the points in the cells at Chebyshev distance exactly `ring` from the cell
+    /// of the `u`-th point.
+    fn ring(&self, u: usize, ring: i64) -> impl Iterator<Item = usize> + '_ {
+        let center = self.point_cells[u];
+        let mut lower = [0; N];
+        let mut upper = [0; N];
+        for i in 0..N {
+            lower[i] = center[i].saturating_sub(ring).max(self.min_cell[i]);
+            upper[i] = center[i].saturating_add(ring).min(self.max_cell[i]);
+        }
+        CellBox::new(lower, upper)
+            .filter(move |cell| {
+                (0..N)
+                    .map(|i| cell[i].abs_diff(center[i]))
+                    .max()
+                    .unwrap_or(0)
+                    == ring.unsigned_abs()
+            })
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+}
+
+/// The cell of the point, or None if a coordinate is not finite or its cell does not fit in an
+/// `i64`.
+fn cell_of<T: Float, const N: usize>(p: &Point<T, N>, cell_size: T) -> Option<[i64; N]> {
+    let mut cell = [0; N];
+    for i in 0..N {
+        cell[i] = (p.0[i] / cell_size).floor().to_i64()?;
+    }
+    Some(cell)
+}
+
+/// Iterates over the cells of a box of cells, including both corners.
+struct CellBox<const N: usize> {
+    lower: [i64; N],
+    upper: [i64; N],
+    next: Option<[i64; N]>,
+}
+
+impl<const N: usize> CellBox<N> {
+    fn new(lower: [i64; N], upper: [i64; N]) -> Self {
+        let is_empty = (0..N).any(|i| lower[i] > upper[i]);
+        Self {
+            lower,
+            upper,
+            next: if is_empty { None } else { Some(lower) },
+        }
+    }
+}
+
+impl<const N: usize> Iterator for CellBox<N> {
+    type Item = [i64; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        let mut next = current;
+        self.next = None;
+        for i in 0..N {
+            if next[i] < self.upper[i] {
+                next[i] += 1;
+                self.next = Some(next);
+                break;
+            }
+            next[i] = self.lower[i];
+        }
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::datasets::sampling::sample_torus_with_rng;
+    use crate::edges::BareEdge;
+    use crate::points::neighbours::{
+        density_neighbourhood_graph, neighbourhood_graph, NeighbourhoodGraph,
+    };
+    use crate::points::{Metric, Point, PointCloud};
+
+    const METRICS: [Metric; 4] = [
+        Metric::Euclidean,
+        Metric::Manhattan,
+        Metric::Chebyshev,
+        Metric::Cosine,
+    ];
+
+    #[test]
+    fn threshold_graph_agrees_with_distance_matrix() {
+        let points = sample_torus_with_rng(200, &mut StdRng::seed_from_u64(1292));
+        for metric in METRICS {
+            let threshold = 0.7;
+            let graph =
+                neighbourhood_graph(&points, NeighbourhoodGraph::Threshold(threshold), metric);
+            let matrix = points.distance_matrix_with_metric(metric);
+
+            let mut expected = Vec::new();
+            for u in 0..points.len() {
+                for v in (u + 1)..points.len() {
+                    if *matrix.get(u, v) <= threshold {
+                        expected.push(BareEdge(u, v));
+                    }
+                }
+            }
+            let edges: Vec<BareEdge> = graph.edge_iter().map(|e| e.edge).collect();
+            assert_eq!(edges, expected);
+        }
+    }
+
+    #[test]
+    fn k_nearest_graph_agrees_with_distance_matrix() {
+        let points = sample_torus_with_rng(200, &mut StdRng::seed_from_u64(1292));
+        let k = 5;
+        for metric in METRICS {
+            for threshold in [0.3, f64::INFINITY] {
+                let graph = neighbourhood_graph(
+                    &points,
+                    NeighbourhoodGraph::KNearest { k, threshold },
+                    metric,
+                );
+                let matrix = points.distance_matrix_with_metric(metric);
+
+                let mut expected = Vec::new();
+                for u in 0..points.len() {
+                    let mut others: Vec<usize> = (0..points.len()).filter(|&v| v != u).collect();
+                    others
+                        .sort_by(|&a, &b| matrix.get(u, a).partial_cmp(matrix.get(u, b)).unwrap());
+                    for &v in others.iter().take(k) {
+                        if *matrix.get(u, v) <= threshold {
+                            expected.push(BareEdge(u.min(v), u.max(v)));
+                        }
+                    }
+                }
+                expected.sort();
+                expected.dedup();
+
+                let edges: Vec<BareEdge> = graph.edge_iter().map(|e| e.edge).collect();
+                assert_eq!(edges, expected);
+                assert!(graph.edge_iter().all(|e| e.grade.0[0] <= threshold));
+            }
+        }
+    }
+
+    #[test]
+    fn huge_coordinates_fall_back_to_all_pairs() {
+        let points: PointCloud<f64, 2> = PointCloud(vec![
+            Point([0., 0.]),
+            Point([1e300, 0.]),
+            Point([1e300, 0.5]),
+            Point([-1e300, f64::MAX]),
+            Point([0.5, 0.]),
+        ]);
+        for metric in [Metric::Euclidean, Metric::Chebyshev] {
+            let graph = neighbourhood_graph(&points, NeighbourhoodGraph::Threshold(1.), metric);
+            let edges: Vec<BareEdge> = graph.edge_iter().map(|e| e.edge).collect();
+            assert_eq!(edges, vec![BareEdge(0, 4), BareEdge(1, 2)]);
+
+            let nearest = neighbourhood_graph(
+                &points,
+                NeighbourhoodGraph::KNearest {
+                    k: 1,
+                    threshold: f64::INFINITY,
+                },
+                metric,
+            );
+            assert!(nearest.edge_iter().any(|e| e.edge == BareEdge(1, 2)));
+        }
+    }
+
+    #[test]
+    fn density_graph_grades() {
+        let points = sample_torus_with_rng(100, &mut StdRng::seed_from_u64(1292));
+        let graph = NeighbourhoodGraph::KNearest {
+            k: 8,
+            threshold: 1.0,
+        };
+        let single = neighbourhood_graph(&points, graph, Metric::Euclidean);
+        let bifiltered = density_neighbourhood_graph(&points, graph, Metric::Euclidean, 0.5);
+        assert_eq!(single.len(), bifiltered.len());
+        for (e, f) in single.edge_iter().zip(bifiltered.edge_iter()) {
+            assert_eq!(e.edge, f.edge);
+            assert_eq!(e.grade.0[0], f.grade.0[1]);
+            assert!(f.grade.0[0] >= 0. && f.grade.0[0] <= 1.);
+        }
+    }
+}
@@ -1,6 +1,11 @@
 //! Utilities to write point clouds to disk.
+//!
+//! The writers match the readers of [crate::points::input], so that a point cloud written in a
+//! [PointCloudFormat] can be read back in the same format.
 use num::Float;
+use std::io;
 
+use crate::points::input::PointCloudFormat;
 use crate::points::PointCloud;
 
 /// Write the point cloud to the given writer.
@@ -21,17 +26,134 @@ pub fn write_point_cloud<T: Float + std::fmt::Display, W: std::io::Write, const
     Ok(())
 }
 
+/// Write the point cloud in the given format.
+pub fn write_point_cloud_in_format<T: Float + std::fmt::Display, W: io::Write, const N: usize>(
+    cloud: &PointCloud<T, N>,
+    format: PointCloudFormat,
+    w: &mut W,
+) -> io::Result<()> {
+    match format {
+        PointCloudFormat::Csv { header } => write_csv_point_cloud(cloud, header, w),
+        PointCloudFormat::Xyz => write_xyz_point_cloud(cloud, w),
+        PointCloudFormat::Off => write_off_point_cloud(cloud, w),
+        PointCloudFormat::Ply => write_ply_point_cloud(cloud, w),
+    }
+}
+
+/// Write the point cloud with comma-separated coordinates, one point per line. If `header` is
+/// true, the first line names the coordinates `x0`, `x1`, and so on.
+pub fn write_csv_point_cloud<T: Float + std::fmt::Display, W: io::Write, const N: usize>(
+    cloud: &PointCloud<T, N>,
+    header: bool,
+    w: &mut W,
+) -> io::Result<()> {
+    if header {
+        let names: Vec<String> = (0..N).map(|i| format!("x{}", i)).collect();
+        writeln!(w, "{}", names.join(","))?;
+    }
+    write_separated(cloud, ",", w)
+}
+
+/// Write the point cloud with space-separated coordinates, one point per line.
+pub fn write_xyz_point_cloud<T: Float + std::fmt::Display, W: io::Write, const N: usize>(
+    cloud: &PointCloud<T, N>,
+    w: &mut W,
+) -> io::Result<()> {
+    write_separated(cloud, " ", w)
+}
+
+/// Write the point cloud as the vertices of an OFF file without faces. The header is `OFF` for
+/// points of dimension 3, `4OFF` for dimension 4, and `nOFF` otherwise.
+pub fn write_off_point_cloud<T: Float + std::fmt::Display, W: io::Write, const N: usize>(
+    cloud: &PointCloud<T, N>,
+    w: &mut W,
+) -> io::Result<()> {
+    match N {
+        3 => writeln!(w, "OFF")?,
+        4 => writeln!(w, "4OFF")?,
+        _ => writeln!(w, "nOFF\n{}", N)?,
+    }
+    writeln!(w, "{} 0 0", cloud.len())?;
+    write_separated(cloud, " ", w)
+}
+
+/// Write the point cloud as the vertices of an ASCII PLY file, with properties `x`, `y` and `z`.
+/// Returns an error if the points have more than 3 coordinates.
+pub fn write_ply_point_cloud<T: Float + std::fmt::Display, W: io::Write, const N: usize>(
+    cloud: &PointCloud<T, N>,
+    w: &mut W,
+) -> io::Result<()> {
+    if N > 3 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("PLY files have at most 3 coordinates, found {}", N),
+        ));
+    }
+    writeln!(w, "ply\nformat ascii 1.0")?;
+    writeln!(w, "element vertex {}", cloud.len())?;
+    for name in ["x", "y", "z"].iter().take(N) {
+        writeln!(w, "property double {}", name)?;
+    }
+    writeln!(w, "end_header")?;
+    write_separated(cloud, " ", w)
+}
+
+fn write_separated<T: Float + std::fmt::Display, W: io::Write, const N: usize>(
+    cloud: &PointCloud<T, N>,
+    separator: &str,
+    w: &mut W,
+) -> io::Result<()> {
+    for p in cloud.0.iter() {
+        for (i, coord) in p.0.iter().enumerate() {
+            if i > 0 {
+                write!(w, "{}", separator)?;
+            }
+            write!(w, "{}", coord)?;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::points::output::write_point_cloud;
+    use crate::points::input::{read_point_cloud_in_format, PointCloudFormat};
+    use crate::points::output::{write_point_cloud, write_point_cloud_in_format};
     use crate::points::{Point, PointCloud};
 
     #[test]
     fn write_point_cloud_happy_case() {
-        let f: PointCloud<f64, 2> = PointCloud(vec![Point([2., 1.]), Point([0., -2.14])]);
+        let f: PointCloud<f64, 2> = PointCloud::from(vec![Point([2., 1.]), Point([0., -2.14])]);
         let mut buf = Vec::new();
         write_point_cloud(&f, &mut buf).unwrap();
         let out = String::from_utf8(buf).unwrap();
         assert_eq!(out, "2, 1\n0, -2.14\n")
     }
+
+    #[test]
+    fn write_and_read_every_format() {
+        let f: PointCloud<f64, 3> =
+            PointCloud::from(vec![Point([2., 1., 0.5]), Point([0., -2.14, 1e-3])]);
+        for format in [
+            PointCloudFormat::Csv { header: true },
+            PointCloudFormat::Csv { header: false },
+            PointCloudFormat::Xyz,
+            PointCloudFormat::Off,
+            PointCloudFormat::Ply,
+        ] {
+            let mut buf = Vec::new();
+            write_point_cloud_in_format(&f, format, &mut buf).unwrap();
+            let read: PointCloud<f64, 3> =
+                read_point_cloud_in_format(buf.as_slice(), format).unwrap();
+            assert_eq!(read.0, f.0, "Format {:?}", format);
+        }
+
+        let g: PointCloud<f64, 5> = PointCloud::from(vec![Point([1., 2., 3., 4., 5.])]);
+        let mut buf = Vec::new();
+        write_point_cloud_in_format(&g, PointCloudFormat::Off, &mut buf).unwrap();
+        let read: PointCloud<f64, 5> =
+            read_point_cloud_in_format(buf.as_slice(), PointCloudFormat::Off).unwrap();
+        assert_eq!(read.0, g.0);
+        assert!(write_point_cloud_in_format(&g, PointCloudFormat::Ply, &mut Vec::new()).is_err());
+    }
 }
@@ -0,0 +1,275 @@
+//! Transformations of point clouds, to build custom synthetic datasets from the samplers of
+//! [sampling](crate::datasets::sampling): centering, fitting into the unit box, scaling, Gaussian
+//! jitter, and rotations. They modify the points in place. As the samplers, the random
+//! transformations have a `_with_rng` variant that takes the random number generator to use, so
+//! that they can be reproduced from a seed.
+//!
+//! ```
+//! use filtration_domination::datasets::sampling::sample_torus_with_rng;
+//! use filtration_domination::points::transform::{
+//!     jitter_with_rng, random_rotation_with_rng, rotate, scale,
+//! };
+//! use rand::rngs::StdRng;
+//! use rand::SeedableRng;
+//!
+//! let mut rng = StdRng::seed_from_u64(1374);
+//! let mut torus = sample_torus_with_rng(100, &mut rng);
+//! scale(&mut torus, 2.);
+//! rotate(&mut torus, &random_rotation_with_rng(&mut rng));
+//! jitter_with_rng(&mut torus, 0.01, &mut rng);
+//! assert_eq!(torus.len(), 100);
+//! ```
+use std::f64::consts::PI;
+
+use num::Float;
+use rand::Rng;
+
+use crate::points::{Point, PointCloud};
+
+/// Translates the points so that their mean is the origin.
+pub fn center<T: Float, const N: usize>(cloud: &mut PointCloud<T, N>) {
+    if cloud.is_empty() {
+        return;
+    }
+    let mut mean = [T::zero(); N];
+    for p in cloud.0.iter() {
+        for (m, &x) in mean.iter_mut().zip(p.0.iter()) {
+            *m = *m + x;
+        }
+    }
+    let n = T::from(cloud.len()).unwrap_or_else(T::infinity);
+    for m in mean.iter_mut() {
+        *m = *m / n;
+    }
+    translate(cloud, Point(mean.map(|m| -m)));
+}
+
+/// Translates and scales the points, by the same factor in every coordinate, so that their
+/// bounding box is inside `[0, 1]^N`, touches the origin in every coordinate, and has a side of
+/// length one. If all the points are equal, they are moved to the origin.
+pub fn normalize_to_unit_box<T: Float, const N: usize>(cloud: &mut PointCloud<T, N>) {
+    if cloud.is_empty() {
+        return;
+    }
+    let mut min = [T::infinity(); N];
+    let mut max = [T::neg_infinity(); N];
+    for p in cloud.0.iter() {
+        for i in 0..N {
+            min[i] = min[i].min(p.0[i]);
+            max[i] = max[i].max(p.0[i]);
+        }
+    }
+    translate(cloud, Point(min.map(|m| -m)));
+    let side = (0..N).fold(T::zero(), |side, i| side.max(max[i] - min[i]));
+    if side > T::zero() {
+        scale(cloud, side.recip());
+    }
+}
+
+/// Adds the given vector to every point.
+pub fn translate<T: Float, const N: usize>(cloud: &mut PointCloud<T, N>, offset: Point<T, N>) {
+    for p in cloud.0.iter_mut() {
+        for (x, &o) in p.0.iter_mut().zip(offset.0.iter()) {
+            *x = *x + o;
+        }
+    }
+}
+
+/// Multiplies every coordinate by the given factor, which scales all the distances by its
+/// absolute value.
+pub fn scale<T: Float, const N: usize>(cloud: &mut PointCloud<T, N>, factor: T) {
+    for p in cloud.0.iter_mut() {
+        for x in p.0.iter_mut() {
+            *x = *x * factor;
+        }
+    }
+}
+
+/// Adds independent Gaussian noise, of mean zero and the given standard deviation, to each
+/// coordinate of every point.
+pub fn jitter<T: Float, const N: usize>(cloud: &mut PointCloud<T, N>, std_dev: T) {
+    jitter_with_rng(cloud, std_dev, &mut rand::thread_rng())
+}
+
+/// See [jitter]. Uses the given random number generator.
+pub fn jitter_with_rng<T: Float, R: Rng, const N: usize>(
+    cloud: &mut PointCloud<T, N>,
+    std_dev: T,
+    rng: &mut R,
+) {
+    if std_dev.is_zero() {
+        return;
+    }
+    for p in cloud.0.iter_mut() {
+        for x in p.0.iter_mut() {
+            *x = *x + std_dev * standard_normal(rng);
+        }
+    }
+}
+
+/// Multiplies every point, as a column vector, by the given matrix, given by its rows. With an
+/// orthogonal matrix, like those of [random_rotation], the distances are preserved.
+pub fn rotate<T: Float, const N: usize>(cloud: &mut PointCloud<T, N>, matrix: &[[T; N]; N]) {
+    for p in cloud.0.iter_mut() {
+        let original = p.0;
+        for (x, row) in p.0.iter_mut().zip(matrix.iter()) {
+            *x = row
+                .iter()
+                .zip(original.iter())
+                .fold(T::zero(), |sum, (&a, &b)| sum + a * b);
+        }
+    }
+}
+
+/// Rotates the points around the origin by a uniformly random rotation, see [random_rotation].
+pub fn rotate_randomly<T: Float, const N: usize>(cloud: &mut PointCloud<T, N>) {
+    rotate(cloud, &random_rotation())
+}
+
+/// See [rotate_randomly]. Uses the given random number generator.
+pub fn rotate_randomly_with_rng<T: Float, R: Rng, const N: usize>(
+    cloud: &mut PointCloud<T, N>,
+    rng: &mut R,
+) {
+    rotate(cloud, &random_rotation_with_rng(rng))
+}
+
+/// A uniformly random rotation of `R^N`, as an orthogonal matrix of determinant one, to use with
+/// [rotate].
+pub fn random_rotation<T: Float, const N: usize>() -> [[T; N]; N] {
+    random_rotation_with_rng(&mut rand::thread_rng())
+}
+
+/// See [random_rotation]. Uses the given random number generator.
+///
+/// The rows of a matrix of independent standard normal entries are orthonormalized with the
+/// Gram–Schmidt process, which gives a uniformly random orthogonal matrix, and then the sign of
+/// the first row is flipped if needed to make its determinant one.
+pub fn random_rotation_with_rng<T: Float, R: Rng, const N: usize>(rng: &mut R) -> [[T; N]; N] {
+    let mut matrix = [[T::zero(); N]; N];
+    let mut i = 0;
+    while i < N {
+        let mut row = [T::zero(); N];
+        for x in row.iter_mut() {
+            *x = standard_normal(rng);
+        }
+        for previous in matrix[..i].iter() {
+            let dot = dot(&row, previous);
+            for (x, &y) in row.iter_mut().zip(previous.iter()) {
+                *x = *x - dot * y;
+            }
+        }
+        let norm = dot(&row, &row).sqrt();
+        // Draw the row again if it is, numerically, in the span of the previous ones.
+        if norm > T::epsilon() {
+            matrix[i] = row.map(|x| x / norm);
+            i += 1;
+        }
+    }
+    if N > 0 && determinant(matrix) < T::zero() {
+        matrix[0] = matrix[0].map(|x| -x);
+    }
+    matrix
+}
+
+/// Samples from the standard normal distribution with the Box-Muller transform.
+pub(crate) fn standard_normal<T: Float, R: Rng>(rng: &mut R) -> T {
+    // Avoid taking the logarithm of zero.
+    let u1: f64 = 1. - rng.gen_range(0.0..1.0);
+    let u2: f64 = rng.gen_range(0.0..1.0);
+    T::from((-2. * u1.ln()).sqrt() * (2. * PI * u2).cos()).unwrap_or_else(T::zero)
+}
+
+fn dot<T: Float, const N: usize>(a: &[T; N], b: &[T; N]) -> T {
+    a.iter()
+        .zip(b.iter())
+        .fold(T::zero(), |sum, (&x, &y)| sum + x * y)
+}
+
+/// The determinant of the matrix, by Gaussian elimination with partial pivoting.
+fn determinant<T: Float, const N: usize>(mut matrix: [[T; N]; N]) -> T {
+    let mut det = T::one();
+    for col in 0..N {
+        let pivot = (col..N)
+            .max_by(|&a, &b| {
+                matrix[a][col]
+                    .abs()
+                    .partial_cmp(&matrix[b][col].abs())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(col);
+        if matrix[pivot][col].is_zero() {
+            return T::zero();
+        }
+        if pivot != col {
+            matrix.swap(pivot, col);
+            det = -det;
+        }
+        det = det * matrix[col][col];
+        for row in (col + 1)..N {
+            let factor = matrix[row][col] / matrix[col][col];
+            for k in col..N {
+                matrix[row][k] = matrix[row][k] - factor * matrix[col][k];
+            }
+        }
+    }
+    det
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::datasets::sampling::sample_random_points_with_rng;
+    use crate::points::transform::{
+        center, determinant, jitter_with_rng, normalize_to_unit_box, random_rotation_with_rng,
+        rotate, scale,
+    };
+    use crate::points::{Point, PointCloud};
+
+    #[test]
+    fn center_and_normalize() {
+        let mut cloud: PointCloud<f64, 2> =
+            PointCloud::from(vec![Point([1., 2.]), Point([3., 2.]), Point([2., 5.])]);
+        center(&mut cloud);
+        assert_eq!(cloud.0[0], Point([-1., -1.]));
+
+        normalize_to_unit_box(&mut cloud);
+        assert_eq!(
+            cloud.0,
+            vec![Point([0., 0.]), Point([2. / 3., 0.]), Point([1. / 3., 1.])]
+        );
+
+        scale(&mut cloud, 3.);
+        assert_eq!(cloud.0[1], Point([2., 0.]));
+
+        let mut equal: PointCloud<f64, 2> = PointCloud::from(vec![Point([4., 4.]); 3]);
+        normalize_to_unit_box(&mut equal);
+        assert_eq!(equal.0, vec![Point([0., 0.]); 3]);
+    }
+
+    #[test]
+    fn random_rotations_preserve_distances() {
+        let mut rng = StdRng::seed_from_u64(1374);
+        let mut cloud: PointCloud<f64, 4> = sample_random_points_with_rng(30, &mut rng);
+        let before = cloud.distance_matrix();
+
+        let rotation: [[f64; 4]; 4] = random_rotation_with_rng(&mut rng);
+        assert!((determinant(rotation) - 1.).abs() < 1e-9);
+        rotate(&mut cloud, &rotation);
+        let after = cloud.distance_matrix();
+        for (d_before, d_after) in before.to_condensed().iter().zip(after.to_condensed()) {
+            assert!((d_before - d_after).abs() < 1e-9);
+        }
+
+        let original = cloud.0.clone();
+        jitter_with_rng(&mut cloud, 0., &mut rng);
+        assert_eq!(cloud.0, original);
+        jitter_with_rng(&mut cloud, 0.01, &mut rng);
+        assert_ne!(cloud.0, original);
+        for (p, q) in cloud.0.iter().zip(original.iter()) {
+            assert!(p.euclidean_distance(q) < 0.1);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use rayon::prelude::*;
+
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::removal::adjacency::AdjacencyMatrix;
+use crate::removal::strong::{strongly_dominates, strongly_dominating_vertex};
+use crate::removal::EdgeOrder;
+use crate::CriticalGrade;
+
+/// The number of edges of a batch, by default.
+const DEFAULT_BATCH_SIZE: usize = 1024;
+
+/// Options of [remove_strongly_filtration_dominated_chunked].
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkedOptions {
+    /// The order in which the edges are processed. The batches are consecutive blocks of edges in
+    /// this order, so, with the default order, each batch covers a block of grades.
+    pub order: EdgeOrder,
+    /// The number of edges of each batch. Larger batches remove more edges, and fewer of them are
+    /// processed in parallel.
+    pub batch_size: usize,
+}
+
+impl Default for ChunkedOptions {
+    fn default() -> Self {
+        Self {
+            order: EdgeOrder::default(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+/// As [remove_strongly_filtration_dominated](crate::removal::remove_strongly_filtration_dominated),
+/// but most of the work is done in parallel. Requires the `parallel` feature.
+///
+/// The edges, in the given order, are split into batches of consecutive edges, which are processed
+/// in parallel. Each batch runs the strong removal on its own edges, in order, in a copy of the
+/// whole graph from which only the edges of the batch that it removes are deleted. The removed
+/// edges are candidates, together with the vertex that dominates them. Then a sequential
+/// reconciliation pass goes through the candidates in order, removing them from the graph, and
+/// only removes those that are still strongly filtration-dominated, which is usually quick to
+/// confirm with the vertex found before.
+///
+/// Every removal is confirmed in the graph from which the previous edges have been removed, as in
+/// the sequential algorithm, so the result is a valid reduction: its clique bifiltration has the
+/// same homology as the one of the input. It depends on the batch size but not on the number of
+/// threads. It may keep some edges that the sequential algorithm removes, namely those that are
+/// only dominated once the edges of previous batches are removed, and with a single batch it is
+/// the result of the sequential algorithm.
+///
+/// Each thread keeps its own copy of the graph, so this takes as much memory as one adjacency
+/// matrix per thread.
+pub fn remove_strongly_filtration_dominated_chunked<G: CriticalGrade>(
+    edge_list: &mut EdgeList<FilteredEdge<G>>,
+    options: &ChunkedOptions,
+) -> EdgeList<FilteredEdge<G>> {
+    options.order.apply(edge_list);
+    let mut adjacency_matrix = AdjacencyMatrix::from_edge_list(edge_list);
+
+    let witnesses: Vec<Option<usize>> = edge_list
+        .edges()
+        .par_chunks(options.batch_size.max(1))
+        .map_init(
+            || adjacency_matrix.clone(),
+            |batch_matrix, batch| remove_in_batch(batch_matrix, batch),
+        )
+        .flat_map_iter(|batch_witnesses| batch_witnesses.into_iter())
+        .collect();
+
+    let mut remaining = EdgeList::new(edge_list.n_vertices);
+    for (edge, witness) in edge_list.edge_iter().zip(witnesses) {
+        let is_dominated = if let Some(w) = witness {
+            strongly_dominates(&adjacency_matrix, edge, w)
+                || strongly_dominating_vertex(&adjacency_matrix, edge).is_some()
+        } else {
+            false
+        };
+        if is_dominated {
+            adjacency_matrix.delete_edge(edge);
+        } else {
+            remaining.add_edge(edge.clone());
+        }
+    }
+    remaining
+}
+
+/// Removes the strongly filtration-dominated edges of the batch, in order, from the graph, and
+/// returns the vertex that dominates each edge of the batch, if it was removed. The removed edges
+/// are added back before returning, so the graph can be reused for the next batch.
+fn remove_in_batch<G: CriticalGrade>(
+    adjacency_matrix: &mut AdjacencyMatrix<G>,
+    batch: &[FilteredEdge<G>],
+) -> Vec<Option<usize>> {
+    let witnesses: Vec<Option<usize>> = batch
+        .iter()
+        .map(|edge| {
+            let witness = strongly_dominating_vertex(adjacency_matrix, edge);
+            if witness.is_some() {
+                adjacency_matrix.delete_edge(edge);
+            }
+            witness
+        })
+        .collect();
+    for (edge, witness) in batch.iter().zip(&witnesses) {
+        if witness.is_some() {
+            adjacency_matrix.add_edge(edge.clone());
+        }
+    }
+    witnesses
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::{EdgeList, FilteredEdge};
+    use crate::removal::adjacency::AdjacencyMatrix;
+    use crate::removal::{
+        is_strongly_filtration_dominated, remove_strongly_filtration_dominated,
+        remove_strongly_filtration_dominated_chunked, ChunkedOptions, EdgeOrder,
+    };
+    use crate::OneCriticalGrade;
+    use ordered_float::OrderedFloat;
+
+    type Edges = EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>;
+
+    fn torus_edges() -> Edges {
+        get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 60 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap()
+    }
+
+    // Replaying the removals in order, each removed edge is dominated when it is removed.
+    fn assert_valid_removal(edges: &Edges, remaining: &Edges) {
+        let mut adjacency = AdjacencyMatrix::from_edge_list(edges);
+        let mut kept = remaining.edge_iter().peekable();
+        for edge in edges.edge_iter() {
+            if kept.peek() == Some(&edge) {
+                kept.next();
+            } else {
+                assert!(is_strongly_filtration_dominated(&adjacency, edge));
+                adjacency.delete_edge(edge);
+            }
+        }
+        assert_eq!(kept.next(), None);
+    }
+
+    #[test]
+    fn chunked_removal_is_valid() {
+        let mut edges = torus_edges();
+        for batch_size in [1, 7, 100] {
+            let remaining = remove_strongly_filtration_dominated_chunked(
+                &mut edges,
+                &ChunkedOptions {
+                    order: EdgeOrder::ReverseLexicographic,
+                    batch_size,
+                },
+            );
+            assert!(remaining.len() < edges.len());
+            assert_valid_removal(&edges, &remaining);
+        }
+    }
+
+    #[test]
+    fn chunked_removal_with_one_batch_is_sequential() {
+        let mut edges = torus_edges();
+        let options = ChunkedOptions {
+            order: EdgeOrder::ReverseLexicographic,
+            batch_size: edges.len(),
+        };
+        let chunked = remove_strongly_filtration_dominated_chunked(&mut edges, &options);
+        let sequential =
+            remove_strongly_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+        assert_eq!(chunked.edges(), sequential.edges());
+    }
+
+    #[test]
+    fn chunked_removal_does_not_depend_on_threads() {
+        let mut edges = torus_edges();
+        let options = ChunkedOptions {
+            order: EdgeOrder::ReverseLexicographic,
+            batch_size: 50,
+        };
+        let results: Vec<Edges> = [1, 4]
+            .into_iter()
+            .map(|n_threads| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(n_threads)
+                    .build()
+                    .unwrap()
+                    .install(|| remove_strongly_filtration_dominated_chunked(&mut edges, &options))
+            })
+            .collect();
+        assert_eq!(results[0].edges(), results[1].edges());
+    }
+}
@@ -0,0 +1,338 @@
+//! Checks that the connected components of a bifiltered graph are preserved by a removal.
+//!
+//! The connected components of the graph at a grade are the 0-th homology of its clique complex,
+//! so the removal algorithms already preserve them. The functions in this module check it
+//! directly, without computing a minimal presentation, and can restore the components in case they
+//! were not preserved, for example when the output is fed to tools that only use the connectivity
+//! of the graph, like hierarchical clustering.
+//!
+//! A graph without some edges has the same connected components as the full graph at every grade
+//! if and only if the endpoints of each missing edge are connected, at the grade of the edge, by
+//! the edges that are left. To answer these queries, they are split into chains of increasing
+//! grades, and each chain is answered with a single union-find that grows as the grades increase:
+//! the edges below the grade of each query are added to it once, when the first query above them
+//! is reached. With two parameters, the grades of `r` missing edges split into `O(sqrt(r))` chains
+//! when they are in general position, instead of one union-find per query.
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use rustc_hash::FxHashSet;
+
+use crate::edges::{BareEdge, Edge, EdgeList, FilteredEdge};
+use crate::CriticalGrade;
+
+/// Returns whether the graph given by `reduced`, which must be a subgraph of `original`, has the
+/// same connected components as `original` at every grade. See the [module](self) documentation
+/// for the complexity.
+pub fn preserves_connectivity<G: CriticalGrade>(
+    original: &EdgeList<FilteredEdge<G>>,
+    reduced: &EdgeList<FilteredEdge<G>>,
+) -> bool {
+    let n_vertices = original.n_vertices.max(reduced.n_vertices);
+    let removed = removed_edges(original, reduced);
+    edges_to_restore(n_vertices, reduced.edges(), &removed).is_empty()
+}
+
+/// Adds back to `reduced` the edges of `original` that are needed so that `reduced` has the same
+/// connected components as `original` at every grade. The edge list `reduced` must be a subgraph
+/// of `original`. Returns the number of edges added back.
+///
+/// The edges are checked chain by chain, see the [module](self) documentation, and an edge added
+/// back for a chain is only used by the later ones, so a few more edges than strictly needed may
+/// be added back.
+pub fn restore_connectivity<G: CriticalGrade>(
+    original: &EdgeList<FilteredEdge<G>>,
+    reduced: &mut EdgeList<FilteredEdge<G>>,
+) -> usize {
+    let n_vertices = original.n_vertices.max(reduced.n_vertices);
+    let removed = removed_edges(original, reduced);
+    let restore = edges_to_restore(n_vertices, reduced.edges(), &removed);
+    let n_restored = restore.len();
+    for idx in restore {
+        reduced.add_edge(removed[idx].clone());
+    }
+    n_restored
+}
+
+/// Returns the indices of the `removed` edges that must be added to the `kept` edges, on vertices
+/// in the range 0..`n_vertices`, so that the endpoints of every removed edge are connected at its
+/// grade. The indices are in increasing order.
+///
+/// Every edge whose endpoints are not connected at its grade when it is checked is added back, so
+/// the kept edges together with the restored ones always have the connected components of all the
+/// edges. The chains of queries are answered one after the other, and an edge restored for a
+/// chain is only seen by the later ones, so a few more edges than strictly needed may be restored
+/// when the kept edges do not preserve the connected components.
+pub(crate) fn edges_to_restore<G: CriticalGrade>(
+    n_vertices: usize,
+    kept: &[FilteredEdge<G>],
+    removed: &[FilteredEdge<G>],
+) -> Vec<usize> {
+    // The edges available to answer queries, sorted by grade, to which the restored edges are
+    // added as the chains are answered.
+    let mut available: Vec<FilteredEdge<G>> = kept.to_vec();
+    available.sort_by(|a, b| a.grade.cmp(&b.grade));
+
+    let mut restore = Vec::new();
+    for chain in grade_chains(removed) {
+        let mut components = UnionFind::new(n_vertices);
+        // The available edges are added in order. Those that are not below the current query yet,
+        // but precede it in the order, wait in a heap ordered colexicographically: with two
+        // parameters, an edge is below a query exactly when it precedes it in both orders.
+        let mut next_available = 0;
+        let mut waiting: BinaryHeap<Reverse<ColexicographicEdge<G>>> = BinaryHeap::new();
+        let mut restored_in_chain = Vec::new();
+        for &idx in chain.iter() {
+            let query = &removed[idx];
+            while let Some(edge) = available.get(next_available) {
+                if edge.grade.cmp(&query.grade) == Ordering::Greater {
+                    break;
+                }
+                waiting.push(Reverse(ColexicographicEdge(edge.clone())));
+                next_available += 1;
+            }
+            let mut not_below = Vec::new();
+            while let Some(Reverse(ColexicographicEdge(edge))) = waiting.peek() {
+                if edge.grade.cmp_colexicographically(&query.grade) == Ordering::Greater {
+                    break;
+                }
+                if edge.grade.lte(&query.grade) {
+                    components.union(edge.u(), edge.v());
+                } else {
+                    // Only possible with more than two parameters.
+                    not_below.push(edge.clone());
+                }
+                waiting.pop();
+            }
+            waiting.extend(
+                not_below
+                    .into_iter()
+                    .map(|e| Reverse(ColexicographicEdge(e))),
+            );
+
+            if components.find(query.u()) != components.find(query.v()) {
+                components.union(query.u(), query.v());
+                restored_in_chain.push(query.clone());
+                restore.push(idx);
+            }
+        }
+        for edge in restored_in_chain {
+            let position = available.partition_point(|e| e.grade <= edge.grade);
+            available.insert(position, edge);
+        }
+    }
+    restore.sort_unstable();
+    restore
+}
+
+/// Splits the indices of the edges into chains whose grades are increasing, in the partial order
+/// of grades. Each edge is put in the first chain whose last grade is below its own, going
+/// through the edges sorted by grade, so with two parameters the number of chains is the minimum
+/// one: the length of the longest sequence of incomparable grades.
+fn grade_chains<G: CriticalGrade>(edges: &[FilteredEdge<G>]) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&a, &b| edges[a].grade.cmp(&edges[b].grade));
+    let mut chains: Vec<Vec<usize>> = Vec::new();
+    for idx in order {
+        let grade = &edges[idx].grade;
+        let chain = chains
+            .iter_mut()
+            .find(|chain| matches!(chain.last(), Some(&last) if edges[last].grade.lte(grade)));
+        match chain {
+            Some(chain) => chain.push(idx),
+            None => chains.push(vec![idx]),
+        }
+    }
+    chains
+}
+
+/// An edge ordered colexicographically by grade.
+struct ColexicographicEdge<G>(FilteredEdge<G>);
+
+impl<G: CriticalGrade> PartialEq for ColexicographicEdge<G> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<G: CriticalGrade> Eq for ColexicographicEdge<G> {}
+
+impl<G: CriticalGrade> PartialOrd for ColexicographicEdge<G> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<G: CriticalGrade> Ord for ColexicographicEdge<G> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.grade.cmp_colexicographically(&other.0.grade)
+    }
+}
+
+/// The edges of `original` that are not in `reduced`.
+fn removed_edges<G: CriticalGrade>(
+    original: &EdgeList<FilteredEdge<G>>,
+    reduced: &EdgeList<FilteredEdge<G>>,
+) -> Vec<FilteredEdge<G>> {
+    let kept: FxHashSet<BareEdge> = reduced.edge_iter().map(|e| e.edge).collect();
+    original
+        .edge_iter()
+        .filter(|e| !kept.contains(&e.edge))
+        .cloned()
+        .collect()
+}
+
+/// A union-find structure with path halving and union by size.
+struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self {
+            parents: (0..n).collect(),
+            sizes: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parents[x] != x {
+            self.parents[x] = self.parents[self.parents[x]];
+            x = self.parents[x];
+        }
+        x
+    }
+
+    fn union(&mut self, x: usize, y: usize) {
+        let (mut root_x, mut root_y) = (self.find(x), self.find(y));
+        if root_x == root_y {
+            return;
+        }
+        if self.sizes[root_x] < self.sizes[root_y] {
+            std::mem::swap(&mut root_x, &mut root_y);
+        }
+        self.parents[root_y] = root_x;
+        self.sizes[root_x] += self.sizes[root_y];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::{BareEdge, Edge, EdgeList, FilteredEdge};
+    use crate::removal::connectivity::{preserves_connectivity, restore_connectivity, UnionFind};
+    use crate::removal::{remove_filtration_dominated, EdgeOrder};
+    use crate::{CriticalGrade, OneCriticalGrade};
+
+    #[test]
+    fn removal_preserves_connectivity() {
+        let mut edge_list = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 60 },
+            Threshold::Percentile(0.5),
+            None,
+            false,
+        )
+        .unwrap();
+        let original = edge_list.clone();
+        let reduced = remove_filtration_dominated(&mut edge_list, EdgeOrder::ReverseLexicographic);
+        assert!(preserves_connectivity(&original, &reduced));
+    }
+
+    #[test]
+    fn restore_disconnected_edges() {
+        let edge = |u, v, x, y| FilteredEdge {
+            grade: OneCriticalGrade([x, y]),
+            edge: BareEdge(u, v),
+        };
+        let original: EdgeList<_> =
+            vec![edge(0, 1, 1, 1), edge(1, 2, 1, 1), edge(0, 2, 2, 0)].into();
+
+        // The edge 0-2 is needed at the grade (2, 0).
+        let mut reduced: EdgeList<_> = vec![edge(0, 1, 1, 1), edge(1, 2, 1, 1)].into();
+        assert!(!preserves_connectivity(&original, &reduced));
+        assert_eq!(restore_connectivity(&original, &mut reduced), 1);
+        assert!(preserves_connectivity(&original, &reduced));
+    }
+
+    #[test]
+    fn connectivity_through_other_edges() {
+        let edge = |u, v, x, y| FilteredEdge {
+            grade: OneCriticalGrade([x, y]),
+            edge: BareEdge(u, v),
+        };
+        // The edge 0-1 is not needed, since 0 and 1 are connected through 2 before it appears.
+        let original: EdgeList<_> =
+            vec![edge(0, 1, 2, 2), edge(1, 2, 1, 1), edge(0, 2, 1, 1)].into();
+        let mut reduced: EdgeList<_> = vec![edge(1, 2, 1, 1), edge(0, 2, 1, 1)].into();
+        assert!(preserves_connectivity(&original, &reduced));
+        assert_eq!(restore_connectivity(&original, &mut reduced), 0);
+    }
+
+    /// Whether the endpoints of the query are connected by the edges below its grade, building a
+    /// union-find for the query.
+    fn naive_are_connected<G: CriticalGrade>(
+        n_vertices: usize,
+        edges: &[FilteredEdge<G>],
+        query: &FilteredEdge<G>,
+    ) -> bool {
+        let mut components = UnionFind::new(n_vertices);
+        for e in edges.iter().filter(|e| e.grade.lte(&query.grade)) {
+            components.union(e.u(), e.v());
+        }
+        components.find(query.u()) == components.find(query.v())
+    }
+
+    fn assert_restores_like_naive<const N: usize>(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let n_vertices = 12;
+        let mut original = EdgeList::new(n_vertices);
+        for u in 0..n_vertices {
+            for v in (u + 1)..n_vertices {
+                if rng.gen_bool(0.5) {
+                    let mut grade = [0; N];
+                    for value in grade.iter_mut() {
+                        *value = rng.gen_range(0..6);
+                    }
+                    original.add_edge(FilteredEdge {
+                        edge: BareEdge(u, v),
+                        grade: OneCriticalGrade(grade),
+                    });
+                }
+            }
+        }
+        let kept: Vec<_> = original
+            .edge_iter()
+            .filter(|_| rng.gen_bool(0.3))
+            .cloned()
+            .collect();
+        let mut reduced: EdgeList<_> = kept.clone().into();
+        reduced.n_vertices = n_vertices;
+
+        let expected = original
+            .edge_iter()
+            .all(|e| naive_are_connected(n_vertices, &kept, e));
+        assert_eq!(preserves_connectivity(&original, &reduced), expected);
+
+        let n_restored = restore_connectivity(&original, &mut reduced);
+        assert_eq!(n_restored == 0, expected);
+        assert!(original
+            .edge_iter()
+            .all(|e| naive_are_connected(n_vertices, reduced.edges(), e)));
+        // Every restored edge was disconnected by the kept edges.
+        for restored in &reduced.edges()[kept.len()..] {
+            assert!(!naive_are_connected(n_vertices, &kept, restored));
+        }
+    }
+
+    #[test]
+    fn incremental_union_find_agrees_with_naive() {
+        for seed in 0..20 {
+            assert_restores_like_naive::<2>(seed);
+            assert_restores_like_naive::<3>(seed);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Removal of the edges that are in no clique large enough to matter for the homology of a given
+//! dimension, found with the core numbers of the graph, see [remove_low_core_edges].
+use crate::cliques::core_numbers;
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::CriticalGrade;
+
+/// Returns the core number of each vertex of the graph: the largest `k` such that the vertex is
+/// in the `k`-core of the graph, the largest subgraph whose vertices all have at least `k`
+/// neighbours in it. A vertex in a clique of `k + 1` vertices has core number at least `k`.
+///
+/// Repeated edges are counted once, and self-loops are ignored.
+pub fn vertex_core_numbers<G: CriticalGrade>(edge_list: &EdgeList<FilteredEdge<G>>) -> Vec<usize> {
+    core_numbers_of_edges(edge_list.n_vertices, edge_list.edge_iter())
+}
+
+/// As [vertex_core_numbers], but of the graph at the given grade, made of the edges whose grade
+/// is less than or equal to it. Since the graph at any grade is a subgraph of the graph of all
+/// the edges, these core numbers are at most those of [vertex_core_numbers].
+pub fn vertex_core_numbers_at<G: CriticalGrade>(
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    grade: &G,
+) -> Vec<usize> {
+    core_numbers_of_edges(
+        edge_list.n_vertices,
+        edge_list.edge_iter().filter(|e| e.grade.lte(grade)),
+    )
+}
+
+/// Removes the edges that are in no clique of `homology + 1` vertices at any grade, that is, in
+/// no simplex of dimension `homology` of the clique bifiltration, and returns the remaining edges,
+/// in the same order. The homology of dimension `homology`, and above, of the clique bifiltration
+/// is preserved at every grade: the removed simplices all have dimension less than `homology`,
+/// and none is a face of a simplex of dimension `homology`. The homology of lower dimensions is
+/// not preserved. If `homology` is less than 2, every edge is a simplex of dimension at most
+/// `homology` and no edge is removed.
+///
+/// The cliques at a grade are also cliques of the graph of all the edges, so an edge is removed if
+/// one of its endpoints has a core number, see [vertex_core_numbers], less than `homology`. This
+/// runs in time linear in the size of the graph and does not find all such edges, but it is a
+/// cheap pass to shrink the graph before the removal of dominated edges, which is more expensive.
+/// The remaining edges are those of the `homology`-core, so a second pass removes nothing.
+///
+/// The core numbers at each grade, from [vertex_core_numbers_at], would not remove more edges: the
+/// graph at the join of the grades of all the edges is the graph of all the edges, and a clique at
+/// any grade is also a clique at that grade. So the grade-free core numbers are used, which are
+/// computed once instead of once per grade.
+pub fn remove_low_core_edges<G: CriticalGrade>(
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    homology: usize,
+) -> EdgeList<FilteredEdge<G>> {
+    if homology < 2 {
+        return edge_list.clone();
+    }
+    let core = vertex_core_numbers(edge_list);
+    let mut remaining = EdgeList::from_iterator(
+        edge_list
+            .edge_iter()
+            .filter(|e| core[e.edge.0] >= homology && core[e.edge.1] >= homology)
+            .cloned(),
+    );
+    remaining.n_vertices = edge_list.n_vertices;
+    remaining
+}
+
+fn core_numbers_of_edges<'a, G: CriticalGrade + 'a>(
+    n_vertices: usize,
+    edges: impl Iterator<Item = &'a FilteredEdge<G>>,
+) -> Vec<usize> {
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); n_vertices];
+    for e in edges {
+        let (u, v) = (e.edge.0, e.edge.1);
+        if u != v {
+            neighbours[u].push(v);
+            neighbours[v].push(u);
+        }
+    }
+    for vertex_neighbours in neighbours.iter_mut() {
+        vertex_neighbours.sort_unstable();
+        vertex_neighbours.dedup();
+    }
+    core_numbers(&neighbours)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::filtration::{build_flag_filtration, Filtration};
+    use crate::removal::{remove_low_core_edges, vertex_core_numbers, vertex_core_numbers_at};
+    use crate::simplicial_complex::MapSimplicialComplex;
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn core_numbers_of_triangle_with_tail() {
+        // A triangle 0, 1, 2 whose edge 01 appears late, and a path 2, 3, 4.
+        let mut edges: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = EdgeList::new(5);
+        for (u, v, grade) in [
+            (0, 1, [3, 3]),
+            (0, 2, [0, 0]),
+            (1, 2, [0, 0]),
+            (2, 3, [1, 1]),
+            (3, 4, [1, 1]),
+        ] {
+            edges.add_edge(FilteredEdge {
+                edge: BareEdge(u, v),
+                grade: OneCriticalGrade(grade),
+            });
+        }
+        assert_eq!(vertex_core_numbers(&edges), vec![2, 2, 2, 1, 1]);
+        assert_eq!(
+            vertex_core_numbers_at(&edges, &OneCriticalGrade([1, 1])),
+            vec![1, 1, 1, 1, 1]
+        );
+        // At the join of all the grades, the graph has all the edges.
+        assert_eq!(
+            vertex_core_numbers_at(&edges, &OneCriticalGrade([3, 3])),
+            vertex_core_numbers(&edges)
+        );
+
+        let remaining = remove_low_core_edges(&edges, 2);
+        assert_eq!(
+            remaining.edge_iter().map(|e| e.edge).collect::<Vec<_>>(),
+            vec![BareEdge(0, 1), BareEdge(0, 2), BareEdge(1, 2)]
+        );
+        assert_eq!(remove_low_core_edges(&edges, 1).edges(), edges.edges());
+        assert!(remove_low_core_edges(&edges, 3).is_empty());
+    }
+
+    #[test]
+    fn low_core_edges_are_in_no_large_simplex() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 60 },
+            Threshold::Fixed(0.6),
+            None,
+            false,
+        )
+        .unwrap();
+        for homology in [2, 3, 4] {
+            let remaining = remove_low_core_edges(&edges, homology);
+            assert!(remaining.len() <= edges.len());
+            assert_eq!(
+                remove_low_core_edges(&remaining, homology).edges(),
+                remaining.edges()
+            );
+
+            // The simplices of dimension homology are all kept.
+            let full: Filtration<_, MapSimplicialComplex> =
+                build_flag_filtration(edges.n_vertices, homology, edges.edge_iter().copied());
+            let pruned: Filtration<_, MapSimplicialComplex> = build_flag_filtration(
+                remaining.n_vertices,
+                homology,
+                remaining.edge_iter().copied(),
+            );
+            let mut full_simplices: Vec<_> = full.simplices(homology).collect();
+            let mut pruned_simplices: Vec<_> = pruned.simplices(homology).collect();
+            full_simplices.sort_by(|a, b| a.0.cmp(&b.0));
+            pruned_simplices.sort_by(|a, b| a.0.cmp(&b.0));
+            assert_eq!(full_simplices, pruned_simplices);
+        }
+    }
+}
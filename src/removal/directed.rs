@@ -0,0 +1,393 @@
+//! Strong removal on directed filtered graphs, whose clique complexes are directed flag
+//! complexes: a simplex is a sequence of vertices with an edge from each vertex to every later
+//! one. These are the complexes of ordered and Dowker-type constructions in network analysis.
+//!
+//! The edge from u to v, written u→v, is distinct from the edge v→u, and both may be in the
+//! graph. In a simplex that contains u→v, every other vertex x is either before u, so that the
+//! graph has the edges x→u and x→v, or after u, so that it has the edge u→x and one of the edges
+//! x→v and v→x. A vertex w dominates u→v if it can be inserted right after u in all these
+//! simplices, and in no other place: see [strongly_dominating_vertex_directed] for the precise condition on the in- and
+//! out-neighbourhoods.
+use litemap::LiteMap;
+
+use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+use crate::removal::EdgeOrder;
+use crate::CriticalGrade;
+
+/// A directed graph with the grade of each edge, with the out- and in-neighbourhood of each
+/// vertex. The endpoints of a [FilteredEdge] are read as the edge from its first endpoint to its
+/// second one.
+pub struct DirectedAdjacencyMatrix<G> {
+    out_neighbourhoods: Vec<LiteMap<usize, G>>,
+    in_neighbourhoods: Vec<LiteMap<usize, G>>,
+}
+
+impl<G: CriticalGrade> DirectedAdjacencyMatrix<G> {
+    /// A graph with the given number of vertices and no edges.
+    pub fn new(n_vertices: usize) -> Self {
+        Self {
+            out_neighbourhoods: vec![LiteMap::new(); n_vertices],
+            in_neighbourhoods: vec![LiteMap::new(); n_vertices],
+        }
+    }
+
+    /// The graph of the edges of the list, each from its first endpoint to its second one.
+    pub fn from_edge_list(edge_list: &EdgeList<FilteredEdge<G>>) -> Self {
+        let mut matrix = Self::new(edge_list.n_vertices);
+        for edge in edge_list.edge_iter() {
+            matrix.add_edge(edge.clone());
+        }
+        matrix
+    }
+
+    /// The number of vertices of the graph.
+    pub fn n_vertices(&self) -> usize {
+        self.out_neighbourhoods.len()
+    }
+
+    /// Adds the edge from the first endpoint of the given edge to the second one, replacing the
+    /// grade of the edge in the same direction, if any.
+    ///
+    /// Panics: if an endpoint of the edge is not a vertex of the graph.
+    pub fn add_edge(&mut self, edge: FilteredEdge<G>) {
+        let BareEdge(u, v) = edge.edge;
+        self.out_neighbourhoods[u].insert(v, edge.grade.clone());
+        self.in_neighbourhoods[v].insert(u, edge.grade);
+    }
+
+    /// Deletes the edge from u to v, if any, and returns its grade. The edge from v to u is kept.
+    pub fn delete_edge(&mut self, u: usize, v: usize) -> Option<G> {
+        self.in_neighbourhoods.get_mut(v)?.remove(&u);
+        self.out_neighbourhoods.get_mut(u)?.remove(&v)
+    }
+
+    /// The grade of the edge from u to v, if any.
+    pub fn edge_grade(&self, u: usize, v: usize) -> Option<&G> {
+        self.out_neighbourhoods.get(u)?.get(&v)
+    }
+
+    /// The vertices x with an edge from u to x, with the grades of the edges, sorted by vertex.
+    pub fn out_neighbours(&self, u: usize) -> impl Iterator<Item = (usize, &G)> + '_ {
+        self.out_neighbourhoods
+            .get(u)
+            .into_iter()
+            .flat_map(|neighbours| neighbours.iter().map(|(&x, grade)| (x, grade)))
+    }
+
+    /// The vertices x with an edge from x to u, with the grades of the edges, sorted by vertex.
+    pub fn in_neighbours(&self, u: usize) -> impl Iterator<Item = (usize, &G)> + '_ {
+        self.in_neighbourhoods
+            .get(u)
+            .into_iter()
+            .flat_map(|neighbours| neighbours.iter().map(|(&x, grade)| (x, grade)))
+    }
+}
+
+/// If the directed edge u→v is strongly filtration-dominated in the graph, returns a vertex w
+/// that dominates it. This is the case if:
+/// - the graph has the edges u→w and w→v, at grades less than or equal to the grade of u→v, and
+///   has neither w→u nor v→w, so that w is between u and v in every simplex that contains the
+///   three vertices,
+/// - for every in-neighbour x of both u and v, it has the edge x→w, at a grade less than or equal
+///   to the join of the grades of x→u, x→v and u→v, and not w→x, and
+/// - for every out-neighbour x of u that is an in- or out-neighbour of v, it has the edge w→x, at
+///   a grade less than or equal to the join of the grades of u→x, of each edge between x and v,
+///   and of u→v, and not x→w.
+///
+/// Then, at every grade at which u→v is present, w can be inserted right after u in every simplex
+/// of the directed flag complex that contains u→v, and this is the only place where w appears in
+/// the simplices that contain u→v and w, so the link of the edge is a cone with apex w. The
+/// reciprocal edges are rejected at any grade, since at a large enough grade they would let w
+/// appear in two places.
+pub fn strongly_dominating_vertex_directed<G: CriticalGrade>(
+    adjacency_matrix: &DirectedAdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+) -> Option<usize> {
+    let BareEdge(u, v) = edge.edge;
+    adjacency_matrix
+        .out_neighbours(u)
+        .filter(|&(w, grade_uw)| w != v && grade_uw.lte(&edge.grade))
+        .filter(|&(w, _)| {
+            matches!(adjacency_matrix.edge_grade(w, v), Some(grade_wv) if grade_wv.lte(&edge.grade))
+        })
+        .filter(|&(w, _)| {
+            adjacency_matrix.edge_grade(w, u).is_none()
+                && adjacency_matrix.edge_grade(v, w).is_none()
+        })
+        .map(|(w, _)| w)
+        .find(|&w| dominates_directed(adjacency_matrix, edge, w))
+}
+
+/// Whether the directed edge is strongly filtration-dominated in the graph, see
+/// [strongly_dominating_vertex_directed].
+pub fn is_strongly_filtration_dominated_directed<G: CriticalGrade>(
+    adjacency_matrix: &DirectedAdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+) -> bool {
+    strongly_dominating_vertex_directed(adjacency_matrix, edge).is_some()
+}
+
+/// Removes the strongly filtration-dominated edges of a directed filtered graph, whose edges go
+/// from their first endpoint to their second one. The clique complexes of the remaining edges are
+/// directed flag complexes with the same homology as those of the original edges, at every grade.
+/// See [strongly_dominating_vertex_directed] for when an edge is dominated.
+///
+/// As [remove_strongly_filtration_dominated](crate::removal::remove_strongly_filtration_dominated),
+/// the edges are first put in the given order.
+pub fn remove_strongly_filtration_dominated_directed<G: CriticalGrade>(
+    edge_list: &mut EdgeList<FilteredEdge<G>>,
+    order: EdgeOrder,
+) -> EdgeList<FilteredEdge<G>> {
+    order.apply(edge_list);
+    let mut adjacency_matrix = DirectedAdjacencyMatrix::from_edge_list(edge_list);
+    let mut remaining = EdgeList::new(edge_list.n_vertices);
+    for edge in edge_list.edge_iter() {
+        if is_strongly_filtration_dominated_directed(&adjacency_matrix, edge) {
+            adjacency_matrix.delete_edge(edge.edge.0, edge.edge.1);
+        } else {
+            remaining.add_edge(edge.clone());
+        }
+    }
+    remaining
+}
+
+/// Whether w, with the edges u→w and w→v, dominates the edge u→v at every grade greater than or
+/// equal to its own.
+fn dominates_directed<G: CriticalGrade>(
+    adjacency_matrix: &DirectedAdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+    w: usize,
+) -> bool {
+    let BareEdge(u, v) = edge.edge;
+    let is_other = |x: usize| x != u && x != v && x != w;
+    let has_edge_below = |from: usize, to: usize, grade: &G| -> bool {
+        matches!(adjacency_matrix.edge_grade(from, to), Some(g) if g.lte(grade))
+    };
+
+    // The vertices that may come before u in a simplex with u→v.
+    let predecessors_dominated = adjacency_matrix
+        .in_neighbours(u)
+        .filter(|&(x, _)| is_other(x))
+        .all(|(x, grade_xu)| match adjacency_matrix.edge_grade(x, v) {
+            Some(grade_xv) => {
+                let entry = grade_xu.join(grade_xv).join(&edge.grade);
+                has_edge_below(x, w, &entry) && adjacency_matrix.edge_grade(w, x).is_none()
+            }
+            None => true,
+        });
+    if !predecessors_dominated {
+        return false;
+    }
+
+    // The vertices that may come after u in a simplex with u→v, either before or after v.
+    adjacency_matrix
+        .out_neighbours(u)
+        .filter(|&(x, _)| is_other(x))
+        .all(|(x, grade_ux)| {
+            let neighbours_v = adjacency_matrix.edge_grade(x, v).is_some()
+                || adjacency_matrix.edge_grade(v, x).is_some();
+            if neighbours_v && adjacency_matrix.edge_grade(x, w).is_some() {
+                return false;
+            }
+            [
+                adjacency_matrix.edge_grade(x, v),
+                adjacency_matrix.edge_grade(v, x),
+            ]
+            .into_iter()
+            .flatten()
+            .all(|grade_xv| {
+                let entry = grade_ux.join(grade_xv).join(&edge.grade);
+                has_edge_below(w, x, &entry)
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::removal::{
+        remove_strongly_filtration_dominated, remove_strongly_filtration_dominated_directed,
+        strongly_dominating_vertex_directed, DirectedAdjacencyMatrix, EdgeOrder,
+    };
+    use crate::{CriticalGrade, OneCriticalGrade};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn directed_edges(
+        edges: &[(usize, usize, [usize; 2])],
+    ) -> EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> {
+        EdgeList::from_iterator(edges.iter().map(|&(u, v, grade)| FilteredEdge {
+            grade: OneCriticalGrade(grade),
+            edge: BareEdge(u, v),
+        }))
+    }
+
+    #[test]
+    fn orientation_decides_domination() {
+        // In the transitive triangle, vertex 1 dominates 0→2, and the complex is a 2-simplex.
+        let mut transitive = directed_edges(&[(0, 1, [0, 0]), (1, 2, [0, 0]), (0, 2, [0, 0])]);
+        let matrix = DirectedAdjacencyMatrix::from_edge_list(&transitive);
+        assert_eq!(
+            strongly_dominating_vertex_directed(&matrix, &transitive.edges()[2]),
+            Some(1)
+        );
+        assert_eq!(
+            strongly_dominating_vertex_directed(&matrix, &transitive.edges()[0]),
+            None
+        );
+        let remaining = remove_strongly_filtration_dominated_directed(
+            &mut transitive,
+            EdgeOrder::ReverseLexicographic,
+        );
+        assert_eq!(remaining.len(), 2);
+
+        // The cyclic triangle has no 2-simplex, so its directed flag complex is a circle, and no
+        // edge is dominated, unlike in the undirected triangle.
+        let mut cyclic = directed_edges(&[(0, 1, [0, 0]), (1, 2, [0, 0]), (2, 0, [0, 0])]);
+        let remaining_cyclic = remove_strongly_filtration_dominated_directed(
+            &mut cyclic,
+            EdgeOrder::ReverseLexicographic,
+        );
+        assert_eq!(remaining_cyclic.len(), 3);
+        let undirected =
+            remove_strongly_filtration_dominated(&mut cyclic, EdgeOrder::ReverseLexicographic);
+        assert_eq!(undirected.len(), 2);
+    }
+
+    #[test]
+    fn domination_checks_in_and_out_neighbours() {
+        // Vertex 2 is in the middle of 0→1: 0→2→1. Vertex 3 comes before 0 and 1, and vertex 4
+        // comes after 0 and before 1.
+        let edges = [
+            (0, 1, [1, 1]),
+            (0, 2, [0, 0]),
+            (2, 1, [0, 0]),
+            (3, 0, [0, 0]),
+            (3, 1, [0, 0]),
+            (0, 4, [0, 0]),
+            (4, 1, [0, 0]),
+        ];
+        let graph = directed_edges(&edges);
+        let mut matrix = DirectedAdjacencyMatrix::from_edge_list(&graph);
+        let edge = graph.edges()[0];
+        assert_eq!(strongly_dominating_vertex_directed(&matrix, &edge), None);
+
+        // Vertex 2 needs the edge 3→2, from the in-neighbour, and 2→4, to the out-neighbour.
+        matrix.add_edge(FilteredEdge {
+            grade: OneCriticalGrade([0, 2]),
+            edge: BareEdge(3, 2),
+        });
+        matrix.add_edge(FilteredEdge {
+            grade: OneCriticalGrade([0, 0]),
+            edge: BareEdge(2, 4),
+        });
+        // The edge 3→2 appears too late.
+        assert_eq!(strongly_dominating_vertex_directed(&matrix, &edge), None);
+        matrix.add_edge(FilteredEdge {
+            grade: OneCriticalGrade([1, 0]),
+            edge: BareEdge(3, 2),
+        });
+        assert_eq!(strongly_dominating_vertex_directed(&matrix, &edge), Some(2));
+
+        // The edge in the opposite direction does not count.
+        assert_eq!(matrix.delete_edge(2, 4), Some(OneCriticalGrade([0, 0])));
+        matrix.add_edge(FilteredEdge {
+            grade: OneCriticalGrade([0, 0]),
+            edge: BareEdge(4, 2),
+        });
+        assert_eq!(strongly_dominating_vertex_directed(&matrix, &edge), None);
+    }
+
+    /// The Euler characteristic of the directed flag complex of the edges present at the grade.
+    fn euler_characteristic(
+        edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>>,
+        grade: &OneCriticalGrade<usize, 2>,
+    ) -> i64 {
+        let mut matrix = DirectedAdjacencyMatrix::new(edge_list.n_vertices);
+        for edge in edge_list.edge_iter().filter(|e| e.grade.lte(grade)) {
+            matrix.add_edge(*edge);
+        }
+        let mut characteristic = 0;
+        let mut simplices: Vec<Vec<usize>> = (0..edge_list.n_vertices).map(|u| vec![u]).collect();
+        let mut sign = 1;
+        while !simplices.is_empty() {
+            characteristic += sign * simplices.len() as i64;
+            sign = -sign;
+            simplices = simplices
+                .iter()
+                .flat_map(|simplex| {
+                    let last = simplex[simplex.len() - 1];
+                    matrix
+                        .out_neighbours(last)
+                        .map(|(x, _)| x)
+                        .filter(|&x| simplex.iter().all(|&y| matrix.edge_grade(y, x).is_some()))
+                        .map(|x| {
+                            let mut longer = simplex.clone();
+                            longer.push(x);
+                            longer
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+        }
+        characteristic
+    }
+
+    #[test]
+    fn reciprocal_edges_prevent_domination() {
+        // Vertex 2 is between 0 and 1, but also before 0 thanks to 2→0, so the simplices (0, 2, 1)
+        // and (2, 0, 1) both contain 0→1, which cannot be removed.
+        let mut edges = directed_edges(&[
+            (0, 1, [0, 0]),
+            (0, 2, [0, 0]),
+            (2, 1, [0, 0]),
+            (2, 0, [0, 0]),
+        ]);
+        let matrix = DirectedAdjacencyMatrix::from_edge_list(&edges);
+        assert_eq!(
+            strongly_dominating_vertex_directed(&matrix, &edges.edges()[0]),
+            None
+        );
+        let remaining =
+            remove_strongly_filtration_dominated_directed(&mut edges, EdgeOrder::Maintain);
+        assert_eq!(remaining.len(), 4);
+    }
+
+    #[test]
+    fn removal_preserves_euler_characteristic() {
+        let mut rng = StdRng::seed_from_u64(1360);
+        for _ in 0..200 {
+            let n_vertices = 6;
+            let mut edges = EdgeList::new(n_vertices);
+            // Mostly edges in a single direction, with a few reciprocal ones.
+            for u in 0..n_vertices {
+                for v in (u + 1)..n_vertices {
+                    let directions = match rng.gen_range(0..10) {
+                        0..=2 => vec![],
+                        3..=5 => vec![(u, v)],
+                        6..=8 => vec![(v, u)],
+                        _ => vec![(u, v), (v, u)],
+                    };
+                    for (from, to) in directions {
+                        edges.add_edge(FilteredEdge {
+                            grade: OneCriticalGrade([rng.gen_range(0..3), rng.gen_range(0..3)]),
+                            edge: BareEdge(from, to),
+                        });
+                    }
+                }
+            }
+            let original = edges.clone();
+            let remaining =
+                remove_strongly_filtration_dominated_directed(&mut edges, EdgeOrder::Maintain);
+            for x in 0..3 {
+                for y in 0..3 {
+                    let grade = OneCriticalGrade([x, y]);
+                    assert_eq!(
+                        euler_characteristic(&original, &grade),
+                        euler_characteristic(&remaining, &grade)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Single-parameter collapses along lines of a bifiltration, in the style of the fibered barcode
+//! of RIVET: along each monotone line, the bifiltration restricts to a single-parameter
+//! filtration, which can be reduced with the single-parameter collapse, cheaper than the
+//! multi-parameter algorithms.
+use num::Float;
+use thiserror::Error;
+
+use crate::edges::transform::slice_along_line;
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::removal::{remove_filtration_dominated_single_parameter, EdgeOrder};
+use crate::{OneCriticalGrade, Value};
+
+/// The monotone line of grades `base + t * direction`, for real `t`. All the coordinates of the
+/// direction must be non-negative, and some of them positive. See [slice_along_line].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Line<VF> {
+    pub base: [VF; 2],
+    pub direction: [VF; 2],
+}
+
+impl<VF: Value + Float> Line<VF> {
+    /// Whether the direction is non-negative and non-zero, so that the line is monotone.
+    pub fn is_monotone(&self) -> bool {
+        self.direction.iter().all(|d| *d >= VF::zero())
+            && self.direction.iter().any(|d| *d > VF::zero())
+    }
+}
+
+/// The number of edges of the slice of a bifiltration along a line, before and after the
+/// single-parameter collapse. Returned by [collapse_along_lines].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineCollapse {
+    /// The number of edges that the line reaches, see [slice_along_line].
+    pub sliced_edges: usize,
+    /// The number of edges that remain after [remove_filtration_dominated_single_parameter].
+    pub remaining_edges: usize,
+}
+
+/// A line given to [collapse_along_lines] that is not monotone, see [Line::is_monotone].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("Line {0} is not monotone: its direction must be non-negative and non-zero")]
+pub struct NonMonotoneLine(pub usize);
+
+/// Slices the bifiltered edge list along each of the given lines, and collapses each slice with
+/// [remove_filtration_dominated_single_parameter], in reverse lexicographic order. Returns the
+/// number of edges of each slice before and after the collapse, in the order of the lines, or an
+/// error with the index of the first line that is not monotone.
+pub fn collapse_along_lines<VF: Value + Float>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    lines: &[Line<VF>],
+) -> Result<Vec<LineCollapse>, NonMonotoneLine> {
+    if let Some(idx) = lines.iter().position(|line| !line.is_monotone()) {
+        return Err(NonMonotoneLine(idx));
+    }
+    let collapses = lines
+        .iter()
+        .map(|line| {
+            let mut slice = slice_along_line(edge_list, line.base, line.direction);
+            let remaining = remove_filtration_dominated_single_parameter(
+                &mut slice,
+                EdgeOrder::ReverseLexicographic,
+            );
+            LineCollapse {
+                sliced_edges: slice.len(),
+                remaining_edges: remaining.len(),
+            }
+        })
+        .collect();
+    Ok(collapses)
+}
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::transform::slice_along_line;
+    use crate::removal::{
+        collapse_along_lines, remove_filtration_dominated_single_parameter, EdgeOrder, Line,
+        NonMonotoneLine,
+    };
+
+    #[test]
+    fn collapses_along_lines() {
+        let edge_list = get_dataset_density_edge_list(
+            Dataset::Sphere { n_points: 40 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let origin = [OrderedFloat(0.), OrderedFloat(0.)];
+        let lines = [
+            Line {
+                base: origin,
+                direction: [OrderedFloat(1.), OrderedFloat(1.)],
+            },
+            // A vertical line through a codensity that only some vertices reach.
+            Line {
+                base: [OrderedFloat(0.5), OrderedFloat(0.)],
+                direction: [OrderedFloat(0.), OrderedFloat(1.)],
+            },
+        ];
+        assert!(lines.iter().all(Line::is_monotone));
+        let collapses = collapse_along_lines(&edge_list, &lines).unwrap();
+        assert_eq!(collapses.len(), 2);
+
+        let diagonal = &collapses[0];
+        assert_eq!(diagonal.sliced_edges, edge_list.len());
+        assert!(diagonal.remaining_edges < diagonal.sliced_edges);
+
+        let mut vertical_slice = slice_along_line(&edge_list, lines[1].base, lines[1].direction);
+        let vertical_remaining = remove_filtration_dominated_single_parameter(
+            &mut vertical_slice,
+            EdgeOrder::ReverseLexicographic,
+        );
+        assert!(collapses[1].sliced_edges < edge_list.len());
+        assert_eq!(collapses[1].sliced_edges, vertical_slice.len());
+        assert_eq!(collapses[1].remaining_edges, vertical_remaining.len());
+
+        let horizontal = Line {
+            base: origin,
+            direction: [OrderedFloat(1.), OrderedFloat(0.)],
+        };
+        let decreasing = Line {
+            base: origin,
+            direction: [OrderedFloat(1.), OrderedFloat(-1.)],
+        };
+        assert_eq!(
+            collapse_along_lines(&edge_list, &[horizontal, decreasing]),
+            Err(NonMonotoneLine(1))
+        );
+    }
+}
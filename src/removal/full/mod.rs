@@ -2,13 +2,16 @@ use std::collections::BTreeSet;
 use std::time::Duration;
 
 use crate::edges::{EdgeList, FilteredEdge};
-use crate::removal::adjacency::AdjacencyMatrix;
-use crate::removal::EdgeOrder;
+use crate::removal::adjacency::{AdjacencyMatrix, EdgeNeighbourhood};
+use crate::removal::report::{remove_dominated, RemovalReport};
+use crate::removal::{DeletionMode, EdgeOrder, RemovalOptions};
 use crate::Value;
 use crate::{CriticalGrade, OneCriticalGrade};
 
 mod regions;
-mod stripes;
+pub(crate) mod stripes;
+
+pub use regions::{non_domination_region, NonDominationRegion};
 
 /// Go through the given edge list, and check each edge for filtration-domination.
 /// If it is filtration-dominated we remove them.
@@ -29,61 +32,116 @@ pub fn remove_filtration_dominated_timed<VF: Value>(
     order: EdgeOrder,
     max_time: Option<Duration>,
 ) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>> {
-    match order {
-        EdgeOrder::ReverseLexicographic => {
-            edge_list.edges_mut().sort_unstable_by(|a, b| b.cmp(a));
-        }
-        EdgeOrder::Maintain => {}
-    }
-
-    let mut remaining_edges: Vec<FilteredEdge<OneCriticalGrade<VF, 2>>> =
-        Vec::with_capacity(edge_list.len());
-    let mut adjacency_matrix = AdjacencyMatrix::new(edge_list.n_vertices);
+    remove_filtration_dominated_with_deletion(edge_list, order, DeletionMode::Immediate, max_time)
+}
 
-    for edge in edge_list.edge_iter() {
-        adjacency_matrix.add_edge(*edge);
-    }
+/// As [remove_filtration_dominated_timed], but the removed edges are deleted from the internal
+/// adjacency structure as specified by `deletion_mode`. See [DeletionMode].
+pub fn remove_filtration_dominated_with_deletion<VF: Value>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    order: EdgeOrder,
+    deletion_mode: DeletionMode,
+    max_time: Option<Duration>,
+) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>> {
+    remove_dominated(
+        edge_list,
+        &RemovalOptions {
+            order,
+            deletion_mode,
+            max_time,
+            ..RemovalOptions::default()
+        },
+        false,
+        filtration_dominators,
+    )
+    .remaining
+}
 
-    let start = std::time::Instant::now();
-    for edge in edge_list.edge_iter() {
-        if let Some(max_time) = max_time {
-            if start.elapsed() > max_time {
-                return edge_list.clone();
-            }
-        }
-        if is_filtration_dominated(&adjacency_matrix, edge) {
-            adjacency_matrix.delete_edge(edge);
-        } else {
-            remaining_edges.push(*edge);
-        }
-    }
+/// As [remove_filtration_dominated_timed], but returns a [RemovalReport] with the removed edges,
+/// the vertices that dominate them, the time taken, and whether a timeout happened.
+pub fn remove_filtration_dominated_with_report<VF: Value>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    order: EdgeOrder,
+    max_time: Option<Duration>,
+) -> RemovalReport<OneCriticalGrade<VF, 2>> {
+    remove_dominated(
+        edge_list,
+        &RemovalOptions {
+            order,
+            max_time,
+            ..RemovalOptions::default()
+        },
+        true,
+        filtration_dominators,
+    )
+}
 
-    remaining_edges.shrink_to_fit();
-    remaining_edges.into()
+/// As [remove_filtration_dominated_with_report], but the order, the deletion mode, the timeout,
+/// and the other parameters are given by the [RemovalOptions].
+pub fn remove_filtration_dominated_with_options<VF: Value>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    options: &RemovalOptions,
+) -> RemovalReport<OneCriticalGrade<VF, 2>> {
+    remove_dominated(edge_list, options, true, filtration_dominators)
 }
 
-fn is_filtration_dominated<VF: Value>(
+/// Whether the edge is filtration-dominated in the graph of the adjacency matrix, that is, whether
+/// at every grade greater than or equal to its own some vertex dominates it.
+///
+/// The removal algorithms check each edge in the graph from which the previous edges have been
+/// removed, so the result depends on the edges that the matrix contains.
+pub fn is_filtration_dominated<VF: Value>(
     adjacency_matrix: &AdjacencyMatrix<OneCriticalGrade<VF, 2>>,
     edge: &FilteredEdge<OneCriticalGrade<VF, 2>>,
 ) -> bool {
+    filtration_dominators(adjacency_matrix, edge).is_some()
+}
+
+/// If the edge is filtration-dominated, returns vertices that together dominate it at every
+/// grade. Otherwise, returns None.
+fn filtration_dominators<VF: Value>(
+    adjacency_matrix: &AdjacencyMatrix<OneCriticalGrade<VF, 2>>,
+    edge: &FilteredEdge<OneCriticalGrade<VF, 2>>,
+) -> Option<Vec<usize>> {
+    let edge_neighbourhood = adjacency_matrix.edge_neighbourhood(edge);
+
     // Compute regions of non-domination for every vertex in the edge neighbourhood.
     let mut non_domination_regions = Vec::new();
-    for (v, value_v) in adjacency_matrix.common_neighbours(edge) {
-        let non_domination_region =
-            regions::calculate_non_domination_region(adjacency_matrix, edge, v, value_v);
+    for (v, value_v) in edge_neighbourhood.candidates() {
+        let non_domination_region = regions::calculate_non_domination_region(
+            adjacency_matrix,
+            &edge_neighbourhood,
+            edge,
+            v,
+            value_v,
+        );
         if non_domination_region.is_empty() {
             // The vertex v strongly dominates the edge.
-            return true;
+            return Some(vec![v]);
         }
-        non_domination_regions.push(non_domination_region);
+        non_domination_regions.push((v, non_domination_region));
     }
 
-    // Compute all critical grades, where we need to check for domination.
+    let mut dominators = Vec::new();
+    for grade in domination_grades(&edge_neighbourhood, edge) {
+        let dominator = non_domination_regions
+            .iter()
+            .find(|(_, region)| !region.contains_point(grade))?;
+        dominators.push(dominator.0);
+    }
+    Some(dominators)
+}
+
+/// Returns all critical grades where we need to check for domination of the given edge.
+fn domination_grades<VF: Value>(
+    edge_neighbourhood: &EdgeNeighbourhood<OneCriticalGrade<VF, 2>>,
+    edge: &FilteredEdge<OneCriticalGrade<VF, 2>>,
+) -> BTreeSet<OneCriticalGrade<VF, 2>> {
     let mut first_domination_times: BTreeSet<OneCriticalGrade<VF, 2>> =
         BTreeSet::from_iter([edge.grade]);
 
-    for (_neigh_vertex, neigh_value) in adjacency_matrix.common_neighbours(edge) {
-        first_domination_times.insert(edge.grade.join(&neigh_value));
+    for (_neigh_vertex, neigh_value) in edge_neighbourhood.candidates() {
+        first_domination_times.insert(neigh_value);
     }
     let mut domination_times: BTreeSet<OneCriticalGrade<VF, 2>> = BTreeSet::new();
     for time in first_domination_times.iter() {
@@ -91,18 +149,33 @@ fn is_filtration_dominated<VF: Value>(
             domination_times.insert(time.join(other_time));
         }
     }
+    domination_times
+}
 
-    for grade in domination_times {
-        let mut dominated = false;
-        for region in non_domination_regions.iter() {
-            if !region.contains_point(grade) {
-                dominated = true;
-                break;
-            }
-        }
-        if !dominated {
-            return false;
-        }
-    }
-    true
+/// For every vertex in the edge neighbourhood of the given edge, returns the vertex together with
+/// the critical grades (see [domination_grades]) at which the vertex does not dominate the edge.
+pub(crate) fn non_domination_grades<VF: Value>(
+    adjacency_matrix: &AdjacencyMatrix<OneCriticalGrade<VF, 2>>,
+    edge: &FilteredEdge<OneCriticalGrade<VF, 2>>,
+) -> Vec<(usize, Vec<OneCriticalGrade<VF, 2>>)> {
+    let edge_neighbourhood = adjacency_matrix.edge_neighbourhood(edge);
+    let grades = domination_grades(&edge_neighbourhood, edge);
+    edge_neighbourhood
+        .candidates()
+        .map(|(v, value_v)| {
+            let region = regions::calculate_non_domination_region(
+                adjacency_matrix,
+                &edge_neighbourhood,
+                edge,
+                v,
+                value_v,
+            );
+            let failing_grades = grades
+                .iter()
+                .copied()
+                .filter(|grade| region.contains_point(*grade))
+                .collect();
+            (v, failing_grades)
+        })
+        .collect()
 }
@@ -1,12 +1,26 @@
+//! The regions of the grades where a vertex does not dominate an edge.
 use std::cmp::Ordering;
+use std::ops::{Mul, Sub};
 
 use crate::edges::FilteredEdge;
-use crate::removal::adjacency::AdjacencyMatrix;
+use crate::removal::adjacency::{AdjacencyMatrix, EdgeNeighbourhood};
 use crate::removal::full::stripes::{Stripe, Stripes};
 use crate::{CriticalGrade, OneCriticalGrade, Value};
 
 pub type Pair<VF> = (OneCriticalGrade<VF, 2>, OneCriticalGrade<VF, 2>);
 
+/// The grades, greater than or equal to the grade of an edge, where a vertex does not dominate
+/// the edge. Build it with [non_domination_region]. The full removal removes an edge if, at each
+/// of the grades where it has to check, some vertex of the edge neighbourhood does not contain the
+/// grade in its region.
+///
+/// Each vertex of the edge neighbourhood that is not adjacent to the candidate vertex from the
+/// grade where it joins the neighbourhood contributes the grades that are greater than or equal
+/// to the first grade, but not to the second. The region is the union of these contributions,
+/// and it is kept as two staircases: vertical stripes, which are bounded in the first parameter
+/// and extend upwards in the second, and horizontal stripes, which are bounded in the second
+/// parameter and extend rightwards in the first. A grade is in the region if it is in a stripe of
+/// either staircase.
 #[derive(Debug)]
 pub struct NonDominationRegion<VF> {
     vertical_stripes: Stripes<VF>,
@@ -14,27 +28,139 @@ pub struct NonDominationRegion<VF> {
 }
 
 impl<VF: Value> NonDominationRegion<VF> {
-    pub fn new(vertical_stripes: Vec<Stripe<VF>>, horizontal_stripes: Vec<Stripe<VF>>) -> Self {
+    pub(crate) fn new(
+        vertical_stripes: Vec<Stripe<VF>>,
+        horizontal_stripes: Vec<Stripe<VF>>,
+    ) -> Self {
         Self {
             vertical_stripes: Stripes::new(vertical_stripes),
             horizontal_stripes: Stripes::new(horizontal_stripes),
         }
     }
 
+    /// Whether the region has no grades, that is, whether the vertex strongly dominates the edge.
     pub fn is_empty(&self) -> bool {
         self.vertical_stripes.is_empty() && self.horizontal_stripes.is_empty()
     }
 
+    /// Whether the vertex does not dominate the edge at the given grade.
     pub fn contains_point(&self, grade: OneCriticalGrade<VF, 2>) -> bool {
         let vertical_point = (grade.0[0], grade.0[1]);
         let horizontal_point = (grade.0[1], grade.0[0]);
         self.vertical_stripes.contains_point(vertical_point)
             || self.horizontal_stripes.contains_point(horizontal_point)
     }
+
+    /// Returns an iterator over the corners of the staircases of the region: the lower left corner
+    /// of each maximal vertical stripe, by increasing first parameter, and then the lower left
+    /// corner of each maximal horizontal stripe, by increasing second parameter. A maximal stripe
+    /// extends from its corner until the corner of the next stripe of its staircase, or until the
+    /// end of the staircase, and from there upwards or rightwards. The same grade can be the
+    /// corner of a vertical and of a horizontal stripe.
+    pub fn staircase_corners(&self) -> impl Iterator<Item = OneCriticalGrade<VF, 2>> + '_ {
+        let vertical = steps(&self.vertical_stripes).map(|(x, y)| OneCriticalGrade([x, y]));
+        let horizontal = steps(&self.horizontal_stripes).map(|(y, x)| OneCriticalGrade([x, y]));
+        vertical.chain(horizontal)
+    }
+
+    /// The smallest box, given by its lower and upper corners, that contains the region, or None
+    /// if the region is empty. The box is half-open: it contains the grades that are greater than
+    /// or equal to the lower corner, and less than the upper one, in both parameters. The
+    /// coordinates of the upper corner are [Bounded::max_value](num::Bounded::max_value) in the
+    /// parameters where the region is unbounded, which is at least one of them.
+    pub fn bounding_box(&self) -> Option<(OneCriticalGrade<VF, 2>, OneCriticalGrade<VF, 2>)> {
+        let lower = self.staircase_corners().reduce(|lower, corner| {
+            OneCriticalGrade([lower.0[0].min(corner.0[0]), lower.0[1].min(corner.0[1])])
+        })?;
+        // A staircase ends at the last delimiter of its stripes.
+        let end = |stripes: &Stripes<VF>| match stripes.arranged_stripes().last() {
+            Some((endpoint, _)) => *endpoint,
+            None => lower.0[0],
+        };
+        let upper_x = if self.horizontal_stripes.is_empty() {
+            end(&self.vertical_stripes)
+        } else {
+            VF::max_value()
+        };
+        let upper_y = if self.vertical_stripes.is_empty() {
+            end(&self.horizontal_stripes)
+        } else {
+            VF::max_value()
+        };
+        Some((lower, OneCriticalGrade([upper_x, upper_y])))
+    }
+
+    /// The area of the part of the region whose grades are less than the given grade in both
+    /// parameters.
+    pub fn area_below(&self, upper: OneCriticalGrade<VF, 2>) -> VF
+    where
+        VF: Sub<Output = VF> + Mul<Output = VF>,
+    {
+        // The boundaries of the stripes split the plane in cells that are either inside or outside
+        // of the region.
+        let mut xs = vec![upper.0[0]];
+        let mut ys = vec![upper.0[1]];
+        for (x, y) in self.vertical_stripes.arranged_stripes() {
+            xs.push(*x);
+            ys.push(*y);
+        }
+        for (y, x) in self.horizontal_stripes.arranged_stripes() {
+            xs.push(*x);
+            ys.push(*y);
+        }
+        let cut = |values: &mut Vec<VF>, end: VF| {
+            values.retain(|value| *value <= end);
+            values.sort_unstable();
+            values.dedup();
+        };
+        cut(&mut xs, upper.0[0]);
+        cut(&mut ys, upper.0[1]);
+
+        let mut area = VF::zero();
+        for x_cell in xs.windows(2) {
+            for y_cell in ys.windows(2) {
+                if self.contains_point(OneCriticalGrade([x_cell[0], y_cell[0]])) {
+                    area = area + (x_cell[1] - x_cell[0]) * (y_cell[1] - y_cell[0]);
+                }
+            }
+        }
+        area
+    }
+}
+
+/// The lower left corners of the maximal stripes of the staircase, with the coordinates of the
+/// stripes.
+fn steps<VF: Value>(stripes: &Stripes<VF>) -> impl Iterator<Item = (VF, VF)> + '_ {
+    stripes
+        .arranged_stripes()
+        .iter()
+        .copied()
+        .filter(|(_, value)| *value != VF::max_value())
+}
+
+/// The non-domination region of the vertex w for the edge, see [NonDominationRegion], or None if
+/// w is not a common neighbour of the endpoints of the edge, or is one of them.
+pub fn non_domination_region<VF: Value>(
+    adjacency_matrix: &AdjacencyMatrix<OneCriticalGrade<VF, 2>>,
+    edge: &FilteredEdge<OneCriticalGrade<VF, 2>>,
+    w: usize,
+) -> Option<NonDominationRegion<VF>> {
+    let edge_neighbourhood = adjacency_matrix.edge_neighbourhood(edge);
+    let (_, value_w) = edge_neighbourhood
+        .candidates()
+        .find(|(vertex, _)| *vertex == w)?;
+    Some(calculate_non_domination_region(
+        adjacency_matrix,
+        &edge_neighbourhood,
+        edge,
+        w,
+        value_w,
+    ))
 }
 
 pub(crate) fn calculate_non_domination_region<VF: Value>(
     adjacency_matrix: &AdjacencyMatrix<OneCriticalGrade<VF, 2>>,
+    edge_neighbourhood: &EdgeNeighbourhood<OneCriticalGrade<VF, 2>>,
     edge: &FilteredEdge<OneCriticalGrade<VF, 2>>,
     v: usize,
     value_v: OneCriticalGrade<VF, 2>,
@@ -42,7 +168,7 @@ pub(crate) fn calculate_non_domination_region<VF: Value>(
     let mut vertical_stripes = Vec::new();
     let mut horizontal_stripes = Vec::new();
 
-    let mut edge_neighs = adjacency_matrix.closed_neighbours_edge(edge).peekable();
+    let mut edge_neighs = edge_neighbourhood.closed_neighbours().peekable();
     let mut v_neighs = adjacency_matrix
         .closed_neighbours(v, value_v.join(&edge.grade))
         .peekable();
@@ -107,7 +233,7 @@ mod tests {
     use crate::edges::{BareEdge, FilteredEdge};
     use crate::removal::adjacency::AdjacencyMatrix;
     use crate::removal::full::regions::{
-        add_pair, calculate_non_domination_region, NonDominationRegion,
+        add_pair, calculate_non_domination_region, non_domination_region, NonDominationRegion,
     };
     use crate::OneCriticalGrade;
 
@@ -224,8 +350,13 @@ mod tests {
                 (5, OneCriticalGrade([10, 10])),
             ]
         );
-        let region =
-            calculate_non_domination_region(&adj, &query_edge, 3, OneCriticalGrade([4, 4]));
+        let region = calculate_non_domination_region(
+            &adj,
+            &adj.edge_neighbourhood(&query_edge),
+            &query_edge,
+            3,
+            OneCriticalGrade([4, 4]),
+        );
 
         // Vertex 3 is not connected to vertex 2 at grade [2, 2].
         assert!(region.contains_point(OneCriticalGrade([2, 2])));
@@ -244,4 +375,69 @@ mod tests {
         assert!(region.contains_point(OneCriticalGrade([11, 10])));
         assert!(!region.contains_point(OneCriticalGrade([9, 10])));
     }
+
+    #[test]
+    fn non_domination_region_geometry() {
+        let mut adj: AdjacencyMatrix<OneCriticalGrade<usize, 2>> = AdjacencyMatrix::new(5);
+        let query_edge = FilteredEdge {
+            edge: BareEdge(0, 1),
+            grade: OneCriticalGrade([2, 2]),
+        };
+        adj.add_edge(query_edge);
+        // Vertex 2 joins the edge neighbourhood at [2, 2], and vertex 3 at [4, 4]. They are
+        // adjacent from [5, 5].
+        for (u, v, grade) in [
+            (0, 2, [1, 2]),
+            (1, 2, [2, 1]),
+            (0, 3, [4, 3]),
+            (1, 3, [3, 4]),
+            (2, 3, [5, 5]),
+        ] {
+            adj.add_edge(FilteredEdge {
+                edge: BareEdge(u, v),
+                grade: OneCriticalGrade(grade),
+            });
+        }
+
+        // Vertex 2 does not dominate the edge from [4, 4] until [5, 5].
+        let region_2 = non_domination_region(&adj, &query_edge, 2).unwrap();
+        assert!(!region_2.is_empty());
+        assert!(region_2.contains_point(OneCriticalGrade([4, 10])));
+        assert!(!region_2.contains_point(OneCriticalGrade([5, 5])));
+        assert_eq!(
+            region_2.staircase_corners().collect::<Vec<_>>(),
+            vec![OneCriticalGrade([4, 4]), OneCriticalGrade([4, 4])]
+        );
+        assert_eq!(
+            region_2.bounding_box(),
+            Some((
+                OneCriticalGrade([4, 4]),
+                OneCriticalGrade([usize::MAX, usize::MAX])
+            ))
+        );
+        assert_eq!(region_2.area_below(OneCriticalGrade([6, 6])), 3);
+        assert_eq!(region_2.area_below(OneCriticalGrade([3, 3])), 0);
+
+        // Vertex 3 does not dominate the edge from [2, 2] until [5, 5].
+        let region_3 = non_domination_region(&adj, &query_edge, 3).unwrap();
+        assert_eq!(region_3.area_below(OneCriticalGrade([6, 6])), 15);
+        assert_eq!(
+            region_3.bounding_box().map(|(lower, _)| lower),
+            Some(OneCriticalGrade([2, 2]))
+        );
+
+        // Endpoints and vertices outside the edge neighbourhood have no region.
+        assert!(non_domination_region(&adj, &query_edge, 0).is_none());
+        assert!(non_domination_region(&adj, &query_edge, 4).is_none());
+
+        // The region of a vertex that strongly dominates the edge is empty.
+        let dominated_edge = FilteredEdge {
+            edge: BareEdge(2, 3),
+            grade: OneCriticalGrade([5, 5]),
+        };
+        let empty_region = non_domination_region(&adj, &dominated_edge, 0).unwrap();
+        assert!(empty_region.is_empty());
+        assert_eq!(empty_region.bounding_box(), None);
+        assert_eq!(empty_region.area_below(OneCriticalGrade([6, 6])), 0);
+    }
 }
@@ -136,6 +136,13 @@ impl<VF: Value> Stripes<VF> {
     pub fn is_empty(&self) -> bool {
         self.arranged_stripes.is_empty()
     }
+
+    /// The delimiters of the stripes, in increasing order, with the least value of the stripes
+    /// that are active from each of them until the next one, or the maximum value if there are
+    /// none.
+    pub fn arranged_stripes(&self) -> &[(VF, VF)] {
+        &self.arranged_stripes
+    }
 }
 
 #[cfg(test)]
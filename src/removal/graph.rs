@@ -0,0 +1,154 @@
+//! A read-only view of a multifiltered graph, on the adjacency structure of the removal
+//! algorithms, to implement other domination-like criteria.
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::removal::AdjacencyMatrix;
+use crate::CriticalGrade;
+
+/// A multifiltered graph, built from an edge list, that answers neighbourhood queries at a grade.
+/// It is read-only: to delete edges while checking them, use an [AdjacencyMatrix] instead.
+///
+/// The queries panic if a given vertex is not less than [BifilteredGraph::n_vertices].
+pub struct BifilteredGraph<G> {
+    adjacency_matrix: AdjacencyMatrix<G>,
+}
+
+impl<G: CriticalGrade> BifilteredGraph<G> {
+    /// The graph of the edges of the list. If some vertices are joined by several edges, the last
+    /// one is kept.
+    pub fn new(edge_list: &EdgeList<FilteredEdge<G>>) -> Self {
+        Self {
+            adjacency_matrix: AdjacencyMatrix::from_edge_list(edge_list),
+        }
+    }
+
+    /// The number of vertices of the graph.
+    pub fn n_vertices(&self) -> usize {
+        self.adjacency_matrix.n_vertices()
+    }
+
+    /// Returns the grade of the edge between u and v, if they are adjacent.
+    pub fn edge_grade(&self, u: usize, v: usize) -> Option<&G> {
+        self.adjacency_matrix.edge_grade(u, v)
+    }
+
+    /// Returns an iterator over the neighbours of the vertex u and the grade of the edge that
+    /// connects u and each neighbour.
+    ///
+    /// The returned iterator is sorted by vertex.
+    pub fn neighbours(&self, u: usize) -> impl Iterator<Item = (usize, G)> + '_ {
+        self.adjacency_matrix.open_neighbours(u)
+    }
+
+    /// Returns an iterator over the neighbours of the vertex u at the given grade, that is, the
+    /// vertices connected to u by an edge whose grade is less than or equal to the given one.
+    ///
+    /// The returned iterator is sorted by vertex.
+    pub fn neighbours_at<'a>(&'a self, u: usize, grade: &'a G) -> impl Iterator<Item = usize> + 'a {
+        self.neighbours(u)
+            .filter(move |(_, edge_grade)| edge_grade.lte(grade))
+            .map(|(v, _)| v)
+    }
+
+    /// The number of neighbours of the vertex u at the given grade, see
+    /// [BifilteredGraph::neighbours_at].
+    pub fn degree_at(&self, u: usize, grade: &G) -> usize {
+        self.neighbours_at(u, grade).count()
+    }
+
+    /// Returns an iterator over the common neighbours of the vertices u and v, and the join of the
+    /// grades of the edges that connect them to u and v, that is, the grade from which they are
+    /// common neighbours.
+    ///
+    /// The returned iterator is sorted by vertex.
+    pub fn common_neighbours(&self, u: usize, v: usize) -> impl Iterator<Item = (usize, G)> + '_ {
+        self.adjacency_matrix.common_neighbours_of_vertices(u, v)
+    }
+
+    /// Returns an iterator over the common neighbours of the vertices u and v at the given grade.
+    ///
+    /// The returned iterator is sorted by vertex.
+    pub fn common_neighbours_at<'a>(
+        &'a self,
+        u: usize,
+        v: usize,
+        grade: &'a G,
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.common_neighbours(u, v)
+            .filter(move |(_, join)| join.lte(grade))
+            .map(|(w, _)| w)
+    }
+
+    /// The adjacency structure of the graph, to check its edges with
+    /// [is_strongly_filtration_dominated](crate::removal::is_strongly_filtration_dominated) and
+    /// [is_filtration_dominated](crate::removal::is_filtration_dominated).
+    pub fn adjacency_matrix(&self) -> &AdjacencyMatrix<G> {
+        &self.adjacency_matrix
+    }
+}
+
+impl<G: CriticalGrade> From<&EdgeList<FilteredEdge<G>>> for BifilteredGraph<G> {
+    fn from(edge_list: &EdgeList<FilteredEdge<G>>) -> Self {
+        Self::new(edge_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::removal::{is_strongly_filtration_dominated, BifilteredGraph};
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn bifiltered_graph_queries() {
+        let edges: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = vec![
+            FilteredEdge {
+                grade: OneCriticalGrade([2, 2]),
+                edge: BareEdge(0, 1),
+            },
+            FilteredEdge {
+                grade: OneCriticalGrade([0, 1]),
+                edge: BareEdge(0, 2),
+            },
+            FilteredEdge {
+                grade: OneCriticalGrade([1, 0]),
+                edge: BareEdge(1, 2),
+            },
+            FilteredEdge {
+                grade: OneCriticalGrade([0, 3]),
+                edge: BareEdge(0, 3),
+            },
+        ]
+        .into();
+        let graph = BifilteredGraph::from(&edges);
+        assert_eq!(graph.n_vertices(), 4);
+        assert_eq!(graph.edge_grade(2, 1), Some(&OneCriticalGrade([1, 0])));
+        assert_eq!(graph.edge_grade(1, 3), None);
+
+        assert_eq!(
+            graph.neighbours(0).map(|(v, _)| v).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        let grade = OneCriticalGrade([2, 2]);
+        assert_eq!(
+            graph.neighbours_at(0, &grade).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(graph.degree_at(0, &OneCriticalGrade([0, 3])), 2);
+        assert_eq!(graph.degree_at(3, &OneCriticalGrade([0, 0])), 0);
+
+        assert_eq!(
+            graph.common_neighbours(0, 1).collect::<Vec<_>>(),
+            vec![(2, OneCriticalGrade([1, 1]))]
+        );
+        assert_eq!(
+            graph
+                .common_neighbours_at(0, 1, &OneCriticalGrade([1, 0]))
+                .count(),
+            0
+        );
+        assert!(is_strongly_filtration_dominated(
+            graph.adjacency_matrix(),
+            &edges.edges()[0]
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::removal::{
+    remove_filtration_dominated_with_report, remove_strongly_filtration_dominated_with_report,
+    EdgeOrder, PassStatistics,
+};
+use crate::{OneCriticalGrade, Value};
+
+/// Options of [remove_filtration_dominated_hybrid].
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HybridOptions {
+    /// The order in which both passes go through the edges.
+    pub order: EdgeOrder,
+    /// The maximum time of both passes together. If None then no timeout is applied.
+    pub max_time: Option<Duration>,
+    /// The maximum time of the strong pass, so that some of `max_time` is left for the full
+    /// pass. If None then the strong pass may take all of `max_time`.
+    pub strong_max_time: Option<Duration>,
+}
+
+/// The output of [remove_filtration_dominated_hybrid].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HybridReport<G> {
+    /// The edges remaining after the last completed pass.
+    pub remaining: EdgeList<FilteredEdge<G>>,
+    /// Statistics of the strong pass, or None if it timed out.
+    pub strong: Option<PassStatistics>,
+    /// Statistics of the full pass, or None if it timed out or there was no time left for it.
+    pub full: Option<PassStatistics>,
+    /// Whether a pass was stopped because it exceeded its maximum time.
+    pub timed_out: bool,
+}
+
+impl<G> HybridReport<G> {
+    /// The total number of removed edges.
+    pub fn n_removed(&self) -> usize {
+        self.strong
+            .iter()
+            .chain(self.full.iter())
+            .map(|pass| pass.n_removed())
+            .sum()
+    }
+}
+
+/// Removes the strongly filtration-dominated edges, which is cheap and usually removes most
+/// edges, and then the filtration-dominated edges among the remaining ones, which is expensive
+/// but now runs on a much smaller graph. This is usually much faster than
+/// [remove_filtration_dominated](crate::removal::remove_filtration_dominated) on all the edges,
+/// and removes a similar number of edges.
+///
+/// If the strong pass exceeds its maximum time, the full pass runs on all the edges with the time
+/// left. If the full pass exceeds it, the output of the strong pass is returned.
+pub fn remove_filtration_dominated_hybrid<VF: Value>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    options: &HybridOptions,
+) -> HybridReport<OneCriticalGrade<VF, 2>> {
+    let start = std::time::Instant::now();
+    let mut remaining = edge_list.clone();
+    let mut timed_out = false;
+
+    let strong_max_time = match (options.max_time, options.strong_max_time) {
+        (Some(max_time), Some(strong_max_time)) => Some(max_time.min(strong_max_time)),
+        (max_time, strong_max_time) => max_time.or(strong_max_time),
+    };
+    let strong_report = remove_strongly_filtration_dominated_with_report(
+        &mut remaining,
+        options.order,
+        strong_max_time,
+    );
+    let strong = if strong_report.timed_out() {
+        timed_out = true;
+        None
+    } else {
+        let statistics = PassStatistics {
+            order: options.order,
+            edges_before: remaining.len(),
+            edges_after: strong_report.remaining.len(),
+            duration: strong_report.duration,
+        };
+        remaining = strong_report.remaining;
+        Some(statistics)
+    };
+
+    let full_max_time = options.max_time.map(|max_time| {
+        max_time
+            .checked_sub(start.elapsed())
+            .unwrap_or(Duration::ZERO)
+    });
+    let full_report =
+        remove_filtration_dominated_with_report(&mut remaining, options.order, full_max_time);
+    let full = if full_report.timed_out() {
+        timed_out = true;
+        None
+    } else {
+        let statistics = PassStatistics {
+            order: options.order,
+            edges_before: remaining.len(),
+            edges_after: full_report.remaining.len(),
+            duration: full_report.duration,
+        };
+        remaining = full_report.remaining;
+        Some(statistics)
+    };
+
+    HybridReport {
+        remaining,
+        strong,
+        full,
+        timed_out,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::removal::{
+        remove_filtration_dominated, remove_filtration_dominated_hybrid,
+        remove_strongly_filtration_dominated, EdgeOrder, HybridOptions,
+    };
+
+    #[test]
+    fn hybrid_matches_consecutive_passes() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 100 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let order = EdgeOrder::ReverseLexicographic;
+
+        let report = remove_filtration_dominated_hybrid(&edges, &HybridOptions::default());
+        assert!(!report.timed_out);
+        assert_eq!(report.n_removed(), edges.len() - report.remaining.len());
+        let strong = report.strong.unwrap();
+        assert_eq!(strong.edges_before, edges.len());
+        assert_eq!(report.full.unwrap().edges_before, strong.edges_after);
+
+        let mut strong_remaining = remove_strongly_filtration_dominated(&mut edges.clone(), order);
+        let expected = remove_filtration_dominated(&mut strong_remaining, order);
+        assert_eq!(report.remaining.edges(), expected.edges());
+
+        let no_time = remove_filtration_dominated_hybrid(
+            &edges,
+            &HybridOptions {
+                max_time: Some(Duration::ZERO),
+                ..HybridOptions::default()
+            },
+        );
+        assert!(no_time.timed_out);
+        assert!(no_time.strong.is_none() && no_time.full.is_none());
+        assert_eq!(no_time.remaining.len(), edges.len());
+    }
+}
@@ -0,0 +1,26 @@
+//! The building blocks of the removal algorithms that are not otherwise public, exposed for the
+//! criterion benchmarks of `benches/hot_paths.rs`. Requires the `bench-internals` feature.
+//!
+//! This module is not part of the stable interface of the crate: it may change with the
+//! implementation of the removal algorithms.
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+pub use crate::removal::full::stripes::{Interval, Stripe, Stripes};
+
+/// The environment variable that sets the seed of [seeded_rng].
+pub const SEED_ENV_VAR: &str = "FILTRATION_DOMINATION_BENCH_SEED";
+
+/// The seed of [seeded_rng] when [SEED_ENV_VAR] is not set.
+pub const DEFAULT_SEED: u64 = 42;
+
+/// A random number generator seeded with the value of [SEED_ENV_VAR], or with [DEFAULT_SEED] if
+/// it is not set or is not a number, so that the sampled datasets of the benchmarks are the same
+/// across runs.
+pub fn seeded_rng() -> StdRng {
+    let seed = std::env::var(SEED_ENV_VAR)
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(DEFAULT_SEED);
+    StdRng::seed_from_u64(seed)
+}
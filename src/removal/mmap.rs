@@ -0,0 +1,104 @@
+//! Strong removal on edge lists stored in memory-mapped files. Requires the `mmap` feature.
+use std::path::Path;
+
+use crate::edges::mmap::{FixedSizeValue, MmapEdgeList, MmapEdgeListError};
+use crate::removal::adjacency::AdjacencyMatrix;
+use crate::removal::strong::strongly_dominating_vertex;
+use crate::removal::DeletionMode;
+use crate::trace::pass;
+
+/// Removes the strongly filtration-dominated edges of the memory-mapped edge list, and writes the
+/// remaining ones to a new file at the given path, as [MmapEdgeList::create]. The input file is
+/// not modified, and can be opened with [MmapEdgeList::open_read_only]. Returns
+/// [MmapEdgeListError::SameFile] if the output path is that of the input file.
+///
+/// The edges are processed in the order of the file, as with [EdgeOrder::Maintain], so sort them
+/// first with [MmapEdgeList::sort], usually in [EdgeOrder::ReverseLexicographic] order. The
+/// adjacency structure of the graph is kept in memory, but the edges are read from the input file
+/// and written to the output file one at a time.
+///
+/// [EdgeOrder::Maintain]: crate::removal::EdgeOrder::Maintain
+/// [EdgeOrder::ReverseLexicographic]: crate::removal::EdgeOrder::ReverseLexicographic
+pub fn remove_strongly_filtration_dominated_mmap<VF, P, const N: usize>(
+    edge_list: &MmapEdgeList<VF, N>,
+    output: P,
+) -> Result<MmapEdgeList<VF, N>, MmapEdgeListError>
+where
+    VF: FixedSizeValue,
+    P: AsRef<Path>,
+{
+    if let Ok(output_path) = std::fs::canonicalize(output.as_ref()) {
+        if output_path == edge_list.path() {
+            return Err(MmapEdgeListError::SameFile(output_path));
+        }
+    }
+    let pass = pass!(
+        "remove_strongly_filtration_dominated_mmap",
+        n_vertices = edge_list.n_vertices(),
+        input_edges = edge_list.len();
+        remaining_edges
+    );
+    let mut adjacency_matrix = AdjacencyMatrix::from_edges(
+        edge_list.n_vertices(),
+        edge_list.len(),
+        edge_list.edge_iter(),
+        DeletionMode::Immediate,
+    );
+    let remaining = edge_list.edge_iter().filter(|edge| {
+        if strongly_dominating_vertex(&adjacency_matrix, edge).is_some() {
+            adjacency_matrix.delete_edge(edge);
+            false
+        } else {
+            true
+        }
+    });
+    let remaining_list = MmapEdgeList::create(output, edge_list.n_vertices(), remaining)?;
+    pass.record("remaining_edges", remaining_list.len());
+    Ok(remaining_list)
+}
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::mmap::{MmapEdgeList, MmapEdgeListError};
+    use crate::io_utils::unique_temp_path;
+    use crate::removal::{
+        remove_strongly_filtration_dominated, remove_strongly_filtration_dominated_mmap, EdgeOrder,
+    };
+
+    #[test]
+    fn mmap_removal_agrees_with_removal() {
+        let directory = unique_temp_path("mmap_tests");
+        std::fs::create_dir_all(&directory).unwrap();
+        let input_path = directory.join("removal_input.edges");
+        let output_path = directory.join("removal_output.edges");
+
+        let mut edges = get_dataset_density_edge_list(
+            Dataset::Sphere { n_points: 60 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let mut mapped: MmapEdgeList<OrderedFloat<f64>, 2> =
+            MmapEdgeList::from_edge_list(&input_path, &edges).unwrap();
+        mapped.sort(EdgeOrder::ReverseLexicographic).unwrap();
+        let remaining = remove_strongly_filtration_dominated_mmap(&mapped, &output_path).unwrap();
+
+        let expected =
+            remove_strongly_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+        assert!(remaining.len() < mapped.len());
+        assert_eq!(remaining.to_edge_list().edges(), expected.edges());
+
+        // Writing the output over the input would truncate the mapped file.
+        assert!(matches!(
+            remove_strongly_filtration_dominated_mmap(&mapped, &input_path),
+            Err(MmapEdgeListError::SameFile(_))
+        ));
+        assert_eq!(mapped.len(), edges.len());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
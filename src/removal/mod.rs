@@ -5,24 +5,375 @@
 //! The two main functions are:
 //! - [remove_filtration_dominated], which removes filtration-dominated edges, and
 //! - [remove_strongly_filtration_dominated], which removes strongly filtration-dominated edges.
+//!
+//! For single-parameter filtered graphs, [remove_filtration_dominated_single_parameter] is the
+//! edge collapse of Boissonnat and Pritam. [collapse_along_lines] applies it to the slices of a
+//! bifiltered graph along several lines. For Vietoris–Rips filtrations,
+//! [remove_strongly_dominated_rips] runs the strong removal on the edges of a distance matrix.
+//!
 //! See the documentation of the functions, and the paper, for more details.
-pub use full::{remove_filtration_dominated, remove_filtration_dominated_timed};
+//!
+//! Each of these functions has a `_with_report` variant that returns a [RemovalReport], which
+//! also lists the removed edges and the vertices that dominate them, and a `_with_options`
+//! variant that takes all its parameters in [RemovalOptions], including whether to record a
+//! [RemovalTrace] with the grades at which the dominating vertices witnessed each removal.
+//!
+//! A single pass may leave edges that become dominated once others are removed:
+//! [remove_strongly_filtration_dominated_until_stable] repeats the passes until no more edges are
+//! removed, and [iterate_strongly_filtration_dominated] runs the passes one at a time, reporting
+//! the edges removed by each. [remove_filtration_dominated_hybrid] runs the cheap strong pass
+//! first, and the full pass only on the edges that survive it.
+//!
+//! To query single edges instead of running a full removal pass, build an [AdjacencyMatrix] and
+//! call [is_strongly_filtration_dominated] or [is_filtration_dominated]. A [BifilteredGraph]
+//! answers neighbourhood queries at a grade on the same structure, to implement other criteria.
+//! The [NonDominationRegion] of a vertex, from [non_domination_region], is the set of grades
+//! where it does not dominate an edge, as computed by the full removal.
+//!
+//! With the `parallel` feature, [remove_strongly_filtration_dominated_chunked] removes edges
+//! within batches of consecutive edges in parallel, and then confirms the removals in a short
+//! sequential pass.
+//!
+//! With the `mmap` feature, [remove_strongly_filtration_dominated_mmap] streams the edges of a
+//! [MmapEdgeList](crate::edges::mmap::MmapEdgeList), stored in a file, through the strong removal.
+//!
+//! For directed graphs, whose clique complexes are directed flag complexes,
+//! [remove_strongly_filtration_dominated_directed] checks the domination of each edge against the
+//! in- and out-neighbourhoods of its endpoints, on a [DirectedAdjacencyMatrix].
+//!
+//! Besides edges, [remove_dominated_vertices] removes the vertices that are dominated by another
+//! vertex at every grade, which further shrinks the graph.
+//!
+//! Before any of these, [remove_low_core_edges] cheaply removes the edges that are in no clique
+//! large enough to matter for the homology of a given dimension, using the [vertex_core_numbers]
+//! of the graph. Unlike the removal of dominated edges, it does not preserve the homology of lower
+//! dimensions.
+//!
+//! After removing edges by other means, for example with
+//! [EdgeList::remove_edges](crate::edges::EdgeList::remove_edges), [validate_strong_removal] and
+//! [validate_removal] check that the removed edges were dominated.
+//!
+//! The [connectivity] module checks, without computing a minimal presentation, that the
+//! connected components of the graph are preserved at every grade, and can restore them after
+//! removing edges by other means.
+//!
+//! The [reference](mod@reference) module has a naive implementation of filtration-domination removal, which is
+//! slow but useful as a correctness oracle in tests.
+//!
+//! # Panics
+//!
+//! None of the functions of this module panic when given an edge list that passes
+//! [EdgeList::validate](crate::edges::EdgeList::validate). Edge lists built with
+//! [EdgeList::add_edge](crate::edges::EdgeList::add_edge) always do, but deserialized ones should
+//! be validated before calling the removal algorithms. To keep this guarantee, explicit panics and
+//! unwraps are denied in this module.
+#![cfg_attr(
+    not(test),
+    deny(clippy::panic, clippy::unwrap_used, clippy::expect_used)
+)]
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::edges::{Edge, EdgeList, FilteredEdge};
+use crate::CriticalGrade;
+
+pub use adjacency::AdjacencyMatrix;
+#[cfg(feature = "parallel")]
+pub use chunked::{remove_strongly_filtration_dominated_chunked, ChunkedOptions};
+pub use cores::{remove_low_core_edges, vertex_core_numbers, vertex_core_numbers_at};
+pub use directed::{
+    is_strongly_filtration_dominated_directed, remove_strongly_filtration_dominated_directed,
+    strongly_dominating_vertex_directed, DirectedAdjacencyMatrix,
+};
+pub use fibered::{collapse_along_lines, Line, LineCollapse, NonMonotoneLine};
+pub use full::{
+    is_filtration_dominated, non_domination_region, remove_filtration_dominated,
+    remove_filtration_dominated_timed, remove_filtration_dominated_with_deletion,
+    remove_filtration_dominated_with_options, remove_filtration_dominated_with_report,
+    NonDominationRegion,
+};
+pub use graph::BifilteredGraph;
+pub use hybrid::{remove_filtration_dominated_hybrid, HybridOptions, HybridReport};
+#[cfg(feature = "mmap")]
+pub use mmap::remove_strongly_filtration_dominated_mmap;
+pub use report::{GradeBuckets, RemovalReport, RemovalTrace, RemovedEdge, StopReason, Witness};
+pub use rips::remove_strongly_dominated_rips;
+pub use single::{
+    remove_filtration_dominated_single_parameter,
+    remove_filtration_dominated_single_parameter_timed,
+    remove_filtration_dominated_single_parameter_with_report,
+};
 pub use strong::{
-    remove_strongly_filtration_dominated, remove_strongly_filtration_dominated_timed,
+    is_strongly_filtration_dominated, remove_strongly_filtration_dominated,
+    remove_strongly_filtration_dominated_timed, remove_strongly_filtration_dominated_with_deletion,
+    remove_strongly_filtration_dominated_with_options,
+    remove_strongly_filtration_dominated_with_report,
 };
+pub use until_stable::{
+    iterate_strongly_filtration_dominated, remove_strongly_filtration_dominated_until_stable,
+    IterationReport, PassOrder, PassStatistics, UntilStableIterations, UntilStableReport,
+};
+pub use validation::{validate_removal, validate_strong_removal, UndominatedEdge};
+pub use vertices::{remove_dominated_vertices, RemovedVertex, VertexRemoval};
 
+pub mod connectivity;
+pub mod reference;
 pub mod utils;
 
 mod adjacency;
+#[cfg(feature = "parallel")]
+mod chunked;
+mod cores;
+mod directed;
+mod fibered;
 mod full;
+mod graph;
+mod hybrid;
+#[cfg(feature = "bench-internals")]
+#[doc(hidden)]
+pub mod internals;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod neighbour_bits;
+mod report;
+mod rips;
+mod single;
 mod strong;
+mod until_stable;
+mod validation;
+mod vertices;
 
 /// The order in which we process the edges, and possibly remove them.
-#[derive(Debug, Clone, Copy)]
+///
+/// Except for [EdgeOrder::Maintain], the removal functions sort or shuffle the given edge list
+/// in place before going through it. Edges with the same grade are ordered by their vertices.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EdgeOrder {
     /// Go through the order in reverse lexicographic order.
     /// This is usually the fastest.
+    #[default]
     ReverseLexicographic,
-    /// Go through the edges in the order they currently have in the edge list.
+    /// Go through the edges in the order they currently have in the edge list. The removal is
+    /// usually much less effective if the list is not sorted: set
+    /// [RemovalOptions::expected_order] to check that it is.
     Maintain,
+    /// Go through the edges in lexicographic order.
+    Lexicographic,
+    /// Go through the edges in colexicographic order, in which the last parameter takes
+    /// precedence.
+    Colexicographic,
+    /// Go through the edges in reverse colexicographic order.
+    ReverseColexicographic,
+    /// Go through the edges in a random order, drawn from a generator with the given seed.
+    Random(u64),
+    /// Go first through the edges that are more likely to be dominated: those with more common
+    /// neighbours at their own grade, see [domination_scores]. Edges with the same score are
+    /// in reverse lexicographic order. Computing the scores takes about as long as building the
+    /// adjacency structure, and it is meant for removals with a timeout or a budget, so that they
+    /// check the most promising edges before stopping.
+    Heuristic,
+}
+
+impl EdgeOrder {
+    /// Puts the edges of the list in this order.
+    pub fn apply<G: CriticalGrade>(&self, edge_list: &mut EdgeList<FilteredEdge<G>>) {
+        self.sort(edge_list.edges_mut())
+    }
+
+    /// Whether the edges of the list are in this order, that is, whether [EdgeOrder::apply] would
+    /// leave the grades and vertices of the edges as they are. It is None for
+    /// [EdgeOrder::Maintain] and [EdgeOrder::Random], which do not sort the edges, so there is no
+    /// order to check.
+    pub fn is_applied<G: CriticalGrade>(
+        &self,
+        edge_list: &EdgeList<FilteredEdge<G>>,
+    ) -> Option<bool> {
+        let edges = edge_list.edges();
+        let is_sorted_by = |cmp: fn(&FilteredEdge<G>, &FilteredEdge<G>) -> Ordering| {
+            edges
+                .windows(2)
+                .all(|pair| cmp(&pair[0], &pair[1]) != Ordering::Greater)
+        };
+        let is_applied = match *self {
+            EdgeOrder::ReverseLexicographic => is_sorted_by(|a, b| b.cmp(a)),
+            EdgeOrder::Maintain | EdgeOrder::Random(_) => return None,
+            EdgeOrder::Heuristic => {
+                let scores = domination_scores(edge_list);
+                (1..edges.len()).all(|i| {
+                    cmp_heuristically((scores[i - 1], &edges[i - 1]), (scores[i], &edges[i]))
+                        != Ordering::Greater
+                })
+            }
+            EdgeOrder::Lexicographic => is_sorted_by(|a, b| a.cmp(b)),
+            EdgeOrder::Colexicographic => is_sorted_by(cmp_colexicographically),
+            EdgeOrder::ReverseColexicographic => is_sorted_by(|a, b| cmp_colexicographically(b, a)),
+        };
+        Some(is_applied)
+    }
+
+    /// The comparison function of the orders that sort the edges by their grades and vertices, or
+    /// None for the other orders.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn comparator<G: CriticalGrade>(&self) -> Option<EdgeComparator<G>> {
+        match *self {
+            EdgeOrder::ReverseLexicographic => Some(|a, b| b.cmp(a)),
+            EdgeOrder::Lexicographic => Some(|a, b| a.cmp(b)),
+            EdgeOrder::Colexicographic => Some(cmp_colexicographically),
+            EdgeOrder::ReverseColexicographic => Some(|a, b| cmp_colexicographically(b, a)),
+            EdgeOrder::Maintain | EdgeOrder::Random(_) | EdgeOrder::Heuristic => None,
+        }
+    }
+
+    fn sort<G: CriticalGrade>(&self, edges: &mut [FilteredEdge<G>]) {
+        match *self {
+            EdgeOrder::ReverseLexicographic => edges.sort_unstable_by(|a, b| b.cmp(a)),
+            EdgeOrder::Maintain => {}
+            EdgeOrder::Lexicographic => edges.sort_unstable(),
+            EdgeOrder::Colexicographic => edges.sort_unstable_by(cmp_colexicographically),
+            EdgeOrder::ReverseColexicographic => {
+                edges.sort_unstable_by(|a, b| cmp_colexicographically(b, a))
+            }
+            EdgeOrder::Random(seed) => edges.shuffle(&mut ChaCha8Rng::seed_from_u64(seed)),
+            EdgeOrder::Heuristic => {
+                let scores = edge_scores(edges);
+                let mut scored: Vec<(usize, FilteredEdge<G>)> =
+                    scores.into_iter().zip(edges.iter().cloned()).collect();
+                scored.sort_unstable_by(|a, b| cmp_heuristically((a.0, &a.1), (b.0, &b.1)));
+                for (slot, (_, edge)) in edges.iter_mut().zip(scored) {
+                    *slot = edge;
+                }
+            }
+        }
+    }
+}
+
+/// For each edge of the list, the number of common neighbours of its endpoints at the grade of the
+/// edge, that is, of vertices that form a triangle with the edge at its grade. Edges with more
+/// such neighbours are more likely to be dominated by one of them. See [EdgeOrder::Heuristic].
+pub fn domination_scores<G: CriticalGrade>(edge_list: &EdgeList<FilteredEdge<G>>) -> Vec<usize> {
+    edge_scores(edge_list.edges())
+}
+
+fn edge_scores<G: CriticalGrade>(edges: &[FilteredEdge<G>]) -> Vec<usize> {
+    let n_vertices = edges
+        .iter()
+        .map(|edge| Edge::max(edge) + 1)
+        .max()
+        .unwrap_or(0);
+    let mut adjacency_matrix = AdjacencyMatrix::new(n_vertices);
+    for edge in edges {
+        adjacency_matrix.add_edge(edge.clone());
+    }
+    edges
+        .iter()
+        .map(|edge| {
+            adjacency_matrix
+                .common_neighbours(edge)
+                .filter(|(_, grade)| grade.lte(&edge.grade))
+                .count()
+        })
+        .collect()
+}
+
+/// Higher scores first, and then reverse lexicographic order.
+fn cmp_heuristically<G: CriticalGrade>(
+    (score_a, a): (usize, &FilteredEdge<G>),
+    (score_b, b): (usize, &FilteredEdge<G>),
+) -> Ordering {
+    score_b.cmp(&score_a).then_with(|| b.cmp(a))
+}
+
+#[cfg(feature = "mmap")]
+type EdgeComparator<G> = fn(&FilteredEdge<G>, &FilteredEdge<G>) -> Ordering;
+
+fn cmp_colexicographically<G: CriticalGrade>(a: &FilteredEdge<G>, b: &FilteredEdge<G>) -> Ordering {
+    a.grade
+        .cmp_colexicographically(&b.grade)
+        .then_with(|| a.edge.cmp(&b.edge))
+}
+
+/// Options of the removal algorithms, for the `_with_options` variants of the removal functions.
+///
+/// # Reproducibility
+///
+/// Every order sorts the edges by a total order on their grades and vertices, so with the same
+/// options the removal functions remove the same edges on every run and platform, as long as the
+/// input list has the same edges. [EdgeOrder::Random] shuffles the edges with the ChaCha8
+/// generator, whose output for a given seed is fixed across versions of `rand` and platforms, but
+/// the shuffle depends on the input order. Without a `max_time` or a memory guard, which depend on
+/// the machine, published experiments only need to record the options together with the input.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemovalOptions {
+    /// The order in which the edges are processed.
+    pub order: EdgeOrder,
+    /// How the removed edges are deleted from the internal adjacency structure.
+    pub deletion_mode: DeletionMode,
+    /// If the removal takes more than this time, it stops and the original list is returned.
+    /// If None then no timeout is applied.
+    pub max_time: Option<Duration>,
+    /// The order that the edges must be in once `order` is applied, which is useful with
+    /// [EdgeOrder::Maintain] to check that a presorted list is sorted as intended. If the edges
+    /// are not in this order, see [EdgeOrder::is_applied], the removal does not start, the
+    /// original list is returned, and the report says so with [StopReason::UnexpectedOrder]. This
+    /// is also the case if it is [EdgeOrder::Maintain] or [EdgeOrder::Random], which do not sort
+    /// the edges, so no order can be checked. If None then the order is not checked.
+    pub expected_order: Option<EdgeOrder>,
+    /// Whether to record the [RemovalTrace] of the removal in the report, with the vertex that
+    /// witnessed the removal of each edge and the grade at which it did.
+    pub record_trace: bool,
+    /// If set, the removal stops once it has removed this many edges.
+    pub max_removed: Option<usize>,
+    /// If set, the removal stops once at most this many edges are left, counting both the edges
+    /// that were kept and those that were not checked yet.
+    pub target_size: Option<usize>,
+    /// If the process uses more memory than the guard allows, the removal stops and the original
+    /// list is returned. If None, or if the memory usage cannot be read on this platform, then the
+    /// memory is not checked.
+    #[cfg(feature = "memory-guard")]
+    pub memory_guard: Option<crate::resource::MemoryGuard>,
+    /// Whether to reuse the decisions on edges with identical grades, which are common once the
+    /// grades are quantized. Consecutive edges with the same grade form a bucket, so with the
+    /// lexicographic and colexicographic orders each grade is a single bucket. Within a bucket,
+    /// the vertex that dominated an edge is checked first on the next edges that share one of its
+    /// endpoints, and if it strongly dominates them they are removed without searching for other
+    /// vertices. The same edges are removed as without buckets, but the report may list other
+    /// dominating vertices. The report counts the buckets and the reused decisions, see
+    /// [GradeBuckets].
+    ///
+    /// On a torus of 400 points with grades quantized to 4 to 64 values per parameter, this makes
+    /// the full removal 1.5 to 2 times faster, but the strong removal about 15% slower, since it
+    /// already finds a strongly dominating vertex quickly.
+    pub bucket_grades: bool,
+}
+
+impl RemovalOptions {
+    /// Whether a removal that has removed `n_removed` of the `n_edges` edges of the list must
+    /// stop, according to `max_removed` and `target_size`.
+    fn budget_exhausted(&self, n_removed: usize, n_edges: usize) -> bool {
+        matches!(self.max_removed, Some(max_removed) if n_removed >= max_removed)
+            || matches!(self.target_size, Some(target_size) if n_edges - n_removed <= target_size)
+    }
+}
+
+/// How edges are deleted from the adjacency structure used by the removal algorithms.
+///
+/// On sampled datasets of 800 points, where over 90% of the edges are removed, deferred deletion
+/// is 5-10% slower than immediate deletion: iterating over the tombstones costs more than the
+/// shifts it saves. Use the `deletion` experiment of the experiment runner to compare them. Only
+/// the deferred mode keeps track of deleted edges, so the default mode does not pay for it.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeletionMode {
+    /// Delete the edge as soon as it is removed.
+    #[default]
+    Immediate,
+    /// Mark the removed edge as deleted, and compact the neighbourhood of a vertex once half of
+    /// its entries are deleted. This avoids shifting the neighbourhoods on every removal.
+    Deferred,
 }
@@ -0,0 +1,217 @@
+//! A naive implementation of filtration-domination removal, meant as a correctness oracle.
+//!
+//! The function [edge_collapse_naive] checks the definition of filtration-domination directly: an
+//! edge is removed if, at every grade greater than or equal to its own, some vertex dominates it
+//! in the graph formed by the remaining edges. The graph only changes at grades whose coordinates
+//! are coordinates of the grades of the edges, so it is enough to check the points of that grid.
+//!
+//! It is exponential in the number of parameters and very slow, but it shares no code with the
+//! optimized algorithms, so it is useful to test them, and any other removal algorithm, on small
+//! graphs. When given the same order, [edge_collapse_naive] removes exactly the same edges as
+//! [remove_filtration_dominated](crate::removal::remove_filtration_dominated) and
+//! [remove_filtration_dominated_single_parameter](crate::removal::remove_filtration_dominated_single_parameter).
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use rustc_hash::FxHashSet;
+
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::removal::EdgeOrder;
+use crate::{CriticalGrade, OneCriticalGrade, Value};
+
+/// Go through the given edge list in the given order, and remove the edges that are
+/// filtration-dominated in the graph formed by the edges that have not been removed yet.
+///
+/// Returns a reduced edge list.
+pub fn edge_collapse_naive<VF: Value, const N: usize>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    order: EdgeOrder,
+) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>> {
+    edge_collapse_naive_timed(edge_list, order, None)
+}
+
+/// As [edge_collapse_naive], but if we take more than the time given in `max_time` then
+/// execution stops and a clone of the original list is returned.
+/// If `max_time` is None then no timeout is applied.
+pub fn edge_collapse_naive_timed<VF: Value, const N: usize>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>,
+    order: EdgeOrder,
+    max_time: Option<Duration>,
+) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>> {
+    order.apply(edge_list);
+
+    let grid = Grid::new(edge_list);
+    let mut removed = vec![false; edge_list.len()];
+
+    let start = std::time::Instant::now();
+    for (idx, edge) in edge_list.edge_iter().enumerate() {
+        if let Some(max_time) = max_time {
+            if start.elapsed() > max_time {
+                return edge_list.clone();
+            }
+        }
+        let remaining = edge_list
+            .edge_iter()
+            .zip(removed.iter())
+            .filter(|(_, &is_removed)| !is_removed)
+            .map(|(e, _)| e);
+        let graph: Vec<_> = remaining.collect();
+        removed[idx] = grid
+            .points_above(&edge.grade)
+            .iter()
+            .all(|point| is_dominated_at(edge_list.n_vertices, &graph, edge, point));
+    }
+
+    edge_list
+        .edge_iter()
+        .zip(removed)
+        .filter(|(_, is_removed)| !is_removed)
+        .map(|(e, _)| *e)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// The coordinates, in each parameter, of the grades of a set of edges.
+struct Grid<VF, const N: usize> {
+    coordinates: [Vec<VF>; N],
+}
+
+impl<VF: Value, const N: usize> Grid<VF, N> {
+    fn new(edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, N>>>) -> Self {
+        let mut values: [BTreeSet<VF>; N] = [(); N].map(|_| BTreeSet::new());
+        for edge in edge_list.edge_iter() {
+            for (parameter_values, value) in values.iter_mut().zip(edge.grade.0) {
+                parameter_values.insert(value);
+            }
+        }
+        Self {
+            coordinates: values.map(|parameter_values| parameter_values.into_iter().collect()),
+        }
+    }
+
+    /// All points of the grid that are greater than or equal to the given grade.
+    fn points_above(&self, grade: &OneCriticalGrade<VF, N>) -> Vec<OneCriticalGrade<VF, N>> {
+        let mut points = vec![*grade];
+        for (parameter, values) in self.coordinates.iter().enumerate() {
+            let values_above: Vec<VF> = values
+                .iter()
+                .copied()
+                .filter(|value| *value >= grade.0[parameter])
+                .collect();
+            points = points
+                .iter()
+                .flat_map(|point| {
+                    values_above.iter().map(move |value| {
+                        let mut next = *point;
+                        next.0[parameter] = *value;
+                        next
+                    })
+                })
+                .collect();
+        }
+        points
+    }
+}
+
+/// Whether the edge is dominated in the graph formed by the edges of `graph` whose grade is less
+/// than or equal to `point`, that is, whether there is a vertex, other than the endpoints of the
+/// edge, that is adjacent to every vertex of the closed neighbourhood of the edge.
+fn is_dominated_at<G: CriticalGrade>(
+    n_vertices: usize,
+    graph: &[&FilteredEdge<G>],
+    edge: &FilteredEdge<G>,
+    point: &G,
+) -> bool {
+    let mut closed_neighbours: Vec<FxHashSet<usize>> =
+        (0..n_vertices).map(|v| FxHashSet::from_iter([v])).collect();
+    for e in graph.iter().filter(|e| e.grade.lte(point)) {
+        let (u, v) = (e.edge.0, e.edge.1);
+        closed_neighbours[u].insert(v);
+        closed_neighbours[v].insert(u);
+    }
+
+    let (u, v) = (edge.edge.0, edge.edge.1);
+    let edge_neighbourhood: Vec<usize> = closed_neighbours[u]
+        .intersection(&closed_neighbours[v])
+        .copied()
+        .collect();
+    edge_neighbourhood
+        .iter()
+        .filter(|&&w| w != u && w != v)
+        .any(|&w| {
+            edge_neighbourhood
+                .iter()
+                .all(|x| closed_neighbours[w].contains(x))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::edges::transform::project;
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::removal::reference::edge_collapse_naive;
+    use crate::removal::{
+        remove_filtration_dominated, remove_filtration_dominated_single_parameter, EdgeOrder,
+    };
+    use crate::OneCriticalGrade;
+
+    fn random_graph(
+        rng: &mut StdRng,
+        n_vertices: usize,
+        density: f64,
+    ) -> EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> {
+        let mut edge_list = EdgeList::new(n_vertices);
+        for u in 0..n_vertices {
+            for v in (u + 1)..n_vertices {
+                if rng.gen_bool(density) {
+                    edge_list.add_edge(FilteredEdge {
+                        grade: OneCriticalGrade([rng.gen_range(0..4), rng.gen_range(0..4)]),
+                        edge: BareEdge(u, v),
+                    });
+                }
+            }
+        }
+        edge_list
+    }
+
+    fn bare_edges<G>(edge_list: &EdgeList<FilteredEdge<G>>) -> Vec<BareEdge> {
+        edge_list.edge_iter().map(|e| e.edge).collect()
+    }
+
+    #[test]
+    fn naive_agrees_with_filtration_domination() {
+        let mut rng = StdRng::seed_from_u64(1286);
+        let mut n_removed = 0;
+        for _ in 0..50 {
+            let mut edge_list = random_graph(&mut rng, 8, 0.6);
+            let mut naive_input = edge_list.clone();
+            let optimized =
+                remove_filtration_dominated(&mut edge_list, EdgeOrder::ReverseLexicographic);
+            let naive = edge_collapse_naive(&mut naive_input, EdgeOrder::ReverseLexicographic);
+            assert_eq!(bare_edges(&optimized), bare_edges(&naive));
+            n_removed += naive_input.len() - naive.len();
+        }
+        assert!(n_removed > 0);
+    }
+
+    #[test]
+    fn naive_agrees_with_single_parameter_collapse() {
+        let mut rng = StdRng::seed_from_u64(1286);
+        let mut n_removed = 0;
+        for _ in 0..50 {
+            let mut edge_list = project(&random_graph(&mut rng, 8, 0.6), [0]);
+            let mut naive_input = edge_list.clone();
+            let optimized = remove_filtration_dominated_single_parameter(
+                &mut edge_list,
+                EdgeOrder::ReverseLexicographic,
+            );
+            let naive = edge_collapse_naive(&mut naive_input, EdgeOrder::ReverseLexicographic);
+            assert_eq!(bare_edges(&optimized), bare_edges(&naive));
+            n_removed += naive_input.len() - naive.len();
+        }
+        assert!(n_removed > 0);
+    }
+}
@@ -0,0 +1,721 @@
+use std::io;
+use std::time::Duration;
+
+use rustc_hash::FxHashMap;
+
+use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+use crate::removal::adjacency::AdjacencyMatrix;
+use crate::removal::strong::strongly_dominates;
+use crate::removal::RemovalOptions;
+#[cfg(feature = "memory-guard")]
+use crate::resource::MemoryGuardError;
+use crate::trace::pass;
+use crate::{CriticalGrade, OneCriticalGrade, Value};
+
+/// The output of a removal algorithm, together with information about what was removed.
+/// Returned by the `_with_report` variants of the removal functions.
+///
+/// The remaining and the removed edges together are the edges of the original list.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemovalReport<G> {
+    /// The edges that were not removed.
+    pub remaining: EdgeList<FilteredEdge<G>>,
+    /// The removed edges, in the order in which they were removed.
+    /// It is empty if the algorithm timed out.
+    pub removed: Vec<RemovedEdge<G>>,
+    /// The wall-clock time taken by the algorithm, not including the construction of the
+    /// internal adjacency structure.
+    pub duration: Duration,
+    /// Why the algorithm stopped before checking every edge, or None if it checked them all.
+    pub stop_reason: Option<StopReason>,
+    /// The witnesses of the removal of each edge, if `record_trace` is set in the
+    /// [RemovalOptions]. It is None otherwise, and for the functions that only return the
+    /// remaining edges.
+    pub trace: Option<RemovalTrace<G>>,
+    /// How many decisions were reused across the edges with identical grades, if
+    /// `bucket_grades` is set in the [RemovalOptions]. It is None otherwise.
+    pub buckets: Option<GradeBuckets>,
+}
+
+impl<G> RemovalReport<G> {
+    /// The number of removed edges.
+    pub fn n_removed(&self) -> usize {
+        self.removed.len()
+    }
+
+    /// Whether the algorithm was stopped because it exceeded its maximum time.
+    pub fn timed_out(&self) -> bool {
+        self.stop_reason == Some(StopReason::TimedOut)
+    }
+}
+
+/// Why a removal stopped before checking every edge. See [RemovalReport::stop_reason].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StopReason {
+    /// The removal exceeded the maximum time of the [RemovalOptions]. The remaining edges are a
+    /// clone of the original list.
+    TimedOut,
+    /// The process exceeded the memory allowed by the guard of the [RemovalOptions]. The
+    /// remaining edges are a clone of the original list. It never happens without the
+    /// `memory-guard` feature.
+    MemoryLimitExceeded,
+    /// The removal did not start because the edges were not in the expected order of the
+    /// [RemovalOptions]. The remaining edges are a clone of the original list.
+    UnexpectedOrder,
+    /// The removal reached the maximum number of removed edges, or the target size, of the
+    /// [RemovalOptions]. The remaining edges are those that were kept and, after them, those that
+    /// were not checked.
+    BudgetExhausted,
+}
+
+/// The runs of consecutive edges with identical grades that a removal went through, and how many
+/// removals it decided by reusing the dominating vertex of a previous edge of the same run. See
+/// [RemovalOptions::bucket_grades].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradeBuckets {
+    /// The number of edges that were checked.
+    pub n_checked: usize,
+    /// The number of buckets, that is, of runs of consecutive checked edges with the same grade.
+    pub n_buckets: usize,
+    /// The number of edges that were removed because a vertex that dominated a previous edge of
+    /// their bucket also dominates them.
+    pub n_reused: usize,
+}
+
+impl GradeBuckets {
+    /// The average number of edges in a bucket, which is 1 if every edge has a different grade.
+    pub fn dedup_factor(&self) -> f64 {
+        if self.n_buckets == 0 {
+            1.0
+        } else {
+            self.n_checked as f64 / self.n_buckets as f64
+        }
+    }
+}
+
+/// An edge removed by a removal algorithm, and why it was removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemovedEdge<G> {
+    /// The removed edge.
+    pub edge: FilteredEdge<G>,
+    /// The vertices that dominate the edge, in increasing order. If the edge was strongly
+    /// filtration-dominated, this is a single vertex. Otherwise, these are vertices that
+    /// dominate the edge at some grade, and together they dominate it at every grade greater
+    /// than or equal to its own.
+    pub dominators: Vec<usize>,
+}
+
+/// For each removed edge, the vertices that witnessed its (strong) filtration-domination, together
+/// with the grades at which they did. Recorded when `record_trace` is set in the [RemovalOptions],
+/// and useful to debug the removal algorithms or to check their output by hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemovalTrace<G> {
+    /// The witnesses, in the order in which the edges were removed, and by increasing vertex for
+    /// each edge.
+    pub witnesses: Vec<Witness<G>>,
+}
+
+/// A vertex that dominates a removed edge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Witness<G> {
+    /// The removed edge.
+    pub edge: FilteredEdge<G>,
+    /// The dominating vertex, a common neighbour of the endpoints of the edge.
+    pub vertex: usize,
+    /// The grade from which the vertex and the edge span a triangle: the join of the grade of the
+    /// edge and the grades of the edges from its endpoints to the vertex.
+    pub grade: G,
+}
+
+impl<VF: Value, const N: usize> RemovalTrace<OneCriticalGrade<VF, N>> {
+    /// Writes the trace as CSV, one witness per line, with a header. The columns are the endpoints
+    /// `u` and `v` of the removed edge, its grade as `grade_0`, `grade_1`, and so on, the
+    /// `witness` vertex, and the grade of the witness as `witness_grade_0`, `witness_grade_1`, and
+    /// so on.
+    pub fn write_csv<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "u,v")?;
+        for parameter in 0..N {
+            write!(w, ",grade_{}", parameter)?;
+        }
+        write!(w, ",witness")?;
+        for parameter in 0..N {
+            write!(w, ",witness_grade_{}", parameter)?;
+        }
+        writeln!(w)?;
+        for witness in self.witnesses.iter() {
+            let BareEdge(u, v) = witness.edge.edge;
+            write!(w, "{},{}", u, v)?;
+            for value in witness.edge.grade.0.iter() {
+                write!(w, ",{}", value)?;
+            }
+            write!(w, ",{}", witness.vertex)?;
+            for value in witness.grade.0.iter() {
+                write!(w, ",{}", value)?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// The main loop of the removal algorithms. Goes through the edges in the given order, and
+/// removes those for which `dominators` returns the vertices that dominate them.
+/// Edges are only recorded in the report if `record_removed` is true.
+pub(crate) fn remove_dominated<G: CriticalGrade, F>(
+    edge_list: &mut EdgeList<FilteredEdge<G>>,
+    options: &RemovalOptions,
+    record_removed: bool,
+    dominators: F,
+) -> RemovalReport<G>
+where
+    F: Fn(&AdjacencyMatrix<G>, &FilteredEdge<G>) -> Option<Vec<usize>>,
+{
+    let pass = pass!(
+        "remove_dominated",
+        n_vertices = edge_list.n_vertices,
+        input_edges = edge_list.len();
+        remaining_edges
+    );
+    let report = remove_dominated_untraced(edge_list, options, record_removed, dominators);
+    pass.record("remaining_edges", report.remaining.len());
+    report
+}
+
+fn remove_dominated_untraced<G: CriticalGrade, F>(
+    edge_list: &mut EdgeList<FilteredEdge<G>>,
+    options: &RemovalOptions,
+    record_removed: bool,
+    dominators: F,
+) -> RemovalReport<G>
+where
+    F: Fn(&AdjacencyMatrix<G>, &FilteredEdge<G>) -> Option<Vec<usize>>,
+{
+    options.order.apply(edge_list);
+    if let Some(expected_order) = options.expected_order {
+        if expected_order.is_applied(edge_list) != Some(true) {
+            return RemovalReport {
+                remaining: edge_list.clone(),
+                removed: Vec::new(),
+                duration: Duration::ZERO,
+                stop_reason: Some(StopReason::UnexpectedOrder),
+                trace: None,
+                buckets: None,
+            };
+        }
+    }
+
+    let mut remaining_edges: Vec<FilteredEdge<G>> = Vec::with_capacity(edge_list.len());
+    let mut removed = Vec::new();
+    let mut trace = options.record_trace.then(|| RemovalTrace {
+        witnesses: Vec::new(),
+    });
+    let mut adjacency_matrix =
+        AdjacencyMatrix::from_edge_list_with_deletion_mode(edge_list, options.deletion_mode);
+
+    let mut buckets = options.bucket_grades.then(GradeBuckets::default);
+    let mut bucket_grade: Option<&G> = None;
+    // The vertex that dominated the last removed edge of the bucket incident to each vertex.
+    let mut bucket_dominators: FxHashMap<usize, usize> = FxHashMap::default();
+
+    let mut n_removed = 0;
+    let mut stop_reason = None;
+    let start = std::time::Instant::now();
+    for (idx, edge) in edge_list.edge_iter().enumerate() {
+        if options.budget_exhausted(n_removed, edge_list.len()) {
+            // The edges that were not checked remain, in their order.
+            remaining_edges.extend(edge_list.edges()[idx..].iter().cloned());
+            stop_reason = Some(StopReason::BudgetExhausted);
+            break;
+        }
+        if let Some(max_time) = options.max_time {
+            if start.elapsed() > max_time {
+                return RemovalReport {
+                    remaining: edge_list.clone(),
+                    removed: Vec::new(),
+                    duration: start.elapsed(),
+                    stop_reason: Some(StopReason::TimedOut),
+                    trace: None,
+                    buckets: None,
+                };
+            }
+        }
+        if memory_limit_exceeded(options, idx) {
+            return RemovalReport {
+                remaining: edge_list.clone(),
+                removed: Vec::new(),
+                duration: start.elapsed(),
+                stop_reason: Some(StopReason::MemoryLimitExceeded),
+                trace: None,
+                buckets: None,
+            };
+        }
+        let reused = match buckets.as_mut() {
+            Some(buckets) => {
+                buckets.n_checked += 1;
+                if bucket_grade != Some(&edge.grade) {
+                    buckets.n_buckets += 1;
+                    bucket_grade = Some(&edge.grade);
+                    bucket_dominators.clear();
+                }
+                let reused = reused_dominator(&adjacency_matrix, edge, &bucket_dominators);
+                if reused.is_some() {
+                    buckets.n_reused += 1;
+                }
+                reused
+            }
+            None => None,
+        };
+        let dominating = match reused {
+            Some(vertex) => Some(vec![vertex]),
+            None => dominators(&adjacency_matrix, edge),
+        };
+        match dominating {
+            Some(mut vertices) => {
+                vertices.sort_unstable();
+                vertices.dedup();
+                if let (Some(_), [vertex]) = (&buckets, vertices.as_slice()) {
+                    let BareEdge(u, v) = edge.edge;
+                    bucket_dominators.insert(u, *vertex);
+                    bucket_dominators.insert(v, *vertex);
+                }
+                if let Some(trace) = trace.as_mut() {
+                    for &vertex in vertices.iter() {
+                        trace.witnesses.push(Witness {
+                            edge: edge.clone(),
+                            vertex,
+                            grade: witness_grade(&adjacency_matrix, edge, vertex),
+                        });
+                    }
+                }
+                adjacency_matrix.delete_edge(edge);
+                n_removed += 1;
+                if record_removed {
+                    removed.push(RemovedEdge {
+                        edge: edge.clone(),
+                        dominators: vertices,
+                    });
+                }
+            }
+            None => remaining_edges.push(edge.clone()),
+        }
+    }
+
+    remaining_edges.shrink_to_fit();
+    RemovalReport {
+        remaining: remaining_edges.into(),
+        removed,
+        duration: start.elapsed(),
+        stop_reason,
+        trace,
+        buckets,
+    }
+}
+
+/// A vertex that dominated a previous edge of the bucket, incident to one of the endpoints of the
+/// edge, if it strongly dominates the edge too. Strong domination implies domination, so the edge
+/// is removed by both algorithms, whichever vertices they would have found.
+fn reused_dominator<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+    bucket_dominators: &FxHashMap<usize, usize>,
+) -> Option<usize> {
+    let BareEdge(u, v) = edge.edge;
+    let from_u = bucket_dominators.get(&u).copied();
+    let from_v = bucket_dominators
+        .get(&v)
+        .copied()
+        .filter(|&vertex| Some(vertex) != from_u);
+    [from_u, from_v]
+        .into_iter()
+        .flatten()
+        .find(|&vertex| strongly_dominates(adjacency_matrix, edge, vertex))
+}
+
+/// The join of the grade of the edge and the grades of the edges from its endpoints to the vertex.
+fn witness_grade<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+    vertex: usize,
+) -> G {
+    let BareEdge(u, v) = edge.edge;
+    [u, v]
+        .iter()
+        .filter_map(|&endpoint| adjacency_matrix.edge_grade(endpoint, vertex))
+        .fold(edge.grade.clone(), |grade, neighbour_grade| {
+            grade.join(neighbour_grade)
+        })
+}
+
+/// Whether the memory guard of the options, if any, fails at the given iteration. If the memory
+/// usage cannot be read, the removal goes on.
+#[cfg(feature = "memory-guard")]
+fn memory_limit_exceeded(options: &RemovalOptions, iteration: usize) -> bool {
+    matches!(
+        options.memory_guard.map(|guard| guard.check(iteration)),
+        Some(Err(MemoryGuardError::LimitExceeded { .. }))
+    )
+}
+
+#[cfg(not(feature = "memory-guard"))]
+fn memory_limit_exceeded(_options: &RemovalOptions, _iteration: usize) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ordered_float::OrderedFloat;
+    use rustc_hash::FxHashSet;
+
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::{quantize, BareEdge, EdgeList, FilteredEdge, Quantization, Rounding};
+    use crate::removal::{
+        domination_scores, remove_filtration_dominated, remove_filtration_dominated_with_options,
+        remove_filtration_dominated_with_report, remove_strongly_filtration_dominated_with_options,
+        remove_strongly_filtration_dominated_with_report, EdgeOrder, RemovalOptions, RemovalReport,
+        StopReason,
+    };
+    use crate::{CriticalGrade, OneCriticalGrade};
+
+    fn torus_edges() -> EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>> {
+        get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 60 },
+            Threshold::Percentile(0.5),
+            None,
+            false,
+        )
+        .unwrap()
+    }
+
+    fn assert_consistent_report(
+        original: &EdgeList<FilteredEdge<OneCriticalGrade<OrderedFloat<f64>, 2>>>,
+        report: &RemovalReport<OneCriticalGrade<OrderedFloat<f64>, 2>>,
+    ) {
+        assert!(!report.timed_out());
+        assert_eq!(report.remaining.len() + report.n_removed(), original.len());
+
+        let original_edges: FxHashSet<BareEdge> = original.edge_iter().map(|e| e.edge).collect();
+        for removed in report.removed.iter() {
+            assert!(original_edges.contains(&removed.edge.edge));
+            assert!(!removed.dominators.is_empty());
+            // Every dominator is a common neighbour of the endpoints of the removed edge.
+            for &w in removed.dominators.iter() {
+                let BareEdge(u, v) = removed.edge.edge;
+                assert!(original_edges.contains(&BareEdge(u.min(w), u.max(w))));
+                assert!(original_edges.contains(&BareEdge(v.min(w), v.max(w))));
+            }
+        }
+    }
+
+    #[test]
+    fn removal_reports_are_consistent() {
+        let mut edges = torus_edges();
+        let original = edges.clone();
+
+        let report = remove_filtration_dominated_with_report(
+            &mut edges,
+            EdgeOrder::ReverseLexicographic,
+            None,
+        );
+        assert_consistent_report(&original, &report);
+        let remaining = remove_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+        assert_eq!(report.remaining.edges(), remaining.edges());
+
+        let strong_report = remove_strongly_filtration_dominated_with_report(
+            &mut edges,
+            EdgeOrder::ReverseLexicographic,
+            None,
+        );
+        assert_consistent_report(&original, &strong_report);
+        assert!(strong_report
+            .removed
+            .iter()
+            .all(|removed| removed.dominators.len() == 1));
+    }
+
+    #[test]
+    fn removal_report_timeout() {
+        let mut edges = torus_edges();
+        let original = edges.clone();
+        let report = remove_filtration_dominated_with_report(
+            &mut edges,
+            EdgeOrder::Maintain,
+            Some(Duration::ZERO),
+        );
+        assert!(report.timed_out());
+        assert!(report.removed.is_empty());
+        assert_eq!(report.remaining.edges(), original.edges());
+    }
+
+    #[test]
+    fn removal_expected_order() {
+        let original = torus_edges();
+        let mut sorted = original.clone();
+        EdgeOrder::ReverseLexicographic.apply(&mut sorted);
+        assert_eq!(
+            EdgeOrder::ReverseLexicographic.is_applied(&sorted),
+            Some(true)
+        );
+        assert_eq!(EdgeOrder::Lexicographic.is_applied(&sorted), Some(false));
+        assert_eq!(EdgeOrder::Maintain.is_applied(&sorted), None);
+        assert_eq!(EdgeOrder::Random(1).is_applied(&sorted), None);
+
+        let options = RemovalOptions {
+            order: EdgeOrder::Maintain,
+            expected_order: Some(EdgeOrder::Lexicographic),
+            ..RemovalOptions::default()
+        };
+        let report = remove_filtration_dominated_with_options(&mut sorted.clone(), &options);
+        assert_eq!(report.stop_reason, Some(StopReason::UnexpectedOrder));
+        assert!(report.removed.is_empty());
+        assert_eq!(report.remaining.edges(), sorted.edges());
+
+        let presorted = RemovalOptions {
+            expected_order: Some(EdgeOrder::ReverseLexicographic),
+            ..options
+        };
+        // An order that does not sort the edges cannot be checked.
+        let unsorted = RemovalOptions {
+            expected_order: Some(EdgeOrder::Maintain),
+            ..options
+        };
+        let unsorted_report =
+            remove_filtration_dominated_with_options(&mut sorted.clone(), &unsorted);
+        assert_eq!(
+            unsorted_report.stop_reason,
+            Some(StopReason::UnexpectedOrder)
+        );
+
+        let presorted_report = remove_filtration_dominated_with_options(&mut sorted, &presorted);
+        assert_eq!(presorted_report.stop_reason, None);
+        assert_consistent_report(&original, &presorted_report);
+        let sorted_report = remove_filtration_dominated_with_options(
+            &mut original.clone(),
+            &RemovalOptions::default(),
+        );
+        assert_eq!(
+            presorted_report.remaining.edges(),
+            sorted_report.remaining.edges()
+        );
+    }
+
+    #[test]
+    fn removal_budget() {
+        let original = torus_edges();
+        let full_report = remove_strongly_filtration_dominated_with_options(
+            &mut original.clone(),
+            &RemovalOptions::default(),
+        );
+        assert_eq!(full_report.stop_reason, None);
+        assert!(full_report.n_removed() > 10);
+
+        let max_removed = RemovalOptions {
+            max_removed: Some(10),
+            ..RemovalOptions::default()
+        };
+        let report =
+            remove_strongly_filtration_dominated_with_options(&mut original.clone(), &max_removed);
+        assert_eq!(report.stop_reason, Some(StopReason::BudgetExhausted));
+        assert_eq!(report.n_removed(), 10);
+        assert_eq!(report.removed[..], full_report.removed[..10]);
+        assert_consistent_report(&original, &report);
+
+        let target = original.len() - 5;
+        let target_size = RemovalOptions {
+            target_size: Some(target),
+            ..RemovalOptions::default()
+        };
+        let target_report =
+            remove_strongly_filtration_dominated_with_options(&mut original.clone(), &target_size);
+        assert_eq!(target_report.stop_reason, Some(StopReason::BudgetExhausted));
+        assert_eq!(target_report.remaining.len(), target);
+    }
+
+    #[test]
+    fn removal_trace() {
+        let mut triangle: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> = vec![
+            FilteredEdge {
+                grade: OneCriticalGrade([0, 0]),
+                edge: BareEdge(0, 2),
+            },
+            FilteredEdge {
+                grade: OneCriticalGrade([0, 0]),
+                edge: BareEdge(1, 2),
+            },
+            FilteredEdge {
+                grade: OneCriticalGrade([1, 1]),
+                edge: BareEdge(0, 1),
+            },
+        ]
+        .into();
+        let options = RemovalOptions {
+            record_trace: true,
+            ..RemovalOptions::default()
+        };
+        let report = remove_strongly_filtration_dominated_with_options(&mut triangle, &options);
+        let trace = report.trace.unwrap();
+        assert_eq!(trace.witnesses.len(), 1);
+        assert_eq!(trace.witnesses[0].vertex, 2);
+        let mut csv = Vec::new();
+        trace.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "u,v,grade_0,grade_1,witness,witness_grade_0,witness_grade_1\n0,1,1,1,2,1,1\n"
+        );
+
+        let original = torus_edges();
+        let torus_report =
+            remove_filtration_dominated_with_options(&mut original.clone(), &options);
+        let torus_trace = torus_report.trace.unwrap();
+        let n_dominators: usize = torus_report
+            .removed
+            .iter()
+            .map(|r| r.dominators.len())
+            .sum();
+        assert_eq!(torus_trace.witnesses.len(), n_dominators);
+        assert!(torus_trace
+            .witnesses
+            .iter()
+            .all(|witness| witness.edge.grade.lte(&witness.grade)));
+        assert!(remove_filtration_dominated_with_report(
+            &mut original.clone(),
+            EdgeOrder::default(),
+            None
+        )
+        .trace
+        .is_none());
+    }
+
+    #[cfg(feature = "memory-guard")]
+    #[test]
+    fn removal_memory_guard() {
+        use crate::resource::MemoryGuard;
+
+        let original = torus_edges();
+        let options = RemovalOptions {
+            order: EdgeOrder::Maintain,
+            memory_guard: Some(MemoryGuard::new(1)),
+            ..RemovalOptions::default()
+        };
+        let report = remove_filtration_dominated_with_options(&mut original.clone(), &options);
+        assert_eq!(report.stop_reason, Some(StopReason::MemoryLimitExceeded));
+        assert!(!report.timed_out());
+        assert_eq!(report.remaining.edges(), original.edges());
+
+        let unlimited = RemovalOptions {
+            memory_guard: Some(MemoryGuard::new(usize::MAX)),
+            ..RemovalOptions::default()
+        };
+        let unlimited_report =
+            remove_filtration_dominated_with_options(&mut original.clone(), &unlimited);
+        assert_eq!(unlimited_report.stop_reason, None);
+        assert_consistent_report(&original, &unlimited_report);
+    }
+
+    #[test]
+    fn removal_with_every_order() {
+        let original = torus_edges();
+        for order in [
+            EdgeOrder::ReverseLexicographic,
+            EdgeOrder::Lexicographic,
+            EdgeOrder::Colexicographic,
+            EdgeOrder::ReverseColexicographic,
+            EdgeOrder::Random(1305),
+            EdgeOrder::Heuristic,
+        ] {
+            let mut edges = original.clone();
+            let report = remove_filtration_dominated_with_report(&mut edges, order, None);
+            assert_consistent_report(&original, &report);
+        }
+
+        let mut colex = original.clone();
+        EdgeOrder::Colexicographic.apply(&mut colex);
+        let mut expected = original.clone();
+        expected.sort_colexicographically();
+        assert_eq!(colex.edges(), expected.edges());
+
+        let mut first = original.clone();
+        let mut second = original.clone();
+        EdgeOrder::Random(1305).apply(&mut first);
+        EdgeOrder::Random(1305).apply(&mut second);
+        assert_eq!(first.edges(), second.edges());
+
+        let mut heuristic = original.clone();
+        EdgeOrder::Heuristic.apply(&mut heuristic);
+        assert_eq!(EdgeOrder::Heuristic.is_applied(&heuristic), Some(true));
+        assert_eq!(EdgeOrder::Heuristic.is_applied(&original), Some(false));
+        let scores = domination_scores(&heuristic);
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    fn seeded_removal_is_reproducible() {
+        let original = torus_edges();
+        let options = RemovalOptions {
+            order: EdgeOrder::Random(1306),
+            ..Default::default()
+        };
+        let first = remove_filtration_dominated_with_options(&mut original.clone(), &options);
+        let second = remove_filtration_dominated_with_options(&mut original.clone(), &options);
+        assert_consistent_report(&original, &first);
+        assert_eq!(first.remaining.edges(), second.remaining.edges());
+        assert_eq!(first.removed, second.removed);
+
+        let default_report = remove_strongly_filtration_dominated_with_options(
+            &mut original.clone(),
+            &Default::default(),
+        );
+        let report = remove_strongly_filtration_dominated_with_report(
+            &mut original.clone(),
+            EdgeOrder::ReverseLexicographic,
+            None,
+        );
+        assert_eq!(default_report.remaining.edges(), report.remaining.edges());
+    }
+
+    #[test]
+    fn bucketed_removal_removes_the_same_edges() {
+        let mut edges = torus_edges();
+        quantize(
+            &mut edges,
+            [Quantization::MaxValues(4), Quantization::MaxValues(4)],
+            Rounding::Up,
+        );
+        let bucketed = RemovalOptions {
+            bucket_grades: true,
+            ..RemovalOptions::default()
+        };
+        for (report, default_report) in [
+            (
+                remove_strongly_filtration_dominated_with_options(&mut edges.clone(), &bucketed),
+                remove_strongly_filtration_dominated_with_options(
+                    &mut edges.clone(),
+                    &RemovalOptions::default(),
+                ),
+            ),
+            (
+                remove_filtration_dominated_with_options(&mut edges.clone(), &bucketed),
+                remove_filtration_dominated_with_options(
+                    &mut edges.clone(),
+                    &RemovalOptions::default(),
+                ),
+            ),
+        ] {
+            assert_consistent_report(&edges, &report);
+            assert_eq!(report.remaining.edges(), default_report.remaining.edges());
+            assert_eq!(default_report.buckets, None);
+            let buckets = report.buckets.unwrap();
+            assert_eq!(buckets.n_checked, edges.len());
+            assert!(buckets.n_buckets <= 16);
+            assert!(buckets.n_reused > 0);
+            assert!(buckets.dedup_factor() > 1.);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Strong removal on Vietoris–Rips filtrations: the single-parameter filtrations of the complete
+//! graph on the points of a distance matrix, where each edge enters at the distance between its
+//! endpoints.
+use crate::distance_matrix::DistanceMatrix;
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::removal::{remove_strongly_filtration_dominated, EdgeOrder};
+use crate::{OneCriticalGrade, Value};
+
+/// Removes the strongly filtration-dominated edges of the Vietoris–Rips filtration of the distance
+/// matrix, keeping only the edges of length at most `threshold`, if given. Returns the remaining
+/// edges, with the distances as single-parameter grades.
+///
+/// This is a convenience wrapper: it collects the edges of [DistanceMatrix::edges] into an
+/// [EdgeList] and runs [remove_strongly_filtration_dominated] on it, in reverse lexicographic
+/// order. The removal is the generic one, and the only saving over a bifiltered edge list whose
+/// first parameter is constant is that the grades have one parameter, so they are smaller and
+/// cheaper to compare.
+pub fn remove_strongly_dominated_rips<T: Value>(
+    distance_matrix: &DistanceMatrix<T>,
+    threshold: Option<T>,
+) -> EdgeList<FilteredEdge<OneCriticalGrade<T, 1>>> {
+    let mut edge_list =
+        EdgeList::from_iterator(distance_matrix.edges().filter(|edge| match threshold {
+            Some(t) => edge.grade.0[0] <= t,
+            None => true,
+        }));
+    edge_list.n_vertices = distance_matrix.len();
+    remove_strongly_filtration_dominated(&mut edge_list, EdgeOrder::ReverseLexicographic)
+}
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::OrderedFloat;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::datasets::sampling::sample_noisy_sphere_with_rng;
+    use crate::distance_matrix::DistanceMatrix;
+    use crate::edges::EdgeList;
+    use crate::removal::{
+        remove_strongly_dominated_rips, remove_strongly_filtration_dominated, EdgeOrder,
+    };
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn rips_removal_agrees_with_bifiltered_removal() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let points = sample_noisy_sphere_with_rng::<f64, _, 3>(80, 0.9, 0.75, 0.3, &mut rng);
+        let condensed: Vec<OrderedFloat<f64>> = points
+            .distance_matrix()
+            .to_condensed()
+            .into_iter()
+            .map(OrderedFloat)
+            .collect();
+        let distance_matrix = DistanceMatrix::from_condensed(&condensed).unwrap();
+        let threshold = *distance_matrix.percentile(0.5);
+
+        let remaining = remove_strongly_dominated_rips(&distance_matrix, Some(threshold));
+
+        let rips = EdgeList::from_iterator(
+            distance_matrix
+                .edges()
+                .filter(|edge| edge.grade.0[0] <= threshold),
+        );
+        let mut bifiltered = rips.map_grades(|&grade| OneCriticalGrade::<_, 2>::from(grade));
+        let n_edges = bifiltered.len();
+        let expected =
+            remove_strongly_filtration_dominated(&mut bifiltered, EdgeOrder::ReverseLexicographic);
+
+        assert!(remaining.len() < n_edges);
+        let remaining_edges: Vec<_> = remaining
+            .edge_iter()
+            .map(|e| (e.edge, e.grade.0[0]))
+            .collect();
+        let expected_edges: Vec<_> = expected
+            .edge_iter()
+            .map(|e| (e.edge, e.grade.0[1]))
+            .collect();
+        assert_eq!(remaining_edges, expected_edges);
+    }
+}
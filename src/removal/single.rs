@@ -0,0 +1,292 @@
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::removal::adjacency::{AdjacencyMatrix, EdgeNeighbourhood};
+use crate::removal::report::{remove_dominated, RemovalReport};
+use crate::removal::{EdgeOrder, RemovalOptions};
+use crate::{OneCriticalGrade, Value};
+
+/// Edge collapse of a single-parameter filtered graph, in the style of Boissonnat and Pritam:
+/// go through the edges in the given order, and remove those that are dominated at every value
+/// from their own grade onwards, possibly by different vertices.
+/// This is filtration-domination in the single-parameter case, and it preserves the persistent
+/// homology of the clique filtration. Contrary to other implementations of edge collapse, the
+/// grades of the remaining edges are not changed.
+///
+/// Returns a reduced edge list.
+pub fn remove_filtration_dominated_single_parameter<VF: Value>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>>,
+    order: EdgeOrder,
+) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>> {
+    remove_filtration_dominated_single_parameter_timed(edge_list, order, None)
+}
+
+/// As [remove_filtration_dominated_single_parameter], but if we take more than the time given in
+/// `max_time` then execution stops and a clone of the original list is returned.
+/// If `max_time` is None then no timeout is applied.
+pub fn remove_filtration_dominated_single_parameter_timed<VF: Value>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>>,
+    order: EdgeOrder,
+    max_time: Option<Duration>,
+) -> EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>> {
+    remove_dominated(
+        edge_list,
+        &RemovalOptions {
+            order,
+            max_time,
+            ..RemovalOptions::default()
+        },
+        false,
+        dominators_from_its_grade,
+    )
+    .remaining
+}
+
+/// As [remove_filtration_dominated_single_parameter_timed], but returns a [RemovalReport] with the
+/// removed edges, the vertices that dominate them, the time taken, and whether a timeout happened.
+pub fn remove_filtration_dominated_single_parameter_with_report<VF: Value>(
+    edge_list: &mut EdgeList<FilteredEdge<OneCriticalGrade<VF, 1>>>,
+    order: EdgeOrder,
+    max_time: Option<Duration>,
+) -> RemovalReport<OneCriticalGrade<VF, 1>> {
+    remove_dominated(
+        edge_list,
+        &RemovalOptions {
+            order,
+            max_time,
+            ..RemovalOptions::default()
+        },
+        true,
+        dominators_from_its_grade,
+    )
+}
+
+/// A half-open interval of values. An end of `None` means that the interval is unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Interval<VF> {
+    start: VF,
+    end: Option<VF>,
+}
+
+impl<VF: Value> Interval<VF> {
+    fn is_empty(&self) -> bool {
+        matches!(self.end, Some(end) if end <= self.start)
+    }
+}
+
+fn cmp_ends<VF: Value>(a: Option<VF>, b: Option<VF>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Checks whether the edge is dominated at all values greater than or equal to its grade.
+///
+/// A candidate vertex `c` fails to dominate the edge at the values where it is not in the edge
+/// neighbourhood yet, and at the values where some vertex `w` of the edge neighbourhood is not
+/// adjacent to `c`, that is, in the interval that starts when `w` enters the neighbourhood and
+/// ends when the edge between `c` and `w` appears. The edge is dominated at all values if the
+/// intersection of the failing values of all candidates is empty.
+///
+/// If the edge is dominated, returns the candidates that dominate it at values not dominated by
+/// the previous candidates. Otherwise, returns None.
+fn dominators_from_its_grade<VF: Value>(
+    adjacency_matrix: &AdjacencyMatrix<OneCriticalGrade<VF, 1>>,
+    edge: &FilteredEdge<OneCriticalGrade<VF, 1>>,
+) -> Option<Vec<usize>> {
+    let edge_neighbourhood = adjacency_matrix.edge_neighbourhood(edge);
+    let mut non_dominated = vec![Interval {
+        start: edge.grade.0[0],
+        end: None,
+    }];
+    let mut dominators = Vec::new();
+    for (candidate, candidate_grade) in edge_neighbourhood.candidates() {
+        let failing = failing_values(
+            adjacency_matrix,
+            &edge_neighbourhood,
+            edge,
+            candidate,
+            candidate_grade.0[0],
+        );
+        let still_non_dominated = intersect(&non_dominated, &failing);
+        if still_non_dominated != non_dominated {
+            dominators.push(candidate);
+        }
+        if still_non_dominated.is_empty() {
+            return Some(dominators);
+        }
+        non_dominated = still_non_dominated;
+    }
+    None
+}
+
+/// The values at which the candidate does not dominate the edge, as a sorted list of disjoint
+/// intervals.
+fn failing_values<VF: Value>(
+    adjacency_matrix: &AdjacencyMatrix<OneCriticalGrade<VF, 1>>,
+    edge_neighbourhood: &EdgeNeighbourhood<OneCriticalGrade<VF, 1>>,
+    edge: &FilteredEdge<OneCriticalGrade<VF, 1>>,
+    candidate: usize,
+    candidate_value: VF,
+) -> Vec<Interval<VF>> {
+    let mut intervals = vec![Interval {
+        start: edge.grade.0[0],
+        end: Some(candidate_value),
+    }];
+    let mut candidate_neighbours = adjacency_matrix.open_neighbours(candidate).peekable();
+    for (w, w_grade) in edge_neighbourhood.closed_neighbours() {
+        if w == candidate {
+            continue;
+        }
+        // Both iterators are sorted by vertex.
+        while candidate_neighbours.next_if(|(x, _)| *x < w).is_some() {}
+        let adjacency_value = candidate_neighbours
+            .next_if(|(x, _)| *x == w)
+            .map(|(_, grade)| grade.0[0]);
+        intervals.push(Interval {
+            start: w_grade.0[0],
+            end: adjacency_value,
+        });
+    }
+    merge(intervals)
+}
+
+/// Sorts the intervals and merges the overlapping ones.
+fn merge<VF: Value>(mut intervals: Vec<Interval<VF>>) -> Vec<Interval<VF>> {
+    intervals.retain(|interval| !interval.is_empty());
+    intervals.sort_by_key(|interval| interval.start);
+    let mut merged: Vec<Interval<VF>> = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if cmp_ends(Some(interval.start), last.end) != Ordering::Greater => {
+                if cmp_ends(interval.end, last.end) == Ordering::Greater {
+                    last.end = interval.end;
+                }
+            }
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+/// Intersects two sorted lists of disjoint intervals.
+fn intersect<VF: Value>(a: &[Interval<VF>], b: &[Interval<VF>]) -> Vec<Interval<VF>> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let start = std::cmp::max(a[i].start, b[j].start);
+        let end_order = cmp_ends(a[i].end, b[j].end);
+        let end = if end_order == Ordering::Greater {
+            b[j].end
+        } else {
+            a[i].end
+        };
+        let interval = Interval { start, end };
+        if !interval.is_empty() {
+            result.push(interval);
+        }
+        match end_order {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::transform::{forget_parameter, project};
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::removal::connectivity::preserves_connectivity;
+    use crate::removal::single::{intersect, merge, Interval};
+    use crate::removal::{
+        remove_filtration_dominated, remove_filtration_dominated_single_parameter, EdgeOrder,
+    };
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn interval_operations() {
+        let interval = |start, end| Interval { start, end };
+        let merged = merge(vec![
+            interval(5, None),
+            interval(1, Some(3)),
+            interval(2, Some(4)),
+            interval(7, Some(7)),
+        ]);
+        assert_eq!(merged, vec![interval(1, Some(4)), interval(5, None)]);
+
+        let other = vec![interval(0, Some(2)), interval(3, Some(6))];
+        assert_eq!(
+            intersect(&merged, &other),
+            vec![
+                interval(1, Some(2)),
+                interval(3, Some(4)),
+                interval(5, Some(6))
+            ]
+        );
+    }
+
+    #[test]
+    fn single_parameter_collapse_of_a_cone() {
+        // A cone with apex 0 over the path 1-2-3, where the edge 1-3 closes a triangle late.
+        let edge = |u, v, t| FilteredEdge {
+            grade: OneCriticalGrade([t]),
+            edge: BareEdge(u, v),
+        };
+        let mut edge_list: EdgeList<_> = vec![
+            edge(0, 1, 0),
+            edge(0, 2, 0),
+            edge(0, 3, 0),
+            edge(1, 2, 1),
+            edge(2, 3, 1),
+            edge(1, 3, 2),
+        ]
+        .into();
+        let reduced = remove_filtration_dominated_single_parameter(
+            &mut edge_list,
+            EdgeOrder::ReverseLexicographic,
+        );
+        // All edges that are not incident to the apex are dominated by it.
+        let mut remaining: Vec<BareEdge> = reduced.edge_iter().map(|e| e.edge).collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            vec![BareEdge(0, 1), BareEdge(0, 2), BareEdge(0, 3)]
+        );
+    }
+
+    #[test]
+    fn single_parameter_collapse_agrees_with_bifiltered() {
+        let mut edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 60 },
+            Threshold::Percentile(0.5),
+            None,
+            false,
+        )
+        .unwrap();
+        // With a constant first parameter, filtration-domination is single-parameter.
+        forget_parameter(&mut edges, 0);
+        let mut single_parameter_edges = project(&edges, [1]);
+        let original = single_parameter_edges.clone();
+
+        let bifiltered = remove_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+        let collapsed = remove_filtration_dominated_single_parameter(
+            &mut single_parameter_edges,
+            EdgeOrder::ReverseLexicographic,
+        );
+        let bifiltered_edges: Vec<BareEdge> = bifiltered.edge_iter().map(|e| e.edge).collect();
+        let collapsed_edges: Vec<BareEdge> = collapsed.edge_iter().map(|e| e.edge).collect();
+        assert_eq!(bifiltered_edges, collapsed_edges);
+        assert!(collapsed.len() < original.len());
+        assert!(preserves_connectivity(&original, &collapsed));
+    }
+}
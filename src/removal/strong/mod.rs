@@ -3,7 +3,9 @@ use std::time::Duration;
 
 use crate::edges::{EdgeList, FilteredEdge};
 use crate::removal::adjacency::AdjacencyMatrix;
-use crate::removal::EdgeOrder;
+use crate::removal::neighbour_bits::CommonNeighbourBits;
+use crate::removal::report::{remove_dominated, RemovalReport};
+use crate::removal::{DeletionMode, EdgeOrder, RemovalOptions};
 use crate::CriticalGrade;
 
 /// As [crate::removal::remove_filtration_dominated], but instead of filtration-dominated edges
@@ -23,51 +25,188 @@ pub fn remove_strongly_filtration_dominated_timed<G: CriticalGrade>(
     order: EdgeOrder,
     max_time: Option<Duration>,
 ) -> EdgeList<FilteredEdge<G>> {
-    match order {
-        EdgeOrder::ReverseLexicographic => {
-            edge_list.edges_mut().sort_unstable_by(|a, b| b.cmp(a));
-        }
-        EdgeOrder::Maintain => {}
-    }
+    remove_strongly_filtration_dominated_with_deletion(
+        edge_list,
+        order,
+        DeletionMode::Immediate,
+        max_time,
+    )
+}
 
-    let mut remaining_edges: Vec<FilteredEdge<G>> = Vec::with_capacity(edge_list.len());
-    let mut adjacency_matrix = AdjacencyMatrix::new(edge_list.n_vertices);
+/// As [remove_strongly_filtration_dominated_timed], but the removed edges are deleted from the
+/// internal adjacency structure as specified by `deletion_mode`. See [DeletionMode].
+pub fn remove_strongly_filtration_dominated_with_deletion<G: CriticalGrade>(
+    edge_list: &mut EdgeList<FilteredEdge<G>>,
+    order: EdgeOrder,
+    deletion_mode: DeletionMode,
+    max_time: Option<Duration>,
+) -> EdgeList<FilteredEdge<G>> {
+    remove_dominated(
+        edge_list,
+        &RemovalOptions {
+            order,
+            deletion_mode,
+            max_time,
+            ..RemovalOptions::default()
+        },
+        false,
+        strong_dominator,
+    )
+    .remaining
+}
 
-    for edge in edge_list.edge_iter() {
-        adjacency_matrix.add_edge(edge.clone());
-    }
+/// As [remove_strongly_filtration_dominated_timed], but returns a [RemovalReport] with the removed
+/// edges, the vertices that dominate them, the time taken, and whether a timeout happened.
+pub fn remove_strongly_filtration_dominated_with_report<G: CriticalGrade>(
+    edge_list: &mut EdgeList<FilteredEdge<G>>,
+    order: EdgeOrder,
+    max_time: Option<Duration>,
+) -> RemovalReport<G> {
+    remove_dominated(
+        edge_list,
+        &RemovalOptions {
+            order,
+            max_time,
+            ..RemovalOptions::default()
+        },
+        true,
+        strong_dominator,
+    )
+}
+
+/// As [remove_strongly_filtration_dominated_with_report], but the order, the deletion mode,
+/// the timeout, and the other parameters are given by the [RemovalOptions].
+pub fn remove_strongly_filtration_dominated_with_options<G: CriticalGrade>(
+    edge_list: &mut EdgeList<FilteredEdge<G>>,
+    options: &RemovalOptions,
+) -> RemovalReport<G> {
+    remove_dominated(edge_list, options, true, strong_dominator)
+}
+
+/// Whether the edge is strongly filtration-dominated in the graph of the adjacency matrix, that
+/// is, whether a single vertex dominates it at every grade greater than or equal to its own.
+///
+/// The removal algorithms check each edge in the graph from which the previous edges have been
+/// removed, so the result depends on the edges that the matrix contains.
+pub fn is_strongly_filtration_dominated<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+) -> bool {
+    strongly_dominating_vertex(adjacency_matrix, edge).is_some()
+}
 
-    let start = std::time::Instant::now();
-    for edge in edge_list.edge_iter() {
-        if let Some(max_time) = max_time {
-            if start.elapsed() > max_time {
-                return edge_list.clone();
+/// Whether the vertex `w` strongly filtration-dominates the edge in the graph of the adjacency
+/// matrix.
+pub(crate) fn strongly_dominates<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+    w: usize,
+) -> bool {
+    let (u, v) = (edge.edge.0, edge.edge.1);
+    match (
+        adjacency_matrix.edge_grade(u, w),
+        adjacency_matrix.edge_grade(v, w),
+    ) {
+        (Some(grade_u), Some(grade_v)) => {
+            let edge_neighs = adjacency_matrix.closed_neighbours_edge(edge);
+            if adjacency_matrix.is_dense() {
+                return dominates_by_lookup(adjacency_matrix, edge_neighs, w);
             }
+            let w_neighs =
+                adjacency_matrix.closed_neighbours(w, grade_u.join(grade_v).join(&edge.grade));
+            is_subset(edge_neighs, w_neighs)
         }
+        _ => false,
+    }
+}
 
-        if is_strongly_filtration_dominated(&adjacency_matrix, edge) {
-            adjacency_matrix.delete_edge(edge);
-        } else {
-            remaining_edges.push(edge.clone());
+/// If the edge is strongly filtration-dominated, returns the vertex that dominates it.
+pub(crate) fn strongly_dominating_vertex<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+) -> Option<usize> {
+    if adjacency_matrix.is_dense() {
+        return strongly_dominating_vertex_dense(adjacency_matrix, edge);
+    }
+    match adjacency_matrix.common_neighbour_bits(edge.edge.0, edge.edge.1) {
+        Some(common_bits) => {
+            strongly_dominating_vertex_with_bits(adjacency_matrix, edge, &common_bits)
         }
+        None => strongly_dominating_vertex_scalar(adjacency_matrix, edge),
     }
+}
 
-    remaining_edges.shrink_to_fit();
-    remaining_edges.into()
+/// As [strongly_dominating_vertex], checking every common neighbour of the endpoints of the edge.
+fn strongly_dominating_vertex_scalar<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+) -> Option<usize> {
+    for (v, value_v) in adjacency_matrix.common_neighbours(edge) {
+        let edge_neighs = adjacency_matrix.closed_neighbours_edge(edge);
+        let v_neighs = adjacency_matrix.closed_neighbours(v, value_v.join(&edge.grade));
+        if is_subset(edge_neighs, v_neighs) {
+            return Some(v);
+        }
+    }
+    None
 }
 
-fn is_strongly_filtration_dominated<G: CriticalGrade>(
+/// As [strongly_dominating_vertex], but the candidates that are not adjacent to every common
+/// neighbour of the endpoints of the edge are skipped without comparing grades.
+fn strongly_dominating_vertex_with_bits<G: CriticalGrade>(
     adjacency_matrix: &AdjacencyMatrix<G>,
     edge: &FilteredEdge<G>,
-) -> bool {
+    common_bits: &CommonNeighbourBits,
+) -> Option<usize> {
     for (v, value_v) in adjacency_matrix.common_neighbours(edge) {
+        if !adjacency_matrix.may_strongly_dominate(common_bits, v) {
+            continue;
+        }
         let edge_neighs = adjacency_matrix.closed_neighbours_edge(edge);
         let v_neighs = adjacency_matrix.closed_neighbours(v, value_v.join(&edge.grade));
         if is_subset(edge_neighs, v_neighs) {
-            return true;
+            return Some(v);
         }
     }
-    false
+    None
+}
+
+/// As [strongly_dominating_vertex], but instead of merging the neighbourhood of each candidate
+/// with the neighbourhood of the edge, it looks up the grade of the edge between the candidate and
+/// each neighbour of the edge, which takes constant time in the dense backend.
+fn strongly_dominating_vertex_dense<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+) -> Option<usize> {
+    let neighbourhood = adjacency_matrix.edge_neighbourhood(edge);
+    let dominating = neighbourhood
+        .candidates()
+        .map(|(w, _)| w)
+        .find(|&w| dominates_by_lookup(adjacency_matrix, neighbourhood.closed_neighbours(), w));
+    dominating
+}
+
+/// Whether the vertex w, a common neighbour of the endpoints of an edge, strongly dominates it,
+/// given the closed neighbourhood of the edge. Looks up the grade of the edge between w and each
+/// neighbour, so it is only fast in the dense backend.
+fn dominates_by_lookup<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    mut edge_neighbours: impl Iterator<Item = (usize, G)>,
+    w: usize,
+) -> bool {
+    // The candidate enters the neighbourhood of the edge at the same grade as its own closed
+    // neighbourhood, so only the other neighbours are checked.
+    edge_neighbours.all(|(x, value_x)| {
+        x == w
+            || matches!(adjacency_matrix.edge_grade(w, x), Some(value_w) if value_w.lte(&value_x))
+    })
+}
+
+fn strong_dominator<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    edge: &FilteredEdge<G>,
+) -> Option<Vec<usize>> {
+    strongly_dominating_vertex(adjacency_matrix, edge).map(|v| vec![v])
 }
 
 fn is_subset<G: CriticalGrade, I, J>(left: I, mut right: J) -> bool
@@ -96,11 +235,95 @@ where
 
 #[cfg(test)]
 mod tests {
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
     use crate::edges::{BareEdge, FilteredEdge};
     use crate::removal::adjacency::AdjacencyMatrix;
-    use crate::removal::strong::{is_strongly_filtration_dominated, is_subset};
+    use crate::removal::strong::{
+        is_subset, strongly_dominating_vertex, strongly_dominating_vertex_dense,
+        strongly_dominating_vertex_scalar, strongly_dominating_vertex_with_bits,
+    };
+    use crate::removal::{
+        is_filtration_dominated, is_strongly_filtration_dominated, remove_filtration_dominated,
+        remove_filtration_dominated_with_deletion, remove_strongly_filtration_dominated,
+        remove_strongly_filtration_dominated_with_deletion, DeletionMode, EdgeOrder,
+    };
     use crate::OneCriticalGrade;
 
+    #[test]
+    fn deletion_modes_agree() {
+        let mut edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 60 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let order = EdgeOrder::ReverseLexicographic;
+
+        let strong_immediate = remove_strongly_filtration_dominated_with_deletion(
+            &mut edges,
+            order,
+            DeletionMode::Immediate,
+            None,
+        );
+        let strong_deferred = remove_strongly_filtration_dominated_with_deletion(
+            &mut edges,
+            order,
+            DeletionMode::Deferred,
+            None,
+        );
+        assert_eq!(strong_immediate.edges(), strong_deferred.edges());
+
+        let immediate = remove_filtration_dominated_with_deletion(
+            &mut edges,
+            order,
+            DeletionMode::Immediate,
+            None,
+        );
+        let deferred = remove_filtration_dominated_with_deletion(
+            &mut edges,
+            order,
+            DeletionMode::Deferred,
+            None,
+        );
+        assert_eq!(immediate.edges(), deferred.edges());
+    }
+
+    #[test]
+    fn predicates_agree_with_removal() {
+        let mut edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 60 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let strong_remaining =
+            remove_strongly_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+        let mut strong_adjacency = AdjacencyMatrix::from_edge_list(&edges);
+        let mut strong_kept = Vec::new();
+        for edge in edges.edge_iter() {
+            if is_strongly_filtration_dominated(&strong_adjacency, edge) {
+                strong_adjacency.delete_edge(edge);
+            } else {
+                strong_kept.push(*edge);
+            }
+        }
+        assert_eq!(strong_kept, strong_remaining.edges());
+
+        let remaining = remove_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+        let mut adjacency = AdjacencyMatrix::from_edge_list(&edges);
+        let mut kept = Vec::new();
+        for edge in edges.edge_iter() {
+            if is_filtration_dominated(&adjacency, edge) {
+                adjacency.delete_edge(edge);
+            } else {
+                kept.push(*edge);
+            }
+        }
+        assert_eq!(kept, remaining.edges());
+    }
+
     #[test]
     fn strongly_filtration_dominated_happy_case() {
         let mut adj: AdjacencyMatrix<OneCriticalGrade<usize, 2>> = AdjacencyMatrix::new(6);
@@ -136,7 +359,7 @@ mod tests {
             grade: OneCriticalGrade([4, 4]),
         });
 
-        assert!(is_strongly_filtration_dominated(&adj, &query_edge));
+        assert!(strongly_dominating_vertex(&adj, &query_edge).is_some());
     }
 
     #[test]
@@ -174,7 +397,58 @@ mod tests {
             grade: OneCriticalGrade([5, 5]),
         });
 
-        assert!(!is_strongly_filtration_dominated(&adj, &query_edge));
+        assert!(strongly_dominating_vertex(&adj, &query_edge).is_none());
+    }
+
+    #[test]
+    fn bits_agree_with_scalar_check() {
+        let mut edges = get_dataset_density_edge_list(
+            Dataset::Sphere { n_points: 80 },
+            Threshold::Percentile(0.4),
+            None,
+            false,
+        )
+        .unwrap();
+        EdgeOrder::ReverseLexicographic.apply(&mut edges);
+        let mut adj = AdjacencyMatrix::from_edge_list(&edges);
+        let mut n_dominated = 0;
+        for edge in edges.edge_iter() {
+            let common_bits = adj.common_neighbour_bits(edge.edge.0, edge.edge.1).unwrap();
+            let dominating = strongly_dominating_vertex_scalar(&adj, edge);
+            assert_eq!(
+                strongly_dominating_vertex_with_bits(&adj, edge, &common_bits),
+                dominating
+            );
+            if dominating.is_some() {
+                adj.delete_edge(edge);
+                n_dominated += 1;
+            }
+        }
+        assert!(n_dominated > 0);
+    }
+
+    #[test]
+    fn dense_check_agrees_with_scalar_check() {
+        let mut edges = get_dataset_density_edge_list(
+            Dataset::Sphere { n_points: 80 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        EdgeOrder::ReverseLexicographic.apply(&mut edges);
+        let mut adj = AdjacencyMatrix::from_edge_list(&edges);
+        assert!(adj.is_dense());
+        let mut n_dominated = 0;
+        for edge in edges.edge_iter() {
+            let dominating = strongly_dominating_vertex_scalar(&adj, edge);
+            assert_eq!(strongly_dominating_vertex_dense(&adj, edge), dominating);
+            if dominating.is_some() {
+                adj.delete_edge(edge);
+                n_dominated += 1;
+            }
+        }
+        assert!(n_dominated > 0);
     }
 
     #[test]
@@ -0,0 +1,359 @@
+use std::time::{Duration, Instant};
+
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::removal::{remove_strongly_filtration_dominated_with_report, EdgeOrder, RemovedEdge};
+use crate::CriticalGrade;
+
+/// The orders in which the passes of [remove_strongly_filtration_dominated_until_stable] go
+/// through the edges.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PassOrder {
+    /// Every pass uses the same order.
+    Fixed(EdgeOrder),
+    /// The passes alternate between the two orders, starting with the first one.
+    Alternating(EdgeOrder, EdgeOrder),
+}
+
+impl PassOrder {
+    fn order_of_pass(&self, pass: usize) -> EdgeOrder {
+        match *self {
+            PassOrder::Fixed(order) => order,
+            PassOrder::Alternating(first, second) => [first, second][pass % 2],
+        }
+    }
+}
+
+impl From<EdgeOrder> for PassOrder {
+    fn from(order: EdgeOrder) -> Self {
+        PassOrder::Fixed(order)
+    }
+}
+
+/// The output of [remove_strongly_filtration_dominated_until_stable].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UntilStableReport<G> {
+    /// The edges remaining after the last completed pass.
+    pub remaining: EdgeList<FilteredEdge<G>>,
+    /// Statistics of each completed pass, in order.
+    pub passes: Vec<PassStatistics>,
+    /// Whether the passes were stopped because they exceeded the maximum time. The pass that
+    /// timed out is not included in `passes`.
+    pub timed_out: bool,
+}
+
+impl<G> UntilStableReport<G> {
+    /// The total number of removed edges.
+    pub fn n_removed(&self) -> usize {
+        self.passes.iter().map(|pass| pass.n_removed()).sum()
+    }
+}
+
+/// Statistics of a pass of [remove_strongly_filtration_dominated_until_stable].
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassStatistics {
+    /// The order in which the pass went through the edges.
+    pub order: EdgeOrder,
+    /// The number of edges before the pass.
+    pub edges_before: usize,
+    /// The number of edges after the pass.
+    pub edges_after: usize,
+    /// The wall-clock time taken by the pass.
+    pub duration: Duration,
+}
+
+impl PassStatistics {
+    /// The number of edges removed by the pass.
+    pub fn n_removed(&self) -> usize {
+        self.edges_before - self.edges_after
+    }
+}
+
+/// A pass of [iterate_strongly_filtration_dominated]: the edges that it removed, and the time it
+/// took.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IterationReport<G> {
+    /// The order in which the pass went through the edges.
+    pub order: EdgeOrder,
+    /// The number of edges before the pass.
+    pub edges_before: usize,
+    /// The edges removed by the pass, in the order in which they were removed.
+    pub removed: Vec<RemovedEdge<G>>,
+    /// The wall-clock time taken by the pass.
+    pub duration: Duration,
+}
+
+impl<G> IterationReport<G> {
+    /// The number of edges removed by the pass.
+    pub fn n_removed(&self) -> usize {
+        self.removed.len()
+    }
+
+    /// The number of edges after the pass.
+    pub fn edges_after(&self) -> usize {
+        self.edges_before - self.n_removed()
+    }
+
+    /// The statistics of the pass, without the removed edges.
+    pub fn statistics(&self) -> PassStatistics {
+        PassStatistics {
+            order: self.order,
+            edges_before: self.edges_before,
+            edges_after: self.edges_after(),
+            duration: self.duration,
+        }
+    }
+}
+
+/// An iterator over the passes of strong removal on an edge list, see
+/// [iterate_strongly_filtration_dominated].
+#[derive(Debug, Clone)]
+pub struct UntilStableIterations<G> {
+    remaining: EdgeList<FilteredEdge<G>>,
+    order: PassOrder,
+    max_time: Option<Duration>,
+    start: Instant,
+    pass: usize,
+    stable: bool,
+    timed_out: bool,
+}
+
+impl<G> UntilStableIterations<G> {
+    /// The edges remaining after the passes done so far.
+    pub fn remaining(&self) -> &EdgeList<FilteredEdge<G>> {
+        &self.remaining
+    }
+
+    /// Consumes the iterator and returns the edges remaining after the passes done so far.
+    pub fn into_remaining(self) -> EdgeList<FilteredEdge<G>> {
+        self.remaining
+    }
+
+    /// Whether the passes were stopped because they exceeded the maximum time.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Whether the last pass removed no edges. Then no edge of the graph is strongly
+    /// filtration-dominated, so more passes would not remove edges either, in any order.
+    pub fn is_stable(&self) -> bool {
+        self.stable
+    }
+}
+
+impl<G: CriticalGrade> Iterator for UntilStableIterations<G> {
+    type Item = IterationReport<G>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.timed_out || self.is_stable() {
+            return None;
+        }
+        let pass_max_time = match self.max_time {
+            Some(max_time) => match max_time.checked_sub(self.start.elapsed()) {
+                Some(left) => Some(left),
+                None => {
+                    self.timed_out = true;
+                    return None;
+                }
+            },
+            None => None,
+        };
+        let order = self.order.order_of_pass(self.pass);
+        let report = remove_strongly_filtration_dominated_with_report(
+            &mut self.remaining,
+            order,
+            pass_max_time,
+        );
+        if report.timed_out() {
+            self.timed_out = true;
+            return None;
+        }
+        let iteration = IterationReport {
+            order,
+            edges_before: self.remaining.len(),
+            removed: report.removed,
+            duration: report.duration,
+        };
+        self.remaining = report.remaining;
+        self.pass += 1;
+        self.stable = iteration.n_removed() == 0;
+        Some(iteration)
+    }
+}
+
+/// Runs [remove_strongly_filtration_dominated](crate::removal::remove_strongly_filtration_dominated)
+/// lazily on its own output, one pass per call to [Iterator::next], returning the edges removed by
+/// each pass. The iterator ends once the passes are stable, as in
+/// [remove_strongly_filtration_dominated_until_stable], or once they take more than `max_time` in
+/// total, in which case the pass that exceeds it is discarded. Limit the number of passes with
+/// [Iterator::take], and get the remaining edges with [UntilStableIterations::remaining].
+///
+/// ```
+/// # use filtration_domination::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+/// use filtration_domination::removal::{
+///     iterate_strongly_filtration_dominated, EdgeOrder, IterationReport,
+/// };
+///
+/// # let edges = get_dataset_density_edge_list(
+/// #     Dataset::Sphere { n_points: 50 },
+/// #     Threshold::KeepAll,
+/// #     None,
+/// #     false,
+/// # )
+/// # .unwrap();
+/// let mut iterations =
+///     iterate_strongly_filtration_dominated(&edges, EdgeOrder::ReverseLexicographic.into(), None);
+/// let reports: Vec<IterationReport<_>> = iterations.by_ref().take(5).collect();
+/// let n_removed: usize = reports.iter().map(IterationReport::n_removed).sum();
+/// assert_eq!(iterations.remaining().len(), edges.len() - n_removed);
+/// ```
+pub fn iterate_strongly_filtration_dominated<G: CriticalGrade>(
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    order: PassOrder,
+    max_time: Option<Duration>,
+) -> UntilStableIterations<G> {
+    UntilStableIterations {
+        remaining: edge_list.clone(),
+        order,
+        max_time,
+        start: Instant::now(),
+        pass: 0,
+        stable: false,
+        timed_out: false,
+    }
+}
+
+/// Repeats [remove_strongly_filtration_dominated](crate::removal::remove_strongly_filtration_dominated)
+/// on its own output, until a pass removes no edges, or `max_iterations` passes have been done.
+/// Removing an edge can make other edges strongly filtration-dominated, even some that were
+/// already checked in the same pass, so repeated passes usually remove more edges.
+///
+/// The passes go through the edges in the order given by `order`. A pass that removes no edges
+/// checks every edge in the same graph, so, whatever the order of the next pass, it would not
+/// remove edges either, and we stop after the first such pass.
+///
+/// If the passes take more than `max_time` in total, the pass that exceeds it is stopped, and the
+/// output of the previous passes is returned. If `max_time` is None then no timeout is applied.
+///
+/// To get the edges removed by each pass, use [iterate_strongly_filtration_dominated].
+pub fn remove_strongly_filtration_dominated_until_stable<G: CriticalGrade>(
+    edge_list: &EdgeList<FilteredEdge<G>>,
+    order: PassOrder,
+    max_iterations: usize,
+    max_time: Option<Duration>,
+) -> UntilStableReport<G> {
+    let mut iterations = iterate_strongly_filtration_dominated(edge_list, order, max_time);
+    let passes = iterations
+        .by_ref()
+        .take(max_iterations)
+        .map(|iteration| iteration.statistics())
+        .collect();
+    UntilStableReport {
+        timed_out: iterations.timed_out(),
+        remaining: iterations.into_remaining(),
+        passes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::removal::{
+        iterate_strongly_filtration_dominated, remove_strongly_filtration_dominated,
+        remove_strongly_filtration_dominated_until_stable, EdgeOrder, PassOrder,
+    };
+
+    #[test]
+    fn until_stable_matches_repeated_passes() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 100 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let order = EdgeOrder::ReverseLexicographic;
+
+        let report =
+            remove_strongly_filtration_dominated_until_stable(&edges, order.into(), 100, None);
+        assert!(!report.timed_out);
+        assert_eq!(report.passes.last().unwrap().n_removed(), 0);
+        assert_eq!(report.n_removed(), edges.len() - report.remaining.len());
+
+        let mut expected = edges.clone();
+        for _ in 0..report.passes.len() {
+            expected = remove_strongly_filtration_dominated(&mut expected, order);
+        }
+        assert_eq!(report.remaining.edges(), expected.edges());
+
+        let one_pass =
+            remove_strongly_filtration_dominated_until_stable(&edges, order.into(), 1, None);
+        assert_eq!(one_pass.passes.len(), 1);
+
+        let alternating = remove_strongly_filtration_dominated_until_stable(
+            &edges,
+            PassOrder::Alternating(EdgeOrder::ReverseLexicographic, EdgeOrder::Maintain),
+            100,
+            None,
+        );
+        assert!(alternating.passes.len() >= 2);
+        let n_passes = alternating.passes.len();
+        assert_eq!(alternating.passes[n_passes - 1].n_removed(), 0);
+        assert!(alternating.passes[n_passes - 2].n_removed() > 0);
+        assert_eq!(alternating.remaining.len(), report.remaining.len());
+
+        let timed_out = remove_strongly_filtration_dominated_until_stable(
+            &edges,
+            order.into(),
+            100,
+            Some(Duration::ZERO),
+        );
+        assert!(timed_out.timed_out);
+        assert_eq!(timed_out.remaining.len(), edges.len());
+    }
+
+    #[test]
+    fn iterations_report_the_removed_edges() {
+        let edges = get_dataset_density_edge_list(
+            Dataset::Torus { n_points: 100 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let order = EdgeOrder::ReverseLexicographic;
+        let report =
+            remove_strongly_filtration_dominated_until_stable(&edges, order.into(), 100, None);
+
+        let mut iterations = iterate_strongly_filtration_dominated(&edges, order.into(), None);
+        let mut remaining = edges.clone();
+        let mut n_iterations = 0;
+        for (iteration, pass) in iterations.by_ref().zip(report.passes.iter()) {
+            assert_eq!(iteration.edges_before, remaining.len());
+            assert_eq!(iteration.n_removed(), pass.n_removed());
+            let removed: Vec<_> = iteration.removed.iter().map(|r| r.edge.edge).collect();
+            remaining.remove_edges(&removed).unwrap();
+            n_iterations += 1;
+        }
+        assert_eq!(n_iterations, report.passes.len());
+        assert!(iterations.is_stable());
+        assert!(iterations.next().is_none());
+        assert_eq!(iterations.remaining().edges(), report.remaining.edges());
+        // The passes sort the edges, so compare them as sets.
+        let mut expected: Vec<_> = report.remaining.edge_iter().map(|e| e.edge).collect();
+        let mut found: Vec<_> = remaining.edge_iter().map(|e| e.edge).collect();
+        expected.sort_unstable();
+        found.sort_unstable();
+        assert_eq!(found, expected);
+
+        let mut timed_out =
+            iterate_strongly_filtration_dominated(&edges, order.into(), Some(Duration::ZERO));
+        assert!(timed_out.next().is_none());
+        assert!(timed_out.timed_out());
+    }
+}
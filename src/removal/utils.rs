@@ -4,70 +4,311 @@ use sorted_iter::SortedIterator;
 
 use crate::edges::{EdgeList, FilteredEdge};
 use crate::removal::adjacency::AdjacencyMatrix;
+use crate::removal::full::non_domination_grades;
 use crate::{CriticalGrade, OneCriticalGrade, Value};
 
-/// Given an edge list, returns a tuple that contains the number of edges that are
-/// isolated (that is, they have empty edge neighborhood) at their critical grade,
-/// and the number of edge dominated at their critical grade.
+/// A vertex in the edge neighbourhood of an edge, together with the grades where it fails to
+/// dominate the edge. See [dominator_suggestions].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DominatorSuggestion<VF> {
+    /// The candidate dominating vertex.
+    pub vertex: usize,
+    /// The critical grades where `vertex` does not dominate the edge. If it is empty, then
+    /// the vertex strongly filtration-dominates the edge.
+    pub failing_grades: Vec<OneCriticalGrade<VF, 2>>,
+}
+
+/// The best candidates to dominate an edge. See [dominator_suggestions].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeptEdgeDiagnostics<VF> {
+    /// The edge.
+    pub edge: FilteredEdge<OneCriticalGrade<VF, 2>>,
+    /// The candidate dominating vertices, from closest to furthest from dominating the edge.
+    pub suggestions: Vec<DominatorSuggestion<VF>>,
+}
+
+/// Explains why the edges of the given edge list are not filtration-dominated.
+/// The edge list is usually the output of [crate::removal::remove_filtration_dominated].
+///
+/// For each edge, returns up to `k` vertices of its edge neighbourhood that are closest to
+/// dominating it, that is, those that fail to dominate the edge at the least number of critical
+/// grades. The failing grades of each vertex describe the region where domination fails.
+/// Edges with an empty edge neighbourhood have no suggestions.
 #[must_use]
-pub fn count_isolated_edges<VF: Value>(
+pub fn dominator_suggestions<VF: Value>(
     edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
-) -> (usize, usize) {
-    let mut isolated_edges = 0;
-    let mut dominated_when_appear = 0;
-
+    k: usize,
+) -> Vec<KeptEdgeDiagnostics<VF>> {
     let mut adjacency_matrix = AdjacencyMatrix::new(edge_list.n_vertices);
-
     for edge in edge_list.edge_iter() {
         adjacency_matrix.add_edge(*edge);
     }
 
+    edge_list
+        .edge_iter()
+        .map(|edge| {
+            let mut suggestions: Vec<DominatorSuggestion<VF>> =
+                non_domination_grades(&adjacency_matrix, edge)
+                    .into_iter()
+                    .map(|(vertex, failing_grades)| DominatorSuggestion {
+                        vertex,
+                        failing_grades,
+                    })
+                    .collect();
+            suggestions.sort_by_key(|s| (s.failing_grades.len(), s.vertex));
+            suggestions.truncate(k);
+            KeptEdgeDiagnostics {
+                edge: *edge,
+                suggestions,
+            }
+        })
+        .collect()
+}
+
+/// Counts of the edges of an edge list that are isolated or dominated when they appear, see
+/// [isolated_edge_counts].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IsolatedEdgeCounts {
+    /// The number of edges whose edge neighbourhood is empty at their critical grade.
+    pub isolated: usize,
+    /// The number of edges that are dominated at their critical grade, see
+    /// [is_dominated_at_time]. Isolated edges are not dominated.
+    pub dominated_at_entry: usize,
+    /// The total number of edges.
+    pub total: usize,
+}
+
+/// Counts the edges of the edge list that are isolated, that is, whose edge neighbourhood is empty
+/// at their critical grade, and those that are dominated at their critical grade.
+#[must_use]
+pub fn isolated_edge_counts<G: CriticalGrade>(
+    edge_list: &EdgeList<FilteredEdge<G>>,
+) -> IsolatedEdgeCounts {
+    let adjacency_matrix = AdjacencyMatrix::from_edge_list(edge_list);
+    isolated_edge_counts_with_adjacency(&adjacency_matrix, edge_list)
+}
+
+/// As [isolated_edge_counts], but the neighbourhoods are those of the given adjacency matrix,
+/// which usually contains the edges of the edge list, so that it is not built again.
+#[must_use]
+pub fn isolated_edge_counts_with_adjacency<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    edge_list: &EdgeList<FilteredEdge<G>>,
+) -> IsolatedEdgeCounts {
+    let mut counts = IsolatedEdgeCounts {
+        total: edge_list.len(),
+        ..IsolatedEdgeCounts::default()
+    };
     for edge in edge_list.edge_iter() {
         let mut neighbors_it = adjacency_matrix
             .common_neighbours(edge)
-            .filter_map(|(v, value)| (value.lte(&edge.grade)).then(|| v));
+            .filter(|(_v, value)| value.lte(&edge.grade));
         if neighbors_it.next().is_none() {
             // Edge has empty neighborhood.
-            isolated_edges += 1;
+            counts.isolated += 1;
         }
-        if is_dominated_at_time(&adjacency_matrix, edge, &edge.grade) {
-            dominated_when_appear += 1;
+        if is_dominated_at_time(adjacency_matrix, edge, &edge.grade) {
+            counts.dominated_at_entry += 1;
         }
     }
+    counts
+}
 
-    (isolated_edges, dominated_when_appear)
+/// Given an edge list, returns a tuple that contains the number of edges that are
+/// isolated (that is, they have empty edge neighborhood) at their critical grade,
+/// and the number of edge dominated at their critical grade.
+#[deprecated(note = "use isolated_edge_counts, which works for any grade")]
+#[must_use]
+pub fn count_isolated_edges<VF: Value>(
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+) -> (usize, usize) {
+    let counts = isolated_edge_counts(edge_list);
+    (counts.isolated, counts.dominated_at_entry)
 }
 
-fn is_dominated_at_time<G: CriticalGrade>(
+/// Whether the edge is dominated at the given grade in the graph of the adjacency matrix, that is,
+/// whether a vertex of its edge neighbourhood at that grade is adjacent to all the other vertices
+/// of the neighbourhood at that grade. The edge itself is assumed to be present at that grade.
+///
+/// An edge is filtration-dominated if it is dominated at every grade greater than or equal to its
+/// own. See [domination_heatmap] to evaluate this function over a grid of grades.
+pub fn is_dominated_at_time<G: CriticalGrade>(
     adjacency_matrix: &AdjacencyMatrix<G>,
     edge: &FilteredEdge<G>,
     critical_value: &G,
 ) -> bool {
-    for neigh_vertex in adjacency_matrix
+    let applicable_neighs: Vec<usize> = adjacency_matrix
         .common_neighbours(edge)
-        .filter_map(|(v, value)| (value.lte(critical_value)).then(|| v))
-    {
-        if is_dominated_at_time_by(adjacency_matrix, edge, critical_value, neigh_vertex) {
+        .filter_map(|(v, value)| value.lte(critical_value).then_some(v))
+        .collect();
+    for &neigh_vertex in applicable_neighs.iter() {
+        if is_dominated_at_time_by(
+            adjacency_matrix,
+            &applicable_neighs,
+            critical_value,
+            neigh_vertex,
+        ) {
             return true;
         }
     }
     false
 }
 
+/// Evaluates the domination of the edge over a grid of grades: the entry `[i][j]` is whether the
+/// edge is dominated at the grade `[xs[i], ys[j]]`, see [is_dominated_at_time]. It is false at
+/// the grades where the edge is not present yet, that is, those that are not greater than or
+/// equal to its grade.
+///
+/// With the critical values of the graph as `xs` and `ys`, the false entries above the grade of
+/// the edge show the region where no single vertex dominates it, for example to plot it.
+#[must_use]
+pub fn domination_heatmap<VF: Value>(
+    adjacency_matrix: &AdjacencyMatrix<OneCriticalGrade<VF, 2>>,
+    edge: &FilteredEdge<OneCriticalGrade<VF, 2>>,
+    xs: &[VF],
+    ys: &[VF],
+) -> Vec<Vec<bool>> {
+    xs.iter()
+        .map(|&x| {
+            ys.iter()
+                .map(|&y| {
+                    let grade = OneCriticalGrade([x, y]);
+                    edge.grade.lte(&grade) && is_dominated_at_time(adjacency_matrix, edge, &grade)
+                })
+                .collect()
+        })
+        .collect()
+}
+
 fn is_dominated_at_time_by<G: CriticalGrade>(
     adjacency_matrix: &AdjacencyMatrix<G>,
-    edge: &FilteredEdge<G>,
+    applicable_neighs: &[usize],
     critical_value: &G,
     neigh_vertex: usize,
 ) -> bool {
     let other_neighs = adjacency_matrix
         .closed_neighbours(neigh_vertex, critical_value.clone())
-        .filter_map(move |(v, v_value)| v_value.lte(critical_value).then(|| v))
-        .assume_sorted_by_item();
-    let applicable_neighs = adjacency_matrix
-        .common_neighbours(edge)
-        .filter_map(|(v, value)| (value.lte(critical_value)).then(|| v))
+        .filter_map(move |(v, v_value)| v_value.lte(critical_value).then_some(v))
         .assume_sorted_by_item();
 
-    applicable_neighs.is_subset(other_neighs)
+    applicable_neighs
+        .iter()
+        .copied()
+        .assume_sorted_by_item()
+        .is_subset(other_neighs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    #[allow(deprecated)]
+    use crate::removal::utils::{
+        count_isolated_edges, domination_heatmap, dominator_suggestions, isolated_edge_counts,
+        isolated_edge_counts_with_adjacency, IsolatedEdgeCounts,
+    };
+    use crate::removal::AdjacencyMatrix;
+    use crate::OneCriticalGrade;
+
+    fn suggestions_graph() -> EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> {
+        vec![
+            FilteredEdge {
+                edge: BareEdge(0, 1),
+                grade: OneCriticalGrade([2, 2]),
+            },
+            // Vertex 2 is connected to 0 and 1 at grade [2, 2], but it is only connected to 3
+            // at grade [5, 5].
+            FilteredEdge {
+                edge: BareEdge(0, 2),
+                grade: OneCriticalGrade([1, 2]),
+            },
+            FilteredEdge {
+                edge: BareEdge(1, 2),
+                grade: OneCriticalGrade([2, 1]),
+            },
+            // Vertex 3 is connected to 0 and 1 at grade [4, 4].
+            FilteredEdge {
+                edge: BareEdge(0, 3),
+                grade: OneCriticalGrade([4, 3]),
+            },
+            FilteredEdge {
+                edge: BareEdge(1, 3),
+                grade: OneCriticalGrade([3, 4]),
+            },
+            FilteredEdge {
+                edge: BareEdge(2, 3),
+                grade: OneCriticalGrade([5, 5]),
+            },
+            FilteredEdge {
+                edge: BareEdge(3, 4),
+                grade: OneCriticalGrade([0, 0]),
+            },
+        ]
+        .into()
+    }
+
+    #[test]
+    fn dominator_suggestions_happy_case() {
+        let edge_list = suggestions_graph();
+        let diagnostics = dominator_suggestions(&edge_list, 1);
+        assert_eq!(diagnostics.len(), edge_list.len());
+
+        let first = &diagnostics[0];
+        assert_eq!(first.edge, edge_list.edges()[0]);
+        assert_eq!(first.suggestions.len(), 1);
+        assert_eq!(first.suggestions[0].vertex, 2);
+        assert_eq!(
+            first.suggestions[0].failing_grades,
+            vec![OneCriticalGrade([4, 4])]
+        );
+
+        // The edge (3, 4) has an empty edge neighbourhood.
+        assert!(diagnostics[6].suggestions.is_empty());
+    }
+
+    #[test]
+    fn isolated_edge_counts_happy_case() {
+        let edge_list = suggestions_graph();
+        let counts = isolated_edge_counts(&edge_list);
+        // Only the edges (0, 1) and (2, 3) have a common neighbour when they appear, vertex 2 and
+        // vertex 0 respectively, which dominates them.
+        assert_eq!(
+            counts,
+            IsolatedEdgeCounts {
+                isolated: 5,
+                dominated_at_entry: 2,
+                total: 7
+            }
+        );
+        let adjacency_matrix = AdjacencyMatrix::from_edge_list(&edge_list);
+        assert_eq!(
+            isolated_edge_counts_with_adjacency(&adjacency_matrix, &edge_list),
+            counts
+        );
+        #[allow(deprecated)]
+        let tuple = count_isolated_edges(&edge_list);
+        assert_eq!(tuple, (counts.isolated, counts.dominated_at_entry));
+    }
+
+    #[test]
+    fn domination_heatmap_happy_case() {
+        let edge_list = suggestions_graph();
+        let adjacency_matrix = AdjacencyMatrix::from_edge_list(&edge_list);
+        let values = [1, 2, 4, 5];
+        let heatmap =
+            domination_heatmap(&adjacency_matrix, &edge_list.edges()[0], &values, &values);
+        // The edge (0, 1) appears at [2, 2], where vertex 2 dominates it. Vertex 3 joins its
+        // edge neighbourhood at [4, 4], and until [5, 5] no vertex is adjacent to both.
+        assert_eq!(
+            heatmap,
+            vec![
+                vec![false, false, false, false],
+                vec![false, true, true, true],
+                vec![false, true, false, false],
+                vec![false, true, false, true],
+            ]
+        );
+    }
 }
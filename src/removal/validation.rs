@@ -0,0 +1,124 @@
+//! Checks that a set of removed edges could have been removed by the removal algorithms, for
+//! removals done by other means, like custom heuristics followed by [EdgeList::remove_edges].
+use thiserror::Error;
+
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::removal::adjacency::AdjacencyMatrix;
+use crate::removal::strong::strongly_dominating_vertex;
+use crate::removal::{is_filtration_dominated, DeletionMode};
+use crate::{CriticalGrade, OneCriticalGrade, Value};
+
+/// A removed edge that is not dominated in the graph of the remaining edges and the edges removed
+/// after it, see [validate_removal].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("The removed edge {} at position {position} is not dominated", .edge.edge)]
+pub struct UndominatedEdge<G> {
+    /// The position of the edge in the removed edges.
+    pub position: usize,
+    /// The edge.
+    pub edge: FilteredEdge<G>,
+}
+
+/// Checks that the removed edges are strongly filtration-dominated, so that the clique
+/// bifiltration of the remaining edges has the same homology as that of all the edges.
+///
+/// The removed edges must be given in the order in which they were removed: each edge is checked
+/// in the graph of the remaining edges and the edges removed after it, as the removal algorithms
+/// do. Returns the first edge that is not strongly filtration-dominated, if any.
+pub fn validate_strong_removal<G: CriticalGrade>(
+    remaining: &EdgeList<FilteredEdge<G>>,
+    removed: &[FilteredEdge<G>],
+) -> Result<(), UndominatedEdge<G>> {
+    validate_removal_with(remaining, removed, |adjacency_matrix, edge| {
+        strongly_dominating_vertex(adjacency_matrix, edge).is_some()
+    })
+}
+
+/// As [validate_strong_removal], but checks that the removed edges are filtration-dominated,
+/// which strongly filtration-dominated edges also are.
+pub fn validate_removal<VF: Value>(
+    remaining: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    removed: &[FilteredEdge<OneCriticalGrade<VF, 2>>],
+) -> Result<(), UndominatedEdge<OneCriticalGrade<VF, 2>>> {
+    validate_removal_with(remaining, removed, is_filtration_dominated)
+}
+
+fn validate_removal_with<G: CriticalGrade>(
+    remaining: &EdgeList<FilteredEdge<G>>,
+    removed: &[FilteredEdge<G>],
+    is_dominated: impl Fn(&AdjacencyMatrix<G>, &FilteredEdge<G>) -> bool,
+) -> Result<(), UndominatedEdge<G>> {
+    // The removed edges may have vertices that no remaining edge has.
+    let n_vertices = removed
+        .iter()
+        .map(|e| e.edge.0.max(e.edge.1) + 1)
+        .fold(remaining.n_vertices, usize::max);
+    let mut adjacency_matrix = AdjacencyMatrix::from_edges(
+        n_vertices,
+        remaining.len() + removed.len(),
+        remaining.edge_iter().chain(removed).cloned(),
+        DeletionMode::Immediate,
+    );
+    for (position, edge) in removed.iter().enumerate() {
+        if !is_dominated(&adjacency_matrix, edge) {
+            return Err(UndominatedEdge {
+                position,
+                edge: edge.clone(),
+            });
+        }
+        adjacency_matrix.delete_edge(edge);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::edges::{BareEdge, EdgeListError};
+    use crate::removal::{
+        remove_strongly_filtration_dominated_with_report, validate_removal,
+        validate_strong_removal, EdgeOrder,
+    };
+
+    #[test]
+    fn validate_removed_edges() {
+        let mut edges = get_dataset_density_edge_list(
+            Dataset::Sphere { n_points: 50 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let report = remove_strongly_filtration_dominated_with_report(
+            &mut edges,
+            EdgeOrder::ReverseLexicographic,
+            None,
+        );
+        let removed_edges: Vec<BareEdge> = report.removed.iter().map(|r| r.edge.edge).collect();
+
+        let mut remaining = edges.clone();
+        let removed = remaining.remove_edges(&removed_edges).unwrap();
+        assert_eq!(remaining.edges(), report.remaining.edges());
+
+        // The removed edges are in the order of the list, in which the removal went through them.
+        assert_eq!(validate_strong_removal(&remaining, &removed), Ok(()));
+        assert_eq!(validate_removal(&remaining, &removed), Ok(()));
+
+        // Removing the first kept edge too breaks the guarantee. The edges before it in the
+        // sorted list were all removed.
+        let kept = remaining.edges()[0];
+        let position = edges.edges().iter().position(|e| *e == kept).unwrap();
+        let mut more_removed = removed.clone();
+        more_removed.insert(position, kept);
+        let mut fewer_remaining = remaining.clone();
+        fewer_remaining.remove_edges(&[kept.edge]).unwrap();
+        let err = validate_strong_removal(&fewer_remaining, &more_removed).unwrap_err();
+        assert_eq!(err.position, position);
+        assert_eq!(err.edge, kept);
+
+        assert_eq!(
+            fewer_remaining.remove_edges(&[kept.edge]),
+            Err(EdgeListError::EdgeNotFound(kept.edge))
+        );
+    }
+}
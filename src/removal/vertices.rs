@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+
+use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+use crate::removal::adjacency::AdjacencyMatrix;
+use crate::CriticalGrade;
+
+/// The output of [remove_dominated_vertices].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VertexRemoval<G> {
+    /// The graph induced by the remaining vertices. Its vertices are renumbered so that they are
+    /// consecutive, and the edges keep their relative order in the original list.
+    pub remaining: EdgeList<FilteredEdge<G>>,
+    /// The index in the original graph of each vertex of `remaining`, in increasing order.
+    pub original_vertices: Vec<usize>,
+    /// The removed vertices, in the order in which they were removed.
+    pub removed: Vec<RemovedVertex>,
+}
+
+impl<G> VertexRemoval<G> {
+    /// The number of removed vertices.
+    pub fn n_removed(&self) -> usize {
+        self.removed.len()
+    }
+}
+
+/// A vertex removed by [remove_dominated_vertices], and the vertex that dominates it, both given
+/// by their index in the original graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemovedVertex {
+    pub vertex: usize,
+    pub dominator: usize,
+}
+
+/// Strong collapse of the vertices of a bifiltered graph: removes, one by one, the vertices
+/// whose closed neighbourhood is contained in the closed neighbourhood of another vertex at every
+/// grade, together with their edges.
+///
+/// Every vertex enters the clique filtration at grade zero. So a vertex `v` is dominated by a
+/// neighbour `w` at every grade if the edge `vw` is also at grade zero, or below it, and, for
+/// every other neighbour `x` of `v`, the edge `wx` exists and its grade is less than or equal to
+/// the grade of `vx`. Removing `v` is then a strong collapse at every grade, so the homology of
+/// the clique bifiltration is preserved. Removing a vertex may make its neighbours dominated, so
+/// they are checked again.
+///
+/// This is complementary to the removal of edges: it also removes the vertices, so it is best
+/// applied after removing the edges, to further shrink the input to mpfree. The edge list must not
+/// have repeated edges.
+pub fn remove_dominated_vertices<G: CriticalGrade>(
+    edge_list: &EdgeList<FilteredEdge<G>>,
+) -> VertexRemoval<G> {
+    let n_vertices = edge_list.n_vertices;
+    let mut adjacency_matrix = AdjacencyMatrix::new(n_vertices);
+    for edge in edge_list.edge_iter() {
+        adjacency_matrix.add_edge(edge.clone());
+    }
+
+    let mut is_removed = vec![false; n_vertices];
+    let mut in_queue = vec![true; n_vertices];
+    let mut queue: VecDeque<usize> = (0..n_vertices).collect();
+    let mut removed = Vec::new();
+    while let Some(v) = queue.pop_front() {
+        in_queue[v] = false;
+        let dominator = match dominating_vertex(&adjacency_matrix, v) {
+            Some(dominator) => dominator,
+            None => continue,
+        };
+        is_removed[v] = true;
+        removed.push(RemovedVertex {
+            vertex: v,
+            dominator,
+        });
+        let neighbours: Vec<(usize, G)> = adjacency_matrix.open_neighbours(v).collect();
+        for (x, grade) in neighbours {
+            adjacency_matrix.delete_edge(&FilteredEdge {
+                grade,
+                edge: BareEdge(v.min(x), v.max(x)),
+            });
+            if !in_queue[x] {
+                in_queue[x] = true;
+                queue.push_back(x);
+            }
+        }
+    }
+
+    let mut new_index = vec![usize::MAX; n_vertices];
+    let mut original_vertices = Vec::with_capacity(n_vertices - removed.len());
+    for v in (0..n_vertices).filter(|&v| !is_removed[v]) {
+        new_index[v] = original_vertices.len();
+        original_vertices.push(v);
+    }
+    let mut remaining = EdgeList::new(original_vertices.len());
+    for edge in edge_list.edge_iter() {
+        let BareEdge(u, v) = edge.edge;
+        if !is_removed[u] && !is_removed[v] {
+            let (u, v) = (new_index[u], new_index[v]);
+            remaining.add_edge(FilteredEdge {
+                grade: edge.grade.clone(),
+                edge: BareEdge(u.min(v), u.max(v)),
+            });
+        }
+    }
+
+    VertexRemoval {
+        remaining,
+        original_vertices,
+        removed,
+    }
+}
+
+/// Returns a vertex that dominates `v` at every grade, if there is one.
+fn dominating_vertex<G: CriticalGrade>(
+    adjacency_matrix: &AdjacencyMatrix<G>,
+    v: usize,
+) -> Option<usize> {
+    let zero = G::zero();
+    adjacency_matrix
+        .open_neighbours(v)
+        .filter(|(_w, grade_vw)| grade_vw.lte(&zero))
+        .map(|(w, _grade_vw)| w)
+        .find(|&w| {
+            adjacency_matrix
+                .open_neighbours(v)
+                .filter(|&(x, _)| x != w)
+                .all(|(x, grade_vx)| {
+                    matches!(adjacency_matrix.edge_grade(w, x), Some(grade_wx) if grade_wx.lte(&grade_vx))
+                })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::chain_complex::ChainComplex;
+    use crate::edges::{BareEdge, EdgeList, FilteredEdge};
+    use crate::removal::{remove_dominated_vertices, RemovedVertex};
+    use crate::{CriticalGrade, OneCriticalGrade};
+
+    fn edge(u: usize, v: usize, grade: [usize; 2]) -> FilteredEdge<OneCriticalGrade<usize, 2>> {
+        FilteredEdge {
+            grade: OneCriticalGrade(grade),
+            edge: BareEdge(u, v),
+        }
+    }
+
+    #[test]
+    fn remove_cone_vertex() {
+        // Vertex 3 is joined to 0 at grade zero, and its other edges appear after those of 0.
+        let edges: EdgeList<_> = vec![
+            edge(0, 1, [1, 1]),
+            edge(0, 2, [1, 2]),
+            edge(1, 2, [2, 2]),
+            edge(0, 3, [0, 0]),
+            edge(1, 3, [1, 1]),
+            edge(2, 3, [2, 2]),
+        ]
+        .into();
+        let removal = remove_dominated_vertices(&edges);
+        assert_eq!(removal.n_removed(), 1);
+        assert_eq!(
+            removal.removed[0],
+            RemovedVertex {
+                vertex: 3,
+                dominator: 0
+            }
+        );
+        assert_eq!(removal.original_vertices, vec![0, 1, 2]);
+        assert_eq!(removal.remaining.edges(), &edges.edges()[..3]);
+    }
+
+    #[test]
+    fn keep_vertices_not_dominated_at_grade_zero() {
+        // The edge between 0 and 3 appears after grade zero, so the components differ before it.
+        let edges: EdgeList<_> = vec![edge(0, 1, [1, 1]), edge(0, 3, [0, 1])].into();
+        let removal = remove_dominated_vertices(&edges);
+        assert_eq!(removal.n_removed(), 0);
+        assert_eq!(removal.remaining.edges(), edges.edges());
+    }
+
+    #[test]
+    fn hilbert_function_preserved_by_vertex_removal() {
+        let mut rng = StdRng::seed_from_u64(1303);
+        let random_grade = |r: &mut StdRng| [r.gen_range(0..4), r.gen_range(0..4)];
+        // A random graph on the first vertices, and vertices attached to them that are dominated.
+        let n_base = 6;
+        let n_vertices = 10;
+        let mut edges: EdgeList<FilteredEdge<OneCriticalGrade<usize, 2>>> =
+            EdgeList::new(n_vertices);
+        let mut base_grades = vec![vec![None; n_base]; n_base];
+        for u in 0..n_base {
+            for v in (u + 1)..n_base {
+                if rng.gen_bool(0.7) {
+                    let grade = OneCriticalGrade(random_grade(&mut rng));
+                    base_grades[u][v] = Some(grade);
+                    base_grades[v][u] = Some(grade);
+                    edges.add_edge(edge(u, v, grade.0));
+                }
+            }
+        }
+        for v in n_base..n_vertices {
+            let w = rng.gen_range(0..n_base);
+            edges.add_edge(edge(w, v, [0, 0]));
+            for x in 0..n_base {
+                if let Some(grade_wx) = base_grades[w][x] {
+                    let grade = grade_wx.join(&OneCriticalGrade(random_grade(&mut rng)));
+                    edges.add_edge(edge(x, v, grade.0));
+                }
+            }
+        }
+        let removal = remove_dominated_vertices(&edges);
+        assert!(removal.n_removed() >= n_vertices - n_base);
+
+        let mut grid = Vec::new();
+        for x in 0..4 {
+            for y in 0..4 {
+                grid.push(OneCriticalGrade([x, y]));
+            }
+        }
+        for homology in 0..2 {
+            let before = ChainComplex::from_flag_filtration(&edges, homology);
+            let after = ChainComplex::from_flag_filtration(&removal.remaining, homology);
+            assert_eq!(
+                before.hilbert_function(&grid).unwrap(),
+                after.hilbert_function(&grid).unwrap()
+            );
+        }
+    }
+}
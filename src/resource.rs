@@ -0,0 +1,140 @@
+//! Limits on the resources used by long computations. Requires the `memory-guard` feature.
+//!
+//! Building a flag filtration, or removing edges from a large graph, can take more memory than
+//! available. A [MemoryGuard] reads, periodically, the memory used by the current process, and
+//! fails once it exceeds a limit. It can be passed as the check of
+//! [build_flag_filtration_with_check](crate::filtration::build_flag_filtration_with_check) and of
+//! [compute_minimal_presentation_with_check](crate::mpfree::compute_minimal_presentation_with_check),
+//! through [MemoryGuard::check_fn], and to the removal algorithms through
+//! [RemovalOptions](crate::removal::RemovalOptions).
+//!
+//! The memory usage is read on Linux, macOS and Windows. On other platforms, every check fails
+//! with [MemoryGuardError::Unavailable].
+use thiserror::Error;
+
+/// The number of calls to [MemoryGuard::check] between two reads of the memory usage, by default.
+const DEFAULT_CHECK_EVERY: usize = 1000;
+
+/// Which memory of the process a [MemoryGuard] limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemoryMeasure {
+    /// The memory in RAM: the resident set size on Linux and macOS, and the working set on
+    /// Windows.
+    #[default]
+    Physical,
+    /// The virtual memory: the virtual size on Linux and macOS, and the pagefile usage on
+    /// Windows.
+    Virtual,
+}
+
+/// Fails once the memory used by the current process exceeds a limit. See the
+/// [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryGuard {
+    /// The maximum memory, in bytes.
+    pub max_bytes: usize,
+    /// Which memory is limited.
+    pub measure: MemoryMeasure,
+    /// The memory usage is read once every this many calls to [MemoryGuard::check], since
+    /// reading it is much slower than the steps of the computations.
+    pub check_every: usize,
+}
+
+impl MemoryGuard {
+    /// A guard that limits the physical memory of the process to the given number of bytes.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            measure: MemoryMeasure::default(),
+            check_every: DEFAULT_CHECK_EVERY,
+        }
+    }
+
+    /// A guard that limits the physical memory of the process to the given number of gigabytes.
+    pub fn with_gigabytes(gigabytes: usize) -> Self {
+        Self::new(gigabytes.saturating_mul(1024 * 1024 * 1024))
+    }
+
+    /// The memory used by the current process, in bytes, according to the measure of the guard.
+    /// Returns None if it cannot be read on this platform.
+    pub fn current_usage(&self) -> Option<usize> {
+        memory_stats::memory_stats().map(|stats| match self.measure {
+            MemoryMeasure::Physical => stats.physical_mem,
+            MemoryMeasure::Virtual => stats.virtual_mem,
+        })
+    }
+
+    /// Checks the memory usage, if `iteration` is a multiple of `check_every`. Computations call
+    /// it with an increasing counter, so that the memory is read periodically.
+    pub fn check(&self, iteration: usize) -> Result<(), MemoryGuardError> {
+        // A `check_every` of 0 reads the memory usage on every call.
+        if iteration.checked_rem(self.check_every).unwrap_or(0) != 0 {
+            return Ok(());
+        }
+        let used = self.current_usage().ok_or(MemoryGuardError::Unavailable)?;
+        if used > self.max_bytes {
+            return Err(MemoryGuardError::LimitExceeded {
+                used,
+                limit: self.max_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns [MemoryGuard::check] as a closure, to be passed as the check of
+    /// [build_flag_filtration_with_check](crate::filtration::build_flag_filtration_with_check).
+    pub fn check_fn(self) -> impl Fn(usize) -> Result<(), MemoryGuardError> {
+        move |iteration| self.check(iteration)
+    }
+}
+
+/// The error of a failed [MemoryGuard::check].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryGuardError {
+    #[error("Memory limit exceeded: using {used} bytes, with a limit of {limit} bytes")]
+    LimitExceeded { used: usize, limit: usize },
+
+    #[error("The memory usage cannot be read on this platform")]
+    Unavailable,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::edges::{BareEdge, FilteredEdge};
+    use crate::filtration::{build_flag_filtration_with_check, Filtration};
+    use crate::resource::{MemoryGuard, MemoryGuardError};
+    use crate::simplicial_complex::MapSimplicialComplex;
+    use crate::OneCriticalGrade;
+
+    #[test]
+    fn memory_guard_stops_filtration() {
+        let edges = || {
+            (0..20).flat_map(|u| {
+                ((u + 1)..20).map(move |v| FilteredEdge {
+                    grade: OneCriticalGrade([u, v]),
+                    edge: BareEdge(u, v),
+                })
+            })
+        };
+        let used = MemoryGuard::new(0).current_usage().unwrap();
+        assert!(used > 0);
+
+        let low = MemoryGuard {
+            check_every: 1,
+            ..MemoryGuard::new(1)
+        };
+        let result: Result<Filtration<_, MapSimplicialComplex>, _> =
+            build_flag_filtration_with_check(20, 2, edges(), Some(low.check_fn()));
+        assert!(matches!(
+            result,
+            Err(MemoryGuardError::LimitExceeded { limit: 1, .. })
+        ));
+
+        let high = MemoryGuard::new(usize::MAX);
+        let filtration: Filtration<_, MapSimplicialComplex> =
+            build_flag_filtration_with_check(20, 2, edges(), Some(high.check_fn())).unwrap();
+        assert_eq!(filtration.sizes(), vec![20, 190, 1140]);
+    }
+}
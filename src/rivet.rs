@@ -0,0 +1,318 @@
+//! Interface with RIVET that allows to compute the bigraded Betti numbers and the Hilbert function
+//! of the homology of a clique bifiltration, as a second backend besides [crate::mpfree].
+//!
+//! RIVET is given the firep file of the chain complex, and the output of
+//! `rivet_console --betti` is parsed. Grades are reported by their values, as printed by RIVET,
+//! instead of by their indices in the grid of grades, so that the Betti numbers of different
+//! bifiltrations of the same complex, like those before and after removing edges, are comparable.
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::chain_complex::PresentationFormat;
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::external::{ExternalError, Tool, ToolOptions};
+use crate::mpfree::write_flag_free_implicit_representation;
+use crate::{OneCriticalGrade, Value};
+
+/// The `rivet_console` binary. Its path can be set with the `RIVET_CONSOLE_PATH` environment
+/// variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rivet;
+
+impl Tool for Rivet {
+    const BINARY: &'static str = "rivet_console";
+    const BINARY_ENV_VAR: &'static str = "RIVET_CONSOLE_PATH";
+}
+
+/// How to run RIVET. See [ToolOptions].
+pub type RivetOptions = ToolOptions<Rivet>;
+
+/// The time taken to run RIVET, and the parsed output.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RivetComputationSummary {
+    pub timers: RivetComputationTime,
+    pub output: ParsedRivetOutput,
+    /// The input file of RIVET, see [WorkingFiles](crate::external::WorkingFiles). It still
+    /// exists only if the options keep it.
+    pub files: Vec<PathBuf>,
+}
+
+/// Timers related to the computation of invariants with RIVET.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RivetComputationTime {
+    /// Building the chain complex and writing it to disk.
+    pub write_input: Duration,
+    pub rivet: Duration,
+}
+
+/// A value at a grade, like a Betti number or the dimension of a homology module.
+/// The grade is given by its coordinates, as printed by RIVET.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BigradedValue {
+    pub x: String,
+    pub y: String,
+    pub value: usize,
+}
+
+/// The invariants computed by RIVET.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParsedRivetOutput {
+    /// The number of distinct values of the first parameter.
+    pub x_grades: usize,
+    /// The number of distinct values of the second parameter.
+    pub y_grades: usize,
+    /// The non-zero dimensions of the homology module at the points of the grid of grades.
+    /// Only comparable between bifiltrations with the same grades.
+    pub hilbert_function: Vec<BigradedValue>,
+    /// The non-zero bigraded Betti numbers of the homology module, in dimensions 0, 1, and 2.
+    pub betti_numbers: [Vec<BigradedValue>; 3],
+}
+
+impl ParsedRivetOutput {
+    /// The sum of the bigraded Betti numbers of each dimension. The first two are the sizes of a
+    /// minimal presentation: the number of generators and of relations.
+    pub fn total_betti_numbers(&self) -> [usize; 3] {
+        self.betti_numbers
+            .clone()
+            .map(|values| values.iter().map(|b| b.value).sum())
+    }
+}
+
+/// A error that happened when executing RIVET.
+#[derive(Error, Debug)]
+pub enum RivetError {
+    #[error(transparent)]
+    External(#[from] ExternalError),
+
+    #[error("Error parsing RIVET output: {0}")]
+    Parsing(String),
+
+    #[error("A unknown IO error happened")]
+    Io(#[from] io::Error),
+
+    #[error("Error parsing number: {0}")]
+    WrongNumberFormat(#[from] std::num::ParseIntError),
+}
+
+/// Compute the bigraded Betti numbers and Hilbert function of the homology at the given dimension
+/// of the clique bifiltration of the given bifiltered edge list, with RIVET.
+///
+/// The `name` parameter is used to name and identify temporary files.
+/// RIVET is run with the default [RivetOptions].
+pub fn compute_rivet_invariants<VF: Value>(
+    name: &str,
+    homology: usize,
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+) -> Result<RivetComputationSummary, RivetError> {
+    compute_rivet_invariants_with_options(name, homology, edge_list, &RivetOptions::default())
+}
+
+/// As [compute_rivet_invariants], but RIVET is run with the given options.
+pub fn compute_rivet_invariants_with_options<VF: Value>(
+    name: &str,
+    homology: usize,
+    edge_list: &EdgeList<FilteredEdge<OneCriticalGrade<VF, 2>>>,
+    options: &RivetOptions,
+) -> Result<RivetComputationSummary, RivetError> {
+    let mut timers = RivetComputationTime::default();
+
+    let mut files = options.working_files()?;
+    let filepath_input = files.file(&format!("{}_firep", name));
+
+    let start_write = std::time::Instant::now();
+    let file = File::create(&filepath_input)?;
+    let mut writer = BufWriter::new(file);
+    write_flag_free_implicit_representation(
+        edge_list,
+        homology,
+        PresentationFormat::Firep,
+        &mut writer,
+    )?;
+    drop(writer);
+    timers.write_input = start_write.elapsed();
+
+    let start_rivet = std::time::Instant::now();
+    let output = run_rivet_with_options(&filepath_input, homology, options)?;
+    timers.rivet = start_rivet.elapsed();
+
+    Ok(RivetComputationSummary {
+        timers,
+        output,
+        files: files.paths().to_vec(),
+    })
+}
+
+/// Runs `rivet_console --betti`, with the default [RivetOptions], on the given input file, and
+/// parses its output.
+pub fn run_rivet<P: AsRef<Path>>(
+    filepath_in: P,
+    homology: usize,
+) -> Result<ParsedRivetOutput, RivetError> {
+    run_rivet_with_options(filepath_in, homology, &RivetOptions::default())
+}
+
+/// As [run_rivet], but RIVET is run with the given options.
+pub fn run_rivet_with_options<P: AsRef<Path>>(
+    filepath_in: P,
+    homology: usize,
+    options: &RivetOptions,
+) -> Result<ParsedRivetOutput, RivetError> {
+    let homology = homology.to_string();
+    let stdout = options.run([
+        filepath_in.as_ref().as_os_str(),
+        "--betti".as_ref(),
+        "-H".as_ref(),
+        homology.as_ref(),
+    ])?;
+    parse_rivet_output(BufReader::new(stdout.as_slice()))
+}
+
+/// The part of the output of `rivet_console --betti` that is being parsed.
+enum Section {
+    None,
+    XGrades,
+    YGrades,
+    Dimensions,
+    Betti(usize),
+}
+
+/// Parses the output of `rivet_console --betti`.
+fn parse_rivet_output<R: BufRead>(r: R) -> Result<ParsedRivetOutput, RivetError> {
+    let mut x_values: Vec<String> = Vec::new();
+    let mut y_values: Vec<String> = Vec::new();
+    let mut output = ParsedRivetOutput::default();
+    let mut section = Section::None;
+    for line in r.lines() {
+        let line = line?;
+        let line = line.trim();
+        match line {
+            "" => continue,
+            "x-grades" => section = Section::XGrades,
+            "y-grades" => section = Section::YGrades,
+            "Dimensions > 0:" => section = Section::Dimensions,
+            "Betti numbers:" => section = Section::None,
+            "xi_0:" => section = Section::Betti(0),
+            "xi_1:" => section = Section::Betti(1),
+            "xi_2:" => section = Section::Betti(2),
+            _ => match section {
+                Section::XGrades => x_values.push(line.to_string()),
+                Section::YGrades => y_values.push(line.to_string()),
+                Section::Dimensions => output
+                    .hilbert_function
+                    .push(parse_bigraded_value(line, &x_values, &y_values)?),
+                Section::Betti(dim) => output.betti_numbers[dim]
+                    .push(parse_bigraded_value(line, &x_values, &y_values)?),
+                Section::None => {}
+            },
+        }
+    }
+    output.x_grades = x_values.len();
+    output.y_grades = y_values.len();
+    output.hilbert_function.sort();
+    for betti_numbers in output.betti_numbers.iter_mut() {
+        betti_numbers.sort();
+    }
+    Ok(output)
+}
+
+/// Parses a line of the form `(i, j, value)`, where `i` and `j` are indices of grades.
+fn parse_bigraded_value(
+    line: &str,
+    x_values: &[String],
+    y_values: &[String],
+) -> Result<BigradedValue, RivetError> {
+    let (i, j, value) = parse_triple(line)?;
+    let grade_error = || RivetError::Parsing(format!("Grade index out of range: {}", line));
+    Ok(BigradedValue {
+        x: x_values.get(i).ok_or_else(grade_error)?.clone(),
+        y: y_values.get(j).ok_or_else(grade_error)?.clone(),
+        value,
+    })
+}
+
+/// Parses a line of the form `(i, j, value)`.
+fn parse_triple(line: &str) -> Result<(usize, usize, usize), RivetError> {
+    let inner = line
+        .strip_prefix('(')
+        .and_then(|l| l.strip_suffix(')'))
+        .ok_or_else(|| RivetError::Parsing(format!("Expected a triple, found: {}", line)))?;
+    let mut parts = inner.split(',').map(str::trim);
+    let mut next = || {
+        parts
+            .next()
+            .ok_or_else(|| RivetError::Parsing(format!("Expected a triple, found: {}", line)))
+    };
+    Ok((next()?.parse()?, next()?.parse()?, next()?.parse()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::rivet::{parse_rivet_output, BigradedValue, RivetError};
+
+    const BETTI_OUTPUT: &str = "x-grades
+0
+1/2
+1
+
+y-grades
+0
+2
+
+Dimensions > 0:
+(0, 1, 1)
+(1, 1, 2)
+(2, 1, 1)
+
+Betti numbers:
+xi_0:
+(0, 1, 1)
+(1, 1, 1)
+xi_1:
+(2, 1, 1)
+xi_2:
+";
+
+    #[test]
+    fn parse_betti_output() {
+        let output = parse_rivet_output(BufReader::new(BETTI_OUTPUT.as_bytes())).unwrap();
+        let value = |x: &str, y: &str, value| BigradedValue {
+            x: x.to_string(),
+            y: y.to_string(),
+            value,
+        };
+        assert_eq!(output.x_grades, 3);
+        assert_eq!(output.y_grades, 2);
+        assert_eq!(
+            output.hilbert_function,
+            vec![value("0", "2", 1), value("1", "2", 1), value("1/2", "2", 2)]
+        );
+        assert_eq!(
+            output.betti_numbers,
+            [
+                vec![value("0", "2", 1), value("1/2", "2", 1)],
+                vec![value("1", "2", 1)],
+                vec![]
+            ]
+        );
+        assert_eq!(output.total_betti_numbers(), [2, 1, 0]);
+    }
+
+    #[test]
+    fn parse_bad_betti_output() {
+        let output = "x-grades\n0\n\ny-grades\n0\n\nBetti numbers:\nxi_0:\n(0, 1, 1)\n";
+        assert!(matches!(
+            parse_rivet_output(BufReader::new(output.as_bytes())),
+            Err(RivetError::Parsing(_))
+        ));
+    }
+}
@@ -1,7 +1,17 @@
+//! Simplicial complexes that store their simplices by dimension, and give each simplex an index in
+//! its dimension, in order of insertion.
+//!
+//! There are two implementations of [SimplicialComplex], which differ on how they find a simplex
+//! from its vertices: [MapSimplicialComplex] uses a hash map, and [SortedSimplicialComplex] uses
+//! binary search in sorted vectors. Both encode simplices as integer keys, see [SimplexKey], and
+//! have wide variants for complexes whose keys do not fit in a `usize`.
+use num::traits::{WrappingAdd, WrappingMul};
+use num::{NumCast, PrimInt, Unsigned};
 use rustc_hash::FxHashMap;
 use sorted_iter::assume::AssumeSortedByItemExt;
 use sorted_iter::SortedIterator;
 use std::collections::hash_map::Entry;
+use std::hash::Hash;
 
 pub type Vertex = usize;
 pub type Dimension = usize;
@@ -15,6 +25,19 @@ pub trait SimplicialComplex<'a> {
 
     fn max_dimension(&self) -> Dimension;
 
+    /// The number of vertices the simplicial complex was created for: its vertices are in the range
+    /// 0..`max_vertices`. By default [Vertex::MAX], for complexes that take any vertex.
+    fn max_vertices(&self) -> Vertex {
+        Vertex::MAX
+    }
+
+    /// Returns whether the simplices of dimension up to `max_dim` on `max_vertices` vertices can be
+    /// added to a simplicial complex of this type. By default true, for complexes that do not
+    /// encode the simplices in keys of bounded size, see [SimplexKey::fits].
+    fn fits(_max_vertices: Vertex, _max_dim: Dimension) -> bool {
+        true
+    }
+
     fn n_cells(&self, dim: Dimension) -> usize;
 
     /// Add a simplex, given by a vector of vertices, to the simplicial complex.
@@ -33,6 +56,16 @@ pub trait SimplicialComplex<'a> {
         iter: I,
     ) -> Option<(Dimension, usize)>;
 
+    /// As [SimplicialComplex::add_iter], but the implementation may skip checking that the
+    /// boundaries of the simplex have been added before, to be faster when the caller knows it.
+    fn add_iter_unchecked<I: SortedIterator<Item = usize>>(
+        &mut self,
+        dim: Dimension,
+        iter: I,
+    ) -> Option<(Dimension, usize)> {
+        self.add_iter(dim, iter)
+    }
+
     /// Returns an iterator over the boundary of the simplex of the given index.
     /// The iterator returns the indexes of the simplexes in the boundary.
     fn boundary_iterator(&'a self, dim: Dimension, idx: usize) -> Self::BoundaryIterator;
@@ -48,29 +81,127 @@ pub trait SimplicialComplex<'a> {
 
     /// Returns an iterator over the vertices of the simplex of the given index.
     fn simplex_vertices(&self, dim: Dimension, idx: usize) -> Self::VertexIterator;
+
+    /// Returns the dimension and the index of the simplex with the given vertices, if it is in
+    /// the simplicial complex. The vertices must be strictly increasing, otherwise no simplex is
+    /// found.
+    fn find(&self, s: &[Vertex]) -> Option<(Dimension, usize)>;
+
+    /// An estimate of the heap memory used by the simplicial complex, in bytes, from the
+    /// capacities of its vectors and maps.
+    fn memory_bytes_estimate(&self) -> usize;
 }
 
-/// A SimplexKey encodes a simplex as a non-negative integer.
-type SimplexKey = usize;
+/// A SimplexKey encodes a simplex as a non-negative integer: the simplex with sorted vertices
+/// v_0, ..., v_d is encoded as v_0 + v_1 * n + ... + v_d * n^d, where n is the maximum number of
+/// vertices.
+///
+/// The encoding overflows when n^(d + 1) does not fit in the key type, see [SimplexKey::fits].
+/// An overflow panics in debug builds, and silently wraps around in release builds.
+pub trait SimplexKey:
+    PrimInt + Unsigned + WrappingAdd + WrappingMul + Hash + std::fmt::Debug + Default + Send + Sync
+{
+    /// Returns whether the simplices of dimension up to `max_dim` on `max_vertices` vertices can be
+    /// encoded without overflow.
+    fn fits(max_vertices: Vertex, max_dim: Dimension) -> bool {
+        match <Self as NumCast>::from(max_vertices) {
+            Some(n) => num::checked_pow(n, max_dim + 1).is_some(),
+            None => false,
+        }
+    }
+
+    fn from_vertex(v: Vertex) -> Self {
+        <Self as NumCast>::from(v).expect("The vertex does not fit in the simplex key type")
+    }
+
+    fn to_vertex(self) -> Vertex {
+        self.to_usize()
+            .expect("The simplex key does not fit in a vertex")
+    }
+}
+
+impl SimplexKey for usize {}
+impl SimplexKey for u64 {}
+impl SimplexKey for u128 {}
+
+/// Finds a simplex, given by its vertices, in a simplicial complex that indexes its simplices by
+/// key. See [SimplicialComplex::find].
+fn find_simplex_key<C: KeyIndex<K>, K: SimplexKey>(
+    complex: &C,
+    max_dim: Dimension,
+    s: &[Vertex],
+) -> Option<(Dimension, usize)> {
+    let max_vertices = complex.max_vertices();
+    let in_range = s.iter().all(|v| *v < max_vertices);
+    let strictly_increasing = s.windows(2).all(|w| w[0] < w[1]);
+    if s.is_empty() || s.len() > max_dim + 1 || !in_range || !strictly_increasing {
+        return None;
+    }
+    let dim = s.len() - 1;
+    let key: K = simplex_to_key(max_vertices, s.iter().copied().assume_sorted_by_item());
+    complex.key_index(dim, &key).map(|idx| (dim, idx))
+}
 
+/// Get the key of the simplex given by a stream of sorted vertices.
+fn simplex_to_key<K: SimplexKey, I: SortedIterator<Item = usize>>(
+    max_vertices: Vertex,
+    iter: I,
+) -> K {
+    let max_n = K::from_vertex(max_vertices);
+    let mut k = K::zero();
+    let mut exp = K::one();
+    for v in iter {
+        debug_assert!(v < max_vertices, "The vertex {} is out of range", v);
+        k = key_add(k, key_mul(K::from_vertex(v), exp));
+        exp = key_mul(exp, max_n);
+    }
+    k
+}
+
+fn key_add<K: SimplexKey>(a: K, b: K) -> K {
+    debug_assert!(a.checked_add(&b).is_some(), "Simplex key overflow");
+    a.wrapping_add(&b)
+}
+
+fn key_mul<K: SimplexKey>(a: K, b: K) -> K {
+    debug_assert!(a.checked_mul(&b).is_some(), "Simplex key overflow");
+    a.wrapping_mul(&b)
+}
+
+/// A simplicial complex that stores its simplices as keys, see [SimplexKey].
+///
+/// With the default `usize` keys, on 64-bit targets, complexes on 10^5 vertices can be built up
+/// to dimension 2. Use [WideMapSimplicialComplex] for larger complexes.
 #[derive(Default, Debug)]
-pub struct MapSimplicialComplex {
+pub struct MapSimplicialComplex<K = usize> {
     /// Associates a simplex id to its key.
     /// The ith-element of the vector contains the simplices of the dimension i.
-    simplices_by_dim: Vec<Vec<SimplexKey>>,
+    simplices_by_dim: Vec<Vec<K>>,
 
     /// Associates a simplex key to its index in the vector of its dimension in simplices_by_dim.
-    key_to_idx: Vec<FxHashMap<SimplexKey, usize>>,
+    key_to_idx: Vec<FxHashMap<K, usize>>,
 
     /// Maximum number of vertices.
     max_n: Vertex,
 }
 
+/// A [MapSimplicialComplex] with 128-bit keys, that can encode, for example, simplices of
+/// dimension up to 6 on 10^5 vertices.
+pub type WideMapSimplicialComplex = MapSimplicialComplex<u128>;
+
 impl MapSimplicialComplex {
     pub fn new(max_vertices: Vertex, max_dim: Dimension) -> Self {
+        Self::with_keys(max_vertices, max_dim)
+    }
+}
+
+impl<K: SimplexKey> MapSimplicialComplex<K> {
+    /// As [MapSimplicialComplex::new], but with keys of type `K`.
+    pub fn with_keys(max_vertices: Vertex, max_dim: Dimension) -> Self {
         let mut s = MapSimplicialComplex {
             max_n: max_vertices,
-            ..Default::default()
+            simplices_by_dim: Vec::new(),
+            key_to_idx: Vec::new(),
         };
         s.simplices_by_dim.resize(max_dim + 1, Default::default());
         s.key_to_idx.resize(max_dim + 1, Default::default());
@@ -78,20 +209,14 @@ impl MapSimplicialComplex {
     }
 
     /// Get the simplex key from a stream of vertices.
-    fn simplex_to_key<I: SortedIterator<Item = usize>>(&self, iter: I) -> SimplexKey {
-        let mut k: SimplexKey = 0;
-        let mut exp: SimplexKey = 1;
-        for v in iter {
-            k += v * exp;
-            exp *= self.max_n;
-        }
-        k
+    fn simplex_to_key<I: SortedIterator<Item = usize>>(&self, iter: I) -> K {
+        simplex_to_key(self.max_n, iter)
     }
 
     fn add_simplex_key_check_boundaries(
         &mut self,
         dim: Dimension,
-        key: SimplexKey,
+        key: K,
     ) -> Option<(Dimension, usize)> {
         if dim > 0 {
             let it = SimplexKeyBoundaryIterator::new(self.max_n, dim, key);
@@ -106,7 +231,7 @@ impl MapSimplicialComplex {
         self.add_simplex_key(dim, key)
     }
 
-    fn add_simplex_key(&mut self, dim: Dimension, key: SimplexKey) -> Option<(Dimension, usize)> {
+    fn add_simplex_key(&mut self, dim: Dimension, key: K) -> Option<(Dimension, usize)> {
         match self.key_to_idx[dim].entry(key) {
             Entry::Occupied(_) => None,
             Entry::Vacant(entry) => {
@@ -126,23 +251,41 @@ impl MapSimplicialComplex {
         }
     }
 
-    fn has_simplex_key(&self, dim: Dimension, k: &SimplexKey) -> bool {
+    fn has_simplex_key(&self, dim: Dimension, k: &K) -> bool {
         self.key_to_idx[dim].contains_key(k)
     }
 }
 
-impl<'a> SimplicialComplex<'a> for MapSimplicialComplex {
-    type BoundaryIterator = MapBoundaryIterator<'a>;
-    type VertexIterator = SimplexKeyVertexIterator;
+impl<K: SimplexKey> KeyIndex<K> for MapSimplicialComplex<K> {
+    fn max_vertices(&self) -> Vertex {
+        self.max_n
+    }
+
+    fn key_index(&self, dim: Dimension, key: &K) -> Option<usize> {
+        self.key_to_idx[dim].get(key).copied()
+    }
+}
+
+impl<'a, K: SimplexKey + 'a> SimplicialComplex<'a> for MapSimplicialComplex<K> {
+    type BoundaryIterator = MapBoundaryIterator<'a, K>;
+    type VertexIterator = SimplexKeyVertexIterator<K>;
 
     fn new(max_n: Vertex, max_dim: Dimension) -> Self {
-        Self::new(max_n, max_dim)
+        Self::with_keys(max_n, max_dim)
+    }
+
+    fn fits(max_vertices: Vertex, max_dim: Dimension) -> bool {
+        K::fits(max_vertices, max_dim)
     }
 
     fn max_dimension(&self) -> Dimension {
         self.simplices_by_dim.len() - 1
     }
 
+    fn max_vertices(&self) -> Vertex {
+        self.max_n
+    }
+
     fn n_cells(&self, dim: Dimension) -> usize {
         self.simplices_by_dim[dim].len()
     }
@@ -165,6 +308,15 @@ impl<'a> SimplicialComplex<'a> for MapSimplicialComplex {
         self.add_simplex_key_check_boundaries(dim, key)
     }
 
+    fn add_iter_unchecked<I: SortedIterator<Item = usize>>(
+        &mut self,
+        dim: Dimension,
+        iter: I,
+    ) -> Option<(Dimension, usize)> {
+        let key = self.simplex_to_key(iter);
+        self.add_simplex_key(dim, key)
+    }
+
     fn boundary_iterator(&'a self, dim: Dimension, idx: usize) -> Self::BoundaryIterator {
         MapBoundaryIterator::new(self, dim, self.simplices_by_dim[dim][idx])
     }
@@ -180,28 +332,236 @@ impl<'a> SimplicialComplex<'a> for MapSimplicialComplex {
     fn simplex_vertices(&self, dim: Dimension, idx: usize) -> Self::VertexIterator {
         SimplexKeyVertexIterator::new(dim, self.simplices_by_dim[dim][idx], self.max_n)
     }
+
+    fn find(&self, s: &[Vertex]) -> Option<(Dimension, usize)> {
+        find_simplex_key(self, self.max_dimension(), s)
+    }
+
+    fn memory_bytes_estimate(&self) -> usize {
+        // Each entry of a hash map also has a control byte.
+        let map_entry = std::mem::size_of::<(K, usize)>() + 1;
+        vec_bytes(&self.simplices_by_dim)
+            + self.simplices_by_dim.iter().map(vec_bytes).sum::<usize>()
+            + vec_bytes(&self.key_to_idx)
+            + self
+                .key_to_idx
+                .iter()
+                .map(|map| map.capacity() * map_entry)
+                .sum::<usize>()
+    }
 }
 
-pub struct MapBoundaryIterator<'a> {
-    complex: &'a MapSimplicialComplex,
+/// A simplicial complex that stores its simplices as keys, see [SimplexKey], like
+/// [MapSimplicialComplex], but finds them by binary search instead of hashing.
+///
+/// The simplices of each dimension are split in buckets by their minimum vertex, and each bucket
+/// is a vector sorted by key. Looking up a facet is a binary search in a small vector, and there
+/// is no hashing, but insertions in large buckets are slow. Which implementation is faster
+/// depends on the complex: compare them with `cargo bench --bench simplicial_complex`.
+#[derive(Default, Debug)]
+pub struct SortedSimplicialComplex<K = usize> {
+    /// Associates a simplex id to its key.
+    /// The ith-element of the vector contains the simplices of the dimension i.
+    simplices_by_dim: Vec<Vec<K>>,
+
+    /// For each dimension and vertex, the keys of the simplices of that dimension whose minimum
+    /// vertex is the given vertex, in increasing order, together with their indices.
+    buckets: Vec<Vec<Vec<(K, usize)>>>,
 
-    simplex_key_iterator: SimplexKeyBoundaryIterator,
+    /// Maximum number of vertices.
+    max_n: Vertex,
 }
 
-impl MapBoundaryIterator<'_> {
-    fn new(
-        complex: &'_ MapSimplicialComplex,
-        dimension: Dimension,
-        key: SimplexKey,
-    ) -> MapBoundaryIterator<'_> {
-        MapBoundaryIterator {
+/// A [SortedSimplicialComplex] with 128-bit keys, see [WideMapSimplicialComplex].
+pub type WideSortedSimplicialComplex = SortedSimplicialComplex<u128>;
+
+impl<K: SimplexKey> SortedSimplicialComplex<K> {
+    /// As [SimplicialComplex::new], but with keys of type `K`.
+    pub fn with_keys(max_vertices: Vertex, max_dim: Dimension) -> Self {
+        SortedSimplicialComplex {
+            max_n: max_vertices,
+            simplices_by_dim: vec![Vec::new(); max_dim + 1],
+            buckets: vec![vec![Vec::new(); max_vertices]; max_dim + 1],
+        }
+    }
+
+    /// The bucket of the simplex with the given key: the minimum vertex of the simplex is the
+    /// least significant digit of its key.
+    fn bucket(&self, key: &K) -> usize {
+        (*key % K::from_vertex(self.max_n)).to_vertex()
+    }
+
+    fn add_simplex_key_check_boundaries(
+        &mut self,
+        dim: Dimension,
+        key: K,
+    ) -> Option<(Dimension, usize)> {
+        if dim > 0 {
+            for facet_key in SimplexKeyBoundaryIterator::new(self.max_n, dim, key) {
+                assert!(
+                    self.key_index(dim - 1, &facet_key).is_some(),
+                    "Adding a simplex requires that its boundaries have been added before."
+                )
+            }
+        }
+        self.add_simplex_key(dim, key)
+    }
+
+    fn add_simplex_key(&mut self, dim: Dimension, key: K) -> Option<(Dimension, usize)> {
+        let idx = self.simplices_by_dim[dim].len();
+        let bucket_idx = self.bucket(&key);
+        let bucket = &mut self.buckets[dim][bucket_idx];
+        match bucket.binary_search_by(|(k, _)| k.cmp(&key)) {
+            Ok(_) => None,
+            Err(position) => {
+                if dim == 0 {
+                    assert!(idx < self.max_n, "Exceeded the maximum number of vertices.");
+                }
+                bucket.insert(position, (key, idx));
+                self.simplices_by_dim[dim].push(key);
+                Some((dim, idx))
+            }
+        }
+    }
+}
+
+impl<K: SimplexKey> KeyIndex<K> for SortedSimplicialComplex<K> {
+    fn max_vertices(&self) -> Vertex {
+        self.max_n
+    }
+
+    fn key_index(&self, dim: Dimension, key: &K) -> Option<usize> {
+        let bucket = &self.buckets[dim][self.bucket(key)];
+        bucket
+            .binary_search_by(|(k, _)| k.cmp(key))
+            .ok()
+            .map(|position| bucket[position].1)
+    }
+}
+
+impl<'a, K: SimplexKey + 'a> SimplicialComplex<'a> for SortedSimplicialComplex<K> {
+    type BoundaryIterator = KeyBoundaryIterator<'a, SortedSimplicialComplex<K>, K>;
+    type VertexIterator = SimplexKeyVertexIterator<K>;
+
+    fn new(max_n: Vertex, max_dim: Dimension) -> Self {
+        Self::with_keys(max_n, max_dim)
+    }
+
+    fn fits(max_vertices: Vertex, max_dim: Dimension) -> bool {
+        K::fits(max_vertices, max_dim)
+    }
+
+    fn max_dimension(&self) -> Dimension {
+        self.simplices_by_dim.len() - 1
+    }
+
+    fn max_vertices(&self) -> Vertex {
+        self.max_n
+    }
+
+    fn n_cells(&self, dim: Dimension) -> usize {
+        self.simplices_by_dim[dim].len()
+    }
+
+    fn add(&mut self, s: &[Vertex]) -> Option<(Dimension, usize)> {
+        assert!(is_sorted(s), "To add a simplex it must be sorted first.");
+
+        let dim = s.len() - 1;
+        let key = simplex_to_key(self.max_n, s.iter().copied().assume_sorted_by_item());
+        self.add_simplex_key_check_boundaries(dim, key)
+    }
+
+    fn add_iter<I: SortedIterator<Item = usize>>(
+        &mut self,
+        dim: Dimension,
+        iter: I,
+    ) -> Option<(Dimension, usize)> {
+        let key = simplex_to_key(self.max_n, iter);
+        self.add_simplex_key_check_boundaries(dim, key)
+    }
+
+    fn add_iter_unchecked<I: SortedIterator<Item = usize>>(
+        &mut self,
+        dim: Dimension,
+        iter: I,
+    ) -> Option<(Dimension, usize)> {
+        let key = simplex_to_key(self.max_n, iter);
+        self.add_simplex_key(dim, key)
+    }
+
+    fn boundary_iterator(&'a self, dim: Dimension, idx: usize) -> Self::BoundaryIterator {
+        KeyBoundaryIterator::new(self, dim, self.simplices_by_dim[dim][idx])
+    }
+
+    fn simplex_boundary<I: SortedIterator<Item = usize>>(
+        &'a self,
+        dim: Dimension,
+        simplex_iter: I,
+    ) -> Self::BoundaryIterator {
+        KeyBoundaryIterator::new(self, dim, simplex_to_key(self.max_n, simplex_iter))
+    }
+
+    fn simplex_vertices(&self, dim: Dimension, idx: usize) -> Self::VertexIterator {
+        SimplexKeyVertexIterator::new(dim, self.simplices_by_dim[dim][idx], self.max_n)
+    }
+
+    fn find(&self, s: &[Vertex]) -> Option<(Dimension, usize)> {
+        find_simplex_key(self, self.max_dimension(), s)
+    }
+
+    fn memory_bytes_estimate(&self) -> usize {
+        vec_bytes(&self.simplices_by_dim)
+            + self.simplices_by_dim.iter().map(vec_bytes).sum::<usize>()
+            + vec_bytes(&self.buckets)
+            + self
+                .buckets
+                .iter()
+                .map(|dim_buckets| {
+                    vec_bytes(dim_buckets) + dim_buckets.iter().map(vec_bytes).sum::<usize>()
+                })
+                .sum::<usize>()
+    }
+}
+
+/// The heap memory used by a vector, in bytes.
+pub(crate) fn vec_bytes<T>(v: &Vec<T>) -> usize {
+    v.capacity() * std::mem::size_of::<T>()
+}
+
+/// A simplicial complex that can find the index of a simplex from its key.
+pub trait KeyIndex<K> {
+    /// The maximum number of vertices, used to encode the keys.
+    fn max_vertices(&self) -> Vertex;
+
+    /// The index of the simplex of the given dimension and key, if it is in the complex.
+    fn key_index(&self, dim: Dimension, key: &K) -> Option<usize>;
+}
+
+/// Iterator over the indices of the facets of a simplex, given by its key, in a complex that
+/// indexes its simplices by key.
+pub struct KeyBoundaryIterator<'a, C, K> {
+    complex: &'a C,
+
+    simplex_key_iterator: SimplexKeyBoundaryIterator<K>,
+}
+
+/// The boundary iterator of a [MapSimplicialComplex].
+pub type MapBoundaryIterator<'a, K> = KeyBoundaryIterator<'a, MapSimplicialComplex<K>, K>;
+
+impl<'a, C: KeyIndex<K>, K: SimplexKey> KeyBoundaryIterator<'a, C, K> {
+    fn new(complex: &'a C, dimension: Dimension, key: K) -> KeyBoundaryIterator<'a, C, K> {
+        KeyBoundaryIterator {
             complex,
-            simplex_key_iterator: SimplexKeyBoundaryIterator::new(complex.max_n, dimension, key),
+            simplex_key_iterator: SimplexKeyBoundaryIterator::new(
+                complex.max_vertices(),
+                dimension,
+                key,
+            ),
         }
     }
 }
 
-impl Iterator for MapBoundaryIterator<'_> {
+impl<C: KeyIndex<K>, K: SimplexKey> Iterator for KeyBoundaryIterator<'_, C, K> {
     type Item = usize;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -212,49 +572,53 @@ impl Iterator for MapBoundaryIterator<'_> {
         let next_key = self.simplex_key_iterator.next();
         if let Some(key) = next_key {
             let facet_dimension = self.simplex_key_iterator.dimension - 1;
-            Some(self.complex.key_to_idx[facet_dimension][&key])
+            let idx = self.complex.key_index(facet_dimension, &key);
+            Some(idx.expect("The facets of a simplex must be in the simplicial complex"))
         } else {
             None
         }
     }
 }
 
-struct SimplexKeyBoundaryIterator {
+struct SimplexKeyBoundaryIterator<K> {
     dimension: Dimension,
-    max_n: Vertex,
+    max_n: K,
     iteration: Dimension,
-    current_power: Vertex,
-    left_to_process: SimplexKey,
-    processed: SimplexKey,
+    current_power: K,
+    left_to_process: K,
+    processed: K,
 }
 
-impl SimplexKeyBoundaryIterator {
-    fn new(max_n: Vertex, dimension: Dimension, key: SimplexKey) -> SimplexKeyBoundaryIterator {
+impl<K: SimplexKey> SimplexKeyBoundaryIterator<K> {
+    fn new(max_n: Vertex, dimension: Dimension, key: K) -> SimplexKeyBoundaryIterator<K> {
         SimplexKeyBoundaryIterator {
-            max_n,
+            max_n: K::from_vertex(max_n),
             dimension,
             iteration: 0,
             left_to_process: key,
-            processed: 0,
-            current_power: 1,
+            processed: K::zero(),
+            current_power: K::one(),
         }
     }
 }
 
-impl Iterator for SimplexKeyBoundaryIterator {
-    type Item = SimplexKey;
+impl<K: SimplexKey> Iterator for SimplexKeyBoundaryIterator<K> {
+    type Item = K;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.iteration == self.dimension + 1 {
             return None;
         }
-        let next_power = self.current_power * self.max_n;
+        let next_power = key_mul(self.current_power, self.max_n);
         let removed_v = self.left_to_process % self.max_n;
 
-        self.left_to_process /= self.max_n;
-        let face = self.left_to_process * self.current_power + self.processed;
+        self.left_to_process = self.left_to_process / self.max_n;
+        let face = key_add(
+            key_mul(self.left_to_process, self.current_power),
+            self.processed,
+        );
 
-        self.processed += removed_v * self.current_power;
+        self.processed = key_add(self.processed, key_mul(removed_v, self.current_power));
 
         self.current_power = next_power;
         self.iteration += 1;
@@ -262,23 +626,23 @@ impl Iterator for SimplexKeyBoundaryIterator {
     }
 }
 
-pub struct SimplexKeyVertexIterator {
-    key: usize,
+pub struct SimplexKeyVertexIterator<K> {
+    key: K,
     vertices_left: usize,
-    modulo: usize,
+    modulo: K,
 }
 
-impl SimplexKeyVertexIterator {
-    fn new(dim: usize, key: usize, modulo: usize) -> SimplexKeyVertexIterator {
+impl<K: SimplexKey> SimplexKeyVertexIterator<K> {
+    fn new(dim: usize, key: K, modulo: usize) -> SimplexKeyVertexIterator<K> {
         SimplexKeyVertexIterator {
             key,
             vertices_left: dim + 1,
-            modulo,
+            modulo: K::from_vertex(modulo),
         }
     }
 }
 
-impl Iterator for SimplexKeyVertexIterator {
+impl<K: SimplexKey> Iterator for SimplexKeyVertexIterator<K> {
     type Item = Vertex;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -286,9 +650,9 @@ impl Iterator for SimplexKeyVertexIterator {
             return None;
         }
         let v = self.key % self.modulo;
-        self.key /= self.modulo;
+        self.key = self.key / self.modulo;
         self.vertices_left -= 1;
-        Some(v)
+        Some(v.to_vertex())
     }
 }
 
@@ -298,8 +662,12 @@ pub(crate) fn is_sorted<T: Ord>(data: &[T]) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::simplicial_complex::MapSimplicialComplex;
+    use sorted_iter::assume::AssumeSortedByItemExt;
+
     use crate::simplicial_complex::SimplicialComplex;
+    use crate::simplicial_complex::{
+        MapSimplicialComplex, SimplexKey, SortedSimplicialComplex, WideMapSimplicialComplex,
+    };
 
     #[test]
     fn simplex_add_one_by_one() {
@@ -342,4 +710,67 @@ mod tests {
         let vertices: Vec<usize> = s.simplex_vertices(dim, idx).collect();
         assert_eq!(vertices, [0, 1, 2]);
     }
+
+    #[test]
+    fn sorted_complex_happy_case() {
+        let mut s: SortedSimplicialComplex = SortedSimplicialComplex::new(10, 10);
+        s.add(&[0usize]);
+        s.add(&[1usize]);
+        s.add(&[2usize]);
+        s.add(&[0usize, 1usize]);
+        s.add(&[1usize, 2usize]);
+        s.add(&[0usize, 2usize]);
+        assert_eq!(s.add(&[1usize, 2usize]), None);
+        let (dim, idx) = s.add(&[0usize, 1usize, 2usize]).unwrap();
+        let boundary: Vec<_> = s.boundary_iterator(dim, idx).collect();
+        assert_eq!(boundary, [1, 2, 0]);
+        let vertices: Vec<usize> = s.simplex_vertices(dim, idx).collect();
+        assert_eq!(vertices, [0, 1, 2]);
+    }
+
+    #[test]
+    fn simplex_key_fits() {
+        assert!(usize::fits(100_000, 2));
+        assert!(!u64::fits(100_000, 3));
+        assert!(u128::fits(100_000, 6));
+        assert!(!u128::fits(100_000, 7));
+    }
+
+    #[test]
+    fn wide_keys_many_vertices() {
+        let n = 200_000;
+        let mut s = WideMapSimplicialComplex::with_keys(n, 3);
+        for v in 0..n {
+            s.add(&[v]);
+        }
+        let simplex = [n - 4, n - 3, n - 2, n - 1];
+        // Add the faces of the simplex by increasing dimension.
+        let mut faces: Vec<Vec<usize>> = (1..15usize)
+            .map(|mask| {
+                (0..4)
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| simplex[i])
+                    .collect()
+            })
+            .collect();
+        faces.sort_by_key(|face| face.len());
+        for face in faces.iter().filter(|face| face.len() > 1) {
+            s.add(face);
+        }
+        let (dim, idx) = s.add(&simplex).unwrap();
+        let vertices: Vec<usize> = s.simplex_vertices(dim, idx).collect();
+        assert_eq!(vertices, simplex);
+        assert_eq!(s.boundary_iterator(dim, idx).count(), 4);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "Simplex key overflow")]
+    fn simplex_key_overflow_panics() {
+        let n = 200_000;
+        let s: MapSimplicialComplex<u64> = MapSimplicialComplex::with_keys(n, 3);
+        let simplex = [n - 4, n - 3, n - 2, n - 1];
+        s.simplex_boundary(3, simplex.iter().copied().assume_sorted_by_item())
+            .count();
+    }
 }
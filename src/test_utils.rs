@@ -0,0 +1,47 @@
+//! End-to-end checks of preprocessing passes, for tests. Requires the `mpfree-test-utils` feature,
+//! and the mpfree binary, see [crate::mpfree].
+use crate::chain_complex::ToFreeImplicitRepresentation;
+use crate::edges::{EdgeList, FilteredEdge};
+use crate::filtration::Filtration;
+use crate::mpfree::{compute_minimal_presentation_with_options, MpfreeOptions};
+use crate::simplicial_complex::{MapSimplicialComplex, WideMapSimplicialComplex};
+use crate::{CriticalGrade, Value};
+
+/// Checks that the clique complexes of the original and the reduced edge lists have the same
+/// minimal presentation in the given homology, that is, that a pass that removes or changes
+/// edges, like the ones of [crate::removal], preserves the multiparameter persistent homology.
+///
+/// The bifiltrations are piped to mpfree, so no temporary files are written.
+///
+/// Panics: if the minimal presentations differ, or if mpfree fails.
+pub fn assert_preserves_minimal_presentation<VF: Value, G: CriticalGrade>(
+    original: &EdgeList<FilteredEdge<G>>,
+    reduced: &EdgeList<FilteredEdge<G>>,
+    homology: usize,
+) where
+    Filtration<G, MapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+    Filtration<G, WideMapSimplicialComplex>: ToFreeImplicitRepresentation<VF, 2>,
+{
+    let options = MpfreeOptions {
+        pipe: true,
+        ..MpfreeOptions::default()
+    };
+    let original_presentation =
+        compute_minimal_presentation_with_options("original", homology, original, &options)
+            .expect("Computing the minimal presentation of the original edges");
+    let reduced_presentation =
+        compute_minimal_presentation_with_options("reduced", homology, reduced, &options)
+            .expect("Computing the minimal presentation of the reduced edges");
+    let diff = original_presentation
+        .output
+        .diff(&reduced_presentation.output);
+    assert!(
+        diff.is_empty(),
+        "The reduced edges ({}) do not have the minimal presentation of the original edges ({}) \
+         in homology {}:\n{}",
+        reduced.len(),
+        original.len(),
+        homology,
+        diff
+    );
+}
@@ -0,0 +1,144 @@
+//! Spans of the tracing crate around the long passes of the crate, like the removal of edges, the
+//! construction of filtrations, and running mpfree. Without the `tracing` feature, the spans do
+//! nothing, and their fields are not evaluated.
+//!
+//! Each span is created by the [pass] macro, with the fields known at the start of the pass, and
+//! declares the fields that the pass records later with [Pass::record]. When the pass is dropped,
+//! the time it took, in microseconds, is recorded in the `duration_us` field.
+
+/// A pass of an algorithm, inside a span until it is dropped.
+pub(crate) struct Pass {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+    #[cfg(feature = "tracing")]
+    start: std::time::Instant,
+}
+
+impl Pass {
+    #[cfg(feature = "tracing")]
+    pub fn enter(span: tracing::Span) -> Self {
+        Self {
+            span: span.entered(),
+            start: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub fn disabled() -> Self {
+        Self {}
+    }
+
+    /// Records the value of a field that was declared when creating the pass.
+    #[allow(unused_variables)]
+    pub fn record(&self, field: &'static str, value: usize) {
+        #[cfg(feature = "tracing")]
+        self.span.record(field, value as u64);
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Pass {
+    fn drop(&mut self) {
+        self.span
+            .record("duration_us", self.start.elapsed().as_micros() as u64);
+    }
+}
+
+/// Starts a [Pass] in a span with the given name and fields, and declares the fields, after the
+/// semicolon, that are recorded later.
+#[cfg(feature = "tracing")]
+macro_rules! pass {
+    ($name:literal $(, $field:ident = $value:expr)* $(; $($later:ident),+)?) => {
+        $crate::trace::Pass::enter(tracing::info_span!(
+            $name,
+            $($field = $value,)*
+            $($($later = tracing::field::Empty,)+)?
+            duration_us = tracing::field::Empty
+        ))
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! pass {
+    ($name:literal $(, $field:ident = $value:expr)* $(; $($later:ident),+)?) => {
+        $crate::trace::Pass::disabled()
+    };
+}
+
+pub(crate) use pass;
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::datasets::{get_dataset_density_edge_list, Dataset, Threshold};
+    use crate::removal::{remove_strongly_filtration_dominated, EdgeOrder};
+
+    /// The name of each span, and the fields of the span that were given a value.
+    type Spans = Vec<(&'static str, Vec<&'static str>)>;
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Spans>>);
+
+    struct FieldNames<'a>(&'a mut Vec<&'static str>);
+
+    impl Visit for FieldNames<'_> {
+        fn record_debug(&mut self, field: &Field, _value: &dyn std::fmt::Debug) {
+            self.0.push(field.name());
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Vec::new();
+            span.record(&mut FieldNames(&mut fields));
+            let mut spans = self.0.lock().unwrap();
+            spans.push((span.metadata().name(), fields));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.0.lock().unwrap();
+            let (_, fields) = &mut spans[span.into_u64() as usize - 1];
+            values.record(&mut FieldNames(fields));
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn removal_records_edge_counts_and_duration() {
+        let mut edges = get_dataset_density_edge_list(
+            Dataset::Sphere { n_points: 30 },
+            Threshold::KeepAll,
+            None,
+            false,
+        )
+        .unwrap();
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            remove_strongly_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+        });
+
+        let spans = recorder.0.lock().unwrap();
+        let (name, fields) = &spans[0];
+        assert_eq!(*name, "remove_dominated");
+        for field in ["input_edges", "remaining_edges", "duration_us"] {
+            assert!(fields.contains(&field));
+        }
+    }
+}
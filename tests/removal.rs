@@ -1,9 +1,10 @@
 use filtration_domination::datasets;
 use filtration_domination::datasets::{Dataset, Threshold};
-use filtration_domination::mpfree::compute_minimal_presentation;
 use filtration_domination::removal::{
     remove_filtration_dominated, remove_strongly_filtration_dominated, EdgeOrder,
 };
+use filtration_domination::rivet::compute_rivet_invariants;
+use filtration_domination::test_utils::assert_preserves_minimal_presentation;
 use paste::paste;
 
 const HOMOLOGY: usize = 1;
@@ -13,60 +14,46 @@ const HOMOLOGY: usize = 1;
 /// (by checking minimal presentations with mpfree).
 macro_rules! test_case {
     ($name:expr, $dataset:expr) => {
-        paste!{
-#[test]
-fn [<$name _remove>]() {
-    let mut edges = datasets::get_dataset_density_edge_list(
-        Dataset::$dataset,
-        Threshold::KeepAll,
-        None,
-        true).expect("Couldn't open dataset");
+        paste! {
+            #[test]
+            fn [<$name _remove>]() {
+                let mut edges = datasets::get_dataset_density_edge_list(
+                    Dataset::$dataset,
+                    Threshold::KeepAll,
+                    None,
+                    true,
+                )
+                .expect("Couldn't open dataset");
 
-    let remaining_edges =
-        remove_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
-    println!("Original edges: {}", edges.len());
-    println!("Remaining edges: {}", remaining_edges.len());
-
-    let mpfree_all_edges =
-        compute_minimal_presentation(&format!("test_mpfree_{}", stringify!($name)), HOMOLOGY, &edges).expect("Computing minimal presentation on all edges");
-
-    let mpfree_remaining = compute_minimal_presentation(
-        &format!("test_mpfree_{}_remaining", stringify!($name)),
-        HOMOLOGY,
-        &remaining_edges,
-    )
-    .expect("Computing minimal presentation on remaining edges");
-
-    assert_eq!(mpfree_remaining.output, mpfree_all_edges.output);
-}
-
-#[test]
-fn [<$name _remove_strong>]() {
-    let mut edges = datasets::get_dataset_density_edge_list(
-        Dataset::$dataset,
-        Threshold::KeepAll,
-        None,
-        true).expect("Couldn't open dataset");
+                let remaining_edges =
+                    remove_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+                println!("Original edges: {}", edges.len());
+                println!("Remaining edges: {}", remaining_edges.len());
 
-    let remaining_edges =
-        remove_strongly_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
-    println!("Original edges: {}", edges.len());
-    println!("Remaining edges: {}", remaining_edges.len());
+                assert_preserves_minimal_presentation(&edges, &remaining_edges, HOMOLOGY);
+            }
 
-    let mpfree_all_edges =
-        compute_minimal_presentation(&format!("test_mpfree_{}_strong", stringify!($name)), HOMOLOGY, &edges).expect("Computing minimal presentation on all edges");
+            #[test]
+            fn [<$name _remove_strong>]() {
+                let mut edges = datasets::get_dataset_density_edge_list(
+                    Dataset::$dataset,
+                    Threshold::KeepAll,
+                    None,
+                    true,
+                )
+                .expect("Couldn't open dataset");
 
-    let mpfree_remaining = compute_minimal_presentation(
-        &format!("test_mpfree_{}_strong_remaining", stringify!($name)),
-        HOMOLOGY,
-        &remaining_edges,
-    )
-    .expect("Computing minimal presentation on remaining edges");
+                let remaining_edges = remove_strongly_filtration_dominated(
+                    &mut edges,
+                    EdgeOrder::ReverseLexicographic,
+                );
+                println!("Original edges: {}", edges.len());
+                println!("Remaining edges: {}", remaining_edges.len());
 
-    assert_eq!(mpfree_remaining.output, mpfree_all_edges.output);
-}
+                assert_preserves_minimal_presentation(&edges, &remaining_edges, HOMOLOGY);
+            }
         }
-    }
+    };
 }
 
 test_case!(senate, Senate);
@@ -78,3 +65,30 @@ test_case!(sphere, Sphere { n_points: 100 });
 test_case!(circle, Circle { n_points: 100 });
 test_case!(torus, Torus { n_points: 200 });
 test_case!(swiss_roll, SwissRoll { n_points: 200 });
+
+/// Check the preservation of the bigraded Betti numbers with RIVET, a backend independent of
+/// mpfree. Run with `cargo test --release -- --ignored` with `rivet_console` installed.
+#[test]
+#[ignore = "requires rivet_console"]
+fn torus_remove_rivet() {
+    let mut edges = datasets::get_dataset_density_edge_list(
+        Dataset::Torus { n_points: 100 },
+        Threshold::KeepAll,
+        None,
+        true,
+    )
+    .expect("Couldn't open dataset");
+
+    let remaining_edges = remove_filtration_dominated(&mut edges, EdgeOrder::ReverseLexicographic);
+
+    let rivet_all_edges = compute_rivet_invariants("test_rivet_torus", HOMOLOGY, &edges)
+        .expect("Computing invariants on all edges");
+    let rivet_remaining =
+        compute_rivet_invariants("test_rivet_torus_remaining", HOMOLOGY, &remaining_edges)
+            .expect("Computing invariants on remaining edges");
+
+    assert_eq!(
+        rivet_remaining.output.betti_numbers,
+        rivet_all_edges.output.betti_numbers
+    );
+}
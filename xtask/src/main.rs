@@ -0,0 +1,176 @@
+//! Development tasks for the filtration-domination crate. Run them with `cargo xtask <task>`.
+//!
+//! The only task is `features`, which builds, lints and runs the unit tests of the crate for
+//! several combinations of its optional features, to catch feature-gated compilation errors that
+//! building with a single set of features misses. By default, it checks the crate with no
+//! optional features, with each feature on its own, and with all of them. With `--powerset`, it
+//! checks every combination. Then it checks that the Python bindings in `python/`, which depend
+//! on the crate with its default features, still compile.
+//!
+//! The integration tests in `tests/` are not run, since they require mpfree and the datasets.
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+/// The optional features of the crate. Each of them must compile on its own.
+const FEATURES: &[&str] = &[
+    "bench-internals",
+    "bundled-examples",
+    "memory-guard",
+    "mmap",
+    "mpfree-test-utils",
+    "npy",
+    "parallel",
+    "petgraph",
+    "serde",
+    "service",
+    "tracing",
+];
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("features") => {
+            let powerset = args[1..].iter().any(|a| a == "--powerset");
+            check_feature_matrix(powerset)
+        }
+        _ => {
+            eprintln!("Usage: cargo xtask features [--powerset]");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn check_feature_matrix(powerset: bool) -> ExitCode {
+    let combinations = if powerset {
+        feature_powerset(FEATURES)
+    } else {
+        feature_combinations(FEATURES)
+    };
+
+    let mut failures: Vec<String> = Vec::new();
+    for features in combinations.iter() {
+        let name = if features.is_empty() {
+            String::from("(no features)")
+        } else {
+            features.join(",")
+        };
+        eprintln!("==> Checking {}", name);
+        if let Err(step) = check_features(features) {
+            eprintln!("==> Failed {} for {}", step, name);
+            failures.push(format!("{}: {}", name, step));
+        }
+    }
+
+    eprintln!("==> Checking the Python bindings");
+    if !check_python_bindings() {
+        eprintln!("==> Failed check for the Python bindings");
+        failures.push(String::from("python bindings: check"));
+    }
+
+    if failures.is_empty() {
+        eprintln!(
+            "==> All {} feature combinations and the Python bindings passed",
+            combinations.len()
+        );
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("==> Failed feature combinations:");
+        for failure in failures {
+            eprintln!("    {}", failure);
+        }
+        ExitCode::FAILURE
+    }
+}
+
+/// Build, lint and test the crate with the given features. On failure, returns the name of the
+/// step that failed.
+fn check_features(features: &[&str]) -> Result<(), &'static str> {
+    let feature_args = vec![
+        String::from("--no-default-features"),
+        String::from("--features"),
+        features.join(","),
+    ];
+    let steps: [(&'static str, Vec<&str>); 3] = [
+        ("build", vec!["build", "--all-targets"]),
+        ("clippy", vec!["clippy", "--all-targets"]),
+        ("test", vec!["test", "--lib", "--bins"]),
+    ];
+    for (step, args) in steps {
+        let mut command = Command::new(cargo());
+        command
+            .current_dir(project_root())
+            .args(args)
+            .args(&feature_args);
+        if step == "clippy" {
+            command.args(["--", "-D", "warnings"]);
+        }
+        let success = command.status().map(|s| s.success()).unwrap_or(false);
+        if !success {
+            return Err(step);
+        }
+    }
+    Ok(())
+}
+
+/// Check the Python bindings, which are a separate crate, so that changes to the API of the crate
+/// that break them are caught. Returns whether the check succeeded.
+fn check_python_bindings() -> bool {
+    Command::new(cargo())
+        .current_dir(project_root().join("python"))
+        .arg("check")
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// No features, each feature on its own, and all features.
+fn feature_combinations<'a>(features: &[&'a str]) -> Vec<Vec<&'a str>> {
+    let mut combinations = vec![Vec::new()];
+    combinations.extend(features.iter().map(|&f| vec![f]));
+    if features.len() > 1 {
+        combinations.push(features.to_vec());
+    }
+    combinations
+}
+
+/// All subsets of the features.
+fn feature_powerset<'a>(features: &[&'a str]) -> Vec<Vec<&'a str>> {
+    (0..1usize << features.len())
+        .map(|mask| {
+            features
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, &f)| f)
+                .collect()
+        })
+        .collect()
+}
+
+fn cargo() -> String {
+    std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"))
+}
+
+fn project_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{feature_combinations, feature_powerset};
+
+    #[test]
+    fn combinations() {
+        let features = ["a", "b", "c"];
+        assert_eq!(
+            feature_combinations(&features),
+            vec![vec![], vec!["a"], vec!["b"], vec!["c"], vec!["a", "b", "c"]]
+        );
+        let powerset = feature_powerset(&features);
+        assert_eq!(powerset.len(), 8);
+        assert!(powerset.contains(&vec!["a", "c"]));
+    }
+}